@@ -5,6 +5,9 @@ use serde_json::value::RawValue;
 use std::sync::Arc;
 use thiserror::Error;
 
+use octofhir_core::paging::SYNC_PARAM;
+pub use octofhir_core::paging::{PageLinkStyle, SyncCursor};
+
 // -------------------------
 // Raw JSON Type for Zero-Copy Serialization
 // -------------------------
@@ -127,7 +130,7 @@ pub struct OperationOutcome {
 pub struct OperationOutcomeIssue {
     /// FHIR issue severity: fatal | error | warning | information
     pub severity: &'static str,
-    /// FHIR issue type code (subset used): invalid | not-found | conflict | forbidden | unauthorized | not-supported | exception
+    /// FHIR issue type code (subset used): invalid | not-found | conflict | duplicate | forbidden | unauthorized | not-supported | transient | exception
     pub code: &'static str,
     /// Human-readable description
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -182,6 +185,9 @@ impl OperationOutcome {
     }
 }
 
+/// Seconds a client is asked to wait before retrying after [`ApiError::Busy`].
+pub const BUSY_RETRY_AFTER_SECS: u32 = 1;
+
 /// High-level API errors to be mapped to HTTP responses and FHIR OperationOutcome
 #[derive(Debug, Error)]
 pub enum ApiError {
@@ -197,14 +203,27 @@ pub enum ApiError {
     Gone(String),
     #[error("Conflict: {0}")]
     Conflict(String),
+    #[error("Duplicate: {0}")]
+    Duplicate(String),
     #[error("Precondition failed: {0}")]
     PreconditionFailed(String),
     #[error("Unsupported media type: {0}")]
     UnsupportedMediaType(String),
+    /// A resource exceeds the server's size limit.
+    #[error("Payload too large: {0}")]
+    PayloadTooLarge(String),
     #[error("Not implemented: {0}")]
     NotImplemented(String),
     #[error("Internal server error: {0}")]
     Internal(String),
+    #[error("Service unavailable: {0}")]
+    ServiceUnavailable(String),
+    /// The server is healthy but saturated; sent with a `Retry-After` header.
+    #[error("Server busy: {0}")]
+    Busy(String),
+    /// The request did not finish within the server's request timeout.
+    #[error("Gateway timeout: {0}")]
+    GatewayTimeout(String),
     #[error("Unprocessable entity: {message}")]
     UnprocessableEntity {
         message: String,
@@ -231,15 +250,30 @@ impl ApiError {
     pub fn conflict(msg: impl Into<String>) -> Self {
         Self::Conflict(msg.into())
     }
+    pub fn duplicate(msg: impl Into<String>) -> Self {
+        Self::Duplicate(msg.into())
+    }
     pub fn precondition_failed(msg: impl Into<String>) -> Self {
         Self::PreconditionFailed(msg.into())
     }
     pub fn unsupported_media_type(msg: impl Into<String>) -> Self {
         Self::UnsupportedMediaType(msg.into())
     }
+    pub fn payload_too_large(msg: impl Into<String>) -> Self {
+        Self::PayloadTooLarge(msg.into())
+    }
     pub fn internal(msg: impl Into<String>) -> Self {
         Self::Internal(msg.into())
     }
+    pub fn service_unavailable(msg: impl Into<String>) -> Self {
+        Self::ServiceUnavailable(msg.into())
+    }
+    pub fn busy(msg: impl Into<String>) -> Self {
+        Self::Busy(msg.into())
+    }
+    pub fn gateway_timeout(msg: impl Into<String>) -> Self {
+        Self::GatewayTimeout(msg.into())
+    }
     pub fn not_implemented(msg: impl Into<String>) -> Self {
         Self::NotImplemented(msg.into())
     }
@@ -261,10 +295,15 @@ impl ApiError {
             ApiError::NotFound(_) => StatusCode::NOT_FOUND,
             ApiError::Gone(_) => StatusCode::GONE,
             ApiError::Conflict(_) => StatusCode::CONFLICT,
+            ApiError::Duplicate(_) => StatusCode::CONFLICT,
             ApiError::PreconditionFailed(_) => StatusCode::PRECONDITION_FAILED,
             ApiError::UnsupportedMediaType(_) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            ApiError::PayloadTooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
             ApiError::NotImplemented(_) => StatusCode::NOT_IMPLEMENTED,
             ApiError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            ApiError::ServiceUnavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+            ApiError::Busy(_) => StatusCode::SERVICE_UNAVAILABLE,
+            ApiError::GatewayTimeout(_) => StatusCode::GATEWAY_TIMEOUT,
             ApiError::UnprocessableEntity { .. } => StatusCode::UNPROCESSABLE_ENTITY,
         }
     }
@@ -277,19 +316,40 @@ impl ApiError {
             ApiError::NotFound(msg) => OperationOutcome::single("error", "not-found", msg),
             ApiError::Gone(msg) => OperationOutcome::single("error", "deleted", msg),
             ApiError::Conflict(msg) => OperationOutcome::single("error", "conflict", msg),
+            ApiError::Duplicate(msg) => OperationOutcome::single("error", "duplicate", msg),
             ApiError::PreconditionFailed(msg) => OperationOutcome::single("error", "conflict", msg),
             ApiError::UnsupportedMediaType(msg) => {
                 OperationOutcome::single("error", "not-supported", msg)
             }
+            ApiError::PayloadTooLarge(msg) => OperationOutcome::single("error", "too-long", msg),
             ApiError::NotImplemented(msg) => {
                 OperationOutcome::single("error", "not-supported", msg)
             }
             ApiError::Internal(msg) => OperationOutcome::single("fatal", "exception", msg),
+            ApiError::ServiceUnavailable(msg) => {
+                OperationOutcome::single("error", "transient", msg)
+            }
+            ApiError::Busy(msg) => OperationOutcome::single("error", "throttled", msg),
+            ApiError::GatewayTimeout(msg) => OperationOutcome::single("error", "timeout", msg),
             ApiError::UnprocessableEntity { message, .. } => {
                 OperationOutcome::single("error", "invalid", message)
             }
         }
     }
+
+    /// The OperationOutcome sent as the response body: the detailed outcome
+    /// of an `UnprocessableEntity` if it has one, otherwise
+    /// [`Self::to_operation_outcome`].
+    pub fn operation_outcome_json(&self) -> serde_json::Value {
+        if let ApiError::UnprocessableEntity {
+            operation_outcome: Some(outcome),
+            ..
+        } = self
+        {
+            return outcome.clone();
+        }
+        serde_json::to_value(self.to_operation_outcome()).unwrap_or_default()
+    }
 }
 
 impl IntoResponse for ApiError {
@@ -328,6 +388,9 @@ impl IntoResponse for ApiError {
             header::CONTENT_TYPE,
             HeaderValue::from_static("application/fhir+json"),
         );
+        if matches!(self, ApiError::Busy(_)) {
+            builder = builder.header(header::RETRY_AFTER, BUSY_RETRY_AFTER_SECS);
+        }
 
         builder
             .body(axum::body::Body::from(body))
@@ -360,6 +423,16 @@ mod tests {
         );
     }
 
+    #[test]
+    fn busy_response_sets_retry_after() {
+        let resp = ApiError::busy("pool exhausted").into_response();
+        assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(resp.headers().get(header::RETRY_AFTER).unwrap(), "1");
+
+        let resp = ApiError::service_unavailable("database down").into_response();
+        assert!(resp.headers().get(header::RETRY_AFTER).is_none());
+    }
+
     #[test]
     fn operation_outcome_shape() {
         let outcome = ApiError::not_found("Patient/123 not found").to_operation_outcome();
@@ -385,6 +458,7 @@ mod tests {
             (ApiError::not_found("x"), StatusCode::NOT_FOUND, "not-found"),
             (ApiError::gone("x"), StatusCode::GONE, "deleted"),
             (ApiError::conflict("x"), StatusCode::CONFLICT, "conflict"),
+            (ApiError::duplicate("x"), StatusCode::CONFLICT, "duplicate"),
             (
                 ApiError::precondition_failed("x"),
                 StatusCode::PRECONDITION_FAILED,
@@ -395,6 +469,11 @@ mod tests {
                 StatusCode::UNSUPPORTED_MEDIA_TYPE,
                 "not-supported",
             ),
+            (
+                ApiError::payload_too_large("x"),
+                StatusCode::PAYLOAD_TOO_LARGE,
+                "too-long",
+            ),
             (
                 ApiError::not_implemented("x"),
                 StatusCode::NOT_IMPLEMENTED,
@@ -405,6 +484,21 @@ mod tests {
                 StatusCode::INTERNAL_SERVER_ERROR,
                 "exception",
             ),
+            (
+                ApiError::service_unavailable("x"),
+                StatusCode::SERVICE_UNAVAILABLE,
+                "transient",
+            ),
+            (
+                ApiError::busy("x"),
+                StatusCode::SERVICE_UNAVAILABLE,
+                "throttled",
+            ),
+            (
+                ApiError::gateway_timeout("x"),
+                StatusCode::GATEWAY_TIMEOUT,
+                "timeout",
+            ),
         ];
         for (err, status, code) in cases.into_iter() {
             assert_eq!(err.status_code(), status);
@@ -560,6 +654,60 @@ pub fn validate_content_type(headers: &HeaderMap) -> Result<(), ApiError> {
     Ok(())
 }
 
+/// Value of the `fhirVersion` MIME parameter for a FHIR release: the
+/// major.minor part of the version ("4.0.1" -> "4.0").
+pub fn fhir_mime_version(release: &str) -> &str {
+    match release.match_indices('.').nth(1) {
+        Some((idx, _)) => &release[..idx],
+        None => release,
+    }
+}
+
+/// Extract the `fhirVersion` parameter of a single media type, e.g.
+/// `application/fhir+json; fhirVersion=4.0`.
+pub fn fhir_version_param(media_type: &str) -> Option<&str> {
+    media_type.split(';').skip(1).find_map(|param| {
+        let (name, value) = param.split_once('=')?;
+        if name.trim().eq_ignore_ascii_case("fhirVersion") {
+            Some(value.trim().trim_matches('"'))
+        } else {
+            None
+        }
+    })
+}
+
+/// Whether an Accept header can be served by a server running `mime_version`
+/// (see [`fhir_mime_version`]).
+///
+/// Media ranges without a `fhirVersion` parameter accept any version, so the
+/// header is only unacceptable when every range pins a different version.
+pub fn accepts_fhir_version(accept: &str, mime_version: &str) -> bool {
+    accept
+        .split(',')
+        .any(|range| match fhir_version_param(range) {
+            Some(requested) => fhir_mime_version(requested) == mime_version,
+            None => true,
+        })
+}
+
+/// Media type requested by a `_format` query parameter, which overrides the
+/// Accept header.
+///
+/// Accepts the FHIR shorthands (`json`) and full JSON media types, keeping
+/// any MIME parameters. Returns `None` for formats this server cannot
+/// produce, including XML.
+pub fn format_param_media_type(value: &str) -> Option<String> {
+    // An unencoded '+' in a query string decodes to a space
+    let value = value.trim().replace(' ', "+");
+    let (essence, params) = value.split_at(value.find(';').unwrap_or(value.len()));
+    let media_type = match essence.trim().to_ascii_lowercase().as_str() {
+        "json" | "application/fhir+json" => "application/fhir+json",
+        "application/json" | "text/json" => "application/json",
+        _ => return None,
+    };
+    Some(format!("{media_type}{params}"))
+}
+
 #[cfg(test)]
 mod content_negotiation_tests {
     use super::*;
@@ -609,6 +757,65 @@ mod content_negotiation_tests {
         assert!(validate_content_type(&headers).is_ok());
     }
 
+    #[test]
+    fn fhir_version_mime_parameter() {
+        assert_eq!(fhir_mime_version("4.0.1"), "4.0");
+        assert_eq!(fhir_mime_version("5.0"), "5.0");
+        assert_eq!(
+            fhir_version_param("application/fhir+json; fhirVersion=4.0"),
+            Some("4.0")
+        );
+        assert_eq!(
+            fhir_version_param("application/fhir+json;charset=utf-8;fhirversion=\"5.0\""),
+            Some("5.0")
+        );
+        assert_eq!(fhir_version_param("application/fhir+json"), None);
+    }
+
+    #[test]
+    fn accept_fhir_version_negotiation() {
+        assert!(accepts_fhir_version("application/fhir+json", "4.0"));
+        assert!(accepts_fhir_version(
+            "application/fhir+json; fhirVersion=4.0",
+            "4.0"
+        ));
+        assert!(accepts_fhir_version(
+            "application/fhir+json; fhirVersion=4.0.1",
+            "4.0"
+        ));
+        assert!(!accepts_fhir_version(
+            "application/fhir+json; fhirVersion=5.0",
+            "4.0"
+        ));
+        assert!(accepts_fhir_version(
+            "application/fhir+json; fhirVersion=5.0, application/json",
+            "4.0"
+        ));
+    }
+
+    #[test]
+    fn format_param_maps_json_and_rejects_others() {
+        assert_eq!(
+            format_param_media_type("json").as_deref(),
+            Some("application/fhir+json")
+        );
+        assert_eq!(
+            format_param_media_type("application/fhir json").as_deref(),
+            Some("application/fhir+json")
+        );
+        assert_eq!(
+            format_param_media_type("text/json").as_deref(),
+            Some("application/json")
+        );
+        assert_eq!(
+            format_param_media_type("application/fhir+json;fhirVersion=4.0").as_deref(),
+            Some("application/fhir+json;fhirVersion=4.0")
+        );
+        assert_eq!(format_param_media_type("xml"), None);
+        assert_eq!(format_param_media_type("application/fhir+xml"), None);
+        assert_eq!(format_param_media_type("ttl"), None);
+    }
+
     #[test]
     fn content_type_rejects_xml() {
         let mut headers = HeaderMap::new();
@@ -730,6 +937,51 @@ impl Bundle {
             entry: entries,
         }
     }
+
+    /// Sets `entry.search.score` on the `match` entries, in order. Extra
+    /// scores are ignored; entries without one keep none.
+    pub fn with_match_scores(mut self, scores: impl IntoIterator<Item = f64>) -> Self {
+        let matches = self
+            .entry
+            .iter_mut()
+            .filter_map(|e| e.search.as_mut())
+            .filter(|search| search.mode == "match");
+        for (search, score) in matches.zip(scores) {
+            search.score = Some(score);
+        }
+        self
+    }
+
+    /// Appends the deletes of an incremental sync page (`_sync`). Like the
+    /// deletes of a history bundle they carry no resource, only the `DELETE`
+    /// request and its outcome. Items are `(id, version_id, last_modified)`.
+    pub fn with_sync_deletions(
+        mut self,
+        base_url: &str,
+        resource_type: &str,
+        deleted: impl IntoIterator<Item = (String, String, String)>,
+    ) -> Self {
+        let method = HistoryBundleMethod::Delete;
+        self.entry
+            .extend(deleted.into_iter().map(|(id, version_id, last_modified)| {
+                let url = format!("{resource_type}/{id}");
+                BundleEntry {
+                    full_url: Some(join_url(base_url, &url)),
+                    resource: None,
+                    search: None,
+                    request: Some(BundleEntryRequest {
+                        method: method.http_method().to_string(),
+                        url,
+                    }),
+                    response: Some(BundleEntryResponse {
+                        status: method.http_status().to_string(),
+                        etag: Some(format!("W/\"{version_id}\"")),
+                        last_modified: Some(last_modified),
+                    }),
+                }
+            }));
+        self
+    }
 }
 
 #[cfg(test)]
@@ -762,6 +1014,56 @@ mod bundle_tests {
         assert_eq!(j["entry"][0]["search"]["mode"], "match");
     }
 
+    #[test]
+    fn match_scores_skip_outcome_and_include_entries() {
+        let entry = |mode: &str| BundleEntry {
+            full_url: None,
+            resource: None,
+            search: Some(BundleEntrySearch {
+                mode: mode.into(),
+                score: None,
+            }),
+            request: None,
+            response: None,
+        };
+        let b = Bundle::searchset(
+            2,
+            vec![
+                entry("outcome"),
+                entry("match"),
+                entry("match"),
+                entry("include"),
+            ],
+            Vec::new(),
+        )
+        .with_match_scores([0.5, 0.25]);
+        let j = serde_json::to_value(&b).unwrap();
+        assert!(j["entry"][0]["search"].get("score").is_none());
+        assert_eq!(j["entry"][1]["search"]["score"], 0.5);
+        assert_eq!(j["entry"][2]["search"]["score"], 0.25);
+        assert!(j["entry"][3]["search"].get("score").is_none());
+    }
+
+    #[test]
+    fn sync_deletions_are_delete_requests() {
+        let b = Bundle::searchset_with_total(None, Vec::new(), Vec::new()).with_sync_deletions(
+            "http://example.org/fhir",
+            "Patient",
+            [(
+                "pat-1".to_string(),
+                "7".to_string(),
+                "2026-01-02T03:04:05Z".to_string(),
+            )],
+        );
+        let j = serde_json::to_value(&b).unwrap();
+        let entry = &j["entry"][0];
+        assert_eq!(entry["fullUrl"], "http://example.org/fhir/Patient/pat-1");
+        assert!(entry.get("resource").is_none());
+        assert_eq!(entry["request"]["method"], "DELETE");
+        assert_eq!(entry["request"]["url"], "Patient/pat-1");
+        assert_eq!(entry["response"]["etag"], "W/\"7\"");
+    }
+
     #[test]
     fn serialize_history_bundle() {
         let entry = BundleEntry {
@@ -865,36 +1167,111 @@ fn join_url(base: &str, path: &str) -> String {
     format!("{base}/{path}")
 }
 
+/// The page of results a bundle holds, and how its links address pages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Page {
+    pub offset: usize,
+    pub count: usize,
+    pub link_style: PageLinkStyle,
+}
+
+impl Page {
+    /// A page of `count` results starting at `offset`, linked by offset.
+    pub fn new(offset: usize, count: usize) -> Self {
+        Self {
+            offset,
+            count,
+            link_style: PageLinkStyle::Offset,
+        }
+    }
+
+    pub fn with_link_style(mut self, link_style: PageLinkStyle) -> Self {
+        self.link_style = link_style;
+        self
+    }
+
+    /// The page of the same size starting at `offset`.
+    fn at(self, offset: usize) -> Self {
+        Self { offset, ..self }
+    }
+
+    fn query(&self) -> String {
+        octofhir_core::paging::page_query(self.link_style, self.offset, self.count)
+    }
+}
+
 fn build_page_url(
     base_url: &str,
     resource_type: &str,
-    offset: usize,
-    count: usize,
+    page: Page,
     query_suffix: Option<&str>,
 ) -> String {
     let mut url = format!(
-        "{}/{}?_count={}&_offset={}",
+        "{}/{}?{}",
         base_url.trim_end_matches('/'),
         resource_type,
-        count,
-        offset
+        page.query()
     );
     if let Some(q) = query_suffix {
+        // Only a leading separator is ours to rewrite; the rest is already encoded.
+        let q = q.trim_start_matches(['&', '?']);
         if !q.is_empty() {
-            // ensure starts with '&' or '?', we append as additional params
-            if q.starts_with('&') {
-                url.push_str(q);
-            } else if q.starts_with('?') {
-                url.push_str(&q.replace('?', "&"));
-            } else {
-                url.push('&');
-                url.push_str(q);
-            }
+            url.push('&');
+            url.push_str(q);
         }
     }
     url
 }
 
+/// Build the query suffix carried over into pagination links from the
+/// search's decoded `(name, value)` pairs.
+///
+/// Order and repeated parameters are kept as given, names keep their
+/// modifiers (`name:contains`, `subject:Patient`), and every name and value
+/// is percent-encoded. `_count`, `_offset`, `_cursor` and `_sync` are
+/// dropped because each page URL sets its own.
+pub fn link_query_suffix<I, K, V>(params: I) -> Option<String>
+where
+    I: IntoIterator<Item = (K, V)>,
+    K: AsRef<str>,
+    V: AsRef<str>,
+{
+    let mut out = String::new();
+    for (name, value) in params {
+        let (name, value) = (name.as_ref(), value.as_ref());
+        if name.is_empty() || matches!(name, "_count" | "_offset" | "_cursor" | "_sync") {
+            continue;
+        }
+        if !out.is_empty() {
+            out.push('&');
+        }
+        encode_query_component(&mut out, name);
+        out.push('=');
+        encode_query_component(&mut out, value);
+    }
+    if out.is_empty() {
+        None
+    } else {
+        Some(out)
+    }
+}
+
+/// Percent-encode one query name or value. Unreserved characters plus `:`
+/// (modifiers) and `,` (FHIR OR lists) are left readable.
+fn encode_query_component(out: &mut String, value: &str) {
+    use std::fmt::Write;
+    for b in value.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b':' | b',' => {
+                out.push(b as char)
+            }
+            _ => {
+                let _ = write!(out, "%{b:02X}");
+            }
+        }
+    }
+}
+
 /// Raw included resource entry for optimized serialization.
 pub struct RawIncludedEntry {
     /// Raw JSON resource
@@ -916,8 +1293,7 @@ pub fn bundle_from_search_raw(
     included: Vec<RawIncludedEntry>,
     base_url: &str,
     resource_type: &str,
-    offset: usize,
-    count: usize,
+    page: Page,
     query_suffix: Option<&str>,
 ) -> Bundle {
     bundle_from_search_raw_with_pagination(
@@ -929,8 +1305,7 @@ pub fn bundle_from_search_raw(
         included,
         base_url,
         resource_type,
-        offset,
-        count,
+        page,
         query_suffix,
     )
 }
@@ -946,8 +1321,7 @@ pub fn bundle_from_search_raw_with_pagination(
     included: Vec<RawIncludedEntry>,
     base_url: &str,
     resource_type: &str,
-    offset: usize,
-    count: usize,
+    page: Page,
     query_suffix: Option<&str>,
 ) -> Bundle {
     let mut entries = Vec::with_capacity(resources.len() + included.len());
@@ -991,8 +1365,7 @@ pub fn bundle_from_search_raw_with_pagination(
         has_more,
         base_url,
         resource_type,
-        offset,
-        count,
+        page,
         query_suffix,
     );
     Bundle::searchset_with_total(total.map(|value| value as u64), entries, links)
@@ -1014,8 +1387,7 @@ pub fn bundle_from_search_raw_with_warnings(
     included: Vec<RawIncludedEntry>,
     base_url: &str,
     resource_type: &str,
-    offset: usize,
-    count: usize,
+    page: Page,
     query_suffix: Option<&str>,
     warnings: Option<OperationOutcome>,
 ) -> Bundle {
@@ -1028,8 +1400,7 @@ pub fn bundle_from_search_raw_with_warnings(
         included,
         base_url,
         resource_type,
-        offset,
-        count,
+        page,
         query_suffix,
         warnings,
     )
@@ -1046,30 +1417,14 @@ pub fn bundle_from_search_raw_with_warnings_and_pagination(
     included: Vec<RawIncludedEntry>,
     base_url: &str,
     resource_type: &str,
-    offset: usize,
-    count: usize,
+    page: Page,
     query_suffix: Option<&str>,
     warnings: Option<OperationOutcome>,
 ) -> Bundle {
     let mut entries = Vec::with_capacity(resources.len() + included.len() + 1);
 
     // Add OperationOutcome as first entry if there are warnings
-    if let Some(outcome) = warnings {
-        if outcome.has_issues() {
-            let outcome_json =
-                serde_json::to_value(&outcome).expect("OperationOutcome should always serialize");
-            entries.push(BundleEntry {
-                full_url: None,
-                resource: Some(RawJson::from(outcome_json)),
-                search: Some(BundleEntrySearch {
-                    mode: "outcome".to_string(),
-                    score: None,
-                }),
-                request: None,
-                response: None,
-            });
-        }
-    }
+    entries.extend(warnings.and_then(outcome_entry));
 
     // Add main match entries
     for (res, id) in resources.into_iter().zip(resource_ids) {
@@ -1110,20 +1465,86 @@ pub fn bundle_from_search_raw_with_warnings_and_pagination(
         has_more,
         base_url,
         resource_type,
-        offset,
-        count,
+        page,
         query_suffix,
     );
     Bundle::searchset_with_total(total.map(|value| value as u64), entries, links)
 }
 
+/// Create a system-level search bundle (`GET [base]?_type=...`).
+///
+/// Matches may span resource types, so each carries its own type; paging
+/// links point at the server root.
+#[allow(clippy::too_many_arguments)]
+pub fn bundle_from_system_search_raw(
+    total: Option<usize>,
+    total_is_exact: bool,
+    has_more: bool,
+    matches: Vec<RawIncludedEntry>,
+    included: Vec<RawIncludedEntry>,
+    base_url: &str,
+    page: Page,
+    query_suffix: Option<&str>,
+    warnings: Option<OperationOutcome>,
+) -> Bundle {
+    let mut entries = Vec::with_capacity(matches.len() + included.len() + 1);
+    entries.extend(warnings.and_then(outcome_entry));
+
+    for (group, mode) in [(matches, "match"), (included, "include")] {
+        for entry in group {
+            entries.push(BundleEntry {
+                full_url: Some(join_url(
+                    base_url,
+                    &format!("{}/{}", entry.resource_type, entry.id),
+                )),
+                resource: Some(entry.resource),
+                search: Some(BundleEntrySearch {
+                    mode: mode.to_string(),
+                    score: None,
+                }),
+                request: None,
+                response: None,
+            });
+        }
+    }
+
+    let links = build_search_links_with_total_mode(
+        total,
+        total_is_exact,
+        has_more,
+        base_url,
+        "",
+        page,
+        query_suffix,
+    );
+    Bundle::searchset_with_total(total.map(|value| value as u64), entries, links)
+}
+
+/// Bundle entry carrying search warnings, `None` when there are none.
+fn outcome_entry(outcome: OperationOutcome) -> Option<BundleEntry> {
+    if !outcome.has_issues() {
+        return None;
+    }
+    let outcome_json =
+        serde_json::to_value(&outcome).expect("OperationOutcome should always serialize");
+    Some(BundleEntry {
+        full_url: None,
+        resource: Some(RawJson::from(outcome_json)),
+        search: Some(BundleEntrySearch {
+            mode: "outcome".to_string(),
+            score: None,
+        }),
+        request: None,
+        response: None,
+    })
+}
+
 pub fn bundle_from_search(
     total: usize,
     resources_json: Vec<JsonValue>,
     base_url: &str,
     resource_type: &str,
-    offset: usize,
-    count: usize,
+    page: Page,
     query_suffix: Option<&str>,
 ) -> Bundle {
     let mut entries = Vec::with_capacity(resources_json.len());
@@ -1144,53 +1565,7 @@ pub fn bundle_from_search(
         });
     }
 
-    // compute links
-    let mut links = Vec::new();
-    // self
-    links.push(BundleLink {
-        relation: "self".to_string(),
-        url: build_page_url(base_url, resource_type, offset, count, query_suffix),
-    });
-
-    // first
-    links.push(BundleLink {
-        relation: "first".to_string(),
-        url: build_page_url(base_url, resource_type, 0, count, query_suffix),
-    });
-
-    // last
-    if count > 0 && total > 0 {
-        let last_offset = ((total - 1) / count) * count;
-        links.push(BundleLink {
-            relation: "last".to_string(),
-            url: build_page_url(base_url, resource_type, last_offset, count, query_suffix),
-        });
-    } else {
-        links.push(BundleLink {
-            relation: "last".to_string(),
-            url: build_page_url(base_url, resource_type, 0, count, query_suffix),
-        });
-    }
-
-    // prev
-    if offset > 0 {
-        let prev_offset = offset.saturating_sub(count);
-        links.push(BundleLink {
-            relation: "previous".to_string(),
-            url: build_page_url(base_url, resource_type, prev_offset, count, query_suffix),
-        });
-    }
-
-    // next
-    if count > 0 && offset + count < total {
-        let next_offset = offset + count;
-        links.push(BundleLink {
-            relation: "next".to_string(),
-            url: build_page_url(base_url, resource_type, next_offset, count, query_suffix),
-        });
-    }
-
-    let links = build_search_links(total, base_url, resource_type, offset, count, query_suffix);
+    let links = build_search_links(total, base_url, resource_type, page, query_suffix);
     Bundle::searchset(total as u64, entries, links)
 }
 
@@ -1215,8 +1590,7 @@ pub fn bundle_from_search_with_includes(
     included_resources: Vec<IncludedResourceEntry>,
     base_url: &str,
     resource_type: &str,
-    offset: usize,
-    count: usize,
+    page: Page,
     query_suffix: Option<&str>,
 ) -> Bundle {
     let total_entries = resources_json.len() + included_resources.len();
@@ -1255,7 +1629,7 @@ pub fn bundle_from_search_with_includes(
         });
     }
 
-    let links = build_search_links(total, base_url, resource_type, offset, count, query_suffix);
+    let links = build_search_links(total, base_url, resource_type, page, query_suffix);
     // Note: total only counts match entries, not includes
     Bundle::searchset(total as u64, entries, links)
 }
@@ -1265,8 +1639,7 @@ pub fn build_search_links(
     total: usize,
     base_url: &str,
     resource_type: &str,
-    offset: usize,
-    count: usize,
+    page: Page,
     query_suffix: Option<&str>,
 ) -> Vec<BundleLink> {
     build_search_links_with_total_mode(
@@ -1275,8 +1648,7 @@ pub fn build_search_links(
         false,
         base_url,
         resource_type,
-        offset,
-        count,
+        page,
         query_suffix,
     )
 }
@@ -1290,22 +1662,22 @@ pub fn build_search_links_with_total_mode(
     has_more: bool,
     base_url: &str,
     resource_type: &str,
-    offset: usize,
-    count: usize,
+    page: Page,
     query_suffix: Option<&str>,
 ) -> Vec<BundleLink> {
+    let Page { offset, count, .. } = page;
     let mut links = Vec::new();
 
     // self
     links.push(BundleLink {
         relation: "self".to_string(),
-        url: build_page_url(base_url, resource_type, offset, count, query_suffix),
+        url: build_page_url(base_url, resource_type, page, query_suffix),
     });
 
     // first
     links.push(BundleLink {
         relation: "first".to_string(),
-        url: build_page_url(base_url, resource_type, 0, count, query_suffix),
+        url: build_page_url(base_url, resource_type, page.at(0), query_suffix),
     });
 
     // last
@@ -1321,7 +1693,7 @@ pub fn build_search_links_with_total_mode(
 
         links.push(BundleLink {
             relation: "last".to_string(),
-            url: build_page_url(base_url, resource_type, last_offset, count, query_suffix),
+            url: build_page_url(base_url, resource_type, page.at(last_offset), query_suffix),
         });
     }
 
@@ -1330,7 +1702,7 @@ pub fn build_search_links_with_total_mode(
         let prev_offset = offset.saturating_sub(count);
         links.push(BundleLink {
             relation: "previous".to_string(),
-            url: build_page_url(base_url, resource_type, prev_offset, count, query_suffix),
+            url: build_page_url(base_url, resource_type, page.at(prev_offset), query_suffix),
         });
     }
 
@@ -1345,13 +1717,56 @@ pub fn build_search_links_with_total_mode(
         let next_offset = offset + count;
         links.push(BundleLink {
             relation: "next".to_string(),
-            url: build_page_url(base_url, resource_type, next_offset, count, query_suffix),
+            url: build_page_url(base_url, resource_type, page.at(next_offset), query_suffix),
         });
     }
 
     links
 }
 
+/// Links of an incremental sync page (`_sync`).
+///
+/// `self` repeats the position the page was asked for. `next` resumes after
+/// `last`, the page's last match, and is left out for an empty page: the
+/// sync has caught up, and its `self` link is where the next run starts.
+pub fn build_sync_links(
+    base_url: &str,
+    resource_type: &str,
+    count: usize,
+    position: Option<&SyncCursor>,
+    last: Option<&SyncCursor>,
+    query_suffix: Option<&str>,
+) -> Vec<BundleLink> {
+    let url = |cursor: Option<&SyncCursor>| {
+        let token = cursor.map_or_else(|| "true".to_string(), SyncCursor::encode);
+        let mut url = format!(
+            "{}/{}?_count={count}&{SYNC_PARAM}={token}",
+            base_url.trim_end_matches('/'),
+            resource_type,
+        );
+        if let Some(q) = query_suffix {
+            let q = q.trim_start_matches(['&', '?']);
+            if !q.is_empty() {
+                url.push('&');
+                url.push_str(q);
+            }
+        }
+        url
+    };
+
+    let mut links = vec![BundleLink {
+        relation: "self".to_string(),
+        url: url(position),
+    }];
+    if let Some(last) = last {
+        links.push(BundleLink {
+            relation: "next".to_string(),
+            url: url(Some(last)),
+        });
+    }
+    links
+}
+
 // -------------------------
 // History Bundle Generation
 // -------------------------
@@ -1405,8 +1820,7 @@ pub fn bundle_from_history(
     base_url: &str,
     resource_type: &str,
     id: Option<&str>,
-    offset: usize,
-    count: usize,
+    page: Page,
     total: Option<u32>,
 ) -> Bundle {
     let bundle_entries: Vec<BundleEntry> = entries
@@ -1446,10 +1860,11 @@ pub fn bundle_from_history(
         .collect();
 
     // Build links
+    let Page { offset, count, .. } = page;
     let mut links = Vec::new();
 
     // Build self link URL
-    let self_url = build_history_link(base_url, resource_type, id, offset, count);
+    let self_url = build_history_link(base_url, resource_type, id, page);
     links.push(BundleLink {
         relation: "self".to_string(),
         url: self_url,
@@ -1462,7 +1877,7 @@ pub fn bundle_from_history(
         // first
         links.push(BundleLink {
             relation: "first".to_string(),
-            url: build_history_link(base_url, resource_type, id, 0, count),
+            url: build_history_link(base_url, resource_type, id, page.at(0)),
         });
 
         // prev
@@ -1470,7 +1885,7 @@ pub fn bundle_from_history(
             let prev_offset = offset.saturating_sub(count);
             links.push(BundleLink {
                 relation: "previous".to_string(),
-                url: build_history_link(base_url, resource_type, id, prev_offset, count),
+                url: build_history_link(base_url, resource_type, id, page.at(prev_offset)),
             });
         }
 
@@ -1478,7 +1893,7 @@ pub fn bundle_from_history(
         if count > 0 && offset + count < total_usize {
             links.push(BundleLink {
                 relation: "next".to_string(),
-                url: build_history_link(base_url, resource_type, id, offset + count, count),
+                url: build_history_link(base_url, resource_type, id, page.at(offset + count)),
             });
         }
 
@@ -1487,7 +1902,7 @@ pub fn bundle_from_history(
             let last_offset = ((total_usize - 1) / count) * count;
             links.push(BundleLink {
                 relation: "last".to_string(),
-                url: build_history_link(base_url, resource_type, id, last_offset, count),
+                url: build_history_link(base_url, resource_type, id, page.at(last_offset)),
             });
         }
     }
@@ -1498,26 +1913,20 @@ pub fn bundle_from_history(
 }
 
 /// Build a history endpoint URL with pagination
-fn build_history_link(
-    base_url: &str,
-    resource_type: &str,
-    id: Option<&str>,
-    offset: usize,
-    count: usize,
-) -> String {
+fn build_history_link(base_url: &str, resource_type: &str, id: Option<&str>, page: Page) -> String {
     let base_url = base_url.trim_end_matches('/');
     let path = match id {
         Some(id) => format!("{base_url}/{resource_type}/{id}/_history"),
         None => format!("{base_url}/{resource_type}/_history"),
     };
 
-    format!("{path}?_count={count}&_offset={offset}")
+    format!("{path}?{}", page.query())
 }
 
 /// Build a system-level history endpoint URL with pagination
-fn build_system_history_link(base_url: &str, offset: usize, count: usize) -> String {
+fn build_system_history_link(base_url: &str, page: Page) -> String {
     let base_url = base_url.trim_end_matches('/');
-    format!("{base_url}/_history?_count={count}&_offset={offset}")
+    format!("{base_url}/_history?{}", page.query())
 }
 
 /// Build a system-level history bundle from history entries
@@ -1527,8 +1936,7 @@ fn build_system_history_link(base_url: &str, offset: usize, count: usize) -> Str
 pub fn bundle_from_system_history(
     entries: Vec<HistoryBundleEntry>,
     base_url: &str,
-    offset: usize,
-    count: usize,
+    page: Page,
     total: Option<u32>,
 ) -> Bundle {
     let bundle_entries: Vec<BundleEntry> = entries
@@ -1568,10 +1976,11 @@ pub fn bundle_from_system_history(
         .collect();
 
     // Build links
+    let Page { offset, count, .. } = page;
     let mut links = Vec::new();
 
     // Build self link URL
-    let self_url = build_system_history_link(base_url, offset, count);
+    let self_url = build_system_history_link(base_url, page);
     links.push(BundleLink {
         relation: "self".to_string(),
         url: self_url,
@@ -1584,7 +1993,7 @@ pub fn bundle_from_system_history(
         // first
         links.push(BundleLink {
             relation: "first".to_string(),
-            url: build_system_history_link(base_url, 0, count),
+            url: build_system_history_link(base_url, page.at(0)),
         });
 
         // prev
@@ -1592,7 +2001,7 @@ pub fn bundle_from_system_history(
             let prev_offset = offset.saturating_sub(count);
             links.push(BundleLink {
                 relation: "previous".to_string(),
-                url: build_system_history_link(base_url, prev_offset, count),
+                url: build_system_history_link(base_url, page.at(prev_offset)),
             });
         }
 
@@ -1600,7 +2009,7 @@ pub fn bundle_from_system_history(
         if count > 0 && offset + count < total_usize {
             links.push(BundleLink {
                 relation: "next".to_string(),
-                url: build_system_history_link(base_url, offset + count, count),
+                url: build_system_history_link(base_url, page.at(offset + count)),
             });
         }
 
@@ -1609,7 +2018,7 @@ pub fn bundle_from_system_history(
             let last_offset = ((total_usize - 1) / count) * count;
             links.push(BundleLink {
                 relation: "last".to_string(),
-                url: build_system_history_link(base_url, last_offset, count),
+                url: build_system_history_link(base_url, page.at(last_offset)),
             });
         }
     }
@@ -1638,8 +2047,7 @@ mod bundle_generation_tests {
             resources,
             "http://example.org",
             "Patient",
-            offset,
-            count,
+            Page::new(offset, count),
             Some("name=John"),
         );
         assert_eq!(b.resource_type, "Bundle");
@@ -1662,6 +2070,59 @@ mod bundle_generation_tests {
         assert!(fu.ends_with("/Patient/11"));
     }
 
+    #[test]
+    fn system_search_bundle_mixes_types() {
+        let raw = |rt: &str, id: &str| RawIncludedEntry {
+            resource: RawJson::from(serde_json::json!({"resourceType": rt, "id": id})),
+            resource_type: rt.to_string(),
+            id: id.to_string(),
+        };
+        let b = bundle_from_system_search_raw(
+            Some(3),
+            true,
+            false,
+            vec![raw("Patient", "p1"), raw("Observation", "o1")],
+            vec![raw("Practitioner", "dr")],
+            "http://example.org",
+            Page::new(0, 2),
+            Some("_type=Patient,Observation"),
+            None,
+        );
+        assert_eq!(b.total, Some(3));
+        let entries: Vec<_> = b
+            .entry
+            .iter()
+            .map(|e| {
+                (
+                    e.full_url.clone().unwrap(),
+                    e.search.as_ref().unwrap().mode.clone(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            entries,
+            vec![
+                (
+                    "http://example.org/Patient/p1".to_string(),
+                    "match".to_string()
+                ),
+                (
+                    "http://example.org/Observation/o1".to_string(),
+                    "match".to_string()
+                ),
+                (
+                    "http://example.org/Practitioner/dr".to_string(),
+                    "include".to_string()
+                ),
+            ]
+        );
+        let next = b.link.iter().find(|l| l.relation == "next").unwrap();
+        assert_eq!(
+            next.url,
+            "http://example.org/?_count=2&_offset=2&_type=Patient,Observation"
+        );
+    }
+
     #[test]
     fn first_page_has_no_prev() {
         let b = bundle_from_search(
@@ -1669,8 +2130,7 @@ mod bundle_generation_tests {
             vec![make_pat("1")],
             "http://example.org",
             "Patient",
-            0,
-            10,
+            Page::new(0, 10),
             None,
         );
         let rels: std::collections::HashMap<_, _> = b
@@ -1689,8 +2149,7 @@ mod bundle_generation_tests {
             vec![make_pat("21")],
             "http://example.org",
             "Patient",
-            20,
-            10,
+            Page::new(20, 10),
             None,
         );
         let rels: std::collections::HashMap<_, _> = b
@@ -1705,7 +2164,14 @@ mod bundle_generation_tests {
 
     #[test]
     fn empty_results_still_have_links() {
-        let b = bundle_from_search(0, Vec::new(), "http://example.org", "Patient", 0, 10, None);
+        let b = bundle_from_search(
+            0,
+            Vec::new(),
+            "http://example.org",
+            "Patient",
+            Page::new(0, 10),
+            None,
+        );
         assert_eq!(b.entry.len(), 0);
         let rels: std::collections::HashMap<_, _> = b
             .link
@@ -1727,8 +2193,7 @@ mod bundle_generation_tests {
             true,
             "http://example.org",
             "Patient",
-            0,
-            10,
+            Page::new(0, 10),
             Some("name=John"),
         );
         let rels: std::collections::HashMap<_, _> = links
@@ -1740,6 +2205,83 @@ mod bundle_generation_tests {
         assert!(rels.get("self").unwrap().contains("name=John"));
     }
 
+    #[test]
+    fn link_query_suffix_preserves_repeats_and_modifiers() {
+        let params = [
+            ("name:contains", "Jo hn"),
+            ("_include", "Observation:subject"),
+            ("_include", "Observation:performer"),
+            ("_count", "5"),
+            ("code", "http://loinc.org|8867-4,8310-5"),
+            ("_offset", "10"),
+            ("date", "ge2020-01-01T00:00:00+02:00"),
+        ];
+        assert_eq!(
+            link_query_suffix(params).as_deref(),
+            Some(
+                "name:contains=Jo%20hn&_include=Observation:subject\
+                 &_include=Observation:performer\
+                 &code=http:%2F%2Floinc.org%7C8867-4,8310-5\
+                 &date=ge2020-01-01T00:00:00%2B02:00"
+            )
+        );
+        assert_eq!(link_query_suffix([("_count", "5")]), None);
+        assert_eq!(link_query_suffix(Vec::<(String, String)>::new()), None);
+    }
+
+    #[test]
+    fn page_links_keep_encoded_suffix_intact() {
+        let suffix = link_query_suffix([("_include", "Patient:organization"), ("q", "a?b&c")]);
+        let links = build_search_links(
+            30,
+            "http://example.org",
+            "Patient",
+            Page::new(10, 10),
+            suffix.as_deref(),
+        );
+        let next = links.iter().find(|l| l.relation == "next").unwrap();
+        assert_eq!(
+            next.url,
+            "http://example.org/Patient?_count=10&_offset=20\
+             &_include=Patient:organization&q=a%3Fb%26c"
+        );
+    }
+
+    #[test]
+    fn sync_links_resume_after_last_match() {
+        let suffix = link_query_suffix([("_sync", "true"), ("gender", "female")]);
+        let last = SyncCursor {
+            last_updated_micros: 1_700_000_000_000_001,
+            id: "p2".to_string(),
+        };
+        let links = build_sync_links(
+            "http://example.org/",
+            "Patient",
+            50,
+            None,
+            Some(&last),
+            suffix.as_deref(),
+        );
+        assert_eq!(links[0].relation, "self");
+        assert_eq!(
+            links[0].url,
+            "http://example.org/Patient?_count=50&_sync=true&gender=female"
+        );
+        assert_eq!(links[1].relation, "next");
+        assert_eq!(
+            links[1].url,
+            format!(
+                "http://example.org/Patient?_count=50&_sync={}&gender=female",
+                last.encode()
+            )
+        );
+
+        let caught_up =
+            build_sync_links("http://example.org", "Patient", 50, Some(&last), None, None);
+        assert_eq!(caught_up.len(), 1);
+        assert!(caught_up[0].url.ends_with(&last.encode()));
+    }
+
     #[test]
     fn query_suffix_is_preserved_in_links() {
         let b = bundle_from_search(
@@ -1747,8 +2289,7 @@ mod bundle_generation_tests {
             vec![make_pat("1")],
             "http://example.org",
             "Patient",
-            0,
-            10,
+            Page::new(0, 10),
             Some("name=John&identifier=urn%3Asys%7C123"),
         );
         let rels: std::collections::HashMap<_, _> = b
@@ -1776,8 +2317,7 @@ mod bundle_generation_tests {
             vec![make_pat("1"), make_pat("2")],
             "http://example.org",
             "Patient",
-            0,
-            count,
+            Page::new(0, count),
             Some("name=Jane"),
         );
         let rels: std::collections::HashMap<_, _> = b
@@ -1806,8 +2346,7 @@ mod bundle_generation_tests {
             "http://example.org/fhir",
             "Patient",
             None,
-            10,
-            10,
+            Page::new(10, 10),
             Some(25),
         );
 
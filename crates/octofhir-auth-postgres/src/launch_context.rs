@@ -1,7 +1,8 @@
 //! SMART launch context storage for PostgreSQL.
 //!
 //! Stores temporary launch contexts during the EHR launch flow.
-//! Contexts are stored with TTL and consumed during token exchange.
+//! Contexts are stored with TTL and consumed once during authorization.
+//! Consumed rows are kept until they expire so replays can be reported.
 //!
 //! # Table Structure
 //!
@@ -10,7 +11,8 @@
 //!     launch_id VARCHAR(64) PRIMARY KEY,
 //!     context_data JSONB NOT NULL,
 //!     expires_at TIMESTAMPTZ NOT NULL,
-//!     created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+//!     created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+//!     consumed_at TIMESTAMPTZ
 //! );
 //!
 //! CREATE INDEX idx_smart_launch_context_expires
@@ -18,6 +20,7 @@
 //! ```
 
 use octofhir_auth::smart::launch::StoredLaunchContext;
+use octofhir_auth::storage::LaunchContextStatus;
 use sqlx_core::query::query;
 use sqlx_core::query_as::query_as;
 use sqlx_core::query_scalar::query_scalar;
@@ -72,7 +75,8 @@ impl<'a> LaunchContextStorage<'a> {
             ON CONFLICT (launch_id) DO UPDATE SET
                 context_data = EXCLUDED.context_data,
                 expires_at = EXCLUDED.expires_at,
-                created_at = EXCLUDED.created_at
+                created_at = EXCLUDED.created_at,
+                consumed_at = NULL
             "#,
         )
         .bind(&context.launch_id)
@@ -86,7 +90,8 @@ impl<'a> LaunchContextStorage<'a> {
 
     /// Retrieve a launch context by launch ID without consuming it.
     ///
-    /// Returns `None` if the context doesn't exist or has expired.
+    /// Returns `None` if the context doesn't exist, has expired or was
+    /// already consumed.
     ///
     /// # Errors
     ///
@@ -98,6 +103,7 @@ impl<'a> LaunchContextStorage<'a> {
             FROM octofhir_auth.smart_launch_context
             WHERE launch_id = $1
               AND expires_at > NOW()
+              AND consumed_at IS NULL
             "#,
         )
         .bind(launch_id)
@@ -113,9 +119,10 @@ impl<'a> LaunchContextStorage<'a> {
         }
     }
 
-    /// Atomically retrieve and delete a launch context.
+    /// Atomically retrieve and mark a launch context as consumed.
     ///
-    /// This ensures single-use semantics for launch contexts.
+    /// This ensures single-use semantics for launch contexts. The row is kept
+    /// until it expires so [`status`](Self::status) can report the replay.
     /// If two concurrent requests try to consume the same context,
     /// only one will succeed.
     ///
@@ -128,9 +135,11 @@ impl<'a> LaunchContextStorage<'a> {
     pub async fn consume(&self, launch_id: &str) -> StorageResult<Option<StoredLaunchContext>> {
         let row: Option<(serde_json::Value,)> = query_as(
             r#"
-            DELETE FROM octofhir_auth.smart_launch_context
+            UPDATE octofhir_auth.smart_launch_context
+            SET consumed_at = NOW()
             WHERE launch_id = $1
               AND expires_at > NOW()
+              AND consumed_at IS NULL
             RETURNING context_data
             "#,
         )
@@ -192,7 +201,7 @@ impl<'a> LaunchContextStorage<'a> {
         Ok(result.rows_affected())
     }
 
-    /// Check if a launch context exists and is not expired.
+    /// Check if a launch context exists, is not expired and is unused.
     ///
     /// # Errors
     ///
@@ -204,6 +213,7 @@ impl<'a> LaunchContextStorage<'a> {
                 SELECT 1 FROM octofhir_auth.smart_launch_context
                 WHERE launch_id = $1
                   AND expires_at > NOW()
+                  AND consumed_at IS NULL
             )
             "#,
         )
@@ -214,6 +224,34 @@ impl<'a> LaunchContextStorage<'a> {
         Ok(exists)
     }
 
+    /// Report whether a launch context is active, expired or consumed.
+    ///
+    /// Expired rows removed by [`cleanup_expired`](Self::cleanup_expired)
+    /// are reported as `NotFound`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database query fails.
+    pub async fn status(&self, launch_id: &str) -> StorageResult<LaunchContextStatus> {
+        let row: Option<(bool, bool)> = query_as(
+            r#"
+            SELECT expires_at <= NOW(), consumed_at IS NOT NULL
+            FROM octofhir_auth.smart_launch_context
+            WHERE launch_id = $1
+            "#,
+        )
+        .bind(launch_id)
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(match row {
+            None => LaunchContextStatus::NotFound,
+            Some((true, _)) => LaunchContextStatus::Expired,
+            Some((false, true)) => LaunchContextStatus::Consumed,
+            Some((false, false)) => LaunchContextStatus::Active,
+        })
+    }
+
     /// Get the count of active (non-expired) launch contexts.
     ///
     /// Useful for monitoring and debugging.
@@ -227,6 +265,7 @@ impl<'a> LaunchContextStorage<'a> {
             SELECT COUNT(*)
             FROM octofhir_auth.smart_launch_context
             WHERE expires_at > NOW()
+              AND consumed_at IS NULL
             "#,
         )
         .fetch_one(self.pool)
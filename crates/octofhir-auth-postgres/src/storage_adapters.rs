@@ -15,7 +15,7 @@ use octofhir_auth::smart::launch::StoredLaunchContext;
 use octofhir_auth::storage::{
     AuthorizeSessionStorage as AuthorizeSessionStorageTrait, BasicAuthStorage,
     ClientStorage as ClientStorageTrait, ConsentStorage as ConsentStorageTrait,
-    LaunchContextStorage as LaunchContextStorageTrait,
+    LaunchContextStatus, LaunchContextStorage as LaunchContextStorageTrait,
     RefreshTokenStorage as RefreshTokenStorageTrait,
     RevokedTokenStorage as RevokedTokenStorageTrait, SessionStorage as SessionStorageTrait, User,
     UserConsent, UserStorage as UserStorageTrait,
@@ -900,4 +900,12 @@ impl LaunchContextStorageTrait for ArcLaunchContextStorage {
             .await
             .map_err(|e| AuthError::storage(e.to_string()))
     }
+
+    async fn status(&self, launch_id: &str) -> AuthResult<LaunchContextStatus> {
+        let storage = LaunchContextStorage::new(&self.pool);
+        storage
+            .status(launch_id)
+            .await
+            .map_err(|e| AuthError::storage(e.to_string()))
+    }
 }
@@ -102,6 +102,12 @@ pub struct OAuthConfig {
     /// This is recommended for security (detects token theft).
     pub refresh_token_rotation: bool,
 
+    /// Absolute maximum session lifetime for refresh tokens.
+    /// Counted from the original authorization; refreshing or rotating
+    /// cannot extend a session past it. Unset means no cap.
+    #[serde(default, with = "humantime_serde")]
+    pub refresh_token_max_lifetime: Option<Duration>,
+
     /// Allowed OAuth 2.0 grant types.
     /// Supported: "authorization_code", "client_credentials", "refresh_token"
     pub grant_types: Vec<String>,
@@ -114,6 +120,7 @@ impl Default for OAuthConfig {
             access_token_lifetime: Duration::from_secs(3600),      // 1 hour
             refresh_token_lifetime: Duration::from_secs(90 * 24 * 3600), // 90 days
             refresh_token_rotation: true,
+            refresh_token_max_lifetime: None,
             grant_types: vec![
                 "authorization_code".to_string(),
                 "client_credentials".to_string(),
@@ -265,6 +272,14 @@ pub struct PolicyConfig {
     /// evaluation is skipped. Intended for testing, benchmarking, and other
     /// trusted-network deployments — never enable in production.
     pub anonymous_access: bool,
+
+    /// Put the deny message and matching policy id in the `diagnostics` of
+    /// 403 responses.
+    ///
+    /// Denials are always logged with their reason; by default clients only
+    /// get the reason code so policy internals are not leaked. Enable while
+    /// debugging policies, not in production.
+    pub expose_deny_reason: bool,
 }
 
 impl Default for PolicyConfig {
@@ -274,6 +289,7 @@ impl Default for PolicyConfig {
             quickjs_enabled: true,
             quickjs: QuickJsConfig::default(),
             anonymous_access: false,
+            expose_deny_reason: false,
         }
     }
 }
@@ -327,9 +343,33 @@ pub struct FederationConfig {
     #[serde(with = "humantime_serde")]
     pub jwks_cache_ttl: Duration,
 
+    /// Fraction of the JWKS TTL randomly shaved off each entry, so instances
+    /// don't all refetch at once.
+    pub jwks_cache_jitter: f64,
+
+    /// How long an expired JWKS is still served while it is refreshed in the
+    /// background or the provider is unreachable.
+    #[serde(with = "humantime_serde")]
+    pub jwks_max_stale: Duration,
+
     /// Refresh JWKS on validation failure.
-    /// When enabled, attempts to refresh JWKS if token validation fails.
+    /// When enabled, attempts to refresh JWKS if a token's signature does not
+    /// verify and the key it names is not cached.
     pub jwks_refresh_on_failure: bool,
+
+    /// Minimum time between refetches of an issuer's JWKS forced by unknown
+    /// keys or failed signature checks.
+    #[serde(with = "humantime_serde")]
+    pub jwks_refresh_cooldown: Duration,
+
+    /// External issuers whose access tokens are accepted directly.
+    /// Tokens from these issuers are validated against the issuer's JWKS
+    /// instead of the server's own signing key.
+    pub trusted_issuers: Vec<TrustedIssuerConfig>,
+
+    /// Clock skew tolerance for `exp`/`nbf` checks on external tokens.
+    #[serde(with = "humantime_serde")]
+    pub clock_skew: Duration,
 }
 
 impl Default for FederationConfig {
@@ -338,11 +378,29 @@ impl Default for FederationConfig {
             allow_external_idp: true,
             auto_provision_users: false,
             jwks_cache_ttl: Duration::from_secs(3600), // 1 hour
+            jwks_cache_jitter: 0.1,
+            jwks_max_stale: Duration::from_secs(900), // 15 minutes
             jwks_refresh_on_failure: true,
+            jwks_refresh_cooldown: Duration::from_secs(60), // 1 minute
+            trusted_issuers: Vec::new(),
+            clock_skew: Duration::from_secs(60),
         }
     }
 }
 
+/// An external issuer whose access tokens are accepted.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TrustedIssuerConfig {
+    /// Expected `iss` claim value.
+    pub issuer: String,
+
+    /// JWKS endpoint with the issuer's signing keys.
+    pub jwks_uri: String,
+
+    /// Accepted `aud` values. A token must contain at least one.
+    pub audiences: Vec<String>,
+}
+
 /// Rate limiting configuration.
 ///
 /// Controls rate limiting for authentication endpoints to prevent abuse.
@@ -598,6 +656,8 @@ impl AuthConfig {
     /// - The signing algorithm is not supported
     /// - An invalid grant type is specified
     /// - QuickJS memory or timeout limits are zero
+    /// - A trusted issuer has an invalid JWKS URI, no audiences, or
+    ///   matches the server's own issuer
     pub fn validate(&self) -> Result<(), ConfigError> {
         // Validate issuer URL
         if self.issuer.is_empty() {
@@ -671,6 +731,28 @@ impl AuthConfig {
             ));
         }
 
+        // Validate trusted external issuers
+        for trusted in &self.federation.trusted_issuers {
+            if trusted.issuer.trim_end_matches('/') == self.issuer.trim_end_matches('/') {
+                return Err(ConfigError::InvalidValue(format!(
+                    "Trusted issuer '{}' must not be the server's own issuer",
+                    trusted.issuer
+                )));
+            }
+            if url::Url::parse(&trusted.jwks_uri).is_err() {
+                return Err(ConfigError::InvalidValue(format!(
+                    "Invalid jwks_uri for trusted issuer '{}': '{}'",
+                    trusted.issuer, trusted.jwks_uri
+                )));
+            }
+            if trusted.audiences.is_empty() {
+                return Err(ConfigError::InvalidValue(format!(
+                    "Trusted issuer '{}' must list at least one audience",
+                    trusted.issuer
+                )));
+            }
+        }
+
         Ok(())
     }
 }
@@ -736,6 +818,25 @@ mod tests {
         assert!(err.to_string().contains("grant type"));
     }
 
+    #[test]
+    fn test_trusted_issuer_validation() {
+        let mut config = AuthConfig::default();
+        config.federation.trusted_issuers = vec![TrustedIssuerConfig {
+            issuer: "https://idp.example.com".to_string(),
+            jwks_uri: "https://idp.example.com/jwks".to_string(),
+            audiences: vec!["https://fhir.example.com".to_string()],
+        }];
+        assert!(config.validate().is_ok());
+
+        config.federation.trusted_issuers[0].audiences.clear();
+        let err = config.validate().unwrap_err();
+        assert!(err.to_string().contains("audience"));
+
+        config.federation.trusted_issuers[0].issuer = config.issuer.clone();
+        let err = config.validate().unwrap_err();
+        assert!(err.to_string().contains("own issuer"));
+    }
+
     #[test]
     fn test_zero_quickjs_memory_fails_validation() {
         let mut config = AuthConfig::default();
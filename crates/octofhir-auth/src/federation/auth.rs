@@ -45,6 +45,7 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use jsonwebtoken::{Validation, decode_header};
+use octofhir_core::http::{HttpClient, HttpClientConfig, RetryPolicy};
 use serde::{Deserialize, Serialize};
 use tokio::sync::RwLock;
 use url::Url;
@@ -64,6 +65,9 @@ pub struct IdpAuthServiceConfig {
     /// HTTP request timeout (default: 30 seconds).
     pub request_timeout: Duration,
 
+    /// Retries for transient failures of outbound calls.
+    pub retry: RetryPolicy,
+
     /// Clock skew tolerance for token validation (default: 60 seconds).
     pub clock_skew_tolerance: Duration,
 
@@ -78,6 +82,7 @@ impl IdpAuthServiceConfig {
         Self {
             callback_url,
             request_timeout: Duration::from_secs(30),
+            retry: RetryPolicy::default(),
             clock_skew_tolerance: Duration::from_secs(60),
             allow_http: false,
         }
@@ -90,6 +95,13 @@ impl IdpAuthServiceConfig {
         self
     }
 
+    /// Sets the retry policy for outbound calls.
+    #[must_use]
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
     /// Sets the clock skew tolerance for token validation.
     #[must_use]
     pub fn with_clock_skew_tolerance(mut self, tolerance: Duration) -> Self {
@@ -112,7 +124,7 @@ pub struct IdpAuthService {
     /// JWKS cache for fetching provider public keys.
     jwks_cache: Arc<ProviderJwksCache>,
     /// HTTP client for token exchange.
-    http_client: reqwest::Client,
+    http_client: HttpClient,
     /// Registered providers.
     providers: Arc<RwLock<HashMap<String, IdentityProviderConfig>>>,
     /// Service configuration.
@@ -125,24 +137,19 @@ impl IdpAuthService {
     pub fn new(config: IdpAuthServiceConfig) -> Self {
         let discovery_config = DiscoveryCacheConfig::default()
             .with_request_timeout(config.request_timeout)
+            .with_retry(config.retry.clone())
             .with_allow_http(config.allow_http);
 
         let jwks_config = ProviderJwksCacheConfig::default()
             .with_request_timeout(config.request_timeout)
+            .with_retry(config.retry.clone())
             .with_allow_http(config.allow_http);
 
-        let http_client = reqwest::Client::builder()
-            .timeout(config.request_timeout)
-            .build()
-            .expect("Failed to create HTTP client");
-
-        Self {
-            discovery_cache: Arc::new(DiscoveryCache::new(discovery_config)),
-            jwks_cache: Arc::new(ProviderJwksCache::new(jwks_config)),
-            http_client,
-            providers: Arc::new(RwLock::new(HashMap::new())),
+        Self::with_caches(
             config,
-        }
+            Arc::new(DiscoveryCache::new(discovery_config)),
+            Arc::new(ProviderJwksCache::new(jwks_config)),
+        )
     }
 
     /// Creates a new service with custom caches (for testing or sharing caches).
@@ -152,10 +159,11 @@ impl IdpAuthService {
         discovery_cache: Arc<DiscoveryCache>,
         jwks_cache: Arc<ProviderJwksCache>,
     ) -> Self {
-        let http_client = reqwest::Client::builder()
-            .timeout(config.request_timeout)
-            .build()
-            .expect("Failed to create HTTP client");
+        let http_client = HttpClient::new(
+            &HttpClientConfig::with_timeout(config.request_timeout)
+                .with_retry(config.retry.clone()),
+        )
+        .expect("Failed to create HTTP client");
 
         Self {
             discovery_cache,
@@ -380,12 +388,8 @@ impl IdpAuthService {
             token_endpoint
         );
 
-        let response = self
-            .http_client
-            .post(token_endpoint.as_str())
-            .form(&params)
-            .send()
-            .await?;
+        let request = self.http_client.post(token_endpoint.as_str()).form(&params);
+        let response = self.http_client.send(request).await?;
 
         if !response.status().is_success() {
             let status = response.status();
@@ -534,12 +538,11 @@ impl IdpAuthService {
             .or(discovery.userinfo_endpoint.as_ref())
             .ok_or_else(|| IdpError::MissingField("userinfo_endpoint".to_string()))?;
 
-        let response = self
+        let request = self
             .http_client
             .get(userinfo_endpoint)
-            .bearer_auth(access_token)
-            .send()
-            .await?;
+            .bearer_auth(access_token);
+        let response = self.http_client.send(request).await?;
 
         if !response.status().is_success() {
             return Err(IdpError::TokenExchangeFailed(format!(
@@ -706,7 +709,7 @@ pub struct IdTokenClaims {
 }
 
 /// Custom deserializer for audience which can be a string or array.
-fn deserialize_audience<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+pub(crate) fn deserialize_audience<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
 where
     D: serde::Deserializer<'de>,
 {
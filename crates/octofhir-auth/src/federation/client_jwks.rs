@@ -23,6 +23,7 @@ use std::time::{Duration, Instant};
 
 use jsonwebtoken::jwk::{Jwk, JwkSet};
 use jsonwebtoken::{Algorithm, DecodingKey};
+use octofhir_core::http::{HttpClient, HttpClientConfig, RetryPolicy};
 use tokio::sync::RwLock;
 
 use crate::AuthResult;
@@ -37,6 +38,9 @@ pub struct JwksCacheConfig {
     /// HTTP request timeout (default: 10 seconds).
     pub request_timeout: Duration,
 
+    /// Retries for transient fetch failures.
+    pub retry: RetryPolicy,
+
     /// Maximum response size in bytes (default: 1 MB).
     pub max_response_size: usize,
 }
@@ -46,7 +50,8 @@ impl Default for JwksCacheConfig {
         Self {
             ttl: Duration::from_secs(3600),           // 1 hour
             request_timeout: Duration::from_secs(10), // 10 seconds
-            max_response_size: 1024 * 1024,           // 1 MB
+            retry: RetryPolicy::default(),
+            max_response_size: 1024 * 1024, // 1 MB
         }
     }
 }
@@ -72,6 +77,13 @@ impl JwksCacheConfig {
         self
     }
 
+    /// Sets the retry policy for fetches.
+    #[must_use]
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
     /// Sets the maximum response size.
     #[must_use]
     pub fn with_max_response_size(mut self, size: usize) -> Self {
@@ -108,6 +120,8 @@ struct CachedJwks {
 /// ).await?;
 /// ```
 pub struct ClientJwksCache {
+    /// HTTP client for fetching JWKS.
+    http_client: HttpClient,
     /// Cached JWKS by URI.
     cache: Arc<RwLock<HashMap<String, CachedJwks>>>,
     /// Configuration.
@@ -116,9 +130,20 @@ pub struct ClientJwksCache {
 
 impl ClientJwksCache {
     /// Creates a new JWKS cache with the specified configuration.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the HTTP client cannot be created (should not happen in practice).
     #[must_use]
     pub fn new(config: JwksCacheConfig) -> Self {
+        let http_client = HttpClient::new(
+            &HttpClientConfig::with_timeout(config.request_timeout)
+                .with_retry(config.retry.clone()),
+        )
+        .expect("Failed to create HTTP client");
+
         Self {
+            http_client,
             cache: Arc::new(RwLock::new(HashMap::new())),
             config,
         }
@@ -227,22 +252,15 @@ impl ClientJwksCache {
             return Err(AuthError::invalid_client("JWKS URI must use HTTPS"));
         }
 
-        // Build HTTP client with timeout
-        let client = reqwest::Client::builder()
-            .timeout(self.config.request_timeout)
-            .build()
-            .map_err(|e| AuthError::internal(format!("Failed to create HTTP client: {}", e)))?;
-
         // Fetch JWKS
-        let response = client
+        let request = self
+            .http_client
             .get(uri)
-            .header("Accept", "application/json")
-            .send()
-            .await
-            .map_err(|e| {
-                tracing::warn!("Failed to fetch JWKS from {}: {}", uri, e);
-                AuthError::internal(format!("Failed to fetch JWKS: {}", e))
-            })?;
+            .header("Accept", "application/json");
+        let response = self.http_client.send(request).await.map_err(|e| {
+            tracing::warn!("Failed to fetch JWKS from {}: {}", uri, e);
+            AuthError::internal(format!("Failed to fetch JWKS: {}", e))
+        })?;
 
         // Check status
         if !response.status().is_success() {
@@ -42,10 +42,14 @@ use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
+use octofhir_core::http::{HttpClient, HttpClientConfig, RetryPolicy};
 use tokio::sync::RwLock;
 use url::Url;
 
 use super::oidc::OidcDiscoveryDocument;
+use super::remote_cache::{
+    BackgroundRefreshes, CacheCounters, Freshness, RemoteCacheStats, jittered,
+};
 
 /// Configuration for the OIDC discovery cache.
 #[derive(Debug, Clone)]
@@ -53,9 +57,20 @@ pub struct DiscoveryCacheConfig {
     /// Time-to-live for cached discovery documents (default: 1 hour).
     pub ttl: Duration,
 
+    /// Fraction of the TTL by which each entry's lifetime is randomly
+    /// shortened, spreading refetches across instances (default: 0.1).
+    pub ttl_jitter: f64,
+
+    /// How long an expired document is still served while it is refreshed
+    /// in the background, or when refreshing fails (default: 1 hour).
+    pub max_stale: Duration,
+
     /// HTTP request timeout (default: 10 seconds).
     pub request_timeout: Duration,
 
+    /// Retries for transient fetch failures.
+    pub retry: RetryPolicy,
+
     /// Maximum response size in bytes (default: 1 MB).
     pub max_response_size: usize,
 
@@ -67,9 +82,12 @@ pub struct DiscoveryCacheConfig {
 impl Default for DiscoveryCacheConfig {
     fn default() -> Self {
         Self {
-            ttl: Duration::from_secs(3600),           // 1 hour
+            ttl: Duration::from_secs(3600), // 1 hour
+            ttl_jitter: 0.1,
+            max_stale: Duration::from_secs(3600),     // 1 hour
             request_timeout: Duration::from_secs(10), // 10 seconds
-            max_response_size: 1024 * 1024,           // 1 MB
+            retry: RetryPolicy::default(),
+            max_response_size: 1024 * 1024, // 1 MB
             allow_http: false,
         }
     }
@@ -89,6 +107,20 @@ impl DiscoveryCacheConfig {
         self
     }
 
+    /// Sets the TTL jitter fraction (clamped to `0..=1`).
+    #[must_use]
+    pub fn with_ttl_jitter(mut self, jitter: f64) -> Self {
+        self.ttl_jitter = jitter;
+        self
+    }
+
+    /// Sets how long expired documents may still be served.
+    #[must_use]
+    pub fn with_max_stale(mut self, max_stale: Duration) -> Self {
+        self.max_stale = max_stale;
+        self
+    }
+
     /// Sets the HTTP request timeout.
     #[must_use]
     pub fn with_request_timeout(mut self, timeout: Duration) -> Self {
@@ -96,6 +128,13 @@ impl DiscoveryCacheConfig {
         self
     }
 
+    /// Sets the retry policy for fetches.
+    #[must_use]
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
     /// Sets the maximum response size.
     #[must_use]
     pub fn with_max_response_size(mut self, size: usize) -> Self {
@@ -165,7 +204,7 @@ pub enum DiscoveryError {
 /// This client fetches OpenID Connect provider metadata from the
 /// `.well-known/openid-configuration` endpoint and validates the response.
 pub struct OidcDiscoveryClient {
-    http_client: reqwest::Client,
+    http_client: HttpClient,
     config: DiscoveryCacheConfig,
 }
 
@@ -177,10 +216,11 @@ impl OidcDiscoveryClient {
     /// Panics if the HTTP client cannot be created (should not happen in practice).
     #[must_use]
     pub fn new(config: DiscoveryCacheConfig) -> Self {
-        let http_client = reqwest::Client::builder()
-            .timeout(config.request_timeout)
-            .build()
-            .expect("Failed to create HTTP client");
+        let http_client = HttpClient::new(
+            &HttpClientConfig::with_timeout(config.request_timeout)
+                .with_retry(config.retry.clone()),
+        )
+        .expect("Failed to create HTTP client");
 
         Self {
             http_client,
@@ -224,16 +264,14 @@ impl OidcDiscoveryClient {
         let discovery_url = self.build_discovery_url(issuer);
 
         // Fetch document
-        let response = self
+        let request = self
             .http_client
             .get(discovery_url.as_str())
-            .header("Accept", "application/json")
-            .send()
-            .await
-            .map_err(|e| {
-                tracing::warn!("Failed to fetch OIDC discovery from {}: {}", issuer, e);
-                DiscoveryError::NetworkError(e.to_string())
-            })?;
+            .header("Accept", "application/json");
+        let response = self.http_client.send(request).await.map_err(|e| {
+            tracing::warn!("Failed to fetch OIDC discovery from {}: {}", issuer, e);
+            DiscoveryError::NetworkError(e.to_string())
+        })?;
 
         // Check status
         if !response.status().is_success() {
@@ -335,14 +373,18 @@ impl OidcDiscoveryClient {
 struct CachedDiscovery {
     /// The cached discovery document.
     document: OidcDiscoveryDocument,
-    /// When this entry was fetched.
-    fetched_at: Instant,
+    /// When this entry expires (TTL minus jitter after fetching).
+    expires_at: Instant,
 }
 
+type DiscoveryEntries = Arc<RwLock<HashMap<String, CachedDiscovery>>>;
+
 /// In-memory cache for OIDC discovery documents.
 ///
-/// This cache stores discovery documents fetched from issuer URLs and
-/// provides automatic expiration based on TTL.
+/// This cache stores discovery documents fetched from issuer URLs. Entries
+/// expire after a jittered TTL; an expired entry is served for up to
+/// `max_stale` while it is refreshed in the background (see
+/// [`remote_cache`](super::remote_cache)).
 ///
 /// # Example
 ///
@@ -361,9 +403,13 @@ struct CachedDiscovery {
 /// ```
 pub struct DiscoveryCache {
     /// The underlying client for fetching documents.
-    client: OidcDiscoveryClient,
+    client: Arc<OidcDiscoveryClient>,
     /// Cached documents by issuer URL.
-    cache: Arc<RwLock<HashMap<String, CachedDiscovery>>>,
+    cache: DiscoveryEntries,
+    /// Background refreshes of stale entries.
+    background: BackgroundRefreshes,
+    /// Lookup and fetch counters.
+    counters: Arc<CacheCounters>,
     /// Configuration.
     config: DiscoveryCacheConfig,
 }
@@ -375,8 +421,10 @@ impl DiscoveryCache {
         let client = OidcDiscoveryClient::new(config.clone());
 
         Self {
-            client,
+            client: Arc::new(client),
             cache: Arc::new(RwLock::new(HashMap::new())),
+            background: BackgroundRefreshes::default(),
+            counters: Arc::new(CacheCounters::default()),
             config,
         }
     }
@@ -390,7 +438,9 @@ impl DiscoveryCache {
     /// Gets a discovery document, using the cache if available.
     ///
     /// If the document is cached and not expired, returns the cached value.
-    /// Otherwise, fetches a fresh document and updates the cache.
+    /// If it expired less than `max_stale` ago, returns it as well and
+    /// refreshes it in the background. Otherwise, fetches a fresh document
+    /// and updates the cache.
     ///
     /// # Arguments
     ///
@@ -408,33 +458,62 @@ impl DiscoveryCache {
 
         // Check cache first
         {
+            let now = Instant::now();
             let cache = self.cache.read().await;
             if let Some(cached) = cache.get(&key) {
-                if cached.fetched_at.elapsed() < self.config.ttl {
-                    tracing::trace!("Cache hit for OIDC discovery: {}", issuer);
-                    return Ok(cached.document.clone());
+                match Freshness::of(cached.expires_at, self.config.max_stale, now) {
+                    Freshness::Fresh => {
+                        tracing::trace!("Cache hit for OIDC discovery: {}", issuer);
+                        self.counters.hit();
+                        return Ok(cached.document.clone());
+                    }
+                    Freshness::Stale => {
+                        tracing::trace!("Serving stale OIDC discovery for {}", issuer);
+                        self.counters.stale_hit();
+                        self.refresh_in_background(issuer, &key, now);
+                        return Ok(cached.document.clone());
+                    }
+                    Freshness::Expired => {
+                        tracing::trace!("Cache expired for OIDC discovery: {}", issuer);
+                    }
                 }
-                tracing::trace!("Cache expired for OIDC discovery: {}", issuer);
             }
         }
 
         // Fetch fresh document
         tracing::debug!("Fetching OIDC discovery document from {}", issuer);
-        let document = self.client.discover(issuer).await?;
-
-        // Update cache
-        {
-            let mut cache = self.cache.write().await;
-            cache.insert(
-                key,
-                CachedDiscovery {
-                    document: document.clone(),
-                    fetched_at: Instant::now(),
-                },
-            );
-        }
+        self.counters.miss();
+        fetch_and_store(
+            &self.client,
+            &self.cache,
+            &self.counters,
+            &self.config,
+            issuer,
+        )
+        .await
+    }
 
-        Ok(document)
+    /// Starts refreshing a stale entry unless a refresh is already running
+    /// or backing off after a failure.
+    fn refresh_in_background(&self, issuer: &Url, key: &str, now: Instant) {
+        let Some(claim) = self.background.try_start(key, now) else {
+            return;
+        };
+        let client = self.client.clone();
+        let cache = self.cache.clone();
+        let counters = self.counters.clone();
+        let config = self.config.clone();
+        let issuer = issuer.clone();
+        tokio::spawn(async move {
+            if let Err(e) = fetch_and_store(&client, &cache, &counters, &config, &issuer).await {
+                tracing::warn!(
+                    issuer = %issuer,
+                    error = %e,
+                    "Background refresh of OIDC discovery failed; serving stale document"
+                );
+                claim.failed();
+            }
+        });
     }
 
     /// Forces a refresh of the cached discovery document.
@@ -450,24 +529,15 @@ impl DiscoveryCache {
     ///
     /// Returns the freshly fetched `OidcDiscoveryDocument`.
     pub async fn refresh(&self, issuer: &Url) -> Result<OidcDiscoveryDocument, DiscoveryError> {
-        let key = normalize_issuer_key(issuer);
-
         tracing::debug!("Force refreshing OIDC discovery document from {}", issuer);
-        let document = self.client.discover(issuer).await?;
-
-        // Update cache
-        {
-            let mut cache = self.cache.write().await;
-            cache.insert(
-                key,
-                CachedDiscovery {
-                    document: document.clone(),
-                    fetched_at: Instant::now(),
-                },
-            );
-        }
-
-        Ok(document)
+        fetch_and_store(
+            &self.client,
+            &self.cache,
+            &self.counters,
+            &self.config,
+            issuer,
+        )
+        .await
     }
 
     /// Invalidates a cached entry.
@@ -481,15 +551,16 @@ impl DiscoveryCache {
         tracing::debug!("Invalidated cache for OIDC discovery: {}", issuer);
     }
 
-    /// Clears all expired entries from the cache.
+    /// Clears all entries too old to be served, even stale, from the cache.
     ///
     /// This is useful for periodic cleanup to free memory from expired entries.
     pub async fn cleanup(&self) {
         let mut cache = self.cache.write().await;
-        let ttl = self.config.ttl;
+        let now = Instant::now();
+        let max_stale = self.config.max_stale;
         let before_count = cache.len();
 
-        cache.retain(|_, v| v.fetched_at.elapsed() < ttl);
+        cache.retain(|_, v| Freshness::of(v.expires_at, max_stale, now) != Freshness::Expired);
 
         let removed = before_count - cache.len();
         if removed > 0 {
@@ -516,6 +587,34 @@ impl DiscoveryCache {
     pub async fn is_empty(&self) -> bool {
         self.cache.read().await.is_empty()
     }
+
+    /// Returns lookup and fetch statistics.
+    pub async fn stats(&self) -> RemoteCacheStats {
+        self.counters.snapshot(self.len().await)
+    }
+}
+
+/// Fetches the document for `issuer` and caches it with a jittered TTL.
+async fn fetch_and_store(
+    client: &OidcDiscoveryClient,
+    cache: &DiscoveryEntries,
+    counters: &CacheCounters,
+    config: &DiscoveryCacheConfig,
+    issuer: &Url,
+) -> Result<OidcDiscoveryDocument, DiscoveryError> {
+    let result = client.discover(issuer).await;
+    counters.fetched(&result);
+    let document = result?;
+
+    cache.write().await.insert(
+        normalize_issuer_key(issuer),
+        CachedDiscovery {
+            document: document.clone(),
+            expires_at: Instant::now() + jittered(config.ttl, config.ttl_jitter),
+        },
+    );
+
+    Ok(document)
 }
 
 /// Normalizes an issuer URL for use as a cache key.
@@ -533,6 +632,8 @@ mod tests {
     fn test_config_defaults() {
         let config = DiscoveryCacheConfig::default();
         assert_eq!(config.ttl, Duration::from_secs(3600));
+        assert_eq!(config.ttl_jitter, 0.1);
+        assert_eq!(config.max_stale, Duration::from_secs(3600));
         assert_eq!(config.request_timeout, Duration::from_secs(10));
         assert_eq!(config.max_response_size, 1024 * 1024);
         assert!(!config.allow_http);
@@ -622,7 +723,7 @@ mod tests {
                 "https://example.com".to_string(),
                 CachedDiscovery {
                     document: create_test_document("https://example.com"),
-                    fetched_at: Instant::now(),
+                    expires_at: Instant::now() + Duration::from_secs(3600),
                 },
             );
         }
@@ -650,14 +751,14 @@ mod tests {
                 "https://a.example.com".to_string(),
                 CachedDiscovery {
                     document: create_test_document("https://a.example.com"),
-                    fetched_at: Instant::now(),
+                    expires_at: Instant::now() + Duration::from_secs(3600),
                 },
             );
             c.insert(
                 "https://b.example.com".to_string(),
                 CachedDiscovery {
                     document: create_test_document("https://b.example.com"),
-                    fetched_at: Instant::now(),
+                    expires_at: Instant::now() + Duration::from_secs(3600),
                 },
             );
         }
@@ -674,7 +775,8 @@ mod tests {
     async fn test_cache_cleanup() {
         let config = DiscoveryCacheConfig::default()
             .with_allow_http(true)
-            .with_ttl(Duration::from_millis(1)); // Very short TTL for testing
+            .with_ttl(Duration::from_millis(1)) // Very short TTL for testing
+            .with_max_stale(Duration::ZERO);
         let cache = DiscoveryCache::new(config);
 
         // Add entry
@@ -684,7 +786,7 @@ mod tests {
                 "https://example.com".to_string(),
                 CachedDiscovery {
                     document: create_test_document("https://example.com"),
-                    fetched_at: Instant::now() - Duration::from_secs(1), // Already expired
+                    expires_at: Instant::now() - Duration::from_secs(1), // Already expired
                 },
             );
         }
@@ -698,6 +800,36 @@ mod tests {
         assert!(cache.is_empty().await);
     }
 
+    #[tokio::test]
+    async fn test_serves_stale_entry_while_refreshing() {
+        let config = DiscoveryCacheConfig::default()
+            .with_allow_http(true)
+            .with_retry(RetryPolicy::none())
+            .with_max_stale(Duration::from_secs(60));
+        let cache = DiscoveryCache::new(config);
+
+        // Nothing listens here, so the background refresh fails
+        let issuer = Url::parse("http://127.0.0.1:9").unwrap();
+        {
+            let mut c = cache.cache.write().await;
+            c.insert(
+                normalize_issuer_key(&issuer),
+                CachedDiscovery {
+                    document: create_test_document("http://127.0.0.1:9"),
+                    expires_at: Instant::now() - Duration::from_secs(1),
+                },
+            );
+        }
+
+        let document = cache.get(&issuer).await.unwrap();
+        assert_eq!(document.issuer, "http://127.0.0.1:9");
+
+        let stats = cache.stats().await;
+        assert_eq!(stats.size, 1);
+        assert_eq!(stats.stale_hits, 1);
+        assert_eq!(stats.hits + stats.misses, 0);
+    }
+
     #[test]
     fn test_discovery_error_display() {
         let err = DiscoveryError::NetworkError("connection refused".to_string());
@@ -0,0 +1,647 @@
+//! Validation of access tokens issued by trusted external issuers.
+//!
+//! When the server acts as a resource server for a corporate IdP, incoming
+//! bearer tokens are not signed by our own key. This module validates them
+//! against a configured list of trusted issuers, each with its own JWKS URI
+//! and accepted audiences:
+//!
+//! 1. The (unverified) `iss` claim selects the trusted issuer
+//! 2. The signature is verified with a key from that issuer's JWKS
+//! 3. `iss`, `aud`, `exp` and `nbf` are checked, with clock skew leeway
+//!
+//! Each failure mode has its own [`ExternalTokenError`] variant so callers
+//! can report why a token was rejected.
+//!
+//! ```ignore
+//! use octofhir_auth::federation::{ExternalTokenValidator, ProviderJwksCache, TrustedIssuer};
+//!
+//! let validator = ExternalTokenValidator::new(Arc::new(ProviderJwksCache::with_defaults()))
+//!     .with_issuer(TrustedIssuer::new(
+//!         "https://idp.example.com",
+//!         Url::parse("https://idp.example.com/jwks")?,
+//!         vec!["https://fhir.example.com".to_string()],
+//!     ));
+//!
+//! if validator.trusts(token) {
+//!     let context = validator.authenticate(token).await?;
+//! }
+//! ```
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use jsonwebtoken::errors::ErrorKind;
+use jsonwebtoken::{Algorithm, Header, Validation, decode_header};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use super::auth::deserialize_audience;
+use super::jwks::{JwksError, ProviderJwksCache};
+use crate::config::FederationConfig;
+use crate::error::AuthError;
+use crate::middleware::AuthContext;
+use crate::token::jwt::AccessTokenClaims;
+use crate::types::Client;
+use crate::types::refresh_token::RefreshToken;
+
+/// Default clock skew tolerance for `exp`/`nbf` checks.
+const DEFAULT_LEEWAY: Duration = Duration::from_secs(60);
+
+/// An external issuer whose access tokens are accepted.
+#[derive(Debug, Clone)]
+pub struct TrustedIssuer {
+    /// Expected `iss` claim value.
+    pub issuer: String,
+    /// JWKS endpoint with the issuer's signing keys.
+    pub jwks_uri: Url,
+    /// Accepted `aud` values; a token must contain at least one.
+    pub audiences: Vec<String>,
+}
+
+impl TrustedIssuer {
+    /// Creates a trusted issuer.
+    #[must_use]
+    pub fn new(issuer: impl Into<String>, jwks_uri: Url, audiences: Vec<String>) -> Self {
+        Self {
+            issuer: issuer.into(),
+            jwks_uri,
+            audiences,
+        }
+    }
+}
+
+/// Reasons an external token is rejected.
+#[derive(Debug, thiserror::Error)]
+pub enum ExternalTokenError {
+    /// The token is not a well-formed JWT.
+    #[error("Malformed token: {0}")]
+    Malformed(String),
+
+    /// The `iss` claim names an issuer that is not trusted.
+    #[error("Untrusted issuer: {0}")]
+    UntrustedIssuer(String),
+
+    /// The token uses a symmetric or unexpected signing algorithm.
+    #[error("Unsupported signing algorithm: {0}")]
+    UnsupportedAlgorithm(String),
+
+    /// No key in the issuer's JWKS matches the token.
+    #[error("No matching signing key: {0}")]
+    NoMatchingKey(String),
+
+    /// The issuer's JWKS could not be fetched or used.
+    #[error("JWKS error: {0}")]
+    Jwks(JwksError),
+
+    /// The signature does not verify with the issuer's keys.
+    #[error("Invalid token signature")]
+    InvalidSignature,
+
+    /// The `aud` claim contains none of the accepted audiences.
+    #[error("Token audience does not include any of: {}", .0.join(", "))]
+    InvalidAudience(Vec<String>),
+
+    /// The token's `exp` has passed.
+    #[error("Token has expired")]
+    Expired,
+
+    /// The token's `nbf` is in the future.
+    #[error("Token is not yet valid")]
+    NotYetValid,
+
+    /// A required claim is missing.
+    #[error("Missing required claim: {0}")]
+    MissingClaim(String),
+}
+
+impl From<JwksError> for ExternalTokenError {
+    fn from(e: JwksError) -> Self {
+        match e {
+            JwksError::KeyNotFound(kid) => Self::NoMatchingKey(kid),
+            JwksError::NoSigningKeys => Self::NoMatchingKey("no signing keys".to_string()),
+            other => Self::Jwks(other),
+        }
+    }
+}
+
+impl From<ExternalTokenError> for AuthError {
+    fn from(e: ExternalTokenError) -> Self {
+        match e {
+            ExternalTokenError::Expired => AuthError::TokenExpired,
+            ExternalTokenError::Jwks(e) => {
+                AuthError::internal(format!("Failed to load issuer JWKS: {e}"))
+            }
+            other => AuthError::invalid_token(other.to_string()),
+        }
+    }
+}
+
+/// Claims of an externally issued access token.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExternalTokenClaims {
+    /// Issuer.
+    pub iss: String,
+
+    /// Subject.
+    #[serde(default)]
+    pub sub: String,
+
+    /// Audience (string or array).
+    #[serde(default, deserialize_with = "deserialize_audience")]
+    pub aud: Vec<String>,
+
+    /// Expiration time (Unix timestamp).
+    pub exp: i64,
+
+    /// Not-before time (Unix timestamp).
+    #[serde(default)]
+    pub nbf: Option<i64>,
+
+    /// Issued at time (Unix timestamp).
+    #[serde(default)]
+    pub iat: Option<i64>,
+
+    /// Token ID.
+    #[serde(default)]
+    pub jti: Option<String>,
+
+    /// Space-separated scopes (RFC 8693 / RFC 9068).
+    #[serde(default)]
+    pub scope: Option<String>,
+
+    /// Scopes as used by some IdPs (string or array).
+    #[serde(default)]
+    pub scp: Option<serde_json::Value>,
+
+    /// OAuth client the token was issued to (RFC 9068).
+    #[serde(default)]
+    pub client_id: Option<String>,
+
+    /// Authorized party (OIDC).
+    #[serde(default)]
+    pub azp: Option<String>,
+
+    /// SMART `fhirUser` claim.
+    #[serde(default, rename = "fhirUser")]
+    pub fhir_user: Option<String>,
+
+    /// Remaining claims.
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+impl ExternalTokenClaims {
+    /// Granted scopes, space-separated, from `scope` or `scp`.
+    #[must_use]
+    pub fn scopes(&self) -> String {
+        if let Some(scope) = &self.scope {
+            return scope.clone();
+        }
+        match &self.scp {
+            Some(serde_json::Value::String(s)) => s.clone(),
+            Some(serde_json::Value::Array(items)) => items
+                .iter()
+                .filter_map(|v| v.as_str())
+                .collect::<Vec<_>>()
+                .join(" "),
+            _ => String::new(),
+        }
+    }
+
+    /// Client the token was issued to: `client_id`, then `azp`, then `iss`.
+    #[must_use]
+    pub fn client_id(&self) -> &str {
+        self.client_id
+            .as_deref()
+            .or(self.azp.as_deref())
+            .unwrap_or(&self.iss)
+    }
+}
+
+/// Validates access tokens from trusted external issuers.
+pub struct ExternalTokenValidator {
+    /// Trusted issuers keyed by `iss`.
+    issuers: HashMap<String, TrustedIssuer>,
+    /// JWKS cache shared with the rest of the federation module.
+    jwks_cache: Arc<ProviderJwksCache>,
+    /// Clock skew tolerance for `exp`/`nbf`.
+    leeway: Duration,
+    /// Refetch the JWKS once when a signature does not verify with any of
+    /// the cached keys.
+    refresh_on_failure: bool,
+}
+
+impl ExternalTokenValidator {
+    /// Creates a validator with no trusted issuers.
+    #[must_use]
+    pub fn new(jwks_cache: Arc<ProviderJwksCache>) -> Self {
+        Self {
+            issuers: HashMap::new(),
+            jwks_cache,
+            leeway: DEFAULT_LEEWAY,
+            refresh_on_failure: true,
+        }
+    }
+
+    /// Creates a validator from the federation configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a trusted issuer's `jwks_uri` is not a valid URL.
+    pub fn from_config(
+        config: &FederationConfig,
+        jwks_cache: Arc<ProviderJwksCache>,
+    ) -> Result<Self, url::ParseError> {
+        let mut validator = Self::new(jwks_cache)
+            .with_leeway(config.clock_skew)
+            .with_refresh_on_failure(config.jwks_refresh_on_failure);
+        for issuer in &config.trusted_issuers {
+            validator = validator.with_issuer(TrustedIssuer::new(
+                issuer.issuer.clone(),
+                Url::parse(&issuer.jwks_uri)?,
+                issuer.audiences.clone(),
+            ));
+        }
+        Ok(validator)
+    }
+
+    /// Adds a trusted issuer (builder pattern).
+    #[must_use]
+    pub fn with_issuer(mut self, issuer: TrustedIssuer) -> Self {
+        self.issuers.insert(issuer.issuer.clone(), issuer);
+        self
+    }
+
+    /// Sets the clock skew tolerance.
+    #[must_use]
+    pub fn with_leeway(mut self, leeway: Duration) -> Self {
+        self.leeway = leeway;
+        self
+    }
+
+    /// Sets whether to refetch the JWKS once when a signature does not verify
+    /// with any of the cached keys.
+    #[must_use]
+    pub fn with_refresh_on_failure(mut self, refresh: bool) -> Self {
+        self.refresh_on_failure = refresh;
+        self
+    }
+
+    /// Returns `true` if no issuers are trusted.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.issuers.is_empty()
+    }
+
+    /// Returns `true` if the token claims to come from a trusted issuer.
+    ///
+    /// This only reads the unverified `iss` claim to route the token; it
+    /// says nothing about validity.
+    #[must_use]
+    pub fn trusts(&self, token: &str) -> bool {
+        peek_issuer(token).is_some_and(|iss| self.issuers.contains_key(&iss))
+    }
+
+    /// Validates a token and returns its claims.
+    ///
+    /// # Errors
+    ///
+    /// Returns the [`ExternalTokenError`] describing the first check that
+    /// failed.
+    pub async fn validate(&self, token: &str) -> Result<ExternalTokenClaims, ExternalTokenError> {
+        let iss = peek_issuer(token)
+            .ok_or_else(|| ExternalTokenError::Malformed("missing iss claim".to_string()))?;
+        let issuer = self
+            .issuers
+            .get(&iss)
+            .ok_or_else(|| ExternalTokenError::UntrustedIssuer(iss.clone()))?;
+
+        let header =
+            decode_header(token).map_err(|e| ExternalTokenError::Malformed(e.to_string()))?;
+        if !is_asymmetric(header.alg) {
+            return Err(ExternalTokenError::UnsupportedAlgorithm(format!(
+                "{:?}",
+                header.alg
+            )));
+        }
+
+        match self.verify(issuer, token, &header).await {
+            Err(ExternalTokenError::InvalidSignature)
+                if self.refresh_on_failure && !self.kid_cached(issuer, &header).await =>
+            {
+                // The issuer may have added a key the token does not name;
+                // a key we already hold will not verify any better refetched.
+                // The cache limits these refetches to one per cooldown.
+                tracing::debug!(issuer = %iss, "Signature check failed, refreshing JWKS");
+                if self.jwks_cache.refresh_if_due(&issuer.jwks_uri).await? {
+                    self.verify(issuer, token, &header).await
+                } else {
+                    Err(ExternalTokenError::InvalidSignature)
+                }
+            }
+            result => result,
+        }
+    }
+
+    /// Validates a token and builds an [`AuthContext`] for it.
+    ///
+    /// The context carries a synthetic, active client named after the token's
+    /// `client_id`/`azp` and no local user; `fhirUser` and scopes come from
+    /// the token.
+    ///
+    /// # Errors
+    ///
+    /// Returns `TokenExpired` for expired tokens and `InvalidToken` for other
+    /// validation failures.
+    pub async fn authenticate(&self, token: &str) -> Result<AuthContext, AuthError> {
+        let claims = self.validate(token).await.inspect_err(|e| {
+            tracing::debug!(error = %e, "External token rejected");
+        })?;
+
+        let client = Client {
+            client_id: claims.client_id().to_string(),
+            client_secret: None,
+            name: format!("External client ({})", claims.iss),
+            description: None,
+            grant_types: Vec::new(),
+            redirect_uris: Vec::new(),
+            post_logout_redirect_uris: Vec::new(),
+            scopes: Vec::new(),
+            confidential: false,
+            active: true,
+            access_token_lifetime: None,
+            refresh_token_lifetime: None,
+            pkce_required: None,
+            allowed_origins: Vec::new(),
+            jwks: None,
+            jwks_uri: None,
+        };
+
+        let token_claims = AccessTokenClaims {
+            iss: claims.iss.clone(),
+            sub: claims.sub.clone(),
+            aud: claims.aud.clone(),
+            exp: claims.exp,
+            iat: claims.iat.unwrap_or_default(),
+            jti: claims
+                .jti
+                .clone()
+                .unwrap_or_else(|| RefreshToken::hash_token(token)),
+            scope: claims.scopes(),
+            client_id: client.client_id.clone(),
+            patient: None,
+            encounter: None,
+            fhir_user: claims.fhir_user.clone(),
+            sid: None,
+        };
+
+        Ok(AuthContext {
+            patient: None,
+            encounter: None,
+            token_claims: Arc::new(token_claims),
+            client,
+            user: None,
+        })
+    }
+
+    /// Returns `true` if the token names a key that is in the issuer's
+    /// cached key set.
+    async fn kid_cached(&self, issuer: &TrustedIssuer, header: &Header) -> bool {
+        match &header.kid {
+            Some(kid) => self.jwks_cache.contains_key(&issuer.jwks_uri, kid).await,
+            None => false,
+        }
+    }
+
+    /// Verifies the signature and registered claims against one issuer.
+    async fn verify(
+        &self,
+        issuer: &TrustedIssuer,
+        token: &str,
+        header: &Header,
+    ) -> Result<ExternalTokenClaims, ExternalTokenError> {
+        let keys = match &header.kid {
+            Some(kid) => vec![self.jwks_cache.get_key(&issuer.jwks_uri, kid).await?],
+            None => self.jwks_cache.find_signing_keys(&issuer.jwks_uri).await?,
+        };
+
+        let mut last_error = ExternalTokenError::NoMatchingKey(format!("{:?}", header.alg));
+        for (key, key_alg) in keys {
+            if key_alg.is_some_and(|alg| alg != header.alg) {
+                continue;
+            }
+            let mut validation = Validation::new(header.alg);
+            validation.set_issuer(&[&issuer.issuer]);
+            validation.set_audience(&issuer.audiences);
+            validation.set_required_spec_claims(&["exp", "iss", "aud"]);
+            validation.validate_nbf = true;
+            validation.leeway = self.leeway.as_secs();
+
+            match jsonwebtoken::decode::<ExternalTokenClaims>(token, &key, &validation) {
+                Ok(data) => return Ok(data.claims),
+                Err(e) => {
+                    let error = map_jwt_error(e, issuer);
+                    // Only a bad signature is worth trying the next key for
+                    if !matches!(error, ExternalTokenError::InvalidSignature) {
+                        return Err(error);
+                    }
+                    last_error = error;
+                }
+            }
+        }
+        Err(last_error)
+    }
+}
+
+/// Reads the `iss` claim without verifying the token.
+fn peek_issuer(token: &str) -> Option<String> {
+    #[derive(Deserialize)]
+    struct Issuer {
+        iss: Option<String>,
+    }
+
+    let payload = token.split('.').nth(1)?;
+    let bytes = URL_SAFE_NO_PAD.decode(payload).ok()?;
+    serde_json::from_slice::<Issuer>(&bytes).ok()?.iss
+}
+
+fn is_asymmetric(alg: Algorithm) -> bool {
+    !matches!(alg, Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512)
+}
+
+fn map_jwt_error(e: jsonwebtoken::errors::Error, issuer: &TrustedIssuer) -> ExternalTokenError {
+    match e.kind() {
+        ErrorKind::InvalidSignature => ExternalTokenError::InvalidSignature,
+        ErrorKind::ExpiredSignature => ExternalTokenError::Expired,
+        ErrorKind::ImmatureSignature => ExternalTokenError::NotYetValid,
+        ErrorKind::InvalidAudience => ExternalTokenError::InvalidAudience(issuer.audiences.clone()),
+        ErrorKind::InvalidIssuer => ExternalTokenError::UntrustedIssuer(issuer.issuer.clone()),
+        ErrorKind::MissingRequiredClaim(claim) => ExternalTokenError::MissingClaim(claim.clone()),
+        ErrorKind::InvalidAlgorithm => {
+            ExternalTokenError::UnsupportedAlgorithm("algorithm does not match key".to_string())
+        }
+        _ => ExternalTokenError::Malformed(e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::federation::ProviderJwksCacheConfig;
+    use crate::token::jwt::{JwtService, SigningAlgorithm, SigningKeyPair};
+    use jsonwebtoken::jwk::JwkSet;
+    use serde_json::json;
+
+    const ISSUER: &str = "https://idp.example.com";
+    const AUDIENCE: &str = "https://fhir.example.com";
+
+    async fn setup() -> (ExternalTokenValidator, JwtService) {
+        let key = SigningKeyPair::generate_rsa(SigningAlgorithm::RS256).unwrap();
+        let jwk = serde_json::to_value(key.to_jwk()).unwrap();
+        let jwks: JwkSet = serde_json::from_value(json!({ "keys": [jwk] })).unwrap();
+
+        let jwks_uri = Url::parse("https://idp.example.com/jwks").unwrap();
+        let cache = Arc::new(ProviderJwksCache::new(ProviderJwksCacheConfig::default()));
+        cache
+            .insert(&jwks_uri, jwks, Duration::from_secs(3600))
+            .await;
+
+        let validator = ExternalTokenValidator::new(cache)
+            .with_refresh_on_failure(false)
+            .with_issuer(TrustedIssuer::new(
+                ISSUER,
+                jwks_uri,
+                vec![AUDIENCE.to_string()],
+            ));
+        (validator, JwtService::new(key, ISSUER))
+    }
+
+    fn claims(overrides: serde_json::Value) -> serde_json::Value {
+        let now = time::OffsetDateTime::now_utc().unix_timestamp();
+        let mut claims = json!({
+            "iss": ISSUER,
+            "sub": "user-1",
+            "aud": AUDIENCE,
+            "exp": now + 300,
+            "iat": now,
+            "scp": ["patient/*.rs", "openid"],
+            "azp": "corp-app",
+        });
+        for (k, v) in overrides.as_object().unwrap() {
+            claims[k] = v.clone();
+        }
+        claims
+    }
+
+    #[tokio::test]
+    async fn test_valid_token_builds_context() {
+        let (validator, signer) = setup().await;
+        let token = signer.encode(&claims(json!({}))).unwrap();
+
+        assert!(validator.trusts(&token));
+        let context = validator.authenticate(&token).await.unwrap();
+        assert_eq!(context.client.client_id, "corp-app");
+        assert_eq!(context.token_claims.sub, "user-1");
+        assert_eq!(context.token_claims.scope, "patient/*.rs openid");
+        assert!(context.user.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_each_failure_mode_is_reported() {
+        let (validator, signer) = setup().await;
+        let now = time::OffsetDateTime::now_utc().unix_timestamp();
+
+        let token = signer
+            .encode(&claims(json!({ "aud": "https://other.example.com" })))
+            .unwrap();
+        assert!(matches!(
+            validator.validate(&token).await,
+            Err(ExternalTokenError::InvalidAudience(_))
+        ));
+
+        let token = signer.encode(&claims(json!({ "exp": now - 600 }))).unwrap();
+        assert!(matches!(
+            validator.validate(&token).await,
+            Err(ExternalTokenError::Expired)
+        ));
+
+        let token = signer.encode(&claims(json!({ "nbf": now + 600 }))).unwrap();
+        assert!(matches!(
+            validator.validate(&token).await,
+            Err(ExternalTokenError::NotYetValid)
+        ));
+
+        let token = signer
+            .encode(&claims(json!({ "iss": "https://evil.example.com" })))
+            .unwrap();
+        assert!(!validator.trusts(&token));
+        assert!(matches!(
+            validator.validate(&token).await,
+            Err(ExternalTokenError::UntrustedIssuer(_))
+        ));
+
+        // Signed by a key that is not in the issuer's JWKS, same kid
+        let (_, other_signer) = setup().await;
+        let mut forged = other_signer.encode(&claims(json!({}))).unwrap();
+        let good = signer.encode(&claims(json!({}))).unwrap();
+        let good_header = good.split('.').next().unwrap();
+        let rest = forged.split_once('.').unwrap().1.to_string();
+        forged = format!("{good_header}.{rest}");
+        assert!(matches!(
+            validator.validate(&forged).await,
+            Err(ExternalTokenError::InvalidSignature)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_bad_signature_with_cached_kid_does_not_refetch() {
+        let key = SigningKeyPair::generate_rsa(SigningAlgorithm::RS256).unwrap();
+        let jwk = serde_json::to_value(key.to_jwk()).unwrap();
+        let jwks: JwkSet = serde_json::from_value(json!({ "keys": [jwk] })).unwrap();
+        let jwks_uri = Url::parse("https://idp.example.com/jwks").unwrap();
+        let cache = Arc::new(ProviderJwksCache::with_defaults());
+        cache
+            .insert(&jwks_uri, jwks, Duration::from_secs(3600))
+            .await;
+        let validator = ExternalTokenValidator::new(cache.clone()).with_issuer(TrustedIssuer::new(
+            ISSUER,
+            jwks_uri,
+            vec![AUDIENCE.to_string()],
+        ));
+        let signer = JwtService::new(key, ISSUER);
+
+        // Same kid as the cached key, signed by another key
+        let (_, other_signer) = setup().await;
+        let good = signer.encode(&claims(json!({}))).unwrap();
+        let forged = other_signer.encode(&claims(json!({}))).unwrap();
+        let forged = format!(
+            "{}.{}",
+            good.split('.').next().unwrap(),
+            forged.split_once('.').unwrap().1
+        );
+
+        for _ in 0..3 {
+            assert!(matches!(
+                validator.validate(&forged).await,
+                Err(ExternalTokenError::InvalidSignature)
+            ));
+        }
+        let stats = cache.stats().await;
+        assert_eq!(stats.refreshes + stats.refresh_failures, 0);
+    }
+
+    #[test]
+    fn test_expired_maps_to_token_expired() {
+        assert!(matches!(
+            AuthError::from(ExternalTokenError::Expired),
+            AuthError::TokenExpired
+        ));
+        assert!(matches!(
+            AuthError::from(ExternalTokenError::InvalidSignature),
+            AuthError::InvalidToken { .. }
+        ));
+    }
+}
@@ -17,7 +17,9 @@
 //!
 //! The cache respects `Cache-Control: max-age=X` headers from providers,
 //! allowing dynamic TTL based on provider recommendations. The TTL is
-//! constrained by configurable minimum and maximum bounds.
+//! constrained by configurable minimum and maximum bounds, then shortened by
+//! a random jitter. Expired sets are served for up to `max_stale` while they
+//! are refreshed in the background (see [`remote_cache`](super::remote_cache)).
 //!
 //! # Example
 //!
@@ -41,6 +43,7 @@
 //! - HTTP timeouts prevent hanging on slow endpoints
 //! - Response size is limited to prevent DoS attacks
 //! - TTL is bounded to prevent cache poisoning via malicious Cache-Control
+//! - Refetches forced by unknown keys are limited to one per cooldown
 //!
 //! # Difference from ClientJwksCache
 //!
@@ -48,14 +51,19 @@
 //! - [`ClientJwksCache`] - For verifying client assertions in `private_key_jwt` auth
 
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
 use jsonwebtoken::jwk::{JwkSet, PublicKeyUse};
 use jsonwebtoken::{Algorithm, DecodingKey};
+use octofhir_core::http::{HttpClient, HttpClientConfig, RetryPolicy};
 use tokio::sync::RwLock;
 use url::Url;
 
+use super::remote_cache::{
+    BackgroundRefreshes, CacheCounters, Freshness, RemoteCacheStats, jittered,
+};
+
 /// Configuration for the provider JWKS cache.
 #[derive(Debug, Clone)]
 pub struct ProviderJwksCacheConfig {
@@ -68,9 +76,24 @@ pub struct ProviderJwksCacheConfig {
     /// Minimum TTL regardless of Cache-Control (default: 5 minutes).
     pub min_ttl: Duration,
 
+    /// Fraction of the TTL by which each entry's lifetime is randomly
+    /// shortened, spreading refetches across instances (default: 0.1).
+    pub ttl_jitter: f64,
+
+    /// How long an expired JWKS is still served while it is refreshed in
+    /// the background, or when refreshing fails (default: 15 minutes).
+    pub max_stale: Duration,
+
+    /// Minimum time between refetches of a key set forced by an unknown
+    /// key or a failed signature check (default: 1 minute).
+    pub refresh_cooldown: Duration,
+
     /// HTTP request timeout (default: 10 seconds).
     pub request_timeout: Duration,
 
+    /// Retries for transient fetch failures.
+    pub retry: RetryPolicy,
+
     /// Maximum response size in bytes (default: 1 MB).
     pub max_response_size: usize,
 
@@ -82,11 +105,15 @@ pub struct ProviderJwksCacheConfig {
 impl Default for ProviderJwksCacheConfig {
     fn default() -> Self {
         Self {
-            default_ttl: Duration::from_secs(3600),   // 1 hour
-            max_ttl: Duration::from_secs(86400),      // 24 hours
-            min_ttl: Duration::from_secs(300),        // 5 minutes
+            default_ttl: Duration::from_secs(3600), // 1 hour
+            max_ttl: Duration::from_secs(86400),    // 24 hours
+            min_ttl: Duration::from_secs(300),      // 5 minutes
+            ttl_jitter: 0.1,
+            max_stale: Duration::from_secs(900), // 15 minutes
+            refresh_cooldown: Duration::from_secs(60), // 1 minute
             request_timeout: Duration::from_secs(10), // 10 seconds
-            max_response_size: 1024 * 1024,           // 1 MB
+            retry: RetryPolicy::default(),
+            max_response_size: 1024 * 1024, // 1 MB
             allow_http: false,
         }
     }
@@ -120,6 +147,27 @@ impl ProviderJwksCacheConfig {
         self
     }
 
+    /// Sets the TTL jitter fraction (clamped to `0..=1`).
+    #[must_use]
+    pub fn with_ttl_jitter(mut self, jitter: f64) -> Self {
+        self.ttl_jitter = jitter;
+        self
+    }
+
+    /// Sets how long expired key sets may still be served.
+    #[must_use]
+    pub fn with_max_stale(mut self, max_stale: Duration) -> Self {
+        self.max_stale = max_stale;
+        self
+    }
+
+    /// Sets the minimum time between forced refetches of a key set.
+    #[must_use]
+    pub fn with_refresh_cooldown(mut self, cooldown: Duration) -> Self {
+        self.refresh_cooldown = cooldown;
+        self
+    }
+
     /// Sets the HTTP request timeout.
     #[must_use]
     pub fn with_request_timeout(mut self, timeout: Duration) -> Self {
@@ -127,6 +175,13 @@ impl ProviderJwksCacheConfig {
         self
     }
 
+    /// Sets the retry policy for fetches.
+    #[must_use]
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
     /// Sets the maximum response size.
     #[must_use]
     pub fn with_max_response_size(mut self, size: usize) -> Self {
@@ -194,6 +249,8 @@ struct CachedJwks {
     expires_at: Instant,
 }
 
+type JwksEntries = Arc<RwLock<HashMap<String, CachedJwks>>>;
+
 /// In-memory cache for provider JWKS.
 ///
 /// This cache stores JWKS fetched from external identity provider endpoints
@@ -202,15 +259,23 @@ struct CachedJwks {
 /// # Features
 ///
 /// - Automatic caching with TTL from Cache-Control headers
-/// - Configurable TTL bounds (min/max)
+/// - Configurable TTL bounds (min/max) and jitter
 /// - Key lookup by kid or all signing keys
-/// - Automatic refresh on cache miss
+/// - Automatic refresh on cache miss or unknown kid, at most once per
+///   `refresh_cooldown` per endpoint
+/// - Stale entries served while refreshed in the background
 /// - Manual invalidation and cleanup
 pub struct ProviderJwksCache {
     /// HTTP client for fetching JWKS.
-    http_client: reqwest::Client,
+    http_client: HttpClient,
     /// Cached JWKS by URI.
-    cache: Arc<RwLock<HashMap<String, CachedJwks>>>,
+    cache: JwksEntries,
+    /// Background refreshes of stale entries.
+    background: BackgroundRefreshes,
+    /// When each key set was last fetched in the foreground.
+    last_refresh: Mutex<HashMap<String, Instant>>,
+    /// Lookup and fetch counters.
+    counters: Arc<CacheCounters>,
     /// Configuration.
     config: ProviderJwksCacheConfig,
 }
@@ -223,14 +288,18 @@ impl ProviderJwksCache {
     /// Panics if the HTTP client cannot be created (should not happen in practice).
     #[must_use]
     pub fn new(config: ProviderJwksCacheConfig) -> Self {
-        let http_client = reqwest::Client::builder()
-            .timeout(config.request_timeout)
-            .build()
-            .expect("Failed to create HTTP client");
+        let http_client = HttpClient::new(
+            &HttpClientConfig::with_timeout(config.request_timeout)
+                .with_retry(config.retry.clone()),
+        )
+        .expect("Failed to create HTTP client");
 
         Self {
             http_client,
             cache: Arc::new(RwLock::new(HashMap::new())),
+            background: BackgroundRefreshes::default(),
+            last_refresh: Mutex::new(HashMap::new()),
+            counters: Arc::new(CacheCounters::default()),
             config,
         }
     }
@@ -243,8 +312,11 @@ impl ProviderJwksCache {
 
     /// Gets a decoding key by key ID from a JWKS endpoint.
     ///
-    /// This method checks the cache first. If the key is not found or the
-    /// cache has expired, it fetches a fresh JWKS from the endpoint.
+    /// This method checks the cache first. If the cache has expired, or the
+    /// key is not in it (the provider may have rotated its keys), it fetches
+    /// a fresh JWKS from the endpoint. Unknown keys trigger at most one fetch
+    /// per `refresh_cooldown`, so tokens with made-up key IDs cannot make
+    /// the server hammer the endpoint.
     ///
     /// # Arguments
     ///
@@ -267,14 +339,17 @@ impl ProviderJwksCache {
         kid: &str,
     ) -> Result<(DecodingKey, Option<Algorithm>), JwksError> {
         // Check cache first
+        let fetched = self.ensure_cached(jwks_uri).await?;
         if let Some(result) = self.get_cached_key(jwks_uri, kid).await {
             tracing::trace!("Cache hit for JWKS key: {} from {}", kid, jwks_uri);
             return Ok(result);
         }
 
-        // Fetch fresh JWKS
-        tracing::debug!("Cache miss for JWKS key: {} from {}", kid, jwks_uri);
-        self.refresh(jwks_uri).await?;
+        // Unknown kid: fetch fresh JWKS unless we just did
+        if !fetched {
+            tracing::debug!("Cache miss for JWKS key: {} from {}", kid, jwks_uri);
+            self.refresh_if_due(jwks_uri).await?;
+        }
 
         // Try cache again
         self.get_cached_key(jwks_uri, kid)
@@ -292,11 +367,6 @@ impl ProviderJwksCache {
         let key = normalize_uri(jwks_uri);
 
         cache.get(&key).and_then(|cached| {
-            // Check if expired
-            if Instant::now() >= cached.expires_at {
-                return None;
-            }
-
             // Find key by kid
             cached
                 .jwks
@@ -311,6 +381,18 @@ impl ProviderJwksCache {
         })
     }
 
+    /// Returns `true` if the cached key set for `jwks_uri` has a key `kid`.
+    pub async fn contains_key(&self, jwks_uri: &Url, kid: &str) -> bool {
+        let cache = self.cache.read().await;
+        cache.get(&normalize_uri(jwks_uri)).is_some_and(|cached| {
+            cached
+                .jwks
+                .keys
+                .iter()
+                .any(|k| k.common.key_id.as_deref() == Some(kid))
+        })
+    }
+
     /// Gets all signing keys from a JWKS endpoint.
     ///
     /// This is useful when the token doesn't have a `kid` header and you need
@@ -367,20 +449,60 @@ impl ProviderJwksCache {
         }
     }
 
-    /// Ensures the cache has a fresh entry for the given URI.
-    async fn ensure_cached(&self, jwks_uri: &Url) -> Result<(), JwksError> {
+    /// Ensures the cache has a usable entry for the given URI.
+    ///
+    /// A stale entry is kept and refreshed in the background. Returns
+    /// whether the JWKS was fetched by this call.
+    async fn ensure_cached(&self, jwks_uri: &Url) -> Result<bool, JwksError> {
         let key = normalize_uri(jwks_uri);
 
         {
+            let now = Instant::now();
             let cache = self.cache.read().await;
-            if let Some(cached) = cache.get(&key)
-                && Instant::now() < cached.expires_at
-            {
-                return Ok(());
+            if let Some(cached) = cache.get(&key) {
+                match Freshness::of(cached.expires_at, self.config.max_stale, now) {
+                    Freshness::Fresh => {
+                        self.counters.hit();
+                        return Ok(false);
+                    }
+                    Freshness::Stale => {
+                        self.counters.stale_hit();
+                        self.refresh_in_background(jwks_uri, &key, now);
+                        return Ok(false);
+                    }
+                    Freshness::Expired => {}
+                }
             }
         }
 
-        self.refresh(jwks_uri).await
+        self.counters.miss();
+        self.refresh(jwks_uri).await?;
+        Ok(true)
+    }
+
+    /// Starts refreshing a stale entry unless a refresh is already running
+    /// or backing off after a failure.
+    fn refresh_in_background(&self, jwks_uri: &Url, key: &str, now: Instant) {
+        let Some(claim) = self.background.try_start(key, now) else {
+            return;
+        };
+        let http_client = self.http_client.clone();
+        let cache = self.cache.clone();
+        let counters = self.counters.clone();
+        let config = self.config.clone();
+        let jwks_uri = jwks_uri.clone();
+        tokio::spawn(async move {
+            if let Err(e) =
+                fetch_and_store(&http_client, &cache, &counters, &config, &jwks_uri).await
+            {
+                tracing::warn!(
+                    jwks_uri = %jwks_uri,
+                    error = %e,
+                    "Background refresh of JWKS failed; serving stale keys"
+                );
+                claim.failed();
+            }
+        });
     }
 
     /// Fetches JWKS from the endpoint and updates the cache.
@@ -398,106 +520,70 @@ impl ProviderJwksCache {
     /// - The HTTP request fails
     /// - The response cannot be parsed as JWKS
     pub async fn refresh(&self, jwks_uri: &Url) -> Result<(), JwksError> {
-        // Validate scheme
-        self.validate_scheme(jwks_uri)?;
-
-        tracing::debug!("Fetching JWKS from {}", jwks_uri);
-
-        let response = self
-            .http_client
-            .get(jwks_uri.as_str())
-            .header("Accept", "application/json")
-            .send()
-            .await
-            .map_err(|e| {
-                tracing::warn!("Failed to fetch JWKS from {}: {}", jwks_uri, e);
-                JwksError::NetworkError(e.to_string())
-            })?;
-
-        // Check status
-        if !response.status().is_success() {
-            return Err(JwksError::HttpError(response.status().as_u16()));
-        }
+        self.lock_last_refresh()
+            .insert(normalize_uri(jwks_uri), Instant::now());
+        fetch_and_store(
+            &self.http_client,
+            &self.cache,
+            &self.counters,
+            &self.config,
+            jwks_uri,
+        )
+        .await
+    }
 
-        // Check content length
-        if let Some(len) = response.content_length()
-            && len as usize > self.config.max_response_size
+    /// Fetches JWKS from the endpoint unless it was fetched less than
+    /// `refresh_cooldown` ago.
+    ///
+    /// Returns whether a fetch was made. A failed fetch also starts the
+    /// cooldown.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`refresh`](Self::refresh).
+    pub async fn refresh_if_due(&self, jwks_uri: &Url) -> Result<bool, JwksError> {
+        let key = normalize_uri(jwks_uri);
         {
-            return Err(JwksError::ResponseTooLarge {
-                max_size: self.config.max_response_size,
-            });
+            let now = Instant::now();
+            let mut last_refresh = self.lock_last_refresh();
+            if let Some(at) = last_refresh.get(&key)
+                && now < *at + self.config.refresh_cooldown
+            {
+                tracing::debug!("Skipping JWKS refresh of {}: fetched recently", jwks_uri);
+                return Ok(false);
+            }
+            last_refresh.insert(key, now);
         }
-
-        // Parse Cache-Control for TTL
-        let ttl = self.parse_cache_control(response.headers());
-
-        // Parse JWKS
-        let jwks: JwkSet = response.json().await.map_err(|e| {
-            tracing::warn!("Failed to parse JWKS from {}: {}", jwks_uri, e);
-            JwksError::ParseError(e.to_string())
-        })?;
-
-        tracing::debug!(
-            "Cached JWKS from {} with {} keys, TTL {:?}",
+        fetch_and_store(
+            &self.http_client,
+            &self.cache,
+            &self.counters,
+            &self.config,
             jwks_uri,
-            jwks.keys.len(),
-            ttl
-        );
+        )
+        .await?;
+        Ok(true)
+    }
 
-        // Update cache
-        let now = Instant::now();
-        let key = normalize_uri(jwks_uri);
+    /// Locks the foreground fetch times, recovering from poisoning.
+    fn lock_last_refresh(&self) -> std::sync::MutexGuard<'_, HashMap<String, Instant>> {
+        self.last_refresh.lock().unwrap_or_else(|e| e.into_inner())
+    }
 
+    /// Seeds the cache with a JWKS for `jwks_uri`, valid for `ttl`.
+    ///
+    /// Useful for keys distributed out of band; once the entry expires the
+    /// JWKS is fetched from the endpoint as usual.
+    pub async fn insert(&self, jwks_uri: &Url, jwks: JwkSet, ttl: Duration) {
+        let key = normalize_uri(jwks_uri);
         let mut cache = self.cache.write().await;
         cache.insert(
             key,
             CachedJwks {
                 jwks,
-                expires_at: now + ttl,
+                expires_at: Instant::now() + ttl,
             },
         );
-
-        Ok(())
-    }
-
-    /// Validates that the URI uses an allowed scheme.
-    fn validate_scheme(&self, uri: &Url) -> Result<(), JwksError> {
-        let scheme = uri.scheme();
-
-        if scheme == "https" {
-            return Ok(());
-        }
-
-        if scheme == "http" && self.config.allow_http {
-            return Ok(());
-        }
-
-        Err(JwksError::InvalidScheme)
-    }
-
-    /// Parses Cache-Control header to determine TTL.
-    ///
-    /// Extracts `max-age` directive and clamps it between `min_ttl` and `max_ttl`.
-    /// Returns `default_ttl` if no Cache-Control header or max-age is present.
-    fn parse_cache_control(&self, headers: &reqwest::header::HeaderMap) -> Duration {
-        let ttl = headers
-            .get(reqwest::header::CACHE_CONTROL)
-            .and_then(|v| v.to_str().ok())
-            .and_then(|v| {
-                v.split(',').find_map(|directive| {
-                    let directive = directive.trim();
-                    if let Some(stripped) = directive.strip_prefix("max-age=") {
-                        stripped.parse::<u64>().ok()
-                    } else {
-                        None
-                    }
-                })
-            })
-            .map(Duration::from_secs)
-            .unwrap_or(self.config.default_ttl);
-
-        // Clamp to configured bounds
-        ttl.min(self.config.max_ttl).max(self.config.min_ttl)
     }
 
     /// Invalidates a cached JWKS entry.
@@ -511,15 +597,16 @@ impl ProviderJwksCache {
         tracing::debug!("Invalidated JWKS cache for {}", jwks_uri);
     }
 
-    /// Clears all expired entries from the cache.
+    /// Clears all entries too old to be served, even stale, from the cache.
     ///
     /// This is useful for periodic cleanup to free memory.
     pub async fn cleanup(&self) {
         let mut cache = self.cache.write().await;
         let now = Instant::now();
+        let max_stale = self.config.max_stale;
         let before_count = cache.len();
 
-        cache.retain(|_, v| v.expires_at > now);
+        cache.retain(|_, v| Freshness::of(v.expires_at, max_stale, now) != Freshness::Expired);
 
         let removed = before_count - cache.len();
         if removed > 0 {
@@ -543,6 +630,136 @@ impl ProviderJwksCache {
     pub async fn is_empty(&self) -> bool {
         self.cache.read().await.is_empty()
     }
+
+    /// Returns lookup and fetch statistics.
+    pub async fn stats(&self) -> RemoteCacheStats {
+        self.counters.snapshot(self.len().await)
+    }
+}
+
+/// Fetches the JWKS at `jwks_uri` and caches it with a jittered TTL.
+async fn fetch_and_store(
+    http_client: &HttpClient,
+    cache: &JwksEntries,
+    counters: &CacheCounters,
+    config: &ProviderJwksCacheConfig,
+    jwks_uri: &Url,
+) -> Result<(), JwksError> {
+    let result = fetch(http_client, cache, config, jwks_uri).await;
+    counters.fetched(&result);
+    result
+}
+
+/// Fetches and caches the JWKS, without counting the outcome.
+async fn fetch(
+    http_client: &HttpClient,
+    cache: &JwksEntries,
+    config: &ProviderJwksCacheConfig,
+    jwks_uri: &Url,
+) -> Result<(), JwksError> {
+    // Validate scheme
+    validate_scheme(config, jwks_uri)?;
+
+    tracing::debug!("Fetching JWKS from {}", jwks_uri);
+
+    let request = http_client
+        .get(jwks_uri.as_str())
+        .header("Accept", "application/json");
+    let response = http_client.send(request).await.map_err(|e| {
+        tracing::warn!("Failed to fetch JWKS from {}: {}", jwks_uri, e);
+        JwksError::NetworkError(e.to_string())
+    })?;
+
+    // Check status
+    if !response.status().is_success() {
+        return Err(JwksError::HttpError(response.status().as_u16()));
+    }
+
+    // Check content length
+    if let Some(len) = response.content_length()
+        && len as usize > config.max_response_size
+    {
+        return Err(JwksError::ResponseTooLarge {
+            max_size: config.max_response_size,
+        });
+    }
+
+    // Parse Cache-Control for TTL
+    let ttl = jittered(
+        parse_cache_control(config, response.headers()),
+        config.ttl_jitter,
+    );
+
+    // Parse JWKS
+    let jwks: JwkSet = response.json().await.map_err(|e| {
+        tracing::warn!("Failed to parse JWKS from {}: {}", jwks_uri, e);
+        JwksError::ParseError(e.to_string())
+    })?;
+
+    tracing::debug!(
+        "Cached JWKS from {} with {} keys, TTL {:?}",
+        jwks_uri,
+        jwks.keys.len(),
+        ttl
+    );
+
+    // Update cache
+    let now = Instant::now();
+    let key = normalize_uri(jwks_uri);
+
+    let mut cache = cache.write().await;
+    cache.insert(
+        key,
+        CachedJwks {
+            jwks,
+            expires_at: now + ttl,
+        },
+    );
+
+    Ok(())
+}
+
+/// Validates that the URI uses an allowed scheme.
+fn validate_scheme(config: &ProviderJwksCacheConfig, uri: &Url) -> Result<(), JwksError> {
+    let scheme = uri.scheme();
+
+    if scheme == "https" {
+        return Ok(());
+    }
+
+    if scheme == "http" && config.allow_http {
+        return Ok(());
+    }
+
+    Err(JwksError::InvalidScheme)
+}
+
+/// Parses Cache-Control header to determine TTL.
+///
+/// Extracts `max-age` directive and clamps it between `min_ttl` and `max_ttl`.
+/// Returns `default_ttl` if no Cache-Control header or max-age is present.
+fn parse_cache_control(
+    config: &ProviderJwksCacheConfig,
+    headers: &reqwest::header::HeaderMap,
+) -> Duration {
+    let ttl = headers
+        .get(reqwest::header::CACHE_CONTROL)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| {
+            v.split(',').find_map(|directive| {
+                let directive = directive.trim();
+                if let Some(stripped) = directive.strip_prefix("max-age=") {
+                    stripped.parse::<u64>().ok()
+                } else {
+                    None
+                }
+            })
+        })
+        .map(Duration::from_secs)
+        .unwrap_or(config.default_ttl);
+
+    // Clamp to configured bounds
+    ttl.min(config.max_ttl).max(config.min_ttl)
 }
 
 /// Normalizes a URI for use as a cache key.
@@ -576,6 +793,9 @@ mod tests {
         assert_eq!(config.default_ttl, Duration::from_secs(3600));
         assert_eq!(config.max_ttl, Duration::from_secs(86400));
         assert_eq!(config.min_ttl, Duration::from_secs(300));
+        assert_eq!(config.ttl_jitter, 0.1);
+        assert_eq!(config.max_stale, Duration::from_secs(900));
+        assert_eq!(config.refresh_cooldown, Duration::from_secs(60));
         assert_eq!(config.request_timeout, Duration::from_secs(10));
         assert_eq!(config.max_response_size, 1024 * 1024);
         assert!(!config.allow_http);
@@ -605,15 +825,15 @@ mod tests {
         let cache = ProviderJwksCache::new(config);
 
         let https = Url::parse("https://example.com/jwks").unwrap();
-        assert!(cache.validate_scheme(&https).is_ok());
+        assert!(validate_scheme(&cache.config, &https).is_ok());
 
         let http = Url::parse("http://example.com/jwks").unwrap();
-        assert!(cache.validate_scheme(&http).is_err());
+        assert!(validate_scheme(&cache.config, &http).is_err());
 
         // With allow_http
         let config = ProviderJwksCacheConfig::default().with_allow_http(true);
         let cache = ProviderJwksCache::new(config);
-        assert!(cache.validate_scheme(&http).is_ok());
+        assert!(validate_scheme(&cache.config, &http).is_ok());
     }
 
     #[test]
@@ -627,7 +847,7 @@ mod tests {
         // No header - use default
         let headers = reqwest::header::HeaderMap::new();
         assert_eq!(
-            cache.parse_cache_control(&headers),
+            parse_cache_control(&cache.config, &headers),
             Duration::from_secs(3600)
         );
 
@@ -638,7 +858,7 @@ mod tests {
             "public, max-age=1800".parse().unwrap(),
         );
         assert_eq!(
-            cache.parse_cache_control(&headers),
+            parse_cache_control(&cache.config, &headers),
             Duration::from_secs(1800)
         );
 
@@ -648,7 +868,10 @@ mod tests {
             reqwest::header::CACHE_CONTROL,
             "max-age=30".parse().unwrap(),
         );
-        assert_eq!(cache.parse_cache_control(&headers), Duration::from_secs(60));
+        assert_eq!(
+            parse_cache_control(&cache.config, &headers),
+            Duration::from_secs(60)
+        );
 
         // max-age above max - clamped to max
         let mut headers = reqwest::header::HeaderMap::new();
@@ -657,7 +880,7 @@ mod tests {
             "max-age=100000".parse().unwrap(),
         );
         assert_eq!(
-            cache.parse_cache_control(&headers),
+            parse_cache_control(&cache.config, &headers),
             Duration::from_secs(7200)
         );
 
@@ -668,7 +891,7 @@ mod tests {
             "max-age=invalid".parse().unwrap(),
         );
         assert_eq!(
-            cache.parse_cache_control(&headers),
+            parse_cache_control(&cache.config, &headers),
             Duration::from_secs(3600)
         );
     }
@@ -681,6 +904,47 @@ mod tests {
         assert_eq!(normalize_uri(&uri1), "https://example.com/jwks");
     }
 
+    #[tokio::test]
+    async fn test_refresh_if_due_respects_cooldown() {
+        // HTTP is rejected before any request is sent, so nothing is fetched
+        let cache = ProviderJwksCache::new(
+            ProviderJwksCacheConfig::default().with_refresh_cooldown(Duration::from_secs(60)),
+        );
+        let uri = Url::parse("http://example.com/jwks").unwrap();
+
+        assert!(matches!(
+            cache.refresh_if_due(&uri).await,
+            Err(JwksError::InvalidScheme)
+        ));
+        // The failed attempt starts the cooldown
+        assert!(!cache.refresh_if_due(&uri).await.unwrap());
+
+        let other = Url::parse("http://example.org/jwks").unwrap();
+        assert!(cache.refresh_if_due(&other).await.is_err());
+
+        let uncooled = ProviderJwksCache::new(
+            ProviderJwksCacheConfig::default().with_refresh_cooldown(Duration::ZERO),
+        );
+        assert!(uncooled.refresh_if_due(&uri).await.is_err());
+        assert!(uncooled.refresh_if_due(&uri).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_contains_key() {
+        let cache = ProviderJwksCache::with_defaults();
+        let uri = Url::parse("https://example.com/jwks").unwrap();
+        assert!(!cache.contains_key(&uri, "key-1").await);
+
+        let jwks: JwkSet = serde_json::from_value(serde_json::json!({
+            "keys": [{ "kty": "oct", "kid": "key-1", "k": "c2VjcmV0" }]
+        }))
+        .unwrap();
+        cache.insert(&uri, jwks, Duration::from_secs(60)).await;
+
+        assert!(cache.contains_key(&uri, "key-1").await);
+        assert!(!cache.contains_key(&uri, "key-2").await);
+    }
+
     #[tokio::test]
     async fn test_cache_invalidate() {
         let config = ProviderJwksCacheConfig::default().with_allow_http(true);
@@ -8,6 +8,7 @@
 //! - User identity mapping and linking
 //! - User provisioning from IdP authentication
 //! - JWK set fetching and caching
+//! - Validation of access tokens from trusted external issuers
 //!
 //! # OIDC Discovery
 //!
@@ -73,6 +74,12 @@
 //! // Redirect user to auth_request.authorization_url...
 //! ```
 //!
+//! # External Access Tokens
+//!
+//! The [`ExternalTokenValidator`] accepts access tokens issued directly by
+//! trusted external issuers (e.g. a corporate IdP), checking the signature
+//! against each issuer's JWKS along with `iss`, `aud`, `exp` and `nbf`.
+//!
 //! # Client JWKS
 //!
 //! The [`ClientJwksCache`] provides caching for client JWKS used in
@@ -121,11 +128,13 @@ pub mod auth;
 pub mod client_jwks;
 pub mod discovery;
 pub mod error;
+pub mod external_token;
 pub mod identity;
 pub mod jwks;
 pub mod oidc;
 pub mod provider;
 pub mod provisioning;
+pub mod remote_cache;
 pub mod resources;
 
 pub use auth::{
@@ -135,6 +144,9 @@ pub use auth::{
 pub use client_jwks::{ClientJwksCache, JwksCacheConfig};
 pub use discovery::{DiscoveryCache, DiscoveryCacheConfig, DiscoveryError, OidcDiscoveryClient};
 pub use error::IdpError;
+pub use external_token::{
+    ExternalTokenClaims, ExternalTokenError, ExternalTokenValidator, TrustedIssuer,
+};
 pub use identity::{
     IDENTITIES_KEY, UserIdentity, add_identity, find_identity, find_identity_by_provider,
     get_identities, has_identity_for_provider, remove_identity, set_identities,
@@ -147,6 +159,7 @@ pub use provisioning::{
     create_fhir_resource_json, create_identity_from_auth_result, create_user_from_auth_result,
     determine_username, has_provider_identity,
 };
+pub use remote_cache::RemoteCacheStats;
 pub use resources::{
     IdentityProviderResource, IdentityProviderType, Reference, UserIdentityElement,
     UserMappingElement, UserResource, UserValidationError,
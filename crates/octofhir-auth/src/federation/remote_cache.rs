@@ -0,0 +1,213 @@
+//! Expiry, background refresh and statistics shared by the caches of remote
+//! IdP metadata ([`DiscoveryCache`] and [`ProviderJwksCache`]).
+//!
+//! Entries expire after a TTL shortened by a random jitter, so instances
+//! that filled their caches together (e.g. right after a deploy) do not all
+//! refetch at the same moment. An expired entry is still served for up to
+//! `max_stale` while a single background task refreshes it
+//! (stale-while-revalidate); if that refresh fails the stale entry keeps
+//! being served, and the next attempt waits [`REFRESH_FAILURE_BACKOFF`].
+//!
+//! [`DiscoveryCache`]: super::DiscoveryCache
+//! [`ProviderJwksCache`]: super::ProviderJwksCache
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+/// Wait between background refresh attempts of an entry after one failed.
+pub const REFRESH_FAILURE_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Point-in-time statistics of a remote metadata cache.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct RemoteCacheStats {
+    /// Entries currently cached, fresh or stale.
+    pub size: usize,
+    /// Lookups answered by a fresh entry.
+    pub hits: u64,
+    /// Lookups answered by a stale entry while it was refreshed.
+    pub stale_hits: u64,
+    /// Lookups that had to fetch before answering.
+    pub misses: u64,
+    /// Successful fetches, foreground or background.
+    pub refreshes: u64,
+    /// Failed fetches, foreground or background.
+    pub refresh_failures: u64,
+}
+
+/// Counters behind [`RemoteCacheStats`].
+#[derive(Debug, Default)]
+pub(crate) struct CacheCounters {
+    hits: AtomicU64,
+    stale_hits: AtomicU64,
+    misses: AtomicU64,
+    refreshes: AtomicU64,
+    refresh_failures: AtomicU64,
+}
+
+impl CacheCounters {
+    pub(crate) fn hit(&self) {
+        self.hits.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn stale_hit(&self) {
+        self.stale_hits.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn miss(&self) {
+        self.misses.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Counts the outcome of one fetch.
+    pub(crate) fn fetched<T, E>(&self, result: &Result<T, E>) {
+        let counter = if result.is_ok() {
+            &self.refreshes
+        } else {
+            &self.refresh_failures
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn snapshot(&self, size: usize) -> RemoteCacheStats {
+        RemoteCacheStats {
+            size,
+            hits: self.hits.load(Ordering::Relaxed),
+            stale_hits: self.stale_hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            refreshes: self.refreshes.load(Ordering::Relaxed),
+            refresh_failures: self.refresh_failures.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// How usable a cached entry is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Freshness {
+    /// Before its expiry.
+    Fresh,
+    /// Expired, but within `max_stale`: serve it and refresh in the background.
+    Stale,
+    /// Too old to serve.
+    Expired,
+}
+
+impl Freshness {
+    pub(crate) fn of(expires_at: Instant, max_stale: Duration, now: Instant) -> Self {
+        if now < expires_at {
+            Self::Fresh
+        } else if now < expires_at + max_stale {
+            Self::Stale
+        } else {
+            Self::Expired
+        }
+    }
+}
+
+/// `ttl` shortened by a random share of up to `jitter` (a fraction in `0..=1`).
+pub(crate) fn jittered(ttl: Duration, jitter: f64) -> Duration {
+    let jitter = jitter.clamp(0.0, 1.0);
+    if jitter == 0.0 {
+        return ttl;
+    }
+    ttl.mul_f64(1.0 - rand::random::<f64>() * jitter)
+}
+
+/// Background refreshes per cache key: at most one runs at a time, and a
+/// failed one blocks the next until its backoff has passed.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct BackgroundRefreshes(Arc<Mutex<HashMap<String, Option<Instant>>>>);
+
+impl BackgroundRefreshes {
+    /// Claims the refresh of `key`, unless one is running or backing off.
+    pub(crate) fn try_start(&self, key: &str, now: Instant) -> Option<RefreshClaim> {
+        let mut state = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        match state.get(key) {
+            // In flight
+            Some(None) => return None,
+            Some(Some(retry_at)) if now < *retry_at => return None,
+            _ => {}
+        }
+        state.insert(key.to_string(), None);
+        Some(RefreshClaim {
+            refreshes: self.clone(),
+            key: key.to_string(),
+            failed: false,
+        })
+    }
+}
+
+/// Held by the task refreshing one key; releases the key when dropped.
+pub(crate) struct RefreshClaim {
+    refreshes: BackgroundRefreshes,
+    key: String,
+    failed: bool,
+}
+
+impl RefreshClaim {
+    /// Delays the next background refresh of this key by
+    /// [`REFRESH_FAILURE_BACKOFF`].
+    pub(crate) fn failed(mut self) {
+        self.failed = true;
+    }
+}
+
+impl Drop for RefreshClaim {
+    fn drop(&mut self) {
+        let mut state = self.refreshes.0.lock().unwrap_or_else(|e| e.into_inner());
+        if self.failed {
+            state.insert(
+                std::mem::take(&mut self.key),
+                Some(Instant::now() + REFRESH_FAILURE_BACKOFF),
+            );
+        } else {
+            state.remove(&self.key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_freshness_windows() {
+        let now = Instant::now();
+        let max_stale = Duration::from_secs(60);
+        let at = |secs: u64| now + Duration::from_secs(secs);
+
+        assert_eq!(Freshness::of(at(10), max_stale, now), Freshness::Fresh);
+        assert_eq!(Freshness::of(now, max_stale, at(30)), Freshness::Stale);
+        assert_eq!(Freshness::of(now, max_stale, at(60)), Freshness::Expired);
+        assert_eq!(Freshness::of(now, Duration::ZERO, now), Freshness::Expired);
+    }
+
+    #[test]
+    fn test_jittered_stays_within_bounds() {
+        let ttl = Duration::from_secs(100);
+        assert_eq!(jittered(ttl, 0.0), ttl);
+        for _ in 0..100 {
+            let value = jittered(ttl, 0.2);
+            assert!(value <= ttl && value >= Duration::from_secs(80));
+        }
+    }
+
+    #[test]
+    fn test_background_refresh_is_single_flight_with_failure_backoff() {
+        let refreshes = BackgroundRefreshes::default();
+        let now = Instant::now();
+
+        let claim = refreshes.try_start("a", now).unwrap();
+        assert!(refreshes.try_start("a", now).is_none());
+        assert!(refreshes.try_start("b", now).is_some());
+        drop(claim);
+        assert!(refreshes.try_start("a", now).is_some());
+
+        refreshes.try_start("a", now).unwrap().failed();
+        assert!(refreshes.try_start("a", Instant::now()).is_none());
+        let later = Instant::now() + REFRESH_FAILURE_BACKOFF;
+        assert!(refreshes.try_start("a", later).is_some());
+    }
+}
@@ -37,6 +37,7 @@ use uuid::Uuid;
 
 use crate::config::SessionConfig;
 use crate::device::{extract_ip_address, extract_user_agent, generate_device_name};
+use crate::error::AuthError;
 use crate::oauth::authorize::{
     AuthorizationError, AuthorizationErrorCode, AuthorizationRequest, AuthorizationResponse,
 };
@@ -157,14 +158,26 @@ pub async fn authorize_get(
         );
     }
 
-    // Validate PKCE based on client type (RFC 8252, RFC 9207)
-    if !client.confidential {
-        // Public client: PKCE is REQUIRED (RFC 8252)
+    // Validate aud is this FHIR server (SMART App Launch)
+    if let Err(AuthError::InvalidRequest { message }) =
+        state.authorization_service.validate_audience(&params.aud)
+    {
+        return redirect_with_error(
+            &params.redirect_uri,
+            AuthorizationErrorCode::InvalidRequest,
+            &message,
+            &params.state,
+        );
+    }
+
+    // Validate PKCE per client (RFC 8252, RFC 9207, SMART v2)
+    if client.requires_pkce() {
+        // Public clients and clients configured with pkce_required
         if params.code_challenge.is_none() || params.code_challenge_method.is_none() {
             return redirect_with_error(
                 &params.redirect_uri,
                 AuthorizationErrorCode::InvalidRequest,
-                "PKCE (code_challenge and code_challenge_method) is required for public clients",
+                "PKCE (code_challenge and code_challenge_method) is required for this client",
                 &params.state,
             );
         }
@@ -40,10 +40,11 @@
 //! # Flow
 //!
 //! 1. EHR calls POST /auth/launch with patient/encounter context
-//! 2. Server returns a launch ID
+//! 2. Server checks the referenced resources exist and returns a launch ID
 //! 3. EHR redirects to app with `launch=<launch_id>` parameter
 //! 4. App includes launch parameter in authorization request
-//! 5. Server retrieves context and includes in token response
+//! 5. Server consumes the context (single use) and includes it in the
+//!    token response
 
 use std::sync::Arc;
 
@@ -68,6 +69,8 @@ pub struct LaunchState {
     pub launch_storage: Arc<dyn LaunchContextStorage>,
     /// TTL for launch contexts in seconds (default 600 = 10 minutes).
     pub launch_ttl_seconds: u64,
+    /// FHIR storage for checking that context references exist (optional).
+    pub fhir_storage: Option<Arc<dyn octofhir_storage::FhirStorage>>,
 }
 
 impl LaunchState {
@@ -76,6 +79,7 @@ impl LaunchState {
         Self {
             launch_storage,
             launch_ttl_seconds: DEFAULT_LAUNCH_CONTEXT_TTL,
+            fhir_storage: None,
         }
     }
 
@@ -85,6 +89,17 @@ impl LaunchState {
         self.launch_ttl_seconds = ttl_seconds;
         self
     }
+
+    /// Sets FHIR storage so `patient`, `encounter` and `fhirContext`
+    /// references are checked to exist before the launch is created.
+    #[must_use]
+    pub fn with_fhir_storage(
+        mut self,
+        fhir_storage: Arc<dyn octofhir_storage::FhirStorage>,
+    ) -> Self {
+        self.fhir_storage = Some(fhir_storage);
+        self
+    }
 }
 
 // =============================================================================
@@ -149,6 +164,13 @@ pub struct LaunchErrorResponse {
 }
 
 impl LaunchErrorResponse {
+    fn invalid_request(description: impl Into<String>) -> Self {
+        Self {
+            error: "invalid_request".to_string(),
+            error_description: description.into(),
+        }
+    }
+
     fn server_error(description: impl Into<String>) -> Self {
         Self {
             error: "server_error".to_string(),
@@ -181,6 +203,8 @@ impl LaunchErrorResponse {
 /// # Response
 ///
 /// - 201 Created: Launch context created successfully
+/// - 400 Bad Request: A referenced patient, encounter or fhirContext resource
+///   is malformed or does not exist
 /// - 500 Internal Server Error: Storage error
 ///
 /// ```json
@@ -211,6 +235,12 @@ pub async fn create_launch_handler(
         tenant: request.tenant,
     };
 
+    if let Some(fhir_storage) = &state.fhir_storage
+        && let Err(response) = validate_context_references(fhir_storage.as_ref(), &context).await
+    {
+        return response;
+    }
+
     match state
         .launch_storage
         .store(&context, state.launch_ttl_seconds)
@@ -240,6 +270,87 @@ pub async fn create_launch_handler(
     }
 }
 
+/// Checks that every resource referenced by the launch context exists.
+///
+/// `patient` and `encounter` accept a bare ID or a `Type/id` reference;
+/// `fhirContext` items must be `Type/id` references.
+async fn validate_context_references(
+    fhir_storage: &dyn octofhir_storage::FhirStorage,
+    context: &StoredLaunchContext,
+) -> Result<(), axum::response::Response> {
+    let mut references = Vec::new();
+    if let Some(patient) = &context.patient {
+        references.push(("patient", parse_reference(patient, Some("Patient"))));
+    }
+    if let Some(encounter) = &context.encounter {
+        references.push(("encounter", parse_reference(encounter, Some("Encounter"))));
+    }
+    for item in &context.fhir_context {
+        references.push(("fhirContext", parse_reference(&item.reference, None)));
+    }
+
+    for (field, reference) in references {
+        let (resource_type, id) = reference
+            .map_err(|value| bad_request(format!("Invalid {field} reference '{value}'")))?;
+        match fhir_storage.exists(resource_type, id).await {
+            Ok(true) => {}
+            Ok(false) => {
+                return Err(bad_request(format!(
+                    "{field} resource {resource_type}/{id} does not exist"
+                )));
+            }
+            Err(e) => {
+                tracing::error!(error = %e, "Failed to check launch context reference");
+                let error_response = LaunchErrorResponse::server_error(e.to_string());
+                return Err(
+                    (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)).into_response()
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Splits a reference into `(type, id)`. A bare ID takes `default_type`.
+/// Returns the original value as the error when it is malformed or names a
+/// different type than `default_type`.
+fn parse_reference<'a>(
+    value: &'a str,
+    default_type: Option<&'a str>,
+) -> Result<(&'a str, &'a str), &'a str> {
+    let (resource_type, id) = match (value.split_once('/'), default_type) {
+        (Some((resource_type, id)), expected) => {
+            if expected.is_some_and(|expected| expected != resource_type) {
+                return Err(value);
+            }
+            (resource_type, id)
+        }
+        (None, Some(default_type)) => (default_type, value),
+        (None, None) => return Err(value),
+    };
+    let valid_type = resource_type
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_uppercase())
+        && resource_type.chars().all(|c| c.is_ascii_alphanumeric());
+    let valid_id = !id.is_empty()
+        && id.len() <= 64
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.');
+    if valid_type && valid_id {
+        Ok((resource_type, id))
+    } else {
+        Err(value)
+    }
+}
+
+fn bad_request(description: String) -> axum::response::Response {
+    let error_response = LaunchErrorResponse::invalid_request(description);
+    (StatusCode::BAD_REQUEST, Json(error_response)).into_response()
+}
+
 // =============================================================================
 // Tests
 // =============================================================================
@@ -312,6 +423,26 @@ mod tests {
         // This is a compile-time check that the API works
     }
 
+    #[test]
+    fn test_parse_reference() {
+        assert_eq!(
+            parse_reference("123", Some("Patient")),
+            Ok(("Patient", "123"))
+        );
+        assert_eq!(
+            parse_reference("Patient/123", Some("Patient")),
+            Ok(("Patient", "123"))
+        );
+        assert_eq!(
+            parse_reference("Observation/obs-1", None),
+            Ok(("Observation", "obs-1"))
+        );
+        assert!(parse_reference("Encounter/1", Some("Patient")).is_err());
+        assert!(parse_reference("obs-1", None).is_err());
+        assert!(parse_reference("Patient/1/_history/2", None).is_err());
+        assert!(parse_reference("Patient/", Some("Patient")).is_err());
+    }
+
     #[test]
     fn test_launch_error_response_serialize() {
         let error = LaunchErrorResponse::server_error("Storage error");
@@ -54,6 +54,7 @@ use crate::storage::session::SessionStorage;
 use crate::storage::{
     ClientStorage, JtiStorage, RefreshTokenStorage, RevokedTokenStorage, UserStorage,
 };
+use crate::token::introspection_cache::IntrospectionCache;
 use crate::token::jwt::{AccessTokenClaims, JwtService};
 use crate::token::service::{TokenConfig, TokenService};
 use crate::types::{Client, GrantType};
@@ -138,6 +139,20 @@ impl TokenState {
         self
     }
 
+    /// Sets the introspection result cache on the inner token service.
+    #[must_use]
+    pub fn with_introspection_cache(mut self, cache: Arc<dyn IntrospectionCache>) -> Self {
+        if let Some(ts) = Arc::get_mut(&mut self.token_service) {
+            ts.set_introspection_cache(cache);
+        }
+        self
+    }
+
+    /// Returns the token service.
+    pub fn token_service(&self) -> &Arc<TokenService> {
+        &self.token_service
+    }
+
     /// Sets cookie configuration for browser-based authentication.
     #[must_use]
     pub fn with_cookie_config(mut self, cookie_config: CookieConfig) -> Self {
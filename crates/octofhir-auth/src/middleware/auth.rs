@@ -22,6 +22,7 @@ use std::sync::Arc;
 
 use crate::config::CookieConfig;
 use crate::error::AuthError;
+use crate::federation::ExternalTokenValidator;
 use crate::storage::{ClientStorage, RevokedTokenStorage, UserStorage};
 use crate::token::jwt::{AccessTokenClaims, JwtService};
 use axum::{
@@ -71,6 +72,9 @@ pub struct AuthState {
 
     /// Cookie configuration for browser-based auth.
     pub cookie_config: CookieConfig,
+
+    /// Validator for tokens issued by trusted external issuers.
+    pub external_token_validator: Option<Arc<ExternalTokenValidator>>,
 }
 
 impl AuthState {
@@ -87,6 +91,7 @@ impl AuthState {
             revoked_token_storage,
             user_storage,
             cookie_config: CookieConfig::default(),
+            external_token_validator: None,
         }
     }
 
@@ -96,6 +101,13 @@ impl AuthState {
         self.cookie_config = cookie_config;
         self
     }
+
+    /// Accepts tokens from trusted external issuers.
+    #[must_use]
+    pub fn with_external_token_validator(mut self, validator: Arc<ExternalTokenValidator>) -> Self {
+        self.external_token_validator = Some(validator);
+        self
+    }
 }
 
 // =============================================================================
@@ -106,7 +118,8 @@ impl AuthState {
 ///
 /// This extractor:
 /// 1. Extracts the `Authorization: Bearer <token>` header
-/// 2. Decodes and validates the JWT
+/// 2. Decodes and validates the JWT (against the issuer's JWKS for tokens
+///    from a trusted external issuer)
 /// 3. Checks token expiration
 /// 4. Checks token revocation
 /// 5. Loads the OAuth client (and verifies it's active)
@@ -175,6 +188,13 @@ where
             return Err(AuthError::unauthorized("Empty Bearer token"));
         }
 
+        // Tokens from trusted external issuers are verified against their JWKS
+        if let Some(validator) = &auth_state.external_token_validator
+            && validator.trusts(&token)
+        {
+            return validator.authenticate(&token).await.map(BearerAuth);
+        }
+
         // 3. Decode and validate JWT (using spawn_blocking to avoid blocking async runtime)
         let claims = auth_state
             .jwt_service
@@ -42,8 +42,8 @@ use crate::oauth::session::{AuthorizationSession, LaunchContext};
 use crate::smart::launch::StoredLaunchContext;
 use crate::smart::scopes::SmartScopes;
 use crate::storage::ClientStorage;
-use crate::storage::LaunchContextStorage;
 use crate::storage::session::SessionStorage;
+use crate::storage::{LaunchContextStatus, LaunchContextStorage};
 use crate::types::GrantType;
 
 /// Authorization service for handling OAuth 2.0 authorization requests.
@@ -79,6 +79,10 @@ pub struct AuthorizationConfig {
     /// Whether to require the `aud` parameter.
     /// Default: true (required for SMART on FHIR).
     pub require_aud: bool,
+
+    /// FHIR base URLs accepted as `aud`.
+    /// Default: empty (any audience is accepted).
+    pub audiences: Vec<String>,
 }
 
 impl Default for AuthorizationConfig {
@@ -87,6 +91,7 @@ impl Default for AuthorizationConfig {
             code_lifetime: Duration::minutes(10),
             min_state_entropy_bits: 122,
             require_aud: true,
+            audiences: Vec::new(),
         }
     }
 }
@@ -112,6 +117,13 @@ impl AuthorizationConfig {
         self.require_aud = false;
         self
     }
+
+    /// Adds a FHIR base URL accepted as `aud`.
+    #[must_use]
+    pub fn with_audience(mut self, audience: impl Into<String>) -> Self {
+        self.audiences.push(audience.into());
+        self
+    }
 }
 
 impl AuthorizationService {
@@ -176,15 +188,18 @@ impl AuthorizationService {
     /// - Client is inactive (`InvalidClient`)
     /// - Redirect URI is not allowed (`InvalidGrant`)
     /// - Grant type is not allowed (`InvalidGrant`)
+    /// - PKCE is required for the client but missing (`InvalidRequest`)
     /// - PKCE method is not S256 (`InvalidRequest`)
     /// - PKCE challenge is invalid (`InvalidRequest`)
     /// - State has insufficient entropy (`InvalidRequest`)
+    /// - `aud` is missing or not this FHIR server (`InvalidRequest`)
     ///
     /// # Security
     ///
     /// - Never log the authorization code or state parameter
     /// - Redirect URI must exactly match a registered URI
-    /// - PKCE is always required (no fallback to plain)
+    /// - PKCE is required for public clients and clients with `pkce_required`
+    /// - Only S256 is accepted (no fallback to plain)
     pub async fn authorize(
         &self,
         request: &AuthorizationRequest,
@@ -217,12 +232,12 @@ impl AuthorizationService {
             ));
         }
 
-        // 5. Validate PKCE based on client type (RFC 8252, RFC 9207)
-        if !client.confidential {
-            // Public client: PKCE is REQUIRED (RFC 8252)
+        // 5. Validate PKCE per client (RFC 8252, RFC 9207, SMART v2)
+        if client.requires_pkce() {
+            // Public clients and clients configured with pkce_required
             if request.code_challenge.is_none() || request.code_challenge_method.is_none() {
                 return Err(AuthError::invalid_request(
-                    "PKCE (code_challenge and code_challenge_method) is required for public clients",
+                    "PKCE (code_challenge and code_challenge_method) is required for this client",
                 ));
             }
         } else {
@@ -258,12 +273,8 @@ impl AuthorizationService {
         // 7. Validate state entropy
         self.validate_state_entropy(&request.state)?;
 
-        // 8. Validate aud parameter if required
-        if self.config.require_aud && request.aud.is_empty() {
-            return Err(AuthError::invalid_request(
-                "Missing required parameter: aud",
-            ));
-        }
+        // 8. Validate aud parameter against the FHIR server
+        self.validate_audience(&request.aud)?;
 
         // 9. Validate scopes are allowed for this client
         if !request.scope.is_empty() {
@@ -312,6 +323,47 @@ impl AuthorizationService {
         Ok(session)
     }
 
+    /// Validates the `aud` parameter of an authorization request.
+    ///
+    /// SMART on FHIR requires `aud` to be the base URL of the FHIR server the
+    /// client wants to access, so tokens are not requested for one server and
+    /// presented to another. URLs are compared after normalising case of the
+    /// scheme and host, default ports and trailing slashes.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidRequest` if `aud` is missing while required, or does
+    /// not match any configured audience.
+    pub fn validate_audience(&self, aud: &str) -> AuthResult<()> {
+        if aud.is_empty() {
+            if self.config.require_aud {
+                return Err(AuthError::invalid_request(
+                    "Missing required parameter: aud",
+                ));
+            }
+            return Ok(());
+        }
+
+        if self.config.audiences.is_empty() {
+            return Ok(());
+        }
+
+        let requested = normalize_audience(aud);
+        if self
+            .config
+            .audiences
+            .iter()
+            .any(|audience| normalize_audience(audience) == requested)
+        {
+            Ok(())
+        } else {
+            Err(AuthError::invalid_request(format!(
+                "aud '{}' does not match this FHIR server",
+                aud
+            )))
+        }
+    }
+
     /// Validates that the state parameter has sufficient entropy.
     ///
     /// The state parameter must have at least `min_state_entropy_bits` bits
@@ -360,8 +412,11 @@ impl AuthorizationService {
     /// - `launch` scope requested but no launch parameter provided
     /// - Launch parameter present but no `launch` scope requested
     /// - Launch context storage not configured
-    /// - Launch context not found or expired
+    /// - Launch context not found, expired or already used
     /// - Launch scopes don't match context (e.g., `launch/patient` without patient)
+    ///
+    /// A launch ID is single use: it is consumed once the scopes have been
+    /// validated, so replaying it in another authorization fails.
     async fn process_launch_parameter(
         &self,
         launch_param: Option<&str>,
@@ -393,14 +448,18 @@ impl AuthorizationService {
             return Err(AuthError::internal("Launch context storage not configured"));
         };
 
-        let stored_context = launch_storage
-            .get(launch_id)
-            .await?
-            .ok_or_else(|| AuthError::invalid_grant("Invalid or expired launch parameter"))?;
+        let Some(stored_context) = launch_storage.get(launch_id).await? else {
+            return Err(Self::launch_unavailable(launch_storage.as_ref(), launch_id).await);
+        };
 
         // Validate launch scopes against context
         self.validate_launch_scopes(scopes, &stored_context)?;
 
+        // Consume atomically; a concurrent request may have won the race
+        if launch_storage.consume(launch_id).await?.is_none() {
+            return Err(Self::launch_unavailable(launch_storage.as_ref(), launch_id).await);
+        }
+
         // Convert StoredLaunchContext to session LaunchContext
         let launch_context = LaunchContext {
             patient: stored_context.patient.clone(),
@@ -421,6 +480,27 @@ impl AuthorizationService {
         Ok(Some(launch_context))
     }
 
+    /// Builds the error for a launch ID that could not be retrieved.
+    async fn launch_unavailable(
+        launch_storage: &dyn LaunchContextStorage,
+        launch_id: &str,
+    ) -> AuthError {
+        let status = match launch_storage.status(launch_id).await {
+            Ok(status) => status,
+            Err(e) => return e,
+        };
+        tracing::warn!(?status, "Rejected launch parameter");
+        match status {
+            LaunchContextStatus::Expired => AuthError::invalid_grant("Launch context has expired"),
+            LaunchContextStatus::Consumed => {
+                AuthError::invalid_grant("Launch context has already been used")
+            }
+            LaunchContextStatus::Active | LaunchContextStatus::NotFound => {
+                AuthError::invalid_grant("Invalid launch parameter")
+            }
+        }
+    }
+
     /// Validates that launch scopes match the launch context.
     ///
     /// # Arguments
@@ -545,6 +625,15 @@ impl AuthorizationService {
     }
 }
 
+/// Normalises an audience URL for comparison.
+///
+/// Unparsable values are compared as given, without a trailing slash.
+fn normalize_audience(aud: &str) -> String {
+    url::Url::parse(aud)
+        .map(|url| url.as_str().trim_end_matches('/').to_string())
+        .unwrap_or_else(|_| aud.trim_end_matches('/').to_string())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -715,6 +804,60 @@ mod tests {
         }
     }
 
+    /// Mock launch context storage that keeps consumed entries.
+    struct MockLaunchStorage {
+        contexts: RwLock<HashMap<String, (StoredLaunchContext, bool)>>,
+    }
+
+    #[async_trait::async_trait]
+    impl LaunchContextStorage for MockLaunchStorage {
+        async fn store(&self, context: &StoredLaunchContext, _ttl_seconds: u64) -> AuthResult<()> {
+            self.contexts
+                .write()
+                .unwrap()
+                .insert(context.launch_id.clone(), (context.clone(), false));
+            Ok(())
+        }
+
+        async fn get(&self, launch_id: &str) -> AuthResult<Option<StoredLaunchContext>> {
+            Ok(self
+                .contexts
+                .read()
+                .unwrap()
+                .get(launch_id)
+                .filter(|(_, consumed)| !consumed)
+                .map(|(ctx, _)| ctx.clone()))
+        }
+
+        async fn consume(&self, launch_id: &str) -> AuthResult<Option<StoredLaunchContext>> {
+            let mut contexts = self.contexts.write().unwrap();
+            match contexts.get_mut(launch_id) {
+                Some((ctx, consumed)) if !*consumed => {
+                    *consumed = true;
+                    Ok(Some(ctx.clone()))
+                }
+                _ => Ok(None),
+            }
+        }
+
+        async fn delete(&self, launch_id: &str) -> AuthResult<()> {
+            self.contexts.write().unwrap().remove(launch_id);
+            Ok(())
+        }
+
+        async fn cleanup_expired(&self) -> AuthResult<u64> {
+            Ok(0)
+        }
+
+        async fn status(&self, launch_id: &str) -> AuthResult<LaunchContextStatus> {
+            Ok(match self.contexts.read().unwrap().get(launch_id) {
+                Some((_, true)) => LaunchContextStatus::Consumed,
+                Some((_, false)) => LaunchContextStatus::Active,
+                None => LaunchContextStatus::NotFound,
+            })
+        }
+    }
+
     fn create_test_client() -> Client {
         Client {
             client_id: "test-client".to_string(),
@@ -884,6 +1027,45 @@ mod tests {
         assert!(matches!(result, Err(AuthError::InvalidRequest { .. })));
     }
 
+    #[tokio::test]
+    async fn test_authorize_pkce_per_client() {
+        let (service, client_storage, _) = create_service();
+
+        let mut client = create_test_client();
+        client.confidential = true;
+        client_storage.add_client(client.clone());
+
+        let mut request = create_test_request();
+        request.code_challenge = None;
+        request.code_challenge_method = None;
+
+        // Optional for confidential clients by default
+        assert!(service.authorize(&request).await.is_ok());
+
+        // Enforced once the client requires it
+        client.pkce_required = Some(true);
+        client_storage.add_client(client);
+        let result = service.authorize(&request).await;
+        assert!(matches!(result, Err(AuthError::InvalidRequest { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_authorize_public_client_requires_pkce() {
+        let (service, client_storage, _) = create_service();
+
+        // pkce_required cannot opt a public client out
+        let mut client = create_test_client();
+        client.pkce_required = Some(false);
+        client_storage.add_client(client);
+
+        let mut request = create_test_request();
+        request.code_challenge = None;
+        request.code_challenge_method = None;
+
+        let result = service.authorize(&request).await;
+        assert!(matches!(result, Err(AuthError::InvalidRequest { .. })));
+    }
+
     #[tokio::test]
     async fn test_authorize_insufficient_state_entropy() {
         let (service, client_storage, _) = create_service();
@@ -908,6 +1090,63 @@ mod tests {
         assert!(matches!(result, Err(AuthError::InvalidRequest { .. })));
     }
 
+    #[tokio::test]
+    async fn test_authorize_aud_mismatch() {
+        let client_storage = Arc::new(MockClientStorage::new());
+        let service = AuthorizationService::new(
+            client_storage.clone(),
+            Arc::new(MockSessionStorage::new()),
+            AuthorizationConfig::default().with_audience("https://fhir.example.com/r4"),
+        );
+        client_storage.add_client(create_test_client());
+
+        let mut request = create_test_request();
+        request.aud = "https://other.example.com/r4".to_string();
+        let result = service.authorize(&request).await;
+        assert!(matches!(result, Err(AuthError::InvalidRequest { .. })));
+
+        // Case of scheme and host and a trailing slash don't matter
+        request.aud = "HTTPS://FHIR.example.com/r4/".to_string();
+        let session = service.authorize(&request).await.unwrap();
+        assert_eq!(session.aud, "HTTPS://FHIR.example.com/r4/");
+    }
+
+    #[test]
+    fn test_validate_audience() {
+        let service = AuthorizationService::new(
+            Arc::new(MockClientStorage::new()),
+            Arc::new(MockSessionStorage::new()),
+            AuthorizationConfig::default()
+                .with_audience("https://fhir.example.com")
+                .with_audience("https://fhir.example.com/fhir"),
+        );
+
+        assert!(
+            service
+                .validate_audience("https://fhir.example.com/")
+                .is_ok()
+        );
+        assert!(
+            service
+                .validate_audience("https://fhir.example.com:443/fhir")
+                .is_ok()
+        );
+        assert!(
+            service
+                .validate_audience("https://fhir.example.com/FHIR")
+                .is_err()
+        );
+        assert!(service.validate_audience("").is_err());
+
+        // Without configured audiences only presence is checked
+        let (service, _, _) = create_service();
+        assert!(
+            service
+                .validate_audience("https://anything.example.com")
+                .is_ok()
+        );
+    }
+
     #[tokio::test]
     async fn test_authorize_invalid_scope() {
         let (service, client_storage, _) = create_service();
@@ -953,6 +1192,40 @@ mod tests {
         assert!(matches!(result, Err(AuthError::InvalidScope { .. })));
     }
 
+    #[tokio::test]
+    async fn test_authorize_launch_is_single_use() {
+        let (service, client_storage, _) = create_service();
+        client_storage.add_client(create_test_client());
+        let launch_storage = Arc::new(MockLaunchStorage {
+            contexts: RwLock::new(HashMap::new()),
+        });
+        launch_storage
+            .store(
+                &StoredLaunchContext::with_patient("launch-1", "patient-1"),
+                600,
+            )
+            .await
+            .unwrap();
+        let service = service.with_launch_storage(launch_storage);
+
+        let mut request = create_test_request();
+        request.launch = Some("launch-1".to_string());
+        request.scope = "launch openid patient/*.rs".to_string();
+
+        let session = service.authorize(&request).await.unwrap();
+        assert_eq!(
+            session.launch_context.unwrap().patient,
+            Some("patient-1".to_string())
+        );
+
+        let err = service.authorize(&request).await.unwrap_err();
+        assert!(err.to_string().contains("already been used"));
+
+        request.launch = Some("unknown".to_string());
+        let err = service.authorize(&request).await.unwrap_err();
+        assert!(matches!(err, AuthError::InvalidGrant { .. }));
+    }
+
     #[tokio::test]
     async fn test_config_defaults() {
         let config = AuthorizationConfig::default();
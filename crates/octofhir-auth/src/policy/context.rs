@@ -703,6 +703,21 @@ impl PolicyContextBuilder {
         self
     }
 
+    /// Set identity information directly, for contexts not derived from a
+    /// real token (e.g. evaluating policies against a synthetic request).
+    #[must_use]
+    pub fn with_identity(
+        mut self,
+        user: Option<UserIdentity>,
+        client: ClientIdentity,
+        scope_string: &str,
+    ) -> Self {
+        self.user = user;
+        self.client = Some(client);
+        self.scopes = Some(ScopeSummary::from_scope_string(scope_string));
+        self
+    }
+
     /// Set request information.
     #[must_use]
     pub fn with_request(
@@ -40,7 +40,8 @@ use crate::smart::scopes::FhirOperation;
 // =============================================================================
 
 /// Result of policy evaluation.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "outcome", content = "reason", rename_all = "lowercase")]
 pub enum AccessDecision {
     /// Access is granted.
     Allow,
@@ -210,11 +211,19 @@ pub enum DefaultDecision {
 // =============================================================================
 
 /// Complete result of policy evaluation with audit information.
-#[derive(Debug)]
+///
+/// This is the evaluation trace: it tells policy authors why a request was
+/// allowed or denied. It is meant for admin diagnostics only and must not be
+/// returned to regular API clients.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct EvaluationResult {
     /// The final access decision.
     pub decision: AccessDecision,
 
+    /// What produced the final decision.
+    pub decided_by: DecisionSource,
+
     /// Policies that were evaluated.
     pub evaluated_policies: Vec<EvaluatedPolicy>,
 
@@ -228,8 +237,27 @@ pub struct EvaluationResult {
     pub scope_decision: Option<AccessDecision>,
 }
 
+/// What produced the final decision of an evaluation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "source", rename_all = "camelCase")]
+pub enum DecisionSource {
+    /// The SMART scope check denied the request.
+    Scopes,
+    /// A policy denied, or was the first to allow.
+    #[serde(rename_all = "camelCase")]
+    Policy {
+        /// ID of the deciding policy.
+        policy_id: String,
+    },
+    /// No policy decided, so the configured default applied.
+    Default,
+    /// Policies could not be loaded.
+    Error,
+}
+
 /// Information about a policy that was evaluated.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct EvaluatedPolicy {
     /// Policy ID.
     pub policy_id: String,
@@ -380,6 +408,10 @@ impl PolicyEvaluator {
     }
 
     /// Evaluate access with detailed audit information.
+    ///
+    /// Debug counterpart of [`evaluate`](Self::evaluate): reaches the same
+    /// decision, but records the scope check, every candidate policy and
+    /// whether it matched, and what decided the outcome.
     pub async fn evaluate_with_audit(&self, context: &PolicyContext) -> EvaluationResult {
         let start = std::time::Instant::now();
         let mut evaluated_policies = Vec::new();
@@ -394,6 +426,7 @@ impl PolicyEvaluator {
             if decision.is_denied() {
                 return EvaluationResult {
                     decision,
+                    decided_by: DecisionSource::Scopes,
                     evaluated_policies,
                     evaluation_time_ms: start.elapsed().as_secs_f64() * 1000.0,
                     scopes_checked,
@@ -415,6 +448,7 @@ impl PolicyEvaluator {
                     decision: AccessDecision::Deny(DenyReason::policy_error(
                         "Failed to evaluate access policies",
                     )),
+                    decided_by: DecisionSource::Error,
                     evaluated_policies,
                     evaluation_time_ms: start.elapsed().as_secs_f64() * 1000.0,
                     scopes_checked,
@@ -424,7 +458,7 @@ impl PolicyEvaluator {
         };
 
         // Step 3: Evaluate policies in priority order
-        let mut has_allow = false;
+        let mut first_allow = None;
         let mut final_decision = None;
 
         for policy in &policies {
@@ -453,30 +487,34 @@ impl PolicyEvaluator {
 
             match decision {
                 AccessDecision::Deny(_) => {
-                    final_decision = Some(decision);
+                    final_decision = Some((decision, policy.id.clone()));
                     break;
                 }
                 AccessDecision::Allow => {
-                    has_allow = true;
+                    first_allow.get_or_insert_with(|| policy.id.clone());
                 }
                 AccessDecision::Abstain => {}
             }
         }
 
         // Step 4: Determine final decision
-        let decision = final_decision.unwrap_or_else(|| {
-            if has_allow {
-                AccessDecision::Allow
-            } else {
-                match self.config.default_decision {
+        let (decision, decided_by) = match (final_decision, first_allow) {
+            (Some((decision, policy_id)), _) => (decision, DecisionSource::Policy { policy_id }),
+            (None, Some(policy_id)) => {
+                (AccessDecision::Allow, DecisionSource::Policy { policy_id })
+            }
+            (None, None) => {
+                let decision = match self.config.default_decision {
                     DefaultDecision::Allow => AccessDecision::Allow,
                     DefaultDecision::Deny => AccessDecision::Deny(DenyReason::no_matching_policy()),
-                }
+                };
+                (decision, DecisionSource::Default)
             }
-        });
+        };
 
         EvaluationResult {
             decision,
+            decided_by,
             evaluated_policies,
             evaluation_time_ms: start.elapsed().as_secs_f64() * 1000.0,
             scopes_checked,
@@ -1051,7 +1089,49 @@ mod tests {
 
         // Final decision should be allow
         assert!(result.decision.is_allowed());
+        assert_eq!(
+            result.decided_by,
+            DecisionSource::Policy {
+                policy_id: "p2".to_string()
+            }
+        );
         assert!(result.evaluation_time_ms >= 0.0);
+
+        let json = serde_json::to_value(&result).unwrap();
+        assert_eq!(json["decision"]["outcome"], "allow");
+        assert_eq!(json["decidedBy"]["source"], "policy");
+        assert_eq!(json["decidedBy"]["policyId"], "p2");
+        assert_eq!(json["evaluatedPolicies"][0]["matched"], false);
+    }
+
+    #[tokio::test]
+    async fn test_evaluate_with_audit_records_default_and_scope_decisions() {
+        let storage = Arc::new(MockPolicyStorage::new());
+        let cache = Arc::new(PolicyCache::new(storage, Duration::minutes(5)));
+        cache.refresh().await.unwrap();
+        let engine = PolicyEvaluator::new(
+            cache,
+            PolicyEvaluatorConfig {
+                evaluate_scopes_first: true,
+                ..Default::default()
+            },
+        );
+
+        let context = create_test_context("Patient", FhirOperation::Read);
+        let result = engine.evaluate_with_audit(&context).await;
+        assert_eq!(result.decided_by, DecisionSource::Default);
+        assert_eq!(
+            result.decision.deny_reason().unwrap().code,
+            "no-matching-policy"
+        );
+
+        let mut context = create_test_context("Patient", FhirOperation::Read);
+        context.request.resource_type = "Observation".to_string();
+        let result = engine.evaluate_with_audit(&context).await;
+        assert_eq!(result.decided_by, DecisionSource::Scopes);
+        let json = serde_json::to_value(&result).unwrap();
+        assert_eq!(json["decision"]["outcome"], "deny");
+        assert_eq!(json["decision"]["reason"]["code"], "insufficient-scope");
     }
 
     #[tokio::test]
@@ -110,7 +110,7 @@ pub mod resources;
 pub use cache::{PolicyCache, PolicyCacheError, PolicyCacheStats};
 
 pub use engine::{
-    AccessDecision, DefaultDecision, DenyReason, EvaluatedPolicy, EvaluationResult,
+    AccessDecision, DecisionSource, DefaultDecision, DenyReason, EvaluatedPolicy, EvaluationResult,
     PolicyEvaluator, PolicyEvaluatorConfig,
 };
 
@@ -10,7 +10,7 @@
 //! 2. EHR sends `launch=<launch_id>` parameter to app
 //! 3. App includes `launch` in authorization request
 //! 4. Server retrieves launch context by `launch_id`
-//! 5. Launch context is consumed (deleted) during token exchange
+//! 5. Launch context is consumed when the authorization code is issued
 //! 6. Expired contexts are cleaned up automatically
 //!
 //! # Security Considerations
@@ -22,14 +22,29 @@
 //!
 //! # Implementation Notes
 //!
-//! The `consume` method should atomically retrieve and delete the context.
-//! This prevents the same launch context from being used multiple times.
+//! The `consume` method should atomically retrieve and invalidate the
+//! context. This prevents the same launch context from being used multiple
+//! times. Implementations that keep consumed entries until their TTL elapses
+//! can report them via `status` so replays get a clear error.
 
 use async_trait::async_trait;
 
 use crate::AuthResult;
 use crate::smart::launch::StoredLaunchContext;
 
+/// Lookup state of a launch ID.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LaunchContextStatus {
+    /// The context exists, has not expired and has not been used.
+    Active,
+    /// The context's TTL has elapsed.
+    Expired,
+    /// The context was already consumed by an authorization.
+    Consumed,
+    /// No context is known for this launch ID.
+    NotFound,
+}
+
 /// Storage trait for SMART launch contexts.
 ///
 /// This trait defines the interface for storing and retrieving launch
@@ -113,9 +128,9 @@ pub trait LaunchContextStorage: Send + Sync {
     /// Returns an error if the storage operation fails.
     async fn get(&self, launch_id: &str) -> AuthResult<Option<StoredLaunchContext>>;
 
-    /// Atomically retrieves and deletes a launch context.
+    /// Atomically retrieves and invalidates a launch context.
     ///
-    /// This is the preferred method for the token exchange flow as it
+    /// This is the preferred method for the authorization flow as it
     /// ensures single-use semantics. If two concurrent requests try to
     /// consume the same context, only one will succeed.
     ///
@@ -135,11 +150,11 @@ pub trait LaunchContextStorage: Send + Sync {
     /// # Atomicity
     ///
     /// Implementations must ensure this operation is atomic. A common approach
-    /// is to use `DELETE ... RETURNING`:
+    /// is a conditional `UPDATE ... RETURNING` that marks the context used:
     ///
     /// ```sql
-    /// DELETE FROM smart_launch_contexts
-    /// WHERE launch_id = $1 AND expires_at > NOW()
+    /// UPDATE smart_launch_contexts SET consumed_at = NOW()
+    /// WHERE launch_id = $1 AND expires_at > NOW() AND consumed_at IS NULL
     /// RETURNING context_data
     /// ```
     async fn consume(&self, launch_id: &str) -> AuthResult<Option<StoredLaunchContext>>;
@@ -191,4 +206,21 @@ pub trait LaunchContextStorage: Send + Sync {
         // Default implementation using get()
         Ok(self.get(launch_id).await?.is_some())
     }
+
+    /// Reports why a launch ID is (or is not) usable.
+    ///
+    /// Used to return a specific error when `get` or `consume` find nothing.
+    /// The default implementation cannot tell expired or consumed contexts
+    /// from unknown ones and reports them as `NotFound`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the storage operation fails.
+    async fn status(&self, launch_id: &str) -> AuthResult<LaunchContextStatus> {
+        Ok(if self.exists(launch_id).await? {
+            LaunchContextStatus::Active
+        } else {
+            LaunchContextStatus::NotFound
+        })
+    }
 }
@@ -41,7 +41,7 @@ pub use basic_auth::BasicAuthStorage;
 pub use client::ClientStorage;
 pub use consent::{ConsentStorage, UserConsent};
 pub use jti::JtiStorage;
-pub use launch_context::LaunchContextStorage;
+pub use launch_context::{LaunchContextStatus, LaunchContextStorage};
 pub use policy::{PolicySearchParams, PolicyStorage};
 pub use refresh_token::RefreshTokenStorage;
 pub use revoked_token::RevokedTokenStorage;
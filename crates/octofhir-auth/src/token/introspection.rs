@@ -62,7 +62,7 @@ pub struct IntrospectionRequest {
 /// Contains the token's active state and metadata if active.
 /// If the token is invalid, expired, revoked, or unknown, the response
 /// will only contain `active: false` with no additional claims.
-#[derive(Debug, Clone, Serialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct IntrospectionResponse {
     /// Boolean indicator of whether the token is currently active.
     ///
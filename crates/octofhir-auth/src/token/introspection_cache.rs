@@ -0,0 +1,289 @@
+//! Short-lived cache for access token introspection results.
+//!
+//! Resource servers often introspect the same access token on every request.
+//! Each introspection verifies the JWT signature and checks the revocation
+//! list in storage; this cache keeps the result for a short TTL so repeated
+//! calls skip both.
+//!
+//! [`IntrospectionCache`] is the extension point: the server backs it with
+//! its shared cache so results and revocations are seen by every instance,
+//! while [`LocalIntrospectionCache`] keeps everything in process memory.
+//!
+//! # Revocation
+//!
+//! [`IntrospectionCache::invalidate_jti`] must stop every cached result for
+//! the JTI from being served, and keep refusing to cache it for one TTL, so
+//! a lookup that read the revocation list just before the revocation cannot
+//! put an `active: true` result back afterwards. [`TokenService`] calls it
+//! from its own revocation path; code that revokes JTIs directly through
+//! `RevokedTokenStorage` (e.g. logout) must call it as well.
+//!
+//! Only access tokens are cached. Refresh tokens are revoked in bulk (per
+//! client, per user, on rotation) without a per-token hook, so their
+//! introspection always goes to storage.
+//!
+//! [`TokenService`]: super::service::TokenService
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use time::OffsetDateTime;
+
+use super::introspection::IntrospectionResponse;
+use crate::types::refresh_token::RefreshToken;
+
+/// Default time-to-live for cached introspection results.
+pub const DEFAULT_INTROSPECTION_CACHE_TTL: Duration = Duration::from_secs(30);
+
+/// Default maximum number of cached results.
+const DEFAULT_MAX_SIZE: usize = 10_000;
+
+struct CachedIntrospection {
+    response: IntrospectionResponse,
+    expires_at: Instant,
+}
+
+#[derive(Default)]
+struct CacheState {
+    /// Results keyed by SHA-256 of the token.
+    entries: HashMap<String, CachedIntrospection>,
+    /// JTI -> token hashes, for invalidation on revocation.
+    by_jti: HashMap<String, Vec<String>>,
+    /// Recently revoked JTIs that must not be cached again.
+    tombstones: HashMap<String, Instant>,
+}
+
+/// Cache of access token introspection results.
+#[async_trait]
+pub trait IntrospectionCache: Send + Sync {
+    /// Returns the cached result for `token`, if still fresh.
+    async fn get(&self, token: &str) -> Option<IntrospectionResponse>;
+
+    /// Caches the result for `token`.
+    ///
+    /// Active results must be kept no longer than the token's own `exp`
+    /// (see [`cache_ttl`]) and skipped for JTIs revoked within the last TTL.
+    async fn insert(&self, token: &str, response: &IntrospectionResponse);
+
+    /// Stops serving cached results for a revoked JTI and blocks re-caching
+    /// it for one TTL.
+    async fn invalidate_jti(&self, jti: &str);
+}
+
+/// How long `response` may be cached with a cache TTL of `ttl`, or `None`
+/// if an active result lacks an expiry or has already expired.
+pub fn cache_ttl(response: &IntrospectionResponse, ttl: Duration) -> Option<Duration> {
+    if !response.active {
+        return Some(ttl);
+    }
+    let remaining = response.exp? - OffsetDateTime::now_utc().unix_timestamp();
+    if remaining <= 0 {
+        return None;
+    }
+    Some(ttl.min(Duration::from_secs(remaining as u64)))
+}
+
+/// In-memory cache of access token introspection results.
+pub struct LocalIntrospectionCache {
+    state: Mutex<CacheState>,
+    ttl: Duration,
+    max_size: usize,
+}
+
+impl LocalIntrospectionCache {
+    /// Creates a cache with the given TTL.
+    #[must_use]
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            state: Mutex::new(CacheState::default()),
+            ttl,
+            max_size: DEFAULT_MAX_SIZE,
+        }
+    }
+
+    /// Sets the maximum number of cached results (builder pattern).
+    #[must_use]
+    pub fn with_max_size(mut self, max_size: usize) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    /// Number of cached results.
+    pub fn len(&self) -> usize {
+        self.state
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .entries
+            .len()
+    }
+
+    /// Whether the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[async_trait]
+impl IntrospectionCache for LocalIntrospectionCache {
+    async fn get(&self, token: &str) -> Option<IntrospectionResponse> {
+        let key = RefreshToken::hash_token(token);
+        let now = Instant::now();
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        match state.entries.get(&key) {
+            Some(entry) if entry.expires_at > now => Some(entry.response.clone()),
+            Some(_) => {
+                state.remove(&key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    async fn insert(&self, token: &str, response: &IntrospectionResponse) {
+        let now = Instant::now();
+        let Some(ttl) = cache_ttl(response, self.ttl) else {
+            return;
+        };
+
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.tombstones.retain(|_, until| *until > now);
+        if let Some(jti) = &response.jti
+            && state.tombstones.contains_key(jti)
+        {
+            return;
+        }
+        if state.entries.len() >= self.max_size {
+            state.cleanup_expired(now);
+            if state.entries.len() >= self.max_size {
+                tracing::warn!(
+                    max_size = self.max_size,
+                    "Introspection cache at capacity, skipping insertion"
+                );
+                return;
+            }
+        }
+
+        let key = RefreshToken::hash_token(token);
+        if let Some(jti) = &response.jti {
+            state
+                .by_jti
+                .entry(jti.clone())
+                .or_default()
+                .push(key.clone());
+        }
+        state.entries.insert(
+            key,
+            CachedIntrospection {
+                response: response.clone(),
+                expires_at: now + ttl,
+            },
+        );
+    }
+
+    async fn invalidate_jti(&self, jti: &str) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(keys) = state.by_jti.remove(jti) {
+            for key in keys {
+                state.entries.remove(&key);
+            }
+        }
+        state
+            .tombstones
+            .insert(jti.to_string(), Instant::now() + self.ttl);
+    }
+}
+
+impl Default for LocalIntrospectionCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_INTROSPECTION_CACHE_TTL)
+    }
+}
+
+impl CacheState {
+    fn remove(&mut self, key: &str) {
+        if let Some(entry) = self.entries.remove(key)
+            && let Some(jti) = entry.response.jti
+            && let Some(keys) = self.by_jti.get_mut(&jti)
+        {
+            keys.retain(|k| k != key);
+            if keys.is_empty() {
+                self.by_jti.remove(&jti);
+            }
+        }
+    }
+
+    fn cleanup_expired(&mut self, now: Instant) {
+        let expired: Vec<String> = self
+            .entries
+            .iter()
+            .filter(|(_, entry)| entry.expires_at <= now)
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in expired {
+            self.remove(&key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn active(jti: &str) -> IntrospectionResponse {
+        IntrospectionResponse::active()
+            .with_jti(jti.to_string())
+            .with_exp(OffsetDateTime::now_utc().unix_timestamp() + 3600)
+    }
+
+    #[tokio::test]
+    async fn test_caches_results_by_token() {
+        let cache = LocalIntrospectionCache::default();
+        cache.insert("token-a", &active("jti-a")).await;
+        cache
+            .insert("token-b", &IntrospectionResponse::inactive())
+            .await;
+
+        assert!(cache.get("token-a").await.unwrap().active);
+        assert!(!cache.get("token-b").await.unwrap().active);
+        assert!(cache.get("token-c").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_revoked_jti_is_never_served_active() {
+        let cache = LocalIntrospectionCache::default();
+        cache.insert("token-a", &active("jti-a")).await;
+
+        cache.invalidate_jti("jti-a").await;
+        assert!(cache.get("token-a").await.is_none());
+
+        // A lookup that raced the revocation cannot re-populate the entry
+        cache.insert("token-a", &active("jti-a")).await;
+        assert!(cache.get("token-a").await.is_none());
+        assert!(cache.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_expired_tokens_and_entries_are_not_served() {
+        let cache = LocalIntrospectionCache::default();
+        let expired = IntrospectionResponse::active()
+            .with_jti("jti-old".to_string())
+            .with_exp(OffsetDateTime::now_utc().unix_timestamp() - 1);
+        cache.insert("token-old", &expired).await;
+        assert!(cache.get("token-old").await.is_none());
+
+        let cache = LocalIntrospectionCache::new(Duration::ZERO);
+        cache.insert("token-a", &active("jti-a")).await;
+        assert!(cache.get("token-a").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_respects_max_size() {
+        let cache = LocalIntrospectionCache::default().with_max_size(1);
+        cache.insert("token-a", &active("jti-a")).await;
+        cache.insert("token-b", &active("jti-b")).await;
+        assert_eq!(cache.len(), 1);
+        assert!(cache.get("token-b").await.is_none());
+    }
+}
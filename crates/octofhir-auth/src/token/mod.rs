@@ -9,6 +9,7 @@
 //! - JWT encoding and decoding
 
 pub mod introspection;
+pub mod introspection_cache;
 pub mod jwt;
 pub mod revocation;
 pub mod service;
@@ -16,6 +17,9 @@ pub mod service;
 pub use introspection::{
     IntrospectionError, IntrospectionErrorCode, IntrospectionRequest, IntrospectionResponse,
 };
+pub use introspection_cache::{
+    DEFAULT_INTROSPECTION_CACHE_TTL, IntrospectionCache, LocalIntrospectionCache, cache_ttl,
+};
 pub use jwt::{
     AccessTokenClaims, AccessTokenClaimsBuilder, IdTokenClaims, Jwk, Jwks, JwtError, JwtService,
     SigningAlgorithm, SigningKeyPair,
@@ -33,6 +33,7 @@ use crate::storage::revoked_token::RevokedTokenStorage;
 use crate::storage::session::SessionStorage;
 use crate::storage::user::UserStorage;
 use crate::token::introspection::{IntrospectionRequest, IntrospectionResponse};
+use crate::token::introspection_cache::IntrospectionCache;
 use crate::token::jwt::{AccessTokenClaims, IdTokenClaims, JwtService};
 use crate::token::revocation::{RevocationRequest, TokenTypeHint};
 use crate::types::Client;
@@ -55,6 +56,9 @@ pub struct TokenService {
     /// User storage for loading fhir_user (optional).
     user_storage: Option<Arc<dyn UserStorage>>,
 
+    /// Cache of access token introspection results (optional).
+    introspection_cache: Option<Arc<dyn IntrospectionCache>>,
+
     /// Service configuration.
     config: TokenConfig,
 }
@@ -83,6 +87,11 @@ pub struct TokenConfig {
     /// When true, the old token is revoked and a new one is issued.
     /// When false, the same token is reused.
     pub rotate_refresh_tokens: bool,
+
+    /// Absolute maximum lifetime of a refresh token family.
+    /// Measured from the original authorization; rotation cannot extend a
+    /// session past it. `None` disables the cap.
+    pub refresh_token_max_lifetime: Option<Duration>,
 }
 
 impl TokenConfig {
@@ -101,6 +110,7 @@ impl TokenConfig {
             refresh_token_lifetime: Duration::days(90),
             id_token_lifetime: Duration::hours(1),
             rotate_refresh_tokens: true, // Default to rotating for security
+            refresh_token_max_lifetime: None,
         }
     }
 
@@ -131,6 +141,13 @@ impl TokenConfig {
         self.rotate_refresh_tokens = rotate;
         self
     }
+
+    /// Sets the absolute maximum refresh token family lifetime.
+    #[must_use]
+    pub fn with_refresh_token_max_lifetime(mut self, lifetime: Option<Duration>) -> Self {
+        self.refresh_token_max_lifetime = lifetime;
+        self
+    }
 }
 
 impl TokenService {
@@ -157,6 +174,7 @@ impl TokenService {
             refresh_token_storage,
             revoked_token_storage,
             user_storage: None,
+            introspection_cache: None,
             config,
         }
     }
@@ -176,6 +194,27 @@ impl TokenService {
         self.user_storage = Some(user_storage);
     }
 
+    /// Sets the introspection result cache (builder pattern).
+    ///
+    /// Access tokens revoked through [`TokenService::revoke`] are dropped
+    /// from the cache immediately. Callers that revoke JTIs directly in
+    /// `RevokedTokenStorage` must call [`IntrospectionCache::invalidate_jti`].
+    #[must_use]
+    pub fn with_introspection_cache(mut self, cache: Arc<dyn IntrospectionCache>) -> Self {
+        self.introspection_cache = Some(cache);
+        self
+    }
+
+    /// Sets the introspection result cache (mutable reference).
+    pub fn set_introspection_cache(&mut self, cache: Arc<dyn IntrospectionCache>) {
+        self.introspection_cache = Some(cache);
+    }
+
+    /// Returns the introspection result cache, if configured.
+    pub fn introspection_cache(&self) -> Option<&Arc<dyn IntrospectionCache>> {
+        self.introspection_cache.as_ref()
+    }
+
     /// Helper to load fhir_user from user storage if available.
     async fn load_fhir_user(&self, user_id: Option<&str>) -> Option<String> {
         let user_id = user_id?;
@@ -221,12 +260,13 @@ impl TokenService {
     /// - Authorization code is invalid, expired, or consumed
     /// - Client ID doesn't match
     /// - Redirect URI doesn't match
-    /// - PKCE verification fails
+    /// - PKCE verification fails, or the code was issued without PKCE to a
+    ///   client that requires it
     ///
     /// # Security
     ///
     /// - Authorization codes are consumed atomically (one-time use)
-    /// - PKCE is always verified (no fallback)
+    /// - PKCE is verified whenever the code was issued with a challenge
     /// - Tokens are never logged
     pub async fn exchange_code(
         &self,
@@ -249,11 +289,6 @@ impl TokenService {
             .as_ref()
             .ok_or_else(|| AuthError::invalid_grant("Missing redirect_uri parameter"))?;
 
-        let code_verifier = request
-            .code_verifier
-            .as_ref()
-            .ok_or_else(|| AuthError::invalid_grant("Missing code_verifier parameter"))?;
-
         // 3. Find and consume session (atomic one-time use)
         let session = self.session_storage.consume(code).await.map_err(|e| {
             // Map storage errors to appropriate grant errors
@@ -284,6 +319,11 @@ impl TokenService {
 
         // 7. Verify PKCE if present in session
         if let Some(ref challenge_str) = session.code_challenge {
+            let code_verifier = request
+                .code_verifier
+                .as_ref()
+                .ok_or_else(|| AuthError::invalid_grant("Missing code_verifier parameter"))?;
+
             let challenge = PkceChallenge::new(challenge_str.clone())
                 .map_err(|e| AuthError::invalid_grant(format!("Invalid PKCE challenge: {}", e)))?;
 
@@ -293,13 +333,21 @@ impl TokenService {
             challenge
                 .verify(&verifier)
                 .map_err(|_| AuthError::PkceVerificationFailed)?;
-        } else {
+        } else if client.requires_pkce() {
+            // Code issued without PKCE, e.g. before the client was configured
+            // to require it
+            return Err(AuthError::invalid_grant(
+                "Authorization code was issued without PKCE, which this client requires",
+            ));
+        } else if request
+            .code_verifier
+            .as_ref()
+            .is_some_and(|v| !v.is_empty())
+        {
             // No PKCE in session (confidential client), code_verifier should not be provided
-            if !code_verifier.is_empty() {
-                return Err(AuthError::invalid_grant(
-                    "code_verifier provided but session was created without PKCE",
-                ));
-            }
+            return Err(AuthError::invalid_grant(
+                "code_verifier provided but session was created without PKCE",
+            ));
         }
 
         // 8. Generate tokens
@@ -425,8 +473,9 @@ impl TokenService {
             scope: session.scope.clone(),
             launch_context: session.launch_context.clone(),
             created_at: now,
-            expires_at: Some(now + lifetime),
+            expires_at: self.cap_refresh_expiry(now, Some(now + lifetime)),
             revoked_at: None,
+            family_issued_at: Some(now),
         };
 
         // Store token
@@ -702,6 +751,7 @@ impl TokenService {
             let new_token_value = RefreshToken::generate_token();
             let new_token_hash = RefreshToken::hash_token(&new_token_value);
 
+            let family_issued_at = stored_token.family_issued_at();
             let new_token = RefreshToken {
                 id: Uuid::new_v4(),
                 token_hash: new_token_hash,
@@ -710,8 +760,10 @@ impl TokenService {
                 scope: scope.clone(),
                 launch_context: stored_token.launch_context.clone(),
                 created_at: now,
-                expires_at: stored_token.expires_at, // Keep original expiration
+                // Keep original expiration, bounded by the family's absolute cap
+                expires_at: self.cap_refresh_expiry(family_issued_at, stored_token.expires_at),
                 revoked_at: None,
+                family_issued_at: Some(family_issued_at),
             };
 
             self.refresh_token_storage.create(&new_token).await?;
@@ -772,9 +824,36 @@ impl TokenService {
             return Err(AuthError::invalid_grant("Refresh token has expired"));
         }
 
+        // Check absolute session lifetime (covers tokens issued before the cap)
+        if self.exceeds_max_lifetime(token) {
+            return Err(AuthError::invalid_grant(
+                "Refresh token has exceeded the maximum session lifetime",
+            ));
+        }
+
         Ok(())
     }
 
+    /// Bounds a refresh token expiry by the absolute family lifetime.
+    fn cap_refresh_expiry(
+        &self,
+        family_issued_at: OffsetDateTime,
+        expires_at: Option<OffsetDateTime>,
+    ) -> Option<OffsetDateTime> {
+        let Some(max) = self.config.refresh_token_max_lifetime else {
+            return expires_at;
+        };
+        let cap = family_issued_at + max;
+        Some(expires_at.map_or(cap, |exp| exp.min(cap)))
+    }
+
+    /// Returns `true` if the token's family is older than the absolute cap.
+    fn exceeds_max_lifetime(&self, token: &RefreshToken) -> bool {
+        self.config
+            .refresh_token_max_lifetime
+            .is_some_and(|max| OffsetDateTime::now_utc() > token.family_issued_at() + max)
+    }
+
     /// Determines the scope to use for a refreshed token.
     ///
     /// Per OAuth 2.0 spec, the scope can be narrowed but not expanded.
@@ -890,6 +969,9 @@ impl TokenService {
                 self.revoked_token_storage
                     .revoke(&token_data.claims.jti, expires_at)
                     .await?;
+                if let Some(cache) = &self.introspection_cache {
+                    cache.invalidate_jti(&token_data.claims.jti).await;
+                }
 
                 tracing::info!(
                     jti = %token_data.claims.jti,
@@ -1033,8 +1115,26 @@ impl TokenService {
         }
     }
 
-    /// Introspects an access token (JWT).
+    /// Introspects an access token (JWT), consulting the cache first.
+    ///
+    /// Only active results are cached; failures to read the revocation list
+    /// are always retried against storage.
     async fn introspect_access_token(&self, token: &str) -> IntrospectionResponse {
+        let Some(cache) = &self.introspection_cache else {
+            return self.introspect_access_token_uncached(token).await;
+        };
+        if let Some(response) = cache.get(token).await {
+            return response;
+        }
+        let response = self.introspect_access_token_uncached(token).await;
+        if response.active {
+            cache.insert(token, &response).await;
+        }
+        response
+    }
+
+    /// Introspects an access token (JWT) against storage.
+    async fn introspect_access_token_uncached(&self, token: &str) -> IntrospectionResponse {
         // Try to decode the token - use regular decode which validates expiration
         match self.jwt_service.decode::<AccessTokenClaims>(token) {
             Ok(token_data) => {
@@ -1122,7 +1222,7 @@ impl TokenService {
                 }
 
                 // Check if expired
-                if stored_token.is_expired() {
+                if stored_token.is_expired() || self.exceeds_max_lifetime(&stored_token) {
                     tracing::debug!("Token introspection: refresh token is expired");
                     return IntrospectionResponse::inactive();
                 }
@@ -1253,8 +1353,9 @@ impl TokenService {
             scope: scope.to_string(),
             launch_context: None,
             created_at: now,
-            expires_at: Some(now + lifetime),
+            expires_at: self.cap_refresh_expiry(now, Some(now + lifetime)),
             revoked_at: None,
+            family_issued_at: Some(now),
         };
 
         // Store token
@@ -1270,6 +1371,7 @@ mod tests {
     use super::*;
     use crate::oauth::pkce::PkceChallenge;
     use crate::oauth::session::LaunchContext;
+    use crate::token::introspection_cache::LocalIntrospectionCache;
     use crate::token::jwt::{SigningAlgorithm, SigningKeyPair};
     use crate::types::GrantType;
     use std::collections::HashMap;
@@ -1764,6 +1866,58 @@ mod tests {
         assert!(matches!(result, Err(AuthError::PkceVerificationFailed)));
     }
 
+    fn code_request(code_verifier: Option<&str>) -> TokenRequest {
+        TokenRequest {
+            grant_type: "authorization_code".to_string(),
+            code: Some("test-auth-code".to_string()),
+            redirect_uri: Some("https://app.example.com/callback".to_string()),
+            code_verifier: code_verifier.map(str::to_string),
+            client_id: None,
+            client_secret: None,
+            client_assertion_type: None,
+            client_assertion: None,
+            refresh_token: None,
+            scope: None,
+            username: None,
+            password: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_exchange_code_missing_verifier() {
+        let (service, session_storage, _, _) = create_test_service();
+        let client = create_test_client();
+
+        session_storage.add_session(create_test_session(
+            "dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk",
+        ));
+
+        let result = service.exchange_code(&code_request(None), &client).await;
+        assert!(matches!(result, Err(AuthError::InvalidGrant { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_exchange_code_without_pkce() {
+        let (service, session_storage, _, _) = create_test_service();
+        let mut client = create_test_client();
+        client.confidential = true;
+
+        let mut session = create_test_session("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk");
+        session.code_challenge = None;
+        session.code_challenge_method = None;
+
+        // Confidential clients may skip PKCE
+        session_storage.add_session(session.clone());
+        let result = service.exchange_code(&code_request(None), &client).await;
+        assert!(result.is_ok());
+
+        // Not once they require it
+        client.pkce_required = Some(true);
+        session_storage.add_session(session);
+        let result = service.exchange_code(&code_request(None), &client).await;
+        assert!(matches!(result, Err(AuthError::InvalidGrant { .. })));
+    }
+
     #[tokio::test]
     async fn test_exchange_code_with_offline_access() {
         let (service, session_storage, refresh_storage, _) = create_test_service();
@@ -1911,6 +2065,7 @@ mod tests {
             created_at: now,
             expires_at: Some(now + Duration::days(90)),
             revoked_at: None,
+            family_issued_at: None,
         }
     }
 
@@ -1954,6 +2109,67 @@ mod tests {
         assert!(response.id_token.is_none());
     }
 
+    #[tokio::test]
+    async fn test_refresh_rejected_past_max_lifetime() {
+        let (service, session_storage, refresh_storage, revoked_storage) = create_test_service();
+        let config = service
+            .config
+            .clone()
+            .with_refresh_token_max_lifetime(Some(Duration::days(30)));
+        let service = TokenService::new(
+            service.jwt_service.clone(),
+            session_storage,
+            refresh_storage.clone(),
+            revoked_storage,
+            config,
+        );
+        let client = create_refresh_client();
+        let now = OffsetDateTime::now_utc();
+
+        // Family started 10 days ago: rotation keeps the origin and caps expiry
+        let token_value = "fresh-family-refresh-token-1234567890";
+        let mut stored = create_stored_refresh_token(&client.client_id, "openid", token_value);
+        stored.family_issued_at = Some(now - Duration::days(10));
+        refresh_storage.create(&stored).await.unwrap();
+
+        let mut request = TokenRequest {
+            grant_type: "refresh_token".to_string(),
+            code: None,
+            redirect_uri: None,
+            code_verifier: None,
+            client_id: None,
+            client_secret: None,
+            client_assertion_type: None,
+            client_assertion: None,
+            refresh_token: Some(token_value.to_string()),
+            scope: None,
+            username: None,
+            password: None,
+        };
+        let response = service.refresh(&request, &client).await.unwrap();
+        let rotated_value = response.refresh_token.unwrap();
+        let rotated = refresh_storage
+            .find_by_hash(&RefreshToken::hash_token(&rotated_value))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(rotated.family_issued_at, stored.family_issued_at);
+        assert_eq!(
+            rotated.expires_at,
+            Some(now - Duration::days(10) + Duration::days(30))
+        );
+
+        // Family started 31 days ago: rejected even though the token itself is unexpired
+        let token_value = "old-family-refresh-token-1234567890";
+        let mut stored = create_stored_refresh_token(&client.client_id, "openid", token_value);
+        stored.family_issued_at = Some(now - Duration::days(31));
+        refresh_storage.create(&stored).await.unwrap();
+
+        request.refresh_token = Some(token_value.to_string());
+        let result = service.refresh(&request, &client).await;
+        assert!(matches!(result, Err(AuthError::InvalidGrant { .. })));
+    }
+
     #[tokio::test]
     async fn test_refresh_invalid_grant_type() {
         let (service, _, _, _) = create_test_service();
@@ -2047,6 +2263,7 @@ mod tests {
             created_at: now - Duration::days(100),
             expires_at: Some(now - Duration::days(1)), // Expired yesterday
             revoked_at: None,
+            family_issued_at: None,
         };
         refresh_storage.create(&expired_token).await.unwrap();
 
@@ -2087,6 +2304,7 @@ mod tests {
             created_at: now,
             expires_at: Some(now + Duration::days(90)),
             revoked_at: Some(now), // Revoked now
+            family_issued_at: None,
         };
         refresh_storage.create(&revoked_token).await.unwrap();
 
@@ -2226,6 +2444,7 @@ mod tests {
             created_at: now,
             expires_at: Some(now + Duration::days(90)),
             revoked_at: None,
+            family_issued_at: None,
         };
         refresh_storage.create(&token_with_context).await.unwrap();
 
@@ -2706,6 +2925,48 @@ mod tests {
         assert!(!response.active);
     }
 
+    #[tokio::test]
+    async fn test_introspect_cached_token_inactive_after_revoke() {
+        let (service, session_storage, _, _) = create_test_service();
+        let cache = Arc::new(LocalIntrospectionCache::default());
+        let service = service.with_introspection_cache(cache.clone());
+        let client = create_test_client();
+
+        let verifier = "dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk";
+        session_storage.add_session(create_test_session(verifier));
+
+        let request = TokenRequest {
+            grant_type: "authorization_code".to_string(),
+            code: Some("test-auth-code".to_string()),
+            redirect_uri: Some("https://app.example.com/callback".to_string()),
+            code_verifier: Some(verifier.to_string()),
+            client_id: Some("test-client".to_string()),
+            client_secret: None,
+            client_assertion_type: None,
+            client_assertion: None,
+            refresh_token: None,
+            scope: None,
+            username: None,
+            password: None,
+        };
+        let token_response = service.exchange_code(&request, &client).await.unwrap();
+
+        let introspect_request = IntrospectionRequest {
+            token: token_response.access_token.clone(),
+            token_type_hint: Some(TokenTypeHint::AccessToken),
+        };
+        assert!(service.introspect(&introspect_request).await.active);
+        assert_eq!(cache.len(), 1);
+
+        let revoke_request = RevocationRequest {
+            token: token_response.access_token.clone(),
+            token_type_hint: Some(TokenTypeHint::AccessToken),
+        };
+        service.revoke(&revoke_request, &client).await.unwrap();
+
+        assert!(!service.introspect(&introspect_request).await.active);
+    }
+
     #[tokio::test]
     async fn test_introspect_invalid_access_token() {
         let (service, _, _, _) = create_test_service();
@@ -2764,6 +3025,7 @@ mod tests {
             created_at: now - Duration::days(100),
             expires_at: Some(now - Duration::days(1)), // Expired
             revoked_at: None,
+            family_issued_at: None,
         };
         refresh_storage.create(&expired_token).await.unwrap();
 
@@ -2794,6 +3056,7 @@ mod tests {
             created_at: now,
             expires_at: Some(now + Duration::days(90)),
             revoked_at: Some(now), // Revoked
+            family_issued_at: None,
         };
         refresh_storage.create(&revoked_token).await.unwrap();
 
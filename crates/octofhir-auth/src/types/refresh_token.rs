@@ -73,6 +73,16 @@ pub struct RefreshToken {
         with = "time::serde::rfc3339::option"
     )]
     pub revoked_at: Option<OffsetDateTime>,
+
+    /// When the token family was originally authorized.
+    /// Carried over on rotation to enforce the absolute session lifetime.
+    /// `None` for tokens issued before this was tracked.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "time::serde::rfc3339::option"
+    )]
+    pub family_issued_at: Option<OffsetDateTime>,
 }
 
 impl RefreshToken {
@@ -96,6 +106,13 @@ impl RefreshToken {
         !self.is_expired() && !self.is_revoked()
     }
 
+    /// Returns when the token family was originally authorized, falling back
+    /// to this token's creation time.
+    #[must_use]
+    pub fn family_issued_at(&self) -> OffsetDateTime {
+        self.family_issued_at.unwrap_or(self.created_at)
+    }
+
     /// Hash a token value using SHA-256.
     ///
     /// This is used both when storing new tokens and when looking up
@@ -242,6 +259,7 @@ mod tests {
             created_at: OffsetDateTime::now_utc(),
             expires_at,
             revoked_at,
+            family_issued_at: None,
         }
     }
 }
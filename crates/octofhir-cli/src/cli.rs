@@ -53,6 +53,8 @@ pub enum Commands {
     Delete(DeleteArgs),
     /// View resource history
     History(HistoryArgs),
+    /// Compare two resources, or two versions of one resource
+    Diff(DiffArgs),
     /// Search for resources
     Search(SearchArgs),
     /// Get server CapabilityStatement
@@ -127,6 +129,21 @@ pub struct HistoryArgs {
     pub reference: String,
 }
 
+#[derive(clap::Args)]
+pub struct DiffArgs {
+    /// Resource reference (e.g. Patient/123 or Patient/123/_history/2)
+    pub reference: String,
+    /// Reference of the resource to compare against
+    #[arg(required_unless_present = "versions", conflicts_with = "versions")]
+    pub other: Option<String>,
+    /// Compare two versions of the resource instead (e.g. --versions 1 3)
+    #[arg(long, num_args = 2, value_names = ["V1", "V2"])]
+    pub versions: Option<Vec<String>>,
+    /// Also report changes to meta.versionId and meta.lastUpdated
+    #[arg(long)]
+    pub include_meta: bool,
+}
+
 #[derive(clap::Args)]
 pub struct SearchArgs {
     /// Resource type (e.g. Patient)
@@ -47,6 +47,16 @@ impl FhirClient {
         handle_response(resp).await
     }
 
+    pub async fn vread(&self, resource_type: &str, id: &str, version: &str) -> Result<Value> {
+        let url = self.fhir_url(&format!("{resource_type}/{id}/_history/{version}"));
+        let resp = self
+            .request(reqwest::Method::GET, &url)
+            .send()
+            .await
+            .context("Failed to connect to server")?;
+        handle_response(resp).await
+    }
+
     pub async fn create(&self, resource_type: &str, body: &Value) -> Result<Value> {
         let url = self.fhir_url(resource_type);
         let resp = self
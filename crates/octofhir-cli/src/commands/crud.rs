@@ -8,7 +8,7 @@ use crate::cli::OutputFormat;
 use crate::client::FhirClient;
 use crate::output::{print_success, print_value};
 
-fn parse_reference(reference: &str) -> Result<(&str, &str)> {
+pub(crate) fn parse_reference(reference: &str) -> Result<(&str, &str)> {
     let parts: Vec<&str> = reference.splitn(2, '/').collect();
     if parts.len() != 2 {
         anyhow::bail!("Invalid reference \"{reference}\". Expected format: ResourceType/id");
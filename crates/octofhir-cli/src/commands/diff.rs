@@ -0,0 +1,208 @@
+use anyhow::Result;
+use colored::Colorize;
+use serde_json::{Value, json};
+
+use crate::cli::{DiffArgs, OutputFormat};
+use crate::client::FhirClient;
+use crate::commands::crud::parse_reference;
+use crate::output::print_value;
+
+/// Paths that change on every write; skipped unless `--include-meta`.
+const VOLATILE_META_PATHS: &[&str] = &["meta.versionId", "meta.lastUpdated"];
+
+enum Change {
+    Added {
+        path: String,
+        value: Value,
+    },
+    Removed {
+        path: String,
+        value: Value,
+    },
+    Changed {
+        path: String,
+        from: Value,
+        to: Value,
+    },
+}
+
+impl Change {
+    fn path(&self) -> &str {
+        match self {
+            Change::Added { path, .. }
+            | Change::Removed { path, .. }
+            | Change::Changed { path, .. } => path,
+        }
+    }
+}
+
+pub async fn diff(
+    client: &FhirClient,
+    args: &DiffArgs,
+    format: Option<OutputFormat>,
+) -> Result<()> {
+    let (left_label, left, right_label, right) = match (&args.other, &args.versions) {
+        (_, Some(versions)) => {
+            let (rt, id) = parse_reference(&args.reference)?;
+            let [v1, v2] = versions.as_slice() else {
+                anyhow::bail!("--versions takes exactly two version ids");
+            };
+            (
+                format!("{rt}/{id}/_history/{v1}"),
+                client.vread(rt, id, v1).await?,
+                format!("{rt}/{id}/_history/{v2}"),
+                client.vread(rt, id, v2).await?,
+            )
+        }
+        (Some(other), None) => (
+            args.reference.clone(),
+            fetch(client, &args.reference).await?,
+            other.clone(),
+            fetch(client, other).await?,
+        ),
+        (None, None) => anyhow::bail!("Specify a second reference or --versions V1 V2"),
+    };
+
+    let mut changes = Vec::new();
+    diff_values("", &left, &right, &mut changes);
+    if !args.include_meta {
+        changes.retain(|c| !VOLATILE_META_PATHS.contains(&c.path()));
+    }
+
+    match format {
+        Some(format @ (OutputFormat::Json | OutputFormat::Yaml)) => {
+            print_value(
+                &changes_to_json(&left_label, &right_label, &changes),
+                format,
+            );
+        }
+        _ => print_changes(&left_label, &right_label, &changes),
+    }
+    Ok(())
+}
+
+async fn fetch(client: &FhirClient, reference: &str) -> Result<Value> {
+    // The id may carry a `/_history/{vid}` suffix, which reads that version
+    let (rt, id) = parse_reference(reference)?;
+    client.read(rt, id).await
+}
+
+/// Collect the differences between `left` and `right` below `path`.
+///
+/// Objects are compared key by key and arrays index by index, so an element
+/// inserted at the front of an array shows up as a change of every later one.
+fn diff_values(path: &str, left: &Value, right: &Value, changes: &mut Vec<Change>) {
+    if left == right {
+        return;
+    }
+
+    match (left, right) {
+        (Value::Object(l), Value::Object(r)) => {
+            for (key, lv) in l {
+                let child = child_path(path, key);
+                match r.get(key) {
+                    Some(rv) => diff_values(&child, lv, rv, changes),
+                    None => changes.push(Change::Removed {
+                        path: child,
+                        value: lv.clone(),
+                    }),
+                }
+            }
+            for (key, rv) in r {
+                if !l.contains_key(key) {
+                    changes.push(Change::Added {
+                        path: child_path(path, key),
+                        value: rv.clone(),
+                    });
+                }
+            }
+        }
+        (Value::Array(l), Value::Array(r)) => {
+            for i in 0..l.len().max(r.len()) {
+                let child = format!("{path}[{i}]");
+                match (l.get(i), r.get(i)) {
+                    (Some(lv), Some(rv)) => diff_values(&child, lv, rv, changes),
+                    (Some(lv), None) => changes.push(Change::Removed {
+                        path: child,
+                        value: lv.clone(),
+                    }),
+                    (None, Some(rv)) => changes.push(Change::Added {
+                        path: child,
+                        value: rv.clone(),
+                    }),
+                    (None, None) => {}
+                }
+            }
+        }
+        _ => changes.push(Change::Changed {
+            path: path.to_string(),
+            from: left.clone(),
+            to: right.clone(),
+        }),
+    }
+}
+
+fn child_path(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{path}.{key}")
+    }
+}
+
+fn changes_to_json(left: &str, right: &str, changes: &[Change]) -> Value {
+    let mut added = Vec::new();
+    let mut removed = Vec::new();
+    let mut changed = Vec::new();
+    for change in changes {
+        match change {
+            Change::Added { path, value } => added.push(json!({"path": path, "value": value})),
+            Change::Removed { path, value } => removed.push(json!({"path": path, "value": value})),
+            Change::Changed { path, from, to } => {
+                changed.push(json!({"path": path, "from": from, "to": to}))
+            }
+        }
+    }
+    json!({
+        "left": left,
+        "right": right,
+        "added": added,
+        "removed": removed,
+        "changed": changed,
+    })
+}
+
+fn print_changes(left: &str, right: &str, changes: &[Change]) {
+    println!("{} {}", "---".red(), left.red());
+    println!("{} {}", "+++".green(), right.green());
+
+    if changes.is_empty() {
+        println!("No differences.");
+        return;
+    }
+
+    for change in changes {
+        match change {
+            Change::Added { path, value } => {
+                println!("{}", format!("+ {path}: {}", compact(value)).green());
+            }
+            Change::Removed { path, value } => {
+                println!("{}", format!("- {path}: {}", compact(value)).red());
+            }
+            Change::Changed { path, from, to } => {
+                println!(
+                    "{} {}: {} → {}",
+                    "~".yellow(),
+                    path.yellow(),
+                    compact(from).red(),
+                    compact(to).green()
+                );
+            }
+        }
+    }
+    println!("{} difference(s)", changes.len());
+}
+
+fn compact(value: &Value) -> String {
+    serde_json::to_string(value).unwrap_or_default()
+}
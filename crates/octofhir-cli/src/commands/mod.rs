@@ -1,4 +1,5 @@
 pub mod auth;
 pub mod crud;
+pub mod diff;
 pub mod search;
 pub mod server;
@@ -100,6 +100,11 @@ async fn run() -> Result<()> {
             let client = make_client(&server, profile)?;
             commands::crud::history(&client, &args.reference, format).await?;
         }
+        Commands::Diff(args) => {
+            let server = config::resolve_server(&cli.server, profile)?;
+            let client = make_client(&server, profile)?;
+            commands::diff::diff(&client, args, cli.format).await?;
+        }
         Commands::Search(args) => {
             let server = config::resolve_server(&cli.server, profile)?;
             let client = make_client(&server, profile)?;
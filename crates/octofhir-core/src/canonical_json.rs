@@ -0,0 +1,172 @@
+//! Deterministic JSON canonicalization for no-op update detection.
+//!
+//! Clients frequently round-trip resources with object keys in a different
+//! order. That does not change the meaning of the resource, so deciding
+//! whether an update changes anything must compare a canonical form instead
+//! of the raw bytes. Versions and ETags do not depend on it: they come from
+//! the stored `versionId`.
+//!
+//! The canonical form is:
+//!   - object keys sorted by their UTF-8 byte order,
+//!   - no insignificant whitespace,
+//!   - numbers written as `serde_json` holds them. With its
+//!     `arbitrary_precision` feature (on in the server's default
+//!     `arbitrary-precision` feature) that is their text as parsed, so `1.0`
+//!     and `1` differ, and so do `1.00000000000000001` and `1.0`. Without it
+//!     decimals go through `f64`, and the latter two compare equal,
+//!   - strings escaped exactly as `serde_json` escapes them.
+//!
+//! Resources are not stored in this form. The `resource` column is JSONB,
+//! which drops whitespace and orders keys its own way on every write, so
+//! rewriting a resource to sorted keys before storing it changes nothing
+//! that is kept.
+
+use serde_json::{Map, Number, Value};
+
+/// Serialize a JSON value to its canonical string form.
+pub fn to_canonical_string(value: &Value) -> String {
+    let mut out = String::new();
+    write_value(value, &mut out);
+    out
+}
+
+/// Returns a copy of `resource` with server-managed version metadata removed.
+///
+/// Strips `meta.versionId` and `meta.lastUpdated`, and drops `meta` entirely
+/// when nothing else remains in it. The result is what should be compared when
+/// deciding whether an update carries any new content.
+pub fn strip_version_metadata(resource: &Value) -> Value {
+    let mut stripped = resource.clone();
+    if let Some(obj) = stripped.as_object_mut() {
+        let meta_empty = match obj.get_mut("meta").and_then(Value::as_object_mut) {
+            Some(meta) => {
+                meta.remove("versionId");
+                meta.remove("lastUpdated");
+                meta.is_empty()
+            }
+            None => false,
+        };
+        if meta_empty {
+            obj.remove("meta");
+        }
+    }
+    stripped
+}
+
+/// Returns true when two resources have the same content, ignoring key order
+/// and `meta.versionId` / `meta.lastUpdated`. Numbers compare by text, so
+/// `1.0` and `1` differ.
+pub fn resources_equivalent(a: &Value, b: &Value) -> bool {
+    to_canonical_string(&strip_version_metadata(a))
+        == to_canonical_string(&strip_version_metadata(b))
+}
+
+fn write_value(value: &Value, out: &mut String) {
+    match value {
+        Value::Null => out.push_str("null"),
+        Value::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        Value::Number(n) => write_number(n, out),
+        Value::String(s) => write_string(s, out),
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_value(item, out);
+            }
+            out.push(']');
+        }
+        Value::Object(map) => write_object(map, out),
+    }
+}
+
+fn write_object(map: &Map<String, Value>, out: &mut String) {
+    let mut keys: Vec<&String> = map.keys().collect();
+    keys.sort_unstable();
+
+    out.push('{');
+    for (i, key) in keys.into_iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        write_string(key, out);
+        out.push(':');
+        write_value(&map[key], out);
+    }
+    out.push('}');
+}
+
+fn write_number(n: &Number, out: &mut String) {
+    // With `arbitrary_precision` this is the number's original text;
+    // without it, the shortest text of its `f64` or integer value.
+    out.push_str(&n.to_string());
+}
+
+fn write_string(s: &str, out: &mut String) {
+    // serde_json string escaping cannot fail for &str.
+    out.push_str(&serde_json::to_string(s).unwrap_or_default());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_key_order_is_irrelevant() {
+        let a = json!({"resourceType": "Patient", "id": "p1", "name": [{"family": "Doe", "given": ["J"]}]});
+        let b = json!({"name": [{"given": ["J"], "family": "Doe"}], "id": "p1", "resourceType": "Patient"});
+        assert_eq!(to_canonical_string(&a), to_canonical_string(&b));
+    }
+
+    #[test]
+    fn test_canonical_output_is_sorted_and_compact() {
+        let v = json!({"b": 1, "a": [true, null, "x"]});
+        assert_eq!(to_canonical_string(&v), r#"{"a":[true,null,"x"],"b":1}"#);
+    }
+
+    #[test]
+    fn test_numbers_compare_by_text() {
+        let a: Value = serde_json::from_str(r#"{"value": 1.0}"#).unwrap();
+        let b: Value = serde_json::from_str(r#"{"value": 1}"#).unwrap();
+        assert_ne!(to_canonical_string(&a), to_canonical_string(&b));
+
+        let c: Value = serde_json::from_str(r#"{"value": 1.00000000000000001}"#).unwrap();
+        assert_ne!(to_canonical_string(&c), to_canonical_string(&b));
+
+        let d: Value = serde_json::from_str(r#"{"value": 1.5}"#).unwrap();
+        assert_eq!(to_canonical_string(&d), r#"{"value":1.5}"#);
+    }
+
+    #[test]
+    fn test_array_order_is_significant() {
+        let a = json!({"given": ["A", "B"]});
+        let b = json!({"given": ["B", "A"]});
+        assert_ne!(to_canonical_string(&a), to_canonical_string(&b));
+    }
+
+    #[test]
+    fn test_resources_equivalent_ignores_version_metadata() {
+        let stored = json!({
+            "resourceType": "Patient",
+            "id": "p1",
+            "meta": {"versionId": "3", "lastUpdated": "2024-01-01T00:00:00Z"},
+            "active": true
+        });
+        let submitted = json!({"active": true, "id": "p1", "resourceType": "Patient"});
+        assert!(resources_equivalent(&stored, &submitted));
+
+        let changed = json!({"active": false, "id": "p1", "resourceType": "Patient"});
+        assert!(!resources_equivalent(&stored, &changed));
+    }
+
+    #[test]
+    fn test_strip_version_metadata_keeps_other_meta() {
+        let v = json!({"meta": {"versionId": "1", "profile": ["http://example.org/p"]}});
+        assert_eq!(
+            strip_version_metadata(&v),
+            json!({"meta": {"profile": ["http://example.org/p"]}})
+        );
+    }
+}
@@ -4,7 +4,8 @@
 //! publish and subscribe to events. It uses tokio's broadcast channel for
 //! efficient multi-producer, multi-consumer messaging.
 
-use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
 use tokio::sync::broadcast;
 
 use super::types::{AuthEvent, ResourceEvent, SystemEvent};
@@ -35,6 +36,8 @@ const DEFAULT_BUFFER_SIZE: usize = 1024;
 #[derive(Clone)]
 pub struct EventBroadcaster {
     sender: broadcast::Sender<SystemEvent>,
+    /// Backlog gauges of tracked subscribers, incremented on every send.
+    backlogs: Arc<RwLock<Vec<Arc<AtomicUsize>>>>,
 }
 
 impl EventBroadcaster {
@@ -46,7 +49,10 @@ impl EventBroadcaster {
     /// Create a new broadcaster with custom buffer size.
     pub fn with_capacity(capacity: usize) -> Self {
         let (sender, _) = broadcast::channel(capacity);
-        Self { sender }
+        Self {
+            sender,
+            backlogs: Arc::new(RwLock::new(Vec::new())),
+        }
     }
 
     /// Create a new broadcaster wrapped in an Arc for sharing.
@@ -59,7 +65,21 @@ impl EventBroadcaster {
     /// Returns the number of subscribers that received the event.
     /// Returns 0 if there are no active subscribers.
     pub fn send(&self, event: SystemEvent) -> usize {
-        self.sender.send(event).unwrap_or_default()
+        // Count the event before a tracked subscriber can receive it
+        let backlogs = self.backlogs.read().unwrap_or_else(|e| e.into_inner());
+        for backlog in backlogs.iter() {
+            backlog.fetch_add(1, Ordering::SeqCst);
+        }
+        match self.sender.send(event) {
+            Ok(receivers) => receivers,
+            Err(_) => {
+                // No subscriber at all, so nobody will handle it
+                for backlog in backlogs.iter() {
+                    backlog.fetch_sub(1, Ordering::SeqCst);
+                }
+                0
+            }
+        }
     }
 
     /// Send a resource event to all subscribers.
@@ -113,6 +133,19 @@ impl EventBroadcaster {
         self.sender.subscribe()
     }
 
+    /// Subscribe to events, counting each event sent from now on in
+    /// `backlog`.
+    ///
+    /// The subscriber decrements `backlog` once it has handled an event, so
+    /// the gauge reads zero only when no received event is still waiting.
+    pub fn subscribe_tracked(&self, backlog: Arc<AtomicUsize>) -> broadcast::Receiver<SystemEvent> {
+        // Subscribe under the lock so no send falls between the two
+        let mut backlogs = self.backlogs.write().unwrap_or_else(|e| e.into_inner());
+        let receiver = self.sender.subscribe();
+        backlogs.push(backlog);
+        receiver
+    }
+
     /// Get the number of active subscribers.
     pub fn subscriber_count(&self) -> usize {
         self.sender.receiver_count()
@@ -7,6 +7,7 @@
 //! - **Composable**: Multiple hooks can react to the same event
 
 use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
 use super::types::{AuthEvent, AuthEventType, ResourceEvent, ResourceEventType, SystemEvent};
@@ -56,6 +57,25 @@ impl HookError {
     }
 }
 
+/// Delivery guarantee of a resource hook.
+///
+/// Best-effort hooks receive events from the in-process broadcast channel:
+/// an event is lost if the process stops between the write and the hook
+/// running, or if the dispatcher lags behind. At-least-once hooks receive
+/// events from the database outbox, which is written in the same
+/// transaction as the resource change; an event is delivered again until
+/// the hook acknowledges it by returning `Ok`, so the hook must tolerate
+/// duplicates.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeliveryGuarantee {
+    /// Deliver from the in-process event channel.
+    #[default]
+    BestEffort,
+    /// Deliver from the transactional outbox, retrying until acknowledged.
+    AtLeastOnce,
+}
+
 // ============================================================================
 // Hook Traits
 // ============================================================================
@@ -141,9 +161,21 @@ pub trait ResourceHook: Send + Sync {
         &[] // default: all event types
     }
 
+    /// Delivery guarantee this hook needs.
+    ///
+    /// Replaced by a per-name setting from
+    /// [`HookRegistry::with_delivery_overrides`](super::HookRegistry::with_delivery_overrides),
+    /// or at registration with
+    /// [`HookRegistry::register_resource_with_delivery`](super::HookRegistry::register_resource_with_delivery).
+    fn delivery(&self) -> DeliveryGuarantee {
+        DeliveryGuarantee::BestEffort
+    }
+
     /// Handle a resource change event.
     ///
-    /// This method should be quick and non-blocking.
+    /// This method should be quick and non-blocking. For an
+    /// [`DeliveryGuarantee::AtLeastOnce`] hook, returning `Ok` acknowledges
+    /// the event; an error or timeout has it delivered again.
     async fn handle(&self, event: &ResourceEvent) -> Result<(), HookError>;
 
     /// Called when the hook system starts.
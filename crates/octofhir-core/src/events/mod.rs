@@ -40,7 +40,8 @@
 //!
 //! // Start dispatcher
 //! let dispatcher = HookDispatcher::new(registry);
-//! tokio::spawn(dispatcher.run(broadcaster.subscribe()));
+//! let receiver = dispatcher.subscribe(&broadcaster);
+//! tokio::spawn(dispatcher.run(receiver));
 //!
 //! // Send events
 //! broadcaster.send_created("Patient", "123", json!({}));
@@ -61,7 +62,8 @@ pub mod types;
 // Re-export main types for convenience
 pub use broadcaster::EventBroadcaster;
 pub use hooks::{
-    AuthHook, AuthHookAdapter, HookError, ResourceHook, ResourceHookAdapter, SystemHook,
+    AuthHook, AuthHookAdapter, DeliveryGuarantee, HookError, ResourceHook, ResourceHookAdapter,
+    SystemHook,
 };
 pub use registry::{HookDispatcher, HookRegistry, HookSystemBuilder};
 pub use types::{AuthEvent, AuthEventType, ResourceEvent, ResourceEventType, SystemEvent};
@@ -3,18 +3,23 @@
 //! The registry manages hook registration and the dispatcher handles
 //! event routing to hooks with proper isolation and error handling.
 
+use std::collections::HashMap;
 use std::panic::AssertUnwindSafe;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::Duration;
 
 use futures_util::FutureExt;
 use tokio::sync::{RwLock, broadcast};
 use tracing::{debug, error, info, warn};
 
+use super::broadcaster::EventBroadcaster;
 use super::hooks::{
-    AuthHook, AuthHookAdapter, HookError, ResourceHook, ResourceHookAdapter, SystemHook,
+    AuthHook, AuthHookAdapter, DeliveryGuarantee, HookError, ResourceHook, ResourceHookAdapter,
+    SystemHook,
 };
 use super::types::SystemEvent;
+use crate::in_flight::InFlight;
 
 /// Default timeout for hook execution.
 const DEFAULT_HOOK_TIMEOUT: Duration = Duration::from_secs(30);
@@ -30,8 +35,16 @@ const DEFAULT_HOOK_TIMEOUT: Duration = Duration::from_secs(30);
 pub struct HookRegistry {
     /// All registered hooks.
     hooks: RwLock<Vec<Arc<dyn SystemHook>>>,
+    /// Resource hooks delivered from the outbox rather than by [`Self::dispatch`].
+    at_least_once: RwLock<Vec<Arc<dyn ResourceHook>>>,
+    /// Delivery guarantees by hook name, replacing what the hooks declare.
+    delivery_overrides: HashMap<String, DeliveryGuarantee>,
     /// Hook execution timeout.
     timeout: Duration,
+    /// Hook tasks currently running.
+    in_flight: InFlight,
+    /// Events sent to the dispatcher but not yet dispatched.
+    backlog: Arc<AtomicUsize>,
 }
 
 impl HookRegistry {
@@ -39,7 +52,11 @@ impl HookRegistry {
     pub fn new() -> Self {
         Self {
             hooks: RwLock::new(Vec::new()),
+            at_least_once: RwLock::new(Vec::new()),
+            delivery_overrides: HashMap::new(),
             timeout: DEFAULT_HOOK_TIMEOUT,
+            in_flight: InFlight::new(),
+            backlog: Arc::new(AtomicUsize::new(0)),
         }
     }
 
@@ -47,10 +64,24 @@ impl HookRegistry {
     pub fn with_timeout(timeout: Duration) -> Self {
         Self {
             hooks: RwLock::new(Vec::new()),
+            at_least_once: RwLock::new(Vec::new()),
+            delivery_overrides: HashMap::new(),
             timeout,
+            in_flight: InFlight::new(),
+            backlog: Arc::new(AtomicUsize::new(0)),
         }
     }
 
+    /// Set delivery guarantees by hook name, applied by
+    /// [`Self::register_resource`] in place of the guarantee a hook declares.
+    pub fn with_delivery_overrides(
+        mut self,
+        overrides: HashMap<String, DeliveryGuarantee>,
+    ) -> Self {
+        self.delivery_overrides = overrides;
+        self
+    }
+
     /// Register a system hook.
     pub async fn register(&self, hook: Arc<dyn SystemHook>) {
         let name = hook.name().to_string();
@@ -58,14 +89,42 @@ impl HookRegistry {
         debug!(hook = %name, "Registered system hook");
     }
 
-    /// Register a resource hook.
+    /// Register a resource hook with the delivery guarantee configured for
+    /// its name, or else the one it declares.
     ///
-    /// The hook is wrapped in an adapter to convert it to a SystemHook.
+    /// A best-effort hook is wrapped in an adapter to convert it to a
+    /// SystemHook; an at-least-once hook is left to the outbox relay.
     pub async fn register_resource<H: ResourceHook + 'static>(&self, hook: Arc<H>) {
+        let delivery = self
+            .delivery_overrides
+            .get(hook.name())
+            .copied()
+            .unwrap_or_else(|| hook.delivery());
+        self.register_resource_with_delivery(hook, delivery).await;
+    }
+
+    /// Register a resource hook, overriding the delivery guarantee it
+    /// declares.
+    ///
+    /// At-least-once hooks are not called by [`Self::dispatch`]; they only
+    /// receive events from an outbox relay, which must be running for them to
+    /// see any event.
+    pub async fn register_resource_with_delivery<H: ResourceHook + 'static>(
+        &self,
+        hook: Arc<H>,
+        delivery: DeliveryGuarantee,
+    ) {
         let name = hook.name().to_string();
-        let adapter = Arc::new(ResourceHookAdapter(hook));
-        self.hooks.write().await.push(adapter);
-        debug!(hook = %name, "Registered resource hook");
+        match delivery {
+            DeliveryGuarantee::BestEffort => {
+                let adapter = Arc::new(ResourceHookAdapter(hook));
+                self.hooks.write().await.push(adapter);
+            }
+            DeliveryGuarantee::AtLeastOnce => {
+                self.at_least_once.write().await.push(hook);
+            }
+        }
+        debug!(hook = %name, ?delivery, "Registered resource hook");
     }
 
     /// Register an auth hook.
@@ -80,7 +139,25 @@ impl HookRegistry {
 
     /// Get the number of registered hooks.
     pub async fn hook_count(&self) -> usize {
-        self.hooks.read().await.len()
+        self.hooks.read().await.len() + self.at_least_once.read().await.len()
+    }
+
+    /// Resource hooks registered with [`DeliveryGuarantee::AtLeastOnce`].
+    pub async fn at_least_once_hooks(&self) -> Vec<Arc<dyn ResourceHook>> {
+        self.at_least_once.read().await.clone()
+    }
+
+    /// Timeout applied to each hook invocation.
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// Events queued for dispatch plus hook tasks still running.
+    ///
+    /// Zero once every event emitted so far has been fully handled; used to
+    /// flush the event queue on shutdown.
+    pub fn pending(&self) -> usize {
+        self.backlog.load(Ordering::SeqCst) + self.in_flight.count()
     }
 
     /// Get hooks that match an event.
@@ -110,7 +187,9 @@ impl HookRegistry {
             let event = event.clone();
 
             // Each hook runs in an isolated task
+            let in_flight = self.in_flight.enter();
             tokio::spawn(async move {
+                let _in_flight = in_flight;
                 let hook_name = hook.name().to_string();
 
                 // Wrap in timeout
@@ -170,6 +249,11 @@ impl HookRegistry {
                 warn!(hook = %hook_name, error = %e, "Hook on_start failed");
             }
         }
+        for hook in self.at_least_once.read().await.iter() {
+            if let Err(e) = hook.on_start().await {
+                warn!(hook = %hook.name(), error = %e, "Hook on_start failed");
+            }
+        }
         Ok(())
     }
 
@@ -182,6 +266,11 @@ impl HookRegistry {
                 warn!(hook = %hook_name, error = %e, "Hook on_shutdown failed");
             }
         }
+        for hook in self.at_least_once.read().await.iter() {
+            if let Err(e) = hook.on_shutdown().await {
+                warn!(hook = %hook.name(), error = %e, "Hook on_shutdown failed");
+            }
+        }
         Ok(())
     }
 }
@@ -196,6 +285,7 @@ impl std::fmt::Debug for HookRegistry {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("HookRegistry")
             .field("timeout", &self.timeout)
+            .field("delivery_overrides", &self.delivery_overrides)
             .finish_non_exhaustive()
     }
 }
@@ -217,19 +307,31 @@ impl HookDispatcher {
         Self { registry }
     }
 
+    /// Subscribe to `broadcaster` so that events sent but not yet dispatched
+    /// count towards [`HookRegistry::pending`].
+    pub fn subscribe(&self, broadcaster: &EventBroadcaster) -> broadcast::Receiver<SystemEvent> {
+        broadcaster.subscribe_tracked(self.registry.backlog.clone())
+    }
+
     /// Run the dispatcher, consuming events from the receiver.
     ///
-    /// This method runs indefinitely until the channel is closed.
+    /// This method runs indefinitely until the channel is closed. Events
+    /// waiting in a receiver not obtained from [`Self::subscribe`] are not
+    /// counted by [`HookRegistry::pending`].
     pub async fn run(self, mut receiver: broadcast::Receiver<SystemEvent>) {
         info!("Starting hook dispatcher");
 
         loop {
             match receiver.recv().await {
                 Ok(event) => {
+                    // The event stays counted until its hook tasks are
+                    // spawned and counted as in flight
                     self.registry.dispatch(&event).await;
+                    self.release(1);
                 }
                 Err(broadcast::error::RecvError::Lagged(n)) => {
                     warn!(missed = n, "Dispatcher lagged, missed events");
+                    self.release(usize::try_from(n).unwrap_or(usize::MAX));
                 }
                 Err(broadcast::error::RecvError::Closed) => {
                     info!("Hook dispatcher channel closed, stopping");
@@ -244,6 +346,16 @@ impl HookDispatcher {
         }
     }
 
+    /// Take `n` handled or missed events off the backlog.
+    fn release(&self, n: usize) {
+        let _ = self
+            .registry
+            .backlog
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |count| {
+                Some(count.saturating_sub(n))
+            });
+    }
+
     /// Get the registry.
     pub fn registry(&self) -> &Arc<HookRegistry> {
         &self.registry
@@ -294,6 +406,18 @@ impl HookSystemBuilder {
         self
     }
 
+    /// Register a resource hook with an explicit delivery guarantee.
+    pub async fn register_resource_with_delivery<H: ResourceHook + 'static>(
+        self,
+        hook: Arc<H>,
+        delivery: DeliveryGuarantee,
+    ) -> Self {
+        self.registry
+            .register_resource_with_delivery(hook, delivery)
+            .await;
+        self
+    }
+
     /// Register an auth hook.
     pub async fn register_auth<H: AuthHook + 'static>(self, hook: Arc<H>) -> Self {
         self.registry.register_auth(hook).await;
@@ -372,6 +496,46 @@ mod tests {
         assert_eq!(registry.hook_count().await, 1);
     }
 
+    #[tokio::test]
+    async fn test_at_least_once_hook_skips_dispatch() {
+        let registry = HookRegistry::new();
+        let hook = Arc::new(CountingHook::new("durable"));
+        registry
+            .register_resource_with_delivery(hook.clone(), DeliveryGuarantee::AtLeastOnce)
+            .await;
+        assert_eq!(registry.hook_count().await, 1);
+        assert_eq!(registry.at_least_once_hooks().await.len(), 1);
+
+        let event = SystemEvent::Resource(ResourceEvent::created(
+            "Patient",
+            "123",
+            serde_json::json!({}),
+        ));
+        registry.dispatch(&event).await;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        assert_eq!(hook.count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_delivery_override_by_name() {
+        let registry = HookRegistry::new().with_delivery_overrides(HashMap::from([(
+            "durable".to_string(),
+            DeliveryGuarantee::AtLeastOnce,
+        )]));
+        registry
+            .register_resource(Arc::new(CountingHook::new("durable")))
+            .await;
+        registry
+            .register_resource(Arc::new(CountingHook::new("other")))
+            .await;
+
+        let durable = registry.at_least_once_hooks().await;
+        assert_eq!(durable.len(), 1);
+        assert_eq!(durable[0].name(), "durable");
+        assert_eq!(registry.hook_count().await, 2);
+    }
+
     #[tokio::test]
     async fn test_registry_dispatch() {
         let registry = HookRegistry::new();
@@ -390,6 +554,7 @@ mod tests {
         tokio::time::sleep(Duration::from_millis(50)).await;
 
         assert_eq!(hook.count(), 1);
+        assert_eq!(registry.pending(), 0);
     }
 
     #[tokio::test]
@@ -402,19 +567,21 @@ mod tests {
         registry.register_resource(hook.clone()).await;
 
         let dispatcher = HookDispatcher::new(registry.clone());
-        let receiver = broadcaster.subscribe();
-
-        // Start dispatcher in background
-        tokio::spawn(dispatcher.run(receiver));
+        let receiver = dispatcher.subscribe(&broadcaster);
 
-        // Send events
+        // Send events; they are pending before the dispatcher runs
         broadcaster.send_created("Patient", "1", serde_json::json!({}));
         broadcaster.send_created("Patient", "2", serde_json::json!({}));
+        assert_eq!(registry.pending(), 2);
+
+        // Start dispatcher in background
+        tokio::spawn(dispatcher.run(receiver));
 
         // Give time for processing
         tokio::time::sleep(Duration::from_millis(100)).await;
 
         assert_eq!(hook.count(), 2);
+        assert_eq!(registry.pending(), 0);
     }
 
     struct PanicHook;
@@ -0,0 +1,313 @@
+//! Shared outbound HTTP client with retries.
+//!
+//! [`HttpClient`] wraps a pooled [`reqwest::Client`] and retries transient
+//! failures with exponential backoff. Connection failures and `429`/`503`
+//! responses are always retried, the latter honouring `Retry-After`;
+//! timeouts, `502` and `504` are retried only for idempotent methods so that
+//! a non-idempotent request is never sent twice after the upstream may have
+//! acted on it.
+
+use std::time::Duration;
+
+use reqwest::{Method, RequestBuilder, Response, StatusCode, header};
+use serde::{Deserialize, Serialize};
+
+/// Retry behaviour for [`HttpClient`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetryPolicy {
+    /// Retries after the first attempt; `0` disables retrying
+    #[serde(default = "default_max_retries")]
+    pub max_retries: u32,
+    /// Delay before the first retry; doubled for every further retry
+    #[serde(default = "default_initial_backoff_ms")]
+    pub initial_backoff_ms: u64,
+    /// Upper bound for a single delay, including one asked for by `Retry-After`
+    #[serde(default = "default_max_backoff_ms")]
+    pub max_backoff_ms: u64,
+}
+
+fn default_max_retries() -> u32 {
+    2
+}
+
+fn default_initial_backoff_ms() -> u64 {
+    200
+}
+
+fn default_max_backoff_ms() -> u64 {
+    5_000
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: default_max_retries(),
+            initial_backoff_ms: default_initial_backoff_ms(),
+            max_backoff_ms: default_max_backoff_ms(),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy that sends every request exactly once.
+    pub fn none() -> Self {
+        Self {
+            max_retries: 0,
+            ..Self::default()
+        }
+    }
+
+    /// Delay before retry number `retry` (0-based), or the server's
+    /// `Retry-After` when given, capped at `max_backoff_ms`.
+    pub fn delay(&self, retry: u32, retry_after: Option<Duration>) -> Duration {
+        let max = Duration::from_millis(self.max_backoff_ms);
+        let backoff = retry_after.unwrap_or_else(|| {
+            Duration::from_millis(self.initial_backoff_ms.saturating_mul(1 << retry.min(16)))
+        });
+        backoff.min(max)
+    }
+}
+
+/// Connection, timeout and retry settings for [`HttpClient`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HttpClientConfig {
+    /// Total time allowed for one attempt, including reading the body
+    #[serde(default = "default_timeout_ms")]
+    pub timeout_ms: u64,
+    /// Time allowed to establish a connection
+    #[serde(default = "default_connect_timeout_ms")]
+    pub connect_timeout_ms: u64,
+    /// How long an idle pooled connection is kept open
+    #[serde(default = "default_pool_idle_timeout_ms")]
+    pub pool_idle_timeout_ms: u64,
+    /// Idle connections kept per host
+    #[serde(default = "default_pool_max_idle_per_host")]
+    pub pool_max_idle_per_host: usize,
+    /// Retry behaviour
+    #[serde(default)]
+    pub retry: RetryPolicy,
+}
+
+fn default_timeout_ms() -> u64 {
+    30_000
+}
+
+fn default_connect_timeout_ms() -> u64 {
+    10_000
+}
+
+fn default_pool_idle_timeout_ms() -> u64 {
+    90_000
+}
+
+fn default_pool_max_idle_per_host() -> usize {
+    32
+}
+
+impl Default for HttpClientConfig {
+    fn default() -> Self {
+        Self {
+            timeout_ms: default_timeout_ms(),
+            connect_timeout_ms: default_connect_timeout_ms(),
+            pool_idle_timeout_ms: default_pool_idle_timeout_ms(),
+            pool_max_idle_per_host: default_pool_max_idle_per_host(),
+            retry: RetryPolicy::default(),
+        }
+    }
+}
+
+impl HttpClientConfig {
+    /// Default settings with a different per-attempt timeout.
+    pub fn with_timeout(timeout: Duration) -> Self {
+        Self {
+            timeout_ms: timeout.as_millis() as u64,
+            ..Self::default()
+        }
+    }
+
+    /// Replace the retry policy.
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+}
+
+/// Pooled HTTP client that retries transient failures.
+///
+/// Cloning is cheap and shares the connection pool.
+#[derive(Debug, Clone)]
+pub struct HttpClient {
+    inner: reqwest::Client,
+    retry: RetryPolicy,
+}
+
+impl HttpClient {
+    /// Build a client from `config`.
+    pub fn new(config: &HttpClientConfig) -> reqwest::Result<Self> {
+        let inner = reqwest::Client::builder()
+            .timeout(Duration::from_millis(config.timeout_ms))
+            .connect_timeout(Duration::from_millis(config.connect_timeout_ms))
+            .pool_idle_timeout(Duration::from_millis(config.pool_idle_timeout_ms))
+            .pool_max_idle_per_host(config.pool_max_idle_per_host)
+            .build()?;
+        Ok(Self {
+            inner,
+            retry: config.retry.clone(),
+        })
+    }
+
+    /// The underlying client, for requests that must not be retried
+    /// (e.g. streaming bodies).
+    pub fn inner(&self) -> &reqwest::Client {
+        &self.inner
+    }
+
+    /// The retry policy applied by [`HttpClient::send`].
+    pub fn retry_policy(&self) -> &RetryPolicy {
+        &self.retry
+    }
+
+    /// Start a request; send it with [`HttpClient::send`] to get retries.
+    pub fn request(&self, method: Method, url: impl reqwest::IntoUrl) -> RequestBuilder {
+        self.inner.request(method, url)
+    }
+
+    /// Start a `GET` request.
+    pub fn get(&self, url: impl reqwest::IntoUrl) -> RequestBuilder {
+        self.inner.get(url)
+    }
+
+    /// Start a `POST` request.
+    pub fn post(&self, url: impl reqwest::IntoUrl) -> RequestBuilder {
+        self.inner.post(url)
+    }
+
+    /// Send `request`, retrying per the policy.
+    ///
+    /// Requests whose body cannot be cloned (streams) are sent once. The
+    /// last response is returned as-is once retries are exhausted, so
+    /// callers still see the upstream status.
+    pub async fn send(&self, request: RequestBuilder) -> reqwest::Result<Response> {
+        let mut retry = 0;
+        let mut pending = request;
+        loop {
+            let Some(next) = (retry < self.retry.max_retries)
+                .then(|| pending.try_clone())
+                .flatten()
+            else {
+                return pending.send().await;
+            };
+            let (client, request) = pending.build_split();
+            let request = request?;
+            let idempotent = is_idempotent(request.method());
+            let url = request.url().clone();
+
+            let delay = match client.execute(request).await {
+                Ok(response) => {
+                    let status = response.status();
+                    if !is_retryable_status(status, idempotent) {
+                        return Ok(response);
+                    }
+                    let delay = self.retry.delay(retry, retry_after(&response));
+                    tracing::debug!(%url, %status, retry, ?delay, "Retrying HTTP request");
+                    delay
+                }
+                Err(e) if e.is_connect() || (idempotent && e.is_timeout()) => {
+                    let delay = self.retry.delay(retry, None);
+                    tracing::debug!(%url, error = %e, retry, ?delay, "Retrying HTTP request");
+                    delay
+                }
+                Err(e) => return Err(e),
+            };
+
+            tokio::time::sleep(delay).await;
+            retry += 1;
+            pending = next;
+        }
+    }
+}
+
+/// Methods that can be repeated without changing the outcome (RFC 9110 §9.2.2)
+fn is_idempotent(method: &Method) -> bool {
+    matches!(
+        *method,
+        Method::GET | Method::HEAD | Method::OPTIONS | Method::PUT | Method::DELETE
+    )
+}
+
+/// `429`/`503` mean the request was not processed; gateway errors are only
+/// safe to repeat for idempotent requests.
+fn is_retryable_status(status: StatusCode, idempotent: bool) -> bool {
+    match status {
+        StatusCode::TOO_MANY_REQUESTS | StatusCode::SERVICE_UNAVAILABLE => true,
+        StatusCode::BAD_GATEWAY | StatusCode::GATEWAY_TIMEOUT => idempotent,
+        _ => false,
+    }
+}
+
+/// `Retry-After` as delay-seconds or an HTTP-date.
+fn retry_after(response: &Response) -> Option<Duration> {
+    let value = response.headers().get(header::RETRY_AFTER)?.to_str().ok()?;
+    parse_retry_after(value, time::OffsetDateTime::now_utc())
+}
+
+fn parse_retry_after(value: &str, now: time::OffsetDateTime) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let at =
+        time::OffsetDateTime::parse(value, &time::format_description::well_known::Rfc2822).ok()?;
+    Some((at - now).try_into().unwrap_or(Duration::ZERO))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delay_doubles_and_caps() {
+        let policy = RetryPolicy {
+            max_retries: 5,
+            initial_backoff_ms: 100,
+            max_backoff_ms: 350,
+        };
+        assert_eq!(policy.delay(0, None), Duration::from_millis(100));
+        assert_eq!(policy.delay(1, None), Duration::from_millis(200));
+        assert_eq!(policy.delay(2, None), Duration::from_millis(350));
+        assert_eq!(
+            policy.delay(0, Some(Duration::from_secs(60))),
+            Duration::from_millis(350)
+        );
+    }
+
+    #[test]
+    fn test_parse_retry_after() {
+        let now = time::macros::datetime!(2015-10-21 07:28:00 UTC);
+        assert_eq!(
+            parse_retry_after("120", now),
+            Some(Duration::from_secs(120))
+        );
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:30 GMT", now),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:27:00 GMT", now),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(parse_retry_after("soon", now), None);
+    }
+
+    #[test]
+    fn test_retryable_status() {
+        assert!(is_retryable_status(StatusCode::TOO_MANY_REQUESTS, false));
+        assert!(is_retryable_status(StatusCode::SERVICE_UNAVAILABLE, false));
+        assert!(is_retryable_status(StatusCode::BAD_GATEWAY, true));
+        assert!(!is_retryable_status(StatusCode::BAD_GATEWAY, false));
+        assert!(!is_retryable_status(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            true
+        ));
+    }
+}
@@ -16,8 +16,13 @@ pub enum IdError {
 /// Generates a new UUID-based ID for a FHIR resource.
 ///
 /// This is the default ID generation strategy when users don't provide an ID.
+/// IDs are UUIDv7: a 48-bit millisecond timestamp followed by 74 random bits.
+/// Server instances need no coordination to avoid collisions, and IDs sort
+/// by creation time, which keeps primary key inserts local in the B-tree.
+/// Within one process, IDs generated in the same millisecond still sort in
+/// generation order; across instances, ordering is only as good as the clocks.
 pub fn generate_id() -> String {
-    uuid::Uuid::new_v4().to_string()
+    uuid::Uuid::now_v7().to_string()
 }
 
 /// Validates a FHIR resource ID according to the FHIR specification.
@@ -64,3 +69,21 @@ pub fn validate_id(id: &str) -> Result<(), IdError> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_id_is_time_ordered_v7() {
+        let ids: Vec<String> = (0..100).map(|_| generate_id()).collect();
+        for id in &ids {
+            let uuid = uuid::Uuid::parse_str(id).unwrap();
+            assert_eq!(uuid.get_version_num(), 7);
+            assert!(validate_id(id).is_ok());
+        }
+        let mut sorted = ids.clone();
+        sorted.sort();
+        assert_eq!(ids, sorted);
+    }
+}
@@ -1,22 +1,32 @@
+pub mod canonical_json;
 pub mod error;
 pub mod events;
 pub mod fhir;
 pub mod fhir_reference;
+pub mod http;
 pub mod id;
+pub mod in_flight;
 pub mod monitoring;
 pub mod operations;
+pub mod paging;
 pub mod resource;
+pub mod signing;
 pub mod text;
 pub mod time;
 
+pub use canonical_json::{resources_equivalent, to_canonical_string};
 pub use error::{CoreError, Result};
 pub use fhir::{FhirVersion, ResourceType};
 pub use fhir_reference::{FhirReference, UnresolvableReference, parse_reference};
+pub use http::{HttpClient, HttpClientConfig, RetryPolicy};
 pub use id::{IdError, generate_id, validate_id};
+pub use in_flight::{InFlight, InFlightGuard};
 pub use monitoring::{
     HealthCheck, HealthStatus, MemoryStats, MetricsCollector, ResourceStats, SystemMetrics,
 };
 pub use operations::{AppReference, OperationDefinition, OperationProvider, categories, modules};
-pub use resource::{ResourceEnvelope, ResourceMeta, ResourceStatus};
+pub use paging::{PageCursor, PageLinkStyle, SyncCursor};
+pub use resource::{ResourceEnvelope, ResourceMeta, ResourceStatus, serialized_size};
+pub use signing::WebhookSignature;
 pub use text::normalize_string;
 pub use time::{FhirDateTime, now_utc};
@@ -0,0 +1,169 @@
+//! Paging links shared by search, history and `$everything` bundles.
+//!
+//! Page links either spell out the window as `_count` and `_offset`, or carry
+//! it in an opaque `_cursor` token. Cursor links keep clients from building
+//! page URLs of their own, so the paging strategy behind them can change
+//! without breaking anyone who follows the links.
+
+//!
+//! Incremental sync (`_sync`) pages differently: results are ordered by
+//! `(_lastUpdated, _id)` and each link carries a [`SyncCursor`] naming the
+//! last resource already returned, so pages hold up while resources change.
+
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+/// Query parameter carrying a page cursor.
+pub const CURSOR_PARAM: &str = "_cursor";
+
+/// Query parameter selecting an incremental sync: `true` to start one, or a
+/// [`SyncCursor`] token to resume it.
+pub const SYNC_PARAM: &str = "_sync";
+
+/// How page links in a bundle address their page.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PageLinkStyle {
+    /// `_count=N&_offset=M`
+    #[default]
+    Offset,
+    /// `_cursor=<token>`
+    Cursor,
+}
+
+/// The window of results a page link points at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PageCursor {
+    pub offset: u32,
+    pub count: u32,
+}
+
+impl PageCursor {
+    /// Encodes the cursor as a `_cursor` token.
+    pub fn encode(&self) -> String {
+        let mut bytes = [0u8; 8];
+        bytes[..4].copy_from_slice(&self.offset.to_be_bytes());
+        bytes[4..].copy_from_slice(&self.count.to_be_bytes());
+        hex::encode(bytes)
+    }
+
+    /// Decodes a `_cursor` token, `None` if it is malformed.
+    pub fn decode(token: &str) -> Option<Self> {
+        let bytes: [u8; 8] = hex::decode(token).ok()?.try_into().ok()?;
+        Some(Self {
+            offset: u32::from_be_bytes(bytes[..4].try_into().ok()?),
+            count: u32::from_be_bytes(bytes[4..].try_into().ok()?),
+        })
+    }
+}
+
+/// Position in an incremental sync: the last resource returned, by
+/// `(_lastUpdated, _id)`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncCursor {
+    /// `meta.lastUpdated` in microseconds since the Unix epoch, the
+    /// precision it is stored with
+    pub last_updated_micros: i64,
+    pub id: String,
+}
+
+impl SyncCursor {
+    /// Cursor after the resource `id` last updated at `last_updated`.
+    pub fn new(last_updated: OffsetDateTime, id: impl Into<String>) -> Self {
+        let micros = last_updated.unix_timestamp_nanos() / 1_000;
+        Self {
+            last_updated_micros: i64::try_from(micros).unwrap_or(i64::MAX),
+            id: id.into(),
+        }
+    }
+
+    /// `last_updated_micros` as a timestamp.
+    pub fn last_updated(&self) -> Option<OffsetDateTime> {
+        OffsetDateTime::from_unix_timestamp_nanos(i128::from(self.last_updated_micros) * 1_000).ok()
+    }
+
+    /// Encodes the cursor as a `_sync` token.
+    pub fn encode(&self) -> String {
+        let mut bytes = self.last_updated_micros.to_be_bytes().to_vec();
+        bytes.extend_from_slice(self.id.as_bytes());
+        hex::encode(bytes)
+    }
+
+    /// Decodes a `_sync` token, `None` if it is malformed.
+    pub fn decode(token: &str) -> Option<Self> {
+        let bytes = hex::decode(token).ok()?;
+        if bytes.len() <= 8 {
+            return None;
+        }
+        let (micros, id) = bytes.split_at(8);
+        let id = String::from_utf8(id.to_vec()).ok()?;
+        crate::validate_id(&id).ok()?;
+        let cursor = Self {
+            last_updated_micros: i64::from_be_bytes(micros.try_into().ok()?),
+            id,
+        };
+        cursor.last_updated().map(|_| cursor)
+    }
+}
+
+/// Query string addressing a page (without a leading separator).
+pub fn page_query(style: PageLinkStyle, offset: usize, count: usize) -> String {
+    match style {
+        PageLinkStyle::Offset => format!("_count={count}&_offset={offset}"),
+        PageLinkStyle::Cursor => {
+            let cursor = PageCursor {
+                offset: u32::try_from(offset).unwrap_or(u32::MAX),
+                count: u32::try_from(count).unwrap_or(u32::MAX),
+            };
+            format!("{CURSOR_PARAM}={}", cursor.encode())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cursor_round_trip() {
+        let cursor = PageCursor {
+            offset: 120,
+            count: 20,
+        };
+        assert_eq!(PageCursor::decode(&cursor.encode()), Some(cursor));
+        assert_eq!(PageCursor::decode("not-a-cursor"), None);
+        assert_eq!(PageCursor::decode("00"), None);
+    }
+
+    #[test]
+    fn test_sync_cursor_round_trip() {
+        let last_updated = OffsetDateTime::from_unix_timestamp_nanos(1_700_000_000_123_456_789)
+            .expect("valid timestamp");
+        let cursor = SyncCursor::new(last_updated, "pat-1");
+        assert_eq!(cursor.last_updated_micros, 1_700_000_000_123_456);
+        assert_eq!(SyncCursor::decode(&cursor.encode()), Some(cursor.clone()));
+        assert_eq!(
+            cursor.last_updated().map(|t| t.unix_timestamp_nanos()),
+            Some(1_700_000_000_123_456_000)
+        );
+
+        assert_eq!(SyncCursor::decode("true"), None);
+        // A timestamp without an id
+        assert_eq!(SyncCursor::decode("0000000000000001"), None);
+        // An id the server would never assign
+        let bad_id = format!("{}{}", "0000000000000001", hex::encode("a b"));
+        assert_eq!(SyncCursor::decode(&bad_id), None);
+    }
+
+    #[test]
+    fn test_page_query() {
+        assert_eq!(
+            page_query(PageLinkStyle::Offset, 40, 20),
+            "_count=20&_offset=40"
+        );
+        assert_eq!(
+            page_query(PageLinkStyle::Cursor, 40, 20),
+            "_cursor=0000002800000014"
+        );
+    }
+}
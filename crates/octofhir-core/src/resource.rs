@@ -132,11 +132,42 @@ impl ResourceEnvelope {
     }
 }
 
+/// Size in bytes of `resource` serialized as compact JSON, counted without
+/// buffering the serialization.
+///
+/// Every write path compares this against `server.max_resource_size_bytes`.
+pub fn serialized_size(resource: &Value) -> usize {
+    struct ByteCounter(usize);
+    impl std::io::Write for ByteCounter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0 += buf.len();
+            Ok(buf.len())
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let mut counter = ByteCounter(0);
+    // Serializing a `Value` into an infallible writer cannot fail
+    let _ = serde_json::to_writer(&mut counter, resource);
+    counter.0
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use serde_json::json;
 
+    #[test]
+    fn test_serialized_size() {
+        let resource = json!({"resourceType": "Patient", "id": "p1"});
+        assert_eq!(
+            serialized_size(&resource),
+            serde_json::to_vec(&resource).unwrap().len()
+        );
+    }
+
     #[test]
     fn test_resource_status_default() {
         let status = ResourceStatus::default();
@@ -0,0 +1,39 @@
+-- Transactional event outbox for at-least-once hook delivery.
+--
+-- Every resource table has an AFTER trigger calling enqueue_resource_event(),
+-- so a row is added here in the same transaction as the change it describes.
+-- The function is a no-op until the server enables the outbox (see
+-- event_outbox.rs), which keeps writes free of the extra insert when no hook
+-- asks for at-least-once delivery.
+
+-- ============================================================================
+-- OUTBOX
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS _event_outbox (
+    id BIGSERIAL PRIMARY KEY,
+    resource_type TEXT NOT NULL,
+    resource_id TEXT NOT NULL,
+    event_type TEXT NOT NULL,
+    txid BIGINT NOT NULL,
+    -- The resource as written; NULL for deletions.
+    payload JSONB,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Outbox rows each at-least-once hook has acknowledged. Tracked per row
+-- rather than as a high-water mark because ids are allocated before commit:
+-- a row can become visible after rows with higher ids were delivered.
+CREATE TABLE IF NOT EXISTS _event_outbox_ack (
+    event_id BIGINT NOT NULL REFERENCES _event_outbox (id) ON DELETE CASCADE,
+    hook_name TEXT NOT NULL,
+    acked_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (event_id, hook_name)
+);
+
+CREATE OR REPLACE FUNCTION enqueue_resource_event()
+RETURNS TRIGGER AS $func$
+BEGIN
+    RETURN NULL;
+END;
+$func$ LANGUAGE plpgsql;
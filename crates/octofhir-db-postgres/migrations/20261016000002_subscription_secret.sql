@@ -0,0 +1,14 @@
+-- Rest-hook signing secrets, kept out of the Subscription resource.
+--
+-- The server takes the signing secret extension off a Subscription before
+-- writing it and stores the secret here instead (see subscriptions/secrets.rs),
+-- so it never appears in reads, search results or history.
+
+CREATE TABLE IF NOT EXISTS subscription_secret (
+    -- Tenant id, empty outside multitenancy.
+    tenant TEXT NOT NULL DEFAULT '',
+    subscription_id TEXT NOT NULL,
+    secret TEXT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (tenant, subscription_id)
+);
@@ -0,0 +1,7 @@
+-- Single-use SMART launch contexts.
+--
+-- A launch context is marked consumed the first time authorization uses it;
+-- the row is kept until it expires so a replayed launch id can be reported.
+
+ALTER TABLE octofhir_auth.smart_launch_context
+    ADD COLUMN IF NOT EXISTS consumed_at TIMESTAMPTZ;
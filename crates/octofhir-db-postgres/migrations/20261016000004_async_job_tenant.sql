@@ -0,0 +1,8 @@
+-- Tenant of an async job.
+--
+-- Jobs interrupted by a restart are resumed by whichever instance starts
+-- next, outside the request that submitted them; the tenant is recorded so
+-- they run against the same tenant schema.
+
+ALTER TABLE async_jobs
+    ADD COLUMN IF NOT EXISTS tenant_id TEXT;
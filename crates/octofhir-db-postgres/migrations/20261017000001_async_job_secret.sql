@@ -0,0 +1,12 @@
+-- Credentials an async job needs, kept out of its request body.
+--
+-- A job such as $import may need a secret to reach its inputs (e.g.
+-- `storageDetail.authorization`). It is stored here rather than in
+-- `async_jobs.request_body`, so `_async-status` never reports it, and it is
+-- deleted as soon as the job completes, fails or is cancelled. Jobs that
+-- never finish lose it when they expire and are cleaned up.
+
+CREATE TABLE IF NOT EXISTS async_job_secret (
+    job_id UUID PRIMARY KEY REFERENCES async_jobs(id) ON DELETE CASCADE,
+    secret TEXT NOT NULL
+);
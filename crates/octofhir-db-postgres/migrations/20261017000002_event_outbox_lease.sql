@@ -0,0 +1,13 @@
+-- Per-hook leases of the event outbox relay.
+--
+-- A relay delivering to an at-least-once hook holds that hook's lease, so
+-- with several server instances each hook is driven by one instance at a
+-- time. The lease is claimed and renewed in short statements and expires on
+-- its own if the holder dies, so no transaction or connection stays open
+-- while hooks run.
+
+CREATE TABLE IF NOT EXISTS _event_outbox_lease (
+    hook_name TEXT PRIMARY KEY,
+    holder UUID NOT NULL,
+    leased_until TIMESTAMPTZ NOT NULL
+);
@@ -2,6 +2,8 @@
 
 use serde::{Deserialize, Serialize};
 
+use crate::schema::JsonbCompression;
+
 /// Configuration for the PostgreSQL storage backend.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PostgresConfig {
@@ -17,9 +19,20 @@ pub struct PostgresConfig {
     #[serde(default)]
     pub min_connections: Option<u32>,
 
+    /// Connections [`crate::pool::warm_pool`] opens right after startup
+    /// (capped at `pool_size`). 0 leaves the pool to fill on demand.
+    /// Default: 0.
+    #[serde(default)]
+    pub warmup_connections: u32,
+
     /// Connection timeout in milliseconds.
     pub connect_timeout_ms: u64,
 
+    /// How long (milliseconds) a request waits for a free pooled connection
+    /// before failing as busy. Defaults to `connect_timeout_ms` if not set.
+    #[serde(default)]
+    pub acquire_timeout_ms: Option<u64>,
+
     /// Idle timeout in milliseconds.
     /// Connections idle longer than this will be closed.
     pub idle_timeout_ms: Option<u64>,
@@ -47,6 +60,26 @@ pub struct PostgresConfig {
     /// Default: 120.
     #[serde(default = "default_gin_maintenance_interval_secs")]
     pub gin_maintenance_interval_secs: u64,
+
+    /// TOAST compression method for the `resource` JSONB column. When not
+    /// `default`, existing tables are altered on startup and new tables are
+    /// created with it. See [`JsonbCompression`] for the tradeoffs.
+    #[serde(default)]
+    pub resource_compression: JsonbCompression,
+
+    /// Add the `_text` / `_content` full-text columns to existing tables that
+    /// lack them on startup. Adding the columns rewrites each table under an
+    /// exclusive lock, so this is opt-in; without it startup only warns about
+    /// such tables. Default: false.
+    #[serde(default)]
+    pub upgrade_full_text_columns: bool,
+
+    /// Point each acquired connection's `search_path` at the schema of the
+    /// current tenant (see `octofhir_storage::tenant`). Costs one extra
+    /// round-trip per acquire, so it is only enabled for multi-tenant
+    /// deployments. Default: false.
+    #[serde(default)]
+    pub tenant_isolation: bool,
 }
 
 fn default_gin_maintenance() -> bool {
@@ -66,12 +99,17 @@ impl Default for PostgresConfig {
             // and expected concurrent request load.
             pool_size: 20,
             min_connections: None,
+            warmup_connections: 0,
             connect_timeout_ms: 5000,
+            acquire_timeout_ms: None,
             idle_timeout_ms: Some(300_000),
             max_lifetime_secs: None,
             run_migrations: true,
             gin_maintenance: true,
             gin_maintenance_interval_secs: 120,
+            resource_compression: JsonbCompression::Default,
+            upgrade_full_text_columns: false,
+            tenant_isolation: false,
         }
     }
 }
@@ -100,6 +138,13 @@ impl PostgresConfig {
         self
     }
 
+    /// Sets how long to wait for a free pooled connection.
+    #[must_use]
+    pub fn with_acquire_timeout_ms(mut self, timeout: Option<u64>) -> Self {
+        self.acquire_timeout_ms = timeout;
+        self
+    }
+
     /// Sets the idle timeout.
     #[must_use]
     pub fn with_idle_timeout_ms(mut self, timeout: Option<u64>) -> Self {
@@ -114,6 +159,13 @@ impl PostgresConfig {
         self
     }
 
+    /// Sets how many connections to open when the pool is warmed.
+    #[must_use]
+    pub fn with_warmup_connections(mut self, connections: u32) -> Self {
+        self.warmup_connections = connections;
+        self
+    }
+
     /// Sets the maximum lifetime of a connection in seconds.
     #[must_use]
     pub fn with_max_lifetime_secs(mut self, lifetime: Option<u64>) -> Self {
@@ -141,6 +193,33 @@ impl PostgresConfig {
         self.gin_maintenance_interval_secs = secs;
         self
     }
+
+    /// Sets the TOAST compression method for the `resource` column.
+    #[must_use]
+    pub fn with_resource_compression(mut self, compression: JsonbCompression) -> Self {
+        self.resource_compression = compression;
+        self
+    }
+
+    /// Sets whether startup adds missing full-text columns to existing tables.
+    #[must_use]
+    pub fn with_upgrade_full_text_columns(mut self, on: bool) -> Self {
+        self.upgrade_full_text_columns = on;
+        self
+    }
+
+    /// Sets whether connections follow the current tenant's schema.
+    #[must_use]
+    pub fn with_tenant_isolation(mut self, on: bool) -> Self {
+        self.tenant_isolation = on;
+        self
+    }
+
+    /// Returns the effective pool acquire timeout in milliseconds.
+    #[must_use]
+    pub fn effective_acquire_timeout_ms(&self) -> u64 {
+        self.acquire_timeout_ms.unwrap_or(self.connect_timeout_ms)
+    }
 }
 
 #[cfg(test)]
@@ -155,6 +234,7 @@ mod tests {
         assert_eq!(config.connect_timeout_ms, 5000);
         assert_eq!(config.idle_timeout_ms, Some(300_000));
         assert!(config.run_migrations);
+        assert!(!config.upgrade_full_text_columns);
     }
 
     #[test]
@@ -172,6 +252,27 @@ mod tests {
         assert!(!config.run_migrations);
     }
 
+    #[test]
+    fn test_warmup_connections() {
+        let config = PostgresConfig::default();
+        assert_eq!(config.warmup_connections, 0);
+
+        let config = config
+            .with_min_connections(Some(4))
+            .with_warmup_connections(8);
+        assert_eq!(config.min_connections, Some(4));
+        assert_eq!(config.warmup_connections, 8);
+    }
+
+    #[test]
+    fn test_acquire_timeout_defaults_to_connect_timeout() {
+        let config = PostgresConfig::default().with_connect_timeout_ms(3000);
+        assert_eq!(config.effective_acquire_timeout_ms(), 3000);
+
+        let config = config.with_acquire_timeout_ms(Some(500));
+        assert_eq!(config.effective_acquire_timeout_ms(), 500);
+    }
+
     #[test]
     fn test_config_serialization() {
         let config = PostgresConfig::default();
@@ -0,0 +1,160 @@
+//! Statements bounded by the request deadline and canceled with the request.
+//!
+//! Dropping a query future does not stop the statement on the server: the
+//! connection waits for it to finish before it goes back to the pool. Inside
+//! an [`octofhir_storage::with_deadline`] scope, search and history
+//! statements therefore run in a transaction whose `statement_timeout` is the
+//! time left, so PostgreSQL cancels them when the request gives up.
+//!
+//! Transactions begun with [`begin_transaction`] carry the same limit for
+//! every statement they run, so transaction and batch bundles are bounded
+//! too. Single-resource reads and writes outside a transaction use the pool
+//! directly and are not bounded: they are indexed id lookups.
+//!
+//! Inside an [`octofhir_storage::with_cancel_scope`], the transaction's
+//! backend is also registered with the scope, so the statement can be
+//! canceled with `pg_cancel_backend` when the client disconnects.
+
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use sqlx_core::pool::PoolConnection;
+use sqlx_core::query::query;
+use sqlx_core::query_as::query_as;
+use sqlx_core::sql_str::AssertSqlSafe;
+use sqlx_postgres::{PgConnection, PgPool, PgTransaction, Postgres};
+
+use octofhir_storage::{
+    Canceller, StatementGuard, StorageError, cancel_scope_active, register_statement,
+    remaining_time,
+};
+
+use crate::error::database_error;
+
+/// Connection for a read-only statement, bounded by the request deadline if
+/// there is one.
+pub(crate) struct BoundedConnection {
+    conn: Connection,
+    /// Registration with the request's cancel scope
+    statement: Option<StatementGuard>,
+}
+
+enum Connection {
+    Pooled(PoolConnection<Postgres>),
+    /// Never committed: the ROLLBACK is sent when the connection is released.
+    Bounded(PgTransaction<'static>),
+}
+
+impl BoundedConnection {
+    /// Acquires a connection. Inside a deadline scope, `BEGIN` and
+    /// `SET LOCAL statement_timeout` go out in one round-trip. Inside a
+    /// cancel scope, one more round-trip reads the backend to cancel.
+    ///
+    /// # Errors
+    ///
+    /// Returns `StorageError::Timeout` if the deadline has already passed.
+    pub(crate) async fn acquire(pool: &PgPool) -> Result<Self, StorageError> {
+        let remaining = remaining_time();
+        let cancelable = cancel_scope_active();
+        if remaining.is_none() && !cancelable {
+            return pool
+                .acquire()
+                .await
+                .map(|conn| Self {
+                    conn: Connection::Pooled(conn),
+                    statement: None,
+                })
+                .map_err(|e| database_error("Failed to acquire connection", e));
+        }
+
+        let mut tx = pool
+            .begin_with(AssertSqlSafe(begin_statement(remaining)?))
+            .await
+            .map_err(|e| database_error("Failed to start bounded query", e))?;
+
+        let statement = if cancelable {
+            let (pid, started): (i32, DateTime<Utc>) = query_as("SELECT pg_backend_pid(), now()")
+                .fetch_one(&mut *tx)
+                .await
+                .map_err(|e| database_error("Failed to start bounded query", e))?;
+            register_statement(canceller(pool.clone(), pid, started))
+        } else {
+            None
+        };
+
+        Ok(Self {
+            conn: Connection::Bounded(tx),
+            statement,
+        })
+    }
+
+    pub(crate) fn conn(&mut self) -> &mut PgConnection {
+        match &mut self.conn {
+            Connection::Pooled(conn) => conn,
+            Connection::Bounded(tx) => tx,
+        }
+    }
+
+    /// Releases the connection once the statement's result is back. A
+    /// connection dropped without `finish` leaves its statement to be
+    /// canceled with the request.
+    pub(crate) fn finish(self) {
+        if let Some(statement) = self.statement {
+            statement.finish();
+        }
+    }
+}
+
+/// Begins a write transaction whose statements are bounded by the request
+/// deadline, if there is one.
+///
+/// # Errors
+///
+/// Returns `StorageError::Timeout` if the deadline has already passed.
+pub(crate) async fn begin_transaction(
+    pool: &PgPool,
+) -> Result<PgTransaction<'static>, StorageError> {
+    pool.begin_with(AssertSqlSafe(begin_statement(remaining_time())?))
+        .await
+        .map_err(|e| StorageError::transaction_error(format!("Failed to begin transaction: {e}")))
+}
+
+/// `BEGIN`, plus a `statement_timeout` of the time left before the deadline.
+fn begin_statement(remaining: Option<Duration>) -> Result<String, StorageError> {
+    match remaining {
+        Some(remaining) if remaining.is_zero() => Err(StorageError::timeout(
+            "request deadline passed before the query started",
+        )),
+        // Round up: a zero statement_timeout would disable the limit
+        Some(remaining) => Ok(format!(
+            "BEGIN; SET LOCAL statement_timeout = {}",
+            remaining.as_millis() + 1
+        )),
+        None => Ok("BEGIN".to_string()),
+    }
+}
+
+/// Cancels the statement running in the transaction started at `started` on
+/// backend `pid`.
+fn canceller(pool: PgPool, pid: i32, started: DateTime<Utc>) -> Canceller {
+    Box::new(move || {
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+        runtime.spawn(async move {
+            // Only while the backend is still in that transaction: once it is
+            // rolled back, the connection may serve another request
+            let result = query(
+                "SELECT pg_cancel_backend(pid) FROM pg_stat_activity \
+                 WHERE pid = $1 AND xact_start = $2",
+            )
+            .bind(pid)
+            .bind(started)
+            .execute(&pool)
+            .await;
+            if let Err(e) = result {
+                tracing::debug!(error = %e, pid, "Failed to cancel abandoned statement");
+            }
+        });
+    })
+}
@@ -6,6 +6,13 @@ use sqlx_core::error::Error as SqlxError;
 /// PostgreSQL error code for undefined table (42P01).
 pub const PG_UNDEFINED_TABLE: &str = "42P01";
 
+/// PostgreSQL error code for unique violation (23505).
+pub const PG_UNIQUE_VIOLATION: &str = "23505";
+
+/// PostgreSQL error code for a statement canceled by `statement_timeout` or
+/// a cancel request (57014).
+pub const PG_QUERY_CANCELED: &str = "57014";
+
 /// Checks if a sqlx error has a specific PostgreSQL error code.
 pub fn has_pg_error_code(err: &SqlxError, code: &str) -> bool {
     if let SqlxError::Database(db_err) = err {
@@ -20,6 +27,44 @@ pub fn is_undefined_table(err: &SqlxError) -> bool {
     has_pg_error_code(err, PG_UNDEFINED_TABLE)
 }
 
+/// Checks if a sqlx error is "unique violation" (23505), e.g. an insert of
+/// an id that already exists.
+pub fn is_unique_violation(err: &SqlxError) -> bool {
+    has_pg_error_code(err, PG_UNIQUE_VIOLATION)
+}
+
+/// Converts a failed statement into a `StorageError`, keeping the SQLSTATE of
+/// database errors so callers can map them precisely.
+///
+/// A pool acquire timeout becomes [`StorageError::Busy`]: every connection is
+/// in use, which is worth a retry, unlike a database that cannot be reached.
+/// A statement canceled once the request deadline has passed becomes
+/// [`StorageError::Timeout`].
+pub fn database_error(context: &str, err: SqlxError) -> StorageError {
+    match err {
+        SqlxError::Database(db_err) => match db_err.code() {
+            Some(code) if code == PG_QUERY_CANCELED && octofhir_storage::deadline_passed() => {
+                StorageError::timeout(format!("{context}: request deadline exceeded"))
+            }
+            Some(code) => StorageError::database(code, format!("{context}: {}", db_err.message())),
+            None => StorageError::internal(format!("{context}: {db_err}")),
+        },
+        SqlxError::PoolTimedOut => pool_timed_out(context),
+        SqlxError::PoolClosed | SqlxError::Io(_) | SqlxError::Tls(_) => {
+            StorageError::connection_error(format!("{context}: {err}"))
+        }
+        other => StorageError::internal(format!("{context}: {other}")),
+    }
+}
+
+fn pool_timed_out(context: &str) -> StorageError {
+    tracing::warn!(
+        context,
+        "Connection pool saturated: no connection became free within the acquire timeout"
+    );
+    StorageError::busy(format!("{context}: all database connections are in use"))
+}
+
 /// Errors specific to the PostgreSQL storage backend.
 #[derive(Debug, thiserror::Error)]
 pub enum PostgresError {
@@ -61,6 +106,9 @@ impl PostgresError {
 impl From<PostgresError> for StorageError {
     fn from(err: PostgresError) -> Self {
         match err {
+            PostgresError::Connection(SqlxError::PoolTimedOut) => {
+                pool_timed_out("Database connection error")
+            }
             PostgresError::Connection(e) => StorageError::connection_error(e.to_string()),
             PostgresError::Migration(e) => StorageError::internal(format!("Migration error: {e}")),
             PostgresError::Config { message } => {
@@ -89,6 +137,19 @@ mod tests {
         assert!(err.to_string().contains("Pool error"));
     }
 
+    #[test]
+    fn test_database_error_without_sqlstate() {
+        let err = database_error("Failed to read resource", SqlxError::PoolTimedOut);
+        assert!(matches!(err, StorageError::Busy { .. }));
+        assert_eq!(err.sqlstate(), None);
+
+        let err = database_error("Failed to read resource", SqlxError::PoolClosed);
+        assert!(matches!(err, StorageError::ConnectionError { .. }));
+
+        let err = database_error("Failed to read resource", SqlxError::RowNotFound);
+        assert!(matches!(err, StorageError::Internal { .. }));
+    }
+
     #[test]
     fn test_conversion_to_storage_error() {
         let pg_err = PostgresError::config("test error");
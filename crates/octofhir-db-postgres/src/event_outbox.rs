@@ -0,0 +1,438 @@
+//! Transactional event outbox for at-least-once hook delivery.
+//!
+//! Events broadcast in-process after a write are lost if the server stops
+//! before the hooks run. For hooks registered with
+//! [`DeliveryGuarantee::AtLeastOnce`](octofhir_core::events::DeliveryGuarantee)
+//! (audit trails, synchronisation with external systems) the change is also
+//! recorded in the `_event_outbox` table by an `AFTER` trigger on every
+//! resource table, so the event commits or rolls back together with the
+//! write, including writes inside FHIR transaction bundles.
+//!
+//! [`OutboxRelay`] polls the outbox and hands each row to every at-least-once
+//! hook. A delivery is acknowledged in `_event_outbox_ack` only when the hook
+//! returns `Ok`; on an error, a timeout or a crash the row is delivered again
+//! on a later poll. Rows acknowledged by every configured hook are deleted.
+//! Like the in-process events, the outbox covers writes outside a tenant
+//! scope only.
+//!
+//! Delivery order follows outbox ids, which is the order writes reached the
+//! trigger and not necessarily the order they committed. With several server
+//! instances the relays take a per-hook lease in `_event_outbox_lease`, so
+//! each hook is driven by one instance at a time. No transaction or
+//! connection is held while hooks run: rows are read, the lease is renewed
+//! and each delivery is acknowledged in short statements of their own.
+
+use std::panic::AssertUnwindSafe;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use futures_util::FutureExt;
+use octofhir_core::events::{HookRegistry, ResourceEvent, ResourceEventType, ResourceHook};
+use serde_json::Value;
+use sqlx_core::sql_str::AssertSqlSafe;
+use sqlx_postgres::PgPool;
+use time::OffsetDateTime;
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+use crate::error::{PostgresError, Result};
+
+/// How often the relay polls the outbox.
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Maximum outbox rows delivered to one hook per poll.
+const DEFAULT_BATCH_SIZE: i64 = 100;
+
+/// How long a hook's lease outlasts one invocation of the hook, to cover the
+/// acknowledgment and renewal around it.
+const LEASE_MARGIN: Duration = Duration::from_secs(30);
+
+/// `enqueue_resource_event()` when the outbox is enabled: one outbox row per
+/// changed resource row in `public`.
+///
+/// Writes to tenant schemas are not recorded, as [`EventedStorage`] emits no
+/// events for them: the hooks are not tenant-aware and the relay runs outside
+/// any tenant scope, so they would act on the shared data instead.
+///
+/// [`EventedStorage`]: octofhir_storage::EventedStorage
+const ENQUEUE_ENABLED_SQL: &str = r#"
+    CREATE OR REPLACE FUNCTION enqueue_resource_event()
+    RETURNS TRIGGER AS $func$
+    BEGIN
+        IF TG_TABLE_SCHEMA <> 'public' THEN
+            RETURN NULL;
+        END IF;
+        IF TG_OP = 'DELETE' THEN
+            INSERT INTO public._event_outbox (resource_type, resource_id, event_type, txid, payload)
+            VALUES (OLD.resource->>'resourceType', OLD.id::text, 'deleted', OLD.txid, NULL);
+        ELSE
+            INSERT INTO public._event_outbox (resource_type, resource_id, event_type, txid, payload)
+            VALUES (
+                NEW.resource->>'resourceType',
+                NEW.id::text,
+                CASE TG_OP WHEN 'INSERT' THEN 'created' ELSE 'updated' END,
+                NEW.txid,
+                NEW.resource
+            );
+        END IF;
+        RETURN NULL;
+    END;
+    $func$ LANGUAGE plpgsql;
+"#;
+
+/// `enqueue_resource_event()` when the outbox is disabled: the triggers stay
+/// in place but record nothing.
+const ENQUEUE_DISABLED_SQL: &str = r#"
+    CREATE OR REPLACE FUNCTION enqueue_resource_event()
+    RETURNS TRIGGER AS $func$
+    BEGIN
+        RETURN NULL;
+    END;
+    $func$ LANGUAGE plpgsql;
+"#;
+
+/// Turns recording of resource changes into the outbox on or off.
+///
+/// The setting is database-wide: it replaces the body of the
+/// `enqueue_resource_event()` trigger function shared by all resource tables.
+/// Call once at startup, before writes are accepted.
+///
+/// # Errors
+///
+/// Returns an error if the function cannot be replaced.
+pub async fn set_outbox_enabled(pool: &PgPool, enabled: bool) -> Result<()> {
+    let sql = if enabled {
+        ENQUEUE_ENABLED_SQL
+    } else {
+        ENQUEUE_DISABLED_SQL
+    };
+    sqlx_core::raw_sql::raw_sql(AssertSqlSafe(sql.to_string()))
+        .execute(pool)
+        .await
+        .map_err(PostgresError::from)?;
+    debug!(enabled, "Configured event outbox");
+    Ok(())
+}
+
+/// One `_event_outbox` row.
+type OutboxRow = (
+    i64,
+    String,
+    String,
+    String,
+    i64,
+    Option<Value>,
+    DateTime<Utc>,
+);
+
+/// Builds the hook event for an outbox row, or `None` for an unknown event
+/// type.
+fn event_from_row(row: OutboxRow) -> Option<ResourceEvent> {
+    let (_, resource_type, resource_id, event_type, txid, payload, created_at) = row;
+    let event_type = match event_type.as_str() {
+        "created" => ResourceEventType::Created,
+        "updated" => ResourceEventType::Updated,
+        "deleted" => ResourceEventType::Deleted,
+        _ => return None,
+    };
+    let timestamp = created_at
+        .timestamp_nanos_opt()
+        .and_then(|nanos| OffsetDateTime::from_unix_timestamp_nanos(i128::from(nanos)).ok())
+        .unwrap_or_else(OffsetDateTime::now_utc);
+    Some(ResourceEvent {
+        event_type,
+        resource_type,
+        resource_id,
+        version_id: Some(txid),
+        resource: payload.map(Arc::new),
+        timestamp,
+    })
+}
+
+/// Background task delivering outbox rows to at-least-once hooks.
+pub struct OutboxRelay {
+    pool: PgPool,
+    registry: Arc<HookRegistry>,
+    poll_interval: Duration,
+    batch_size: i64,
+    /// Identifies this relay as the holder of hook leases.
+    holder: Uuid,
+}
+
+impl OutboxRelay {
+    /// Creates a relay for the at-least-once hooks of `registry`.
+    pub fn new(pool: PgPool, registry: Arc<HookRegistry>) -> Self {
+        Self {
+            pool,
+            registry,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            batch_size: DEFAULT_BATCH_SIZE,
+            holder: Uuid::new_v4(),
+        }
+    }
+
+    /// Sets how often the outbox is polled.
+    #[must_use]
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Sets the maximum number of rows delivered to one hook per poll.
+    #[must_use]
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = i64::try_from(batch_size.max(1)).unwrap_or(i64::MAX);
+        self
+    }
+
+    /// Spawns the relay loop.
+    ///
+    /// The task runs forever; cancel it by aborting the returned `JoinHandle`.
+    pub fn spawn(self) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(self.poll_interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+            loop {
+                ticker.tick().await;
+                if let Err(e) = self.poll().await {
+                    warn!(error = %e, "Event outbox relay poll failed");
+                }
+            }
+        })
+    }
+
+    /// Delivers pending rows to every at-least-once hook, then prunes rows
+    /// all of them have acknowledged.
+    ///
+    /// Returns the number of events the hooks acknowledged.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if reading the outbox or recording acknowledgments
+    /// fails. Hook failures are logged, not returned.
+    pub async fn poll(&self) -> Result<usize> {
+        let hooks = self.registry.at_least_once_hooks().await;
+        if hooks.is_empty() {
+            return Ok(0);
+        }
+
+        let mut delivered = 0;
+        for hook in &hooks {
+            delivered += self.deliver(hook.as_ref()).await?;
+        }
+
+        let names: Vec<String> = hooks.iter().map(|h| h.name().to_string()).collect();
+        let pruned = sqlx_core::query::query(
+            "DELETE FROM _event_outbox
+             WHERE id IN (
+                 SELECT event_id FROM _event_outbox_ack
+                 WHERE hook_name = ANY($1)
+                 GROUP BY event_id
+                 HAVING count(*) = cardinality($1)
+             )",
+        )
+        .bind(&names)
+        .execute(&self.pool)
+        .await
+        .map_err(PostgresError::from)?
+        .rows_affected();
+        if pruned > 0 {
+            debug!(pruned, "Pruned delivered outbox events");
+        }
+
+        Ok(delivered)
+    }
+
+    /// Delivers one batch of unacknowledged rows to `hook`.
+    ///
+    /// Skips the hook while another relay holds its lease. Each delivery is
+    /// acknowledged as soon as the hook returns, so if the process dies
+    /// mid-batch only the event being handled is delivered again.
+    async fn deliver(&self, hook: &dyn ResourceHook) -> Result<usize> {
+        let name = hook.name();
+        if !self.claim(name).await? {
+            return Ok(0);
+        }
+        let result = self.deliver_leased(hook).await;
+        if let Err(e) = self.release(name).await {
+            warn!(hook = %name, error = %e, "Failed to release event outbox lease");
+        }
+        result
+    }
+
+    /// Delivers one batch to `hook` while holding its lease.
+    async fn deliver_leased(&self, hook: &dyn ResourceHook) -> Result<usize> {
+        let name = hook.name();
+        let rows: Vec<OutboxRow> = sqlx_core::query_as::query_as(
+            "SELECT o.id, o.resource_type, o.resource_id, o.event_type, o.txid, o.payload, o.created_at
+             FROM _event_outbox o
+             WHERE NOT EXISTS (
+                 SELECT 1 FROM _event_outbox_ack a
+                 WHERE a.event_id = o.id AND a.hook_name = $1
+             )
+             ORDER BY o.id
+             LIMIT $2",
+        )
+        .bind(name)
+        .bind(self.batch_size)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(PostgresError::from)?;
+
+        let mut delivered = 0;
+        for row in rows {
+            let id = row.0;
+            if let Some(event) = event_from_row(row)
+                && hook.matches(&event)
+            {
+                // Cover this invocation; stop if the lease was lost to
+                // another relay after it expired.
+                if !self.claim(name).await? {
+                    warn!(hook = %name, "Event outbox lease lost, stopping batch");
+                    break;
+                }
+                if let Err(reason) = self.invoke(hook, &event).await {
+                    // Stop here so the failed event and everything after it
+                    // are retried, in order, on the next poll.
+                    warn!(
+                        hook = %name,
+                        event_id = id,
+                        error = %reason,
+                        "At-least-once hook failed, will retry"
+                    );
+                    break;
+                }
+                delivered += 1;
+            }
+            // Non-matching rows are acknowledged too, so they can be pruned.
+            sqlx_core::query::query(
+                "INSERT INTO _event_outbox_ack (event_id, hook_name)
+                 VALUES ($1, $2)
+                 ON CONFLICT DO NOTHING",
+            )
+            .bind(id)
+            .bind(name)
+            .execute(&self.pool)
+            .await
+            .map_err(PostgresError::from)?;
+        }
+
+        Ok(delivered)
+    }
+
+    /// Takes or renews the lease of hook `name` for one hook invocation plus
+    /// [`LEASE_MARGIN`].
+    ///
+    /// Returns `false` when another relay holds an unexpired lease.
+    async fn claim(&self, name: &str) -> Result<bool> {
+        let lease = self.registry.timeout() + LEASE_MARGIN;
+        let claimed: Option<String> = sqlx_core::query_scalar::query_scalar(
+            "INSERT INTO _event_outbox_lease (hook_name, holder, leased_until)
+             VALUES ($1, $2, NOW() + make_interval(secs => $3))
+             ON CONFLICT (hook_name) DO UPDATE
+             SET holder = EXCLUDED.holder, leased_until = EXCLUDED.leased_until
+             WHERE _event_outbox_lease.holder = EXCLUDED.holder
+                OR _event_outbox_lease.leased_until < NOW()
+             RETURNING hook_name",
+        )
+        .bind(name)
+        .bind(self.holder)
+        .bind(lease.as_secs_f64())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(PostgresError::from)?;
+        Ok(claimed.is_some())
+    }
+
+    /// Gives up the lease of hook `name`, if this relay holds it.
+    async fn release(&self, name: &str) -> Result<()> {
+        sqlx_core::query::query(
+            "DELETE FROM _event_outbox_lease WHERE hook_name = $1 AND holder = $2",
+        )
+        .bind(name)
+        .bind(self.holder)
+        .execute(&self.pool)
+        .await
+        .map_err(PostgresError::from)?;
+        Ok(())
+    }
+
+    /// Runs the hook with the registry's timeout and panic protection.
+    async fn invoke(
+        &self,
+        hook: &dyn ResourceHook,
+        event: &ResourceEvent,
+    ) -> std::result::Result<(), String> {
+        let timeout = self.registry.timeout();
+        match tokio::time::timeout(timeout, AssertUnwindSafe(hook.handle(event)).catch_unwind())
+            .await
+        {
+            Ok(Ok(Ok(()))) => Ok(()),
+            Ok(Ok(Err(e))) => Err(e.to_string()),
+            Ok(Err(_)) => Err("hook panicked".to_string()),
+            Err(_) => Err(format!("hook timed out after {}s", timeout.as_secs())),
+        }
+    }
+}
+
+impl std::fmt::Debug for OutboxRelay {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OutboxRelay")
+            .field("poll_interval", &self.poll_interval)
+            .field("batch_size", &self.batch_size)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn row(event_type: &str, payload: Option<Value>) -> OutboxRow {
+        (
+            7,
+            "Patient".to_string(),
+            "p1".to_string(),
+            event_type.to_string(),
+            42,
+            payload,
+            Utc::now(),
+        )
+    }
+
+    #[test]
+    fn test_event_from_row() {
+        let event = event_from_row(row(
+            "updated",
+            Some(json!({"resourceType": "Patient", "id": "p1"})),
+        ))
+        .unwrap();
+        assert_eq!(event.event_type, ResourceEventType::Updated);
+        assert_eq!(event.resource_type, "Patient");
+        assert_eq!(event.resource_id, "p1");
+        assert_eq!(event.version_id, Some(42));
+        assert!(event.resource.is_some());
+
+        let event = event_from_row(row("deleted", None)).unwrap();
+        assert_eq!(event.event_type, ResourceEventType::Deleted);
+        assert!(event.resource.is_none());
+    }
+
+    #[test]
+    fn test_event_from_row_unknown_type() {
+        assert!(event_from_row(row("patched", None)).is_none());
+    }
+
+    #[test]
+    fn test_enqueue_skips_tenant_schemas() {
+        let guard = ENQUEUE_ENABLED_SQL
+            .find("IF TG_TABLE_SCHEMA <> 'public' THEN")
+            .expect("tenant writes should be skipped");
+        let insert = ENQUEUE_ENABLED_SQL.find("INSERT INTO").unwrap();
+        assert!(guard < insert);
+    }
+}
@@ -9,9 +9,11 @@
 //! and reduce memory usage by using an LRU cache backed by the database.
 
 use sqlx_core::sql_str::AssertSqlSafe;
+use std::cmp::Ordering;
 use std::path::PathBuf;
 
 use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use sqlx_core::query::query;
 use sqlx_core::query_scalar::query_scalar;
@@ -24,7 +26,7 @@ use octofhir_canonical_manager::error::{FcmError, StorageError};
 use octofhir_canonical_manager::package::{ExtractedPackage, FhirResource};
 use octofhir_canonical_manager::traits::{PackageStore, SearchStorage};
 
-use crate::SchemaManager;
+use crate::{JsonbCompression, SchemaManager};
 
 /// PostgreSQL storage backend for FHIR packages from canonical manager.
 ///
@@ -34,6 +36,33 @@ use crate::SchemaManager;
 #[derive(Debug, Clone)]
 pub struct PostgresPackageStore {
     pool: PgPool,
+    compression: JsonbCompression,
+    resource_types: Vec<String>,
+    version_policy: CanonicalVersionPolicy,
+}
+
+/// Which version a canonical URL without `|version` resolves to when
+/// several versions of it are loaded (e.g. two versions of the same IG).
+///
+/// A URL with `|version` always resolves to exactly that version.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CanonicalVersionPolicy {
+    /// The highest version.
+    #[default]
+    Latest,
+    /// The highest version whose status is neither `draft` nor `retired`.
+    /// Falls back to the highest version when every version is.
+    LatestStable,
+    /// No version is picked: the URL does not resolve while more than one
+    /// version is loaded.
+    Strict,
+}
+
+/// A stored resource a canonical URL may resolve to.
+struct CanonicalCandidate {
+    index: ResourceIndex,
+    status: Option<String>,
 }
 
 /// Helper struct for StructureDefinition fields
@@ -136,7 +165,34 @@ fn row_to_resource_index(row: &sqlx_postgres::PgRow) -> ResourceIndex {
 impl PostgresPackageStore {
     /// Creates a new PostgreSQL package store.
     pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+        Self {
+            pool,
+            compression: JsonbCompression::Default,
+            resource_types: Vec::new(),
+            version_policy: CanonicalVersionPolicy::default(),
+        }
+    }
+
+    /// Sets the `resource` column compression used by [`Self::ensure_resource_tables`].
+    #[must_use]
+    pub fn with_compression(mut self, compression: JsonbCompression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Limits [`Self::ensure_resource_tables`] to these resource types, plus
+    /// the internal types of the `octofhir.*` packages. Empty means all types.
+    #[must_use]
+    pub fn with_resource_types(mut self, resource_types: Vec<String>) -> Self {
+        self.resource_types = resource_types;
+        self
+    }
+
+    /// Sets how canonical URLs without a version pick between loaded versions.
+    #[must_use]
+    pub fn with_version_policy(mut self, policy: CanonicalVersionPolicy) -> Self {
+        self.version_policy = policy;
+        self
     }
 
     /// Returns a reference to the connection pool.
@@ -464,11 +520,110 @@ impl PostgresPackageStore {
         FROM fcm.resources
     "#;
 
+    /// Like [`Self::RESOURCE_SELECT`], plus the resource status, for rows
+    /// ordered by package priority.
+    const CANDIDATE_SELECT: &'static str = r#"
+        SELECT
+            r.resource_type, r.resource_id, r.url, r.name, r.version,
+            r.package_name, r.package_version, r.fhir_version, r.content_hash,
+            r.sd_kind, r.sd_derivation, r.sd_type, r.sd_base_definition, r.sd_abstract,
+            r.sd_impose_profiles, r.sd_characteristics, r.sd_flavor,
+            r.content->>'status' AS status
+        FROM fcm.resources r
+        JOIN fcm.packages p ON r.package_name = p.name AND r.package_version = p.version
+    "#;
+
+    /// Resolves a canonical URL, optionally in `url|version` form, following
+    /// the store's [`CanonicalVersionPolicy`].
+    async fn resolve_canonical(
+        &self,
+        canonical_url: &str,
+        fhir_version: Option<&str>,
+    ) -> octofhir_canonical_manager::error::Result<Option<ResourceIndex>> {
+        let (url, version) = match canonical_url.split_once('|') {
+            Some((url, version)) => (url, Some(version)),
+            None => (canonical_url, None),
+        };
+
+        let sql = format!(
+            "{} WHERE (r.url = $1 OR r.url_lower = lower($1)) \
+             AND ($2::text IS NULL OR r.fhir_version = $2) \
+             ORDER BY p.priority DESC, r.package_name, r.package_version",
+            Self::CANDIDATE_SELECT
+        );
+        let rows = query(AssertSqlSafe(sql))
+            .bind(url)
+            .bind(fhir_version)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        let candidates = rows
+            .iter()
+            .map(|row| CanonicalCandidate {
+                index: row_to_resource_index(row),
+                status: row.get("status"),
+            })
+            .collect();
+        Ok(select_canonical_version(
+            url,
+            candidates,
+            version,
+            self.version_policy,
+        ))
+    }
+
+    /// Canonical URLs loaded in more than one version, with those versions
+    /// from highest to lowest.
+    pub async fn list_version_conflicts(&self) -> Result<Vec<(String, Vec<String>)>, FcmError> {
+        let rows = query(
+            r#"
+            SELECT url, array_agg(DISTINCT version) AS versions
+            FROM fcm.resources
+            WHERE url IS NOT NULL AND version IS NOT NULL
+            GROUP BY url
+            HAVING count(DISTINCT version) > 1
+            ORDER BY url
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(rows
+            .iter()
+            .map(|row| {
+                let mut versions: Vec<String> = row.get("versions");
+                versions.sort_by(|a, b| compare_versions(b, a));
+                (row.get("url"), versions)
+            })
+            .collect())
+    }
+
+    /// Resource types defined by the internal `octofhir.*` packages (`User`,
+    /// `Client`, `AccessPolicy`, ...).
+    pub async fn internal_resource_types(&self) -> Result<Vec<String>, FcmError> {
+        query_scalar(
+            r#"
+            SELECT DISTINCT name
+            FROM fcm.resources
+            WHERE resource_type = 'StructureDefinition'
+              AND (sd_kind = 'resource' OR sd_kind = 'logical')
+              AND name IS NOT NULL
+              AND package_name LIKE 'octofhir.%'
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)
+    }
+
     /// Creates database tables for all resource-kind StructureDefinitions in FCM.
     ///
     /// This method queries the FCM resources table for StructureDefinitions with
     /// `sd_kind = 'resource'` or `sd_kind = 'logical'` and ensures that corresponding
-    /// tables exist in the public schema.
+    /// tables exist in the public schema. Types outside the allowlist set with
+    /// [`Self::with_resource_types`] are skipped.
     ///
     /// # Returns
     ///
@@ -489,9 +644,13 @@ impl PostgresPackageStore {
               AND (sd_kind = 'resource' OR sd_kind = 'logical')
               AND (sd_derivation IS NULL OR sd_derivation = 'specialization')
               AND name IS NOT NULL
+              AND (cardinality($1::text[]) = 0
+                   OR name = ANY($1)
+                   OR package_name LIKE 'octofhir.%')
             ORDER BY name
             "#,
         )
+        .bind(&self.resource_types)
         .fetch_all(&self.pool)
         .await
         .map_err(db_error)?;
@@ -558,9 +717,10 @@ impl PostgresPackageStore {
         for resource_type in need_create {
             let pool = self.pool.clone();
             let sem = semaphore.clone();
+            let compression = self.compression;
             set.spawn(async move {
                 let _permit = sem.acquire_owned().await.ok();
-                let schema_manager = SchemaManager::new(pool);
+                let schema_manager = SchemaManager::new(pool).with_compression(compression);
                 let result = schema_manager.create_resource_schema(&resource_type).await;
                 (resource_type, result)
             });
@@ -1649,18 +1809,7 @@ impl PackageStore for PostgresPackageStore {
     ) -> octofhir_canonical_manager::error::Result<Option<ResourceIndex>> {
         debug!("Finding resource by canonical URL: {}", canonical_url);
 
-        let sql = format!(
-            "{} WHERE url = $1 OR url_lower = lower($1) LIMIT 1",
-            Self::RESOURCE_SELECT
-        );
-
-        let row = query(AssertSqlSafe(sql.to_string()))
-            .bind(canonical_url)
-            .fetch_optional(&self.pool)
-            .await
-            .map_err(db_error)?;
-
-        Ok(row.as_ref().map(row_to_resource_index))
+        self.resolve_canonical(canonical_url, None).await
     }
 
     #[instrument(skip(self))]
@@ -1715,19 +1864,8 @@ impl SearchStorage for PostgresPackageStore {
             canonical_url, fhir_version
         );
 
-        let sql = format!(
-            "{} WHERE (url = $1 OR url_lower = lower($1)) AND fhir_version = $2 LIMIT 1",
-            Self::RESOURCE_SELECT
-        );
-
-        let row = query(AssertSqlSafe(sql.to_string()))
-            .bind(canonical_url)
-            .bind(fhir_version)
-            .fetch_optional(&self.pool)
+        self.resolve_canonical(canonical_url, Some(fhir_version))
             .await
-            .map_err(db_error)?;
-
-        Ok(row.as_ref().map(row_to_resource_index))
     }
 
     async fn find_by_base_url(
@@ -2030,10 +2168,186 @@ impl SearchStorage for PostgresPackageStore {
     }
 }
 
+/// Picks the resource a canonical URL resolves to. `candidates` are all
+/// stored rows for the URL, highest package priority first; among rows with
+/// the same version the first one wins.
+fn select_canonical_version(
+    url: &str,
+    candidates: Vec<CanonicalCandidate>,
+    version: Option<&str>,
+    policy: CanonicalVersionPolicy,
+) -> Option<ResourceIndex> {
+    if let Some(version) = version {
+        return candidates
+            .into_iter()
+            .find(|c| c.index.version.as_deref() == Some(version))
+            .map(|c| c.index);
+    }
+
+    let mut versions: Vec<String> = candidates
+        .iter()
+        .filter_map(|c| c.index.version.clone())
+        .collect();
+    versions.sort_by(|a, b| compare_versions(b, a));
+    versions.dedup();
+    if versions.len() > 1 {
+        if policy == CanonicalVersionPolicy::Strict {
+            warn!(
+                url = %url,
+                versions = ?versions,
+                "Canonical URL is loaded in several versions; the strict version policy requires url|version"
+            );
+            return None;
+        }
+        debug!(url = %url, versions = ?versions, policy = ?policy, "Canonical URL is loaded in several versions");
+    }
+
+    let is_stable =
+        |c: &CanonicalCandidate| !matches!(c.status.as_deref(), Some("draft" | "retired"));
+    let candidates: Vec<CanonicalCandidate> =
+        if policy == CanonicalVersionPolicy::LatestStable && candidates.iter().any(is_stable) {
+            candidates.into_iter().filter(is_stable).collect()
+        } else {
+            candidates
+        };
+
+    candidates
+        .into_iter()
+        .reduce(|best, c| {
+            let newer = match (&c.index.version, &best.index.version) {
+                (Some(a), Some(b)) => compare_versions(a, b) == Ordering::Greater,
+                (Some(_), None) => true,
+                _ => false,
+            };
+            if newer { c } else { best }
+        })
+        .map(|c| c.index)
+}
+
+/// Orders business versions: dot-separated numeric parts compare as numbers,
+/// and a pre-release (`1.0.0-ballot`) sorts before its release.
+fn compare_versions(a: &str, b: &str) -> Ordering {
+    let (a_core, a_pre) = a.split_once('-').map_or((a, None), |(c, p)| (c, Some(p)));
+    let (b_core, b_pre) = b.split_once('-').map_or((b, None), |(c, p)| (c, Some(p)));
+
+    let mut a_parts = a_core.split('.');
+    let mut b_parts = b_core.split('.');
+    loop {
+        let ord = match (a_parts.next(), b_parts.next()) {
+            (None, None) => break,
+            (Some(_), None) => Ordering::Greater,
+            (None, Some(_)) => Ordering::Less,
+            (Some(x), Some(y)) => match (x.parse::<u64>(), y.parse::<u64>()) {
+                (Ok(x), Ok(y)) => x.cmp(&y),
+                _ => x.cmp(y),
+            },
+        };
+        if ord != Ordering::Equal {
+            return ord;
+        }
+    }
+
+    match (a_pre, b_pre) {
+        (None, None) => Ordering::Equal,
+        (None, Some(_)) => Ordering::Greater,
+        (Some(_), None) => Ordering::Less,
+        (Some(x), Some(y)) => x.cmp(y),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn candidate(version: &str, status: &str, package: &str) -> CanonicalCandidate {
+        CanonicalCandidate {
+            index: ResourceIndex {
+                canonical_url: "http://example.org/StructureDefinition/x".to_string(),
+                resource_type: "StructureDefinition".to_string(),
+                package_name: package.to_string(),
+                package_version: version.to_string(),
+                fhir_version: "4.0.1".to_string(),
+                file_path: PathBuf::new(),
+                id: None,
+                name: None,
+                version: Some(version.to_string()),
+                sd_kind: None,
+                sd_derivation: None,
+                sd_type: None,
+                sd_base_definition: None,
+                sd_abstract: None,
+                sd_impose_profiles: None,
+                sd_characteristics: None,
+                sd_flavor: None,
+            },
+            status: Some(status.to_string()),
+        }
+    }
+
+    fn select(version: Option<&str>, policy: CanonicalVersionPolicy) -> Option<String> {
+        let candidates = vec![
+            candidate("1.10.0", "draft", "ig"),
+            candidate("1.9.0", "active", "ig"),
+            candidate("1.2.0", "retired", "ig"),
+        ];
+        select_canonical_version("http://example.org", candidates, version, policy)
+            .and_then(|index| index.version)
+    }
+
+    #[test]
+    fn test_compare_versions() {
+        assert_eq!(compare_versions("1.10.0", "1.9.0"), Ordering::Greater);
+        assert_eq!(compare_versions("2.0", "2.0.0"), Ordering::Less);
+        assert_eq!(compare_versions("1.0.0-ballot", "1.0.0"), Ordering::Less);
+        assert_eq!(compare_versions("3.1.1", "3.1.1"), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_select_canonical_version_by_policy() {
+        use CanonicalVersionPolicy::*;
+
+        assert_eq!(select(None, Latest).as_deref(), Some("1.10.0"));
+        assert_eq!(select(None, LatestStable).as_deref(), Some("1.9.0"));
+        assert_eq!(select(None, Strict), None);
+        // An explicit version wins under every policy.
+        for policy in [Latest, LatestStable, Strict] {
+            assert_eq!(select(Some("1.2.0"), policy).as_deref(), Some("1.2.0"));
+            assert_eq!(select(Some("3.0.0"), policy), None);
+        }
+    }
+
+    #[test]
+    fn test_select_canonical_version_single_version() {
+        // The same version from two packages is not a conflict; the
+        // higher-priority package (listed first) wins.
+        let candidates = vec![
+            candidate("1.0.0", "draft", "a"),
+            candidate("1.0.0", "draft", "b"),
+        ];
+        let selected = select_canonical_version(
+            "http://example.org",
+            candidates,
+            None,
+            CanonicalVersionPolicy::Strict,
+        )
+        .unwrap();
+        assert_eq!(selected.package_name, "a");
+
+        // Every version draft: latest-stable falls back to the latest.
+        let candidates = vec![
+            candidate("1.0.0", "draft", "a"),
+            candidate("2.0.0", "draft", "a"),
+        ];
+        let selected = select_canonical_version(
+            "http://example.org",
+            candidates,
+            None,
+            CanonicalVersionPolicy::LatestStable,
+        )
+        .unwrap();
+        assert_eq!(selected.version.as_deref(), Some("2.0.0"));
+    }
+
     #[test]
     fn test_sd_flavor_determination() {
         let content = serde_json::json!({
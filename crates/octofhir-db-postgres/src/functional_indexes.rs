@@ -7,8 +7,8 @@
 //!
 //! Plain `CREATE INDEX IF NOT EXISTS` (not CONCURRENTLY) is correct here: the
 //! tables are empty and not yet serving traffic at bootstrap, so the brief
-//! ACCESS EXCLUSIVE lock is free. A future runtime "add index on a live table"
-//! path (DB console / suggest-index) should use CONCURRENTLY instead.
+//! ACCESS EXCLUSIVE lock is free. [`rebuild_search_indexes`] (the `$reindex`
+//! operation) runs against live tables and builds CONCURRENTLY instead.
 
 use octofhir_search::SearchParameterRegistry;
 use octofhir_search::loader::ElementTypeResolver;
@@ -20,6 +20,7 @@ use octofhir_search::sql_builder::{
 };
 use sqlx_core::sql_str::AssertSqlSafe;
 use sqlx_postgres::PgPool;
+use std::time::Duration;
 use tracing::{debug, info, warn};
 
 /// Normalizes a scalar-or-array JSONB value to an array (null -> `[]`). Used by the
@@ -117,6 +118,74 @@ pub async fn create_default_search_indexes(
     registry: &SearchParameterRegistry,
     params: &[String],
     resolver: &dyn ElementTypeResolver,
+) -> usize {
+    build_search_indexes(pool, registry, params, resolver, None).await
+}
+
+/// Rebuild the functional search indexes of `params` on live tables, for the
+/// current SearchParameter definitions.
+///
+/// Each index is built `CONCURRENTLY` under a temporary name and then swapped
+/// in for the old one, so searches keep an index throughout and writes are not
+/// blocked. `pause` is slept after every build and swap to leave the database
+/// room for regular traffic. Returns the number of indexes built.
+pub async fn rebuild_search_indexes(
+    pool: &PgPool,
+    registry: &SearchParameterRegistry,
+    params: &[String],
+    resolver: &dyn ElementTypeResolver,
+    pause: Duration,
+) -> usize {
+    build_search_indexes(pool, registry, params, resolver, Some(pause)).await
+}
+
+/// Runs one `CREATE INDEX IF NOT EXISTS "name" ...` statement. With a rebuild
+/// pause, builds the index concurrently and replaces the existing one.
+async fn execute_index_ddl(
+    pool: &PgPool,
+    ddl: &str,
+    rebuild_pause: Option<Duration>,
+) -> Result<(), sqlx_core::Error> {
+    let execute = |sql: String| async move {
+        sqlx_core::raw_sql::raw_sql(AssertSqlSafe(sql))
+            .execute(pool)
+            .await
+            .map(|_| ())
+    };
+    let Some(pause) = rebuild_pause else {
+        return execute(ddl.to_string()).await;
+    };
+    let Some(name) = ddl.split('"').nth(1) else {
+        return execute(ddl.to_string()).await;
+    };
+
+    let temp = format!("{name}_rebuild");
+    let build = ddl.replacen(
+        &format!("CREATE INDEX IF NOT EXISTS \"{name}\""),
+        &format!("CREATE INDEX CONCURRENTLY \"{temp}\""),
+        1,
+    );
+    // Left behind by an interrupted rebuild.
+    execute(format!("DROP INDEX CONCURRENTLY IF EXISTS \"{temp}\"")).await?;
+    if let Err(e) = execute(build).await {
+        // A failed concurrent build leaves an INVALID index.
+        let _ = execute(format!("DROP INDEX CONCURRENTLY IF EXISTS \"{temp}\"")).await;
+        return Err(e);
+    }
+    tokio::time::sleep(pause).await;
+
+    execute(format!("DROP INDEX CONCURRENTLY IF EXISTS \"{name}\"")).await?;
+    execute(format!("ALTER INDEX \"{temp}\" RENAME TO \"{name}\"")).await?;
+    tokio::time::sleep(pause).await;
+    Ok(())
+}
+
+async fn build_search_indexes(
+    pool: &PgPool,
+    registry: &SearchParameterRegistry,
+    params: &[String],
+    resolver: &dyn ElementTypeResolver,
+    rebuild_pause: Option<Duration>,
 ) -> usize {
     // Ensure the array-normalization helper exists before any typed function uses it.
     if let Err(e) = sqlx_core::raw_sql::raw_sql(AssertSqlSafe((FHIR_ARR_DDL).to_string()))
@@ -212,10 +281,7 @@ pub async fn create_default_search_indexes(
                             "CREATE INDEX IF NOT EXISTS \"idx_{table}_{code}_str\" ON \"{table}\" \
                              USING gin (fhir_text_blob({fn_name}(resource)) gin_trgm_ops)"
                         );
-                        match sqlx_core::raw_sql::raw_sql(AssertSqlSafe(index_ddl.to_string()))
-                            .execute(pool)
-                            .await
-                        {
+                        match execute_index_ddl(pool, &index_ddl, rebuild_pause).await {
                             Ok(_) => {
                                 registry.upsert((*param).clone().with_typed_extract_fn(fn_name));
                                 created += 1;
@@ -365,10 +431,7 @@ pub async fn create_default_search_indexes(
                         debug!(resource_type, code, "skipped duplicate quantity index");
                         continue;
                     }
-                    match sqlx_core::raw_sql::raw_sql(AssertSqlSafe(ddl.to_string()))
-                        .execute(pool)
-                        .await
-                    {
+                    match execute_index_ddl(pool, ddl, rebuild_pause).await {
                         Ok(_) => {
                             created += 1;
                             debug!(resource_type, code, "created quantity functional index");
@@ -391,10 +454,7 @@ pub async fn create_default_search_indexes(
             debug!(resource_type, code, "skipped duplicate functional index");
             continue;
         }
-        match sqlx_core::raw_sql::raw_sql(AssertSqlSafe(ddl.to_string()))
-            .execute(pool)
-            .await
-        {
+        match execute_index_ddl(pool, &ddl, rebuild_pause).await {
             Ok(_) => {
                 created += 1;
                 debug!(resource_type, code, "created functional search index");
@@ -567,7 +627,7 @@ async fn annotate_path(
 /// Build the annotated extraction paths for a STRING param: the same generic paths
 /// (`extraction_paths` over the FHIRPath-derived segments), each annotated with
 /// per-segment array cardinality.
-async fn build_annotated_paths_for_param(
+pub(crate) async fn build_annotated_paths_for_param(
     param: &octofhir_search::parameters::SearchParameter,
     resource_type: &str,
     resolver: &dyn ElementTypeResolver,
@@ -50,7 +50,9 @@
 //! - [`migrations`]: Database migration management
 
 mod config;
+mod deadline;
 mod error;
+pub mod event_outbox;
 mod fcm_storage;
 pub mod functional_indexes;
 pub mod gin_maintenance;
@@ -58,6 +60,7 @@ mod notification_storage;
 pub mod pool;
 mod query_analyzer;
 mod schema;
+pub mod search_index_tables;
 mod storage;
 mod transaction;
 
@@ -70,16 +73,23 @@ pub mod queries;
 // Re-export main types
 pub use config::PostgresConfig;
 pub use error::{PostgresError, Result};
-pub use fcm_storage::{FhirSchemaInfo, FhirSchemaRecord, PostgresPackageStore};
+pub use event_outbox::OutboxRelay;
+pub use fcm_storage::{
+    CanonicalVersionPolicy, FhirSchemaInfo, FhirSchemaRecord, PostgresPackageStore,
+};
 pub use functional_indexes::{
     CompositePartialIndex, create_composite_partial_indexes, create_default_search_indexes,
+    rebuild_search_indexes,
 };
 pub use notification_storage::PostgresNotificationStorage;
 pub use query_analyzer::{
     AnalyzerConfig, AnalyzerError, AnalyzerStatsSnapshot, BufferStats, IndexSuggestion, IndexUsage,
     QueryAnalysis, QueryAnalyzer, SeqScanInfo, SlowQueryRecord, SuggestionImpact,
 };
-pub use schema::SchemaManager;
+pub use schema::{CONTENT_TSV_COLUMN, JsonbCompression, SchemaManager, TEXT_TSV_COLUMN};
+pub use search_index_tables::{
+    backfill_search_index_tables, create_search_index_tables, search_index_table_name,
+};
 pub use storage::PostgresStorage;
 
 // Re-export storage traits for convenience
@@ -112,7 +122,9 @@ pub async fn create_storage(
 pub mod prelude {
     pub use crate::config::PostgresConfig;
     pub use crate::error::{PostgresError, Result};
-    pub use crate::fcm_storage::{FhirSchemaInfo, FhirSchemaRecord, PostgresPackageStore};
+    pub use crate::fcm_storage::{
+        CanonicalVersionPolicy, FhirSchemaInfo, FhirSchemaRecord, PostgresPackageStore,
+    };
     pub use crate::query_analyzer::{
         AnalyzerConfig, IndexSuggestion, QueryAnalysis, QueryAnalyzer,
     };
@@ -23,6 +23,36 @@ macro_rules! embedded_migrations {
                 "consolidated_schema",
                 include_str!("../../migrations/20241213000001_consolidated_schema.sql"),
             ),
+            (
+                20261016000001i64,
+                "event_outbox",
+                include_str!("../../migrations/20261016000001_event_outbox.sql"),
+            ),
+            (
+                20261016000002i64,
+                "subscription_secret",
+                include_str!("../../migrations/20261016000002_subscription_secret.sql"),
+            ),
+            (
+                20261016000003i64,
+                "smart_launch_consumed_at",
+                include_str!("../../migrations/20261016000003_smart_launch_consumed_at.sql"),
+            ),
+            (
+                20261016000004i64,
+                "async_job_tenant",
+                include_str!("../../migrations/20261016000004_async_job_tenant.sql"),
+            ),
+            (
+                20261017000001i64,
+                "async_job_secret",
+                include_str!("../../migrations/20261017000001_async_job_secret.sql"),
+            ),
+            (
+                20261017000002i64,
+                "event_outbox_lease",
+                include_str!("../../migrations/20261017000002_event_outbox_lease.sql"),
+            ),
         ]
     };
 }
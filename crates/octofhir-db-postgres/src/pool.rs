@@ -1,9 +1,9 @@
 //! Connection pool management for the PostgreSQL storage backend.
 
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use sqlx_core::pool::PoolOptions;
-use sqlx_postgres::{PgPool, Postgres};
+use sqlx_postgres::{PgConnection, PgPool, Postgres};
 use tracing::{debug, info, instrument};
 
 use crate::config::PostgresConfig;
@@ -19,6 +19,7 @@ pub async fn create_pool(config: &PostgresConfig) -> Result<PgPool> {
         pool_size = config.pool_size,
         min_connections = ?config.min_connections,
         connect_timeout_ms = config.connect_timeout_ms,
+        acquire_timeout_ms = config.effective_acquire_timeout_ms(),
         max_lifetime_secs = ?config.max_lifetime_secs,
         "Creating PostgreSQL connection pool"
     );
@@ -33,7 +34,7 @@ pub async fn create_pool(config: &PostgresConfig) -> Result<PgPool> {
     let mut options = PgPoolOptions::new()
         .max_connections(config.pool_size)
         .min_connections(min_connections)
-        .acquire_timeout(Duration::from_millis(config.connect_timeout_ms))
+        .acquire_timeout(Duration::from_millis(config.effective_acquire_timeout_ms()))
         .max_lifetime(Duration::from_secs(max_lifetime_secs))
         .test_before_acquire(false);
 
@@ -41,6 +42,17 @@ pub async fn create_pool(config: &PostgresConfig) -> Result<PgPool> {
         options = options.idle_timeout(Duration::from_millis(idle_timeout));
     }
 
+    // Pool hooks run inside `acquire()`, on the acquiring task, so they see
+    // that task's tenant. Every hand-out resets the path: a connection last
+    // used by one tenant must never reach another with its schema still set.
+    if config.tenant_isolation {
+        options = options
+            .after_connect(|conn, _| Box::pin(apply_tenant_search_path(conn)))
+            .before_acquire(|conn, _| {
+                Box::pin(async move { apply_tenant_search_path(conn).await.map(|()| true) })
+            });
+    }
+
     let pool = options.connect(&config.url).await?;
 
     debug!("PostgreSQL connection pool created successfully");
@@ -48,6 +60,63 @@ pub async fn create_pool(config: &PostgresConfig) -> Result<PgPool> {
     Ok(pool)
 }
 
+/// Opens `config.warmup_connections` connections (capped at the pool size),
+/// so the first requests after startup do not pay for connection setup.
+///
+/// The connections go back to the pool idle; those above `min_connections`
+/// are closed after `idle_timeout_ms` like any other.
+#[instrument(skip_all)]
+pub async fn warm_pool(pool: &PgPool, config: &PostgresConfig) -> Result<()> {
+    let connections = config.warmup_connections.min(config.pool_size);
+    if connections == 0 {
+        return Ok(());
+    }
+
+    let started = Instant::now();
+    // Held together: released one by one, each acquire would reuse the last
+    let held = futures_util::future::try_join_all((0..connections).map(|_| pool.acquire())).await?;
+    drop(held);
+
+    info!(
+        connections,
+        open = pool.size(),
+        elapsed_ms = started.elapsed().as_millis() as u64,
+        "PostgreSQL connection pool warmed"
+    );
+
+    Ok(())
+}
+
+/// `search_path` of connections outside any tenant scope (PostgreSQL's default).
+const DEFAULT_SEARCH_PATH: &str = "\"$user\", public";
+
+/// `search_path` for the current tenant.
+///
+/// The tenant schema comes first, so DDL for resource types created at
+/// runtime lands in it. `public` stays on the path for the shared functions,
+/// extensions, types, sequences and internal tables (users, clients,
+/// policies) that are not provisioned per tenant.
+///
+/// Resource tables are not resolved through this path: queries name them
+/// with [`crate::SchemaManager::qualified_table_name`], so a table missing
+/// from the tenant schema is an error rather than a read of `public`.
+pub fn tenant_search_path() -> String {
+    match octofhir_storage::current_tenant() {
+        Some(tenant) => format!("\"{}\", public", tenant.schema_name()),
+        None => DEFAULT_SEARCH_PATH.to_string(),
+    }
+}
+
+async fn apply_tenant_search_path(
+    conn: &mut PgConnection,
+) -> std::result::Result<(), sqlx_core::Error> {
+    sqlx_core::query::query("SELECT set_config('search_path', $1, false)")
+        .bind(tenant_search_path())
+        .execute(conn)
+        .await?;
+    Ok(())
+}
+
 /// Tests the connection to the database.
 #[allow(dead_code)]
 #[instrument(skip(pool))]
@@ -80,6 +149,14 @@ pub fn mask_password(url: &str) -> String {
 mod tests {
     use super::*;
 
+    #[tokio::test]
+    async fn test_tenant_search_path() {
+        assert_eq!(tenant_search_path(), "\"$user\", public");
+        let tenant = octofhir_storage::TenantId::new("acme").unwrap();
+        let path = octofhir_storage::with_tenant(tenant, async { tenant_search_path() }).await;
+        assert_eq!(path, "\"tenant_acme\", public");
+    }
+
     #[test]
     fn test_mask_password() {
         assert_eq!(
@@ -10,10 +10,10 @@ use sqlx_core::query_scalar::query_scalar;
 use sqlx_core::sql_str::AssertSqlSafe;
 use sqlx_postgres::{PgPool, PgTransaction};
 use time::OffsetDateTime;
-use uuid::Uuid;
 
 use octofhir_storage::{RawStoredResource, StorageError, StoredResource};
 
+use crate::error::{database_error, is_unique_violation};
 use crate::schema::SchemaManager;
 
 /// Converts chrono DateTime to time OffsetDateTime.
@@ -29,7 +29,7 @@ pub async fn create_transaction(pool: &PgPool) -> Result<i64, StorageError> {
     let txid: i64 = query_scalar("SELECT nextval('_transaction_txid_seq') AS txid")
         .fetch_one(pool)
         .await
-        .map_err(|e| StorageError::internal(format!("Failed to create transaction: {e}")))?;
+        .map_err(|e| database_error("Failed to create transaction", e))?;
 
     Ok(txid)
 }
@@ -63,28 +63,26 @@ pub async fn create(pool: &PgPool, resource: &Value) -> Result<StoredResource, S
         octofhir_core::generate_id()
     };
 
-    let now = Utc::now();
-
     // Insert into table with CTE for transaction creation (single query instead of two)
     // This combines the transaction creation and resource insertion atomically
     // We use jsonb_set to add id, meta.versionId and meta.lastUpdated in the SQL
     // This avoids cloning the entire resource JSON just to inject the id field
-    let table = SchemaManager::table_name(resource_type);
+    let table = SchemaManager::qualified_table_name(resource_type);
     let sql = format!(
         r#"WITH new_tx AS (
                SELECT nextval('_transaction_txid_seq') AS txid
            )
-           INSERT INTO "{table}" (id, txid, created_at, updated_at, resource, status)
+           INSERT INTO {table} (id, txid, created_at, updated_at, resource, status)
            SELECT
                $1,
                new_tx.txid,
-               $2,
-               $2,
-               $3::jsonb || jsonb_build_object(
+               now(),
+               now(),
+               $2::jsonb || jsonb_build_object(
                    'id', $1::text,
-                   'meta', COALESCE($3::jsonb -> 'meta', '{{}}'::jsonb) || jsonb_build_object(
+                   'meta', COALESCE($2::jsonb -> 'meta', '{{}}'::jsonb) || jsonb_build_object(
                        'versionId', new_tx.txid::text,
-                       'lastUpdated', to_char($2 AT TIME ZONE 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS.MS"Z"')
+                       'lastUpdated', to_char(now() AT TIME ZONE 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS.MS"Z"')
                    )
                ),
                'created'
@@ -95,16 +93,14 @@ pub async fn create(pool: &PgPool, resource: &Value) -> Result<StoredResource, S
     let row: (String, i64, DateTime<Utc>, DateTime<Utc>, Value) =
         query_as(AssertSqlSafe(sql.to_string()))
             .bind(&id)
-            .bind(now)
             .bind(resource)
             .fetch_one(pool)
             .await
             .map_err(|e| {
-                // Check for unique constraint violation
-                if e.to_string().contains("duplicate key") {
+                if is_unique_violation(&e) {
                     StorageError::already_exists(resource_type, &id)
                 } else {
-                    StorageError::internal(format!("Failed to create resource: {e}"))
+                    database_error("Failed to create resource", e)
                 }
             })?;
 
@@ -145,24 +141,22 @@ pub async fn create_raw(
         octofhir_core::generate_id()
     };
 
-    let now = Utc::now();
-
-    let table = SchemaManager::table_name(resource_type);
+    let table = SchemaManager::qualified_table_name(resource_type);
     let sql = format!(
         r#"WITH new_tx AS (
                SELECT nextval('_transaction_txid_seq') AS txid
            )
-           INSERT INTO "{table}" (id, txid, created_at, updated_at, resource, status)
+           INSERT INTO {table} (id, txid, created_at, updated_at, resource, status)
            SELECT
                $1,
                new_tx.txid,
-               $2,
-               $2,
-               $3::jsonb || jsonb_build_object(
+               now(),
+               now(),
+               $2::jsonb || jsonb_build_object(
                    'id', $1::text,
-                   'meta', COALESCE($3::jsonb -> 'meta', '{{}}'::jsonb) || jsonb_build_object(
+                   'meta', COALESCE($2::jsonb -> 'meta', '{{}}'::jsonb) || jsonb_build_object(
                        'versionId', new_tx.txid::text,
-                       'lastUpdated', to_char($2 AT TIME ZONE 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS.MS"Z"')
+                       'lastUpdated', to_char(now() AT TIME ZONE 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS.MS"Z"')
                    )
                ),
                'created'
@@ -173,15 +167,14 @@ pub async fn create_raw(
     let row: (String, i64, DateTime<Utc>, DateTime<Utc>, String) =
         query_as(AssertSqlSafe(sql.to_string()))
             .bind(&id)
-            .bind(now)
             .bind(resource)
             .fetch_one(pool)
             .await
             .map_err(|e| {
-                if e.to_string().contains("duplicate key") {
+                if is_unique_violation(&e) {
                     StorageError::already_exists(resource_type, &id)
                 } else {
-                    StorageError::internal(format!("Failed to create resource: {e}"))
+                    database_error("Failed to create resource", e)
                 }
             })?;
 
@@ -200,10 +193,10 @@ pub async fn create_raw(
 
 /// Checks whether a live (non-deleted) resource exists, without reading its body.
 pub async fn exists(pool: &PgPool, resource_type: &str, id: &str) -> Result<bool, StorageError> {
-    let table = SchemaManager::table_name(resource_type);
+    let table = SchemaManager::qualified_table_name(resource_type);
 
     let sql =
-        format!(r#"SELECT EXISTS(SELECT 1 FROM "{table}" WHERE id = $1 AND status != 'deleted')"#);
+        format!(r#"SELECT EXISTS(SELECT 1 FROM {table} WHERE id = $1 AND status != 'deleted')"#);
 
     let exists: (bool,) = query_as(AssertSqlSafe(sql.to_string()))
         .bind(id)
@@ -213,7 +206,7 @@ pub async fn exists(pool: &PgPool, resource_type: &str, id: &str) -> Result<bool
             if e.to_string().contains("does not exist") {
                 return StorageError::internal(format!("Table does not exist: {e}"));
             }
-            StorageError::internal(format!("Failed to check resource existence: {e}"))
+            database_error("Failed to check resource existence", e)
         })?;
 
     Ok(exists.0)
@@ -230,8 +223,8 @@ pub async fn exists_many(
     if ids.is_empty() {
         return Ok(std::collections::HashSet::new());
     }
-    let table = SchemaManager::table_name(resource_type);
-    let sql = format!(r#"SELECT id FROM "{table}" WHERE id = ANY($1) AND status != 'deleted'"#);
+    let table = SchemaManager::qualified_table_name(resource_type);
+    let sql = format!(r#"SELECT id FROM {table} WHERE id = ANY($1) AND status != 'deleted'"#);
 
     let rows: Vec<(String,)> = query_as(AssertSqlSafe(sql.to_string()))
         .bind(ids)
@@ -242,7 +235,7 @@ pub async fn exists_many(
             if e.to_string().contains("does not exist") {
                 return StorageError::internal(format!("Table does not exist: {e}"));
             }
-            StorageError::internal(format!("Failed to batch-check existence: {e}"))
+            database_error("Failed to batch-check existence", e)
         })?;
 
     Ok(rows.into_iter().map(|(id,)| id).collect())
@@ -285,12 +278,12 @@ pub async fn exists_many_grouped(
     // so no resource-type string is interpolated into the SQL.
     let mut sql = String::new();
     for (i, (rt, _ids)) in groups.iter().enumerate() {
-        let table = SchemaManager::table_name(rt);
+        let table = SchemaManager::qualified_table_name(rt);
         if i > 0 {
             sql.push_str(" UNION ALL ");
         }
         sql.push_str(&format!(
-            r#"SELECT {i}::int AS grp, id FROM "{table}" WHERE id = ANY(${n}) AND status != 'deleted'"#,
+            r#"SELECT {i}::int AS grp, id FROM {table} WHERE id = ANY(${n}) AND status != 'deleted'"#,
             n = i + 1
         ));
     }
@@ -337,10 +330,10 @@ pub async fn read_many(
     if ids.is_empty() {
         return Ok(Vec::new());
     }
-    let table = SchemaManager::table_name(resource_type);
+    let table = SchemaManager::qualified_table_name(resource_type);
     let sql = format!(
         r#"SELECT id, txid, created_at, updated_at, resource
-           FROM "{table}"
+           FROM {table}
            WHERE id = ANY($1) AND status != 'deleted'"#
     );
 
@@ -353,7 +346,7 @@ pub async fn read_many(
                 if e.to_string().contains("does not exist") {
                     return StorageError::internal(format!("Table does not exist: {e}"));
                 }
-                StorageError::internal(format!("Failed to batch-read resources: {e}"))
+                database_error("Failed to batch-read resources", e)
             })?;
 
     let out = rows
@@ -396,12 +389,12 @@ pub async fn read(
     resource_type: &str,
     id: &str,
 ) -> Result<Option<StoredResource>, StorageError> {
-    let table = SchemaManager::table_name(resource_type);
+    let table = SchemaManager::qualified_table_name(resource_type);
 
     // Query including status to detect deleted resources
     let sql = format!(
         r#"SELECT id, txid, created_at, updated_at, resource, status::text
-           FROM "{table}"
+           FROM {table}
            WHERE id = $1"#
     );
 
@@ -415,7 +408,7 @@ pub async fn read(
                 if e.to_string().contains("does not exist") {
                     return StorageError::internal(format!("Table does not exist: {e}"));
                 }
-                StorageError::internal(format!("Failed to read resource: {e}"))
+                database_error("Failed to read resource", e)
             })?;
 
     match row {
@@ -455,7 +448,7 @@ pub async fn read(
         }
         // Absent from the live table: never created (404) or deleted (410).
         // Deletes archive their versions to history, so a history hit means Gone.
-        None if history_has(pool, &table, id).await? => {
+        None if history_has(pool, resource_type, id).await? => {
             Err(StorageError::deleted(resource_type, id))
         }
         None => Ok(None),
@@ -473,11 +466,11 @@ pub async fn read_raw(
     resource_type: &str,
     id: &str,
 ) -> Result<Option<RawStoredResource>, StorageError> {
-    let table = SchemaManager::table_name(resource_type);
+    let table = SchemaManager::qualified_table_name(resource_type);
 
     let sql = format!(
         r#"SELECT id, txid, created_at, updated_at, resource::text, status::text
-           FROM "{table}"
+           FROM {table}
            WHERE id = $1"#
     );
 
@@ -490,7 +483,7 @@ pub async fn read_raw(
                 if e.to_string().contains("does not exist") {
                     return StorageError::internal(format!("Table does not exist: {e}"));
                 }
-                StorageError::internal(format!("Failed to read resource: {e}"))
+                database_error("Failed to read resource", e)
             })?;
 
     match row {
@@ -513,19 +506,20 @@ pub async fn read_raw(
         }
         // Absent from the live table: never created (404) or deleted (410).
         // Deletes archive their versions to history, so a history hit means Gone.
-        None if history_has(pool, &table, id).await? => {
+        None if history_has(pool, resource_type, id).await? => {
             Err(StorageError::deleted(resource_type, id))
         }
         None => Ok(None),
     }
 }
 
-/// True if any version of `id` exists in `{table}_history` — i.e. the resource
-/// once existed and was deleted (the live row is gone). Used to return 410 Gone
-/// instead of 404, since deletes physically remove the live row and archive it
-/// to history.
-async fn history_has(pool: &PgPool, table: &str, id: &str) -> Result<bool, StorageError> {
-    let sql = format!(r#"SELECT EXISTS(SELECT 1 FROM "{table}_history" WHERE id = $1)"#);
+/// True if any version of `id` exists in the history table of `resource_type`
+/// — i.e. the resource once existed and was deleted (the live row is gone).
+/// Used to return 410 Gone instead of 404, since deletes physically remove the
+/// live row and archive it to history.
+async fn history_has(pool: &PgPool, resource_type: &str, id: &str) -> Result<bool, StorageError> {
+    let history_table = SchemaManager::qualified_history_table_name(resource_type);
+    let sql = format!(r#"SELECT EXISTS(SELECT 1 FROM {history_table} WHERE id = $1)"#);
     let exists: bool = query_scalar(AssertSqlSafe(sql))
         .bind(id)
         .fetch_one(pool)
@@ -553,8 +547,7 @@ pub async fn update(
 
     // Tables are created at startup by bootstrap_conformance_if_postgres()
 
-    let table = SchemaManager::table_name(resource_type);
-    let now = Utc::now();
+    let table = SchemaManager::qualified_table_name(resource_type);
 
     // Single query with CTE: create transaction + update resource + set meta atomically
     // Version check is done in the WHERE clause if if_match is provided
@@ -570,15 +563,15 @@ pub async fn update(
                        SELECT nextval('_transaction_txid_seq') AS txid
                    ),
                    current AS (
-                       SELECT id, txid, created_at FROM "{table}"
+                       SELECT id, txid, created_at FROM {table}
                        WHERE id = $1 AND status != 'deleted'
                    )
-                   UPDATE "{table}" t
+                   UPDATE {table} t
                    SET txid = new_tx.txid,
                        resource = $2::jsonb || jsonb_build_object(
                            'meta', COALESCE($2::jsonb -> 'meta', '{{}}'::jsonb) || jsonb_build_object(
                                'versionId', new_tx.txid::text,
-                               'lastUpdated', to_char($3 AT TIME ZONE 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS.MS"Z"')
+                               'lastUpdated', to_char(now() AT TIME ZONE 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS.MS"Z"')
                            )
                        ),
                        status = 'updated'
@@ -597,12 +590,12 @@ pub async fn update(
                 r#"WITH new_tx AS (
                        SELECT nextval('_transaction_txid_seq') AS txid
                    )
-                   UPDATE "{table}" t
+                   UPDATE {table} t
                    SET txid = new_tx.txid,
                        resource = $2::jsonb || jsonb_build_object(
                            'meta', COALESCE($2::jsonb -> 'meta', '{{}}'::jsonb) || jsonb_build_object(
                                'versionId', new_tx.txid::text,
-                               'lastUpdated', to_char($3 AT TIME ZONE 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS.MS"Z"')
+                               'lastUpdated', to_char(now() AT TIME ZONE 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS.MS"Z"')
                            )
                        ),
                        status = 'updated'
@@ -618,10 +611,9 @@ pub async fn update(
         query_as(AssertSqlSafe(update_sql.to_string()))
             .bind(id)
             .bind(resource)
-            .bind(now)
             .fetch_optional(pool)
             .await
-            .map_err(|e| StorageError::internal(format!("Failed to update resource: {e}")))?;
+            .map_err(|e| database_error("Failed to update resource", e))?;
 
     match row {
         Some((
@@ -648,14 +640,12 @@ pub async fn update(
             if has_version_check {
                 // Check if resource exists with different version
                 let check_sql =
-                    format!(r#"SELECT txid FROM "{table}" WHERE id = $1 AND status != 'deleted'"#);
+                    format!(r#"SELECT txid FROM {table} WHERE id = $1 AND status != 'deleted'"#);
                 let current: Option<i64> = query_scalar(AssertSqlSafe(check_sql.to_string()))
                     .bind(id)
                     .fetch_optional(pool)
                     .await
-                    .map_err(|e| {
-                        StorageError::internal(format!("Failed to check resource: {e}"))
-                    })?;
+                    .map_err(|e| database_error("Failed to check resource", e))?;
 
                 match current {
                     Some(v) => Err(StorageError::version_conflict(
@@ -688,8 +678,7 @@ pub async fn update_raw(
         .as_str()
         .ok_or_else(|| StorageError::invalid_resource("Missing id field"))?;
 
-    let table = SchemaManager::table_name(resource_type);
-    let now = Utc::now();
+    let table = SchemaManager::qualified_table_name(resource_type);
 
     let (update_sql, has_version_check) = if let Some(expected_version) = if_match {
         let expected_txid: i64 = expected_version.parse().map_err(|_| {
@@ -702,15 +691,15 @@ pub async fn update_raw(
                        SELECT nextval('_transaction_txid_seq') AS txid
                    ),
                    current AS (
-                       SELECT id, txid, created_at FROM "{table}"
+                       SELECT id, txid, created_at FROM {table}
                        WHERE id = $1 AND status != 'deleted'
                    )
-                   UPDATE "{table}" t
+                   UPDATE {table} t
                    SET txid = new_tx.txid,
                        resource = $2::jsonb || jsonb_build_object(
                            'meta', COALESCE($2::jsonb -> 'meta', '{{}}'::jsonb) || jsonb_build_object(
                                'versionId', new_tx.txid::text,
-                               'lastUpdated', to_char($3 AT TIME ZONE 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS.MS"Z"')
+                               'lastUpdated', to_char(now() AT TIME ZONE 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS.MS"Z"')
                            )
                        ),
                        status = 'updated'
@@ -729,12 +718,12 @@ pub async fn update_raw(
                 r#"WITH new_tx AS (
                        SELECT nextval('_transaction_txid_seq') AS txid
                    )
-                   UPDATE "{table}" t
+                   UPDATE {table} t
                    SET txid = new_tx.txid,
                        resource = $2::jsonb || jsonb_build_object(
                            'meta', COALESCE($2::jsonb -> 'meta', '{{}}'::jsonb) || jsonb_build_object(
                                'versionId', new_tx.txid::text,
-                               'lastUpdated', to_char($3 AT TIME ZONE 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS.MS"Z"')
+                               'lastUpdated', to_char(now() AT TIME ZONE 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS.MS"Z"')
                            )
                        ),
                        status = 'updated'
@@ -750,10 +739,9 @@ pub async fn update_raw(
         query_as(AssertSqlSafe(update_sql.to_string()))
             .bind(id)
             .bind(resource)
-            .bind(now)
             .fetch_optional(pool)
             .await
-            .map_err(|e| StorageError::internal(format!("Failed to update resource: {e}")))?;
+            .map_err(|e| database_error("Failed to update resource", e))?;
 
     match row {
         Some((_returned_id, returned_txid, created_at, updated_at, resource_json, _old_txid)) => {
@@ -771,14 +759,12 @@ pub async fn update_raw(
         None => {
             if has_version_check {
                 let check_sql =
-                    format!(r#"SELECT txid FROM "{table}" WHERE id = $1 AND status != 'deleted'"#);
+                    format!(r#"SELECT txid FROM {table} WHERE id = $1 AND status != 'deleted'"#);
                 let current: Option<i64> = query_scalar(AssertSqlSafe(check_sql.to_string()))
                     .bind(id)
                     .fetch_optional(pool)
                     .await
-                    .map_err(|e| {
-                        StorageError::internal(format!("Failed to check resource: {e}"))
-                    })?;
+                    .map_err(|e| database_error("Failed to check resource", e))?;
 
                 match current {
                     Some(v) => Err(StorageError::version_conflict(
@@ -804,12 +790,12 @@ pub async fn update_raw(
 /// autovacuum cannot reclaim them), bloating the heap and the GIN index and
 /// degrading every read and write over time.
 ///
+/// The delete also records a tombstone, see [`delete_sql`].
+///
 /// Per FHIR spec, delete is idempotent - deleting a non-existent or
 /// already deleted resource returns success (204 No Content).
 pub async fn delete(pool: &PgPool, resource_type: &str, id: &str) -> Result<(), StorageError> {
-    let table = SchemaManager::table_name(resource_type);
-
-    let sql = format!(r#"DELETE FROM "{table}" WHERE id = $1"#);
+    let sql = delete_sql(resource_type);
 
     let _result = query(AssertSqlSafe(sql.to_string()))
         .bind(id)
@@ -820,7 +806,7 @@ pub async fn delete(pool: &PgPool, resource_type: &str, id: &str) -> Result<(),
             if e.to_string().contains("does not exist") {
                 return StorageError::internal(format!("Table does not exist: {e}"));
             }
-            StorageError::internal(format!("Failed to delete resource: {e}"))
+            database_error("Failed to delete resource", e)
         })?;
 
     // Per FHIR spec: delete is idempotent, so success regardless of whether
@@ -828,6 +814,31 @@ pub async fn delete(pool: &PgPool, resource_type: &str, id: &str) -> Result<(),
     Ok(())
 }
 
+/// SQL deleting the live row `$1` of `resource_type`.
+///
+/// Besides the final version archived by the history trigger, a versioned
+/// resource gets a tombstone: a `deleted` history version stamped with the
+/// database clock. The tombstone is what history reports as the DELETE and
+/// what incremental sync (`_sync`) pages on, so deletes surface there in
+/// commit order like any other write. It always takes a fresh version id: a
+/// bundle txid may already version the row being deleted.
+fn delete_sql(resource_type: &str) -> String {
+    let table = SchemaManager::qualified_table_name(resource_type);
+    if !SchemaManager::has_history(resource_type) {
+        return format!(r#"DELETE FROM {table} WHERE id = $1"#);
+    }
+    let history_table = SchemaManager::qualified_history_table_name(resource_type);
+    format!(
+        r#"WITH gone AS (
+               DELETE FROM {table} WHERE id = $1
+               RETURNING id, created_at, resource
+           )
+           INSERT INTO {history_table} (id, txid, created_at, updated_at, resource, status)
+           SELECT id, nextval('_transaction_txid_seq'), created_at, now(), resource, 'deleted'
+           FROM gone"#
+    )
+}
+
 /// Reads a specific version of a FHIR resource.
 ///
 /// First checks the current table, then falls back to the history table.
@@ -837,8 +848,8 @@ pub async fn vread(
     id: &str,
     version: &str,
 ) -> Result<Option<StoredResource>, StorageError> {
-    let table = SchemaManager::table_name(resource_type);
-    let history_table = format!("{}_history", table);
+    let table = SchemaManager::qualified_table_name(resource_type);
+    let history_table = SchemaManager::qualified_history_table_name(resource_type);
 
     // Parse version as i64
     let version_id: i64 = version
@@ -848,7 +859,7 @@ pub async fn vread(
     // First check current table
     let current_sql = format!(
         r#"SELECT id, txid, created_at, updated_at, resource
-           FROM "{table}"
+           FROM {table}
            WHERE id = $1 AND txid = $2"#
     );
 
@@ -862,7 +873,7 @@ pub async fn vread(
                 if e.to_string().contains("does not exist") {
                     return StorageError::internal(format!("Table does not exist: {e}"));
                 }
-                StorageError::internal(format!("Failed to read version: {e}"))
+                database_error("Failed to read version", e)
             })?;
 
     if let Some((row_id, txid, created_at, updated_at, resource)) = row {
@@ -880,12 +891,12 @@ pub async fn vread(
 
     // Check history table
     let history_sql = format!(
-        r#"SELECT id, txid, created_at, updated_at, resource
-           FROM "{history_table}"
+        r#"SELECT id, txid, created_at, updated_at, resource, status::text
+           FROM {history_table}
            WHERE id = $1 AND txid = $2"#
     );
 
-    let row: Option<(String, i64, DateTime<Utc>, DateTime<Utc>, Value)> =
+    let row: Option<(String, i64, DateTime<Utc>, DateTime<Utc>, Value, String)> =
         query_as(AssertSqlSafe(history_sql.to_string()))
             .bind(id)
             .bind(version_id)
@@ -895,11 +906,13 @@ pub async fn vread(
                 if e.to_string().contains("does not exist") {
                     return StorageError::internal(format!("History table does not exist: {e}"));
                 }
-                StorageError::internal(format!("Failed to read version from history: {e}"))
+                database_error("Failed to read version from history", e)
             })?;
 
     match row {
-        Some((row_id, txid, created_at, updated_at, resource)) => {
+        // The tombstone of a delete: that version is gone
+        Some((.., status)) if status == "deleted" => Err(StorageError::deleted(resource_type, id)),
+        Some((row_id, txid, created_at, updated_at, resource, _)) => {
             let created_at_time = chrono_to_time(created_at);
             let updated_at_time = chrono_to_time(updated_at);
             Ok(Some(StoredResource {
@@ -925,8 +938,8 @@ pub async fn vread_raw(
     id: &str,
     version: &str,
 ) -> Result<Option<RawStoredResource>, StorageError> {
-    let table = SchemaManager::table_name(resource_type);
-    let history_table = format!("{}_history", table);
+    let table = SchemaManager::qualified_table_name(resource_type);
+    let history_table = SchemaManager::qualified_history_table_name(resource_type);
 
     let version_id: i64 = version
         .parse()
@@ -935,7 +948,7 @@ pub async fn vread_raw(
     // First check current table
     let current_sql = format!(
         r#"SELECT id, txid, created_at, updated_at, resource::text
-           FROM "{table}"
+           FROM {table}
            WHERE id = $1 AND txid = $2"#
     );
 
@@ -949,7 +962,7 @@ pub async fn vread_raw(
                 if e.to_string().contains("does not exist") {
                     return StorageError::internal(format!("Table does not exist: {e}"));
                 }
-                StorageError::internal(format!("Failed to read version: {e}"))
+                database_error("Failed to read version", e)
             })?;
 
     if let Some((row_id, txid, created_at, updated_at, resource_json)) = row {
@@ -967,12 +980,12 @@ pub async fn vread_raw(
 
     // Check history table
     let history_sql = format!(
-        r#"SELECT id, txid, created_at, updated_at, resource::text
-           FROM "{history_table}"
+        r#"SELECT id, txid, created_at, updated_at, resource::text, status::text
+           FROM {history_table}
            WHERE id = $1 AND txid = $2"#
     );
 
-    let row: Option<(String, i64, DateTime<Utc>, DateTime<Utc>, String)> =
+    let row: Option<(String, i64, DateTime<Utc>, DateTime<Utc>, String, String)> =
         query_as(AssertSqlSafe(history_sql.to_string()))
             .bind(id)
             .bind(version_id)
@@ -982,11 +995,13 @@ pub async fn vread_raw(
                 if e.to_string().contains("does not exist") {
                     return StorageError::internal(format!("History table does not exist: {e}"));
                 }
-                StorageError::internal(format!("Failed to read version from history: {e}"))
+                database_error("Failed to read version from history", e)
             })?;
 
     match row {
-        Some((row_id, txid, created_at, updated_at, resource_json)) => {
+        // The tombstone of a delete: that version is gone
+        Some((.., status)) if status == "deleted" => Err(StorageError::deleted(resource_type, id)),
+        Some((row_id, txid, created_at, updated_at, resource_json, _)) => {
             let created_at_time = chrono_to_time(created_at);
             let updated_at_time = chrono_to_time(updated_at);
             Ok(Some(RawStoredResource {
@@ -1030,24 +1045,22 @@ pub async fn create_with_tx(
     let id = resource["id"]
         .as_str()
         .map(String::from)
-        .unwrap_or_else(|| Uuid::new_v4().to_string());
-
-    let now = Utc::now();
+        .unwrap_or_else(octofhir_core::generate_id);
 
-    let table = SchemaManager::table_name(resource_type);
+    let table = SchemaManager::qualified_table_name(resource_type);
     let sql = if let Some(t) = txid {
         format!(
-            r#"INSERT INTO "{table}" (id, txid, created_at, updated_at, resource, status)
+            r#"INSERT INTO {table} (id, txid, created_at, updated_at, resource, status)
                VALUES (
                    $1,
                    {t}::bigint,
-                   $2,
-                   $2,
-                   $3::jsonb || jsonb_build_object(
+                   now(),
+                   now(),
+                   $2::jsonb || jsonb_build_object(
                        'id', $1::text,
-                       'meta', COALESCE($3::jsonb -> 'meta', '{{}}'::jsonb) || jsonb_build_object(
+                       'meta', COALESCE($2::jsonb -> 'meta', '{{}}'::jsonb) || jsonb_build_object(
                            'versionId', '{t}',
-                           'lastUpdated', to_char($2 AT TIME ZONE 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS.MS"Z"')
+                           'lastUpdated', to_char(now() AT TIME ZONE 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS.MS"Z"')
                        )
                    ),
                    'created'
@@ -1059,17 +1072,17 @@ pub async fn create_with_tx(
             r#"WITH new_tx AS (
                    SELECT nextval('_transaction_txid_seq') AS txid
                )
-               INSERT INTO "{table}" (id, txid, created_at, updated_at, resource, status)
+               INSERT INTO {table} (id, txid, created_at, updated_at, resource, status)
                SELECT
                    $1,
                    new_tx.txid,
-                   $2,
-                   $2,
-                   $3::jsonb || jsonb_build_object(
+                   now(),
+                   now(),
+                   $2::jsonb || jsonb_build_object(
                        'id', $1::text,
-                       'meta', COALESCE($3::jsonb -> 'meta', '{{}}'::jsonb) || jsonb_build_object(
+                       'meta', COALESCE($2::jsonb -> 'meta', '{{}}'::jsonb) || jsonb_build_object(
                            'versionId', new_tx.txid::text,
-                           'lastUpdated', to_char($2 AT TIME ZONE 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS.MS"Z"')
+                           'lastUpdated', to_char(now() AT TIME ZONE 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS.MS"Z"')
                        )
                    ),
                    'created'
@@ -1081,15 +1094,14 @@ pub async fn create_with_tx(
     let row: (String, i64, DateTime<Utc>, DateTime<Utc>, Value) =
         query_as(AssertSqlSafe(sql.to_string()))
             .bind(&id)
-            .bind(now)
             .bind(resource)
             .fetch_one(&mut **tx)
             .await
             .map_err(|e| {
-                if e.to_string().contains("duplicate key") {
+                if is_unique_violation(&e) {
                     StorageError::already_exists(resource_type, &id)
                 } else {
-                    StorageError::internal(format!("Failed to create resource: {e}"))
+                    database_error("Failed to create resource", e)
                 }
             })?;
 
@@ -1126,7 +1138,11 @@ pub async fn create_batch_with_tx(
         return Ok(Vec::new());
     }
 
-    let now = Utc::now();
+    // The database clock, as for single creates; constant within `tx`
+    let now: DateTime<Utc> = query_scalar("SELECT now()")
+        .fetch_one(&mut **tx)
+        .await
+        .map_err(|e| database_error("Failed to read the database clock", e))?;
     let last_updated_str = now.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
     let version_id = txid.to_string();
 
@@ -1136,7 +1152,7 @@ pub async fn create_batch_with_tx(
         let id = r["id"]
             .as_str()
             .map(String::from)
-            .unwrap_or_else(|| Uuid::new_v4().to_string());
+            .unwrap_or_else(octofhir_core::generate_id);
         let mut owned = r.clone();
         owned["id"] = Value::String(id.clone());
         // Merge server-managed meta into any client-supplied meta so
@@ -1160,9 +1176,9 @@ pub async fn create_batch_with_tx(
         payloads.push(owned);
     }
 
-    let table = SchemaManager::table_name(resource_type);
+    let table = SchemaManager::qualified_table_name(resource_type);
     let sql = format!(
-        r#"INSERT INTO "{table}" (id, txid, created_at, updated_at, resource, status)
+        r#"INSERT INTO {table} (id, txid, created_at, updated_at, resource, status)
            SELECT t.id, {txid}::bigint, $3::timestamptz, $3::timestamptz, t.resource, 'created'
            FROM UNNEST($1::text[], $2::jsonb[]) AS t(id, resource)"#
     );
@@ -1174,10 +1190,10 @@ pub async fn create_batch_with_tx(
         .execute(&mut **tx)
         .await
         .map_err(|e| {
-            if e.to_string().contains("duplicate key") {
+            if is_unique_violation(&e) {
                 StorageError::already_exists(resource_type, "<batch>")
             } else {
-                StorageError::internal(format!("Failed to batch-create resources: {e}"))
+                database_error("Failed to batch-create resources", e)
             }
         })?;
 
@@ -1238,8 +1254,7 @@ pub async fn update_with_tx_if_match(
         .as_str()
         .ok_or_else(|| StorageError::invalid_resource("Missing id field"))?;
 
-    let table = SchemaManager::table_name(resource_type);
-    let now = Utc::now();
+    let table = SchemaManager::qualified_table_name(resource_type);
 
     let (update_sql, has_version_check) = match (if_match, txid) {
         (Some(expected_version), Some(t)) => {
@@ -1251,15 +1266,15 @@ pub async fn update_with_tx_if_match(
             (
                 format!(
                     r#"WITH current AS (
-                           SELECT id, txid, created_at FROM "{table}"
+                           SELECT id, txid, created_at FROM {table}
                            WHERE id = $1 AND status != 'deleted'
                        )
-                       UPDATE "{table}" t
+                       UPDATE {table} t
                        SET txid = {t}::bigint,
                            resource = $2::jsonb || jsonb_build_object(
                                'meta', COALESCE($2::jsonb -> 'meta', '{{}}'::jsonb) || jsonb_build_object(
                                    'versionId', '{t}',
-                                   'lastUpdated', to_char($3 AT TIME ZONE 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS.MS"Z"')
+                                   'lastUpdated', to_char(now() AT TIME ZONE 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS.MS"Z"')
                                )
                            ),
                            status = 'updated'
@@ -1285,15 +1300,15 @@ pub async fn update_with_tx_if_match(
                            SELECT nextval('_transaction_txid_seq') AS txid
                        ),
                        current AS (
-                           SELECT id, txid, created_at FROM "{table}"
+                           SELECT id, txid, created_at FROM {table}
                            WHERE id = $1 AND status != 'deleted'
                        )
-                       UPDATE "{table}" t
+                       UPDATE {table} t
                        SET txid = new_tx.txid,
                            resource = $2::jsonb || jsonb_build_object(
                                'meta', COALESCE($2::jsonb -> 'meta', '{{}}'::jsonb) || jsonb_build_object(
                                    'versionId', new_tx.txid::text,
-                                   'lastUpdated', to_char($3 AT TIME ZONE 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS.MS"Z"')
+                                   'lastUpdated', to_char(now() AT TIME ZONE 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS.MS"Z"')
                                )
                            ),
                            status = 'updated'
@@ -1309,12 +1324,12 @@ pub async fn update_with_tx_if_match(
         }
         (None, Some(t)) => (
             format!(
-                r#"UPDATE "{table}" t
+                r#"UPDATE {table} t
                    SET txid = {t}::bigint,
                        resource = $2::jsonb || jsonb_build_object(
                            'meta', COALESCE($2::jsonb -> 'meta', '{{}}'::jsonb) || jsonb_build_object(
                                'versionId', '{t}',
-                               'lastUpdated', to_char($3 AT TIME ZONE 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS.MS"Z"')
+                               'lastUpdated', to_char(now() AT TIME ZONE 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS.MS"Z"')
                            )
                        ),
                        status = 'updated'
@@ -1329,12 +1344,12 @@ pub async fn update_with_tx_if_match(
                 r#"WITH new_tx AS (
                        SELECT nextval('_transaction_txid_seq') AS txid
                    )
-                   UPDATE "{table}" t
+                   UPDATE {table} t
                    SET txid = new_tx.txid,
                        resource = $2::jsonb || jsonb_build_object(
                            'meta', COALESCE($2::jsonb -> 'meta', '{{}}'::jsonb) || jsonb_build_object(
                                'versionId', new_tx.txid::text,
-                               'lastUpdated', to_char($3 AT TIME ZONE 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS.MS"Z"')
+                               'lastUpdated', to_char(now() AT TIME ZONE 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS.MS"Z"')
                            )
                        ),
                        status = 'updated'
@@ -1351,10 +1366,9 @@ pub async fn update_with_tx_if_match(
         query_as(AssertSqlSafe(update_sql.to_string()))
             .bind(id)
             .bind(resource)
-            .bind(now)
             .fetch_optional(&mut **tx)
             .await
-            .map_err(|e| StorageError::internal(format!("Failed to update resource: {e}")))?;
+            .map_err(|e| database_error("Failed to update resource", e))?;
 
     match row {
         Some((
@@ -1379,14 +1393,12 @@ pub async fn update_with_tx_if_match(
         None => {
             if has_version_check {
                 let check_sql =
-                    format!(r#"SELECT txid FROM "{table}" WHERE id = $1 AND status != 'deleted'"#);
+                    format!(r#"SELECT txid FROM {table} WHERE id = $1 AND status != 'deleted'"#);
                 let current: Option<i64> = query_scalar(AssertSqlSafe(check_sql.to_string()))
                     .bind(id)
                     .fetch_optional(&mut **tx)
                     .await
-                    .map_err(|e| {
-                        StorageError::internal(format!("Failed to check resource: {e}"))
-                    })?;
+                    .map_err(|e| database_error("Failed to check resource", e))?;
 
                 match current {
                     Some(v) => Err(StorageError::version_conflict(
@@ -1412,14 +1424,12 @@ pub async fn delete_with_tx(
     id: &str,
     txid: Option<i64>,
 ) -> Result<(), StorageError> {
-    let table = SchemaManager::table_name(resource_type);
-
     // Physically remove the live row; the BEFORE DELETE trigger archives the
     // final version to history (see `delete` for why deleted rows must not stay
-    // in the live table). `txid` is unused now — the archived version keeps its
-    // own version id and nothing new is written to the live table.
+    // in the live table). `txid` is unused — the tombstone takes a fresh
+    // version id (see `delete_sql`).
     let _ = txid;
-    let sql = format!(r#"DELETE FROM "{table}" WHERE id = $1"#);
+    let sql = delete_sql(resource_type);
 
     let _result = query(AssertSqlSafe(sql.to_string()))
         .bind(id)
@@ -1429,7 +1439,7 @@ pub async fn delete_with_tx(
             if e.to_string().contains("does not exist") {
                 return StorageError::internal(format!("Table does not exist: {e}"));
             }
-            StorageError::internal(format!("Failed to delete resource: {e}"))
+            database_error("Failed to delete resource", e)
         })?;
 
     // Per FHIR spec: delete is idempotent
@@ -1444,12 +1454,12 @@ pub async fn read_with_tx(
     resource_type: &str,
     id: &str,
 ) -> Result<Option<StoredResource>, StorageError> {
-    let table = SchemaManager::table_name(resource_type);
+    let table = SchemaManager::qualified_table_name(resource_type);
 
     // Query including status to detect deleted resources
     let sql = format!(
         r#"SELECT id, txid, created_at, updated_at, resource, status::text
-           FROM "{table}"
+           FROM {table}
            WHERE id = $1"#
     );
 
@@ -1462,7 +1472,7 @@ pub async fn read_with_tx(
                 if e.to_string().contains("does not exist") {
                     return StorageError::internal(format!("Table does not exist: {e}"));
                 }
-                StorageError::internal(format!("Failed to read resource: {e}"))
+                database_error("Failed to read resource", e)
             })?;
 
     match row {
@@ -1491,6 +1501,17 @@ pub async fn read_with_tx(
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_delete_records_a_tombstone_for_versioned_types() {
+        let sql = delete_sql("Patient");
+        assert!(sql.contains(r#"DELETE FROM "public"."patient" WHERE id = $1"#));
+        assert!(sql.contains(r#"INSERT INTO "public"."patient_history""#));
+        assert!(sql.contains("now(), resource, 'deleted'"));
+
+        let sql = delete_sql("User");
+        assert_eq!(sql, r#"DELETE FROM "public"."user" WHERE id = $1"#);
+    }
+
     #[test]
     fn test_chrono_to_time_conversion() {
         let chrono_dt = Utc::now();
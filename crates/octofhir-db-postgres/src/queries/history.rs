@@ -10,7 +10,7 @@ use serde_json::Value;
 use sqlx_core::query_as::query_as;
 use sqlx_core::query_scalar::query_scalar;
 use sqlx_core::sql_str::AssertSqlSafe;
-use sqlx_postgres::PgPool;
+use sqlx_postgres::{PgConnection, PgPool};
 use time::OffsetDateTime;
 
 use octofhir_storage::{
@@ -18,6 +18,8 @@ use octofhir_storage::{
     RawStoredResource, StorageError, StoredResource, TotalMode,
 };
 
+use crate::deadline::BoundedConnection;
+use crate::error::database_error;
 use crate::schema::SchemaManager;
 
 /// Row type for history queries (id, txid, created_at, updated_at, resource, status).
@@ -69,8 +71,8 @@ pub async fn get_history(
     id: Option<&str>,
     params: &HistoryParams,
 ) -> Result<HistoryResult, StorageError> {
-    let table = SchemaManager::table_name(resource_type);
-    let history_table = format!("{}_history", table);
+    let table = SchemaManager::qualified_table_name(resource_type);
+    let history_table = SchemaManager::qualified_history_table_name(resource_type);
 
     // ID column is TEXT in the database, bind as string directly
     let id_str: Option<String> = id.map(|s| s.to_string());
@@ -100,6 +102,7 @@ pub async fn get_history(
            ORDER BY txid DESC
            LIMIT {limit} OFFSET {offset}"#
     );
+    let mut conn = BoundedConnection::acquire(pool).await?;
     let total = if matches!(params.total, Some(TotalMode::Accurate)) {
         let count_sql = format!(
             r#"WITH all_versions AS ({sql})
@@ -108,7 +111,7 @@ pub async fn get_history(
         Some(
             execute_history_count_query(
                 &count_sql,
-                pool,
+                conn.conn(),
                 id_str.clone(),
                 since_chrono,
                 at_chrono,
@@ -124,7 +127,7 @@ pub async fn get_history(
 
     let rows = execute_history_query(
         &full_sql,
-        pool,
+        conn.conn(),
         id_str,
         since_chrono,
         at_chrono,
@@ -134,6 +137,8 @@ pub async fn get_history(
     )
     .await?;
 
+    conn.finish();
+
     // Convert rows to history entries
     let entries: Vec<HistoryEntry> = rows
         .into_iter()
@@ -210,17 +215,19 @@ pub async fn get_system_history(
     };
 
     // Build UNION ALL query across all tables
+    let resource_schema = SchemaManager::resource_schema();
     let mut unions = Vec::new();
-    for table in &tables {
-        let history_table = format!("{}_history", table);
+    for table_name in &tables {
+        let table = format!("\"{resource_schema}\".\"{table_name}\"");
+        let history_table = format!("\"{resource_schema}\".\"{table_name}_history\"");
         // Include resource_type from the JSONB resource field
         unions.push(format!(
             r#"SELECT id, txid, created_at, updated_at, resource, status::text, resource->>'resourceType' as resource_type
-               FROM "{table}" {where_clause}"#
+               FROM {table} {where_clause}"#
         ));
         unions.push(format!(
             r#"SELECT id, txid, created_at, updated_at, resource, status::text, resource->>'resourceType' as resource_type
-               FROM "{history_table}" {where_clause}"#
+               FROM {history_table} {where_clause}"#
         ));
     }
 
@@ -232,13 +239,17 @@ pub async fn get_system_history(
            LIMIT {limit} OFFSET {offset}"#,
         unions.join(" UNION ALL ")
     );
+    let mut conn = BoundedConnection::acquire(pool).await?;
     let total = if matches!(params.total, Some(TotalMode::Accurate)) {
         let count_sql = format!(
             r#"WITH all_versions AS ({})
                SELECT COUNT(*)::bigint FROM all_versions"#,
             unions.join(" UNION ALL ")
         );
-        Some(execute_system_history_count_query(&count_sql, pool, since_chrono, at_chrono).await?)
+        Some(
+            execute_system_history_count_query(&count_sql, conn.conn(), since_chrono, at_chrono)
+                .await?,
+        )
     } else {
         None
     };
@@ -249,28 +260,30 @@ pub async fn get_system_history(
             query_as(AssertSqlSafe(sql.to_string()))
                 .bind(since)
                 .bind(at)
-                .fetch_all(pool)
+                .fetch_all(conn.conn())
                 .await
         }
         (Some(since), None) => {
             query_as(AssertSqlSafe(sql.to_string()))
                 .bind(since)
-                .fetch_all(pool)
+                .fetch_all(conn.conn())
                 .await
         }
         (None, Some(at)) => {
             query_as(AssertSqlSafe(sql.to_string()))
                 .bind(at)
-                .fetch_all(pool)
+                .fetch_all(conn.conn())
                 .await
         }
         (None, None) => {
             query_as(AssertSqlSafe(sql.to_string()))
-                .fetch_all(pool)
+                .fetch_all(conn.conn())
                 .await
         }
     }
-    .map_err(|e| StorageError::internal(format!("Failed to query system history: {e}")))?;
+    .map_err(|e| database_error("Failed to query system history", e))?;
+
+    conn.finish();
 
     // Convert rows to history entries
     let entries: Vec<HistoryEntry> = rows
@@ -322,8 +335,8 @@ pub async fn get_history_raw(
     id: Option<&str>,
     params: &HistoryParams,
 ) -> Result<RawHistoryResult, StorageError> {
-    let table = SchemaManager::table_name(resource_type);
-    let history_table = format!("{}_history", table);
+    let table = SchemaManager::qualified_table_name(resource_type);
+    let history_table = SchemaManager::qualified_history_table_name(resource_type);
 
     let id_str: Option<String> = id.map(|s| s.to_string());
 
@@ -349,6 +362,7 @@ pub async fn get_history_raw(
            ORDER BY txid DESC
            LIMIT {limit} OFFSET {offset}"#
     );
+    let mut conn = BoundedConnection::acquire(pool).await?;
     let total = if matches!(params.total, Some(TotalMode::Accurate)) {
         let count_sql = format!(
             r#"WITH all_versions AS ({sql})
@@ -357,7 +371,7 @@ pub async fn get_history_raw(
         Some(
             execute_history_count_query(
                 &count_sql,
-                pool,
+                conn.conn(),
                 id_str.clone(),
                 since_chrono,
                 at_chrono,
@@ -373,7 +387,7 @@ pub async fn get_history_raw(
 
     let rows: Vec<RawHistoryRow> = execute_raw_history_query(
         &full_sql,
-        pool,
+        conn.conn(),
         id_str,
         since_chrono,
         at_chrono,
@@ -383,6 +397,8 @@ pub async fn get_history_raw(
     )
     .await?;
 
+    conn.finish();
+
     let entries: Vec<RawHistoryEntry> = rows
         .into_iter()
         .map(
@@ -449,16 +465,18 @@ pub async fn get_system_history_raw(
         format!("WHERE {}", where_conditions.join(" AND "))
     };
 
+    let resource_schema = SchemaManager::resource_schema();
     let mut unions = Vec::new();
-    for table in &tables {
-        let history_table = format!("{}_history", table);
+    for table_name in &tables {
+        let table = format!("\"{resource_schema}\".\"{table_name}\"");
+        let history_table = format!("\"{resource_schema}\".\"{table_name}_history\"");
         unions.push(format!(
             r#"SELECT id, txid, created_at, updated_at, resource::text as resource_text, status::text, resource->>'resourceType' as resource_type
-               FROM "{table}" {where_clause}"#
+               FROM {table} {where_clause}"#
         ));
         unions.push(format!(
             r#"SELECT id, txid, created_at, updated_at, resource::text as resource_text, status::text, resource->>'resourceType' as resource_type
-               FROM "{history_table}" {where_clause}"#
+               FROM {history_table} {where_clause}"#
         ));
     }
 
@@ -470,13 +488,17 @@ pub async fn get_system_history_raw(
            LIMIT {limit} OFFSET {offset}"#,
         unions.join(" UNION ALL ")
     );
+    let mut conn = BoundedConnection::acquire(pool).await?;
     let total = if matches!(params.total, Some(TotalMode::Accurate)) {
         let count_sql = format!(
             r#"WITH all_versions AS ({})
                SELECT COUNT(*)::bigint FROM all_versions"#,
             unions.join(" UNION ALL ")
         );
-        Some(execute_system_history_count_query(&count_sql, pool, since_chrono, at_chrono).await?)
+        Some(
+            execute_system_history_count_query(&count_sql, conn.conn(), since_chrono, at_chrono)
+                .await?,
+        )
     } else {
         None
     };
@@ -486,28 +508,30 @@ pub async fn get_system_history_raw(
             query_as(AssertSqlSafe(sql.to_string()))
                 .bind(since)
                 .bind(at)
-                .fetch_all(pool)
+                .fetch_all(conn.conn())
                 .await
         }
         (Some(since), None) => {
             query_as(AssertSqlSafe(sql.to_string()))
                 .bind(since)
-                .fetch_all(pool)
+                .fetch_all(conn.conn())
                 .await
         }
         (None, Some(at)) => {
             query_as(AssertSqlSafe(sql.to_string()))
                 .bind(at)
-                .fetch_all(pool)
+                .fetch_all(conn.conn())
                 .await
         }
         (None, None) => {
             query_as(AssertSqlSafe(sql.to_string()))
-                .fetch_all(pool)
+                .fetch_all(conn.conn())
                 .await
         }
     }
-    .map_err(|e| StorageError::internal(format!("Failed to query system history: {e}")))?;
+    .map_err(|e| database_error("Failed to query system history", e))?;
+
+    conn.finish();
 
     let entries: Vec<RawHistoryEntry> = rows
         .into_iter()
@@ -534,6 +558,103 @@ pub async fn get_system_history_raw(
     Ok(RawHistoryResult { entries, total })
 }
 
+/// Deletes old versions of `resource_type` from its history table.
+///
+/// Versions are ranked per resource from newest to oldest, counting the
+/// current version in the live table as the first. A version is deleted when
+/// it ranks beyond `keep_versions` and was written more than `max_age` ago;
+/// a limit that is `None` does not hold a version back. The newest version of
+/// every resource is always kept, so a deleted resource still reads as gone
+/// rather than never existing. With neither limit set, nothing is deleted.
+///
+/// The history table is walked in id order, `batch_size` resources per
+/// statement: each statement ranks only the versions of its own resources,
+/// found through the `(id, txid)` primary key, so a run reads every version
+/// once however large the backlog. Returns the number of versions deleted.
+pub async fn prune_history(
+    pool: &PgPool,
+    resource_type: &str,
+    keep_versions: Option<u32>,
+    max_age: Option<std::time::Duration>,
+    batch_size: u32,
+) -> Result<u64, StorageError> {
+    if keep_versions.is_none() && max_age.is_none() {
+        return Ok(0);
+    }
+    let sql = prune_history_sql(resource_type);
+    let keep_versions = i64::from(keep_versions.unwrap_or(1).max(1));
+    let max_age_secs = max_age.map(|age| age.as_secs_f64());
+
+    let mut total = 0u64;
+    let mut after = String::new();
+    loop {
+        let (last_id, deleted): (Option<String>, i64) = query_as(AssertSqlSafe(sql.clone()))
+            .bind(keep_versions)
+            .bind(max_age_secs)
+            .bind(i64::from(batch_size.max(1)))
+            .bind(&after)
+            .fetch_one(pool)
+            .await
+            .map_err(|e| database_error("Failed to prune history", e))?;
+        total += u64::try_from(deleted).unwrap_or_default();
+        match last_id {
+            Some(last_id) => after = last_id,
+            None => return Ok(total),
+        }
+    }
+}
+
+/// One batch of [`prune_history`]: prunes the `$3` resources with history
+/// whose ids follow `$4`, and returns the last of those ids (`NULL` once the
+/// table is exhausted) with the number of versions deleted.
+fn prune_history_sql(resource_type: &str) -> String {
+    let table = SchemaManager::qualified_table_name(resource_type);
+    let history_table = SchemaManager::qualified_history_table_name(resource_type);
+    format!(
+        r#"WITH ids AS (
+               SELECT DISTINCT id FROM {history_table}
+               WHERE id > $4
+               ORDER BY id
+               LIMIT $3
+           ), ranked AS (
+               SELECT h.id, h.txid, h.updated_at,
+                      row_number() OVER (PARTITION BY h.id ORDER BY h.txid DESC)
+                        + CASE WHEN m.id IS NULL THEN 0 ELSE 1 END AS version_rank
+               FROM ids
+               JOIN {history_table} h ON h.id = ids.id
+               LEFT JOIN {table} m ON m.id = ids.id
+           ), deleted AS (
+               DELETE FROM {history_table} h
+               USING ranked r
+               WHERE h.id = r.id AND h.txid = r.txid
+                 AND r.version_rank > $1
+                 AND ($2::float8 IS NULL OR r.updated_at < NOW() - make_interval(secs => $2))
+               RETURNING 1
+           )
+           SELECT (SELECT max(id) FROM ids), (SELECT count(*) FROM deleted)"#
+    )
+}
+
+/// Resource types (as table names) in the current tenant's schema that keep
+/// history.
+pub async fn history_table_resource_types(pool: &PgPool) -> Result<Vec<String>, StorageError> {
+    query_scalar(
+        r#"SELECT t.table_name::text
+           FROM information_schema.tables t
+           WHERE t.table_schema = $1
+             AND EXISTS (
+                 SELECT 1 FROM information_schema.tables h
+                 WHERE h.table_schema = t.table_schema
+                   AND h.table_name = t.table_name || '_history'
+             )
+           ORDER BY t.table_name"#,
+    )
+    .bind(SchemaManager::resource_schema())
+    .fetch_all(pool)
+    .await
+    .map_err(|e| database_error("Failed to list history tables", e))
+}
+
 /// Builds a UNION ALL query with `resource::text` for raw history.
 fn build_raw_union_query(
     table: &str,
@@ -568,9 +689,9 @@ fn build_raw_union_query(
     };
 
     let sql = format!(
-        r#"SELECT id, txid, created_at, updated_at, resource::text as resource_text, status::text FROM "{table}" {where_clause}
+        r#"SELECT id, txid, created_at, updated_at, resource::text as resource_text, status::text FROM {table} {where_clause}
            UNION ALL
-           SELECT id, txid, created_at, updated_at, resource::text as resource_text, status::text FROM "{history_table}" {where_clause}"#
+           SELECT id, txid, created_at, updated_at, resource::text as resource_text, status::text FROM {history_table} {where_clause}"#
     );
 
     (sql, has_id, has_since, has_at)
@@ -610,9 +731,9 @@ fn build_union_query(
     };
 
     let sql = format!(
-        r#"SELECT id, txid, created_at, updated_at, resource, status::text FROM "{table}" {where_clause}
+        r#"SELECT id, txid, created_at, updated_at, resource, status::text FROM {table} {where_clause}
            UNION ALL
-           SELECT id, txid, created_at, updated_at, resource, status::text FROM "{history_table}" {where_clause}"#
+           SELECT id, txid, created_at, updated_at, resource, status::text FROM {history_table} {where_clause}"#
     );
 
     (sql, has_id, has_since, has_at)
@@ -622,7 +743,7 @@ fn build_union_query(
 #[allow(clippy::too_many_arguments)]
 async fn execute_history_count_query(
     sql: &str,
-    pool: &PgPool,
+    conn: &mut PgConnection,
     id_str: Option<String>,
     since: Option<DateTime<Utc>>,
     at: Option<DateTime<Utc>>,
@@ -636,51 +757,51 @@ async fn execute_history_count_query(
                 .bind(id_str.unwrap())
                 .bind(since.unwrap())
                 .bind(at.unwrap())
-                .fetch_one(pool)
+                .fetch_one(&mut *conn)
                 .await
         }
         (true, true, false) => {
             query_scalar(AssertSqlSafe((sql).to_string()))
                 .bind(id_str.unwrap())
                 .bind(since.unwrap())
-                .fetch_one(pool)
+                .fetch_one(&mut *conn)
                 .await
         }
         (true, false, true) => {
             query_scalar(AssertSqlSafe((sql).to_string()))
                 .bind(id_str.unwrap())
                 .bind(at.unwrap())
-                .fetch_one(pool)
+                .fetch_one(&mut *conn)
                 .await
         }
         (true, false, false) => {
             query_scalar(AssertSqlSafe((sql).to_string()))
                 .bind(id_str.unwrap())
-                .fetch_one(pool)
+                .fetch_one(&mut *conn)
                 .await
         }
         (false, true, true) => {
             query_scalar(AssertSqlSafe((sql).to_string()))
                 .bind(since.unwrap())
                 .bind(at.unwrap())
-                .fetch_one(pool)
+                .fetch_one(&mut *conn)
                 .await
         }
         (false, true, false) => {
             query_scalar(AssertSqlSafe((sql).to_string()))
                 .bind(since.unwrap())
-                .fetch_one(pool)
+                .fetch_one(&mut *conn)
                 .await
         }
         (false, false, true) => {
             query_scalar(AssertSqlSafe((sql).to_string()))
                 .bind(at.unwrap())
-                .fetch_one(pool)
+                .fetch_one(&mut *conn)
                 .await
         }
         (false, false, false) => {
             query_scalar(AssertSqlSafe((sql).to_string()))
-                .fetch_one(pool)
+                .fetch_one(&mut *conn)
                 .await
         }
     }
@@ -688,7 +809,7 @@ async fn execute_history_count_query(
         if e.to_string().contains("does not exist") {
             return StorageError::internal(format!("Table does not exist: {e}"));
         }
-        StorageError::internal(format!("Failed to count history: {e}"))
+        database_error("Failed to count history", e)
     })?;
 
     u32::try_from(count).map_err(|_| {
@@ -698,7 +819,7 @@ async fn execute_history_count_query(
 
 async fn execute_system_history_count_query(
     sql: &str,
-    pool: &PgPool,
+    conn: &mut PgConnection,
     since: Option<DateTime<Utc>>,
     at: Option<DateTime<Utc>>,
 ) -> Result<u32, StorageError> {
@@ -707,28 +828,28 @@ async fn execute_system_history_count_query(
             query_scalar(AssertSqlSafe((sql).to_string()))
                 .bind(since)
                 .bind(at)
-                .fetch_one(pool)
+                .fetch_one(&mut *conn)
                 .await
         }
         (Some(since), None) => {
             query_scalar(AssertSqlSafe((sql).to_string()))
                 .bind(since)
-                .fetch_one(pool)
+                .fetch_one(&mut *conn)
                 .await
         }
         (None, Some(at)) => {
             query_scalar(AssertSqlSafe((sql).to_string()))
                 .bind(at)
-                .fetch_one(pool)
+                .fetch_one(&mut *conn)
                 .await
         }
         (None, None) => {
             query_scalar(AssertSqlSafe((sql).to_string()))
-                .fetch_one(pool)
+                .fetch_one(&mut *conn)
                 .await
         }
     }
-    .map_err(|e| StorageError::internal(format!("Failed to count system history: {e}")))?;
+    .map_err(|e| database_error("Failed to count system history", e))?;
 
     u32::try_from(count).map_err(|_| {
         StorageError::internal(format!(
@@ -741,7 +862,7 @@ async fn execute_system_history_count_query(
 #[allow(clippy::too_many_arguments)]
 async fn execute_history_query(
     sql: &str,
-    pool: &PgPool,
+    conn: &mut PgConnection,
     id_str: Option<String>,
     since: Option<DateTime<Utc>>,
     at: Option<DateTime<Utc>>,
@@ -756,51 +877,51 @@ async fn execute_history_query(
                 .bind(id_str.unwrap())
                 .bind(since.unwrap())
                 .bind(at.unwrap())
-                .fetch_all(pool)
+                .fetch_all(&mut *conn)
                 .await
         }
         (true, true, false) => {
             query_as(AssertSqlSafe((sql).to_string()))
                 .bind(id_str.unwrap())
                 .bind(since.unwrap())
-                .fetch_all(pool)
+                .fetch_all(&mut *conn)
                 .await
         }
         (true, false, true) => {
             query_as(AssertSqlSafe((sql).to_string()))
                 .bind(id_str.unwrap())
                 .bind(at.unwrap())
-                .fetch_all(pool)
+                .fetch_all(&mut *conn)
                 .await
         }
         (true, false, false) => {
             query_as(AssertSqlSafe((sql).to_string()))
                 .bind(id_str.unwrap())
-                .fetch_all(pool)
+                .fetch_all(&mut *conn)
                 .await
         }
         (false, true, true) => {
             query_as(AssertSqlSafe((sql).to_string()))
                 .bind(since.unwrap())
                 .bind(at.unwrap())
-                .fetch_all(pool)
+                .fetch_all(&mut *conn)
                 .await
         }
         (false, true, false) => {
             query_as(AssertSqlSafe((sql).to_string()))
                 .bind(since.unwrap())
-                .fetch_all(pool)
+                .fetch_all(&mut *conn)
                 .await
         }
         (false, false, true) => {
             query_as(AssertSqlSafe((sql).to_string()))
                 .bind(at.unwrap())
-                .fetch_all(pool)
+                .fetch_all(&mut *conn)
                 .await
         }
         (false, false, false) => {
             query_as(AssertSqlSafe((sql).to_string()))
-                .fetch_all(pool)
+                .fetch_all(&mut *conn)
                 .await
         }
     }
@@ -808,7 +929,7 @@ async fn execute_history_query(
         if e.to_string().contains("does not exist") {
             return StorageError::internal(format!("Table does not exist: {e}"));
         }
-        StorageError::internal(format!("Failed to query history: {e}"))
+        database_error("Failed to query history", e)
     })?;
 
     Ok(result)
@@ -818,7 +939,7 @@ async fn execute_history_query(
 #[allow(clippy::too_many_arguments)]
 async fn execute_raw_history_query(
     sql: &str,
-    pool: &PgPool,
+    conn: &mut PgConnection,
     id_str: Option<String>,
     since: Option<DateTime<Utc>>,
     at: Option<DateTime<Utc>>,
@@ -832,51 +953,51 @@ async fn execute_raw_history_query(
                 .bind(id_str.unwrap())
                 .bind(since.unwrap())
                 .bind(at.unwrap())
-                .fetch_all(pool)
+                .fetch_all(&mut *conn)
                 .await
         }
         (true, true, false) => {
             query_as(AssertSqlSafe((sql).to_string()))
                 .bind(id_str.unwrap())
                 .bind(since.unwrap())
-                .fetch_all(pool)
+                .fetch_all(&mut *conn)
                 .await
         }
         (true, false, true) => {
             query_as(AssertSqlSafe((sql).to_string()))
                 .bind(id_str.unwrap())
                 .bind(at.unwrap())
-                .fetch_all(pool)
+                .fetch_all(&mut *conn)
                 .await
         }
         (true, false, false) => {
             query_as(AssertSqlSafe((sql).to_string()))
                 .bind(id_str.unwrap())
-                .fetch_all(pool)
+                .fetch_all(&mut *conn)
                 .await
         }
         (false, true, true) => {
             query_as(AssertSqlSafe((sql).to_string()))
                 .bind(since.unwrap())
                 .bind(at.unwrap())
-                .fetch_all(pool)
+                .fetch_all(&mut *conn)
                 .await
         }
         (false, true, false) => {
             query_as(AssertSqlSafe((sql).to_string()))
                 .bind(since.unwrap())
-                .fetch_all(pool)
+                .fetch_all(&mut *conn)
                 .await
         }
         (false, false, true) => {
             query_as(AssertSqlSafe((sql).to_string()))
                 .bind(at.unwrap())
-                .fetch_all(pool)
+                .fetch_all(&mut *conn)
                 .await
         }
         (false, false, false) => {
             query_as(AssertSqlSafe((sql).to_string()))
-                .fetch_all(pool)
+                .fetch_all(&mut *conn)
                 .await
         }
     }
@@ -884,7 +1005,7 @@ async fn execute_raw_history_query(
         if e.to_string().contains("does not exist") {
             return StorageError::internal(format!("Table does not exist: {e}"));
         }
-        StorageError::internal(format!("Failed to query history: {e}"))
+        database_error("Failed to query history", e)
     })?;
 
     Ok(result)
@@ -894,6 +1015,23 @@ async fn execute_raw_history_query(
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_prune_history_sql_walks_ids_of_the_tenant_schema() {
+        let sql = prune_history_sql("Patient");
+        assert!(sql.contains(r#"FROM "public"."patient_history""#));
+        assert!(sql.contains("WHERE id > $4"));
+        assert!(sql.contains("LIMIT $3"));
+
+        let acme = octofhir_storage::TenantId::new("acme").unwrap();
+        let sql =
+            futures_util::FutureExt::now_or_never(octofhir_storage::with_tenant(acme, async {
+                prune_history_sql("Patient")
+            }))
+            .unwrap();
+        assert!(sql.contains(r#"DELETE FROM "tenant_acme"."patient_history""#));
+        assert!(sql.contains(r#"LEFT JOIN "tenant_acme"."patient" m"#));
+    }
+
     #[test]
     fn test_status_to_method() {
         assert_eq!(status_to_method("created"), HistoryMethod::Create);
@@ -4,6 +4,7 @@
 
 pub mod crud;
 pub mod history;
+pub mod references;
 pub mod search;
 
 // Re-export CRUD operations
@@ -13,12 +14,21 @@ pub use crud::{
 };
 
 // Re-export history operations
-pub use history::{get_history, get_history_raw, get_system_history, get_system_history_raw};
+pub use history::{
+    get_history, get_history_raw, get_system_history, get_system_history_raw,
+    history_table_resource_types, prune_history,
+};
+
+// Re-export reference scanning
+pub use references::{
+    CompartmentScope, ReferencePage, ReferenceValue, reference_param_codes, scan_references,
+};
 
 // Re-export search operations
 pub use search::{
-    RawSearchOptions, SearchUnknownParamHandling, execute_search, execute_search_raw,
-    execute_search_raw_with_config, execute_search_raw_with_options,
-    execute_search_raw_with_terminology, execute_search_raw_with_terminology_options,
-    execute_search_with_tx,
+    DEFAULT_MAX_INCLUDED, RawSearchOptions, SearchExplain, SearchUnknownParamHandling,
+    execute_search, execute_search_raw, execute_search_raw_with_config,
+    execute_search_raw_with_options, execute_search_raw_with_terminology,
+    execute_search_raw_with_terminology_options, execute_search_with_tx, explain_search,
+    included_limit_warning,
 };
@@ -0,0 +1,252 @@
+//! Reference scanning for integrity checks.
+//!
+//! Walks the reference-typed search parameters of a resource type and returns
+//! the raw reference strings held by each live resource, one keyset page at a
+//! time. Existence of the targets is checked separately (see
+//! [`super::crud::exists_many_grouped`]) so callers can deduplicate targets
+//! across pages before hitting the database.
+
+use octofhir_search::{SearchParameterRegistry, SearchParameterType};
+use octofhir_storage::StorageError;
+use sqlx_core::query_as::query_as;
+use sqlx_core::sql_str::AssertSqlSafe;
+use sqlx_postgres::PgPool;
+
+use super::search::{REFERENCE_TYPE_ID_RE, reference_array_sql};
+use crate::error::is_undefined_table;
+use crate::schema::SchemaManager;
+
+/// Restricts a scan to resources in one compartment instance
+/// (e.g. `Patient/123`).
+#[derive(Debug, Clone)]
+pub struct CompartmentScope<'a> {
+    /// Compartment type, e.g. `Patient`
+    pub compartment_type: &'a str,
+    /// Compartment instance id
+    pub compartment_id: &'a str,
+    /// Inclusion search parameters for the scanned resource type
+    pub params: &'a [String],
+}
+
+/// A reference value found in a source resource.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReferenceValue {
+    /// Id of the resource holding the reference
+    pub source_id: String,
+    /// Search parameter code the reference was found through
+    pub param: String,
+    /// The raw `Reference.reference` string
+    pub reference: String,
+}
+
+/// One page of a reference scan.
+#[derive(Debug, Clone, Default)]
+pub struct ReferencePage {
+    /// Source resources covered by this page, in id order
+    pub source_ids: Vec<String>,
+    /// References held by those resources
+    pub references: Vec<ReferenceValue>,
+}
+
+impl ReferencePage {
+    /// Keyset cursor for the next page, `None` when the scan is finished.
+    pub fn next_cursor(&self, page_size: usize) -> Option<&str> {
+        if self.source_ids.len() < page_size {
+            return None;
+        }
+        self.source_ids.last().map(String::as_str)
+    }
+}
+
+/// Reference-typed search parameter codes for a resource type that have an
+/// expression the JSONB accessor can follow.
+pub fn reference_param_codes(
+    registry: &SearchParameterRegistry,
+    resource_type: &str,
+) -> Vec<String> {
+    let mut codes: Vec<String> = registry
+        .get_all_for_type(resource_type)
+        .into_iter()
+        .filter(|p| p.param_type == SearchParameterType::Reference && p.expression.is_some())
+        .map(|p| p.code.clone())
+        .collect();
+    codes.sort();
+    codes.dedup();
+    codes
+}
+
+/// Scan one page of live `resource_type` resources (ids strictly after
+/// `after_id`) and return every reference held through `params`.
+///
+/// A missing table is treated as an empty resource type.
+pub async fn scan_references(
+    pool: &PgPool,
+    registry: &SearchParameterRegistry,
+    resource_type: &str,
+    params: &[String],
+    after_id: Option<&str>,
+    page_size: usize,
+    compartment: Option<&CompartmentScope<'_>>,
+) -> Result<ReferencePage, StorageError> {
+    let source_ids = match scan_page_ids(
+        pool,
+        registry,
+        resource_type,
+        after_id,
+        page_size,
+        compartment,
+    )
+    .await
+    {
+        Ok(ids) => ids,
+        Err(e) if is_undefined_table(&e) => return Ok(ReferencePage::default()),
+        Err(e) => {
+            return Err(StorageError::internal(format!(
+                "Reference scan of {resource_type} failed: {e}"
+            )));
+        }
+    };
+    if source_ids.is_empty() {
+        return Ok(ReferencePage::default());
+    }
+
+    // One UNION ALL branch per parameter; branches are tagged with their index
+    // so parameter codes never get interpolated into SQL.
+    let branches: Vec<(usize, String)> = params
+        .iter()
+        .enumerate()
+        .filter_map(|(i, code)| {
+            reference_array_sql(registry, resource_type, code, "s.resource").map(|arr| {
+                (
+                    i,
+                    format!(
+                        "SELECT {i}::int AS grp, ref->>'reference' AS reference \
+                         FROM jsonb_array_elements({arr}) AS ref"
+                    ),
+                )
+            })
+        })
+        .collect();
+    if branches.is_empty() {
+        return Ok(ReferencePage {
+            source_ids,
+            references: Vec::new(),
+        });
+    }
+
+    let table = SchemaManager::qualified_table_name(resource_type);
+    let union = branches
+        .iter()
+        .map(|(_, sql)| sql.as_str())
+        .collect::<Vec<_>>()
+        .join(" UNION ALL ");
+    let sql = format!(
+        r#"SELECT s.id, b.grp, b.reference
+           FROM {table} s
+           CROSS JOIN LATERAL ({union}) AS b
+           WHERE s.id = ANY($1::text[]) AND b.reference IS NOT NULL
+           ORDER BY s.id"#
+    );
+
+    let rows: Vec<(String, i32, String)> = query_as(AssertSqlSafe(sql))
+        .bind(&source_ids)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| {
+            StorageError::internal(format!("Reference scan of {resource_type} failed: {e}"))
+        })?;
+
+    let references = rows
+        .into_iter()
+        .filter_map(|(source_id, grp, reference)| {
+            params.get(grp as usize).map(|param| ReferenceValue {
+                source_id,
+                param: param.clone(),
+                reference,
+            })
+        })
+        .collect();
+
+    Ok(ReferencePage {
+        source_ids,
+        references,
+    })
+}
+
+async fn scan_page_ids(
+    pool: &PgPool,
+    registry: &SearchParameterRegistry,
+    resource_type: &str,
+    after_id: Option<&str>,
+    page_size: usize,
+    compartment: Option<&CompartmentScope<'_>>,
+) -> Result<Vec<String>, sqlx_core::error::Error> {
+    let table = SchemaManager::qualified_table_name(resource_type);
+    let mut sql = format!(r#"SELECT s.id FROM {table} s WHERE s.status != 'deleted'"#);
+    let mut next_param = 1;
+
+    if after_id.is_some() {
+        sql.push_str(&format!(" AND s.id > ${next_param}"));
+        next_param += 1;
+    }
+
+    let mut compartment_binds: Vec<&str> = Vec::new();
+    if let Some(scope) = compartment {
+        if scope.compartment_type == resource_type {
+            sql.push_str(&format!(" AND s.id = ${next_param}"));
+            next_param += 1;
+            compartment_binds.push(scope.compartment_id);
+        } else {
+            let arrays: Vec<String> = scope
+                .params
+                .iter()
+                .filter_map(|code| reference_array_sql(registry, resource_type, code, "s.resource"))
+                .map(|arr| format!("SELECT ref FROM jsonb_array_elements({arr}) AS ref"))
+                .collect();
+            if arrays.is_empty() {
+                // Nothing links this type to the compartment.
+                return Ok(Vec::new());
+            }
+            sql.push_str(&format!(
+                " AND EXISTS (SELECT 1 FROM ({union}) AS c(ref) \
+                 CROSS JOIN LATERAL (SELECT regexp_match(c.ref->>'reference', '{REFERENCE_TYPE_ID_RE}') AS m) x \
+                 WHERE x.m[1] = ${t} AND x.m[2] = ${i})",
+                union = arrays.join(" UNION ALL "),
+                t = next_param,
+                i = next_param + 1,
+            ));
+            next_param += 2;
+            compartment_binds.push(scope.compartment_type);
+            compartment_binds.push(scope.compartment_id);
+        }
+    }
+
+    sql.push_str(&format!(" ORDER BY s.id LIMIT ${next_param}"));
+
+    let mut q = query_as::<_, (String,)>(AssertSqlSafe(sql));
+    if let Some(after_id) = after_id {
+        q = q.bind(after_id);
+    }
+    for value in compartment_binds {
+        q = q.bind(value);
+    }
+    let rows = q.bind(page_size as i64).fetch_all(pool).await?;
+
+    Ok(rows.into_iter().map(|(id,)| id).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_cursor() {
+        let page = ReferencePage {
+            source_ids: vec!["a".into(), "b".into()],
+            references: Vec::new(),
+        };
+        assert_eq!(page.next_cursor(2), Some("b"));
+        assert_eq!(page.next_cursor(3), None);
+        assert_eq!(ReferencePage::default().next_cursor(1), None);
+    }
+}
@@ -11,9 +11,11 @@ use std::{
 
 use chrono::{DateTime, Utc};
 use serde_json::Value;
+use sqlx_core::from_row::FromRow;
 use sqlx_core::query_as::query_as;
 use sqlx_core::query_scalar::query_scalar;
-use sqlx_postgres::{PgPool, PgTransaction};
+use sqlx_core::row::Row;
+use sqlx_postgres::{PgPool, PgRow, PgTransaction};
 use time::OffsetDateTime;
 
 use octofhir_fhir_model::terminology::TerminologyProvider;
@@ -23,23 +25,48 @@ use octofhir_search::terminology_preprocess::{
 };
 use octofhir_search::{
     BuiltQuery, ParamsSearchConfig, PreparedQuery, QueryCache, QueryCacheKey, QueryParamKey,
-    SearchParameterRegistry, SqlValue, UnknownParamHandling, build_jsonb_accessor,
-    build_native_ir_query_from_params, build_native_ir_query_from_params_with_config,
-    fhirpath_to_jsonb_path,
+    SearchCondition, SearchParameterParser, SearchParameterRegistry, SqlValue,
+    UnknownParamHandling, build_jsonb_accessor, build_native_ir_query_from_params,
+    build_native_ir_query_from_params_with_config, fhirpath_to_jsonb_path, sync_horizon_sql,
 };
 use octofhir_storage::{
     RawSearchDebug, RawSearchResult, RawStoredResource, SearchParams, SearchResult, StorageError,
-    StoredResource, TotalMode,
+    StoredResource, SyncCursor, SyncDeletion, SyncPosition, TotalMode, charge_queries,
+    charge_query, is_resource_type_allowed,
 };
 
 /// Re-export UnknownParamHandling for convenience.
 pub use octofhir_search::UnknownParamHandling as SearchUnknownParamHandling;
 
-use crate::error::is_undefined_table;
+use crate::deadline::BoundedConnection;
+use crate::error::{database_error, is_undefined_table};
 use crate::schema::SchemaManager;
 
 const INCLUDE_ITERATE_MAX_DEPTH: usize = 100;
 
+/// Default cap on distinct `_include`/`_revinclude` resources per search.
+pub const DEFAULT_MAX_INCLUDED: usize = 1000;
+
+/// Warning returned with a search whose included resources were cut at
+/// `max_included`.
+pub fn included_limit_warning(max_included: usize) -> String {
+    format!(
+        "Included resources were limited to {max_included}; some _include/_revinclude \
+         matches were omitted. Narrow the search or the include parameters."
+    )
+}
+
+/// Joins and subqueries a search adds to its statement, charged against the
+/// query budget when it is planned: one per chained reference link and one
+/// per `_has` level.
+fn plan_joins(params: &SearchParams) -> u32 {
+    params
+        .parameters
+        .keys()
+        .map(|key| (key.matches('.').count() + key.matches("_has:").count()) as u32)
+        .sum()
+}
+
 /// Per-request raw search execution options.
 #[derive(Debug, Clone, Copy, Default)]
 pub struct RawSearchOptions {
@@ -50,6 +77,17 @@ pub struct RawSearchOptions {
     /// Cap on ValueSet/hierarchy expansion size for `:in`/`:not-in`/`:above`/
     /// `:below` pre-expansion. `None` falls back to [`DEFAULT_MAX_EXPANSION_SIZE`].
     pub max_valueset_expansion: Option<usize>,
+    /// Cap on distinct resources added by `_include`/`_revinclude`. Past it the
+    /// included set is truncated and a warning is returned. `None` falls back
+    /// to [`DEFAULT_MAX_INCLUDED`].
+    pub max_included: Option<usize>,
+    /// Maximum reference links in one chained parameter. Longer chains are
+    /// rejected. `None` falls back to [`octofhir_search::DEFAULT_MAX_CHAIN_DEPTH`].
+    pub max_chain_depth: Option<usize>,
+    /// Only compute `total` (`_summary=count`): no resources are fetched and
+    /// includes are skipped. `_total=estimate` uses the planner's row
+    /// estimate instead of running `COUNT(*)`.
+    pub count_only: bool,
 }
 
 /// Converts chrono DateTime to time OffsetDateTime.
@@ -106,17 +144,22 @@ pub async fn execute_search(
     params: &SearchParams,
     registry: Option<&Arc<SearchParameterRegistry>>,
 ) -> Result<SearchResult, StorageError> {
+    if !is_resource_type_allowed(resource_type) {
+        return Ok(SearchResult::empty());
+    }
     let requested_limit = params.count.unwrap_or(10) as usize;
     let mut effective_params = params.clone();
     effective_params.count = Some(params.count.unwrap_or(10).saturating_add(1));
+    charge_queries(plan_joins(params))?;
 
     // Use default registry if none provided
     let empty_registry = SearchParameterRegistry::new();
     let registry = registry.map(|r| r.as_ref()).unwrap_or(&empty_registry);
 
     // Convert SearchParams to SQL query through the native-IR search path.
+    let schema = SchemaManager::resource_schema();
     let converted =
-        build_native_ir_query_from_params(resource_type, &effective_params, registry, "public")
+        build_native_ir_query_from_params(resource_type, &effective_params, registry, &schema)
             .map_err(|e| {
                 tracing::warn!(error = %e, "Failed to build search query");
                 StorageError::invalid_resource(format!("Invalid search parameters: {e}"))
@@ -158,14 +201,22 @@ pub async fn execute_search(
     let mut all_entries = entries;
 
     if !converted.includes.is_empty() || !converted.revincludes.is_empty() {
-        let included = resolve_includes_revincludes(
+        let (included, truncated) = resolve_includes_revincludes(
             pool,
             &all_entries,
             &converted.includes,
             &converted.revincludes,
             registry,
+            DEFAULT_MAX_INCLUDED,
         )
         .await?;
+        if truncated {
+            tracing::info!(
+                resource_type = %resource_type,
+                max_included = DEFAULT_MAX_INCLUDED,
+                "Search include expansion truncated"
+            );
+        }
         all_entries.extend(included);
     }
 
@@ -186,15 +237,20 @@ pub async fn execute_search_with_tx(
     params: &SearchParams,
     registry: Option<&Arc<SearchParameterRegistry>>,
 ) -> Result<SearchResult, StorageError> {
+    if !is_resource_type_allowed(resource_type) {
+        return Ok(SearchResult::empty());
+    }
     let requested_limit = params.count.unwrap_or(10) as usize;
     let mut effective_params = params.clone();
     effective_params.count = Some(params.count.unwrap_or(10).saturating_add(1));
+    charge_queries(plan_joins(params))?;
 
     let empty_registry = SearchParameterRegistry::new();
     let registry = registry.map(|r| r.as_ref()).unwrap_or(&empty_registry);
 
+    let schema = SchemaManager::resource_schema();
     let converted =
-        build_native_ir_query_from_params(resource_type, &effective_params, registry, "public")
+        build_native_ir_query_from_params(resource_type, &effective_params, registry, &schema)
             .map_err(|e| {
                 tracing::warn!(error = %e, "Failed to build transaction search query");
                 StorageError::invalid_resource(format!("Invalid search parameters: {e}"))
@@ -281,6 +337,9 @@ pub async fn execute_search_raw_with_config(
             collect_explain_plan: false,
             collect_explain_analyze: false,
             max_valueset_expansion: None,
+            max_included: None,
+            max_chain_depth: None,
+            count_only: false,
         },
     )
     .await
@@ -339,6 +398,9 @@ pub async fn execute_search_raw_with_terminology(
             collect_explain_plan: false,
             collect_explain_analyze: false,
             max_valueset_expansion: None,
+            max_included: None,
+            max_chain_depth: None,
+            count_only: false,
         },
     )
     .await
@@ -375,10 +437,15 @@ async fn execute_search_raw_with_config_inner(
     terminology: Option<&Arc<HybridTerminologyProvider>>,
     options: RawSearchOptions,
 ) -> Result<RawSearchResult, StorageError> {
+    if !is_resource_type_allowed(resource_type) {
+        return Ok(RawSearchResult::empty());
+    }
     let requested_limit = params.count.unwrap_or(10) as usize;
     let mut effective_params = params.clone();
     effective_params.count = Some(params.count.unwrap_or(10).saturating_add(1));
 
+    charge_queries(plan_joins(params))?;
+
     // Use default registry if none provided
     let empty_registry = Arc::new(SearchParameterRegistry::new());
     let registry_arc = registry.unwrap_or(&empty_registry);
@@ -389,50 +456,31 @@ async fn execute_search_raw_with_config_inner(
     // searches (`:in`/`:not-in` against a ValueSet, `:above`/`:below`
     // against a code-system hierarchy).
     if let Some(tx) = terminology {
-        let max_expansion = options
-            .max_valueset_expansion
-            .unwrap_or(DEFAULT_MAX_EXPANSION_SIZE);
-        let trait_view: Arc<dyn TerminologyProvider> = tx.clone();
-        pre_expand_terminology_modifiers(
-            &mut effective_params,
-            registry,
-            resource_type,
-            &trait_view,
-            max_expansion,
-        )
-        .await
-        .map_err(|e| {
-            tracing::warn!(error = %e, "Terminology pre-expansion failed");
-            StorageError::invalid_resource(format!("Terminology expansion failed: {e}"))
-        })?;
-
-        pre_expand_subsumption_modifiers(
+        pre_expand_search_params(
             &mut effective_params,
             registry,
             resource_type,
             tx,
-            max_expansion,
+            options.max_valueset_expansion,
         )
-        .await
-        .map_err(|e| {
-            tracing::warn!(error = %e, "Subsumption pre-expansion failed");
-            StorageError::invalid_resource(format!("Subsumption expansion failed: {e}"))
-        })?;
+        .await?;
     }
 
     // Build search config
     let search_config = ParamsSearchConfig {
         unknown_param_handling: options.unknown_param_handling.unwrap_or_default(),
         collect_debug_plan: options.collect_debug_plan,
+        max_chain_depth: options.max_chain_depth,
     };
 
     // Convert SearchParams to SQL query using the params converter
     let build_started = Instant::now();
-    let converted = build_native_ir_query_from_params_with_config(
+    let schema = SchemaManager::resource_schema();
+    let mut converted = build_native_ir_query_from_params_with_config(
         resource_type,
         &effective_params,
         registry,
-        "public",
+        &schema,
         &search_config,
     )
     .map_err(|e| {
@@ -451,7 +499,7 @@ async fn execute_search_raw_with_config_inner(
     }
 
     // Collect unknown parameters as warnings
-    let warnings: Vec<String> = converted
+    let mut warnings: Vec<String> = converted
         .unknown_params
         .iter()
         .map(|p| format!("Unknown search parameter '{}' was ignored", p.name))
@@ -464,6 +512,64 @@ async fn execute_search_raw_with_config_inner(
         );
     }
 
+    // `_summary=count`: only the total is returned, so skip the page query,
+    // includes and the query cache entirely.
+    if options.count_only {
+        let count_query = converted.builder.build_count().map_err(|e| {
+            tracing::warn!(error = %e, "Failed to build count SQL");
+            StorageError::internal(format!("Failed to build count SQL: {e}"))
+        })?;
+        let build_elapsed = build_started.elapsed();
+        let execute_started = Instant::now();
+        let total = if matches!(converted.total_mode, Some(TotalMode::Estimate)) {
+            execute_estimate_query(pool, &count_query).await?
+        } else {
+            execute_count_query(pool, &count_query).await?
+        };
+        let debug = options.collect_debug_plan.then(|| RawSearchDebug {
+            sql_shape: Some(redact_sql_shape(&count_query.sql)),
+            plan: converted
+                .debug_plan
+                .as_ref()
+                .and_then(|plan| serde_json::to_value(plan).ok()),
+            explain: None,
+            analyze: false,
+            build_elapsed_ms: Some(build_elapsed.as_secs_f64() * 1000.0),
+            db_execute_elapsed_ms: Some(execute_started.elapsed().as_secs_f64() * 1000.0),
+        });
+        return Ok(RawSearchResult {
+            entries: Vec::new(),
+            scores: Vec::new(),
+            included: Vec::new(),
+            total: Some(total),
+            has_more: false,
+            deleted: Vec::new(),
+            warnings,
+            debug,
+        });
+    }
+
+    // A sync page and the deletes reported with it stop at one horizon, read
+    // once up front; each statement evaluating its own could let the deletes
+    // move the cursor past writes the page has not seen yet
+    let sync_horizon = match &params.sync {
+        Some(_) => {
+            let horizon = read_sync_horizon(pool).await?;
+            converted.builder = converted.builder.where_condition(SearchCondition::raw(
+                "r.updated_at < $1::timestamptz",
+                vec![SqlValue::Timestamp(
+                    horizon.to_rfc3339_opts(chrono::SecondsFormat::Micros, true),
+                )],
+            ));
+            Some(horizon)
+        }
+        None => None,
+    };
+
+    // The SQL of a sync or id keyset page depends on its position, which
+    // the cache key does not capture
+    let query_cache = query_cache.filter(|_| params.sync.is_none() && params.after_id.is_none());
+
     // Build cache key for query template reuse
     let cache_key = query_cache.map(|_| {
         let param_keys: Vec<QueryParamKey> = params
@@ -542,7 +648,11 @@ async fn execute_search_raw_with_config_inner(
                     ""
                 };
 
-                let cache_name = format!("{name}#{token_shape}{missing_tag}");
+                // Repeated occurrences AND while commas OR inside one, so
+                // `code=a,b&code=c` and `code=a&code=b,c` bind the same number
+                // of values into differently grouped SQL.
+                let or_groups = or_group_tag(values);
+                let cache_name = format!("{name}#{token_shape}{missing_tag}#{or_groups}");
 
                 // Distinguish prefixes per value — date / number / quantity SQL
                 // shape depends on `eq`/`gt`/`lt`/`ne`/`ge`/`le`/`sa`/`eb`/`ap`
@@ -599,6 +709,7 @@ async fn execute_search_raw_with_config_inner(
             params.count.is_some() || params.offset.is_some(),
             sort_fields,
         )
+        .with_schema(schema.as_str())
         .with_pagination(effective_params.count, effective_params.offset)
     });
 
@@ -684,7 +795,7 @@ async fn execute_search_raw_with_config_inner(
 
     // Execute the main query with raw JSON (SQL already emits resource::text)
     let execute_started = Instant::now();
-    let entries = execute_query_raw(pool, &built_query, resource_type).await?;
+    let (entries, mut scores) = execute_query_raw(pool, &built_query, resource_type).await?;
     let execute_elapsed = execute_started.elapsed();
 
     if options.collect_debug_plan {
@@ -703,6 +814,30 @@ async fn execute_search_raw_with_config_inner(
     let has_more = entries.len() > requested_limit;
     let entries: Vec<RawStoredResource> = entries.into_iter().take(requested_limit).collect();
 
+    // Deletes of a sync page come from the tombstones in history, up to the
+    // last live entry when more live entries follow
+    let (entries, deleted, has_more) = match (&params.sync, sync_horizon) {
+        (Some(position), Some(horizon)) if SchemaManager::has_history(resource_type) => {
+            let upto = entries
+                .last()
+                .filter(|_| has_more)
+                .map(|e| SyncCursor::new(e.last_updated, e.id.clone()));
+            let deleted = sync_deletions(
+                pool,
+                resource_type,
+                position,
+                horizon,
+                upto.as_ref(),
+                requested_limit,
+            )
+            .await?;
+            let (entries, deleted, truncated) = merge_sync_page(entries, deleted, requested_limit);
+            (entries, deleted, has_more || truncated)
+        }
+        _ => (entries, Vec::new(), has_more),
+    };
+    scores.truncate(entries.len());
+
     // Execute count query if requested
     let total = if let Some(cq) = count_query {
         let count_started = Instant::now();
@@ -724,15 +859,26 @@ async fn execute_search_raw_with_config_inner(
 
     // Handle _include and _revinclude natively with raw results
     let included = if !converted.includes.is_empty() || !converted.revincludes.is_empty() {
-        resolve_includes_revincludes_raw(
+        let max_included = options.max_included.unwrap_or(DEFAULT_MAX_INCLUDED);
+        let (included, truncated) = resolve_includes_revincludes_raw(
             pool,
             resource_type,
             &entries,
             &converted.includes,
             &converted.revincludes,
             registry,
+            max_included,
         )
-        .await?
+        .await?;
+        if truncated {
+            tracing::info!(
+                resource_type = %resource_type,
+                max_included,
+                "Search include expansion truncated"
+            );
+            warnings.push(included_limit_warning(max_included));
+        }
+        included
     } else {
         Vec::new()
     };
@@ -751,20 +897,220 @@ async fn execute_search_raw_with_config_inner(
 
     Ok(RawSearchResult {
         entries,
+        scores,
         included,
         total,
         has_more,
+        deleted,
         warnings,
         debug,
     })
 }
 
+/// Reads the [sync horizon](sync_horizon_sql) of a page.
+async fn read_sync_horizon(pool: &PgPool) -> Result<DateTime<Utc>, StorageError> {
+    query_scalar(AssertSqlSafe(format!("SELECT {}", sync_horizon_sql())))
+        .fetch_one(pool)
+        .await
+        .map_err(|e| database_error("Failed to read the sync horizon", e))
+}
+
+/// Deletes of `resource_type` for an incremental sync page: the tombstones
+/// after `position` and below `horizon` (and, when given, up to `upto`), at
+/// most `limit + 1` in page order. A resource created again since has a
+/// live row, which the sync reports instead.
+async fn sync_deletions(
+    pool: &PgPool,
+    resource_type: &str,
+    position: &SyncPosition,
+    horizon: DateTime<Utc>,
+    upto: Option<&SyncCursor>,
+    limit: usize,
+) -> Result<Vec<SyncDeletion>, StorageError> {
+    let table = SchemaManager::qualified_table_name(resource_type);
+    let history_table = SchemaManager::qualified_history_table_name(resource_type);
+    let key = |cursor: Option<&SyncCursor>| {
+        cursor.map_or((None, None), |c| {
+            (
+                DateTime::<Utc>::from_timestamp_micros(c.last_updated_micros),
+                Some(c.id.clone()),
+            )
+        })
+    };
+    let after = key(match position {
+        SyncPosition::Start => None,
+        SyncPosition::After(cursor) => Some(cursor),
+    });
+    let upto = key(upto);
+    let sql = format!(
+        r#"SELECT h.id, h.txid, h.updated_at
+           FROM {history_table} h
+           WHERE h.status = 'deleted'
+             AND h.updated_at < $1
+             AND ($2::timestamptz IS NULL OR (h.updated_at, h.id) > ($2, $3::text))
+             AND ($4::timestamptz IS NULL OR (h.updated_at, h.id) <= ($4, $5::text))
+             AND NOT EXISTS (SELECT 1 FROM {table} r WHERE r.id = h.id)
+           ORDER BY h.updated_at, h.id
+           LIMIT {}"#,
+        limit + 1
+    );
+    let rows: Vec<(String, i64, DateTime<Utc>)> = query_as(AssertSqlSafe(sql))
+        .bind(horizon)
+        .bind(after.0)
+        .bind(after.1)
+        .bind(upto.0)
+        .bind(upto.1)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| database_error("Failed to read sync deletions", e))?;
+    Ok(rows
+        .into_iter()
+        .map(|(id, txid, deleted_at)| SyncDeletion {
+            id,
+            version_id: txid.to_string(),
+            deleted_at: chrono_to_time(deleted_at),
+        })
+        .collect())
+}
+
+/// Merges the live entries and the deletes of a sync page, both in page
+/// order, into at most `limit` positions. Returns what made the page and
+/// whether anything was cut.
+fn merge_sync_page(
+    mut entries: Vec<RawStoredResource>,
+    mut deleted: Vec<SyncDeletion>,
+    limit: usize,
+) -> (Vec<RawStoredResource>, Vec<SyncDeletion>, bool) {
+    let (mut live, mut gone) = (0, 0);
+    while live + gone < limit {
+        let next_live = entries.get(live).map(|e| (e.last_updated, e.id.as_str()));
+        let next_gone = deleted.get(gone).map(|d| (d.deleted_at, d.id.as_str()));
+        match (next_live, next_gone) {
+            (Some(l), Some(d)) if l < d => live += 1,
+            (_, Some(_)) => gone += 1,
+            (Some(_), None) => live += 1,
+            (None, None) => break,
+        }
+    }
+    let truncated = live < entries.len() || gone < deleted.len();
+    entries.truncate(live);
+    deleted.truncate(gone);
+    (entries, deleted, truncated)
+}
+
+/// Pre-expand `:in` / `:not-in` / `:above` / `:below` Token modifiers in place
+/// via the terminology provider.
+async fn pre_expand_search_params(
+    params: &mut SearchParams,
+    registry: &SearchParameterRegistry,
+    resource_type: &str,
+    terminology: &Arc<HybridTerminologyProvider>,
+    max_valueset_expansion: Option<usize>,
+) -> Result<(), StorageError> {
+    let max_expansion = max_valueset_expansion.unwrap_or(DEFAULT_MAX_EXPANSION_SIZE);
+    let trait_view: Arc<dyn TerminologyProvider> = terminology.clone();
+    pre_expand_terminology_modifiers(params, registry, resource_type, &trait_view, max_expansion)
+        .await
+        .map_err(|e| {
+            tracing::warn!(error = %e, "Terminology pre-expansion failed");
+            StorageError::invalid_resource(format!("Terminology expansion failed: {e}"))
+        })?;
+
+    pre_expand_subsumption_modifiers(params, registry, resource_type, terminology, max_expansion)
+        .await
+        .map_err(|e| {
+            tracing::warn!(error = %e, "Subsumption pre-expansion failed");
+            StorageError::invalid_resource(format!("Subsumption expansion failed: {e}"))
+        })
+}
+
+/// Generated SQL, bind parameters and EXPLAIN output for a search.
+#[derive(Debug, Clone)]
+pub struct SearchExplain {
+    /// The generated SQL, unredacted.
+    pub sql: String,
+    /// Bind parameter values in `$1..$n` order.
+    pub params: Vec<String>,
+    /// PostgreSQL `EXPLAIN (..., FORMAT JSON)` output.
+    pub plan: Value,
+    /// Whether the plan was produced with `ANALYZE, BUFFERS` (query executed).
+    pub analyze: bool,
+}
+
+/// Build the SQL for a search exactly as [`execute_search_raw_with_terminology_options`]
+/// would and run `EXPLAIN` on it without returning any resources.
+///
+/// `schema` is the schema holding the resource tables, normally
+/// [`SchemaManager::resource_schema`] of the caller's tenant scope.
+///
+/// Unlike the `_debug` search path, the SQL and bind values are returned
+/// unredacted, so callers must restrict this to administrators.
+pub async fn explain_search(
+    pool: &PgPool,
+    schema: &str,
+    resource_type: &str,
+    params: &SearchParams,
+    registry: Option<&Arc<SearchParameterRegistry>>,
+    terminology: Option<&Arc<HybridTerminologyProvider>>,
+    options: RawSearchOptions,
+) -> Result<SearchExplain, StorageError> {
+    let mut effective_params = params.clone();
+    effective_params.count = Some(params.count.unwrap_or(10).saturating_add(1));
+
+    let empty_registry = Arc::new(SearchParameterRegistry::new());
+    let registry = registry.unwrap_or(&empty_registry).as_ref();
+
+    if let Some(tx) = terminology {
+        pre_expand_search_params(
+            &mut effective_params,
+            registry,
+            resource_type,
+            tx,
+            options.max_valueset_expansion,
+        )
+        .await?;
+    }
+
+    let search_config = ParamsSearchConfig {
+        unknown_param_handling: options.unknown_param_handling.unwrap_or_default(),
+        collect_debug_plan: false,
+        max_chain_depth: options.max_chain_depth,
+    };
+    let built_query = build_native_ir_query_from_params_with_config(
+        resource_type,
+        &effective_params,
+        registry,
+        schema,
+        &search_config,
+    )
+    .map_err(|e| StorageError::invalid_resource(format!("Invalid search parameters: {e}")))?
+    .builder
+    .with_raw_resource(true)
+    .build()
+    .map_err(|e| StorageError::internal(format!("Failed to build search SQL: {e}")))?;
+
+    let plan = explain_built_search_query_json(pool, &built_query, options.collect_explain_analyze)
+        .await?;
+
+    Ok(SearchExplain {
+        params: built_query
+            .params
+            .iter()
+            .map(SqlValue::as_display_str)
+            .collect(),
+        sql: built_query.sql,
+        plan,
+        analyze: options.collect_explain_analyze,
+    })
+}
+
 /// Execute a search query and return StoredResource entries.
 async fn execute_query(
     pool: &PgPool,
     query: &BuiltQuery,
     resource_type: &str,
 ) -> Result<Vec<StoredResource>, StorageError> {
+    charge_query()?;
     // Build dynamic query with parameters
     // The query returns: resource, id, txid, created_at, updated_at
     let mut sqlx_query =
@@ -784,26 +1130,24 @@ async fn execute_query(
     }
 
     // Execute and map results
-    let rows: Vec<(Value, String, i64, DateTime<Utc>, DateTime<Utc>)> =
-        query_as(AssertSqlSafe(query.sql.to_string()))
-            .bind_all_params(&query.params)
-            .fetch_all(pool)
-            .await
-            .map_err(|e| {
-                tracing::warn!(
-                    error = %e,
-                    sql_shape = %redact_sql_shape(&query.sql),
-                    "Search query failed"
-                );
-                // Check for undefined table error (PostgreSQL 42P01)
-                if is_undefined_table(&e) {
-                    return StorageError::internal(format!(
-                        "Table for {} does not exist",
-                        resource_type
-                    ));
-                }
-                StorageError::internal(format!("Search query failed: {e}"))
-            })?;
+    let mut conn = BoundedConnection::acquire(pool).await?;
+    let rows = query_as(AssertSqlSafe(query.sql.to_string()))
+        .bind_all_params(&query.params)
+        .fetch_all(conn.conn())
+        .await;
+    conn.finish();
+    let rows: Vec<(Value, String, i64, DateTime<Utc>, DateTime<Utc>)> = rows.map_err(|e| {
+        tracing::warn!(
+            error = %e,
+            sql_shape = %redact_sql_shape(&query.sql),
+            "Search query failed"
+        );
+        // Check for undefined table error (PostgreSQL 42P01)
+        if is_undefined_table(&e) {
+            return StorageError::internal(format!("Table for {} does not exist", resource_type));
+        }
+        database_error("Search query failed", e)
+    })?;
 
     let entries: Vec<StoredResource> = rows
         .into_iter()
@@ -829,6 +1173,7 @@ async fn execute_query_with_tx(
     query: &BuiltQuery,
     resource_type: &str,
 ) -> Result<Vec<StoredResource>, StorageError> {
+    charge_query()?;
     let rows: Vec<(Value, String, i64, DateTime<Utc>, DateTime<Utc>)> =
         query_as(AssertSqlSafe(query.sql.to_string()))
             .bind_all_params(&query.params)
@@ -846,7 +1191,7 @@ async fn execute_query_with_tx(
                         resource_type
                     ));
                 }
-                StorageError::internal(format!("Search query failed: {e}"))
+                database_error("Search query failed", e)
             })?;
 
     let entries = rows
@@ -868,7 +1213,33 @@ async fn execute_query_with_tx(
     Ok(entries)
 }
 
-/// Execute a search query and return raw JSON entries (optimized path).
+/// Row of a raw search query: the resource as text, its metadata and, for
+/// queries built [`with_score`](octofhir_search::FhirQueryBuilder::with_score),
+/// the relevance score in a sixth column.
+struct RawSearchRow {
+    resource_json: String,
+    id: String,
+    txid: i64,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+    score: Option<f32>,
+}
+
+impl<'r> FromRow<'r, PgRow> for RawSearchRow {
+    fn from_row(row: &'r PgRow) -> Result<Self, sqlx_core::Error> {
+        Ok(Self {
+            resource_json: row.try_get(0)?,
+            id: row.try_get(1)?,
+            txid: row.try_get(2)?,
+            created_at: row.try_get(3)?,
+            updated_at: row.try_get(4)?,
+            score: if row.len() > 5 { row.try_get(5)? } else { None },
+        })
+    }
+}
+
+/// Execute a search query and return raw JSON entries (optimized path),
+/// with their relevance scores if the query selects one (empty otherwise).
 ///
 /// Expects SQL that already selects `resource::text` (via `with_raw_resource(true)`
 /// on the query builder), avoiding JSONB → Value deserialization overhead.
@@ -876,18 +1247,16 @@ async fn execute_query_raw(
     pool: &PgPool,
     query: &BuiltQuery,
     resource_type: &str,
-) -> Result<Vec<RawStoredResource>, StorageError> {
+) -> Result<(Vec<RawStoredResource>, Vec<f64>), StorageError> {
+    charge_query()?;
     // Execute and map results (SQL already selects resource::text)
-    let rows: Vec<(String, String, i64, DateTime<Utc>, DateTime<Utc>)> = query_as::<
-        _,
-        (String, String, i64, DateTime<Utc>, DateTime<Utc>),
-    >(AssertSqlSafe(
-        query.sql.to_string(),
-    ))
-    .bind_all_params_raw(&query.params)
-    .fetch_all(pool)
-    .await
-    .map_err(|e| {
+    let mut conn = BoundedConnection::acquire(pool).await?;
+    let rows = query_as::<_, RawSearchRow>(AssertSqlSafe(query.sql.to_string()))
+        .bind_all_params_raw(&query.params)
+        .fetch_all(conn.conn())
+        .await;
+    conn.finish();
+    let rows = rows.map_err(|e| {
         tracing::warn!(
             error = %e,
             sql_shape = %redact_sql_shape(&query.sql),
@@ -897,42 +1266,83 @@ async fn execute_query_raw(
         if is_undefined_table(&e) {
             return StorageError::internal(format!("Table for {} does not exist", resource_type));
         }
-        StorageError::internal(format!("Search query failed: {e}"))
+        database_error("Search query failed", e)
     })?;
 
+    let scores: Vec<f64> = rows
+        .iter()
+        .map_while(|row| row.score.map(f64::from))
+        .collect();
     let entries: Vec<RawStoredResource> = rows
         .into_iter()
-        .map(|(resource_json, id, txid, created_at, updated_at)| {
-            let created_at_time = chrono_to_time(created_at);
-            let updated_at_time = chrono_to_time(updated_at);
-            RawStoredResource {
-                id,
-                version_id: txid.to_string(),
-                resource_type: resource_type.to_string(),
-                resource_json,
-                last_updated: updated_at_time,
-                created_at: created_at_time,
-            }
+        .map(|row| RawStoredResource {
+            id: row.id,
+            version_id: row.txid.to_string(),
+            resource_type: resource_type.to_string(),
+            resource_json: row.resource_json,
+            last_updated: chrono_to_time(row.updated_at),
+            created_at: chrono_to_time(row.created_at),
         })
         .collect();
 
-    Ok(entries)
+    Ok((entries, scores))
 }
 
 /// Execute a count query and return the total.
 async fn execute_count_query(pool: &PgPool, query: &BuiltQuery) -> Result<u32, StorageError> {
-    let count: i64 = query_scalar(AssertSqlSafe(query.sql.to_string()))
+    charge_query()?;
+    let mut conn = BoundedConnection::acquire(pool).await?;
+    let count = query_scalar(AssertSqlSafe(query.sql.to_string()))
         .bind_all_params(&query.params)
-        .fetch_one(pool)
-        .await
-        .map_err(|e| {
-            tracing::warn!(error = %e, "Count query failed");
-            StorageError::internal(format!("Count query failed: {e}"))
-        })?;
+        .fetch_one(conn.conn())
+        .await;
+    conn.finish();
+    let count: i64 = count.map_err(|e| {
+        tracing::warn!(error = %e, "Count query failed");
+        database_error("Count query failed", e)
+    })?;
 
     Ok(count as u32)
 }
 
+/// Select list emitted by `FhirQueryBuilder::build_count`.
+const COUNT_SELECT_PREFIX: &str = "SELECT COUNT(*) as total FROM ";
+
+/// Estimate the number of matches from the planner's row estimate for the
+/// count query's `FROM`/`WHERE`, without executing it. Falls back to an exact
+/// count when the query doesn't have the expected shape.
+async fn execute_estimate_query(pool: &PgPool, query: &BuiltQuery) -> Result<u32, StorageError> {
+    let Some(explain_sql) = estimate_explain_sql(&query.sql) else {
+        return execute_count_query(pool, query).await;
+    };
+    charge_query()?;
+    let mut conn = BoundedConnection::acquire(pool).await?;
+    let plan = query_scalar(AssertSqlSafe(explain_sql))
+        .bind_all_params(&query.params)
+        .fetch_one(conn.conn())
+        .await;
+    conn.finish();
+    let plan: Value = plan.map_err(|e| {
+        tracing::warn!(error = %e, "Count estimate query failed");
+        database_error("Count estimate query failed", e)
+    })?;
+
+    Ok(plan_row_estimate(&plan).unwrap_or(0))
+}
+
+/// `EXPLAIN` of the count query with `COUNT(*)` swapped for a plain row
+/// select, so the top plan node's row estimate is the match count.
+fn estimate_explain_sql(count_sql: &str) -> Option<String> {
+    let rest = count_sql.strip_prefix(COUNT_SELECT_PREFIX)?;
+    Some(format!("EXPLAIN (FORMAT JSON) SELECT 1 FROM {rest}"))
+}
+
+/// Top-level `Plan Rows` of an `EXPLAIN (FORMAT JSON)` result.
+fn plan_row_estimate(plan: &Value) -> Option<u32> {
+    let rows = plan.get(0)?.get("Plan")?.get("Plan Rows")?.as_f64()?;
+    Some(rows.clamp(0.0, u32::MAX as f64) as u32)
+}
+
 /// Execute PostgreSQL EXPLAIN for an already-built search query and return FORMAT JSON output.
 ///
 /// `analyze = true` runs the query via `EXPLAIN ANALYZE`; callers must keep that behind an
@@ -971,13 +1381,14 @@ async fn execute_count_query_with_tx(
     tx: &mut PgTransaction<'_>,
     query: &BuiltQuery,
 ) -> Result<u32, StorageError> {
+    charge_query()?;
     let count: i64 = query_scalar(AssertSqlSafe(query.sql.to_string()))
         .bind_all_params(&query.params)
         .fetch_one(&mut **tx)
         .await
         .map_err(|e| {
             tracing::warn!(error = %e, "Transaction count query failed");
-            StorageError::internal(format!("Count query failed: {e}"))
+            database_error("Count query failed", e)
         })?;
 
     Ok(count as u32)
@@ -987,7 +1398,7 @@ async fn execute_count_query_with_tx(
 /// parameter's references on `resource_col`, derived from the parameter's
 /// FHIRPath via the registry. Returns `None` when the parameter or its
 /// expression is unknown (the include then yields nothing).
-fn reference_array_sql(
+pub(crate) fn reference_array_sql(
     registry: &SearchParameterRegistry,
     resource_type: &str,
     param_name: &str,
@@ -1006,30 +1417,36 @@ fn reference_array_sql(
 /// Regex pulling (Type, id) out of a FHIR reference string, anchored at the end
 /// so both relative (`Patient/123`) and absolute (`http://h/fhir/Patient/123`)
 /// references match. Capture 1 = type, capture 2 = id.
-const REFERENCE_TYPE_ID_RE: &str = r"([A-Za-z]+)/([A-Za-z0-9.-]{1,64})$";
+pub(crate) const REFERENCE_TYPE_ID_RE: &str = r"([A-Za-z]+)/([A-Za-z0-9.-]{1,64})$";
 
 /// Resolve _include and _revinclude specifications.
 ///
 /// Executes all include and revinclude queries in parallel for better latency.
+/// Like [`resolve_includes_revincludes_raw`], at most `max_included` distinct
+/// resources are returned and the flag reports whether more matched.
 async fn resolve_includes_revincludes(
     pool: &PgPool,
     main_results: &[StoredResource],
     includes: &[octofhir_search::IncludeSpec],
     revincludes: &[octofhir_search::RevIncludeSpec],
     registry: &SearchParameterRegistry,
-) -> Result<Vec<StoredResource>, StorageError> {
+    max_included: usize,
+) -> Result<(Vec<StoredResource>, bool), StorageError> {
     use futures_util::future::try_join_all;
 
+    // One extra row per query is enough to tell that the cap was exceeded.
+    let limit = max_included.saturating_add(1);
+
     // Build futures for all include queries
     let include_futures: Vec<_> = includes
         .iter()
-        .map(|include| resolve_include(pool, main_results, include, registry))
+        .map(|include| resolve_include(pool, main_results, include, registry, limit))
         .collect();
 
     // Build futures for all revinclude queries
     let revinclude_futures: Vec<_> = revincludes
         .iter()
-        .map(|revinclude| resolve_revinclude(pool, main_results, revinclude, registry))
+        .map(|revinclude| resolve_revinclude(pool, main_results, revinclude, registry, limit))
         .collect();
 
     // Execute all queries in parallel
@@ -1038,11 +1455,11 @@ async fn resolve_includes_revincludes(
         try_join_all(revinclude_futures)
     )?;
 
-    // Flatten results
-    let mut included: Vec<StoredResource> = include_results.into_iter().flatten().collect();
-    included.extend(revinclude_results.into_iter().flatten());
-
-    Ok(included)
+    let candidates = include_results
+        .into_iter()
+        .flatten()
+        .chain(revinclude_results.into_iter().flatten());
+    Ok(cap_included(main_results, candidates, max_included))
 }
 
 /// Resolve a single _include specification by matching references in place over
@@ -1052,12 +1469,13 @@ async fn resolve_include(
     main_results: &[StoredResource],
     include: &octofhir_search::IncludeSpec,
     registry: &SearchParameterRegistry,
+    limit: usize,
 ) -> Result<Vec<StoredResource>, StorageError> {
     if include.iterate {
-        return resolve_include_iterate(pool, main_results, include, registry).await;
+        return resolve_include_iterate(pool, main_results, include, registry, limit).await;
     }
 
-    resolve_include_once(pool, main_results, include, registry).await
+    resolve_include_once(pool, main_results, include, registry, limit).await
 }
 
 async fn resolve_include_iterate(
@@ -1065,6 +1483,7 @@ async fn resolve_include_iterate(
     main_results: &[StoredResource],
     include: &octofhir_search::IncludeSpec,
     registry: &SearchParameterRegistry,
+    limit: usize,
 ) -> Result<Vec<StoredResource>, StorageError> {
     let mut visited: HashSet<(String, String)> = main_results
         .iter()
@@ -1078,11 +1497,12 @@ async fn resolve_include_iterate(
     let mut included = Vec::new();
 
     for _ in 0..INCLUDE_ITERATE_MAX_DEPTH {
-        if current.is_empty() {
+        if current.is_empty() || included.len() >= limit {
             break;
         }
 
-        let next = resolve_include_once(pool, &current, include, registry).await?;
+        let next =
+            resolve_include_once(pool, &current, include, registry, limit - included.len()).await?;
         current = Vec::new();
         for entry in next {
             let key = (entry.resource_type.clone(), entry.id.clone());
@@ -1103,6 +1523,7 @@ async fn resolve_include_once(
     main_results: &[StoredResource],
     include: &octofhir_search::IncludeSpec,
     registry: &SearchParameterRegistry,
+    limit: usize,
 ) -> Result<Vec<StoredResource>, StorageError> {
     if main_results.is_empty() {
         return Ok(Vec::new());
@@ -1134,6 +1555,9 @@ async fn resolve_include_once(
 
     let mut entries = Vec::new();
     for target_type in target_types {
+        if entries.len() >= limit {
+            break;
+        }
         let mut matched = query_include_for_target(
             pool,
             source_type,
@@ -1141,6 +1565,7 @@ async fn resolve_include_once(
             &source_ids,
             &target_type,
             registry,
+            limit - entries.len(),
         )
         .await?;
         entries.append(&mut matched);
@@ -1156,27 +1581,31 @@ async fn query_include_for_target(
     source_ids: &[&str],
     target_type: &str,
     registry: &SearchParameterRegistry,
+    limit: usize,
 ) -> Result<Vec<StoredResource>, StorageError> {
     let Some(ref_array) = reference_array_sql(registry, source_type, param_name, "s.resource")
     else {
         return Ok(Vec::new());
     };
-    let source_table = SchemaManager::table_name(source_type);
-    let table = SchemaManager::table_name(target_type);
+    let source_table = SchemaManager::qualified_table_name(source_type);
+    let table = SchemaManager::qualified_table_name(target_type);
 
     let sql = format!(
         r#"SELECT DISTINCT t.resource, t.id, t.txid, t.created_at, t.updated_at
-           FROM "{source_table}" s
+           FROM {source_table} s
            CROSS JOIN LATERAL jsonb_array_elements({ref_array}) AS ref
            CROSS JOIN LATERAL (SELECT regexp_match(ref->>'reference', '{REFERENCE_TYPE_ID_RE}') AS m) x
-           JOIN "{table}" t ON t.id = x.m[2] AND t.status != 'deleted'
-           WHERE s.id = ANY($1::text[]) AND s.status != 'deleted' AND x.m[1] = $2"#
+           JOIN {table} t ON t.id = x.m[2] AND t.status != 'deleted'
+           WHERE s.id = ANY($1::text[]) AND s.status != 'deleted' AND x.m[1] = $2
+           LIMIT $3"#
     );
 
+    charge_query()?;
     let rows: Vec<(Value, String, i64, DateTime<Utc>, DateTime<Utc>)> =
         query_as(AssertSqlSafe(sql.to_string()))
             .bind(source_ids)
             .bind(target_type)
+            .bind(limit as i64)
             .fetch_all(pool)
             .await
             .map_err(|e| {
@@ -1215,9 +1644,10 @@ async fn resolve_revinclude(
     main_results: &[StoredResource],
     revinclude: &octofhir_search::RevIncludeSpec,
     registry: &SearchParameterRegistry,
+    limit: usize,
 ) -> Result<Vec<StoredResource>, StorageError> {
     if revinclude.iterate {
-        return resolve_revinclude_iterate(pool, main_results, revinclude, registry).await;
+        return resolve_revinclude_iterate(pool, main_results, revinclude, registry, limit).await;
     }
 
     if main_results.is_empty() {
@@ -1229,7 +1659,7 @@ async fn resolve_revinclude(
         .map(|r| r.resource_type.as_str())
         .unwrap_or("");
 
-    resolve_revinclude_once(pool, main_type, main_results, revinclude, registry).await
+    resolve_revinclude_once(pool, main_type, main_results, revinclude, registry, limit).await
 }
 
 async fn resolve_revinclude_iterate(
@@ -1237,6 +1667,7 @@ async fn resolve_revinclude_iterate(
     main_results: &[StoredResource],
     revinclude: &octofhir_search::RevIncludeSpec,
     registry: &SearchParameterRegistry,
+    limit: usize,
 ) -> Result<Vec<StoredResource>, StorageError> {
     let mut visited: HashSet<(String, String)> = main_results
         .iter()
@@ -1246,7 +1677,7 @@ async fn resolve_revinclude_iterate(
     let mut included = Vec::new();
 
     for _ in 0..INCLUDE_ITERATE_MAX_DEPTH {
-        if current.is_empty() {
+        if current.is_empty() || included.len() >= limit {
             break;
         }
 
@@ -1260,8 +1691,18 @@ async fn resolve_revinclude_iterate(
 
         current = Vec::new();
         for (target_type, targets) in groups {
-            let next =
-                resolve_revinclude_once(pool, &target_type, &targets, revinclude, registry).await?;
+            if included.len() >= limit {
+                break;
+            }
+            let next = resolve_revinclude_once(
+                pool,
+                &target_type,
+                &targets,
+                revinclude,
+                registry,
+                limit - included.len(),
+            )
+            .await?;
             for entry in next {
                 let key = (entry.resource_type.clone(), entry.id.clone());
                 if visited.insert(key) {
@@ -1281,6 +1722,7 @@ async fn resolve_revinclude_once(
     target_results: &[StoredResource],
     revinclude: &octofhir_search::RevIncludeSpec,
     registry: &SearchParameterRegistry,
+    limit: usize,
 ) -> Result<Vec<StoredResource>, StorageError> {
     if revinclude
         .target_type
@@ -1296,7 +1738,7 @@ async fn resolve_revinclude_once(
     }
 
     let source_type = &revinclude.source_type;
-    let table = SchemaManager::table_name(source_type);
+    let table = SchemaManager::qualified_table_name(source_type);
     let param_name = &revinclude.param_name;
 
     let Some(ref_array) = reference_array_sql(registry, source_type, param_name, "s.resource")
@@ -1307,19 +1749,22 @@ async fn resolve_revinclude_once(
     // Find source resources whose reference (in place) points at any target id.
     let sql = format!(
         r#"SELECT DISTINCT s.resource, s.id, s.txid, s.created_at, s.updated_at
-           FROM "{table}" s
+           FROM {table} s
            WHERE s.status != 'deleted'
            AND EXISTS (
              SELECT 1 FROM jsonb_array_elements({ref_array}) AS ref
              CROSS JOIN LATERAL (SELECT regexp_match(ref->>'reference', '{REFERENCE_TYPE_ID_RE}') AS m) x
              WHERE x.m[1] = $1 AND x.m[2] = ANY($2::text[])
-           )"#
+           )
+           LIMIT $3"#
     );
 
+    charge_query()?;
     let rows: Vec<(Value, String, i64, DateTime<Utc>, DateTime<Utc>)> =
         query_as(AssertSqlSafe(sql.to_string()))
             .bind(target_type)
             .bind(&target_ids)
+            .bind(limit as i64)
             .fetch_all(pool)
             .await
             .map_err(|e| {
@@ -1353,7 +1798,10 @@ async fn resolve_revinclude_once(
 
 /// Resolve _include and _revinclude specifications for raw search results.
 ///
-/// Uses `resource::text` to avoid JSONB -> Value -> String round-trip.
+/// Uses `resource::text` to avoid JSONB -> Value -> String round-trip. At most
+/// `max_included` distinct resources are returned; the flag reports whether
+/// more matched. Every include query is limited as well, so `_include=*` over
+/// a large page never loads the full reference graph.
 async fn resolve_includes_revincludes_raw(
     pool: &PgPool,
     main_resource_type: &str,
@@ -1361,18 +1809,28 @@ async fn resolve_includes_revincludes_raw(
     includes: &[octofhir_search::IncludeSpec],
     revincludes: &[octofhir_search::RevIncludeSpec],
     registry: &SearchParameterRegistry,
-) -> Result<Vec<RawStoredResource>, StorageError> {
+    max_included: usize,
+) -> Result<(Vec<RawStoredResource>, bool), StorageError> {
     use futures_util::future::try_join_all;
 
+    // One extra row per query is enough to tell that the cap was exceeded.
+    let limit = max_included.saturating_add(1);
     let include_futures: Vec<_> = includes
         .iter()
-        .map(|include| resolve_include_raw(pool, main_results, include, registry))
+        .map(|include| resolve_include_raw(pool, main_results, include, registry, limit))
         .collect();
 
     let revinclude_futures: Vec<_> = revincludes
         .iter()
         .map(|revinclude| {
-            resolve_revinclude_raw(pool, main_resource_type, main_results, revinclude, registry)
+            resolve_revinclude_raw(
+                pool,
+                main_resource_type,
+                main_results,
+                revinclude,
+                registry,
+                limit,
+            )
         })
         .collect();
 
@@ -1381,10 +1839,51 @@ async fn resolve_includes_revincludes_raw(
         try_join_all(revinclude_futures)
     )?;
 
-    let mut included: Vec<RawStoredResource> = include_results.into_iter().flatten().collect();
-    included.extend(revinclude_results.into_iter().flatten());
+    let candidates = include_results
+        .into_iter()
+        .flatten()
+        .chain(revinclude_results.into_iter().flatten());
+    Ok(cap_included(main_results, candidates, max_included))
+}
 
-    Ok(included)
+/// Resources `_include`/`_revinclude` results are de-duplicated by.
+trait IncludedResource {
+    fn key(&self) -> (String, String);
+}
+
+impl IncludedResource for StoredResource {
+    fn key(&self) -> (String, String) {
+        (self.resource_type.clone(), self.id.clone())
+    }
+}
+
+impl IncludedResource for RawStoredResource {
+    fn key(&self) -> (String, String) {
+        (self.resource_type.clone(), self.id.clone())
+    }
+}
+
+/// Drop duplicates (and resources already among the matches) and types
+/// outside the allowlist, keeping at most `max_included`. Returns whether
+/// anything was cut.
+fn cap_included<T: IncludedResource>(
+    main_results: &[T],
+    candidates: impl Iterator<Item = T>,
+    max_included: usize,
+) -> (Vec<T>, bool) {
+    let mut seen: HashSet<(String, String)> = main_results.iter().map(T::key).collect();
+    let mut included = Vec::new();
+    for entry in candidates {
+        let key = entry.key();
+        if !is_resource_type_allowed(&key.0) || !seen.insert(key) {
+            continue;
+        }
+        if included.len() == max_included {
+            return (included, true);
+        }
+        included.push(entry);
+    }
+    (included, false)
 }
 
 /// Resolve a single _include specification using raw JSON, matching references
@@ -1394,12 +1893,13 @@ async fn resolve_include_raw(
     main_results: &[RawStoredResource],
     include: &octofhir_search::IncludeSpec,
     registry: &SearchParameterRegistry,
+    limit: usize,
 ) -> Result<Vec<RawStoredResource>, StorageError> {
     if include.iterate {
-        return resolve_include_iterate_raw(pool, main_results, include, registry).await;
+        return resolve_include_iterate_raw(pool, main_results, include, registry, limit).await;
     }
 
-    resolve_include_once_raw(pool, main_results, include, registry).await
+    resolve_include_once_raw(pool, main_results, include, registry, limit).await
 }
 
 async fn resolve_include_iterate_raw(
@@ -1407,6 +1907,7 @@ async fn resolve_include_iterate_raw(
     main_results: &[RawStoredResource],
     include: &octofhir_search::IncludeSpec,
     registry: &SearchParameterRegistry,
+    limit: usize,
 ) -> Result<Vec<RawStoredResource>, StorageError> {
     let mut visited: HashSet<(String, String)> = main_results
         .iter()
@@ -1420,11 +1921,13 @@ async fn resolve_include_iterate_raw(
     let mut included = Vec::new();
 
     for _ in 0..INCLUDE_ITERATE_MAX_DEPTH {
-        if current.is_empty() {
+        if current.is_empty() || included.len() >= limit {
             break;
         }
 
-        let next = resolve_include_once_raw(pool, &current, include, registry).await?;
+        let next =
+            resolve_include_once_raw(pool, &current, include, registry, limit - included.len())
+                .await?;
         current = Vec::new();
         for entry in next {
             let key = (entry.resource_type.clone(), entry.id.clone());
@@ -1445,6 +1948,7 @@ async fn resolve_include_once_raw(
     main_results: &[RawStoredResource],
     include: &octofhir_search::IncludeSpec,
     registry: &SearchParameterRegistry,
+    limit: usize,
 ) -> Result<Vec<RawStoredResource>, StorageError> {
     if main_results.is_empty() {
         return Ok(Vec::new());
@@ -1474,6 +1978,9 @@ async fn resolve_include_once_raw(
 
     let mut entries = Vec::new();
     for target_type in target_types {
+        if entries.len() >= limit {
+            break;
+        }
         let mut matched = query_include_for_target_raw(
             pool,
             source_type,
@@ -1481,6 +1988,7 @@ async fn resolve_include_once_raw(
             &source_ids,
             &target_type,
             registry,
+            limit - entries.len(),
         )
         .await?;
         entries.append(&mut matched);
@@ -1496,27 +2004,31 @@ async fn query_include_for_target_raw(
     source_ids: &[&str],
     target_type: &str,
     registry: &SearchParameterRegistry,
+    limit: usize,
 ) -> Result<Vec<RawStoredResource>, StorageError> {
     let Some(ref_array) = reference_array_sql(registry, source_type, param_name, "s.resource")
     else {
         return Ok(Vec::new());
     };
-    let source_table = SchemaManager::table_name(source_type);
-    let table = SchemaManager::table_name(target_type);
+    let source_table = SchemaManager::qualified_table_name(source_type);
+    let table = SchemaManager::qualified_table_name(target_type);
 
     let sql = format!(
         r#"SELECT DISTINCT t.resource::text, t.id, t.txid, t.created_at, t.updated_at
-           FROM "{source_table}" s
+           FROM {source_table} s
            CROSS JOIN LATERAL jsonb_array_elements({ref_array}) AS ref
            CROSS JOIN LATERAL (SELECT regexp_match(ref->>'reference', '{REFERENCE_TYPE_ID_RE}') AS m) x
-           JOIN "{table}" t ON t.id = x.m[2] AND t.status != 'deleted'
-           WHERE s.id = ANY($1::text[]) AND s.status != 'deleted' AND x.m[1] = $2"#
+           JOIN {table} t ON t.id = x.m[2] AND t.status != 'deleted'
+           WHERE s.id = ANY($1::text[]) AND s.status != 'deleted' AND x.m[1] = $2
+           LIMIT $3"#
     );
 
+    charge_query()?;
     let rows: Vec<(String, String, i64, DateTime<Utc>, DateTime<Utc>)> =
         query_as(AssertSqlSafe(sql.to_string()))
             .bind(source_ids)
             .bind(target_type)
+            .bind(limit as i64)
             .fetch_all(pool)
             .await
             .map_err(|e| {
@@ -1557,16 +2069,26 @@ async fn resolve_revinclude_raw(
     main_results: &[RawStoredResource],
     revinclude: &octofhir_search::RevIncludeSpec,
     registry: &SearchParameterRegistry,
+    limit: usize,
 ) -> Result<Vec<RawStoredResource>, StorageError> {
     if revinclude.iterate {
-        return resolve_revinclude_iterate_raw(pool, main_results, revinclude, registry).await;
+        return resolve_revinclude_iterate_raw(pool, main_results, revinclude, registry, limit)
+            .await;
     }
 
     if main_results.is_empty() {
         return Ok(Vec::new());
     }
 
-    resolve_revinclude_once_raw(pool, main_resource_type, main_results, revinclude, registry).await
+    resolve_revinclude_once_raw(
+        pool,
+        main_resource_type,
+        main_results,
+        revinclude,
+        registry,
+        limit,
+    )
+    .await
 }
 
 async fn resolve_revinclude_iterate_raw(
@@ -1574,6 +2096,7 @@ async fn resolve_revinclude_iterate_raw(
     main_results: &[RawStoredResource],
     revinclude: &octofhir_search::RevIncludeSpec,
     registry: &SearchParameterRegistry,
+    limit: usize,
 ) -> Result<Vec<RawStoredResource>, StorageError> {
     let mut visited: HashSet<(String, String)> = main_results
         .iter()
@@ -1583,7 +2106,7 @@ async fn resolve_revinclude_iterate_raw(
     let mut included = Vec::new();
 
     for _ in 0..INCLUDE_ITERATE_MAX_DEPTH {
-        if current.is_empty() {
+        if current.is_empty() || included.len() >= limit {
             break;
         }
 
@@ -1597,9 +2120,18 @@ async fn resolve_revinclude_iterate_raw(
 
         current = Vec::new();
         for (target_type, targets) in groups {
-            let next =
-                resolve_revinclude_once_raw(pool, &target_type, &targets, revinclude, registry)
-                    .await?;
+            if included.len() >= limit {
+                break;
+            }
+            let next = resolve_revinclude_once_raw(
+                pool,
+                &target_type,
+                &targets,
+                revinclude,
+                registry,
+                limit - included.len(),
+            )
+            .await?;
             for entry in next {
                 let key = (entry.resource_type.clone(), entry.id.clone());
                 if visited.insert(key) {
@@ -1619,6 +2151,7 @@ async fn resolve_revinclude_once_raw(
     target_results: &[RawStoredResource],
     revinclude: &octofhir_search::RevIncludeSpec,
     registry: &SearchParameterRegistry,
+    limit: usize,
 ) -> Result<Vec<RawStoredResource>, StorageError> {
     if revinclude
         .target_type
@@ -1634,7 +2167,7 @@ async fn resolve_revinclude_once_raw(
     }
 
     let source_type = &revinclude.source_type;
-    let table = SchemaManager::table_name(source_type);
+    let table = SchemaManager::qualified_table_name(source_type);
     let param_name = &revinclude.param_name;
 
     let Some(ref_array) = reference_array_sql(registry, source_type, param_name, "s.resource")
@@ -1645,19 +2178,22 @@ async fn resolve_revinclude_once_raw(
     // Find source resources whose reference (in place) points at any target id.
     let sql = format!(
         r#"SELECT DISTINCT s.resource::text, s.id, s.txid, s.created_at, s.updated_at
-           FROM "{table}" s
+           FROM {table} s
            WHERE s.status != 'deleted'
            AND EXISTS (
              SELECT 1 FROM jsonb_array_elements({ref_array}) AS ref
              CROSS JOIN LATERAL (SELECT regexp_match(ref->>'reference', '{REFERENCE_TYPE_ID_RE}') AS m) x
              WHERE x.m[1] = $1 AND x.m[2] = ANY($2::text[])
-           )"#
+           )
+           LIMIT $3"#
     );
 
+    charge_query()?;
     let rows: Vec<(String, String, i64, DateTime<Utc>, DateTime<Utc>)> =
         query_as(AssertSqlSafe(sql.to_string()))
             .bind(target_type)
             .bind(&target_ids)
+            .bind(limit as i64)
             .fetch_all(pool)
             .await
             .map_err(|e| {
@@ -1827,7 +2363,7 @@ impl<'q> BindAllParamsRaw<'q>
     for sqlx_core::query_as::QueryAs<
         'q,
         sqlx_postgres::Postgres,
-        (String, String, i64, DateTime<Utc>, DateTime<Utc>),
+        RawSearchRow,
         sqlx_postgres::PgArguments,
     >
 {
@@ -1847,10 +2383,46 @@ impl<'q> BindAllParamsRaw<'q>
     }
 }
 
+/// Comma-separated value count of each occurrence of one parameter, e.g.
+/// `2.1` for `code=a,b&code=c`. Escaped commas (`\,`) do not separate values.
+fn or_group_tag(occurrences: &[String]) -> String {
+    occurrences
+        .iter()
+        .map(|occurrence| {
+            SearchParameterParser::split_or_values(occurrence)
+                .iter()
+                .filter(|value| !value.trim().is_empty())
+                .count()
+                .to_string()
+        })
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_estimate_explain_sql_replaces_count() {
+        let sql = estimate_explain_sql(
+            "SELECT COUNT(*) as total FROM \"public\".\"patient\" r WHERE r.status != 'deleted'",
+        )
+        .unwrap();
+        assert_eq!(
+            sql,
+            "EXPLAIN (FORMAT JSON) SELECT 1 FROM \"public\".\"patient\" r WHERE r.status != 'deleted'"
+        );
+        assert!(estimate_explain_sql("SELECT r.id FROM patient r").is_none());
+    }
+
+    #[test]
+    fn test_plan_row_estimate_reads_top_plan_rows() {
+        let plan = serde_json::json!([{"Plan": {"Node Type": "Seq Scan", "Plan Rows": 1234.0}}]);
+        assert_eq!(plan_row_estimate(&plan), Some(1234));
+        assert_eq!(plan_row_estimate(&serde_json::json!([])), None);
+    }
+
     #[test]
     fn test_chrono_to_time_conversion() {
         let chrono_dt = Utc::now();
@@ -1862,6 +2434,22 @@ mod tests {
         assert!((chrono_ts - time_ts).abs() <= 1);
     }
 
+    #[test]
+    fn test_or_group_tag_separates_value_grouping() {
+        let tag = |occurrences: &[&str]| {
+            or_group_tag(
+                &occurrences
+                    .iter()
+                    .map(|o| o.to_string())
+                    .collect::<Vec<_>>(),
+            )
+        };
+        assert_eq!(tag(&["a,b", "c"]), "2.1");
+        assert_ne!(tag(&["a,b", "c"]), tag(&["a", "b,c"]));
+        assert_eq!(tag(&["a,,b"]), "2");
+        assert_eq!(tag(&["a\\,b", "c"]), "1.1");
+    }
+
     #[test]
     fn test_redact_sql_shape_removes_bind_indices_and_normalizes_space() {
         let sql = "SELECT *\nFROM patient r WHERE r.id = $1 AND r.updated_at >= $23";
@@ -1885,4 +2473,100 @@ mod tests {
             "EXPLAIN (ANALYZE, BUFFERS, FORMAT JSON) SELECT * FROM patient WHERE id = $1"
         );
     }
+
+    fn raw(resource_type: &str, id: &str) -> RawStoredResource {
+        RawStoredResource {
+            id: id.to_string(),
+            version_id: "1".to_string(),
+            resource_type: resource_type.to_string(),
+            resource_json: "{}".to_string(),
+            last_updated: OffsetDateTime::UNIX_EPOCH,
+            created_at: OffsetDateTime::UNIX_EPOCH,
+        }
+    }
+
+    #[test]
+    fn test_merge_sync_page_interleaves_deletes_by_key() {
+        let at = |secs| OffsetDateTime::UNIX_EPOCH + time::Duration::seconds(secs);
+        let live = |id, secs| RawStoredResource {
+            last_updated: at(secs),
+            ..raw("Patient", id)
+        };
+        let gone = |id: &str, secs| SyncDeletion {
+            id: id.to_string(),
+            version_id: "9".to_string(),
+            deleted_at: at(secs),
+        };
+
+        let (entries, deleted, truncated) = merge_sync_page(
+            vec![live("a", 1), live("c", 3), live("e", 5)],
+            vec![gone("b", 2), gone("d", 4)],
+            3,
+        );
+        let ids: Vec<_> = entries.iter().map(|e| e.id.as_str()).collect();
+        assert_eq!(ids, ["a", "c"]);
+        assert_eq!(deleted.len(), 1);
+        assert_eq!(deleted[0].id, "b");
+        assert!(truncated);
+
+        let (entries, deleted, truncated) =
+            merge_sync_page(vec![live("a", 1)], vec![gone("b", 1)], 3);
+        assert_eq!(entries.len() + deleted.len(), 2);
+        assert!(!truncated);
+    }
+
+    #[test]
+    fn test_cap_included_dedups_and_truncates() {
+        let main = vec![raw("Patient", "p1")];
+        let candidates = vec![
+            raw("Patient", "p1"),
+            raw("Practitioner", "a"),
+            raw("Practitioner", "a"),
+            raw("Organization", "o"),
+            raw("Practitioner", "b"),
+        ];
+
+        let (included, truncated) = cap_included(&main, candidates.clone().into_iter(), 2);
+        let ids: Vec<&str> = included.iter().map(|r| r.id.as_str()).collect();
+        assert_eq!(ids, vec!["a", "o"]);
+        assert!(truncated);
+
+        let (included, truncated) = cap_included(&main, candidates.into_iter(), 3);
+        assert_eq!(included.len(), 3);
+        assert!(!truncated);
+    }
+
+    #[test]
+    fn test_plan_joins_counts_chain_links_and_has_levels() {
+        let params = SearchParams::new()
+            .with_param("name", "smith")
+            .with_param("subject:Patient.organization.name", "acme")
+            .with_param("_has:Observation:patient:_has:AuditEvent:entity:agent", "x");
+        assert_eq!(plan_joins(&params), 4);
+        assert_eq!(plan_joins(&SearchParams::new()), 0);
+    }
+
+    #[test]
+    fn test_cap_included_applies_to_stored_resources() {
+        let stored = |resource_type: &str, id: &str| StoredResource {
+            id: id.to_string(),
+            version_id: "1".to_string(),
+            resource_type: resource_type.to_string(),
+            resource: serde_json::json!({}),
+            last_updated: OffsetDateTime::UNIX_EPOCH,
+            created_at: OffsetDateTime::UNIX_EPOCH,
+        };
+        let main = vec![stored("Patient", "p1")];
+        let candidates = vec![
+            stored("Practitioner", "a"),
+            stored("Patient", "p1"),
+            stored("Practitioner", "a"),
+            stored("Organization", "o"),
+        ];
+
+        let (included, truncated) = cap_included(&main, candidates.into_iter(), 1);
+        let ids: Vec<&str> = included.iter().map(|r| r.id.as_str()).collect();
+        assert_eq!(ids, vec!["a"]);
+        assert!(truncated);
+    }
 }
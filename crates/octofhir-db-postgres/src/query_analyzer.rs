@@ -94,7 +94,7 @@ impl AnalyzerConfig {
 }
 
 /// Result of analyzing a query's execution plan.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct QueryAnalysis {
     /// The original SQL query
     pub sql: String,
@@ -119,7 +119,7 @@ pub struct QueryAnalysis {
 }
 
 /// Information about an index used in a query.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct IndexUsage {
     /// Name of the index
     pub index_name: String,
@@ -134,7 +134,7 @@ pub struct IndexUsage {
 }
 
 /// Information about a sequential scan.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct SeqScanInfo {
     /// Table being scanned
     pub table_name: String,
@@ -174,7 +174,7 @@ pub enum SuggestionImpact {
 }
 
 /// Buffer usage statistics from query execution.
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct BufferStats {
     /// Shared buffer hits
     pub shared_hit: u64,
@@ -195,36 +195,37 @@ pub struct BufferStats {
 #[serde(rename_all = "PascalCase")]
 #[allow(dead_code)] // Fields are used in JSON deserialization
 struct QueryPlanNode {
+    #[serde(rename = "Node Type")]
     node_type: String,
-    #[serde(default)]
+    #[serde(default, rename = "Relation Name")]
     relation_name: Option<String>,
-    #[serde(default)]
+    #[serde(default, rename = "Index Name")]
     index_name: Option<String>,
     #[serde(default)]
     filter: Option<String>,
-    #[serde(default)]
+    #[serde(default, rename = "Index Cond")]
     index_cond: Option<String>,
-    #[serde(default)]
+    #[serde(default, rename = "Startup Cost")]
     startup_cost: f64,
-    #[serde(default)]
+    #[serde(default, rename = "Total Cost")]
     total_cost: f64,
-    #[serde(default)]
+    #[serde(default, rename = "Plan Rows")]
     plan_rows: u64,
-    #[serde(default)]
+    #[serde(default, rename = "Actual Rows")]
     actual_rows: Option<u64>,
-    #[serde(default)]
+    #[serde(default, rename = "Actual Total Time")]
     actual_total_time: Option<f64>,
-    #[serde(default)]
+    #[serde(default, rename = "Shared Hit Blocks")]
     shared_hit_blocks: Option<u64>,
-    #[serde(default)]
+    #[serde(default, rename = "Shared Read Blocks")]
     shared_read_blocks: Option<u64>,
-    #[serde(default)]
+    #[serde(default, rename = "Local Hit Blocks")]
     local_hit_blocks: Option<u64>,
-    #[serde(default)]
+    #[serde(default, rename = "Local Read Blocks")]
     local_read_blocks: Option<u64>,
-    #[serde(default)]
+    #[serde(default, rename = "Temp Read Blocks")]
     temp_read_blocks: Option<u64>,
-    #[serde(default)]
+    #[serde(default, rename = "Temp Written Blocks")]
     temp_written_blocks: Option<u64>,
     #[serde(default)]
     plans: Vec<QueryPlanNode>,
@@ -235,9 +236,9 @@ struct QueryPlanNode {
 #[serde(rename_all = "PascalCase")]
 struct ExplainOutput {
     plan: QueryPlanNode,
-    #[serde(default)]
+    #[serde(default, rename = "Planning Time")]
     planning_time: f64,
-    #[serde(default)]
+    #[serde(default, rename = "Execution Time")]
     execution_time: f64,
 }
 
@@ -385,6 +386,20 @@ impl QueryAnalyzer {
         Ok(analysis)
     }
 
+    /// Summarize EXPLAIN output that was already obtained elsewhere.
+    ///
+    /// Accepts the `FORMAT JSON` result of an `EXPLAIN` the caller ran itself
+    /// (e.g. a search with bound parameters, which [`Self::analyze_query`]
+    /// cannot execute). Does not update analyzer statistics.
+    pub fn summarize_explain(
+        &self,
+        sql: &str,
+        explain: &serde_json::Value,
+    ) -> Result<QueryAnalysis, AnalyzerError> {
+        let explain_output = self.parse_explain_output(explain)?;
+        Ok(self.build_analysis(sql, &explain_output))
+    }
+
     /// Get index suggestions for a table based on common FHIR query patterns.
     pub fn suggest_fhir_indexes(&self, table_name: &str) -> Vec<IndexSuggestion> {
         let table_lower = table_name.to_lowercase();
@@ -812,4 +827,33 @@ mod tests {
         assert!(json.contains("patient"));
         assert!(json.contains("High"));
     }
+
+    #[test]
+    fn test_summarize_explain_detects_seq_scan() {
+        let analyzer = QueryAnalyzer::default_analyzer();
+        let explain = serde_json::json!([{
+            "Plan": {
+                "Node Type": "Limit",
+                "Plan Rows": 11,
+                "Plans": [{
+                    "Node Type": "Seq Scan",
+                    "Relation Name": "patient",
+                    "Filter": "(status = 'active'::text)",
+                    "Plan Rows": 50000,
+                    "Actual Rows": 11
+                }]
+            },
+            "Planning Time": 0.2,
+            "Execution Time": 12.5
+        }]);
+
+        let analysis = analyzer.summarize_explain("SELECT ...", &explain).unwrap();
+
+        assert_eq!(analysis.sequential_scans.len(), 1);
+        assert_eq!(analysis.sequential_scans[0].table_name, "patient");
+        assert!(analysis.sequential_scans[0].is_problematic);
+        assert_eq!(analysis.index_suggestions.len(), 1);
+        assert_eq!(analysis.execution_time_ms, 12.5);
+        assert_eq!(analyzer.stats().queries_analyzed, 0);
+    }
 }
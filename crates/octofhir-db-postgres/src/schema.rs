@@ -3,10 +3,21 @@
 //! This module handles database schema operations such as table creation,
 //! index management, and schema introspection. It uses a table-per-resource
 //! pattern where each FHIR resource type gets its own table.
+//!
+//! The per-type tables already give what partitioning by resource type would:
+//! a query names exactly one type's table and never scans another's, and a
+//! new type's table is created on demand by
+//! [`SchemaManager::create_resource_schema`]. Range partitioning by
+//! `updated_at` is not used: PostgreSQL requires the partition key in every
+//! unique constraint, which would break the `id` primary key and the
+//! `(id, txid)` conflict target the history trigger writes with.
 
+use serde::{Deserialize, Serialize};
 use sqlx_core::sql_str::AssertSqlSafe;
-use sqlx_postgres::PgPool;
-use tracing::{debug, instrument};
+use sqlx_postgres::{PgConnection, PgPool};
+use tracing::{debug, info, instrument};
+
+use octofhir_storage::TenantId;
 
 use crate::error::{PostgresError, Result};
 
@@ -20,14 +31,14 @@ const ARCHIVE_FN_SQL: &str = r#"
     RETURNS TRIGGER AS $$
     BEGIN
         EXECUTE format(
-            'INSERT INTO %I_history (id, txid, created_at, updated_at, resource, status)
+            'INSERT INTO %I.%I (id, txid, created_at, updated_at, resource, status)
              VALUES ($1, $2, $3, $4, $5, $6)
              ON CONFLICT (id, txid) DO UPDATE SET
                  created_at = EXCLUDED.created_at,
                  updated_at = EXCLUDED.updated_at,
                  resource = EXCLUDED.resource,
                  status = EXCLUDED.status',
-            TG_TABLE_NAME
+            TG_TABLE_SCHEMA, TG_TABLE_NAME || '_history'
         ) USING OLD.id, OLD.txid, OLD.created_at, OLD.updated_at, OLD.resource, OLD.status;
         -- NEW on UPDATE (apply it), OLD on DELETE (proceed with removal).
         -- Returning NEW unconditionally would be NULL on DELETE and silently
@@ -37,6 +48,68 @@ const ARCHIVE_FN_SQL: &str = r#"
     $$ LANGUAGE plpgsql;
 "#;
 
+/// Catalog filter (on `pg_namespace n`) for the schemas holding resource
+/// tables: `public` and every tenant schema. Schema upgrades run at startup,
+/// outside any tenant scope, so they cover all of them.
+const RESOURCE_SCHEMAS: &str = r"(n.nspname = 'public' OR n.nspname LIKE 'tenant\_%')";
+
+/// Generated `tsvector` over the resource narrative (`text.div` with markup
+/// stripped), searched by `_text`. Must match the column name the search
+/// builder emits.
+pub const TEXT_TSV_COLUMN: &str = "text_tsv";
+
+/// Generated `tsvector` over every string value of the resource, searched by
+/// `_content`. Must match the column name the search builder emits.
+pub const CONTENT_TSV_COLUMN: &str = "content_tsv";
+
+/// TOAST compression method for the `resource` JSONB column.
+///
+/// Resources larger than ~2 KB are compressed and moved out of line (TOASTed).
+/// The method trades CPU for size:
+///
+/// - `pglz` (PostgreSQL's built-in default) compresses slightly better but is
+///   markedly slower to decompress, which dominates read latency for large
+///   resources (Bundles, big Observations, QuestionnaireResponses).
+/// - `lz4` decompresses several times faster at a small cost in ratio, and is
+///   usually the better choice for read-heavy workloads. Requires PostgreSQL 14+
+///   built with `--with-lz4` (the default for official packages and images).
+///
+/// Changing the method only affects values written afterwards; existing rows
+/// keep their old compression until rewritten (e.g. by `VACUUM FULL` or an
+/// update of the row).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JsonbCompression {
+    /// Leave the column unset and use the server's `default_toast_compression`.
+    #[default]
+    Default,
+    /// PostgreSQL's built-in LZ-family compression.
+    Pglz,
+    /// LZ4 compression (PostgreSQL 14+).
+    Lz4,
+}
+
+impl JsonbCompression {
+    /// Value for `ALTER COLUMN ... SET COMPRESSION`.
+    #[must_use]
+    pub fn as_sql(self) -> &'static str {
+        match self {
+            Self::Default => "default",
+            Self::Pglz => "pglz",
+            Self::Lz4 => "lz4",
+        }
+    }
+
+    /// Value of `pg_attribute.attcompression` for a column using this method.
+    fn attcompression(self) -> &'static str {
+        match self {
+            Self::Default => "",
+            Self::Pglz => "p",
+            Self::Lz4 => "l",
+        }
+    }
+}
+
 /// Manages the database schema for FHIR resources.
 ///
 /// The `SchemaManager` is responsible for:
@@ -51,16 +124,36 @@ const ARCHIVE_FN_SQL: &str = r#"
 /// - A history table (`patient_history`) for previous versions
 /// - GIN indexes for efficient JSONB queries
 /// - A trigger that archives old versions on UPDATE/DELETE
+/// - A trigger that records changes in the event outbox (a no-op unless the
+///   outbox is enabled, see [`crate::event_outbox`])
 #[derive(Debug, Clone)]
 pub struct SchemaManager {
     pool: PgPool,
+    compression: JsonbCompression,
 }
 
 impl SchemaManager {
     /// Creates a new `SchemaManager` with the given connection pool.
     #[must_use]
     pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+        Self {
+            pool,
+            compression: JsonbCompression::Default,
+        }
+    }
+
+    /// Sets the TOAST compression method applied to the `resource` column of
+    /// newly created resource and history tables.
+    #[must_use]
+    pub fn with_compression(mut self, compression: JsonbCompression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Returns the configured `resource` column compression method.
+    #[must_use]
+    pub fn compression(&self) -> JsonbCompression {
+        self.compression
     }
 
     /// Returns a reference to the connection pool.
@@ -78,6 +171,48 @@ impl SchemaManager {
         resource_type.to_lowercase()
     }
 
+    /// Schema holding the resource tables of the current tenant: the tenant's
+    /// own schema inside a [`octofhir_storage::with_tenant`] scope, `public`
+    /// otherwise.
+    ///
+    /// Resource tables are addressed through this schema rather than the
+    /// `search_path`, so a table missing from a tenant schema fails the
+    /// statement instead of resolving to the shared one in `public`.
+    #[must_use]
+    pub fn resource_schema() -> String {
+        octofhir_storage::current_tenant()
+            .map_or_else(|| "public".to_string(), |tenant| tenant.schema_name())
+    }
+
+    /// Quoted, schema-qualified name of the table for `resource_type` in
+    /// [`Self::resource_schema`].
+    #[must_use]
+    pub fn qualified_table_name(resource_type: &str) -> String {
+        format!(
+            "\"{}\".\"{}\"",
+            Self::resource_schema(),
+            Self::table_name(resource_type)
+        )
+    }
+
+    /// True if `resource_type` keeps a history table. Internal resources
+    /// (users, clients, sessions, ...) are not versioned.
+    #[must_use]
+    pub fn has_history(resource_type: &str) -> bool {
+        !Self::is_internal_resource(&Self::table_name(resource_type))
+    }
+
+    /// Quoted, schema-qualified name of the history table for
+    /// `resource_type` in [`Self::resource_schema`].
+    #[must_use]
+    pub fn qualified_history_table_name(resource_type: &str) -> String {
+        format!(
+            "\"{}\".\"{}_history\"",
+            Self::resource_schema(),
+            Self::table_name(resource_type)
+        )
+    }
+
     /// Creates the full schema for a resource type (idempotent).
     ///
     /// Builds a single multi-statement DDL block and runs it via `raw_sql` so
@@ -93,7 +228,7 @@ impl SchemaManager {
     /// Returns an error if any DDL statement fails.
     #[instrument(skip(self), fields(resource_type = %resource_type))]
     pub async fn create_resource_schema(&self, resource_type: &str) -> Result<()> {
-        let sql = Self::build_resource_schema_sql(resource_type);
+        let sql = Self::build_resource_schema_sql(resource_type, self.compression);
         sqlx_core::raw_sql::raw_sql(AssertSqlSafe(sql.to_string()))
             .execute(&self.pool)
             .await
@@ -102,6 +237,27 @@ impl SchemaManager {
         Ok(())
     }
 
+    /// Creates the full schema for a resource type on `conn`, inside the
+    /// transaction open there, so the DDL commits or rolls back with it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any DDL statement fails.
+    #[instrument(skip(self, conn), fields(resource_type = %resource_type))]
+    pub async fn create_resource_schema_in(
+        &self,
+        conn: &mut PgConnection,
+        resource_type: &str,
+    ) -> Result<()> {
+        let sql = Self::build_resource_schema_sql(resource_type, self.compression);
+        sqlx_core::raw_sql::raw_sql(AssertSqlSafe(sql))
+            .execute(conn)
+            .await
+            .map_err(PostgresError::from)?;
+        debug!("Ensured schema for resource type: {}", resource_type);
+        Ok(())
+    }
+
     /// Build the multi-statement DDL string for one resource type.
     ///
     /// Combines table, triggers, indexes, partitions into one semicolon-
@@ -111,7 +267,7 @@ impl SchemaManager {
     /// archive_to_history()` from many concurrent connections trips
     /// "tuple concurrently updated" on `pg_proc`, dropping schema
     /// creates on the floor.
-    fn build_resource_schema_sql(resource_type: &str) -> String {
+    fn build_resource_schema_sql(resource_type: &str, compression: JsonbCompression) -> String {
         let table = Self::table_name(resource_type);
         let history_table = format!("{}_history", table);
         let is_internal = Self::is_internal_resource(&table);
@@ -120,6 +276,14 @@ impl SchemaManager {
 
         let mut sql = String::with_capacity(2048);
 
+        // Serialize concurrent creates of the same table: `IF NOT EXISTS`
+        // alone races on the catalog ("duplicate key value violates unique
+        // constraint pg_type_typname_nsp_index"). The batch runs as one
+        // transaction, so the lock is held until all of it is done.
+        sql.push_str(&format!(
+            "SELECT pg_advisory_xact_lock(hashtext('octofhir_resource_schema:' || current_schema() || '.{table}'));\n"
+        ));
+
         // Main table
         sql.push_str(&format!(
             "CREATE TABLE IF NOT EXISTS \"{table}\" (\n\
@@ -161,6 +325,24 @@ impl SchemaManager {
             ));
         }
 
+        sql.push_str(&Self::event_outbox_trigger_sql(&table));
+
+        // Resource column compression. A metadata-only change, so it is cheap
+        // to repeat on an existing table.
+        if compression != JsonbCompression::Default {
+            let method = compression.as_sql();
+            sql.push_str(&format!(
+                "ALTER TABLE \"{table}\" ALTER COLUMN resource SET COMPRESSION {method};\n"
+            ));
+            if !is_internal {
+                sql.push_str(&format!(
+                    "ALTER TABLE \"{history_table}\" ALTER COLUMN resource SET COMPRESSION {method};\n"
+                ));
+            }
+        }
+
+        sql.push_str(&Self::full_text_sql(&table));
+
         // Indexes (main table)
         sql.push_str(&format!(
             "CREATE INDEX IF NOT EXISTS \"idx_{table}_gin\" ON \"{table}\" \
@@ -175,13 +357,16 @@ impl SchemaManager {
         // History indexes. History rows are append-only and inserted in
         // updated_at order, so a BRIN index on updated_at gives cheap range
         // scans (type/system history time filters, retention pruning) at a
-        // fraction of a btree's size and write cost.
+        // fraction of a btree's size and write cost. Delete tombstones are
+        // paged in key order by incremental sync (`_sync`).
         if !is_internal {
             sql.push_str(&format!(
                 "CREATE INDEX IF NOT EXISTS \"idx_{history_table}_updated_at_brin\" \
                     ON \"{history_table}\" USING BRIN (updated_at);\n\
                  CREATE INDEX IF NOT EXISTS \"idx_{history_table}_id\" \
-                    ON \"{history_table}\"(id);\n"
+                    ON \"{history_table}\"(id);\n\
+                 CREATE INDEX IF NOT EXISTS \"idx_{history_table}_deleted\" \
+                    ON \"{history_table}\"(updated_at, id) WHERE status = 'deleted';\n"
             ));
         }
 
@@ -208,6 +393,107 @@ impl SchemaManager {
         sql
     }
 
+    /// DDL (re)creating the trigger that records changes to `table` in the
+    /// event outbox.
+    fn event_outbox_trigger_sql(table: &str) -> String {
+        let trig = format!("{table}_event_outbox");
+        format!(
+            "DROP TRIGGER IF EXISTS \"{trig}\" ON \"{table}\";\n\
+             CREATE TRIGGER \"{trig}\" AFTER INSERT OR UPDATE OR DELETE ON \"{table}\" \
+             FOR EACH ROW EXECUTE FUNCTION enqueue_resource_event();\n"
+        )
+    }
+
+    /// DDL adding the full-text `tsvector` columns and their GIN indexes to a
+    /// main resource table (idempotent).
+    ///
+    /// The columns are `GENERATED ... STORED`, so PostgreSQL fills them on
+    /// every insert and update and adding them to an existing table backfills
+    /// all rows. Binary `data` is base64 and is left out of `_content`.
+    fn full_text_sql(table: &str) -> String {
+        let content_source = if table == "binary" {
+            "resource - 'data'"
+        } else {
+            "resource"
+        };
+        format!(
+            "ALTER TABLE \"{table}\" \
+                ADD COLUMN IF NOT EXISTS {TEXT_TSV_COLUMN} tsvector GENERATED ALWAYS AS \
+                    (to_tsvector('english', regexp_replace(coalesce(resource->'text'->>'div', ''), '<[^>]*>', ' ', 'g'))) STORED, \
+                ADD COLUMN IF NOT EXISTS {CONTENT_TSV_COLUMN} tsvector GENERATED ALWAYS AS \
+                    (jsonb_to_tsvector('english', {content_source}, '[\"string\"]')) STORED;\n\
+             CREATE INDEX IF NOT EXISTS \"idx_{table}_{TEXT_TSV_COLUMN}\" ON \"{table}\" \
+                USING GIN ({TEXT_TSV_COLUMN});\n\
+             CREATE INDEX IF NOT EXISTS \"idx_{table}_{CONTENT_TSV_COLUMN}\" ON \"{table}\" \
+                USING GIN ({CONTENT_TSV_COLUMN});\n"
+        )
+    }
+
+    /// Creates the schema of `tenant` and the versioned resource tables in it
+    /// (idempotent).
+    ///
+    /// Every table in `public` that has a `_history` companion is recreated in
+    /// the tenant schema, so with the tenant's `search_path` those tables
+    /// resolve to the tenant's copies. Internal, unversioned tables (users,
+    /// clients, policies, ...) are not copied and stay shared through
+    /// `public`. Search-parameter functional indexes are not copied either.
+    ///
+    /// Call after the resource tables in `public` have been created.
+    ///
+    /// Returns the number of resource tables ensured.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the schema or any table cannot be created.
+    #[instrument(skip(self), fields(tenant = %tenant))]
+    pub async fn provision_tenant_schema(&self, tenant: &TenantId) -> Result<usize> {
+        let schema = tenant.schema_name();
+        let tables: Vec<String> = sqlx_core::query_scalar::query_scalar(
+            "SELECT t.tablename::text
+             FROM pg_tables t
+             WHERE t.schemaname = 'public'
+               AND EXISTS (
+                   SELECT 1 FROM pg_tables h
+                   WHERE h.schemaname = 'public' AND h.tablename = t.tablename || '_history'
+               )
+             ORDER BY t.tablename",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(PostgresError::from)?;
+
+        // Pin the search_path for this transaction only, rather than relying
+        // on the pool's tenant hooks, so the DDL lands in the tenant schema
+        // even on a pool without tenant isolation.
+        let mut tx = self.pool.begin().await.map_err(PostgresError::from)?;
+        sqlx_core::query::query(AssertSqlSafe(format!(
+            "CREATE SCHEMA IF NOT EXISTS \"{schema}\""
+        )))
+        .execute(&mut *tx)
+        .await
+        .map_err(PostgresError::from)?;
+        sqlx_core::query::query("SELECT set_config('search_path', $1, true)")
+            .bind(format!("\"{schema}\", public"))
+            .execute(&mut *tx)
+            .await
+            .map_err(PostgresError::from)?;
+        for table in &tables {
+            let sql = Self::build_resource_schema_sql(table, self.compression);
+            sqlx_core::raw_sql::raw_sql(AssertSqlSafe(sql))
+                .execute(&mut *tx)
+                .await
+                .map_err(PostgresError::from)?;
+        }
+        tx.commit().await.map_err(PostgresError::from)?;
+
+        info!(
+            schema = %schema,
+            tables = tables.len(),
+            "Provisioned tenant schema"
+        );
+        Ok(tables.len())
+    }
+
     /// Ensure the shared `archive_to_history()` function exists. Call once
     /// before parallel resource-schema creation.
     pub async fn ensure_archive_function(pool: &PgPool) -> Result<()> {
@@ -218,6 +504,199 @@ impl SchemaManager {
         Ok(())
     }
 
+    /// Brings the `resource` column of every existing table in `public` and
+    /// the tenant schemas in line with the configured compression method.
+    ///
+    /// Only tables whose current setting differs are altered, so this is a
+    /// no-op on an already-migrated database. `SET COMPRESSION` does not
+    /// rewrite existing rows: they are recompressed as they are updated, or
+    /// all at once by `VACUUM FULL`. Does nothing when the compression is
+    /// [`JsonbCompression::Default`], so switching back never touches tables
+    /// implicitly.
+    ///
+    /// Returns the number of tables altered.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the catalog query or any `ALTER TABLE` fails (for
+    /// example, `lz4` on a server built without LZ4 support).
+    #[instrument(skip(self), fields(compression = ?self.compression))]
+    pub async fn apply_resource_compression(&self) -> Result<usize> {
+        if self.compression == JsonbCompression::Default {
+            return Ok(0);
+        }
+
+        let tables: Vec<(String, String)> = sqlx_core::query_as::query_as(AssertSqlSafe(format!(
+            "SELECT n.nspname::text, c.relname::text
+             FROM pg_attribute a
+             JOIN pg_class c ON c.oid = a.attrelid
+             JOIN pg_namespace n ON n.oid = c.relnamespace
+             WHERE {RESOURCE_SCHEMAS}
+               AND c.relkind = 'r'
+               AND a.attname = 'resource'
+               AND NOT a.attisdropped
+               AND a.attcompression::text IS DISTINCT FROM $1
+             ORDER BY n.nspname, c.relname"
+        )))
+        .bind(self.compression.attcompression())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(PostgresError::from)?;
+
+        let method = self.compression.as_sql();
+        for (schema, table) in &tables {
+            let sql = format!(
+                "ALTER TABLE \"{schema}\".\"{table}\" ALTER COLUMN resource SET COMPRESSION {method}"
+            );
+            sqlx_core::query::query(AssertSqlSafe(sql))
+                .execute(&self.pool)
+                .await
+                .map_err(PostgresError::from)?;
+        }
+
+        if !tables.is_empty() {
+            info!(
+                tables = tables.len(),
+                compression = method,
+                "Updated resource column compression on existing tables"
+            );
+        }
+
+        Ok(tables.len())
+    }
+
+    /// Lists the resource tables in `public` and the tenant schemas without
+    /// the `_text` / `_content` full-text columns, i.e. those
+    /// [`Self::ensure_full_text_columns`] would alter, as `(schema, table)`.
+    /// Only reads the catalog.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the catalog query fails.
+    pub async fn tables_without_full_text_columns(&self) -> Result<Vec<(String, String)>> {
+        let tables = sqlx_core::query_as::query_as(AssertSqlSafe(format!(
+            "SELECT n.nspname::text, c.relname::text
+             FROM pg_class c
+             JOIN pg_namespace n ON n.oid = c.relnamespace
+             WHERE {RESOURCE_SCHEMAS}
+               AND c.relkind = 'r'
+               AND c.relname NOT LIKE '%_history'
+               AND EXISTS (SELECT 1 FROM pg_attribute a
+                           WHERE a.attrelid = c.oid AND a.attname = 'resource' AND NOT a.attisdropped)
+               AND EXISTS (SELECT 1 FROM pg_attribute a
+                           WHERE a.attrelid = c.oid AND a.attname = 'txid' AND NOT a.attisdropped)
+               AND NOT EXISTS (SELECT 1 FROM pg_attribute a
+                               WHERE a.attrelid = c.oid AND a.attname = $1 AND NOT a.attisdropped)
+             ORDER BY n.nspname, c.relname"
+        )))
+        .bind(CONTENT_TSV_COLUMN)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(PostgresError::from)?;
+        Ok(tables)
+    }
+
+    /// Adds the `_text` / `_content` full-text columns and GIN indexes to every
+    /// existing resource table in `public` and the tenant schemas that lacks
+    /// them.
+    ///
+    /// Tables created by [`Self::create_resource_schema`] already have them;
+    /// this upgrades tables created before full-text columns existed. Adding a
+    /// stored generated column rewrites the table to backfill it, which takes
+    /// an exclusive lock for the duration, so storage only calls this on
+    /// startup with `upgrade_full_text_columns` set.
+    ///
+    /// Returns the number of tables altered.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the catalog query or any DDL statement fails.
+    #[instrument(skip(self))]
+    pub async fn ensure_full_text_columns(&self) -> Result<usize> {
+        let tables = self.tables_without_full_text_columns().await?;
+
+        for (schema, table) in &tables {
+            self.execute_in_schema(schema, &Self::full_text_sql(table))
+                .await?;
+            debug!(schema = %schema, table = %table, "Added full-text columns");
+        }
+
+        if !tables.is_empty() {
+            info!(
+                tables = tables.len(),
+                "Added full-text search columns to existing tables"
+            );
+        }
+
+        Ok(tables.len())
+    }
+
+    /// Adds the event outbox trigger to every existing resource table in
+    /// `public` and the tenant schemas that lacks it.
+    ///
+    /// Tables created by [`Self::create_resource_schema`] already have it;
+    /// this upgrades tables created before the outbox existed.
+    ///
+    /// Returns the number of tables altered.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the catalog query or any DDL statement fails.
+    #[instrument(skip(self))]
+    pub async fn ensure_event_outbox_triggers(&self) -> Result<usize> {
+        let tables: Vec<(String, String)> = sqlx_core::query_as::query_as(AssertSqlSafe(format!(
+            "SELECT n.nspname::text, c.relname::text
+             FROM pg_class c
+             JOIN pg_namespace n ON n.oid = c.relnamespace
+             WHERE {RESOURCE_SCHEMAS}
+               AND c.relkind = 'r'
+               AND c.relname NOT LIKE '%_history'
+               AND EXISTS (SELECT 1 FROM pg_attribute a
+                           WHERE a.attrelid = c.oid AND a.attname = 'resource' AND NOT a.attisdropped)
+               AND EXISTS (SELECT 1 FROM pg_attribute a
+                           WHERE a.attrelid = c.oid AND a.attname = 'txid' AND NOT a.attisdropped)
+               AND NOT EXISTS (SELECT 1 FROM pg_trigger t
+                               WHERE t.tgrelid = c.oid AND t.tgname = c.relname || '_event_outbox')
+             ORDER BY n.nspname, c.relname"
+        )))
+        .fetch_all(&self.pool)
+        .await
+        .map_err(PostgresError::from)?;
+
+        for (schema, table) in &tables {
+            self.execute_in_schema(schema, &Self::event_outbox_trigger_sql(table))
+                .await?;
+            debug!(schema = %schema, table = %table, "Added event outbox trigger");
+        }
+
+        if !tables.is_empty() {
+            info!(
+                tables = tables.len(),
+                "Added event outbox triggers to existing tables"
+            );
+        }
+
+        Ok(tables.len())
+    }
+
+    /// Runs schema DDL, written against unqualified table names, in `schema`.
+    /// The search path is pinned for one transaction, as when provisioning a
+    /// tenant.
+    async fn execute_in_schema(&self, schema: &str, sql: &str) -> Result<()> {
+        let mut tx = self.pool.begin().await.map_err(PostgresError::from)?;
+        sqlx_core::query::query("SELECT set_config('search_path', $1, true)")
+            .bind(format!("\"{schema}\", public"))
+            .execute(&mut *tx)
+            .await
+            .map_err(PostgresError::from)?;
+        sqlx_core::raw_sql::raw_sql(AssertSqlSafe(sql.to_string()))
+            .execute(&mut *tx)
+            .await
+            .map_err(PostgresError::from)?;
+        tx.commit().await.map_err(PostgresError::from)?;
+        Ok(())
+    }
+
     /// Returns true if this resource type requires gateway notifications.
     fn is_gateway_resource(table: &str) -> bool {
         matches!(table, "app" | "customoperation")
@@ -252,15 +731,19 @@ impl SchemaManager {
     }
 
     /// Lists all resource tables (excludes history and system tables).
+    ///
+    /// Looks in [`Self::resource_schema`]: `public`, or the tenant's schema
+    /// when called inside a tenant scope.
     #[instrument(skip(self))]
     pub async fn list_tables(&self) -> Result<Vec<String>> {
         let rows: Vec<(String,)> = sqlx_core::query_as::query_as(
             "SELECT table_name FROM information_schema.tables
-             WHERE table_schema = 'public'
+             WHERE table_schema = $1
              AND table_name NOT LIKE '%_history'
              AND table_name NOT LIKE '\\_%' ESCAPE '\\'
              ORDER BY table_name",
         )
+        .bind(Self::resource_schema())
         .fetch_all(&self.pool)
         .await
         .map_err(PostgresError::from)?;
@@ -287,4 +770,65 @@ mod tests {
     fn test_table_name_already_lowercase() {
         assert_eq!(SchemaManager::table_name("patient"), "patient");
     }
+
+    #[test]
+    fn test_schema_sql_default_compression_leaves_column_unset() {
+        let sql = SchemaManager::build_resource_schema_sql("Patient", JsonbCompression::Default);
+        assert!(!sql.contains("SET COMPRESSION"));
+    }
+
+    #[test]
+    fn test_schema_sql_sets_compression_on_main_and_history() {
+        let sql = SchemaManager::build_resource_schema_sql("Patient", JsonbCompression::Lz4);
+        assert!(sql.contains("ALTER TABLE \"patient\" ALTER COLUMN resource SET COMPRESSION lz4;"));
+        assert!(sql.contains(
+            "ALTER TABLE \"patient_history\" ALTER COLUMN resource SET COMPRESSION lz4;"
+        ));
+    }
+
+    #[test]
+    fn test_schema_sql_compression_skips_missing_history_table() {
+        let sql = SchemaManager::build_resource_schema_sql("User", JsonbCompression::Pglz);
+        assert!(sql.contains("ALTER TABLE \"user\" ALTER COLUMN resource SET COMPRESSION pglz;"));
+        assert!(!sql.contains("user_history"));
+    }
+
+    #[test]
+    fn test_schema_sql_adds_full_text_columns() {
+        let sql = SchemaManager::build_resource_schema_sql("Patient", JsonbCompression::Default);
+        assert!(sql.contains("ADD COLUMN IF NOT EXISTS text_tsv tsvector GENERATED ALWAYS"));
+        assert!(sql.contains("jsonb_to_tsvector('english', resource, '[\"string\"]')"));
+        assert!(sql.contains("\"idx_patient_content_tsv\" ON \"patient\" USING GIN (content_tsv)"));
+        assert!(!sql.contains("patient_history\" ADD COLUMN"));
+
+        let sql = SchemaManager::build_resource_schema_sql("Binary", JsonbCompression::Default);
+        assert!(sql.contains("jsonb_to_tsvector('english', resource - 'data', '[\"string\"]')"));
+    }
+
+    #[test]
+    fn test_schema_sql_locks_the_table_first() {
+        let sql = SchemaManager::build_resource_schema_sql("Patient", JsonbCompression::Default);
+        assert!(sql.starts_with(
+            "SELECT pg_advisory_xact_lock(hashtext('octofhir_resource_schema:' || current_schema() || '.patient'));"
+        ));
+    }
+
+    #[test]
+    fn test_schema_sql_adds_event_outbox_trigger() {
+        for resource_type in ["Patient", "User"] {
+            let table = SchemaManager::table_name(resource_type);
+            let sql =
+                SchemaManager::build_resource_schema_sql(resource_type, JsonbCompression::Default);
+            assert!(sql.contains(&format!(
+                "CREATE TRIGGER \"{table}_event_outbox\" AFTER INSERT OR UPDATE OR DELETE ON \"{table}\""
+            )));
+        }
+    }
+
+    #[test]
+    fn test_jsonb_compression_deserialize() {
+        let c: JsonbCompression = serde_json::from_str("\"lz4\"").unwrap();
+        assert_eq!(c, JsonbCompression::Lz4);
+        assert_eq!(JsonbCompression::default(), JsonbCompression::Default);
+    }
 }
@@ -0,0 +1,283 @@
+//! Precomputed search index tables for hot string parameters.
+//!
+//! For each configured `"ResourceType.code"` a side table
+//! `"{table}_{code}_idx" (resource_id, value, blob)` holds one row per value the
+//! parameter extracts from a resource. A row trigger on the resource table keeps
+//! it in sync on every write path (CRUD, transactions, bulk import), and the
+//! registry parameter is marked with the table name so the SQL builder matches
+//! against the table instead of extracting from the resource JSONB. `blob` is
+//! the same normalized form the in-place trigram index uses, so search
+//! semantics do not change.
+//!
+//! Tables created at bootstrap are backfilled once; [`backfill_search_index_tables`]
+//! (run by `$reindex`) refills them after SearchParameter definitions change.
+//!
+//! The SQL builder qualifies the table with the schema being searched, so each
+//! tenant schema needs its own copies: run [`create_search_index_tables`] inside
+//! the tenant's scope to create them there.
+
+use std::time::Duration;
+
+use octofhir_search::SearchParameterRegistry;
+use octofhir_search::loader::ElementTypeResolver;
+use octofhir_search::parameters::SearchParameterType;
+use octofhir_search::sql_builder::build_typed_extract_fn;
+use sqlx_core::sql_str::AssertSqlSafe;
+use sqlx_postgres::PgPool;
+use tracing::{debug, info, warn};
+
+use crate::functional_indexes::build_annotated_paths_for_param;
+
+/// Resources backfilled per statement, so a backfill never holds one huge
+/// transaction.
+const BACKFILL_BATCH_SIZE: i64 = 1000;
+
+/// Name of the precomputed index table for `resource_type`/`code`, truncated to
+/// PostgreSQL's 63-byte identifier limit.
+#[must_use]
+pub fn search_index_table_name(resource_type: &str, code: &str) -> String {
+    let mut name = format!(
+        "{}_{}_idx",
+        resource_type.to_lowercase(),
+        code.replace('-', "_")
+    );
+    name.truncate(63);
+    name
+}
+
+/// Create the index tables and sync triggers for `params` (`"Type.code"`) and
+/// mark the registry parameters to use them. Only string parameters are
+/// supported; others are skipped with a warning. Returns the number of tables
+/// in use.
+pub async fn create_search_index_tables(
+    pool: &PgPool,
+    registry: &SearchParameterRegistry,
+    params: &[String],
+    resolver: &dyn ElementTypeResolver,
+) -> usize {
+    let mut created = 0usize;
+    for spec in params {
+        let Some((resource_type, code)) = spec.split_once('.') else {
+            warn!(
+                spec,
+                "ignoring malformed materialized_params entry (want ResourceType.code)"
+            );
+            continue;
+        };
+        let Some(param) = registry.get(resource_type, code) else {
+            continue;
+        };
+        if param.param_type != SearchParameterType::String {
+            warn!(
+                resource_type,
+                code, "only string parameters can be materialized; skipping"
+            );
+            continue;
+        }
+        let annotated = build_annotated_paths_for_param(&param, resource_type, resolver).await;
+        let Some((fn_name, fn_ddl, _)) = build_typed_extract_fn(resource_type, code, &annotated)
+        else {
+            continue;
+        };
+
+        let index_table = search_index_table_name(resource_type, code);
+        let is_new = match table_exists(pool, &index_table).await {
+            Ok(exists) => !exists,
+            Err(e) => {
+                warn!(resource_type, code, error = %e, "skipped search index table");
+                continue;
+            }
+        };
+        let ddl = format!(
+            "{fn_ddl}\n{}",
+            index_table_sql(&resource_type.to_lowercase(), &index_table, &fn_name)
+        );
+        if let Err(e) = sqlx_core::raw_sql::raw_sql(AssertSqlSafe(ddl))
+            .execute(pool)
+            .await
+        {
+            warn!(resource_type, code, error = %e, "skipped search index table");
+            continue;
+        }
+        if is_new {
+            match backfill_table(pool, resource_type, &index_table, &fn_name, None).await {
+                Ok(rows) => debug!(resource_type, code, rows, "backfilled search index table"),
+                Err(e) => {
+                    warn!(resource_type, code, error = %e, "search index table backfill failed");
+                    continue;
+                }
+            }
+        }
+
+        registry.upsert(
+            (*param)
+                .clone()
+                .with_typed_extract_fn(fn_name)
+                .with_index_table(index_table),
+        );
+        created += 1;
+        debug!(resource_type, code, "search index table ready");
+    }
+    if created > 0 {
+        info!(created, "search index tables ensured");
+    }
+    created
+}
+
+/// Refill the index tables of `params` that are in use from the current
+/// resource contents, in batches with `pause` slept between them. Returns the
+/// number of tables refilled.
+pub async fn backfill_search_index_tables(
+    pool: &PgPool,
+    registry: &SearchParameterRegistry,
+    params: &[String],
+    pause: Duration,
+) -> usize {
+    let mut refilled = 0usize;
+    for spec in params {
+        let Some((resource_type, code)) = spec.split_once('.') else {
+            continue;
+        };
+        let Some(param) = registry.get(resource_type, code) else {
+            continue;
+        };
+        let (Some(index_table), Some(fn_name)) = (
+            param.index_table.as_deref(),
+            param.typed_extract_fn.as_deref(),
+        ) else {
+            continue;
+        };
+        match backfill_table(pool, resource_type, index_table, fn_name, Some(pause)).await {
+            Ok(rows) => {
+                refilled += 1;
+                debug!(resource_type, code, rows, "refilled search index table");
+            }
+            Err(e) => {
+                warn!(resource_type, code, error = %e, "search index table refill failed");
+            }
+        }
+    }
+    refilled
+}
+
+/// Whether `table` exists in the current schema (the tenant's inside a tenant
+/// scope), not merely somewhere on the `search_path`.
+async fn table_exists(pool: &PgPool, table: &str) -> Result<bool, sqlx_core::Error> {
+    let exists: bool = sqlx_core::query_scalar::query_scalar(
+        "SELECT to_regclass(format('%I.%I', current_schema(), $1::text)) IS NOT NULL",
+    )
+    .bind(table)
+    .fetch_one(pool)
+    .await?;
+    Ok(exists)
+}
+
+/// DDL for the index table, its indexes and the sync trigger (idempotent).
+fn index_table_sql(table: &str, index_table: &str, fn_name: &str) -> String {
+    let sync = format!("{index_table}_sync");
+    format!(
+        "CREATE TABLE IF NOT EXISTS \"{index_table}\" (\n\
+            resource_id TEXT NOT NULL,\n\
+            value TEXT NOT NULL,\n\
+            blob TEXT GENERATED ALWAYS AS (fhir_text_blob(ARRAY[value])) STORED\n\
+         );\n\
+         CREATE INDEX IF NOT EXISTS \"{index_table}_resource_id\" ON \"{index_table}\" (resource_id);\n\
+         CREATE INDEX IF NOT EXISTS \"{index_table}_value\" ON \"{index_table}\" (value);\n\
+         CREATE INDEX IF NOT EXISTS \"{index_table}_blob\" ON \"{index_table}\" \
+            USING gin (blob gin_trgm_ops);\n\
+         CREATE OR REPLACE FUNCTION \"{sync}\"() RETURNS trigger LANGUAGE plpgsql AS $$\n\
+         BEGIN\n\
+           IF TG_OP <> 'INSERT' THEN\n\
+             DELETE FROM \"{index_table}\" WHERE resource_id = OLD.id;\n\
+           END IF;\n\
+           IF TG_OP <> 'DELETE' THEN\n\
+             INSERT INTO \"{index_table}\" (resource_id, value)\n\
+             SELECT NEW.id, v FROM unnest({fn_name}(NEW.resource)) AS v WHERE v IS NOT NULL;\n\
+           END IF;\n\
+           RETURN NULL;\n\
+         END;\n\
+         $$;\n\
+         DROP TRIGGER IF EXISTS \"{sync}\" ON \"{table}\";\n\
+         CREATE TRIGGER \"{sync}\" AFTER INSERT OR UPDATE OR DELETE ON \"{table}\" \
+            FOR EACH ROW EXECUTE FUNCTION \"{sync}\"();\n"
+    )
+}
+
+/// Rewrite the index rows of every resource in keyset batches. Each batch is
+/// one statement, so concurrent writes to other resources are not blocked and
+/// readers never see a batch half-applied.
+async fn backfill_table(
+    pool: &PgPool,
+    resource_type: &str,
+    index_table: &str,
+    fn_name: &str,
+    pause: Option<Duration>,
+) -> Result<u64, sqlx_core::Error> {
+    let table = resource_type.to_lowercase();
+    let sql = format!(
+        "WITH batch AS (\n\
+            SELECT id, resource FROM \"{table}\" WHERE id > $1 ORDER BY id LIMIT $2\n\
+         ), cleared AS (\n\
+            DELETE FROM \"{index_table}\" WHERE resource_id IN (SELECT id FROM batch)\n\
+         ), inserted AS (\n\
+            INSERT INTO \"{index_table}\" (resource_id, value)\n\
+            SELECT b.id, v FROM batch b, unnest({fn_name}(b.resource)) AS v WHERE v IS NOT NULL\n\
+         )\n\
+         SELECT max(id), count(*) FROM batch"
+    );
+
+    let mut last_id = String::new();
+    let mut rows = 0u64;
+    loop {
+        let (max_id, count): (Option<String>, i64) =
+            sqlx_core::query_as::query_as(AssertSqlSafe(sql.clone()))
+                .bind(&last_id)
+                .bind(BACKFILL_BATCH_SIZE)
+                .fetch_one(pool)
+                .await?;
+        rows += count as u64;
+        let Some(max_id) = max_id else {
+            break;
+        };
+        last_id = max_id;
+        if count < BACKFILL_BATCH_SIZE {
+            break;
+        }
+        if let Some(pause) = pause {
+            tokio::time::sleep(pause).await;
+        }
+    }
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search_index_table_name() {
+        assert_eq!(
+            search_index_table_name("Patient", "name"),
+            "patient_name_idx"
+        );
+        assert_eq!(
+            search_index_table_name("Practitioner", "address-city"),
+            "practitioner_address_city_idx"
+        );
+        assert_eq!(
+            search_index_table_name("Patient", &"x".repeat(80)).len(),
+            63
+        );
+    }
+
+    #[test]
+    fn test_index_table_sql_syncs_on_every_write() {
+        let sql = index_table_sql("patient", "patient_name_idx", "fhir_s_patient_name");
+        assert!(sql.contains("CREATE TABLE IF NOT EXISTS \"patient_name_idx\""));
+        assert!(sql.contains("unnest(fhir_s_patient_name(NEW.resource))"));
+        assert!(sql.contains(
+            "AFTER INSERT OR UPDATE OR DELETE ON \"patient\" \
+             FOR EACH ROW EXECUTE FUNCTION \"patient_name_idx_sync\"()"
+        ));
+    }
+}
@@ -9,10 +9,11 @@ use sqlx_postgres::PgPool;
 use octofhir_search::{QueryCache, SearchParameter, SearchParameterRegistry};
 use octofhir_storage::{
     FhirStorage, HistoryParams, HistoryResult, RawHistoryResult, RawStoredResource, SearchParams,
-    SearchResult, StorageError, StoredResource, Transaction,
+    SearchResult, StorageError, StoredResource, Transaction, is_resource_type_allowed,
 };
 
 use crate::config::PostgresConfig;
+use crate::error::PG_UNDEFINED_TABLE;
 use crate::migrations;
 use crate::pool;
 use crate::queries;
@@ -55,7 +56,52 @@ impl PostgresStorage {
             migrations::run(&pool, &config.url).await?;
         }
 
-        let schema_manager = SchemaManager::new(pool.clone());
+        let schema_manager =
+            SchemaManager::new(pool.clone()).with_compression(config.resource_compression);
+
+        // Tables created before the compression setting changed keep their old
+        // column setting; bring them in line. A failure (e.g. lz4 unsupported by
+        // the server) is not fatal: the old method keeps working.
+        if let Err(e) = schema_manager.apply_resource_compression().await {
+            tracing::warn!(
+                error = %e,
+                compression = ?config.resource_compression,
+                "Failed to apply resource column compression to existing tables"
+            );
+        }
+
+        // Tables created before `_text` / `_content` moved to generated
+        // tsvector columns need the columns added (and backfilled). Searches
+        // using those parameters fail on a table still missing them. The
+        // backfill rewrites the table, so it only runs when asked for.
+        if config.upgrade_full_text_columns {
+            if let Err(e) = schema_manager.ensure_full_text_columns().await {
+                tracing::warn!(
+                    error = %e,
+                    "Failed to add full-text search columns to existing tables"
+                );
+            }
+        } else {
+            match schema_manager.tables_without_full_text_columns().await {
+                Ok(tables) if !tables.is_empty() => tracing::warn!(
+                    tables = tables.len(),
+                    first = %format!("{}.{}", tables[0].0, tables[0].1),
+                    "Resource tables lack full-text columns; _text and _content searches on them \
+                     fail until started once with upgrade_full_text_columns"
+                ),
+                Ok(_) => {}
+                Err(e) => tracing::warn!(error = %e, "Failed to check full-text search columns"),
+            }
+        }
+
+        // Tables created before the event outbox existed need its trigger, or
+        // their changes never reach at-least-once hooks.
+        if let Err(e) = schema_manager.ensure_event_outbox_triggers().await {
+            tracing::warn!(
+                error = %e,
+                "Failed to add event outbox triggers to existing tables"
+            );
+        }
 
         // Run the GIN pending-list flusher on exactly one pool. Small auxiliary
         // pools (e.g. the config pool) disable it so it isn't spawned twice,
@@ -173,19 +219,49 @@ impl PostgresStorage {
     ) -> Result<HistoryResult, StorageError> {
         queries::get_system_history(self.read_pool(), &self.schema_manager, params).await
     }
+
+    /// Runs a write of `resource`, creating the table for its resource type
+    /// and retrying once if the table does not exist yet. Tables are created
+    /// at startup for the types known then; this covers types registered
+    /// later, e.g. by a package installed at runtime.
+    async fn with_resource_table<T, F, Fut>(
+        &self,
+        resource: &Value,
+        write: F,
+    ) -> Result<T, StorageError>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<T, StorageError>>,
+    {
+        match write().await {
+            Err(e) if e.sqlstate() == Some(PG_UNDEFINED_TABLE) => {
+                let Some(resource_type) = resource["resourceType"].as_str() else {
+                    return Err(e);
+                };
+                tracing::info!(resource_type, "Creating table on first write");
+                self.schema_manager
+                    .create_resource_schema(resource_type)
+                    .await?;
+                write().await
+            }
+            result => result,
+        }
+    }
 }
 
 #[async_trait]
 impl FhirStorage for PostgresStorage {
     async fn create(&self, resource: &Value) -> Result<StoredResource, StorageError> {
-        queries::crud::create(&self.pool, resource).await
+        self.with_resource_table(resource, || queries::crud::create(&self.pool, resource))
+            .await
     }
 
     async fn create_raw(
         &self,
         resource: &Value,
     ) -> Result<octofhir_storage::RawStoredResource, StorageError> {
-        queries::crud::create_raw(&self.pool, resource).await
+        self.with_resource_table(resource, || queries::crud::create_raw(&self.pool, resource))
+            .await
     }
 
     async fn read(
@@ -193,6 +269,9 @@ impl FhirStorage for PostgresStorage {
         resource_type: &str,
         id: &str,
     ) -> Result<Option<StoredResource>, StorageError> {
+        if !is_resource_type_allowed(resource_type) {
+            return Ok(None);
+        }
         queries::read(self.read_pool(), resource_type, id).await
     }
 
@@ -220,6 +299,9 @@ impl FhirStorage for PostgresStorage {
         resource_type: &str,
         ids: &[String],
     ) -> Result<Vec<StoredResource>, StorageError> {
+        if !is_resource_type_allowed(resource_type) {
+            return Ok(Vec::new());
+        }
         queries::read_many(self.read_pool(), resource_type, ids).await
     }
 
@@ -228,6 +310,9 @@ impl FhirStorage for PostgresStorage {
         resource_type: &str,
         id: &str,
     ) -> Result<Option<octofhir_storage::RawStoredResource>, StorageError> {
+        if !is_resource_type_allowed(resource_type) {
+            return Ok(None);
+        }
         queries::read_raw(self.read_pool(), resource_type, id).await
     }
 
@@ -236,7 +321,10 @@ impl FhirStorage for PostgresStorage {
         resource: &Value,
         if_match: Option<&str>,
     ) -> Result<StoredResource, StorageError> {
-        queries::crud::update(&self.pool, resource, if_match).await
+        self.with_resource_table(resource, || {
+            queries::crud::update(&self.pool, resource, if_match)
+        })
+        .await
     }
 
     async fn update_raw(
@@ -244,7 +332,10 @@ impl FhirStorage for PostgresStorage {
         resource: &Value,
         if_match: Option<&str>,
     ) -> Result<octofhir_storage::RawStoredResource, StorageError> {
-        queries::crud::update_raw(&self.pool, resource, if_match).await
+        self.with_resource_table(resource, || {
+            queries::crud::update_raw(&self.pool, resource, if_match)
+        })
+        .await
     }
 
     async fn delete(&self, resource_type: &str, id: &str) -> Result<(), StorageError> {
@@ -257,6 +348,9 @@ impl FhirStorage for PostgresStorage {
         id: &str,
         version: &str,
     ) -> Result<Option<StoredResource>, StorageError> {
+        if !is_resource_type_allowed(resource_type) {
+            return Ok(None);
+        }
         queries::vread(self.read_pool(), resource_type, id, version).await
     }
 
@@ -266,6 +360,9 @@ impl FhirStorage for PostgresStorage {
         id: &str,
         version: &str,
     ) -> Result<Option<RawStoredResource>, StorageError> {
+        if !is_resource_type_allowed(resource_type) {
+            return Ok(None);
+        }
         queries::vread_raw(self.read_pool(), resource_type, id, version).await
     }
 
@@ -275,11 +372,20 @@ impl FhirStorage for PostgresStorage {
         id: Option<&str>,
         params: &HistoryParams,
     ) -> Result<HistoryResult, StorageError> {
+        if !is_resource_type_allowed(resource_type) {
+            return Ok(HistoryResult::empty());
+        }
         queries::get_history(self.read_pool(), resource_type, id, params).await
     }
 
     async fn system_history(&self, params: &HistoryParams) -> Result<HistoryResult, StorageError> {
-        queries::get_system_history(self.read_pool(), &self.schema_manager, params).await
+        let mut result =
+            queries::get_system_history(self.read_pool(), &self.schema_manager, params).await?;
+        // Tables of types dropped from the allowlist may still exist
+        result
+            .entries
+            .retain(|e| is_resource_type_allowed(&e.resource.resource_type));
+        Ok(result)
     }
 
     async fn history_raw(
@@ -288,6 +394,9 @@ impl FhirStorage for PostgresStorage {
         id: Option<&str>,
         params: &HistoryParams,
     ) -> Result<RawHistoryResult, StorageError> {
+        if !is_resource_type_allowed(resource_type) {
+            return Ok(RawHistoryResult::default());
+        }
         queries::get_history_raw(self.read_pool(), resource_type, id, params).await
     }
 
@@ -295,7 +404,12 @@ impl FhirStorage for PostgresStorage {
         &self,
         params: &HistoryParams,
     ) -> Result<RawHistoryResult, StorageError> {
-        queries::get_system_history_raw(self.read_pool(), &self.schema_manager, params).await
+        let mut result =
+            queries::get_system_history_raw(self.read_pool(), &self.schema_manager, params).await?;
+        result
+            .entries
+            .retain(|e| is_resource_type_allowed(&e.resource.resource_type));
+        Ok(result)
     }
 
     async fn search(
@@ -313,12 +427,11 @@ impl FhirStorage for PostgresStorage {
     }
 
     async fn begin_transaction(&self) -> Result<Box<dyn Transaction>, StorageError> {
-        // Begin a new PostgreSQL transaction
-        let sqlx_tx = self.pool.begin().await.map_err(|e| {
-            StorageError::transaction_error(format!("Failed to begin transaction: {}", e))
-        })?;
+        // Begin a new PostgreSQL transaction, bounded by the request deadline
+        let sqlx_tx = crate::deadline::begin_transaction(&self.pool).await?;
 
-        let pg_tx = crate::transaction::PostgresTransaction::new(sqlx_tx, None);
+        let pg_tx = crate::transaction::PostgresTransaction::new(sqlx_tx, None)
+            .with_schema_manager(self.schema_manager.clone());
 
         Ok(Box::new(pg_tx))
     }
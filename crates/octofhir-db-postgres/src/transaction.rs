@@ -8,6 +8,7 @@ use async_trait::async_trait;
 use serde_json::Value;
 use sqlx_core::query_scalar::query_scalar;
 use sqlx_postgres::PgTransaction;
+use std::collections::HashSet;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
@@ -15,6 +16,7 @@ use octofhir_search::SearchParameterRegistry;
 use octofhir_storage::{SearchParams, SearchResult, StorageError, StoredResource, Transaction};
 
 use crate::queries;
+use crate::schema::SchemaManager;
 
 /// PostgreSQL transaction wrapper providing ACID guarantees.
 ///
@@ -37,6 +39,10 @@ pub struct PostgresTransaction {
     /// subsequent writes reuse it instead of inserting a new `_transaction`
     /// row per operation.
     txid: Mutex<Option<i64>>,
+    /// Creates the table of a resource type written for the first time.
+    schema_manager: Option<SchemaManager>,
+    /// Resource types whose table is known to exist in this transaction.
+    tables: HashSet<String>,
 }
 
 impl PostgresTransaction {
@@ -49,8 +55,48 @@ impl PostgresTransaction {
             tx: Mutex::new(Some(Box::new(tx))),
             search_registry,
             txid: Mutex::new(None),
+            schema_manager: None,
+            tables: HashSet::new(),
         }
     }
+
+    /// Creates missing resource tables with `schema_manager` on first write.
+    #[must_use]
+    pub fn with_schema_manager(mut self, schema_manager: SchemaManager) -> Self {
+        self.schema_manager = Some(schema_manager);
+        self
+    }
+}
+
+/// Creates the table for `resource_type` inside `tx` if it does not exist
+/// yet, checking once per type and transaction. A write to a missing table
+/// would abort the whole transaction, so this runs before it rather than
+/// retrying after the error like [`crate::PostgresStorage`] does.
+async fn ensure_resource_table(
+    tables: &mut HashSet<String>,
+    schema_manager: Option<&SchemaManager>,
+    tx: &mut PgTransaction<'_>,
+    resource_type: &str,
+) -> Result<(), StorageError> {
+    let Some(schema_manager) = schema_manager else {
+        return Ok(());
+    };
+    if tables.contains(resource_type) {
+        return Ok(());
+    }
+    let exists: bool = query_scalar("SELECT to_regclass($1) IS NOT NULL")
+        .bind(SchemaManager::qualified_table_name(resource_type))
+        .fetch_one(&mut **tx)
+        .await
+        .map_err(|e| StorageError::internal(format!("Failed to look up resource table: {e}")))?;
+    if !exists {
+        tracing::info!(resource_type, "Creating table on first write");
+        schema_manager
+            .create_resource_schema_in(tx, resource_type)
+            .await?;
+    }
+    tables.insert(resource_type.to_string());
+    Ok(())
 }
 
 /// Allocates a `_transaction` row inside the given sqlx transaction if no
@@ -102,6 +148,15 @@ impl Transaction for PostgresTransaction {
         let mut txid_guard = self.txid.lock().await;
         let txid = ensure_txid(&mut txid_guard, tx).await?;
         drop(txid_guard);
+        if let Some(resource_type) = resource["resourceType"].as_str() {
+            ensure_resource_table(
+                &mut self.tables,
+                self.schema_manager.as_ref(),
+                tx,
+                resource_type,
+            )
+            .await?;
+        }
         let result = queries::crud::create_with_tx(tx, resource, Some(txid)).await?;
         Ok(result)
     }
@@ -120,6 +175,15 @@ impl Transaction for PostgresTransaction {
         let mut txid_guard = self.txid.lock().await;
         let txid = ensure_txid(&mut txid_guard, tx).await?;
         drop(txid_guard);
+        if let Some(resource_type) = resource["resourceType"].as_str() {
+            ensure_resource_table(
+                &mut self.tables,
+                self.schema_manager.as_ref(),
+                tx,
+                resource_type,
+            )
+            .await?;
+        }
         let result =
             queries::crud::update_with_tx_if_match(tx, resource, if_match, Some(txid)).await?;
         Ok(result)
@@ -191,6 +255,13 @@ impl Transaction for PostgresTransaction {
         let mut txid_guard = self.txid.lock().await;
         let txid = ensure_txid(&mut txid_guard, tx).await?;
         drop(txid_guard);
+        ensure_resource_table(
+            &mut self.tables,
+            self.schema_manager.as_ref(),
+            tx,
+            resource_type,
+        )
+        .await?;
 
         let stored =
             queries::crud::create_batch_with_tx(tx, resource_type, txid, resources).await?;
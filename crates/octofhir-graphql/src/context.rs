@@ -28,6 +28,7 @@ use octofhir_search::SearchConfig;
 use octofhir_storage::DynStorage;
 
 use crate::loaders::DataLoaders;
+use crate::validation::DynResourceValidator;
 
 /// GraphQL execution context.
 ///
@@ -68,6 +69,13 @@ pub struct GraphQLContext {
     /// These loaders batch and cache resource loads within a single request,
     /// preventing N+1 query problems when resolving references.
     pub loaders: DataLoaders,
+
+    /// Structural validator for mutation input (None skips validation).
+    pub validator: Option<DynResourceValidator>,
+
+    /// Maximum serialized size in bytes of a resource written by a mutation
+    /// (0 disables the limit).
+    pub max_resource_size: usize,
 }
 
 impl GraphQLContext {
@@ -156,6 +164,8 @@ pub struct GraphQLContextBuilder {
     source_ip: Option<IpAddr>,
     target_resource_type: Option<String>,
     target_resource_id: Option<String>,
+    validator: Option<DynResourceValidator>,
+    max_resource_size: usize,
 }
 
 impl GraphQLContextBuilder {
@@ -221,6 +231,20 @@ impl GraphQLContextBuilder {
         self
     }
 
+    /// Sets the validator run on create and update mutation input.
+    #[must_use]
+    pub fn with_validator(mut self, validator: Option<DynResourceValidator>) -> Self {
+        self.validator = validator;
+        self
+    }
+
+    /// Sets the maximum serialized size of a resource written by a mutation.
+    #[must_use]
+    pub fn with_max_resource_size(mut self, bytes: usize) -> Self {
+        self.max_resource_size = bytes;
+        self
+    }
+
     /// Sets both target resource type and ID at once.
     #[must_use]
     pub fn with_target_resource(
@@ -269,6 +293,8 @@ impl GraphQLContextBuilder {
             target_resource_type: self.target_resource_type,
             target_resource_id: self.target_resource_id,
             loaders,
+            validator: self.validator,
+            max_resource_size: self.max_resource_size,
         })
     }
 }
@@ -4,6 +4,7 @@
 //! - `POST /$graphql` - System-level GraphQL endpoint
 //! - `GET /$graphql` - System-level GraphQL (query via URL param)
 //! - `POST /:type/:id/$graphql` - Instance-level GraphQL endpoint
+//! - `GET /:type/:id/$graphql` - Instance-level GraphQL (query via URL param)
 //!
 //! The handlers integrate with the OctoFHIR authentication middleware and
 //! convert GraphQL errors to FHIR-compliant responses.
@@ -45,6 +46,12 @@ pub struct GraphQLContextTemplate {
     /// Broadcaster for subscription events.
     /// This allows mutations to emit events that are delivered to subscription clients.
     pub subscription_broadcaster: Option<Arc<crate::subscriptions::ResourceEventBroadcaster>>,
+    /// Validator run on create and update mutation input before the write.
+    /// `None` stores input after only the primitive value checks.
+    pub validator: Option<crate::validation::DynResourceValidator>,
+    /// Maximum serialized size in bytes of a resource written by a
+    /// mutation, as `server.max_resource_size_bytes`. `0` disables the limit.
+    pub max_resource_size: usize,
 }
 
 /// GraphQL request body.
@@ -74,7 +81,7 @@ pub struct GraphQLQueryParams {
     #[serde(rename = "operationName")]
     pub operation_name: Option<String>,
 
-    /// Optional variables (JSON string).
+    /// Optional variables (URL-encoded JSON object).
     pub variables: Option<String>,
 }
 
@@ -290,6 +297,42 @@ pub async fn instance_graphql_handler(
     .into_response()
 }
 
+/// Handles GET requests to /:type/:id/$graphql (instance-level endpoint).
+///
+/// Same as [`instance_graphql_handler`], with the request in URL parameters.
+pub async fn instance_graphql_handler_get(
+    State(state): State<GraphQLState>,
+    auth_context: Option<Extension<Arc<AuthContext>>>,
+    headers: HeaderMap,
+    Path((resource_type, resource_id)): Path<(String, String)>,
+    Query(params): Query<GraphQLQueryParams>,
+) -> impl IntoResponse {
+    let request = match params_to_request(params) {
+        Ok(req) => req,
+        Err(e) => {
+            return error_response(GraphQLError::InvalidQuery(e.to_string())).into_response();
+        }
+    };
+
+    debug!(
+        resource_type = %resource_type,
+        resource_id = %resource_id,
+        "Processing instance GraphQL GET request"
+    );
+
+    execute_graphql(
+        state,
+        headers,
+        request,
+        Some(resource_type),
+        Some(resource_id),
+        auth_context.map(|Extension(ctx)| (*ctx).clone()),
+        None,
+    )
+    .await
+    .into_response()
+}
+
 /// Checks if a GraphQL query is an introspection query.
 ///
 /// Introspection queries typically start with __schema or __type.
@@ -339,6 +382,27 @@ async fn execute_graphql(
         }
     };
 
+    // Check variables against the operation and scope instance-level
+    // queries to their resource. Documents that do not parse are left to the
+    // executor, which reports syntax errors in the GraphQL response.
+    let mut query = request.query;
+    let mut scoped_type = None;
+    if let Some(document) = crate::request::parse(&query) {
+        if let Err(e) = crate::request::validate_variables(
+            &document,
+            request.operation_name.as_deref(),
+            request.variables.as_ref(),
+        ) {
+            return error_response(e).into_response();
+        }
+        if let (Some(rt), Some(id)) = (&target_resource_type, &target_resource_id)
+            && let Some(scoped) = crate::request::scope_to_instance(&query, &document, rt, id)
+        {
+            query = scoped;
+            scoped_type = Some(rt.clone());
+        }
+    }
+
     // Extract request ID from headers (set by middleware)
     let request_id = headers
         .get("x-request-id")
@@ -362,7 +426,7 @@ async fn execute_graphql(
     };
 
     // Build the async-graphql request
-    let mut gql_request = Request::new(&request.query);
+    let mut gql_request = Request::new(&query);
 
     if let Some(op_name) = request.operation_name {
         gql_request = gql_request.operation_name(op_name);
@@ -377,12 +441,16 @@ async fn execute_graphql(
     gql_request = gql_request.data(context);
 
     // Execute the query
-    debug!(query = %request.query, "Executing GraphQL query");
+    debug!(query = %query, "Executing GraphQL query");
     let response = schema.execute(gql_request).await;
 
     // Convert to JSON response
     // Note: GraphQL always returns 200 OK per spec, even with errors
-    let gql_response = GraphQLResponse::from(response);
+    let mut gql_response = GraphQLResponse::from(response);
+    if let Some(rt) = scoped_type {
+        gql_response.data =
+            crate::request::unscope_response(gql_response.data, &mut gql_response.errors, &rt);
+    }
 
     (
         StatusCode::OK,
@@ -405,6 +473,8 @@ fn build_context(
         .with_storage(template.storage.clone())
         .with_search_config(template.search_config.clone())
         .with_policy_evaluator(template.policy_evaluator.clone())
+        .with_validator(template.validator.clone())
+        .with_max_resource_size(template.max_resource_size)
         .with_auth_context(auth_context)
         .with_source_ip(source_ip)
         .with_request_id(request_id);
@@ -417,16 +487,18 @@ fn build_context(
 }
 
 /// Converts GET query params to a GraphQL request.
+///
+/// Client libraries send absent parameters as empty strings, so empty
+/// `operationName` and `variables` are treated as missing.
 fn params_to_request(params: GraphQLQueryParams) -> Result<GraphQLRequest, serde_json::Error> {
-    let variables = if let Some(vars_str) = params.variables {
-        Some(serde_json::from_str(&vars_str)?)
-    } else {
-        None
+    let variables = match params.variables.as_deref().map(str::trim) {
+        Some(vars_str) if !vars_str.is_empty() => Some(serde_json::from_str(vars_str)?),
+        _ => None,
     };
 
     Ok(GraphQLRequest {
         query: params.query.unwrap_or_default(),
-        operation_name: params.operation_name,
+        operation_name: params.operation_name.filter(|name| !name.is_empty()),
         variables,
         extensions: None,
     })
@@ -546,6 +618,19 @@ mod tests {
         assert!(request.variables.is_none());
     }
 
+    #[test]
+    fn test_params_to_request_empty_params() {
+        let params = GraphQLQueryParams {
+            query: Some("{ _health }".to_string()),
+            operation_name: Some(String::new()),
+            variables: Some(" ".to_string()),
+        };
+
+        let request = params_to_request(params).unwrap();
+        assert!(request.operation_name.is_none());
+        assert!(request.variables.is_none());
+    }
+
     #[test]
     fn test_params_to_request_invalid_variables() {
         let params = GraphQLQueryParams {
@@ -22,6 +22,7 @@
 //! - `POST /$graphql` - System-level GraphQL endpoint
 //! - `GET /$graphql` - System-level GraphQL (query via URL param)
 //! - `POST /:type/:id/$graphql` - Instance-level GraphQL endpoint
+//! - `GET /:type/:id/$graphql` - Instance-level GraphQL (query via URL param)
 //!
 //! ## Configuration
 //!
@@ -42,6 +43,7 @@
 //! - [`schema`] - Schema building and lazy loading
 //! - [`context`] - GraphQL execution context
 //! - [`handler`] - Axum HTTP handlers
+//! - [`request`] - Variable checks and instance scoping of requests
 //! - [`error`] - Error types for GraphQL operations
 
 pub mod config;
@@ -50,16 +52,20 @@ pub mod error;
 pub mod handler;
 pub mod loaders;
 pub mod operations;
+pub mod request;
 pub mod resolvers;
 pub mod schema;
 pub mod subscriptions;
 pub mod types;
+pub mod validation;
 
 // Re-export main types
 pub use config::GraphQLConfig;
 pub use context::{GraphQLContext, GraphQLContextBuilder};
 pub use error::GraphQLError;
-pub use handler::{graphql_handler, graphql_handler_get, instance_graphql_handler};
+pub use handler::{
+    graphql_handler, graphql_handler_get, instance_graphql_handler, instance_graphql_handler_get,
+};
 pub use loaders::{DataLoaders, ReferenceKey, ReferenceLoader, ResourceKey, ResourceLoader};
 pub use operations::GraphQLOperationProvider;
 pub use schema::{
@@ -68,6 +74,7 @@ pub use schema::{
 pub use subscriptions::{
     ResourceChangeEvent, ResourceEventBroadcaster, ResourceEventType, build_subscription_type,
 };
+pub use validation::{DynResourceValidator, ResourceValidator, ValidationIssue};
 
 /// Result type for GraphQL operations.
 pub type Result<T> = std::result::Result<T, GraphQLError>;
@@ -30,7 +30,10 @@ pub use resource::{ResourceKey, ResourceLoader};
 use std::sync::Arc;
 
 use async_graphql::dataloader::DataLoader;
+use futures_util::future::BoxFuture;
 use octofhir_storage::DynStorage;
+use octofhir_storage::tenant::in_current_tenant;
+use tokio::task::JoinHandle;
 
 /// Collection of all DataLoaders for a GraphQL request.
 ///
@@ -59,8 +62,8 @@ impl DataLoaders {
         let reference_loader = ReferenceLoader::new(storage);
 
         Self {
-            resource_loader: Arc::new(DataLoader::new(resource_loader, tokio::spawn)),
-            reference_loader: Arc::new(DataLoader::new(reference_loader, tokio::spawn)),
+            resource_loader: Arc::new(DataLoader::new(resource_loader, spawn_in_tenant)),
+            reference_loader: Arc::new(DataLoader::new(reference_loader, spawn_in_tenant)),
         }
     }
 
@@ -75,14 +78,23 @@ impl DataLoaders {
         let reference_loader = ReferenceLoader::new(storage);
 
         Self {
-            resource_loader: Arc::new(DataLoader::new(resource_loader, tokio::spawn).delay(delay)),
+            resource_loader: Arc::new(
+                DataLoader::new(resource_loader, spawn_in_tenant).delay(delay),
+            ),
             reference_loader: Arc::new(
-                DataLoader::new(reference_loader, tokio::spawn).delay(delay),
+                DataLoader::new(reference_loader, spawn_in_tenant).delay(delay),
             ),
         }
     }
 }
 
+/// Spawns a batch load in the tenant of the resolver that queued it. Batches
+/// run on their own task, which would otherwise lose the tenant scope and
+/// read the default schema.
+fn spawn_in_tenant(fut: BoxFuture<'static, ()>) -> JoinHandle<()> {
+    tokio::spawn(in_current_tenant(fut))
+}
+
 impl std::fmt::Debug for DataLoaders {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("DataLoaders")
@@ -95,6 +107,20 @@ impl std::fmt::Debug for DataLoaders {
 #[cfg(test)]
 mod tests {
     use crate::loaders::reference::ParsedReference;
+    use octofhir_storage::tenant::{TenantId, current_tenant, with_tenant};
+
+    #[tokio::test]
+    async fn test_batches_spawn_in_the_resolver_tenant() {
+        let acme = TenantId::new("acme").unwrap();
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        with_tenant(acme.clone(), async {
+            super::spawn_in_tenant(Box::pin(async move {
+                let _ = tx.send(current_tenant());
+            }));
+        })
+        .await;
+        assert_eq!(rx.await.unwrap(), Some(acme));
+    }
 
     #[test]
     fn test_parsed_reference_relative() {
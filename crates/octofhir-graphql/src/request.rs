@@ -0,0 +1,287 @@
+//! Request preparation before execution.
+//!
+//! Checks the supplied `variables` against the variables the selected
+//! operation declares, and scopes instance-level queries
+//! (`/:type/:id/$graphql`) to their resource.
+//!
+//! # Instance Scope
+//!
+//! Per the FHIR GraphQL specification an instance-level query selects the
+//! resource's own fields, e.g. `{ name { given } }` against `Patient/123`.
+//! The schema has no such root, so the query's selection set is wrapped in
+//! the resource's read field (`Patient(_id: "123") { ... }`) and the response
+//! is unwrapped again. Queries that already select the read field are left
+//! as they are.
+
+use async_graphql_parser::parse_query;
+use async_graphql_parser::types::{
+    DocumentOperations, ExecutableDocument, OperationDefinition, OperationType, Selection,
+};
+
+use crate::error::GraphQLError;
+
+/// The operation a request executes, by `operationName` or as the only one.
+fn select_operation<'a>(
+    document: &'a ExecutableDocument,
+    operation_name: Option<&str>,
+) -> Result<&'a OperationDefinition, GraphQLError> {
+    match (&document.operations, operation_name) {
+        (DocumentOperations::Single(op), _) => Ok(&op.node),
+        (DocumentOperations::Multiple(ops), Some(name)) => ops
+            .iter()
+            .find(|(n, _)| n.as_str() == name)
+            .map(|(_, op)| &op.node)
+            .ok_or_else(|| GraphQLError::InvalidQuery(format!("Unknown operation '{name}'"))),
+        (DocumentOperations::Multiple(ops), None) if ops.len() == 1 => {
+            Ok(&ops.values().next().expect("one operation").node)
+        }
+        (DocumentOperations::Multiple(_), None) => Err(GraphQLError::InvalidQuery(
+            "operationName is required for documents with several operations".to_string(),
+        )),
+    }
+}
+
+/// Checks `variables` against the selected operation's declarations.
+///
+/// Variables must be a JSON object, every variable must be declared, and
+/// non-null variables without a default must be given. Value types are
+/// checked by the executor.
+pub fn validate_variables(
+    document: &ExecutableDocument,
+    operation_name: Option<&str>,
+    variables: Option<&serde_json::Value>,
+) -> Result<(), GraphQLError> {
+    let operation = select_operation(document, operation_name)?;
+    let variables = match variables {
+        None | Some(serde_json::Value::Null) => None,
+        Some(serde_json::Value::Object(map)) => Some(map),
+        Some(_) => {
+            return Err(GraphQLError::InvalidQuery(
+                "variables must be a JSON object".to_string(),
+            ));
+        }
+    };
+
+    if let Some(variables) = variables
+        && let Some(name) = variables.keys().find(|name| {
+            !operation
+                .variable_definitions
+                .iter()
+                .any(|def| def.node.name.node.as_str() == name.as_str())
+        })
+    {
+        return Err(GraphQLError::InvalidQuery(format!(
+            "Variable '${name}' is not declared by the operation"
+        )));
+    }
+
+    for def in &operation.variable_definitions {
+        let name = def.node.name.node.as_str();
+        let given = variables
+            .and_then(|v| v.get(name))
+            .is_some_and(|v| !v.is_null());
+        if !given && def.node.default_value().is_none() {
+            return Err(GraphQLError::InvalidQuery(format!(
+                "Variable '${name}' of type '{}' is required",
+                def.node.var_type.node
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Parses a query, `None` if it does not parse (the executor reports it).
+pub fn parse(query: &str) -> Option<ExecutableDocument> {
+    parse_query(query).ok()
+}
+
+/// Rewrites query operations of `document` to run against the resource
+/// `resource_type/id`.
+///
+/// Returns `None` if no operation needs scoping.
+pub fn scope_to_instance(
+    query: &str,
+    document: &ExecutableDocument,
+    resource_type: &str,
+    id: &str,
+) -> Option<String> {
+    // Opening braces of the selection sets to wrap, as byte offsets
+    let mut openings: Vec<usize> = document
+        .operations
+        .iter()
+        .map(|(_, op)| &op.node)
+        .filter(|op| op.ty == OperationType::Query && !selects_root(op, resource_type))
+        .filter_map(|op| {
+            let pos = op.selection_set.pos;
+            byte_offset(query, pos.line, pos.column)
+        })
+        .collect();
+    if openings.is_empty() {
+        return None;
+    }
+    openings.sort_unstable();
+
+    let id = serde_json::to_string(id).ok()?;
+    let mut scoped = String::with_capacity(query.len() + openings.len() * 32);
+    let mut copied = 0;
+    for open in openings {
+        let close = matching_brace(query, open)?;
+        scoped.push_str(&query[copied..=open]);
+        scoped.push_str(&format!(" {resource_type}(_id: {id}) {{"));
+        scoped.push_str(&query[open + 1..close]);
+        scoped.push_str("} ");
+        copied = close;
+    }
+    scoped.push_str(&query[copied..]);
+    Some(scoped)
+}
+
+/// Unwraps the data and error paths of a response to a scoped query.
+pub fn unscope_response(
+    data: Option<serde_json::Value>,
+    errors: &mut [serde_json::Value],
+    resource_type: &str,
+) -> Option<serde_json::Value> {
+    for error in errors.iter_mut() {
+        if let Some(path) = error.get_mut("path").and_then(|p| p.as_array_mut())
+            && path.first().and_then(|s| s.as_str()) == Some(resource_type)
+        {
+            path.remove(0);
+        }
+    }
+    data.map(|mut data| {
+        data.get_mut(resource_type)
+            .map(serde_json::Value::take)
+            .unwrap_or(serde_json::Value::Null)
+    })
+}
+
+/// Whether the operation already selects the resource's read field.
+fn selects_root(operation: &OperationDefinition, resource_type: &str) -> bool {
+    operation.selection_set.node.items.iter().any(|item| {
+        matches!(&item.node, Selection::Field(field)
+            if field.node.name.node.as_str() == resource_type
+                || field.node.name.node.starts_with("__"))
+    })
+}
+
+/// Byte offset of a 1-based line and (character) column.
+fn byte_offset(text: &str, line: usize, column: usize) -> Option<usize> {
+    let line_start = if line <= 1 {
+        0
+    } else {
+        text.match_indices('\n').nth(line - 2)?.0 + 1
+    };
+    text[line_start..]
+        .char_indices()
+        .nth(column.checked_sub(1)?)
+        .map(|(i, _)| line_start + i)
+}
+
+/// Offset of the `}` closing the `{` at `open`, skipping strings and
+/// comments.
+fn matching_brace(text: &str, open: usize) -> Option<usize> {
+    let bytes = text.as_bytes();
+    let mut depth = 0usize;
+    let mut i = open;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'{' => depth += 1,
+            b'}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            b'#' => {
+                i += text[i..].find('\n')?;
+            }
+            b'"' if text[i..].starts_with("\"\"\"") => {
+                i += 3 + text[i + 3..].find("\"\"\"")? + 2;
+            }
+            b'"' => {
+                i += 1;
+                while bytes.get(i)? != &b'"' {
+                    i += if bytes[i] == b'\\' { 2 } else { 1 };
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn doc(query: &str) -> ExecutableDocument {
+        parse_query(query).unwrap()
+    }
+
+    #[test]
+    fn test_validate_variables() {
+        let document = doc("query Q($id: ID!, $count: Int = 10, $name: String) { _health }");
+
+        assert!(validate_variables(&document, None, Some(&json!({"id": "1"}))).is_ok());
+        assert!(validate_variables(&document, Some("Q"), Some(&json!({"id": "1"}))).is_ok());
+
+        let err = |vars: serde_json::Value| {
+            validate_variables(&document, None, Some(&vars))
+                .unwrap_err()
+                .to_string()
+        };
+        assert!(err(json!({})).contains("'$id' of type 'ID!' is required"));
+        assert!(err(json!({"id": null})).contains("'$id'"));
+        assert!(err(json!({"id": "1", "other": 1})).contains("'$other' is not declared"));
+        assert!(err(json!([1])).contains("JSON object"));
+    }
+
+    #[test]
+    fn test_validate_variables_selects_operation() {
+        let document = doc("query A($x: Int!) { _health } query B { _version }");
+
+        assert!(validate_variables(&document, Some("B"), None).is_ok());
+        assert!(validate_variables(&document, Some("A"), None).is_err());
+        assert!(validate_variables(&document, Some("C"), None).is_err());
+        assert!(validate_variables(&document, None, None).is_err());
+    }
+
+    #[test]
+    fn test_scope_to_instance() {
+        let query = "query Q($n: Int) {\n  name { given } # } in a comment\n  text(x: \"}\")\n}";
+        let scoped = scope_to_instance(query, &doc(query), "Patient", "123").unwrap();
+        assert_eq!(
+            scoped,
+            "query Q($n: Int) { Patient(_id: \"123\") {\n  name { given } # } in a comment\n  text(x: \"}\")\n} }"
+        );
+        assert!(parse_query(&scoped).is_ok());
+
+        // Already scoped, introspection and mutations are left alone
+        for query in [
+            "{ Patient(_id: \"123\") { id } }",
+            "{ __schema { types { name } } }",
+            "mutation { PatientDelete(id: \"1\") { id } }",
+        ] {
+            assert_eq!(
+                scope_to_instance(query, &doc(query), "Patient", "123"),
+                None
+            );
+        }
+    }
+
+    #[test]
+    fn test_unscope_response() {
+        let mut errors = vec![json!({"message": "x", "path": ["Patient", "name"]})];
+        let data = unscope_response(
+            Some(json!({"Patient": {"id": "123"}})),
+            &mut errors,
+            "Patient",
+        );
+        assert_eq!(data, Some(json!({"id": "123"})));
+        assert_eq!(errors[0]["path"], json!(["name"]));
+    }
+}
@@ -2,12 +2,19 @@
 //!
 //! Handles `{Resource}Create` mutations for creating new FHIR resources.
 
+use std::future::Future;
+use std::pin::Pin;
+
 use async_graphql::dynamic::{FieldFuture, ResolverContext, ValueAccessor};
 use async_graphql::{ErrorExtensions, Value};
 use octofhir_auth::smart::scopes::FhirOperation;
+use octofhir_fhir_model::provider::TypeInfo;
 use tracing::{debug, trace, warn};
 
 use super::{evaluate_access_with_resource, get_graphql_context, json_to_graphql_value};
+use crate::schema::{DynModelProvider, is_primitive_type};
+use crate::types::validate_primitive;
+use crate::validation::{check_resource_size, validate_structure};
 
 /// Resolver for resource creation mutations.
 ///
@@ -27,11 +34,14 @@ impl CreateResolver {
     ///
     /// # Arguments
     /// * `resource_type` - The FHIR resource type (e.g., "Patient")
+    /// * `model_provider` - Element types for validating primitive values
     pub fn resolve(
         resource_type: String,
+        model_provider: DynModelProvider,
     ) -> impl Fn(ResolverContext<'_>) -> FieldFuture<'_> + Send + Sync + Clone {
         move |ctx| {
             let resource_type = resource_type.clone();
+            let model_provider = model_provider.clone();
 
             FieldFuture::new(async move {
                 debug!(resource_type = %resource_type, "Processing create mutation");
@@ -47,6 +57,9 @@ impl CreateResolver {
 
                 // Extract the resource JSON from the input
                 let resource_json = extract_resource_from_input(&input, &resource_type)?;
+                check_resource_size(gql_ctx.max_resource_size, &resource_json)?;
+                validate_resource_primitives(&model_provider, &resource_json).await?;
+                validate_structure(gql_ctx, &resource_json).await?;
 
                 // Evaluate access control with the resource being created
                 evaluate_access_with_resource(
@@ -124,6 +137,95 @@ pub(crate) fn extract_resource_from_input(
     Ok(resource)
 }
 
+/// Checks every primitive value in `resource` against its FHIR scalar, so
+/// e.g. a `date` of `"not-a-date"` fails the mutation instead of being stored.
+///
+/// Elements the model does not know are left alone.
+pub(crate) async fn validate_resource_primitives(
+    model_provider: &DynModelProvider,
+    resource: &serde_json::Value,
+) -> Result<(), async_graphql::Error> {
+    let Some(resource_type) = resource.get("resourceType").and_then(|v| v.as_str()) else {
+        return Ok(());
+    };
+    let Ok(Some(type_info)) = model_provider.get_type(resource_type).await else {
+        return Ok(());
+    };
+    check_primitives(
+        model_provider,
+        type_info,
+        resource,
+        resource_type.to_string(),
+    )
+    .await
+    .map_err(|message| {
+        storage_error_to_graphql(octofhir_storage::StorageError::invalid_resource(message))
+    })
+}
+
+/// Recursive walk behind [`validate_resource_primitives`]; `path` is the
+/// FHIRPath of `value` for error messages.
+fn check_primitives<'a>(
+    model_provider: &'a DynModelProvider,
+    type_info: TypeInfo,
+    value: &'a serde_json::Value,
+    path: String,
+) -> Pin<Box<dyn Future<Output = Result<(), String>> + Send + 'a>> {
+    Box::pin(async move {
+        let Some(object) = value.as_object() else {
+            return Ok(());
+        };
+        for (key, child) in object {
+            // Primitive extensions (`_birthDate`) hold no primitive value
+            if key == "resourceType" || key.starts_with('_') {
+                continue;
+            }
+            let Ok(Some(child_type)) = model_provider.get_element_type(&type_info, key).await
+            else {
+                continue;
+            };
+            let Some(type_name) = child_type.name.clone() else {
+                continue;
+            };
+
+            let items: Vec<(String, &serde_json::Value)> = match child {
+                serde_json::Value::Array(items) => items
+                    .iter()
+                    .enumerate()
+                    .map(|(i, item)| (format!("{path}.{key}[{i}]"), item))
+                    .collect(),
+                item => vec![(format!("{path}.{key}"), item)],
+            };
+            for (item_path, item) in items {
+                if is_primitive_type(&type_name) {
+                    validate_primitive(&type_name, item)
+                        .map_err(|e| format!("{item_path}: {e}"))?;
+                } else if matches!(type_name.as_str(), "Resource" | "DomainResource") {
+                    // Contained and Bundle entry resources carry their own type
+                    let nested = item.get("resourceType").and_then(|v| v.as_str());
+                    if let Some(nested) = nested
+                        && let Ok(Some(nested_type)) = model_provider.get_type(nested).await
+                    {
+                        check_primitives(model_provider, nested_type, item, item_path).await?;
+                    }
+                } else {
+                    // Choice variants name their type in lower camel case
+                    // (`valueCodeableConcept` -> `codeableConcept`)
+                    let mut child_type = child_type.clone();
+                    if !type_name.contains('.') {
+                        let mut chars = type_name.chars();
+                        child_type.name = chars
+                            .next()
+                            .map(|c| c.to_uppercase().chain(chars).collect());
+                    }
+                    check_primitives(model_provider, child_type, item, item_path).await?;
+                }
+            }
+        }
+        Ok(())
+    })
+}
+
 /// Converts a ValueAccessor to serde_json::Value.
 pub(crate) fn value_accessor_to_json(
     value: &ValueAccessor<'_>,
@@ -223,6 +325,7 @@ pub(crate) fn storage_error_to_graphql(
         ErrorCategory::Infrastructure => ("infrastructure", "fatal", "transient"),
         ErrorCategory::Internal => ("internal", "fatal", "exception"),
         ErrorCategory::Deleted => ("deleted", "error", "deleted"),
+        ErrorCategory::Busy => ("busy", "error", "transient"),
     };
 
     // Create error with extensions
@@ -296,8 +399,66 @@ mod tests {
         assert_eq!(result, serde_json::json!({"key": "value"}));
     }
 
+    fn test_model_provider() -> DynModelProvider {
+        let schemas =
+            octofhir_fhirschema::get_schemas(octofhir_fhirschema::FhirVersion::R4).clone();
+        std::sync::Arc::new(octofhir_fhirschema::FhirSchemaModelProvider::new(
+            schemas,
+            octofhir_fhir_model::provider::FhirVersion::R4,
+        ))
+    }
+
     #[test]
     fn test_create_resolver_created() {
-        let _resolver = CreateResolver::resolve("Patient".to_string());
+        let _resolver = CreateResolver::resolve("Patient".to_string(), test_model_provider());
+    }
+
+    #[tokio::test]
+    async fn test_validate_resource_primitives() {
+        let provider = test_model_provider();
+
+        let valid = serde_json::json!({
+            "resourceType": "Patient",
+            "gender": "female",
+            "birthDate": "1980-01-01",
+            "telecom": [{"system": "phone", "value": "555-0100"}],
+            "deceasedDateTime": "2020-05-01T10:00:00Z"
+        });
+        assert!(
+            validate_resource_primitives(&provider, &valid)
+                .await
+                .is_ok()
+        );
+
+        let bad_date = serde_json::json!({"resourceType": "Patient", "birthDate": "not-a-date"});
+        let err = validate_resource_primitives(&provider, &bad_date)
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("Patient.birthDate"), "{}", err.message);
+
+        let bad_code = serde_json::json!({
+            "resourceType": "Patient",
+            "telecom": [{"system": " phone"}]
+        });
+        let err = validate_resource_primitives(&provider, &bad_code)
+            .await
+            .unwrap_err();
+        assert!(
+            err.message.contains("Patient.telecom[0].system"),
+            "{}",
+            err.message
+        );
+
+        let bad_instant = serde_json::json!({
+            "resourceType": "Observation",
+            "status": "final",
+            "code": {"text": "x"},
+            "issued": "2024-01-15T10:30:00"
+        });
+        assert!(
+            validate_resource_primitives(&provider, &bad_instant)
+                .await
+                .is_err()
+        );
     }
 }
@@ -6,8 +6,12 @@ use async_graphql::dynamic::{FieldFuture, ResolverContext};
 use octofhir_auth::smart::scopes::FhirOperation;
 use tracing::{debug, trace, warn};
 
-use super::create::{extract_resource_from_input, storage_error_to_graphql};
+use super::create::{
+    extract_resource_from_input, storage_error_to_graphql, validate_resource_primitives,
+};
 use super::{evaluate_access_with_resource, get_graphql_context, json_to_graphql_value};
+use crate::schema::DynModelProvider;
+use crate::validation::{check_resource_size, validate_structure};
 
 /// Resolver for resource update mutations.
 ///
@@ -27,11 +31,14 @@ impl UpdateResolver {
     ///
     /// # Arguments
     /// * `resource_type` - The FHIR resource type (e.g., "Patient")
+    /// * `model_provider` - Element types for validating primitive values
     pub fn resolve(
         resource_type: String,
+        model_provider: DynModelProvider,
     ) -> impl Fn(ResolverContext<'_>) -> FieldFuture<'_> + Send + Sync + Clone {
         move |ctx| {
             let resource_type = resource_type.clone();
+            let model_provider = model_provider.clone();
 
             FieldFuture::new(async move {
                 debug!(resource_type = %resource_type, "Processing update mutation");
@@ -72,6 +79,10 @@ impl UpdateResolver {
                     );
                 }
 
+                check_resource_size(gql_ctx.max_resource_size, &resource_json)?;
+                validate_resource_primitives(&model_provider, &resource_json).await?;
+                validate_structure(gql_ctx, &resource_json).await?;
+
                 // Evaluate access control with the resource being updated
                 evaluate_access_with_resource(
                     gql_ctx,
@@ -120,6 +131,8 @@ mod tests {
 
     #[test]
     fn test_update_resolver_created() {
-        let _resolver = UpdateResolver::resolve("Patient".to_string());
+        let provider: DynModelProvider =
+            std::sync::Arc::new(octofhir_fhir_model::provider::EmptyModelProvider);
+        let _resolver = UpdateResolver::resolve("Patient".to_string(), provider);
     }
 }
@@ -27,7 +27,7 @@ use crate::resolvers::{
 use crate::subscriptions::{
     ResourceEventBroadcaster, build_subscription_type, create_resource_change_event_type,
 };
-use crate::types::{create_all_resources_union, create_reference_type};
+use crate::types::{create_all_resources_union, create_reference_type, validate_scalar};
 
 /// The name of the custom JSON scalar type used for FHIR resources.
 /// Kept for backwards compatibility but no longer primary mechanism.
@@ -277,6 +277,7 @@ impl FhirSchemaBuilder {
             ("FhirOid", "A FHIR OID (urn:oid:...)"),
             ("FhirUuid", "A FHIR UUID (urn:uuid:...)"),
             ("FhirId", "A FHIR resource ID"),
+            ("FhirCode", "A FHIR code (tokens separated by single spaces)"),
             ("FhirBase64Binary", "Base64-encoded binary data"),
             ("FhirMarkdown", "Markdown-formatted text"),
             ("FhirPositiveInt", "A positive integer (> 0)"),
@@ -289,7 +290,9 @@ impl FhirSchemaBuilder {
 
         let mut builder = builder;
         for (name, description) in scalars {
-            let scalar = Scalar::new(name).description(description);
+            let scalar = Scalar::new(name)
+                .description(description)
+                .validator(move |value| validate_scalar(name, value).is_ok());
             builder = builder.register(scalar);
         }
 
@@ -987,7 +990,8 @@ impl FhirSchemaBuilder {
         let create_field_name = format!("{}Create", resource_type);
         let input_type_name = format!("{}Input", resource_type);
 
-        let create_resolver = CreateResolver::resolve(resource_type.to_string());
+        let create_resolver =
+            CreateResolver::resolve(resource_type.to_string(), self.model_provider.clone());
         let create_field = Field::new(
             &create_field_name,
             TypeRef::named(resource_type),
@@ -1004,7 +1008,8 @@ impl FhirSchemaBuilder {
         // Update mutation: PatientUpdate(id: ID!, res: PatientInput!, ifMatch: String): Patient
         let update_field_name = format!("{}Update", resource_type);
 
-        let update_resolver = UpdateResolver::resolve(resource_type.to_string());
+        let update_resolver =
+            UpdateResolver::resolve(resource_type.to_string(), self.model_provider.clone());
         let update_field = Field::new(
             &update_field_name,
             TypeRef::named(resource_type),
@@ -1109,6 +1114,10 @@ mod tests {
             sdl.contains("scalar FhirId"),
             "Schema should have FhirId scalar"
         );
+        assert!(
+            sdl.contains("scalar FhirCode"),
+            "Schema should have FhirCode scalar"
+        );
     }
 
     #[tokio::test]
@@ -9,10 +9,13 @@
 //! built-in scalars. This module provides custom scalar implementations for:
 //!
 //! - Date/Time: `FhirInstant`, `FhirDateTime`, `FhirDate`, `FhirTime`
-//! - Identifiers: `FhirUri`, `FhirUrl`, `FhirCanonical`, `FhirOid`, `FhirUuid`, `FhirId`
+//! - Identifiers: `FhirUri`, `FhirUrl`, `FhirCanonical`, `FhirOid`, `FhirUuid`, `FhirId`, `FhirCode`
 //! - Numbers: `FhirPositiveInt`, `FhirUnsignedInt`, `FhirDecimal`
 //! - Other: `FhirBase64Binary`, `FhirMarkdown`, `FhirXhtml`
 //!
+//! [`validate_primitive`] runs the same parsers over primitive values inside
+//! mutation input, so malformed values are rejected before they reach storage.
+//!
 //! ## Reference Type
 //!
 //! The module also provides the FHIR Reference type with lazy resource resolution:
@@ -25,7 +28,7 @@ mod scalars;
 
 pub use reference::{create_all_resources_union, create_reference_type};
 pub use scalars::{
-    FhirBase64Binary, FhirCanonical, FhirDate, FhirDateTime, FhirDecimal, FhirId, FhirInstant,
-    FhirMarkdown, FhirOid, FhirPositiveInt, FhirTime, FhirUnsignedInt, FhirUri, FhirUrl, FhirUuid,
-    FhirXhtml,
+    FhirBase64Binary, FhirCanonical, FhirCode, FhirDate, FhirDateTime, FhirDecimal, FhirId,
+    FhirInstant, FhirMarkdown, FhirOid, FhirPositiveInt, FhirTime, FhirUnsignedInt, FhirUri,
+    FhirUrl, FhirUuid, FhirXhtml, validate_primitive, validate_scalar,
 };
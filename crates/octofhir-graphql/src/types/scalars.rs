@@ -7,7 +7,7 @@
 //!
 //! Reference: <https://www.hl7.org/fhir/datatypes.html#primitive>
 
-use async_graphql::{InputValueError, InputValueResult, Scalar, ScalarType, Value};
+use async_graphql::{InputValueError, InputValueResult, Pos, Scalar, ScalarType, Value};
 use std::sync::LazyLock;
 
 // =============================================================================
@@ -59,6 +59,77 @@ static UUID_REGEX: LazyLock<regex::Regex> = LazyLock::new(|| {
     .expect("Invalid uuid regex")
 });
 
+/// FHIR code regex: no leading, trailing or repeated whitespace
+static CODE_REGEX: LazyLock<regex::Regex> =
+    LazyLock::new(|| regex::Regex::new(r"^[^\s]+( [^\s]+)*$").expect("Invalid code regex"));
+
+/// URI scheme prefix, e.g. `http:` or `urn:`
+static URI_SCHEME_REGEX: LazyLock<regex::Regex> = LazyLock::new(|| {
+    regex::Regex::new(r"^[A-Za-z][A-Za-z0-9+.\-]*:").expect("Invalid uri scheme regex")
+});
+
+// =============================================================================
+// Validation entry points
+// =============================================================================
+
+/// Validates `value` with the parser of the scalar registered as
+/// `scalar_name`. Names that are not FHIR scalars accept anything.
+pub fn validate_scalar(scalar_name: &str, value: &Value) -> Result<(), String> {
+    fn check<T: ScalarType + async_graphql::InputType>(value: &Value) -> Result<(), String> {
+        <T as ScalarType>::parse(value.clone())
+            .map(|_| ())
+            .map_err(|e| e.into_server_error(Pos::default()).message)
+    }
+
+    match scalar_name {
+        "FhirInstant" => check::<FhirInstant>(value),
+        "FhirDateTime" => check::<FhirDateTime>(value),
+        "FhirDate" => check::<FhirDate>(value),
+        "FhirTime" => check::<FhirTime>(value),
+        "FhirUri" => check::<FhirUri>(value),
+        "FhirUrl" => check::<FhirUrl>(value),
+        "FhirCanonical" => check::<FhirCanonical>(value),
+        "FhirOid" => check::<FhirOid>(value),
+        "FhirUuid" => check::<FhirUuid>(value),
+        "FhirId" => check::<FhirId>(value),
+        "FhirCode" => check::<FhirCode>(value),
+        "FhirBase64Binary" => check::<FhirBase64Binary>(value),
+        "FhirMarkdown" => check::<FhirMarkdown>(value),
+        "FhirPositiveInt" => check::<FhirPositiveInt>(value),
+        "FhirUnsignedInt" => check::<FhirUnsignedInt>(value),
+        "FhirDecimal" => check::<FhirDecimal>(value),
+        "FhirXhtml" => check::<FhirXhtml>(value),
+        _ => Ok(()),
+    }
+}
+
+/// Validates a JSON value of the FHIR primitive type `fhir_type` (`date`,
+/// `code`, ...) with the matching scalar's parser.
+pub fn validate_primitive(fhir_type: &str, value: &serde_json::Value) -> Result<(), String> {
+    let scalar_name = match fhir_type {
+        "instant" => "FhirInstant",
+        "dateTime" => "FhirDateTime",
+        "date" => "FhirDate",
+        "time" => "FhirTime",
+        "uri" => "FhirUri",
+        "url" => "FhirUrl",
+        "canonical" => "FhirCanonical",
+        "oid" => "FhirOid",
+        "uuid" => "FhirUuid",
+        "id" => "FhirId",
+        "code" => "FhirCode",
+        "base64Binary" => "FhirBase64Binary",
+        "markdown" => "FhirMarkdown",
+        "positiveInt" => "FhirPositiveInt",
+        "unsignedInt" => "FhirUnsignedInt",
+        "decimal" => "FhirDecimal",
+        "xhtml" => "FhirXhtml",
+        _ => return Ok(()),
+    };
+    let value = Value::from_json(value.clone()).map_err(|e| e.to_string())?;
+    validate_scalar(scalar_name, &value)
+}
+
 // =============================================================================
 // FhirInstant - xs:dateTime with timezone (required)
 // =============================================================================
@@ -231,6 +302,8 @@ impl ScalarType for FhirTime {
 ///
 /// A Uniform Resource Identifier (RFC 3986). URIs are case-sensitive.
 /// URIs can be absolute or relative and may have an optional fragment.
+/// They never contain whitespace, and one with a scheme must parse as an
+/// absolute URI.
 ///
 /// # Examples
 /// - `http://hl7.org/fhir/Patient`
@@ -244,9 +317,20 @@ impl ScalarType for FhirUri {
     fn parse(value: Value) -> InputValueResult<Self> {
         match value {
             Value::String(s) => {
-                // FHIR URIs are quite permissive, just ensure non-empty
                 if s.is_empty() {
                     Err(InputValueError::custom("FHIR uri cannot be empty"))
+                } else if s.contains(char::is_whitespace) {
+                    Err(InputValueError::custom(format!(
+                        "Invalid FHIR uri: '{}'. URIs cannot contain whitespace",
+                        s
+                    )))
+                } else if URI_SCHEME_REGEX.is_match(&s)
+                    && let Err(e) = url::Url::parse(&s)
+                {
+                    Err(InputValueError::custom(format!(
+                        "Invalid FHIR uri: '{}'. {}",
+                        s, e
+                    )))
                 } else {
                     Ok(FhirUri(s))
                 }
@@ -454,6 +538,44 @@ impl ScalarType for FhirId {
     }
 }
 
+// =============================================================================
+// FhirCode - coded value
+// =============================================================================
+
+/// FHIR `code` type - a value taken from a set of controlled strings.
+///
+/// Tokens separated by single spaces: no leading, trailing or repeated
+/// whitespace.
+///
+/// # Examples
+/// - `final`
+/// - `entered-in-error`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FhirCode(pub String);
+
+#[Scalar(name = "FhirCode")]
+impl ScalarType for FhirCode {
+    fn parse(value: Value) -> InputValueResult<Self> {
+        match value {
+            Value::String(s) => {
+                if CODE_REGEX.is_match(&s) {
+                    Ok(FhirCode(s))
+                } else {
+                    Err(InputValueError::custom(format!(
+                        "Invalid FHIR code: '{}'. Codes cannot be empty or have leading, trailing or repeated whitespace",
+                        s
+                    )))
+                }
+            }
+            _ => Err(InputValueError::expected_type(value)),
+        }
+    }
+
+    fn to_value(&self) -> Value {
+        Value::String(self.0.clone())
+    }
+}
+
 // =============================================================================
 // FhirBase64Binary - base64-encoded binary data
 // =============================================================================
@@ -1045,6 +1167,76 @@ mod tests {
         }
     }
 
+    // -------------------------------------------------------------------------
+    // FhirUri tests
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_fhir_uri_valid() {
+        let valid = vec![
+            "http://hl7.org/fhir/Patient",
+            "urn:uuid:53fefa32-fcbb-4ff8-8a92-55ee120877b7",
+            "urn:oid:1.2.3",
+            "#local-reference",
+            "Patient/123",
+        ];
+        for s in valid {
+            let result = FhirUri::parse(Value::String(s.to_string()));
+            assert!(result.is_ok(), "Expected valid uri: {}", s);
+        }
+    }
+
+    #[test]
+    fn test_fhir_uri_invalid() {
+        let invalid = vec!["", "http://example.com/a b", "http://", " urn:x"];
+        for s in invalid {
+            let result = FhirUri::parse(Value::String(s.to_string()));
+            assert!(result.is_err(), "Expected invalid uri: {}", s);
+        }
+    }
+
+    // -------------------------------------------------------------------------
+    // FhirCode tests
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_fhir_code_valid() {
+        let valid = vec!["final", "entered-in-error", "a b"];
+        for s in valid {
+            let result = FhirCode::parse(Value::String(s.to_string()));
+            assert!(result.is_ok(), "Expected valid code: {}", s);
+        }
+    }
+
+    #[test]
+    fn test_fhir_code_invalid() {
+        let invalid = vec!["", " final", "final ", "a  b", "a\tb"];
+        for s in invalid {
+            let result = FhirCode::parse(Value::String(s.to_string()));
+            assert!(result.is_err(), "Expected invalid code: {:?}", s);
+        }
+    }
+
+    // -------------------------------------------------------------------------
+    // validate_primitive tests
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_validate_primitive() {
+        use serde_json::json;
+
+        assert!(validate_primitive("date", &json!("2024-01-15")).is_ok());
+        assert!(validate_primitive("date", &json!("not-a-date")).is_err());
+        assert!(validate_primitive("instant", &json!("2024-01-15T10:30:00")).is_err());
+        assert!(validate_primitive("code", &json!("fin al")).is_ok());
+        assert!(validate_primitive("code", &json!(" final")).is_err());
+        assert!(validate_primitive("positiveInt", &json!(0)).is_err());
+        assert!(validate_primitive("boolean", &json!("yes")).is_ok());
+
+        let err = validate_primitive("date", &json!("not-a-date")).unwrap_err();
+        assert!(err.contains("not-a-date"), "{err}");
+    }
+
     // -------------------------------------------------------------------------
     // Output serialization tests
     // -------------------------------------------------------------------------
@@ -0,0 +1,189 @@
+//! Structural validation of mutation input.
+//!
+//! Create and update mutations always check primitive values against the
+//! model. When the server supplies a [`ResourceValidator`], the resource is
+//! also validated against its StructureDefinition (cardinality, required
+//! elements, bindings, invariants) before it is written, the same way the
+//! REST API validates it.
+
+use std::sync::Arc;
+
+use async_graphql::{ErrorExtensions, Name, Value};
+use async_trait::async_trait;
+
+use crate::context::GraphQLContext;
+
+/// One problem found in a resource.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationIssue {
+    /// FHIRPath of the failing element, if known (e.g. `Patient.name[0].given`).
+    pub path: Option<String>,
+    /// Human-readable description.
+    pub message: String,
+}
+
+/// Validates resources before GraphQL mutations store them.
+#[async_trait]
+pub trait ResourceValidator: Send + Sync {
+    /// Validates `resource` and returns the issues that make it invalid.
+    ///
+    /// An empty list means the resource may be stored. Warnings and
+    /// informational findings must not be returned.
+    async fn validate(&self, resource: &serde_json::Value) -> Vec<ValidationIssue>;
+}
+
+/// Shared validator handle.
+pub type DynResourceValidator = Arc<dyn ResourceValidator>;
+
+/// Runs the context's validator, if any, over a resource about to be written.
+pub(crate) async fn validate_structure(
+    gql_ctx: &GraphQLContext,
+    resource: &serde_json::Value,
+) -> Result<(), async_graphql::Error> {
+    let Some(validator) = &gql_ctx.validator else {
+        return Ok(());
+    };
+    let issues = validator.validate(resource).await;
+    if issues.is_empty() {
+        Ok(())
+    } else {
+        Err(validation_error(&issues))
+    }
+}
+
+/// Rejects a resource whose serialized JSON exceeds `max_bytes`, the same
+/// limit the REST API enforces with 413. `0` disables the check.
+pub(crate) fn check_resource_size(
+    max_bytes: usize,
+    resource: &serde_json::Value,
+) -> Result<(), async_graphql::Error> {
+    if max_bytes == 0 {
+        return Ok(());
+    }
+    let size = octofhir_core::serialized_size(resource);
+    if size > max_bytes {
+        return Err(validation_error(&[ValidationIssue {
+            path: None,
+            message: format!(
+                "Resource is {size} bytes, exceeding the maximum of {max_bytes} bytes"
+            ),
+        }]));
+    }
+    Ok(())
+}
+
+/// Builds the GraphQL error for a failed validation.
+///
+/// Extensions carry `category: "validation"`, the failing `paths`, and an
+/// `operationOutcome` with one issue per problem, matching what the REST API
+/// returns for the same resource.
+pub(crate) fn validation_error(issues: &[ValidationIssue]) -> async_graphql::Error {
+    let message = match issues {
+        [issue] => format!("Resource validation failed: {}", describe(issue)),
+        _ => format!(
+            "Resource validation failed with {} issues: {}",
+            issues.len(),
+            issues.iter().map(describe).collect::<Vec<_>>().join("; ")
+        ),
+    };
+
+    let paths: Vec<Value> = issues
+        .iter()
+        .filter_map(|issue| issue.path.clone().map(Value::String))
+        .collect();
+    let outcome_issues: Vec<Value> = issues
+        .iter()
+        .map(|issue| {
+            let mut map = async_graphql::indexmap::IndexMap::new();
+            map.insert(Name::new("severity"), Value::String("error".to_string()));
+            map.insert(Name::new("code"), Value::String("invalid".to_string()));
+            map.insert(
+                Name::new("diagnostics"),
+                Value::String(issue.message.clone()),
+            );
+            if let Some(path) = &issue.path {
+                map.insert(
+                    Name::new("expression"),
+                    Value::List(vec![Value::String(path.clone())]),
+                );
+            }
+            Value::Object(map)
+        })
+        .collect();
+
+    async_graphql::Error::new(message).extend_with(|_, e| {
+        e.set("category", "validation");
+        e.set("paths", Value::List(paths));
+        e.set(
+            "operationOutcome",
+            Value::Object({
+                let mut map = async_graphql::indexmap::IndexMap::new();
+                map.insert(
+                    Name::new("resourceType"),
+                    Value::String("OperationOutcome".to_string()),
+                );
+                map.insert(Name::new("issue"), Value::List(outcome_issues));
+                map
+            }),
+        );
+    })
+}
+
+fn describe(issue: &ValidationIssue) -> String {
+    match &issue.path {
+        Some(path) => format!("{path}: {}", issue.message),
+        None => issue.message.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validation_error_lists_paths() {
+        let issues = vec![
+            ValidationIssue {
+                path: Some("Observation.status".to_string()),
+                message: "minimum required = 1, but only found 0".to_string(),
+            },
+            ValidationIssue {
+                path: None,
+                message: "unknown profile".to_string(),
+            },
+        ];
+        let err = validation_error(&issues);
+        assert!(err.message.contains("2 issues"), "{}", err.message);
+        assert!(err.message.contains("Observation.status: minimum"));
+
+        let extensions = err.extensions.expect("extensions");
+        assert_eq!(
+            extensions.get("paths"),
+            Some(&Value::List(vec![Value::String(
+                "Observation.status".to_string()
+            )]))
+        );
+        let Some(Value::Object(outcome)) = extensions.get("operationOutcome") else {
+            panic!("missing operationOutcome");
+        };
+        let Some(Value::List(outcome_issues)) = outcome.get("issue") else {
+            panic!("missing issues");
+        };
+        assert_eq!(outcome_issues.len(), 2);
+    }
+
+    #[test]
+    fn test_check_resource_size() {
+        let resource = serde_json::json!({"resourceType": "Patient", "id": "p1"});
+        let size = octofhir_core::serialized_size(&resource);
+
+        assert!(check_resource_size(0, &resource).is_ok());
+        assert!(check_resource_size(size, &resource).is_ok());
+        let err = check_resource_size(size - 1, &resource).unwrap_err();
+        assert!(
+            err.message.contains("exceeding the maximum"),
+            "{}",
+            err.message
+        );
+    }
+}
@@ -1,6 +1,7 @@
 use async_trait::async_trait;
 use hmac::{Hmac, Mac};
-use reqwest::Client;
+use octofhir_core::WebhookSignature;
+use octofhir_core::http::{HttpClient, HttpClientConfig};
 use serde_json::json;
 use sha2::Sha256;
 use time::OffsetDateTime;
@@ -13,14 +14,19 @@ use crate::types::Notification;
 type HmacSha256 = Hmac<Sha256>;
 
 pub struct WebhookAdapter {
-    http_client: Client,
+    http_client: HttpClient,
 }
 
 impl WebhookAdapter {
     pub fn new() -> Self {
-        Self {
-            http_client: Client::new(),
-        }
+        Self::with_client(
+            HttpClient::new(&HttpClientConfig::default()).expect("Failed to create HTTP client"),
+        )
+    }
+
+    /// Deliver through `http_client`, e.g. one shared with other outbound calls
+    pub fn with_client(http_client: HttpClient) -> Self {
+        Self { http_client }
     }
 
     fn sign_payload(&self, payload: &str, secret: &str) -> String {
@@ -88,15 +94,21 @@ impl NotificationAdapter for WebhookAdapter {
             }
         }
 
-        // Add HMAC signature if secret is configured
+        // Add HMAC signatures if secret is configured: `X-Signature` covers
+        // a timestamp and nonce against replay, `X-Signature-256` the payload
+        // alone for receivers predating it
         if let Some(secret) = &config.webhook_secret {
+            for (name, value) in WebhookSignature::sign(secret, payload_str.as_bytes()).headers() {
+                request = request.header(name, value);
+            }
             let signature = self.sign_payload(&payload_str, secret);
             request = request.header("X-Signature-256", format!("sha256={}", signature));
         }
 
-        let response = request
-            .body(payload_str)
-            .send()
+        // Retried on 429/503 only: the POST may already have been delivered
+        let response = self
+            .http_client
+            .send(request.body(payload_str))
             .await
             .map_err(|e| NotificationError::SendFailed(e.to_string()))?;
 
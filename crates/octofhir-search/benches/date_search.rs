@@ -287,6 +287,7 @@ fn bench_native_ir_query_build_render_with_debug_plan(c: &mut Criterion) {
     let config = ParamsSearchConfig {
         unknown_param_handling: UnknownParamHandling::Lenient,
         collect_debug_plan: true,
+        ..Default::default()
     };
     let mut group = c.benchmark_group("native_ir_query_build_render_debug_plan");
 
@@ -769,6 +769,10 @@ impl ReferenceClause {
         resource_type: &str,
         target_types: &[String],
     ) -> Result<Vec<Self>, SqlBuilderError> {
+        if let Some(SearchModifier::Type(type_name)) = &param.modifier {
+            validate_reference_type_modifier(&param.name, type_name, target_types)?;
+        }
+
         let mut clauses = Vec::new();
 
         for value in &param.values {
@@ -803,6 +807,22 @@ impl ReferenceClause {
     }
 }
 
+/// Reject a `:Type` modifier naming a resource type the reference parameter
+/// cannot point to. Parameters without declared targets accept any type.
+pub(crate) fn validate_reference_type_modifier(
+    param_name: &str,
+    type_name: &str,
+    target_types: &[String],
+) -> Result<(), SqlBuilderError> {
+    if target_types.is_empty() || target_types.iter().any(|t| t == type_name) {
+        return Ok(());
+    }
+    Err(SqlBuilderError::InvalidSearchValue(format!(
+        "resource type '{type_name}' is not a target of search parameter '{param_name}' (allowed: {})",
+        target_types.join(", ")
+    )))
+}
+
 fn parse_reference_predicate(raw: &str, target_types: &[String]) -> ReferencePredicate {
     if raw.starts_with("http://") || raw.starts_with("https://") {
         return ReferencePredicate::External {
@@ -896,4 +916,28 @@ mod tests {
             assert_eq!(clauses[0].predicate, expected);
         }
     }
+
+    #[test]
+    fn reference_type_modifier_must_be_a_declared_target() {
+        let targets = vec!["Group".to_string(), "Patient".to_string()];
+        let mut param = parsed("123");
+        param.name = "subject".to_string();
+
+        param.modifier = Some(SearchModifier::Type("Patient".to_string()));
+        let clauses = ReferenceClause::from_parsed_param(&param, "Observation", &targets).unwrap();
+        assert_eq!(
+            clauses[0].predicate,
+            ReferencePredicate::Local {
+                target_type: Some("Patient".to_string()),
+                target_id: "123".to_string(),
+            }
+        );
+
+        param.modifier = Some(SearchModifier::Type("Device".to_string()));
+        let err = ReferenceClause::from_parsed_param(&param, "Observation", &targets).unwrap_err();
+        assert!(matches!(err, SqlBuilderError::InvalidSearchValue(_)));
+        assert!(err.to_string().contains("allowed: Group, Patient"));
+
+        assert!(ReferenceClause::from_parsed_param(&param, "Observation", &[]).is_ok());
+    }
 }
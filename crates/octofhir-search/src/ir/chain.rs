@@ -36,6 +36,15 @@ pub enum ChainError {
     #[error("Ambiguous chain: parameter {0} has multiple targets, use :Type modifier")]
     AmbiguousTarget(String),
 
+    #[error("Chain of {depth} references exceeds the maximum chain depth of {max}")]
+    ChainTooDeep { depth: usize, max: usize },
+
+    #[error("Chain revisits {resource_type} through {param}; reference cycles are not allowed")]
+    ReferenceCycle {
+        param: String,
+        resource_type: String,
+    },
+
     #[error("SQL builder error: {0}")]
     SqlBuilder(#[from] SqlBuilderError),
 }
@@ -44,6 +53,11 @@ pub enum ChainError {
 // Forward chaining: `subject:Patient.name=Smith`
 // ============================================================================
 
+/// Default maximum number of reference links in one chained parameter
+/// (`subject.organization.partof.name` has three). Each link adds a nested
+/// EXISTS join, so unbounded chains would produce enormous queries.
+pub const DEFAULT_MAX_CHAIN_DEPTH: usize = 3;
+
 /// A single reference link in a chained search parameter.
 #[derive(Debug, Clone)]
 pub struct ChainLink {
@@ -79,12 +93,35 @@ fn parse_parameter_with_type(part: &str) -> (&str, Option<&str>) {
 }
 
 impl ChainClause {
-    /// Parse `param1.param2…=value` (each leading link a reference parameter).
+    /// Parse `param1.param2…=value` (each leading link a reference parameter),
+    /// allowing up to [`DEFAULT_MAX_CHAIN_DEPTH`] links.
     pub fn parse(
         name: &str,
         value: &str,
         registry: &SearchParameterRegistry,
         resource_type: &str,
+    ) -> Result<Self, ChainError> {
+        Self::parse_with_max_depth(
+            name,
+            value,
+            registry,
+            resource_type,
+            DEFAULT_MAX_CHAIN_DEPTH,
+        )
+    }
+
+    /// Parse a chained parameter allowing at most `max_depth` reference links.
+    ///
+    /// Chains that come back to a resource type visited earlier on the path
+    /// (`Patient → RelatedPerson → Patient`) are rejected as reference
+    /// cycles. Self-references such as `Organization?partof.partof.name`
+    /// are allowed; the depth limit bounds them.
+    pub fn parse_with_max_depth(
+        name: &str,
+        value: &str,
+        registry: &SearchParameterRegistry,
+        resource_type: &str,
+        max_depth: usize,
     ) -> Result<Self, ChainError> {
         let parts: Vec<&str> = name.split('.').collect();
         if parts.len() < 2 {
@@ -92,9 +129,17 @@ impl ChainClause {
                 "Chained parameter requires at least two parts".to_string(),
             ));
         }
+        let depth = parts.len() - 1;
+        if depth > max_depth {
+            return Err(ChainError::ChainTooDeep {
+                depth,
+                max: max_depth,
+            });
+        }
 
         let mut chain = Vec::new();
         let mut current_type = resource_type.to_string();
+        let mut visited = vec![resource_type.to_string()];
 
         for (i, part) in parts.iter().take(parts.len() - 1).enumerate() {
             let (param_name, target_type) = parse_parameter_with_type(part);
@@ -129,7 +174,18 @@ impl ChainClause {
                 expression,
             });
 
+            let returns_to_earlier = resolved_type
+                .as_ref()
+                .is_some_and(|t| *t != current_type && visited.contains(t));
+            if returns_to_earlier {
+                return Err(ChainError::ReferenceCycle {
+                    param: param_name.to_string(),
+                    resource_type: resolved_type.unwrap_or(current_type),
+                });
+            }
+
             if let Some(t) = resolved_type {
+                visited.push(t.clone());
                 current_type = t;
             }
         }
@@ -220,7 +276,7 @@ fn render_nested_chain(
     Ok(SqlExpr::Exists(Box::new(SelectStmt {
         projection: vec![SqlTerm::Integer(1)],
         from: SqlFrom {
-            table: format!("\"{target_table}\""),
+            table: builder.table_ref(&target_table),
             alias: Some(alias.clone()),
         },
         where_clause: Some(SqlExpr::And(vec![
@@ -404,7 +460,7 @@ fn render_has_level(
     Ok(SqlExpr::Exists(Box::new(SelectStmt {
         projection: vec![SqlTerm::Integer(1)],
         from: SqlFrom {
-            table: format!("\"{source_table}\""),
+            table: builder.table_ref(&source_table),
             alias: Some(src_alias.clone()),
         },
         where_clause: Some(SqlExpr::And(vec![
@@ -546,6 +602,55 @@ mod tests {
         .with_expression("Organization.name");
         registry.register(org_name);
 
+        let endpoint = SearchParameter::new(
+            "endpoint",
+            "http://hl7.org/fhir/SearchParameter/Organization-endpoint",
+            SearchParameterType::Reference,
+            vec!["Organization".to_string()],
+        )
+        .with_expression("Organization.endpoint")
+        .with_targets(vec!["Endpoint".to_string()]);
+        registry.register(endpoint);
+
+        let partof = SearchParameter::new(
+            "partof",
+            "http://hl7.org/fhir/SearchParameter/Organization-partof",
+            SearchParameterType::Reference,
+            vec!["Organization".to_string()],
+        )
+        .with_expression("Organization.partOf")
+        .with_targets(vec!["Organization".to_string()]);
+        registry.register(partof);
+
+        let link = SearchParameter::new(
+            "link",
+            "http://hl7.org/fhir/SearchParameter/Patient-link",
+            SearchParameterType::Reference,
+            vec!["Patient".to_string()],
+        )
+        .with_expression("Patient.link.other")
+        .with_targets(vec!["Patient".to_string(), "RelatedPerson".to_string()]);
+        registry.register(link);
+
+        let related_patient = SearchParameter::new(
+            "patient",
+            "http://hl7.org/fhir/SearchParameter/RelatedPerson-patient",
+            SearchParameterType::Reference,
+            vec!["RelatedPerson".to_string()],
+        )
+        .with_expression("RelatedPerson.patient")
+        .with_targets(vec!["Patient".to_string()]);
+        registry.register(related_patient);
+
+        let endpoint_name = SearchParameter::new(
+            "name",
+            "http://hl7.org/fhir/SearchParameter/Endpoint-name",
+            SearchParameterType::String,
+            vec!["Endpoint".to_string()],
+        )
+        .with_expression("Endpoint.name");
+        registry.register(endpoint_name);
+
         let code = SearchParameter::new(
             "code",
             "http://hl7.org/fhir/SearchParameter/Observation-code",
@@ -591,6 +696,17 @@ mod tests {
         assert!(sql.contains("chain0.resource"));
     }
 
+    #[test]
+    fn chain_target_is_qualified_with_builder_schema() {
+        let reg = registry();
+        let clause =
+            ChainClause::parse("subject:Patient.name", "Smith", &reg, "Observation").unwrap();
+        let mut builder = SqlBuilder::new().with_schema("tenant_acme");
+        render_chain_clause(&mut builder, &clause, "Observation", &reg).unwrap();
+        let sql = builder.build_where_clause().unwrap();
+        assert!(sql.contains("\"tenant_acme\".\"patient\""));
+    }
+
     #[test]
     fn multi_level_chain_renders_inplace() {
         let reg = registry();
@@ -612,6 +728,73 @@ mod tests {
         assert!(sql.contains("\"organization\""));
     }
 
+    #[test]
+    fn three_level_chain_within_limit() {
+        let reg = registry();
+        let clause = ChainClause::parse(
+            "subject:Patient.general-practitioner:Organization.endpoint.name",
+            "Gateway",
+            &reg,
+            "Observation",
+        )
+        .unwrap();
+        assert_eq!(clause.chain.len(), 3);
+        let mut builder = SqlBuilder::new();
+        render_chain_clause(&mut builder, &clause, "Observation", &reg).unwrap();
+        let sql = builder.build_where_clause().unwrap();
+        assert!(sql.contains("chain2"));
+        assert!(sql.contains("\"endpoint\""));
+    }
+
+    #[test]
+    fn four_level_chain_exceeds_limit() {
+        let reg = registry();
+        let err = ChainClause::parse(
+            "subject:Patient.general-practitioner:Organization.partof.endpoint.name",
+            "Gateway",
+            &reg,
+            "Observation",
+        )
+        .unwrap_err();
+        assert!(matches!(err, ChainError::ChainTooDeep { depth: 4, max: 3 }));
+    }
+
+    #[test]
+    fn chain_depth_is_configurable() {
+        let reg = registry();
+        let err = ChainClause::parse_with_max_depth(
+            "subject:Patient.general-practitioner:Organization.name",
+            "Acme",
+            &reg,
+            "Observation",
+            1,
+        )
+        .unwrap_err();
+        assert!(matches!(err, ChainError::ChainTooDeep { depth: 2, max: 1 }));
+    }
+
+    #[test]
+    fn chain_reference_cycle_fails() {
+        let reg = registry();
+        let err = ChainClause::parse("link:RelatedPerson.patient.name", "Smith", &reg, "Patient")
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            ChainError::ReferenceCycle { ref resource_type, .. } if resource_type == "Patient"
+        ));
+    }
+
+    #[test]
+    fn chain_self_reference_allowed() {
+        let reg = registry();
+        let clause = ChainClause::parse("partof.name", "Acme", &reg, "Organization").unwrap();
+        assert_eq!(clause.chain.len(), 1);
+
+        let clause =
+            ChainClause::parse("partof.partof.name", "Acme", &reg, "Organization").unwrap();
+        assert_eq!(clause.chain.len(), 2);
+    }
+
     #[test]
     fn chain_through_non_reference_fails() {
         let reg = registry();
@@ -528,31 +528,26 @@ fn debug_reference_clause(resource_type: &str, clause: &ReferenceClause) -> Debu
         ReferencePredicate::Local { .. } | ReferencePredicate::External { .. } => {
             reference_overlap()
         }
-        // `:identifier` matches the embedded identifier element via full
-        // resource containment, served by the generic resource GIN index.
+        // `:identifier` matches the embedded identifier element with an `@?`
+        // jsonpath filter, served by the generic resource GIN index.
         ReferencePredicate::Identifier {
             system,
             require_no_system,
             ..
         } => {
-            if system.is_some() {
-                (
-                    IndexStrategy::JsonbContainment,
-                    Some(gin_index_name(resource_type)),
-                    true,
-                    "resource @> {path: {identifier: [{system: $system, value: $value}]}}"
-                        .to_string(),
-                )
+            let filter = if system.is_some() {
+                "@.system == $system && @.value == $value"
             } else if *require_no_system {
-                traversal("EXISTS identifier WHERE ident.system IS NULL AND ident.value = $value")
+                "!exists(@.system) && @.value == $value"
             } else {
-                (
-                    IndexStrategy::JsonbContainment,
-                    Some(gin_index_name(resource_type)),
-                    true,
-                    "resource @> {path: {identifier: [{value: $value}]}}".to_string(),
-                )
-            }
+                "@.value == $value"
+            };
+            (
+                IndexStrategy::JsonbContainment,
+                Some(gin_index_name(resource_type)),
+                true,
+                format!("resource @? '$.path[*].identifier ? ({filter})'"),
+            )
         }
         ReferencePredicate::Missing { is_missing } => {
             if *is_missing {
@@ -1188,7 +1183,7 @@ mod tests {
         );
         assert_eq!(
             plan.predicates[0].sql_shape,
-            "resource @> {path: {identifier: [{system: $system, value: $value}]}}"
+            "resource @? '$.path[*].identifier ? (@.system == $system && @.value == $value)'"
         );
         assert!(
             !json.contains("hospital.example") && !json.contains("12345"),
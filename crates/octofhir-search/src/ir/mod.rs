@@ -22,8 +22,8 @@ pub use ast::{
     TokenPredicate, TokenSetModifier, UriClause, UriPredicate,
 };
 pub use chain::{
-    ChainClause, ChainError, ChainLink, HasClause, HasTail, is_chained_parameter,
-    is_reverse_chain_parameter, render_chain_clause, render_has_clause,
+    ChainClause, ChainError, ChainLink, DEFAULT_MAX_CHAIN_DEPTH, HasClause, HasTail,
+    is_chained_parameter, is_reverse_chain_parameter, render_chain_clause, render_has_clause,
 };
 pub use debug::{
     DebugPredicate, SearchDebugPlan, build_composite_debug_plan, build_date_debug_plan,
@@ -37,16 +37,18 @@ pub use registry::{
 pub use render::{
     render_composite_clauses_as_or, render_date_column_clauses_as_or,
     render_date_inplace_clauses_as_or, render_date_text_path_clauses_as_or,
-    render_id_clauses_as_or, render_indexed_string_clauses_as_or, render_number_clauses_as_or,
+    render_id_clauses_as_or, render_index_table_string_clauses_as_or,
+    render_indexed_string_clauses_as_or, render_number_clauses_as_or,
     render_period_path_clauses_as_or, render_quantity_array_clauses_as_or,
     render_quantity_clauses_as_or, render_quantity_containment_clauses_as_or,
-    render_quantity_union_clauses_as_or, render_sql_expr, render_string_array_clauses_as_or,
-    render_string_human_name_clauses_as_or, render_string_path_clauses_as_or,
-    render_token_coding_array_clauses_as_or, render_token_coding_clauses_as_or,
-    render_token_coding_subtree_clauses_as_or, render_token_identifier_clauses_as_or,
-    render_token_identifier_containment_clauses_as_or, render_token_path_clauses_as_or,
-    render_token_scalar_code_clauses_as_or, render_token_simple_code_clauses_as_or,
-    render_uri_array_clauses_as_or, render_uri_clauses_as_or,
+    render_quantity_union_clauses_as_or, render_reference_identifier_clauses_as_or,
+    render_sql_expr, render_string_array_clauses_as_or, render_string_human_name_clauses_as_or,
+    render_string_path_clauses_as_or, render_token_coding_array_clauses_as_or,
+    render_token_coding_clauses_as_or, render_token_coding_subtree_clauses_as_or,
+    render_token_identifier_clauses_as_or, render_token_identifier_containment_clauses_as_or,
+    render_token_path_clauses_as_or, render_token_scalar_code_clauses_as_or,
+    render_token_simple_code_clauses_as_or, render_uri_array_clauses_as_or,
+    render_uri_clauses_as_or,
 };
 pub use rewrite::{rewrite_date_clauses, rewrite_search_expr};
 pub use strategy::{IndexStrategy, StrategyDecision};
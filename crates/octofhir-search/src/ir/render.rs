@@ -1,7 +1,8 @@
 use crate::ir::ast::{
     CompositeClause, CompositeComponentPredicate, CompositePredicate, IdClause, IdPredicate,
-    NumberClause, NumberPredicate, QuantityClause, QuantityPredicate, StringClause,
-    StringPredicate, TokenClause, TokenIndexShape, TokenPredicate, UriClause, UriPredicate,
+    NumberClause, NumberPredicate, QuantityClause, QuantityPredicate, ReferenceClause,
+    ReferencePredicate, StringClause, StringPredicate, TokenClause, TokenIndexShape,
+    TokenPredicate, UriClause, UriPredicate,
 };
 use crate::ir::sql::{RangeOp, SelectStmt, SqlExpr, SqlFrom, SqlOp, SqlTerm};
 use crate::parameters::SearchParameterType;
@@ -72,15 +73,52 @@ pub fn render_composite_clauses_as_or(
 }
 
 /// Render logical id clauses as one OR group over a resource id column.
+///
+/// Equality values collapse into a single `id IN (...)`. Under `:not` the
+/// whole list is negated at once, so `_id:not=a,b` excludes both ids.
 pub fn render_id_clauses_as_or(
     builder: &mut SqlBuilder,
     clauses: &[IdClause],
     id_column: &str,
 ) -> Option<SqlExpr> {
-    let exprs = clauses
-        .iter()
-        .map(|clause| id_clause_expr(builder, clause, id_column))
-        .collect::<Vec<_>>();
+    let mut ids: Vec<&str> = Vec::new();
+    let mut negated = false;
+    let mut exprs = Vec::new();
+    for clause in clauses {
+        match &clause.predicate {
+            IdPredicate::Equals { value } => {
+                if !ids.contains(&value.as_str()) {
+                    ids.push(value);
+                }
+                negated |= clause.negated;
+            }
+            IdPredicate::Missing { .. } => exprs.push(id_clause_expr(builder, clause, id_column)),
+        }
+    }
+
+    let lhs = SqlTerm::Ident(id_column.to_string());
+    let condition = match ids.as_slice() {
+        [] => None,
+        [id] => Some(SqlExpr::Compare {
+            lhs,
+            op: SqlOp::Eq,
+            rhs: SqlTerm::Param(builder.add_text_param(*id)),
+        }),
+        _ => Some(SqlExpr::In {
+            lhs,
+            items: ids
+                .iter()
+                .map(|id| SqlTerm::Param(builder.add_text_param(*id)))
+                .collect(),
+        }),
+    };
+    if let Some(condition) = condition {
+        exprs.push(if negated {
+            negate_expr(condition)
+        } else {
+            condition
+        });
+    }
     or_exprs(exprs)
 }
 
@@ -463,6 +501,16 @@ pub fn render_quantity_union_clauses_as_or(
     Ok(or_exprs(exprs))
 }
 
+/// Combine per-value token predicates. Values of one occurrence are ORed,
+/// except under `:not`, where every listed value must be absent.
+fn token_or_exprs(clauses: &[TokenClause], exprs: Vec<SqlExpr>) -> Option<SqlExpr> {
+    if exprs.len() > 1 && clauses.iter().all(|clause| clause.negated) {
+        Some(SqlExpr::And(exprs))
+    } else {
+        or_exprs(exprs)
+    }
+}
+
 /// Render simple-code token clauses as one OR group.
 ///
 /// This covers scalar/array code SearchParameters such as `Patient.gender`.
@@ -479,7 +527,7 @@ pub fn render_token_simple_code_clauses_as_or(
                 .map(|cond| token_apply_negation(clause, cond))
         })
         .collect::<Result<Vec<_>, _>>()?;
-    Ok(or_exprs(exprs))
+    Ok(token_or_exprs(clauses, exprs))
 }
 
 /// Render scalar text-path code token clauses as one OR group.
@@ -495,7 +543,7 @@ pub fn render_token_scalar_code_clauses_as_or(
                 .map(|cond| token_apply_negation(clause, cond))
         })
         .collect::<Result<Vec<_>, _>>()?;
-    Ok(or_exprs(exprs))
+    Ok(token_or_exprs(clauses, exprs))
 }
 
 /// Render Coding/CodeableConcept token clauses as one OR group.
@@ -508,7 +556,7 @@ pub fn render_token_coding_clauses_as_or(
         .iter()
         .map(|clause| render_token_coding_clause(builder, clause, path_segments).map(SqlExpr::Raw))
         .collect::<Result<Vec<_>, _>>()?;
-    Ok(or_exprs(exprs))
+    Ok(token_or_exprs(clauses, exprs))
 }
 
 /// Render token clauses for an ARRAY-valued CodeableConcept/Coding field
@@ -532,7 +580,7 @@ pub fn render_token_coding_array_clauses_as_or(
             render_token_coding_array_clause(builder, clause, array_path).map(SqlExpr::Raw)
         })
         .collect::<Result<Vec<_>, _>>()?;
-    Ok(or_exprs(exprs))
+    Ok(token_or_exprs(clauses, exprs))
 }
 
 fn render_token_coding_array_clause(
@@ -618,7 +666,7 @@ pub fn render_token_coding_subtree_clauses_as_or(
             render_token_coding_subtree_clause(builder, clause, subtree_path).map(SqlExpr::Raw)
         })
         .collect::<Result<Vec<_>, _>>()?;
-    Ok(or_exprs(exprs))
+    Ok(token_or_exprs(clauses, exprs))
 }
 
 fn render_token_coding_subtree_clause(
@@ -689,7 +737,7 @@ pub fn render_token_identifier_clauses_as_or(
         .iter()
         .map(|clause| render_token_identifier_clause(builder, clause, array_path).map(SqlExpr::Raw))
         .collect::<Result<Vec<_>, _>>()?;
-    Ok(or_exprs(exprs))
+    Ok(token_or_exprs(clauses, exprs))
 }
 
 /// Render Identifier token clauses as one OR group, using full-resource JSONB
@@ -710,6 +758,60 @@ pub fn render_token_identifier_containment_clauses_as_or(
                 .map(SqlExpr::Raw)
         })
         .collect::<Result<Vec<_>, _>>()?;
+    Ok(token_or_exprs(clauses, exprs))
+}
+
+/// Render reference `:identifier` clauses as one OR group of `@?` jsonpath
+/// filters over `<path>[*].identifier`.
+///
+/// `Reference.identifier` is a single Identifier object and the reference
+/// itself may sit in an array (`Observation.performer`) or under one
+/// (`Encounter.participant.individual`); lax `[*]` on every segment covers
+/// all of these shapes, which JSONB containment cannot without knowing the
+/// cardinality. The filter is bound as a jsonpath parameter and served by the
+/// generic resource GIN index.
+pub fn render_reference_identifier_clauses_as_or(
+    builder: &mut SqlBuilder,
+    clauses: &[ReferenceClause],
+    path_segments: &[String],
+) -> Result<Option<SqlExpr>, SqlBuilderError> {
+    let mut base = String::from("$");
+    for seg in path_segments {
+        base.push_str(&format!(".\"{}\"[*]", jp_quote(seg)));
+    }
+    base.push_str(".\"identifier\"");
+
+    let col = builder.resource_column().to_string();
+    let mut exprs = Vec::new();
+    for clause in clauses {
+        let ReferencePredicate::Identifier {
+            system,
+            require_no_system,
+            value,
+        } = &clause.predicate
+        else {
+            return Err(SqlBuilderError::InvalidModifier(format!(
+                "{:?}",
+                clause.predicate
+            )));
+        };
+
+        let mut conds = Vec::new();
+        match system {
+            Some(system) => conds.push(format!("@.\"system\" == \"{}\"", jp_quote(system))),
+            None if *require_no_system => conds.push("!(exists(@.\"system\"))".to_string()),
+            None => {}
+        }
+        if !value.is_empty() {
+            conds.push(format!("@.\"value\" == \"{}\"", jp_quote(value)));
+        }
+        if conds.is_empty() {
+            continue;
+        }
+
+        let p = builder.add_text_param(format!("{base} ? ({})", conds.join(" && ")));
+        exprs.push(SqlExpr::Raw(format!("{col} @? ${p}::jsonpath")));
+    }
     Ok(or_exprs(exprs))
 }
 
@@ -723,7 +825,7 @@ pub fn render_token_path_clauses_as_or(
         .iter()
         .map(|clause| render_token_path_clause(builder, clause, jsonb_path).map(SqlExpr::Raw))
         .collect::<Result<Vec<_>, _>>()?;
-    Ok(or_exprs(exprs))
+    Ok(token_or_exprs(clauses, exprs))
 }
 
 fn render_token_identifier_containment_clause(
@@ -1769,16 +1871,21 @@ fn id_clause_expr(builder: &mut SqlBuilder, clause: &IdClause, id_column: &str)
     };
 
     if clause.negated {
-        SqlExpr::Compare {
-            lhs: SqlTerm::Expr(Box::new(condition)),
-            op: SqlOp::Eq,
-            rhs: SqlTerm::Bool(false),
-        }
+        negate_expr(condition)
     } else {
         condition
     }
 }
 
+/// `(condition) = false`, the boolean negation used by `:not` renderers.
+fn negate_expr(condition: SqlExpr) -> SqlExpr {
+    SqlExpr::Compare {
+        lhs: SqlTerm::Expr(Box::new(condition)),
+        op: SqlOp::Eq,
+        rhs: SqlTerm::Bool(false),
+    }
+}
+
 fn render_composite_component_at_path_expr(
     builder: &mut SqlBuilder,
     component: &CompositeComponentPredicate,
@@ -2289,11 +2396,7 @@ fn token_simple_code_clause_expr(
 
 fn token_apply_negation(clause: &TokenClause, condition: SqlExpr) -> SqlExpr {
     if clause.negated {
-        SqlExpr::Compare {
-            lhs: SqlTerm::Expr(Box::new(condition)),
-            op: SqlOp::Eq,
-            rhs: SqlTerm::Bool(false),
-        }
+        negate_expr(condition)
     } else {
         condition
     }
@@ -3105,6 +3208,62 @@ pub fn render_indexed_string_clauses_as_or(
     or_exprs(exprs)
 }
 
+/// Render string clauses (one OR group) against a precomputed search index
+/// table: `EXISTS` over its rows for the current resource, matching the
+/// generated `blob` column (default / `:contains`) or the raw `value` (`:exact`).
+pub fn render_index_table_string_clauses_as_or(
+    builder: &mut SqlBuilder,
+    clauses: &[StringClause],
+    index_table: &str,
+) -> Option<SqlExpr> {
+    let row_match = SqlExpr::Compare {
+        lhs: SqlTerm::Ident("ix.resource_id".to_string()),
+        op: SqlOp::Eq,
+        rhs: SqlTerm::Raw(builder.id_column()),
+    };
+    let index_table = builder.table_ref(index_table);
+    let exists = |where_clause: SqlExpr| {
+        SqlExpr::Exists(Box::new(SelectStmt {
+            projection: vec![SqlTerm::Integer(1)],
+            from: SqlFrom {
+                table: index_table.clone(),
+                alias: Some("ix".to_string()),
+            },
+            where_clause: Some(where_clause),
+        }))
+    };
+
+    let mut exprs = Vec::new();
+    let mut value_exprs = Vec::new();
+    for clause in clauses {
+        match &clause.predicate {
+            StringPredicate::Missing { is_missing } => {
+                let present = exists(row_match.clone());
+                exprs.push(if *is_missing {
+                    SqlExpr::Not(Box::new(present))
+                } else {
+                    present
+                });
+            }
+            StringPredicate::Exact { value } => {
+                let p = builder.add_text_param(value.clone());
+                value_exprs.push(SqlExpr::Compare {
+                    lhs: SqlTerm::Ident("ix.value".to_string()),
+                    op: SqlOp::Eq,
+                    rhs: SqlTerm::Param(p),
+                });
+            }
+            _ => value_exprs.push(indexed_string_clause_expr(
+                builder, clause, "ix.blob", "ix.value",
+            )),
+        }
+    }
+    if let Some(values) = or_exprs(value_exprs) {
+        exprs.push(exists(SqlExpr::And(vec![row_match, values])));
+    }
+    or_exprs(exprs)
+}
+
 fn escape_like_pattern(s: &str) -> String {
     s.replace('\\', "\\\\")
         .replace('%', "\\%")
@@ -3314,6 +3473,11 @@ pub fn render_sql_expr(expr: &SqlExpr) -> String {
         }
         SqlExpr::IsNull(term) => format!("{} IS NULL", render_term(term)),
         SqlExpr::IsNotNull(term) => format!("{} IS NOT NULL", render_term(term)),
+        SqlExpr::In { lhs, items } => format!(
+            "{} IN ({})",
+            render_term(lhs),
+            items.iter().map(render_term).collect::<Vec<_>>().join(", ")
+        ),
         SqlExpr::RangeOp { lhs, op, rhs } => {
             format!(
                 "{} {} {}",
@@ -3539,6 +3703,81 @@ mod tests {
         assert_eq!(builder.params()[0].as_str(), "pat-1");
     }
 
+    fn id_clauses(values: &[&str], negated: bool) -> Vec<IdClause> {
+        values
+            .iter()
+            .map(|value| IdClause {
+                resource_type: "Patient".to_string(),
+                param_code: "_id".to_string(),
+                predicate: IdPredicate::Equals {
+                    value: value.to_string(),
+                },
+                negated,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn id_render_multiple_values_uses_single_in_list() {
+        let mut builder = SqlBuilder::new();
+        let clauses = id_clauses(&["1", "2", "3", "2"], false);
+
+        let sql =
+            render_sql_expr(&render_id_clauses_as_or(&mut builder, &clauses, "r.id").unwrap());
+
+        assert_eq!(sql, "r.id IN ($1, $2, $3)");
+        assert_eq!(builder.params().len(), 3);
+        assert_eq!(builder.params()[2].as_str(), "3");
+    }
+
+    #[test]
+    fn id_render_not_multiple_values_excludes_every_id() {
+        let mut builder = SqlBuilder::new();
+        let clauses = id_clauses(&["1", "2"], true);
+
+        let sql =
+            render_sql_expr(&render_id_clauses_as_or(&mut builder, &clauses, "r.id").unwrap());
+
+        assert_eq!(sql, "(r.id IN ($1, $2)) = false");
+    }
+
+    #[test]
+    fn reference_identifier_render_walks_arrays_and_binds_jsonpath() {
+        let mut builder = SqlBuilder::new();
+        let clause = |system: Option<&str>, require_no_system, value: &str| ReferenceClause {
+            resource_type: "Encounter".to_string(),
+            param_code: "participant".to_string(),
+            predicate: ReferencePredicate::Identifier {
+                system: system.map(str::to_string),
+                require_no_system,
+                value: value.to_string(),
+            },
+            target_types: vec!["Practitioner".to_string()],
+        };
+        let clauses = vec![
+            clause(Some("http://npi.example"), false, "123"),
+            clause(None, true, "456"),
+        ];
+        let segments = vec!["participant".to_string(), "individual".to_string()];
+
+        let sql = render_sql_expr(
+            &render_reference_identifier_clauses_as_or(&mut builder, &clauses, &segments)
+                .unwrap()
+                .unwrap(),
+        );
+
+        assert!(sql.contains("@? $1::jsonpath") && sql.contains("@? $2::jsonpath"));
+        assert!(!sql.contains("npi.example"));
+        assert_eq!(
+            builder.params()[0].as_str(),
+            r#"$."participant"[*]."individual"[*]."identifier" ? (@."system" == "http://npi.example" && @."value" == "123")"#
+        );
+        assert_eq!(
+            builder.params()[1].as_str(),
+            r#"$."participant"[*]."individual"[*]."identifier" ? (!(exists(@."system")) && @."value" == "456")"#
+        );
+    }
+
     #[test]
     fn string_path_render_uses_normalized_bound_pattern() {
         let mut builder = SqlBuilder::new();
@@ -3841,6 +4080,45 @@ mod tests {
         assert_eq!(builder.params()[0].as_str(), r#"{"gender":"female"}"#);
     }
 
+    #[test]
+    fn simple_code_token_render_not_multiple_values_ands_negations() {
+        let mut builder = SqlBuilder::with_resource_column("r.resource");
+        let clauses = TokenClause::from_parsed_param(
+            &crate::parser::ParsedParam {
+                name: "gender".to_string(),
+                modifier: Some(crate::parameters::SearchModifier::Not),
+                values: vec![
+                    crate::parser::ParsedValue {
+                        prefix: None,
+                        raw: "female".to_string(),
+                    },
+                    crate::parser::ParsedValue {
+                        prefix: None,
+                        raw: "other".to_string(),
+                    },
+                ],
+            },
+            "Patient",
+            crate::ir::TokenIndexShape::SimpleCode,
+        )
+        .unwrap();
+
+        let sql = render_sql_expr(
+            &render_token_simple_code_clauses_as_or(
+                &mut builder,
+                &clauses,
+                &["gender".to_string()],
+            )
+            .unwrap()
+            .unwrap(),
+        );
+
+        assert_eq!(
+            sql,
+            "((r.resource @> $1::jsonb) = false AND (r.resource @> $2::jsonb) = false)"
+        );
+    }
+
     #[test]
     fn simple_code_token_render_no_system_code_uses_value_containment() {
         let mut builder = SqlBuilder::with_resource_column("r.resource");
@@ -12,6 +12,10 @@ pub enum SqlExpr {
     },
     IsNull(SqlTerm),
     IsNotNull(SqlTerm),
+    In {
+        lhs: SqlTerm,
+        items: Vec<SqlTerm>,
+    },
     RangeOp {
         lhs: SqlTerm,
         op: RangeOp,
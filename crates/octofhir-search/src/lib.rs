@@ -63,14 +63,16 @@ pub use include::{
     is_revinclude_parameter, parse_include, parse_revinclude,
 };
 pub use ir::chain::{
-    ChainClause, ChainError, ChainLink, HasClause, HasTail, is_chained_parameter,
-    is_reverse_chain_parameter, render_chain_clause, render_has_clause,
+    ChainClause, ChainError, ChainLink, DEFAULT_MAX_CHAIN_DEPTH, HasClause, HasTail,
+    is_chained_parameter, is_reverse_chain_parameter, render_chain_clause, render_has_clause,
 };
 pub use query_cache::{
     CacheError, CacheStatsSnapshot, ParamPosition, ParamValueType, PreparedQuery, QueryCache,
     QueryCacheKey, QueryParamKey,
 };
-pub use reloadable::{ReloadableSearchConfig, SearchConfig, SearchOptions};
+pub use reloadable::{
+    DEFAULT_SORT, DefaultSort, ReloadableSearchConfig, SearchConfig, SearchOptions,
+};
 pub use terminology::{
     CacheStats, HierarchyDirection, HybridTerminologyProvider, TerminologyConfig, TerminologyError,
 };
@@ -79,5 +81,5 @@ pub use terminology::{
 pub use params_converter::{
     ConvertedQuery, SearchConfig as ParamsSearchConfig, UnknownParamHandling, UnknownParamWarning,
     build_native_ir_query_from_params, build_native_ir_query_from_params_with_config,
-    parse_query_string,
+    parse_query_string, parse_sort_fields, sync_horizon_sql,
 };
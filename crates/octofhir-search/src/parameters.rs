@@ -376,6 +376,10 @@ pub struct SearchParameter {
     /// Name of the per-param typed extraction SQL function, set at bootstrap when
     /// the function was created. None = use the generic extraction path.
     pub typed_extract_fn: Option<String>,
+    /// Name of the precomputed search index table (`resource_id`, `value`)
+    /// kept in sync on write, set at bootstrap when the table was created.
+    /// None = extract values from the resource JSONB at query time.
+    pub index_table: Option<String>,
 }
 
 impl SearchParameter {
@@ -402,6 +406,7 @@ impl SearchParameter {
             element_type_hint: ElementTypeHint::Unknown,
             user_defined: false,
             typed_extract_fn: None,
+            index_table: None,
         }
     }
 
@@ -412,6 +417,13 @@ impl SearchParameter {
         self
     }
 
+    /// Set the name of the precomputed search index table.
+    #[must_use]
+    pub fn with_index_table(mut self, table: impl Into<String>) -> Self {
+        self.index_table = Some(table.into());
+        self
+    }
+
     /// Set the FHIRPath expression and pre-compute the JSONB path.
     #[must_use]
     pub fn with_expression(mut self, expr: impl Into<String>) -> Self {
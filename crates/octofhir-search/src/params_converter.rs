@@ -5,25 +5,28 @@
 
 use crate::include::{is_include_parameter, is_revinclude_parameter};
 use crate::ir::{
-    ChainClause, CompositeClause, HasClause, IdClause, NumberClause, QuantityClause,
-    ReferenceClause, ResourceColumnParam, SearchDebugPlan, StringClause, TokenClause,
-    TokenIndexShape, build_composite_debug_plan, build_date_debug_plan, build_number_debug_plan,
-    build_quantity_debug_plan, build_reference_debug_plan, build_string_debug_plan,
-    build_string_text_debug_predicate, build_token_debug_plan, is_chained_parameter,
-    is_reverse_chain_parameter, render_chain_clause, render_date_inplace_clauses_as_or,
-    render_has_clause, render_id_clauses_as_or, resolve_composite_component_specs,
-    resolve_resource_column_param, rewrite_date_clauses,
+    ChainClause, CompositeClause, DEFAULT_MAX_CHAIN_DEPTH, HasClause, IdClause, NumberClause,
+    QuantityClause, ReferenceClause, ResourceColumnParam, SearchDebugPlan, StringClause,
+    TokenClause, TokenIndexShape, build_composite_debug_plan, build_date_debug_plan,
+    build_number_debug_plan, build_quantity_debug_plan, build_reference_debug_plan,
+    build_string_debug_plan, build_string_text_debug_predicate, build_token_debug_plan,
+    is_chained_parameter, is_reverse_chain_parameter, render_chain_clause,
+    render_date_inplace_clauses_as_or, render_has_clause, render_id_clauses_as_or,
+    resolve_composite_component_specs, resolve_resource_column_param, rewrite_date_clauses,
 };
 use crate::parameters::{ElementTypeHint, SearchParameter, SearchParameterType, SearchPrefix};
-use crate::parser::{ParsedParam, ParsedValue};
+use crate::parser::{ParsedParam, ParsedValue, SearchParameterParser};
 use crate::registry::SearchParameterRegistry;
 use crate::sql_builder::{
     FhirQueryBuilder, IncludeSpec, JsonbPath, RevIncludeSpec, SearchCondition, SortOrder, SortSpec,
     SqlBuilder, SqlBuilderError, SqlValue, fhirpath_to_jsonb_path,
 };
 use crate::types::date_ast::{DateClause, DatePredicate};
-use crate::types::dispatch_search_with_registry;
-use octofhir_storage::{SearchParams, TotalMode};
+use crate::types::{
+    CONTENT_TSV_COLUMN, TEXT_TSV_COLUMN, dispatch_search_with_registry, full_text_rank_sql,
+};
+use octofhir_core::paging::{CURSOR_PARAM, PageCursor, SYNC_PARAM, SyncCursor};
+use octofhir_storage::{SearchParams, SortParam, SyncPosition, TotalMode};
 use url::form_urlencoded;
 
 /// How to handle unknown search parameters.
@@ -31,9 +34,10 @@ use url::form_urlencoded;
 /// FHIR servers may choose to either reject unknown parameters (strict)
 /// or ignore them and continue (lenient). The behavior is controlled by
 /// the `Prefer: handling=strict|lenient` HTTP header.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum UnknownParamHandling {
-    /// Reject unknown parameters with 400 Bad Request error.
+    /// Reject unknown parameters with 400 Bad Request error, listing all of them.
     Strict,
     /// Ignore unknown parameters and continue with search.
     /// The unknown parameters are collected and can be returned as warnings.
@@ -44,16 +48,22 @@ pub enum UnknownParamHandling {
 impl UnknownParamHandling {
     /// Parse from Prefer header value (e.g., "handling=strict" or "handling=lenient").
     pub fn from_prefer_header(header: &str) -> Self {
-        for part in header.split(';').map(str::trim) {
-            if let Some(value) = part.strip_prefix("handling=") {
-                match value.trim() {
-                    "strict" => return Self::Strict,
-                    "lenient" => return Self::Lenient,
-                    _ => {}
-                }
-            }
-        }
-        Self::default()
+        Self::from_prefer(header).unwrap_or_default()
+    }
+
+    /// The `handling` preference of a Prefer header, if it states one.
+    ///
+    /// Preferences may be separated by `,` or `;`
+    /// (e.g. `return=minimal, handling=strict`).
+    pub fn from_prefer(header: &str) -> Option<Self> {
+        header
+            .split([',', ';'])
+            .filter_map(|part| part.trim().strip_prefix("handling="))
+            .find_map(|value| match value.trim().trim_matches('"') {
+                "strict" => Some(Self::Strict),
+                "lenient" => Some(Self::Lenient),
+                _ => None,
+            })
     }
 }
 
@@ -64,6 +74,9 @@ pub struct SearchConfig {
     pub unknown_param_handling: UnknownParamHandling,
     /// Collect safe internal debug plan data. Off by default and not exposed by routes.
     pub collect_debug_plan: bool,
+    /// Maximum reference links in a chained parameter. `None` falls back to
+    /// [`DEFAULT_MAX_CHAIN_DEPTH`].
+    pub max_chain_depth: Option<usize>,
 }
 
 /// Warning for an unknown search parameter.
@@ -79,6 +92,8 @@ pub struct UnknownParamWarning {
 const CONTROL_PARAMS: &[&str] = &[
     "_count",
     "_offset",
+    "_cursor",
+    "_sync",
     "_sort",
     "_include",
     "_revinclude",
@@ -87,6 +102,7 @@ const CONTROL_PARAMS: &[&str] = &[
     "_total",
     "_contained",
     "_containedType",
+    "_format",
 ];
 
 /// Result of converting SearchParams to a query builder.
@@ -143,7 +159,7 @@ pub fn build_native_ir_query_from_params_with_config(
     let mut builder = FhirQueryBuilder::new(resource_type, schema).with_alias("r");
 
     // Use SqlBuilder to accumulate conditions, then convert to SearchCondition::Raw
-    let mut sql_builder = SqlBuilder::with_resource_column("r.resource");
+    let mut sql_builder = SqlBuilder::with_resource_column("r.resource").with_schema(schema);
 
     // Collect unknown parameters for warnings
     let mut unknown_params = Vec::new();
@@ -202,6 +218,7 @@ pub fn build_native_ir_query_from_params_with_config(
                 registry,
                 schema,
                 resource_type,
+                config.max_chain_depth.unwrap_or(DEFAULT_MAX_CHAIN_DEPTH),
             )?;
             continue;
         }
@@ -227,23 +244,19 @@ pub fn build_native_ir_query_from_params_with_config(
 
             // Look up parameter definition in registry
             let Some(param_def) = registry.get(resource_type, &parsed.name) else {
-                // Unknown parameter - handle based on policy
-                match config.unknown_param_handling {
-                    UnknownParamHandling::Strict => {
-                        return Err(SqlBuilderError::InvalidSearchValue(format!(
-                            "Unknown search parameter: {}",
-                            key
-                        )));
-                    }
-                    UnknownParamHandling::Lenient => {
-                        tracing::debug!(param = %parsed.name, "Unknown search parameter, skipping");
-                        unknown_params.push(UnknownParamWarning {
-                            name: parsed.name.clone(),
-                            modifier: parsed.modifier.as_ref().map(|m| format!("{:?}", m)),
-                        });
-                        continue;
-                    }
+                // Unknown parameter - collected here, rejected after the loop
+                // in strict mode so the error lists all of them
+                tracing::debug!(param = %parsed.name, "Unknown search parameter, skipping");
+                if !unknown_params
+                    .iter()
+                    .any(|p: &UnknownParamWarning| p.name == parsed.name)
+                {
+                    unknown_params.push(UnknownParamWarning {
+                        name: parsed.name.clone(),
+                        modifier: parsed.modifier.as_ref().map(|m| format!("{:?}", m)),
+                    });
                 }
+                continue;
             };
 
             // Validate modifier compatibility with parameter type
@@ -355,9 +368,30 @@ pub fn build_native_ir_query_from_params_with_config(
         });
     }
 
+    // Relevance of `_text` / `_content` matches, reported as
+    // `entry.search.score` and sortable with `_sort=_score`
+    if let Some((sql, score_params)) = full_text_score(params, registry, resource_type) {
+        builder = builder.with_score(sql, score_params);
+    }
+
+    // Incremental sync and id keyset paging page by key: the position
+    // replaces the offset
+    if let Some(position) = &params.sync {
+        builder = builder.where_conditions(sync_conditions(position));
+    } else if let Some(after_id) = &params.after_id {
+        builder = builder.where_condition(SearchCondition::raw(
+            "r.id > $1",
+            vec![SqlValue::Text(after_id.clone())],
+        ));
+    }
+
     // Handle pagination
     let limit = params.count.unwrap_or(10) as usize;
-    let offset = params.offset.unwrap_or(0) as usize;
+    let offset = if params.sync.is_some() || params.after_id.is_some() {
+        0
+    } else {
+        params.offset.unwrap_or(0) as usize
+    };
     // Request limit + 1 to determine if there are more results
     builder = builder.paginate(limit + 1, offset);
 
@@ -369,9 +403,16 @@ pub fn build_native_ir_query_from_params_with_config(
     // index gives no order, so the sort must read+recheck EVERY match before
     // top-N, making LIMIT useless and scaling linearly with the match count.
     // Emitting no ORDER BY lets LIMIT bound the work — the scan stops once it
-    // has enough matching rows. Recency sort stays available, opt-in, via
-    // `_sort=-_lastUpdated`.
-    if let Some(sort_params) = &params.sort {
+    // has enough matching rows. Callers apply the server's `DefaultSort`
+    // (`_lastUpdated,_id` unless configured otherwise) to `params.sort`
+    // before conversion; this only governs params that reach it unsorted.
+    if params.sync.is_some() {
+        builder = builder
+            .sort_by(SortSpec::column("updated_at", SortOrder::Asc)?)
+            .sort_by(SortSpec::column("id", SortOrder::Asc)?);
+    } else if params.after_id.is_some() {
+        builder = builder.sort_by(SortSpec::column("id", SortOrder::Asc)?);
+    } else if let Some(sort_params) = &params.sort {
         for sort_param in sort_params {
             if let Some(sort_spec) = build_sort_spec(
                 &sort_param.field,
@@ -384,6 +425,12 @@ pub fn build_native_ir_query_from_params_with_config(
         }
     }
 
+    if config.unknown_param_handling == UnknownParamHandling::Strict && !unknown_params.is_empty() {
+        return Err(SqlBuilderError::UnknownParameters(
+            unknown_params.into_iter().map(|p| p.name).collect(),
+        ));
+    }
+
     // Extract _include specifications
     let includes = extract_include_specs(params, registry, resource_type);
 
@@ -400,6 +447,56 @@ pub fn build_native_ir_query_from_params_with_config(
     })
 }
 
+/// How long a sync page stays behind the database clock, on top of the
+/// open-transaction horizon. Covers a transaction that commits between the
+/// page's snapshot and its read of `pg_stat_activity`.
+const SYNC_SETTLE: &str = "2 seconds";
+
+/// SQL expression bounding an incremental sync page: `updated_at` of
+/// everything on the page is below it.
+///
+/// Writes stamp `updated_at` with the database clock at the start of their
+/// transaction (`now()`), so a transaction still open stamps at or after its
+/// `xact_start`. The horizon stays before every transaction open in this
+/// database, and before the last [`SYNC_SETTLE`]; a write committed later
+/// then always sorts after it, so a cursor that moved past the horizon
+/// never misses the write. Deletes are stamped the same way by their
+/// tombstones.
+#[must_use]
+pub fn sync_horizon_sql() -> String {
+    format!(
+        "LEAST(now() - INTERVAL '{SYNC_SETTLE}', \
+         (SELECT min(xact_start) FROM pg_stat_activity \
+          WHERE datname = current_database() AND xact_start IS NOT NULL \
+            AND pid <> pg_backend_pid()))"
+    )
+}
+
+/// Conditions of an incremental sync page: below the
+/// [horizon](sync_horizon_sql) and after the cursor. Ties on `updated_at`
+/// are broken by id.
+fn sync_conditions(position: &SyncPosition) -> Vec<SearchCondition> {
+    let mut conditions = vec![SearchCondition::raw(
+        format!("r.updated_at < {}", sync_horizon_sql()),
+        Vec::new(),
+    )];
+    if let SyncPosition::After(cursor) = position
+        && let Some(last_updated) = cursor.last_updated().and_then(|t| {
+            t.format(&time::format_description::well_known::Rfc3339)
+                .ok()
+        })
+    {
+        conditions.push(SearchCondition::raw(
+            "(r.updated_at, r.id) > ($1::timestamptz, $2)",
+            vec![
+                SqlValue::Timestamp(last_updated),
+                SqlValue::Text(cursor.id.clone()),
+            ],
+        ));
+    }
+    conditions
+}
+
 /// Check if a parameter is a control parameter.
 fn is_control_param(name: &str) -> bool {
     CONTROL_PARAMS.contains(&name) || is_include_parameter(name) || is_revinclude_parameter(name)
@@ -780,8 +877,8 @@ fn convert_to_parsed_param(key: &str, value_entry: &str) -> ParsedParam {
     };
 
     // Parse comma-separated values (OR semantics within this occurrence).
-    let parsed_values: Vec<ParsedValue> = value_entry
-        .split(',')
+    let parsed_values: Vec<ParsedValue> = SearchParameterParser::split_or_values(value_entry)
+        .iter()
         .map(|part| {
             let part = part.trim();
             let (prefix, raw) = extract_prefix(part);
@@ -872,6 +969,9 @@ fn handle_has_param(
 /// parameter handler (render_chain_clause handles comma-OR).
 ///
 /// Each `&`-occurrence emits one top-level condition that SqlBuilder AND's.
+/// Chains longer than `max_depth` links or looping back to a resource type
+/// already on the path are rejected before any SQL is built.
+#[allow(clippy::too_many_arguments)]
 fn handle_chained_param(
     _builder: &mut FhirQueryBuilder,
     sql_builder: &mut SqlBuilder,
@@ -880,9 +980,10 @@ fn handle_chained_param(
     registry: &SearchParameterRegistry,
     _schema: &str,
     resource_type: &str,
+    max_depth: usize,
 ) -> Result<(), SqlBuilderError> {
     for value in values {
-        match ChainClause::parse(key, value, registry, resource_type) {
+        match ChainClause::parse_with_max_depth(key, value, registry, resource_type, max_depth) {
             Ok(chained) => {
                 tracing::debug!(
                     chain = ?chained.chain,
@@ -909,13 +1010,63 @@ fn handle_chained_param(
     Ok(())
 }
 
+/// Rank expression summing `ts_rank_cd` over the `_text` / `_content`
+/// values of a search, with its own `$1..` placeholders.
+///
+/// Mirrors the conditions `dispatch_search` emits: only the first value of
+/// each occurrence is matched, and parameters missing from the registry are
+/// skipped.
+fn full_text_score(
+    params: &SearchParams,
+    registry: &SearchParameterRegistry,
+    resource_type: &str,
+) -> Option<(String, Vec<SqlValue>)> {
+    let mut terms = Vec::new();
+    let mut values = Vec::new();
+    for (name, column) in [("_content", CONTENT_TSV_COLUMN), ("_text", TEXT_TSV_COLUMN)] {
+        let Some(entries) = params.parameters.get(name) else {
+            continue;
+        };
+        let Some(param_def) = registry.get(resource_type, name) else {
+            continue;
+        };
+        for entry in entries {
+            let parsed = convert_to_parsed_param(name, entry);
+            let Some(value) = parsed.values.into_iter().next() else {
+                continue;
+            };
+            // Same prefix restoration as the condition itself
+            let raw = match value.prefix {
+                Some(prefix) if !prefix.applicable_to(&param_def.param_type) => {
+                    format!("{prefix}{}", value.raw)
+                }
+                _ => value.raw,
+            };
+            values.push(SqlValue::Text(raw));
+            terms.push(full_text_rank_sql(&format!("r.{column}"), values.len()));
+        }
+    }
+    (!terms.is_empty()).then(|| (terms.join(" + "), values))
+}
+
 /// Build a SortSpec from a sort parameter.
+///
+/// `_score` orders by relevance, most relevant first; `-_score` reverses it.
 fn build_sort_spec(
     field: &str,
     descending: bool,
     registry: &SearchParameterRegistry,
     resource_type: &str,
 ) -> Option<SortSpec> {
+    if field == "_score" {
+        let order = if descending {
+            SortOrder::Asc
+        } else {
+            SortOrder::Desc
+        };
+        return Some(SortSpec::score(order));
+    }
+
     let param_def = registry.get(resource_type, field)?;
     let order = if descending {
         SortOrder::Desc
@@ -1014,6 +1165,22 @@ fn extract_revinclude_specs(
     specs
 }
 
+/// Parse a `_sort` value into sort fields.
+///
+/// `"-date,name"` sorts by date descending, then name ascending. Empty
+/// entries are skipped.
+pub fn parse_sort_fields(value: &str) -> Vec<SortParam> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|field| !field.is_empty() && *field != "-")
+        .map(|field| match field.strip_prefix('-') {
+            Some(field) => SortParam::new(field, true),
+            None => SortParam::new(field, false),
+        })
+        .collect()
+}
+
 /// Parse a URL query string into SearchParams.
 ///
 /// This converts a query string like `name=John&birthdate=ge2000-01-01&_count=10`
@@ -1047,15 +1214,23 @@ pub fn parse_query_string(query: &str, default_count: u32, max_count: u32) -> Se
                     params = params.with_offset(n);
                 }
             }
+            CURSOR_PARAM => {
+                if let Some(cursor) = PageCursor::decode(&value) {
+                    params = params
+                        .with_offset(cursor.offset)
+                        .with_count(cursor.count.min(max_count).max(1));
+                }
+            }
+            SYNC_PARAM => {
+                if value == "true" {
+                    params = params.with_sync(SyncPosition::Start);
+                } else if let Some(cursor) = SyncCursor::decode(&value) {
+                    params = params.with_sync(SyncPosition::After(cursor));
+                }
+            }
             "_sort" => {
-                // Parse sort string: "-date,name" -> sort by date desc, name asc
-                for sort_field in value.split(',') {
-                    let sort_field = sort_field.trim();
-                    if let Some(field) = sort_field.strip_prefix('-') {
-                        params = params.with_sort(field, true);
-                    } else {
-                        params = params.with_sort(sort_field, false);
-                    }
+                for sort in parse_sort_fields(&value) {
+                    params = params.with_sort(sort.field, sort.descending);
                 }
             }
             "_total" => match value.as_str() {
@@ -1453,6 +1628,97 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_query_string_cursor() {
+        let cursor = PageCursor {
+            offset: 40,
+            count: 500,
+        };
+        let params =
+            parse_query_string(&format!("name=smith&_cursor={}", cursor.encode()), 10, 100);
+        assert_eq!(params.offset, Some(40));
+        assert_eq!(params.count, Some(100));
+        assert!(!params.parameters.contains_key(CURSOR_PARAM));
+    }
+
+    #[test]
+    fn test_sync_pages_by_last_updated_and_id() {
+        let registry = SearchParameterRegistry::new();
+        crate::common::register_common_parameters(&registry);
+        let build = |query: &str| {
+            let params = parse_query_string(query, 10, 100);
+            let converted =
+                build_native_ir_query_from_params("Patient", &params, &registry, "public").unwrap();
+            converted.builder.with_raw_resource(true).build().unwrap()
+        };
+
+        let first = build("_sync=true&_sort=name&_offset=20&_count=5");
+        assert!(
+            first
+                .sql
+                .contains("ORDER BY \"r\".\"updated_at\" ASC NULLS LAST, \"r\".\"id\" ASC"),
+            "expected sync order, got: {}",
+            first.sql
+        );
+        assert!(first.sql.contains("pg_stat_activity"), "{}", first.sql);
+        assert!(
+            first.sql.contains("datname = current_database()"),
+            "{}",
+            first.sql
+        );
+        assert!(!first.sql.contains("OFFSET 20"), "{}", first.sql);
+        assert!(first.params.is_empty());
+
+        let cursor = SyncCursor {
+            last_updated_micros: 1_700_000_000_000_001,
+            id: "pat-1".to_string(),
+        };
+        let next = build(&format!("gender=female&_sync={}", cursor.encode()));
+        assert!(
+            next.sql.contains("(r.updated_at, r.id) > ("),
+            "{}",
+            next.sql
+        );
+        assert!(next.params.iter().any(|p| matches!(
+            p,
+            SqlValue::Timestamp(t) if t == "2023-11-14T22:13:20.000001Z"
+        )));
+        assert!(
+            next.params
+                .iter()
+                .any(|p| matches!(p, SqlValue::Text(id) if id == "pat-1"))
+        );
+
+        let params = parse_query_string("_sync=bogus", 10, 100);
+        assert!(params.sync.is_none());
+        assert!(!params.parameters.contains_key(SYNC_PARAM));
+    }
+
+    #[test]
+    fn test_after_id_pages_by_id() {
+        let registry = SearchParameterRegistry::new();
+        crate::common::register_common_parameters(&registry);
+        let params = parse_query_string("gender=female&_sort=name&_offset=20&_count=5", 10, 100)
+            .with_after_id("pat-1");
+        let converted =
+            build_native_ir_query_from_params("Patient", &params, &registry, "public").unwrap();
+        let query = converted.builder.with_raw_resource(true).build().unwrap();
+
+        assert!(query.sql.contains("r.id > $"), "{}", query.sql);
+        assert!(
+            query.sql.contains("ORDER BY \"r\".\"id\" ASC"),
+            "expected id order, got: {}",
+            query.sql
+        );
+        assert!(!query.sql.contains("OFFSET 20"), "{}", query.sql);
+        assert!(
+            query
+                .params
+                .iter()
+                .any(|p| matches!(p, SqlValue::Text(id) if id == "pat-1"))
+        );
+    }
+
     #[test]
     fn test_no_default_sort_when_sort_absent() {
         let registry = SearchParameterRegistry::new();
@@ -1488,6 +1754,74 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_text_search_selects_score_and_sorts_by_it() {
+        let registry = SearchParameterRegistry::new();
+        crate::common::register_common_parameters(&registry);
+        let params = parse_query_string("_text=headache&_sort=_score", 10, 100);
+        let converted =
+            build_native_ir_query_from_params("Patient", &params, &registry, "public").unwrap();
+        assert!(converted.builder.has_score());
+        let built = converted.builder.with_raw_resource(true).build().unwrap();
+
+        let score = "ts_rank_cd(r.text_tsv, websearch_to_tsquery('english', $2))";
+        assert!(
+            built.sql.contains(&format!("{score} AS score"))
+                && built.sql.contains(&format!("ORDER BY ({score}) DESC")),
+            "expected score column and relevance sort, got: {}",
+            built.sql
+        );
+        assert!(matches!(&built.params[1], SqlValue::Text(v) if v == "headache"));
+
+        let params = parse_query_string("name=smith&_sort=-_score", 10, 100);
+        let converted =
+            build_native_ir_query_from_params("Patient", &params, &registry, "public").unwrap();
+        assert!(!converted.builder.has_score());
+        let built = converted.builder.build().unwrap();
+        assert!(!built.sql.contains("ORDER BY"), "got: {}", built.sql);
+    }
+
+    #[test]
+    fn test_default_sort_gives_stable_order_by() {
+        let registry = SearchParameterRegistry::new();
+        crate::common::register_common_parameters(&registry);
+        let default_sort =
+            crate::DefaultSort::new(&["-_lastUpdated".into(), "_id".into()], &Default::default());
+
+        let build = || {
+            let mut params = parse_query_string("name=smith&_count=5", 10, 100);
+            default_sort.apply("Patient", &mut params);
+            build_native_ir_query_from_params("Patient", &params, &registry, "public")
+                .unwrap()
+                .builder
+                .with_raw_resource(true)
+                .build()
+                .unwrap()
+                .sql
+        };
+        let first = build();
+
+        assert!(
+            first.contains("ORDER BY \"r\".\"updated_at\" DESC NULLS LAST, \"r\".\"id\" ASC"),
+            "expected default sort in ORDER BY, got: {first}"
+        );
+        assert_eq!(first, build(), "identical queries must sort identically");
+    }
+
+    #[test]
+    fn test_parse_sort_fields() {
+        let fields = parse_sort_fields("-date, name,,-");
+        assert_eq!(fields.len(), 2);
+        assert_eq!(
+            (fields[0].field.as_str(), fields[0].descending),
+            ("date", true)
+        );
+        assert_eq!(
+            (fields[1].field.as_str(), fields[1].descending),
+            ("name", false)
+        );
+    }
+
     #[test]
     fn test_id_search_uses_column_bound_params() {
         let registry = SearchParameterRegistry::new();
@@ -1498,8 +1832,8 @@ mod tests {
         let built = converted.builder.with_raw_resource(true).build().unwrap();
 
         assert!(
-            built.sql.contains("(r.id = $1 OR r.id = $2)"),
-            "expected _id OR over r.id column, got: {}",
+            built.sql.contains("r.id IN ($1, $2)"),
+            "expected _id IN list over r.id column, got: {}",
             built.sql
         );
         assert!(!built.sql.contains("pat-1"));
@@ -1525,6 +1859,56 @@ mod tests {
         assert!(matches!(&built.params[0], SqlValue::Text(value) if value == "pat-1"));
     }
 
+    #[test]
+    fn test_id_repeated_occurrences_and_their_value_lists() {
+        let registry = SearchParameterRegistry::new();
+        crate::common::register_common_parameters(&registry);
+        let params = parse_query_string("_id=1,2&_id=2,3&_count=5", 10, 100);
+        let converted =
+            build_native_ir_query_from_params("Patient", &params, &registry, "public").unwrap();
+        let built = converted.builder.with_raw_resource(true).build().unwrap();
+
+        assert!(
+            built.sql.contains("r.id IN ($1, $2) AND r.id IN ($3, $4)"),
+            "expected one IN list per _id occurrence, ANDed, got: {}",
+            built.sql
+        );
+    }
+
+    #[test]
+    fn test_token_values_or_within_and_across_occurrences() {
+        let registry = simple_code_registry_with_expression();
+        let params = parse_query_string("gender=male,female&gender=female&_count=5", 10, 100);
+        let converted =
+            build_native_ir_query_from_params("Patient", &params, &registry, "public").unwrap();
+        let built = converted.builder.with_raw_resource(true).build().unwrap();
+
+        assert!(
+            built.sql.contains(
+                "(r.resource @> $1::jsonb OR r.resource @> $2::jsonb) AND r.resource @> $3::jsonb"
+            ),
+            "expected OR within and AND across gender occurrences, got: {}",
+            built.sql
+        );
+    }
+
+    #[test]
+    fn test_token_not_with_multiple_values_excludes_each_value() {
+        let registry = simple_code_registry_with_expression();
+        let params = parse_query_string("gender:not=male,female&_count=5", 10, 100);
+        let converted =
+            build_native_ir_query_from_params("Patient", &params, &registry, "public").unwrap();
+        let built = converted.builder.with_raw_resource(true).build().unwrap();
+
+        assert!(
+            built.sql.contains(
+                "((r.resource @> $1::jsonb) = false AND (r.resource @> $2::jsonb) = false)"
+            ),
+            "expected gender:not to exclude every listed code, got: {}",
+            built.sql
+        );
+    }
+
     #[test]
     fn test_identifier_system_value_through_query_builder() {
         use crate::parameters::{ElementTypeHint, SearchParameter, SearchParameterType};
@@ -1687,6 +2071,7 @@ mod tests {
         let config = SearchConfig {
             unknown_param_handling: UnknownParamHandling::Lenient,
             collect_debug_plan: true,
+            ..Default::default()
         };
         let converted = build_native_ir_query_from_params_with_config(
             "Patient", &params, &registry, "public", &config,
@@ -1709,6 +2094,49 @@ mod tests {
         assert!(built.sql.contains("fhir_s_patient_family("));
     }
 
+    #[test]
+    fn test_unknown_params_strict_lists_all_and_lenient_dedups() {
+        let registry = SearchParameterRegistry::new();
+        let params = parse_query_string("foo=1&foo=2&bar:exact=x&_count=5", 10, 100);
+
+        let lenient = SearchConfig::default();
+        let converted = build_native_ir_query_from_params_with_config(
+            "Patient", &params, &registry, "public", &lenient,
+        )
+        .unwrap();
+        let names: Vec<_> = converted.unknown_params.iter().map(|p| &p.name).collect();
+        assert_eq!(names, ["bar", "foo"]);
+
+        let strict = SearchConfig {
+            unknown_param_handling: UnknownParamHandling::Strict,
+            ..Default::default()
+        };
+        let err = build_native_ir_query_from_params_with_config(
+            "Patient", &params, &registry, "public", &strict,
+        )
+        .err()
+        .unwrap();
+        assert!(matches!(
+            &err,
+            SqlBuilderError::UnknownParameters(names) if names == &["bar", "foo"]
+        ));
+        assert_eq!(err.to_string(), "Unknown search parameters: bar, foo");
+    }
+
+    #[test]
+    fn test_unknown_param_handling_from_prefer() {
+        assert_eq!(
+            UnknownParamHandling::from_prefer("handling=strict"),
+            Some(UnknownParamHandling::Strict)
+        );
+        assert_eq!(
+            UnknownParamHandling::from_prefer("return=minimal, handling=lenient"),
+            Some(UnknownParamHandling::Lenient)
+        );
+        assert_eq!(UnknownParamHandling::from_prefer("return=minimal"), None);
+        assert_eq!(UnknownParamHandling::from_prefer("handling=loose"), None);
+    }
+
     fn token_registry_with_expression() -> SearchParameterRegistry {
         use crate::parameters::SearchParameter;
         let registry = SearchParameterRegistry::new();
@@ -1972,6 +2400,7 @@ mod tests {
         let config = SearchConfig {
             unknown_param_handling: UnknownParamHandling::Lenient,
             collect_debug_plan: true,
+            ..Default::default()
         };
         let converted = build_native_ir_query_from_params_with_config(
             "Patient", &params, &registry, "public", &config,
@@ -2015,6 +2444,7 @@ mod tests {
         let config = SearchConfig {
             unknown_param_handling: UnknownParamHandling::Lenient,
             collect_debug_plan: true,
+            ..Default::default()
         };
         let converted = build_native_ir_query_from_params_with_config(
             "Patient", &params, &registry, "public", &config,
@@ -2118,6 +2548,7 @@ mod tests {
         let config = SearchConfig {
             unknown_param_handling: UnknownParamHandling::Lenient,
             collect_debug_plan: true,
+            ..Default::default()
         };
 
         assert_fallback_disabled(
@@ -2136,6 +2567,7 @@ mod tests {
         let config = SearchConfig {
             unknown_param_handling: UnknownParamHandling::Lenient,
             collect_debug_plan: true,
+            ..Default::default()
         };
 
         let converted = build_native_ir_query_from_params_with_config(
@@ -2173,6 +2605,7 @@ mod tests {
         let config = SearchConfig {
             unknown_param_handling: UnknownParamHandling::Lenient,
             collect_debug_plan: true,
+            ..Default::default()
         };
 
         let converted = build_native_ir_query_from_params_with_config(
@@ -2215,6 +2648,7 @@ mod tests {
         let config = SearchConfig {
             unknown_param_handling: UnknownParamHandling::Lenient,
             collect_debug_plan: true,
+            ..Default::default()
         };
 
         let converted = build_native_ir_query_from_params_with_config(
@@ -2253,6 +2687,7 @@ mod tests {
         let config = SearchConfig {
             unknown_param_handling: UnknownParamHandling::Lenient,
             collect_debug_plan: true,
+            ..Default::default()
         };
 
         let converted = build_native_ir_query_from_params_with_config(
@@ -2279,6 +2714,7 @@ mod tests {
         let config = SearchConfig {
             unknown_param_handling: UnknownParamHandling::Lenient,
             collect_debug_plan: true,
+            ..Default::default()
         };
 
         assert_fallback_disabled(
@@ -2294,6 +2730,38 @@ mod tests {
         );
     }
 
+    #[test]
+    fn token_repeated_code_ands_comma_or_groups() {
+        let registry = token_registry_with_expression();
+        let params = parse_query_string(
+            "code=http://loinc.org|a,http://loinc.org|b&code=http://loinc.org|c&_count=5",
+            10,
+            100,
+        );
+        let converted =
+            build_native_ir_query_from_params("Observation", &params, &registry, "public").unwrap();
+        let built = converted.builder.with_raw_resource(true).build().unwrap();
+
+        // (a OR b) AND c, each code matched in two coding shapes
+        assert!(
+            built.sql.contains(
+                "((r.resource->'code' @> $1::jsonb OR r.resource->'code' @> $2::jsonb) \
+                 OR (r.resource->'code' @> $3::jsonb OR r.resource->'code' @> $4::jsonb)) \
+                 AND (r.resource->'code' @> $5::jsonb OR r.resource->'code' @> $6::jsonb)"
+            ),
+            "expected (a OR b) AND c, got: {}",
+            built.sql
+        );
+        assert_eq!(built.params.len(), 6);
+        for (param, code) in built.params.iter().zip(["a", "a", "b", "b", "c", "c"]) {
+            assert!(
+                matches!(param, SqlValue::Json(value) if value.contains(&format!(r#""code":"{code}""#))),
+                "unexpected param order: {:?}",
+                built.params
+            );
+        }
+    }
+
     #[test]
     fn token_debug_plan_is_collected_only_when_requested() {
         let registry = token_registry_with_expression();
@@ -2306,6 +2774,7 @@ mod tests {
         let config = SearchConfig {
             unknown_param_handling: UnknownParamHandling::Lenient,
             collect_debug_plan: true,
+            ..Default::default()
         };
         let converted = build_native_ir_query_from_params_with_config(
             "Observation",
@@ -2365,6 +2834,7 @@ mod tests {
         let config = SearchConfig {
             unknown_param_handling: UnknownParamHandling::Lenient,
             collect_debug_plan: true,
+            ..Default::default()
         };
 
         let converted = build_native_ir_query_from_params_with_config(
@@ -2478,6 +2948,7 @@ mod tests {
         let config = SearchConfig {
             unknown_param_handling: UnknownParamHandling::Lenient,
             collect_debug_plan: true,
+            ..Default::default()
         };
 
         let converted = build_native_ir_query_from_params_with_config(
@@ -2515,6 +2986,7 @@ mod tests {
         let config = SearchConfig {
             unknown_param_handling: UnknownParamHandling::Lenient,
             collect_debug_plan: true,
+            ..Default::default()
         };
 
         let converted = build_native_ir_query_from_params_with_config(
@@ -2577,6 +3049,7 @@ mod tests {
         let config = SearchConfig {
             unknown_param_handling: UnknownParamHandling::Lenient,
             collect_debug_plan: true,
+            ..Default::default()
         };
 
         let converted = build_native_ir_query_from_params_with_config(
@@ -2601,13 +3074,18 @@ mod tests {
         assert!(
             plan.predicates[0]
                 .sql_shape
-                .contains("identifier: [{system: $system, value: $value}]")
+                .contains("identifier ? (@.system == $system && @.value == $value)")
         );
 
         let built = converted.builder.with_raw_resource(true).build().unwrap();
         assert!(
-            built.sql.contains("r.resource->'subject'->'identifier' @>"),
-            "reference :identifier runtime path should use in-place identifier containment, got: {}",
+            built.sql.contains("r.resource @? $") && built.sql.contains("::jsonpath"),
+            "reference :identifier runtime path should use a bound jsonpath filter, got: {}",
+            built.sql
+        );
+        assert!(
+            !built.sql.contains("hospital.example") && !built.sql.contains("abc"),
+            "identifier values must be bound, not interpolated: {}",
             built.sql
         );
 
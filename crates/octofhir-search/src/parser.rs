@@ -1,5 +1,6 @@
 use crate::parameters::{SearchModifier, SearchParameterType, SearchPrefix};
 use crate::registry::SearchParameterRegistry;
+use octofhir_core::paging::{CURSOR_PARAM, PageCursor};
 use std::borrow::Cow;
 use thiserror::Error;
 use url::form_urlencoded;
@@ -81,10 +82,38 @@ impl SearchParameterParser {
         (None, value)
     }
 
+    /// Split one raw query value into its comma-separated OR alternatives.
+    ///
+    /// A comma escaped as `\,` is part of the value and comes back
+    /// unescaped. Other escapes (`\|`, `\$`, `\\`) are kept as written for
+    /// the type-specific parsing, but an escaped backslash does not escape
+    /// the comma after it. Empty alternatives are returned as is.
+    pub fn split_or_values(value: &str) -> Vec<String> {
+        let mut values = Vec::new();
+        let mut current = String::new();
+        let mut chars = value.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => match chars.next() {
+                    Some(',') => current.push(','),
+                    Some(next) => {
+                        current.push('\\');
+                        current.push(next);
+                    }
+                    None => current.push('\\'),
+                },
+                ',' => values.push(std::mem::take(&mut current)),
+                c => current.push(c),
+            }
+        }
+        values.push(current);
+        values
+    }
+
     /// Parse one raw query value into FHIR comma-OR values with prefixes.
     pub fn parse_values(value: &str) -> Vec<ParsedValue> {
-        value
-            .split(',')
+        Self::split_or_values(value)
+            .iter()
             .filter_map(|raw_val| {
                 let raw_val = raw_val.trim();
                 if raw_val.is_empty() {
@@ -267,6 +296,30 @@ mod tests_parsing {
     use super::*;
     use crate::parameters::SearchModifier;
 
+    #[test]
+    fn splits_or_values_on_unescaped_commas() {
+        assert_eq!(
+            SearchParameterParser::split_or_values("a,b\\,c"),
+            vec!["a", "b,c"]
+        );
+        assert_eq!(
+            SearchParameterParser::split_or_values("a\\\\,b\\|c"),
+            vec!["a\\\\", "b\\|c"]
+        );
+        assert_eq!(
+            SearchParameterParser::split_or_values("a,,"),
+            vec!["a", "", ""]
+        );
+
+        let parsed = SearchParameterParser::parse_query("code=x%5C%2Cy,z");
+        let raws: Vec<_> = parsed.params[0]
+            .values
+            .iter()
+            .map(|v| v.raw.as_str())
+            .collect();
+        assert_eq!(raws, vec!["x,y", "z"]);
+    }
+
     #[test]
     fn parses_contains_modifier_for_name() {
         let parsed = SearchParameterParser::parse_query("name:contains=Jo");
@@ -455,6 +508,7 @@ mod tests_validation {
 const CONTROL_PARAMS: &[&str] = &[
     "_count",
     "_offset",
+    "_cursor",
     "_sort",
     "_include",
     "_revinclude",
@@ -463,6 +517,7 @@ const CONTROL_PARAMS: &[&str] = &[
     "_total",
     "_contained",
     "_containedType",
+    "_format",
 ];
 
 impl ParsedParameters {
@@ -526,6 +581,17 @@ impl ParsedParameters {
             });
         }
 
+        // Validate _cursor (only ever taken from a page link)
+        if let Some(p) = self.params.iter().find(|p| p.name == CURSOR_PARAM)
+            && let Some(v) = p.values.first()
+            && PageCursor::decode(&v.raw).is_none()
+        {
+            return Err(SearchValidationError::InvalidValue {
+                param: CURSOR_PARAM.to_string(),
+                message: "is not a page cursor from this server".to_string(),
+            });
+        }
+
         Ok(())
     }
 }
@@ -50,6 +50,7 @@ const HARD_CAPACITY_MULTIPLIER: f32 = 1.5;
 ///
 /// The key includes:
 /// - Resource type being queried
+/// - Schema the query reads from (one per tenant)
 /// - Parameter names in sorted order
 /// - Modifier for each parameter
 /// - Type of each parameter (token, date, string, etc.)
@@ -57,6 +58,9 @@ const HARD_CAPACITY_MULTIPLIER: f32 = 1.5;
 pub struct QueryCacheKey {
     /// Resource type (e.g., "Patient", "Observation")
     pub resource_type: String,
+    /// Schema the resource table is read from. The SQL names it, so queries
+    /// of different tenants never share a template.
+    pub schema: String,
     /// Sorted list of (parameter_name, modifier, type) tuples
     pub parameters: Vec<QueryParamKey>,
     /// Whether the query uses pagination clauses.
@@ -122,6 +126,7 @@ impl QueryCacheKey {
 
         Self {
             resource_type: resource_type.to_string(),
+            schema: "public".to_string(),
             parameters,
             has_pagination,
             pagination_count: None,
@@ -142,6 +147,7 @@ impl QueryCacheKey {
 
         Self {
             resource_type: resource_type.to_string(),
+            schema: "public".to_string(),
             parameters,
             has_pagination,
             pagination_count: None,
@@ -150,6 +156,13 @@ impl QueryCacheKey {
         }
     }
 
+    /// Set the schema the query reads from (`public` by default).
+    #[must_use]
+    pub fn with_schema(mut self, schema: impl Into<String>) -> Self {
+        self.schema = schema.into();
+        self
+    }
+
     /// Attach concrete pagination values to the cache key.
     #[must_use]
     pub fn with_pagination(mut self, count: Option<u32>, offset: Option<u32>) -> Self {
@@ -228,6 +241,7 @@ impl QueryCacheKey {
 impl Hash for QueryCacheKey {
     fn hash<H: Hasher>(&self, state: &mut H) {
         self.resource_type.hash(state);
+        self.schema.hash(state);
         self.has_pagination.hash(state);
         self.pagination_count.hash(state);
         self.pagination_offset.hash(state);
@@ -702,6 +716,16 @@ mod tests {
         assert_ne!(first_page.cache_hash(), second_page.cache_hash());
     }
 
+    #[test]
+    fn test_cache_key_different_schemas() {
+        let public = QueryCacheKey::from_typed_params("Patient", Vec::new(), false, Vec::new());
+        let tenant = public.clone().with_schema("tenant_acme");
+
+        assert_eq!(public.schema, "public");
+        assert_ne!(public, tenant);
+        assert_ne!(public.cache_hash(), tenant.cache_hash());
+    }
+
     #[test]
     fn test_cache_insert_and_get() {
         let cache = QueryCache::new(10);
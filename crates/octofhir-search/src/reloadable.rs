@@ -22,13 +22,17 @@
 //! ```
 
 use arc_swap::ArcSwap;
+use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tracing::{debug, info};
 
 use octofhir_canonical_manager::CanonicalManager;
+use octofhir_core::paging::PageLinkStyle;
+use octofhir_storage::{SearchParams, SortParam};
 
 use crate::loader::{ElementTypeResolver, LoaderError, load_search_parameters};
+use crate::params_converter::parse_sort_fields;
 use crate::query_cache::{CacheStatsSnapshot, QueryCache};
 use crate::registry::SearchParameterRegistry;
 
@@ -44,6 +48,10 @@ pub struct SearchConfig {
     pub registry: Arc<SearchParameterRegistry>,
     /// Optional query cache for performance optimization
     pub cache: Option<Arc<QueryCache>>,
+    /// Sort applied to searches without `_sort`
+    pub default_sort: DefaultSort,
+    /// How page links in search, history and `$everything` bundles address pages
+    pub link_style: PageLinkStyle,
 }
 
 impl SearchConfig {
@@ -54,6 +62,8 @@ impl SearchConfig {
             max_count: 100,
             registry,
             cache: None,
+            default_sort: DefaultSort::default(),
+            link_style: PageLinkStyle::default(),
         }
     }
 
@@ -64,6 +74,12 @@ impl SearchConfig {
         self
     }
 
+    /// Set the sort applied to searches without `_sort`.
+    pub fn with_default_sort(mut self, default_sort: DefaultSort) -> Self {
+        self.default_sort = default_sort;
+        self
+    }
+
     /// Enable query caching with the given capacity.
     pub fn with_cache(mut self, capacity: usize) -> Self {
         self.cache = Some(Arc::new(QueryCache::new(capacity)));
@@ -82,6 +98,79 @@ impl SearchConfig {
     }
 }
 
+/// `_sort` value used when none is configured.
+pub const DEFAULT_SORT: &str = "_lastUpdated,_id";
+
+/// Sort applied to searches that carry no `_sort`.
+///
+/// Without a sort the result order is whatever the planner produces, which can
+/// differ between two identical queries and makes pages overlap or skip
+/// resources. Defaults to `_lastUpdated,_id`; types whose high-recall searches
+/// can't afford the `ORDER BY` can opt out with an empty override.
+#[derive(Debug, Clone)]
+pub struct DefaultSort {
+    global: Vec<SortParam>,
+    per_type: HashMap<String, Vec<SortParam>>,
+}
+
+impl Default for DefaultSort {
+    fn default() -> Self {
+        Self {
+            global: parse_sort_fields(DEFAULT_SORT),
+            per_type: HashMap::new(),
+        }
+    }
+}
+
+impl DefaultSort {
+    /// Build from `_sort`-style field lists, e.g. `["-_lastUpdated", "_id"]`.
+    ///
+    /// `overrides` maps a resource type to the fields used instead of
+    /// `global` for that type; an empty override disables the default sort
+    /// for the type.
+    pub fn new(global: &[String], overrides: &HashMap<String, Vec<String>>) -> Self {
+        fn parse(fields: &[String]) -> Vec<SortParam> {
+            fields.iter().flat_map(|f| parse_sort_fields(f)).collect()
+        }
+        Self {
+            global: parse(global),
+            per_type: overrides
+                .iter()
+                .map(|(rt, fields)| (rt.clone(), parse(fields)))
+                .collect(),
+        }
+    }
+
+    /// Returns true if no resource type gets a default sort.
+    pub fn is_empty(&self) -> bool {
+        self.global.is_empty() && self.per_type.values().all(Vec::is_empty)
+    }
+
+    /// Sort fields for searches not bound to one resource type.
+    pub fn global(&self) -> &[SortParam] {
+        &self.global
+    }
+
+    /// Sort fields for `resource_type`.
+    pub fn for_type(&self, resource_type: &str) -> &[SortParam] {
+        self.per_type
+            .get(resource_type)
+            .unwrap_or(&self.global)
+            .as_slice()
+    }
+
+    /// Set the default sort on `params` when the client gave no `_sort`.
+    pub fn apply(&self, resource_type: &str, params: &mut SearchParams) {
+        if params.sort.is_some() {
+            return;
+        }
+        let sort = self.for_type(resource_type);
+        if !sort.is_empty() {
+            params.sort = Some(sort.to_vec());
+        }
+    }
+}
+
 /// Configuration options for search behavior.
 #[derive(Debug, Clone)]
 pub struct SearchOptions {
@@ -91,6 +180,10 @@ pub struct SearchOptions {
     pub max_count: usize,
     /// Query cache capacity (0 to disable)
     pub cache_capacity: usize,
+    /// Sort applied to searches without `_sort`
+    pub default_sort: DefaultSort,
+    /// How page links address pages
+    pub link_style: PageLinkStyle,
 }
 
 impl Default for SearchOptions {
@@ -99,6 +192,8 @@ impl Default for SearchOptions {
             default_count: 10,
             max_count: 100,
             cache_capacity: 1000,
+            default_sort: DefaultSort::default(),
+            link_style: PageLinkStyle::default(),
         }
     }
 }
@@ -148,6 +243,8 @@ impl ReloadableSearchConfig {
             max_count: options.max_count,
             registry,
             cache: cache.clone(),
+            default_sort: options.default_sort.clone(),
+            link_style: options.link_style,
         };
 
         Ok(Self {
@@ -170,6 +267,8 @@ impl ReloadableSearchConfig {
             max_count: options.max_count,
             registry,
             cache: cache.clone(),
+            default_sort: options.default_sort.clone(),
+            link_style: options.link_style,
         };
 
         Self {
@@ -214,6 +313,8 @@ impl ReloadableSearchConfig {
             max_count: current.max_count,
             registry: new_registry,
             cache: self.cache.clone(),
+            default_sort: current.default_sort.clone(),
+            link_style: current.link_style,
         };
 
         // Atomic swap - old readers continue with old config, new readers get new config
@@ -229,9 +330,9 @@ impl ReloadableSearchConfig {
         Ok(())
     }
 
-    /// Update search options (count limits).
+    /// Update search options (count limits and default sort).
     ///
-    /// Updates the default and max count settings without reloading the registry.
+    /// Updates the count and default sort settings without reloading the registry.
     /// Uses atomic swap to update configuration without blocking readers.
     pub async fn update_options(&self, new_options: SearchOptions) {
         info!(
@@ -253,6 +354,8 @@ impl ReloadableSearchConfig {
             max_count: new_options.max_count,
             registry: current.registry.clone(),
             cache: self.cache.clone(),
+            default_sort: new_options.default_sort.clone(),
+            link_style: new_options.link_style,
         };
 
         // Atomic swap
@@ -305,6 +408,7 @@ mod tests {
             default_count: 20,
             max_count: 200,
             cache_capacity: 500,
+            ..Default::default()
         };
 
         let config = ReloadableSearchConfig::with_registry(registry, options);
@@ -327,6 +431,7 @@ mod tests {
                 default_count: 50,
                 max_count: 500,
                 cache_capacity: 1000,
+                ..Default::default()
             })
             .await;
 
@@ -365,4 +470,50 @@ mod tests {
         // Cache should be disabled
         assert!(config.cache_stats().is_none());
     }
+
+    #[test]
+    fn test_default_sort_applies_only_without_sort() {
+        let overrides = HashMap::from([
+            ("Observation".to_string(), vec!["-date".to_string()]),
+            ("AuditEvent".to_string(), Vec::new()),
+        ]);
+        let default_sort = DefaultSort::new(&["-_lastUpdated".into(), "_id".into()], &overrides);
+
+        let mut params = SearchParams::new();
+        default_sort.apply("Patient", &mut params);
+        let sort = params.sort.expect("default sort applied");
+        assert_eq!(sort.len(), 2);
+        assert_eq!(
+            (sort[0].field.as_str(), sort[0].descending),
+            ("_lastUpdated", true)
+        );
+        assert_eq!((sort[1].field.as_str(), sort[1].descending), ("_id", false));
+
+        let mut params = SearchParams::new();
+        default_sort.apply("Observation", &mut params);
+        let sort = params.sort.expect("override applied");
+        assert_eq!((sort[0].field.as_str(), sort[0].descending), ("date", true));
+
+        let mut params = SearchParams::new();
+        default_sort.apply("AuditEvent", &mut params);
+        assert!(params.sort.is_none(), "empty override disables the default");
+
+        let mut params = SearchParams::new().with_sort("name", false);
+        default_sort.apply("Patient", &mut params);
+        let sort = params.sort.expect("client sort kept");
+        assert_eq!(sort.len(), 1);
+        assert_eq!(sort[0].field, "name");
+
+        assert!(!default_sort.is_empty());
+        assert!(DefaultSort::new(&[], &Default::default()).is_empty());
+
+        let mut params = SearchParams::new();
+        DefaultSort::default().apply("Patient", &mut params);
+        let sort = params.sort.expect("built-in default applied");
+        assert_eq!(
+            (sort[0].field.as_str(), sort[0].descending),
+            ("_lastUpdated", false)
+        );
+        assert_eq!((sort[1].field.as_str(), sort[1].descending), ("_id", false));
+    }
 }
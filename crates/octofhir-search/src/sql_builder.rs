@@ -37,6 +37,9 @@ pub enum SqlBuilderError {
 
     #[error("Query too complex: {0}")]
     QueryTooComplex(String),
+
+    #[error("Unknown search parameters: {}", .0.join(", "))]
+    UnknownParameters(Vec<String>),
 }
 
 // ============================================================================
@@ -323,6 +326,8 @@ impl SortOrder {
 pub struct SortSpec {
     pub path: Option<JsonbPath>,
     pub column: Option<String>,
+    /// Sort by the query's relevance score (see [`FhirQueryBuilder::with_score`]).
+    pub score: bool,
     pub order: SortOrder,
     pub nulls_last: bool,
 }
@@ -332,6 +337,7 @@ impl SortSpec {
         Self {
             path: Some(path),
             column: None,
+            score: false,
             order,
             nulls_last: true,
         }
@@ -351,10 +357,23 @@ impl SortSpec {
         Ok(Self {
             path: None,
             column: Some(column),
+            score: false,
             order,
             nulls_last: true,
         })
     }
+
+    /// Sort by relevance score. Rows of a query without a score are left
+    /// unordered by this spec.
+    pub fn score(order: SortOrder) -> Self {
+        Self {
+            path: None,
+            column: None,
+            score: true,
+            order,
+            nulls_last: true,
+        }
+    }
 }
 
 /// Pagination settings.
@@ -535,6 +554,8 @@ pub struct FhirQueryBuilder {
     /// When true, emit `resource::text` instead of `resource` in SELECT.
     /// This avoids JSONB -> Value deserialization for raw string output.
     raw_resource: bool,
+    /// Relevance expression selected as `score` after the row columns.
+    score: Option<SearchCondition>,
 }
 
 impl FhirQueryBuilder {
@@ -552,6 +573,7 @@ impl FhirQueryBuilder {
             mode: QueryMode::Resources,
             table_alias: None,
             raw_resource: false,
+            score: None,
         }
     }
 
@@ -567,6 +589,25 @@ impl FhirQueryBuilder {
         self
     }
 
+    /// Select a relevance score as a sixth `score` column and allow sorting
+    /// by it with [`SortSpec::score`].
+    ///
+    /// `sql` numbers its placeholders from `$1` like a
+    /// [`SearchCondition::Raw`] block; they are bound after the WHERE
+    /// parameters.
+    pub fn with_score(mut self, sql: impl Into<String>, params: Vec<SqlValue>) -> Self {
+        self.score = Some(SearchCondition::Raw {
+            sql: sql.into(),
+            params,
+        });
+        self
+    }
+
+    /// Whether the query selects a relevance score.
+    pub fn has_score(&self) -> bool {
+        self.score.is_some()
+    }
+
     /// Add a search condition.
     pub fn where_condition(mut self, condition: SearchCondition) -> Self {
         self.conditions.push(condition);
@@ -666,6 +707,20 @@ impl FhirQueryBuilder {
         let alias = self.table_alias.as_deref().unwrap_or("r");
         let resource_col = format!("{alias}.resource");
 
+        // Build FROM clause with JOINs
+        let from_clause = self.build_from_clause(&full_table, alias)?;
+
+        // Build WHERE clause
+        let where_clause = self.build_where_clause(&resource_col, &mut params)?;
+
+        // Score placeholders follow the WHERE ones (see `extract_params`)
+        let score_sql = match &self.score {
+            Some(score) if self.mode != QueryMode::Count => {
+                Some(Self::condition_to_sql(score, &resource_col, &mut params)?)
+            }
+            _ => None,
+        };
+
         // Build SELECT clause
         let resource_select = if self.raw_resource {
             format!("{alias}.resource::text")
@@ -676,20 +731,18 @@ impl FhirQueryBuilder {
             QueryMode::Count => "SELECT COUNT(*) as total".to_string(),
             QueryMode::IdsOnly => format!("SELECT {alias}.id"),
             QueryMode::Resources | QueryMode::ResourcesWithTotal => {
-                format!(
+                let mut columns = format!(
                     "{resource_select}, {alias}.id, {alias}.txid, {alias}.created_at, {alias}.updated_at"
-                )
+                );
+                if let Some(score_sql) = &score_sql {
+                    columns.push_str(&format!(", {score_sql} AS score"));
+                }
+                columns
             }
         };
 
-        // Build FROM clause with JOINs
-        let from_clause = self.build_from_clause(&full_table, alias)?;
-
-        // Build WHERE clause
-        let where_clause = self.build_where_clause(&resource_col, &mut params)?;
-
         // Build ORDER BY clause
-        let order_clause = self.build_order_clause(&resource_col, alias)?;
+        let order_clause = self.build_order_clause(&resource_col, alias, score_sql.as_deref())?;
 
         // Build LIMIT/OFFSET clause
         let limit_clause = self.build_limit_clause();
@@ -757,6 +810,11 @@ impl FhirQueryBuilder {
         for condition in &self.conditions {
             Self::collect_condition_params(condition, &mut params);
         }
+        if let Some(score) = &self.score
+            && self.mode != QueryMode::Count
+        {
+            Self::collect_condition_params(score, &mut params);
+        }
         params
     }
 
@@ -926,6 +984,7 @@ impl FhirQueryBuilder {
         &self,
         resource_col: &str,
         alias: &str,
+        score_sql: Option<&str>,
     ) -> Result<String, SqlBuilderError> {
         if self.sort.is_empty() {
             return Ok(String::new());
@@ -934,8 +993,11 @@ impl FhirQueryBuilder {
         let parts = self
             .sort
             .iter()
+            .filter(|s| !s.score || score_sql.is_some())
             .map(|s| {
-                let accessor = if let Some(column) = &s.column {
+                let accessor = if s.score {
+                    format!("({})", score_sql.unwrap_or_default())
+                } else if let Some(column) = &s.column {
                     format!(
                         "{}.{}",
                         escape_identifier(alias)?,
@@ -997,6 +1059,7 @@ pub struct SqlBuilder {
     params: Vec<SqlParam>,
     resource_col: String,
     param_offset: usize,
+    schema: Option<String>,
 }
 
 /// A SQL parameter value with its type information.
@@ -1030,6 +1093,7 @@ impl SqlBuilder {
             params: Vec::new(),
             resource_col: "resource".to_string(),
             param_offset: 0,
+            schema: None,
         }
     }
 
@@ -1040,6 +1104,7 @@ impl SqlBuilder {
             params: Vec::new(),
             resource_col: column.into(),
             param_offset: 0,
+            schema: None,
         }
     }
 
@@ -1049,6 +1114,22 @@ impl SqlBuilder {
         self
     }
 
+    /// Set the schema that tables referenced by subqueries (chains, `_has`,
+    /// index tables) are qualified with. Without one they are left to the
+    /// connection's `search_path`.
+    pub fn with_schema(mut self, schema: impl Into<String>) -> Self {
+        self.schema = Some(schema.into());
+        self
+    }
+
+    /// Quoted name of `table`, qualified with the builder's schema if set.
+    pub fn table_ref(&self, table: &str) -> String {
+        match &self.schema {
+            Some(schema) => format!("\"{schema}\".\"{table}\""),
+            None => format!("\"{table}\""),
+        }
+    }
+
     /// Get the resource column name.
     pub fn resource_column(&self) -> &str {
         &self.resource_col
@@ -2046,6 +2127,46 @@ mod tests {
         assert!(query.sql.contains("ORDER BY \"r\".\"updated_at\" DESC"));
     }
 
+    #[test]
+    fn test_fhir_query_builder_score_follows_where_params() {
+        let builder = FhirQueryBuilder::new("Patient", "public")
+            .with_alias("r")
+            .where_condition(SearchCondition::Raw {
+                sql: "r.text_tsv @@ websearch_to_tsquery('english', $1)".into(),
+                params: vec![SqlValue::Text("headache".into())],
+            })
+            .with_score(
+                "ts_rank_cd(r.text_tsv, websearch_to_tsquery('english', $1))",
+                vec![SqlValue::Text("headache".into())],
+            )
+            .sort_by(SortSpec::score(SortOrder::Desc));
+        let query = builder.build().unwrap();
+
+        let score = "ts_rank_cd(r.text_tsv, websearch_to_tsquery('english', $2))";
+        assert!(query.sql.contains(&format!(", {score} AS score FROM")));
+        assert!(query.sql.contains(&format!("ORDER BY ({score}) DESC")));
+        assert_eq!(query.params.len(), 2);
+        // A cached template is re-bound with `extract_params`, in build order
+        let extracted = builder.extract_params();
+        assert_eq!(extracted.len(), 2);
+        assert!(matches!(&extracted[1], SqlValue::Text(v) if v == "headache"));
+
+        let count = builder.count_only().build().unwrap();
+        assert!(!count.sql.contains("ts_rank_cd"));
+        assert_eq!(count.params.len(), 1);
+    }
+
+    #[test]
+    fn test_fhir_query_builder_score_sort_without_score() {
+        let query = FhirQueryBuilder::new("Patient", "public")
+            .sort_by(SortSpec::score(SortOrder::Desc))
+            .build()
+            .unwrap();
+
+        assert!(!query.sql.contains("ORDER BY"));
+        assert!(!query.sql.contains("score"));
+    }
+
     #[test]
     fn test_fhir_query_builder_count_mode() {
         let query = FhirQueryBuilder::new("Patient", "public")
@@ -88,6 +88,12 @@ pub enum TerminologyError {
 
     #[error("CodeSystem not found: {0}")]
     CodeSystemNotFound(String),
+
+    #[error(
+        "Subsumption (:below/:above) is not available for code system {0}: it is neither \
+         SNOMED CT nor a locally loaded CodeSystem"
+    )]
+    SubsumptionUnavailable(String),
 }
 
 /// Hybrid terminology provider with two-tier lookup.
@@ -522,8 +528,9 @@ impl HybridTerminologyProvider {
     ///
     /// # Returns
     ///
-    /// A vector of codes in the hierarchy. If hierarchy expansion is not supported,
-    /// returns just the original code as a fallback.
+    /// A vector of codes in the hierarchy, or
+    /// [`TerminologyError::SubsumptionUnavailable`] if no hierarchy is known for
+    /// the code system.
     ///
     /// # SNOMED CT Support
     ///
@@ -531,6 +538,11 @@ impl HybridTerminologyProvider {
     /// - `<< code` - Self and descendants (Below)
     /// - `>> code` - Self and ancestors (Above)
     ///
+    /// # Local CodeSystems
+    ///
+    /// Other code systems must be loaded locally; their hierarchy is the nesting
+    /// of `CodeSystem.concept`.
+    ///
     /// # Performance
     ///
     /// Target: <200ms for most hierarchies
@@ -552,8 +564,11 @@ impl HybridTerminologyProvider {
             return self.expand_snomed_hierarchy(system, code, direction).await;
         }
 
-        // For other systems, use remote terminology server
-        self.expand_remote_hierarchy(system, code, direction).await
+        if let Some(codes) = self.expand_local_hierarchy(system, code, direction).await {
+            return Ok(codes);
+        }
+
+        Err(TerminologyError::SubsumptionUnavailable(system.to_string()))
     }
 
     /// Expand SNOMED CT hierarchy using Expression Constraint Language (ECL).
@@ -611,36 +626,85 @@ impl HybridTerminologyProvider {
         Ok(codes)
     }
 
-    /// Expand hierarchy using remote terminology server.
+    /// Expand a hierarchy from the nested concepts of a locally loaded
+    /// CodeSystem. Returns `None` if the CodeSystem is not available locally or
+    /// does not list its concepts (e.g. `content: not-present` stubs).
     ///
-    /// This is a generic implementation that attempts to use the $subsumes operation
-    /// or falls back to returning just the code.
-    async fn expand_remote_hierarchy(
+    /// A code the CodeSystem does not define expands to itself.
+    async fn expand_local_hierarchy(
         &self,
         system: &str,
         code: &str,
         direction: HierarchyDirection,
-    ) -> Result<Vec<String>, TerminologyError> {
-        // Note: Full implementation would require:
-        // 1. $subsumes operation to check parent/child relationships
-        // 2. Recursive traversal to build complete hierarchy
-        // 3. Or use of system-specific hierarchy extensions
-        //
-        // For now, we implement a simplified version that returns the code itself
-        // and logs a warning about limited support.
-
-        tracing::warn!(
-            system = system,
-            code = code,
-            direction = ?direction,
-            "Generic hierarchy expansion not fully implemented, returning code only"
-        );
+    ) -> Option<Vec<String>> {
+        let resolved = self.resolve_cached(system).await?;
+        if resolved.resource_type != "CodeSystem" {
+            return None;
+        }
 
-        // Future enhancement: Could try to use $subsumes operation
-        // See: https://www.hl7.org/fhir/codesystem-operation-subsumes.html
+        let concepts = resolved.content.get("concept")?.as_array()?;
+        let codes = match direction {
+            HierarchyDirection::Below => concept_descendants(concepts, code),
+            HierarchyDirection::Above => concept_ancestors(concepts, code),
+        };
+        Some(codes.unwrap_or_else(|| vec![code.to_string()]))
+    }
+}
 
-        Ok(vec![code.to_string()])
+/// The code and every code nested below it, or `None` if the code is not in
+/// the concept tree.
+fn concept_descendants(concepts: &[serde_json::Value], code: &str) -> Option<Vec<String>> {
+    for concept in concepts {
+        let children = concept_children(concept);
+        if concept_code(concept) == Some(code) {
+            let mut codes = vec![code.to_string()];
+            collect_codes(children, &mut codes);
+            return Some(codes);
+        }
+        if let Some(codes) = concept_descendants(children, code) {
+            return Some(codes);
+        }
     }
+    None
+}
+
+/// The code and every code it is nested under, or `None` if the code is not
+/// in the concept tree.
+fn concept_ancestors(concepts: &[serde_json::Value], code: &str) -> Option<Vec<String>> {
+    for concept in concepts {
+        let Some(this) = concept_code(concept) else {
+            continue;
+        };
+        if this == code {
+            return Some(vec![code.to_string()]);
+        }
+        if let Some(mut codes) = concept_ancestors(concept_children(concept), code) {
+            codes.push(this.to_string());
+            return Some(codes);
+        }
+    }
+    None
+}
+
+fn collect_codes(concepts: &[serde_json::Value], codes: &mut Vec<String>) {
+    for concept in concepts {
+        if let Some(code) = concept_code(concept) {
+            codes.push(code.to_string());
+        }
+        collect_codes(concept_children(concept), codes);
+    }
+}
+
+fn concept_code(concept: &serde_json::Value) -> Option<&str> {
+    concept.get("code").and_then(|c| c.as_str())
+}
+
+fn concept_children(concept: &serde_json::Value) -> &[serde_json::Value] {
+    concept
+        .get("concept")
+        .and_then(|c| c.as_array())
+        .map(Vec::as_slice)
+        .unwrap_or_default()
 }
 
 #[async_trait]
@@ -829,6 +893,46 @@ pub struct CacheStats {
 mod tests {
     use super::*;
 
+    fn hierarchy() -> serde_json::Value {
+        serde_json::json!([
+            {"code": "animal", "concept": [
+                {"code": "mammal", "concept": [{"code": "dog"}, {"code": "cat"}]},
+                {"code": "bird"}
+            ]},
+            {"code": "plant"}
+        ])
+    }
+
+    #[test]
+    fn test_concept_descendants() {
+        let concepts = hierarchy();
+        let concepts = concepts.as_array().unwrap();
+        assert_eq!(
+            concept_descendants(concepts, "mammal").unwrap(),
+            vec!["mammal", "dog", "cat"]
+        );
+        assert_eq!(
+            concept_descendants(concepts, "plant").unwrap(),
+            vec!["plant"]
+        );
+        assert!(concept_descendants(concepts, "fungus").is_none());
+    }
+
+    #[test]
+    fn test_concept_ancestors() {
+        let concepts = hierarchy();
+        let concepts = concepts.as_array().unwrap();
+        assert_eq!(
+            concept_ancestors(concepts, "cat").unwrap(),
+            vec!["cat", "mammal", "animal"]
+        );
+        assert_eq!(
+            concept_ancestors(concepts, "animal").unwrap(),
+            vec!["animal"]
+        );
+        assert!(concept_ancestors(concepts, "fungus").is_none());
+    }
+
     #[test]
     fn test_terminology_config_defaults() {
         let config = TerminologyConfig::default();
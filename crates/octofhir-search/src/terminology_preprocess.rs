@@ -31,8 +31,8 @@
 //!   directly and still return `NotImplemented`.
 //! - `:above` / `:below` (subsumption) need `HybridTerminologyProvider`'s
 //!   `expand_hierarchy`, which is not exposed on the `TerminologyProvider`
-//!   trait. Those modifiers continue to return `NotImplemented` until the
-//!   trait is extended or a concrete-typed plumbing is added.
+//!   trait, so they are expanded separately by
+//!   [`pre_expand_subsumption_modifiers`] on the concrete provider.
 
 use crate::parameters::SearchParameterType;
 use crate::registry::SearchParameterRegistry;
@@ -183,8 +183,9 @@ pub async fn pre_expand_terminology_modifiers(
 ///   ancestor.
 ///
 /// The hierarchy traversal lives on `HybridTerminologyProvider` (SNOMED ECL
-/// or remote `$expand` with ECL), so this function takes the concrete
-/// provider — not the dyn-trait used elsewhere.
+/// via remote `$expand`, or the concept tree of a locally loaded CodeSystem),
+/// so this function takes the concrete provider — not the dyn-trait used
+/// elsewhere. Code systems with neither fail with `ExpansionFailed`.
 ///
 /// Rewrites in place:
 ///   `code:below=sys|c` → `code=sys|c,sys|child1,sys|child2,…`
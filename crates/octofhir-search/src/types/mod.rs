@@ -29,9 +29,9 @@ pub use date::build_period_search;
 pub use date::{DateRange, build_date_search, build_indexed_date_inplace, parse_date_range};
 pub use number::{build_gin_quantity_search, build_number_search, build_quantity_search};
 pub use special::{
-    NearParameter, SpecialParameterType, build_content_search, build_filter_search,
-    build_list_search, build_near_search, build_text_search, detect_special_type,
-    parse_near_parameter,
+    CONTENT_TSV_COLUMN, NearParameter, SpecialParameterType, TEXT_TSV_COLUMN, build_content_search,
+    build_filter_search, build_list_search, build_near_search, build_text_search,
+    detect_special_type, full_text_rank_sql, parse_near_parameter,
 };
 pub use string::build_indexed_string_inplace;
 pub use string::{build_array_string_search, build_human_name_search, build_string_search};
@@ -42,8 +42,10 @@ pub use token::{
 };
 pub use uri::{build_uri_array_search, build_uri_search};
 
+use crate::ir::ast::validate_reference_type_modifier;
 use crate::ir::{
-    ResourceColumnParam, render_date_column_clauses_as_or, resolve_composite_component_specs,
+    ReferenceClause, ResourceColumnParam, render_date_column_clauses_as_or,
+    render_reference_identifier_clauses_as_or, resolve_composite_component_specs,
     resolve_resource_column_param,
 };
 use crate::parameters::{ElementTypeHint, SearchModifier, SearchParameter, SearchParameterType};
@@ -90,12 +92,23 @@ fn dispatch_search_inner(
     registry: Option<&SearchParameterRegistry>,
 ) -> Result<(), SqlBuilderError> {
     // Get the FHIRPath expression and convert to JSONB path
-    let expression = definition.expression.as_deref().ok_or_else(|| {
-        SqlBuilderError::InvalidPath(format!(
-            "No expression for search parameter: {}",
-            definition.code
-        ))
-    })?;
+    let expression = match definition.expression.as_deref() {
+        Some(expression) => expression,
+        // `_text`/`_content` match dedicated columns, not a resource path
+        None if matches!(
+            detect_special_type(&definition.code),
+            Some(SpecialParameterType::Text | SpecialParameterType::Content)
+        ) =>
+        {
+            ""
+        }
+        None => {
+            return Err(SqlBuilderError::InvalidPath(format!(
+                "No expression for search parameter: {}",
+                definition.code
+            )));
+        }
+    };
 
     let path_segments = fhirpath_to_jsonb_path(expression, resource_type);
 
@@ -410,20 +423,15 @@ fn build_reference_jsonb_search(
     }
 
     if matches!(param.modifier, Some(SearchModifier::Identifier)) {
-        // `reference:identifier` matches the embedded `.identifier` element as a
-        // plain token; the `:identifier` modifier has already been consumed by
-        // routing here, so drop it before delegating to the token identifier path
-        // (which only accepts token modifiers).
-        let identifier_param = ParsedParam {
-            name: param.name.clone(),
-            modifier: None,
-            values: param.values.clone(),
-        };
-        return build_identifier_search(
-            builder,
-            &identifier_param,
-            &format!("{json_path}->'identifier'"),
-        );
+        // `reference:identifier` matches the logical identifier embedded in the
+        // reference (`<path>.identifier`), not the literal reference.
+        let clauses = ReferenceClause::from_parsed_param(param, "", target_types)?;
+        if let Some(sql) =
+            render_reference_identifier_clauses_as_or(builder, &clauses, path_segments)?
+        {
+            builder.add_condition(sql);
+        }
+        return Ok(());
     }
 
     if matches!(param.modifier, Some(SearchModifier::Missing)) {
@@ -446,7 +454,10 @@ fn build_reference_jsonb_search(
     }
 
     let type_modifier = match &param.modifier {
-        Some(SearchModifier::Type(resource_type)) => Some(resource_type.as_str()),
+        Some(SearchModifier::Type(resource_type)) => {
+            validate_reference_type_modifier(&param.name, resource_type, target_types)?;
+            Some(resource_type.as_str())
+        }
         None => None,
         Some(other) => {
             return Err(SqlBuilderError::InvalidModifier(format!("{other:?}")));
@@ -893,6 +904,16 @@ mod tests {
 
         let result = dispatch_search(&mut builder, &param, &def, "Patient");
         assert!(result.is_err());
+
+        // Only `_text` and `_content` do without an expression
+        let def = Arc::new(SearchParameter::new(
+            "_list",
+            "http://example.org/list",
+            SearchParameterType::Special,
+            vec!["Patient".to_string()],
+        ));
+        let result = dispatch_search(&mut builder, &param, &def, "Patient");
+        assert!(result.is_err());
     }
 
     #[test]
@@ -80,22 +80,58 @@ pub fn build_near_search(
     Ok(())
 }
 
+/// Generated `tsvector` column over the narrative, created by the PostgreSQL
+/// schema manager on every resource table.
+pub const TEXT_TSV_COLUMN: &str = "text_tsv";
+
+/// Generated `tsvector` column over all string values of the resource.
+pub const CONTENT_TSV_COLUMN: &str = "content_tsv";
+
+/// Qualify a column of the resource table with the alias of the resource
+/// column (`r.resource` -> `r.text_tsv`).
+fn sibling_column(builder: &SqlBuilder, column: &str) -> String {
+    match builder.resource_column().strip_suffix("resource") {
+        Some(prefix) => format!("{prefix}{column}"),
+        None => column.to_string(),
+    }
+}
+
+/// Full-text query for a search value.
+///
+/// `websearch_to_tsquery` accepts free text, `"quoted phrases"`, `or` and
+/// `-negation` and never fails on malformed input, unlike `to_tsquery`.
+fn full_text_query(param: usize) -> String {
+    format!("websearch_to_tsquery('english', ${param})")
+}
+
+/// Relevance of a row to a `_text` / `_content` search, for ordering or
+/// `Bundle.entry.search.score`.
+///
+/// `param` is the bind index of the search value; `column` is one of
+/// [`TEXT_TSV_COLUMN`] / [`CONTENT_TSV_COLUMN`], qualified as needed.
+pub fn full_text_rank_sql(column: &str, param: usize) -> String {
+    format!("ts_rank_cd({column}, {})", full_text_query(param))
+}
+
+fn build_full_text_search(builder: &mut SqlBuilder, column: &str, value: &str) {
+    let column = sibling_column(builder, column);
+    let p = builder.add_text_param(value);
+    builder.add_raw_condition(format!("{column} @@ {}", full_text_query(p)));
+}
+
 /// Build SQL for _text search on narrative.
+///
+/// Matches against the GIN-indexed [`TEXT_TSV_COLUMN`].
 pub fn build_text_search(builder: &mut SqlBuilder, value: &str) -> Result<(), SqlBuilderError> {
-    let p = builder.add_text_param(value);
-    builder.add_raw_condition(format!(
-        "to_tsvector('english', regexp_replace(resource->'text'->>'div', '<[^>]*>', '', 'g')) \
-         @@ plainto_tsquery('english', ${p})"
-    ));
+    build_full_text_search(builder, TEXT_TSV_COLUMN, value);
     Ok(())
 }
 
 /// Build SQL for _content search on entire resource.
+///
+/// Matches against the GIN-indexed [`CONTENT_TSV_COLUMN`].
 pub fn build_content_search(builder: &mut SqlBuilder, value: &str) -> Result<(), SqlBuilderError> {
-    let p = builder.add_text_param(value);
-    builder.add_raw_condition(format!(
-        "to_tsvector('english', resource::text) @@ plainto_tsquery('english', ${p})"
-    ));
+    build_full_text_search(builder, CONTENT_TSV_COLUMN, value);
     Ok(())
 }
 
@@ -213,7 +249,12 @@ mod tests {
         let mut builder = SqlBuilder::new();
         build_text_search(&mut builder, "headache").unwrap();
         let clause = builder.build_where_clause().unwrap();
-        assert!(clause.contains("to_tsvector") && clause.contains("text"));
+        assert!(clause.contains("text_tsv @@ websearch_to_tsquery('english', $1)"));
+
+        let mut builder = SqlBuilder::with_resource_column("r.resource");
+        build_text_search(&mut builder, "headache").unwrap();
+        let clause = builder.build_where_clause().unwrap();
+        assert!(clause.contains("r.text_tsv @@"));
     }
 
     #[test]
@@ -221,7 +262,15 @@ mod tests {
         let mut builder = SqlBuilder::new();
         build_content_search(&mut builder, "patient").unwrap();
         let clause = builder.build_where_clause().unwrap();
-        assert!(clause.contains("resource::text"));
+        assert!(clause.contains("content_tsv @@ websearch_to_tsquery('english', $1)"));
+    }
+
+    #[test]
+    fn test_full_text_rank_sql() {
+        assert_eq!(
+            full_text_rank_sql("r.text_tsv", 2),
+            "ts_rank_cd(r.text_tsv, websearch_to_tsquery('english', $2))"
+        );
     }
 
     #[test]
@@ -17,9 +17,9 @@
 use crate::parser::ParsedParam;
 use crate::sql_builder::{SqlBuilder, SqlBuilderError};
 use crate::{
-    ir::StringClause, ir::render_indexed_string_clauses_as_or,
-    ir::render_string_array_clauses_as_or, ir::render_string_human_name_clauses_as_or,
-    ir::render_string_path_clauses_as_or,
+    ir::StringClause, ir::render_index_table_string_clauses_as_or,
+    ir::render_indexed_string_clauses_as_or, ir::render_string_array_clauses_as_or,
+    ir::render_string_human_name_clauses_as_or, ir::render_string_path_clauses_as_or,
 };
 
 /// In-place string search on the resource JSONB (no sidecar): predicates run over
@@ -32,6 +32,15 @@ pub fn build_indexed_string_inplace(
     resource_type: &str,
     definition: &crate::parameters::SearchParameter,
 ) -> Result<(), SqlBuilderError> {
+    // Materialized params read their precomputed index table instead.
+    if let Some(index_table) = definition.index_table.as_deref() {
+        let clauses = StringClause::from_parsed_param(param, resource_type)?;
+        if let Some(sql) = render_index_table_string_clauses_as_or(builder, &clauses, index_table) {
+            builder.add_condition(sql);
+        }
+        return Ok(());
+    }
+
     let expression = definition.expression.as_deref().unwrap_or_default();
     let segments = crate::sql_builder::fhirpath_to_jsonb_path(expression, resource_type);
     let paths_json = crate::sql_builder::paths_to_json(&crate::sql_builder::extraction_paths(
@@ -278,6 +287,57 @@ mod tests {
         assert!(!clause.contains("fhir_extract_text"), "clause: {clause}");
     }
 
+    #[test]
+    fn test_indexed_string_with_index_table() {
+        let mut builder = SqlBuilder::with_resource_column("r.resource");
+        let param = ParsedParam {
+            name: "name".to_string(),
+            modifier: None,
+            values: vec![
+                ParsedValue {
+                    prefix: None,
+                    raw: "John".to_string(),
+                },
+                ParsedValue {
+                    prefix: None,
+                    raw: "Jane".to_string(),
+                },
+            ],
+        };
+        let def = make_string_definition(Some("fhir_s_patient_name"))
+            .with_index_table("patient_name_idx");
+
+        build_indexed_string_inplace(&mut builder, &param, "Patient", &def).unwrap();
+
+        let clause = builder.build_where_clause().unwrap();
+        assert_eq!(
+            clause,
+            "EXISTS (SELECT 1 FROM \"patient_name_idx\" ix WHERE (ix.resource_id = r.id AND \
+             (ix.blob LIKE $1 OR ix.blob LIKE $2)))"
+        );
+        assert_eq!(builder.params()[0].as_str(), "% john%");
+    }
+
+    #[test]
+    fn test_indexed_string_with_index_table_exact_and_missing() {
+        let def = make_string_definition(None).with_index_table("patient_name_idx");
+
+        let mut builder = SqlBuilder::with_resource_column("r.resource");
+        let param = make_param("name", "John Doe", Some(SearchModifier::Exact));
+        build_indexed_string_inplace(&mut builder, &param, "Patient", &def).unwrap();
+        let clause = builder.build_where_clause().unwrap();
+        assert!(clause.contains("ix.value = $1"), "clause: {clause}");
+
+        let mut builder = SqlBuilder::with_resource_column("r.resource");
+        let param = make_param("name", "true", Some(SearchModifier::Missing));
+        build_indexed_string_inplace(&mut builder, &param, "Patient", &def).unwrap();
+        let clause = builder.build_where_clause().unwrap();
+        assert_eq!(
+            clause,
+            "NOT EXISTS (SELECT 1 FROM \"patient_name_idx\" ix WHERE ix.resource_id = r.id)"
+        );
+    }
+
     #[test]
     fn test_indexed_string_without_typed_extract_fn_uses_generic() {
         let mut builder = SqlBuilder::new();
@@ -55,6 +55,7 @@
 //!
 //! - `POST /policies/$reload` - Trigger policy cache reload
 //! - `GET /policies/status` - Get policy cache status and statistics
+//! - `POST /$policy-evaluate` - Evaluate policies against a synthetic request, with trace
 //!
 //! ## OAuth Clients
 //!
@@ -63,13 +64,30 @@
 //! ## Audit Analytics
 //!
 //! - `GET /audit/$analytics` - Get audit event analytics and aggregations
+//!
+//! ## Search Diagnostics
+//!
+//! - `GET /search/:resourceType/$explain` - Query plan, SQL and index suggestions for a search
+//!
+//! ## Maintenance
+//!
+//! - `POST /maintenance/$reference-integrity` - Start a scan for references to resources that do not exist
+//! - `GET /maintenance/$reference-integrity?job=<id>` - Report of a finished scan
+//!
+//! ## Rate Limits
+//!
+//! - `GET /rate-limits/:client` - Get a client's quota in the current window
+//! - `DELETE /rate-limits/:client` - Reset a client's quota
 
 pub mod audit;
 pub mod client;
 pub mod configuration;
 pub mod identity_provider;
 pub mod policy;
+pub mod rate_limit;
+pub mod reference_integrity;
 pub mod role;
+pub mod search_explain;
 pub mod state;
 pub mod user;
 
@@ -83,8 +101,11 @@ pub use identity_provider::{
     create_identity_provider, delete_identity_provider, read_identity_provider,
     search_identity_providers, update_identity_provider,
 };
-pub use policy::{PolicyState, policy_status, reload_policies};
+pub use policy::{PolicyState, evaluate_policy, policy_status, reload_policies};
+pub use rate_limit::{get_rate_limit, reset_rate_limit};
+pub use reference_integrity::{check_reference_integrity, reference_integrity_report};
 pub use role::{create_role, delete_role, list_permissions, read_role, search_roles, update_role};
+pub use search_explain::explain_search;
 pub use state::{AdminState, CombinedAdminState};
 pub use user::{
     bulk_update_users, create_user, delete_user, get_user_sessions, read_user, reset_user_password,
@@ -173,6 +194,76 @@ where
     Router::new().route("/audit/$analytics", get(get_audit_analytics))
 }
 
+/// Creates the search diagnostics routes.
+///
+/// These routes require admin authentication.
+///
+/// # Type Parameters
+///
+/// - `S`: Application state that provides `AuthState` and `AppState` via `FromRef`.
+pub fn search_explain_routes<S>() -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+    AuthState: FromRef<S>,
+    AppState: FromRef<S>,
+{
+    Router::new().route("/search/{resource_type}/$explain", get(explain_search))
+}
+
+/// Creates the policy debugging routes.
+///
+/// These routes require admin authentication.
+///
+/// # Type Parameters
+///
+/// - `S`: Application state that provides `AuthState` and `AppState` via `FromRef`.
+pub fn policy_evaluate_routes<S>() -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+    AuthState: FromRef<S>,
+    AppState: FromRef<S>,
+{
+    Router::new().route("/$policy-evaluate", post(evaluate_policy))
+}
+
+/// Creates the data maintenance routes.
+///
+/// These routes require admin authentication.
+///
+/// # Type Parameters
+///
+/// - `S`: Application state that provides `AuthState` and `AppState` via `FromRef`.
+pub fn maintenance_routes<S>() -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+    AuthState: FromRef<S>,
+    AppState: FromRef<S>,
+{
+    Router::new().route(
+        "/maintenance/$reference-integrity",
+        get(reference_integrity_report).post(check_reference_integrity),
+    )
+}
+
+/// Creates the rate limit quota routes.
+///
+/// These routes require admin authentication.
+///
+/// # Type Parameters
+///
+/// - `S`: Application state that provides `AuthState` and `AppState` via `FromRef`.
+pub fn rate_limit_routes<S>() -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+    AuthState: FromRef<S>,
+    AppState: FromRef<S>,
+{
+    Router::new().route(
+        "/rate-limits/{client}",
+        get(get_rate_limit).delete(reset_rate_limit),
+    )
+}
+
 /// Creates the configuration management routes.
 ///
 /// These routes require admin authentication and ConfigState via `FromRef`.
@@ -6,15 +6,22 @@
 //!
 //! - `POST /admin/policies/$reload` - Trigger policy cache reload
 //! - `GET /admin/policies/status` - Get policy cache status and statistics
+//! - `POST /admin/$policy-evaluate` - Evaluate policies against a synthetic request
 
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use axum::{Json, extract::State, http::StatusCode, response::IntoResponse};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use octofhir_api::ApiError;
 use octofhir_auth::middleware::AdminAuth;
-use octofhir_auth::policy::{PolicyCache, PolicyReloadService, ReloadStats};
+use octofhir_auth::policy::{
+    ClientIdentity, ClientType, PolicyCache, PolicyContextBuilder, PolicyReloadService,
+    ReloadStats, UserIdentity,
+};
+
+use crate::server::AppState;
 
 // =============================================================================
 // Types
@@ -94,6 +101,48 @@ impl From<ReloadStats> for ReloadStatsResponse {
     }
 }
 
+/// Synthetic request for the `$policy-evaluate` endpoint.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PolicyEvaluateRequest {
+    /// HTTP method (default: `GET`).
+    #[serde(default = "default_method")]
+    pub method: String,
+    /// FHIR path relative to the base URL (e.g. `/Patient/123`).
+    pub path: String,
+    /// Query parameters.
+    #[serde(default)]
+    pub query: HashMap<String, String>,
+    /// Request body.
+    #[serde(default)]
+    pub body: Option<serde_json::Value>,
+    /// Existing resource, for read/update/delete.
+    #[serde(default)]
+    pub resource: Option<serde_json::Value>,
+    /// Operation ID for policy targeting (e.g. `fhir.read`).
+    #[serde(default)]
+    pub operation_id: Option<String>,
+    /// Acting user; omit for a client-credentials request.
+    #[serde(default)]
+    pub user: Option<UserIdentity>,
+    /// Acting client (default: a public test client).
+    #[serde(default)]
+    pub client: Option<ClientIdentity>,
+    /// Granted scopes, space separated.
+    #[serde(default)]
+    pub scope: String,
+    /// SMART patient launch context.
+    #[serde(default)]
+    pub patient: Option<String>,
+    /// SMART encounter launch context.
+    #[serde(default)]
+    pub encounter: Option<String>,
+}
+
+fn default_method() -> String {
+    "GET".to_string()
+}
+
 // =============================================================================
 // Handlers
 // =============================================================================
@@ -178,6 +227,57 @@ pub async fn policy_status(
     }))
 }
 
+/// Evaluate access policies against a synthetic request.
+///
+/// POST /admin/$policy-evaluate
+///
+/// Runs the policy evaluator in audit mode and returns the full evaluation
+/// trace: the scope check, every candidate policy and whether it matched,
+/// and what produced the final decision. Nothing is executed against the
+/// resource.
+///
+/// # Authorization
+///
+/// Requires admin authentication. Regular API responses never include the
+/// trace, since it reveals policy internals.
+pub async fn evaluate_policy(
+    admin: AdminAuth,
+    State(state): State<AppState>,
+    Json(request): Json<PolicyEvaluateRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let client = request.client.unwrap_or_else(|| ClientIdentity {
+        id: "policy-evaluate".to_string(),
+        name: "Policy evaluation".to_string(),
+        trusted: false,
+        client_type: ClientType::Public,
+    });
+
+    let mut builder = PolicyContextBuilder::new()
+        .with_identity(request.user, client, &request.scope)
+        .with_request(&request.method, &request.path, request.query, request.body)
+        .with_environment(uuid::Uuid::new_v4().to_string(), None)
+        .with_launch_context(request.patient, request.encounter);
+    if let Some(operation_id) = request.operation_id {
+        builder = builder.with_operation_id(operation_id);
+    }
+    if let Some(resource) = request.resource {
+        builder = builder.with_resource(resource);
+    }
+    let context = builder
+        .build()
+        .map_err(|e| ApiError::bad_request(e.to_string()))?;
+
+    tracing::info!(
+        admin_user = %admin.username,
+        method = %context.request.method,
+        path = %context.request.path,
+        "Evaluating policies for synthetic request"
+    );
+
+    let result = state.policy_evaluator.evaluate_with_audit(&context).await;
+    Ok(Json(result))
+}
+
 // =============================================================================
 // Tests
 // =============================================================================
@@ -204,4 +304,18 @@ mod tests {
         assert_eq!(response.notifications_received, 15);
         assert_eq!(response.notifications_debounced, 5);
     }
+
+    #[test]
+    fn test_policy_evaluate_request_defaults() {
+        let request: PolicyEvaluateRequest = serde_json::from_value(serde_json::json!({
+            "path": "/Patient/123",
+            "scope": "user/Patient.rs"
+        }))
+        .unwrap();
+
+        assert_eq!(request.method, "GET");
+        assert!(request.query.is_empty());
+        assert!(request.user.is_none());
+        assert!(request.client.is_none());
+    }
 }
@@ -0,0 +1,62 @@
+//! Admin rate limit quota endpoints.
+//!
+//! Provides `/admin/rate-limits/{client}` to inspect a client's quota in the
+//! current window and to reset it, e.g. after raising a misbehaving
+//! integration's limit. `client` is the OAuth `client_id`, or `ip:<address>`
+//! for requests counted per peer address.
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use octofhir_api::ApiError;
+use octofhir_auth::middleware::AdminAuth;
+use serde_json::json;
+
+use crate::rate_limit::RateLimiter;
+use crate::server::AppState;
+
+/// Get a client's quota in the current window.
+///
+/// # Authorization
+///
+/// Requires admin authentication.
+pub async fn get_rate_limit(
+    _admin: AdminAuth,
+    State(state): State<AppState>,
+    Path(client): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    let quota = limiter(&state)?.quota(&client).await;
+    Ok(Json(json!({
+        "client": client,
+        "quota": quota,
+    })))
+}
+
+/// Reset a client's quota for the current window.
+///
+/// # Authorization
+///
+/// Requires admin authentication.
+pub async fn reset_rate_limit(
+    admin: AdminAuth,
+    State(state): State<AppState>,
+    Path(client): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    limiter(&state)?.reset(&client).await;
+    tracing::info!(
+        admin_user = %admin.username,
+        client = %client,
+        "Rate limit quota reset"
+    );
+    Ok(StatusCode::NO_CONTENT)
+}
+
+fn limiter(state: &AppState) -> Result<&RateLimiter, ApiError> {
+    state
+        .rate_limiter
+        .as_deref()
+        .ok_or_else(|| ApiError::not_found("Rate limiting is disabled"))
+}
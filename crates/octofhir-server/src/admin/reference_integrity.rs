@@ -0,0 +1,628 @@
+//! Admin reference integrity check.
+//!
+//! Provides `/admin/maintenance/$reference-integrity` for auditing data
+//! quality after migrations. Live resources are scanned page by page through
+//! the reference-typed search parameters of their type; every distinct target
+//! is checked for existence once, and dangling targets are reported together
+//! with the resources that point at them.
+//!
+//! A full scan reads every resource, so it runs as an async job:
+//!
+//! - `POST /maintenance/$reference-integrity` starts a scan and answers
+//!   `202 Accepted` with `Content-Location` pointing at `_async-status`
+//! - `GET /maintenance/$reference-integrity?job=<id>` returns the report of a
+//!   finished scan, as JSON or with `_format=ndjson` as a download
+//!
+//! Reports are bounded: at most `_count` dangling targets are listed, each
+//! with at most [`MAX_SOURCES_PER_TARGET`] sources.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use axum::{
+    Json,
+    extract::{Query, State},
+    http::{HeaderValue, StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use octofhir_api::ApiError;
+use octofhir_auth::middleware::AdminAuth;
+use octofhir_core::ResourceType;
+use octofhir_core::fhir_reference::parse_reference;
+use octofhir_db_postgres::queries::{
+    CompartmentScope, exists_many_grouped, reference_param_codes, scan_references,
+};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+use crate::async_jobs::{AsyncJobError, AsyncJobRequest, AsyncJobStatus};
+use crate::server::AppState;
+
+/// Async job type of a reference integrity scan.
+pub const JOB_TYPE: &str = "reference_integrity";
+
+/// Resources fetched per scan page.
+const SCAN_PAGE_SIZE: usize = 500;
+
+/// Default and maximum number of dangling targets listed in a report.
+const DEFAULT_REPORT_LIMIT: usize = 1000;
+const MAX_REPORT_LIMIT: usize = 10_000;
+
+/// Source resources listed per dangling target.
+const MAX_SOURCES_PER_TARGET: usize = 50;
+
+/// Unlisted dangling targets remembered for an exact count; past this the
+/// report gives a lower bound.
+const MAX_OMITTED_TARGETS: usize = 10_000;
+
+/// Existence results cached across pages before the cache is reset.
+const MAX_CACHED_TARGETS: usize = 200_000;
+
+/// Query parameters for the reference integrity check
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ReferenceIntegrityQuery {
+    /// Comma-separated resource types to scan (default: every type with
+    /// reference search parameters)
+    #[serde(rename = "resourceType")]
+    pub resource_type: Option<String>,
+    /// Restrict the scan to one compartment, e.g. `Patient/123`
+    pub compartment: Option<String>,
+    /// `json` (default) or `ndjson` for a downloadable report
+    #[serde(rename = "_format")]
+    pub format: Option<String>,
+    /// Maximum number of dangling targets to list
+    #[serde(rename = "_count")]
+    pub count: Option<usize>,
+    /// Job whose report to return
+    pub job: Option<Uuid>,
+}
+
+/// Start a scan for references to resources that do not exist.
+///
+/// # Authorization
+///
+/// Requires admin authentication.
+pub async fn check_reference_integrity(
+    admin: AdminAuth,
+    State(state): State<AppState>,
+    Query(query): Query<ReferenceIntegrityQuery>,
+) -> Result<Response, ApiError> {
+    let limit = query
+        .count
+        .unwrap_or(DEFAULT_REPORT_LIMIT)
+        .clamp(1, MAX_REPORT_LIMIT);
+
+    let compartment = query
+        .compartment
+        .as_deref()
+        .map(parse_compartment)
+        .transpose()?;
+    if let Some((compartment_type, _)) = &compartment {
+        state
+            .compartment_registry
+            .get(compartment_type)
+            .map_err(|e| ApiError::bad_request(e.to_string()))?;
+    }
+
+    let resource_types = match &query.resource_type {
+        Some(types) => {
+            let types: Vec<String> = types
+                .split(',')
+                .map(str::trim)
+                .filter(|t| !t.is_empty())
+                .map(str::to_string)
+                .collect();
+            for rt in &types {
+                if rt.parse::<ResourceType>().is_err() {
+                    return Err(ApiError::bad_request(format!(
+                        "Unknown resourceType '{rt}'"
+                    )));
+                }
+            }
+            Some(types)
+        }
+        None => None,
+    };
+
+    let fhir_base = format!("{}/fhir", state.base_url.trim_end_matches('/'));
+    let request = AsyncJobRequest {
+        request_type: JOB_TYPE.to_string(),
+        method: "POST".to_string(),
+        url: format!(
+            "{}/admin/maintenance/$reference-integrity",
+            state.base_url.trim_end_matches('/')
+        ),
+        body: Some(json!({
+            "types": resource_types,
+            "compartment": query.compartment,
+            "limit": limit,
+        })),
+        headers: None,
+        client_id: None,
+        secret: None,
+    };
+    let job_id = state
+        .async_job_manager
+        .submit_job(request)
+        .await
+        .map_err(|e| {
+            ApiError::internal(format!("Failed to submit reference integrity job: {e}"))
+        })?;
+
+    tracing::info!(
+        admin_user = %admin.username,
+        job_id = %job_id,
+        compartment = ?query.compartment,
+        "Reference integrity check submitted"
+    );
+
+    Ok(crate::handlers::create_async_accepted_response(job_id, &fhir_base).into_response())
+}
+
+/// Return the report of a reference integrity scan.
+///
+/// Answers `202 Accepted` with the job status while the scan is running.
+///
+/// # Authorization
+///
+/// Requires admin authentication.
+pub async fn reference_integrity_report(
+    _admin: AdminAuth,
+    State(state): State<AppState>,
+    Query(query): Query<ReferenceIntegrityQuery>,
+) -> Result<Response, ApiError> {
+    let job_id = query
+        .job
+        .ok_or_else(|| ApiError::bad_request("Missing 'job' parameter"))?;
+    let not_found = || ApiError::not_found(format!("Reference integrity job {job_id} not found"));
+    let job = state
+        .async_job_manager
+        .get_job(job_id)
+        .await
+        .map_err(|e| match e {
+            AsyncJobError::NotFound(_) => not_found(),
+            e => ApiError::internal(format!("Failed to get job: {e}")),
+        })?;
+    if job.request_type != JOB_TYPE {
+        return Err(not_found());
+    }
+
+    match job.status {
+        AsyncJobStatus::Completed => {}
+        AsyncJobStatus::Failed => {
+            return Err(ApiError::internal(
+                job.error_message
+                    .unwrap_or_else(|| "Reference integrity check failed".to_string()),
+            ));
+        }
+        status => {
+            let body = json!({
+                "job": job_id,
+                "status": status,
+                "progress": job.progress,
+                "partial": job.result,
+            });
+            return Ok((StatusCode::ACCEPTED, Json(body)).into_response());
+        }
+    }
+
+    let report = job.result.unwrap_or_else(|| json!({}));
+    match query.format.as_deref() {
+        Some("ndjson") => Ok(ndjson_response(&report)),
+        None | Some("json") => Ok(Json(report).into_response()),
+        Some(other) => Err(ApiError::bad_request(format!(
+            "Unsupported _format '{other}'; use json or ndjson"
+        ))),
+    }
+}
+
+/// Executes a scan submitted by [`check_reference_integrity`].
+///
+/// Progress is published after every resource type; a job cancelled through
+/// `_async-status` stops before its next page and reports what it found.
+pub async fn execute_reference_integrity(
+    state: AppState,
+    job_id: Uuid,
+    params: Value,
+) -> Result<Value, String> {
+    let registry = state.search_config.config().registry.clone();
+    let limit = params
+        .get("limit")
+        .and_then(Value::as_u64)
+        .map_or(DEFAULT_REPORT_LIMIT, |l| l as usize)
+        .clamp(1, MAX_REPORT_LIMIT);
+    let compartment_param = params
+        .get("compartment")
+        .and_then(Value::as_str)
+        .map(str::to_string);
+    let compartment = compartment_param
+        .as_deref()
+        .map(parse_compartment)
+        .transpose()
+        .map_err(|e| e.to_string())?;
+    let compartment_def = match &compartment {
+        Some((compartment_type, _)) => Some(
+            state
+                .compartment_registry
+                .get(compartment_type)
+                .map_err(|e| e.to_string())?,
+        ),
+        None => None,
+    };
+
+    let requested: Option<Vec<String>> = params.get("types").and_then(Value::as_array).map(|a| {
+        a.iter()
+            .filter_map(|v| v.as_str().map(str::to_string))
+            .collect()
+    });
+    let mut sorted_types = match requested {
+        Some(types) => types,
+        None => match compartment_def {
+            Some(def) => def
+                .resource_types()
+                .into_iter()
+                .map(str::to_string)
+                .collect(),
+            None => registry.list_resource_types(),
+        },
+    };
+    sorted_types.sort();
+    sorted_types.dedup();
+
+    tracing::info!(
+        job_id = %job_id,
+        resource_types = sorted_types.len(),
+        compartment = ?compartment_param,
+        "Starting reference integrity check"
+    );
+
+    let fhir_base = format!("{}/fhir", state.base_url.trim_end_matches('/'));
+    let mut report = IntegrityReport::new(limit);
+    let mut exists_cache: HashMap<String, bool> = HashMap::new();
+    let mut cancelled = false;
+
+    'types: for (type_index, resource_type) in sorted_types.iter().enumerate() {
+        let params = reference_param_codes(&registry, resource_type);
+        if params.is_empty() {
+            continue;
+        }
+
+        let inclusion_params: Vec<String>;
+        let scope = match (&compartment, compartment_def) {
+            (Some((compartment_type, compartment_id)), Some(def)) => {
+                if resource_type != compartment_type && !def.contains_resource_type(resource_type) {
+                    continue;
+                }
+                inclusion_params = def
+                    .get_inclusion_params(resource_type)
+                    .map(<[String]>::to_vec)
+                    .unwrap_or_default();
+                Some(CompartmentScope {
+                    compartment_type,
+                    compartment_id,
+                    params: &inclusion_params,
+                })
+            }
+            _ => None,
+        };
+
+        let mut cursor: Option<String> = None;
+        loop {
+            if state
+                .async_job_manager
+                .is_cancelled(job_id)
+                .await
+                .unwrap_or(false)
+            {
+                cancelled = true;
+                break 'types;
+            }
+
+            let page = scan_references(
+                &state.read_db_pool,
+                &registry,
+                resource_type,
+                &params,
+                cursor.as_deref(),
+                SCAN_PAGE_SIZE,
+                scope.as_ref(),
+            )
+            .await
+            .map_err(|e| e.to_string())?;
+            report.scanned_resources += page.source_ids.len();
+
+            // Bound the cache between pages, never while a page's targets
+            // are being resolved and reported.
+            if exists_cache.len() > MAX_CACHED_TARGETS {
+                exists_cache.clear();
+            }
+
+            // Resolve each distinct local target once per page.
+            let mut page_refs: Vec<(String, String, String)> = Vec::new();
+            let mut unchecked: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+            for r in &page.references {
+                let Ok(target) = parse_reference(&r.reference, Some(&fhir_base)) else {
+                    // Contained, URN and external references are out of scope.
+                    continue;
+                };
+                let key = target.to_relative();
+                if !target
+                    .resource_type
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric())
+                {
+                    // Not a type name that can have a table.
+                    exists_cache.insert(key.clone(), false);
+                } else if !exists_cache.contains_key(&key) {
+                    unchecked
+                        .entry(target.resource_type.clone())
+                        .or_default()
+                        .insert(target.id.clone());
+                }
+                page_refs.push((
+                    format!("{resource_type}/{}", r.source_id),
+                    r.param.clone(),
+                    key,
+                ));
+            }
+
+            if !unchecked.is_empty() {
+                let groups: Vec<(String, Vec<String>)> = unchecked
+                    .into_iter()
+                    .map(|(rt, ids)| (rt, ids.into_iter().collect()))
+                    .collect();
+                let found = exists_many_grouped(&state.read_db_pool, &groups)
+                    .await
+                    .map_err(|e| e.to_string())?;
+                for (rt, ids) in &groups {
+                    for id in ids {
+                        let key = format!("{rt}/{id}");
+                        let exists = found.contains(&key);
+                        exists_cache.insert(key, exists);
+                    }
+                }
+                report.checked_targets += groups.iter().map(|(_, ids)| ids.len()).sum::<usize>();
+            }
+
+            for (source, param, target) in page_refs {
+                report.checked_references += 1;
+                if exists_cache.get(&target) == Some(&false) {
+                    report.record_dangling(&target, &source, &param);
+                }
+            }
+
+            match page.next_cursor(SCAN_PAGE_SIZE) {
+                Some(next) => cursor = Some(next.to_string()),
+                None => break,
+            }
+        }
+
+        let progress = (type_index + 1) as f32 / sorted_types.len() as f32;
+        let snapshot = json!({
+            "scannedResources": report.scanned_resources,
+            "danglingTargets": report.dangling_target_count(),
+        });
+        if let Err(e) = state
+            .async_job_manager
+            .update_progress_with_result(job_id, progress, &snapshot)
+            .await
+        {
+            tracing::warn!(job_id = %job_id, error = %e, "Failed to update reference integrity progress");
+        }
+    }
+
+    tracing::info!(
+        job_id = %job_id,
+        scanned = report.scanned_resources,
+        dangling = report.dangling.len(),
+        cancelled,
+        "Reference integrity check finished"
+    );
+
+    let scope = json!({
+        "resourceTypes": sorted_types,
+        "compartment": compartment_param,
+    });
+    let mut result = report.to_json(scope);
+    result["cancelled"] = json!(cancelled);
+    Ok(result)
+}
+
+/// Parse `Type/id` for the `compartment` parameter.
+fn parse_compartment(value: &str) -> Result<(String, String), ApiError> {
+    match value.split_once('/') {
+        Some((compartment_type, id))
+            if !id.is_empty()
+                && !id.contains('/')
+                && compartment_type.parse::<ResourceType>().is_ok() =>
+        {
+            Ok((compartment_type.to_string(), id.to_string()))
+        }
+        _ => Err(ApiError::bad_request(format!(
+            "compartment must be 'Type/id', got '{value}'"
+        ))),
+    }
+}
+
+fn ndjson_response(report: &Value) -> Response {
+    let mut body = String::new();
+    for line in report["dangling"].as_array().into_iter().flatten() {
+        body.push_str(&line.to_string());
+        body.push('\n');
+    }
+    let mut response = body.into_response();
+    let headers = response.headers_mut();
+    headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("application/x-ndjson"),
+    );
+    headers.insert(
+        header::CONTENT_DISPOSITION,
+        HeaderValue::from_static("attachment; filename=\"reference-integrity.ndjson\""),
+    );
+    response
+}
+
+/// Deduplicated dangling reference findings.
+///
+/// Each missing target appears once, however many resources or search
+/// parameters point at it. A source resource reached through several
+/// parameters covering the same element (e.g. `subject` and `patient`) is
+/// listed once with all parameter codes.
+///
+/// Memory is bounded by `limit` listed targets of at most
+/// [`MAX_SOURCES_PER_TARGET`] sources each, plus [`MAX_OMITTED_TARGETS`]
+/// remembered unlisted targets.
+#[derive(Debug, Default)]
+struct IntegrityReport {
+    limit: usize,
+    scanned_resources: usize,
+    checked_references: usize,
+    checked_targets: usize,
+    /// target → listed sources
+    dangling: BTreeMap<String, DanglingTarget>,
+    /// Dangling targets not listed because `limit` was reached
+    omitted_targets: BTreeSet<String>,
+    /// Whether more unlisted targets were seen than `omitted_targets` holds
+    omitted_overflow: bool,
+}
+
+/// Sources of one dangling target.
+#[derive(Debug, Default)]
+struct DanglingTarget {
+    /// source → params
+    sources: BTreeMap<String, BTreeSet<String>>,
+    /// References from sources past [`MAX_SOURCES_PER_TARGET`]
+    unlisted_references: usize,
+}
+
+impl IntegrityReport {
+    fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            ..Default::default()
+        }
+    }
+
+    fn record_dangling(&mut self, target: &str, source: &str, param: &str) {
+        if !self.dangling.contains_key(target) && self.dangling.len() >= self.limit {
+            if self.omitted_targets.len() < MAX_OMITTED_TARGETS {
+                self.omitted_targets.insert(target.to_string());
+            } else if !self.omitted_targets.contains(target) {
+                self.omitted_overflow = true;
+            }
+            return;
+        }
+        let entry = self.dangling.entry(target.to_string()).or_default();
+        if let Some(params) = entry.sources.get_mut(source) {
+            params.insert(param.to_string());
+        } else if entry.sources.len() < MAX_SOURCES_PER_TARGET {
+            entry
+                .sources
+                .insert(source.to_string(), BTreeSet::from([param.to_string()]));
+        } else {
+            entry.unlisted_references += 1;
+        }
+    }
+
+    fn dangling_target_count(&self) -> usize {
+        self.dangling.len() + self.omitted_targets.len()
+    }
+
+    fn dangling_entries(&self) -> impl Iterator<Item = Value> + '_ {
+        self.dangling.iter().map(|(target, entry)| {
+            json!({
+                "target": target,
+                "referencingResources": entry.sources.len(),
+                "unlistedReferences": entry.unlisted_references,
+                "sources": entry
+                    .sources
+                    .iter()
+                    .map(|(source, params)| json!({ "resource": source, "params": params }))
+                    .collect::<Vec<_>>(),
+            })
+        })
+    }
+
+    fn to_json(&self, scope: Value) -> Value {
+        json!({
+            "scope": scope,
+            "scannedResources": self.scanned_resources,
+            "checkedReferences": self.checked_references,
+            "checkedTargets": self.checked_targets,
+            "danglingTargets": self.dangling_target_count(),
+            "danglingTargetsExact": !self.omitted_overflow,
+            "truncated": !self.omitted_targets.is_empty(),
+            "dangling": self.dangling_entries().collect::<Vec<_>>(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_compartment() {
+        assert_eq!(
+            parse_compartment("Patient/123").unwrap(),
+            ("Patient".to_string(), "123".to_string())
+        );
+        assert!(parse_compartment("Patient").is_err());
+        assert!(parse_compartment("Patient/").is_err());
+        assert!(parse_compartment("not-a-type/1").is_err());
+    }
+
+    #[test]
+    fn test_report_deduplicates_targets_and_params() {
+        let mut report = IntegrityReport::new(10);
+        report.record_dangling("Patient/missing", "Observation/o1", "subject");
+        report.record_dangling("Patient/missing", "Observation/o1", "patient");
+        report.record_dangling("Patient/missing", "Observation/o2", "subject");
+
+        let json = report.to_json(Value::Null);
+        assert_eq!(json["danglingTargets"], 1);
+        assert_eq!(json["dangling"][0]["referencingResources"], 2);
+        assert_eq!(
+            json["dangling"][0]["sources"][0],
+            json!({ "resource": "Observation/o1", "params": ["patient", "subject"] })
+        );
+    }
+
+    #[test]
+    fn test_report_limit_counts_omitted_targets() {
+        let mut report = IntegrityReport::new(1);
+        report.record_dangling("Patient/a", "Observation/o1", "subject");
+        report.record_dangling("Patient/b", "Observation/o2", "subject");
+        report.record_dangling("Patient/b", "Observation/o3", "subject");
+
+        let json = report.to_json(Value::Null);
+        assert_eq!(json["danglingTargets"], 2);
+        assert_eq!(json["truncated"], true);
+        assert_eq!(json["dangling"].as_array().unwrap().len(), 1);
+        assert_eq!(json["danglingTargetsExact"], true);
+    }
+
+    #[test]
+    fn test_report_bounds_sources_and_omitted_targets() {
+        let mut report = IntegrityReport::new(1);
+        for i in 0..MAX_SOURCES_PER_TARGET + 5 {
+            report.record_dangling("Patient/a", &format!("Observation/o{i}"), "subject");
+        }
+        for i in 0..MAX_OMITTED_TARGETS + 5 {
+            report.record_dangling(&format!("Patient/m{i}"), "Observation/o1", "subject");
+        }
+        assert_eq!(report.omitted_targets.len(), MAX_OMITTED_TARGETS);
+
+        let json = report.to_json(Value::Null);
+        let listed = &json["dangling"][0];
+        assert_eq!(listed["referencingResources"], MAX_SOURCES_PER_TARGET);
+        assert_eq!(listed["unlistedReferences"], 5);
+        assert_eq!(
+            listed["sources"].as_array().unwrap().len(),
+            MAX_SOURCES_PER_TARGET
+        );
+        assert_eq!(json["danglingTargets"], 1 + MAX_OMITTED_TARGETS);
+        assert_eq!(json["danglingTargetsExact"], false);
+    }
+}
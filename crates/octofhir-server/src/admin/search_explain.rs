@@ -0,0 +1,144 @@
+//! Admin search query plan endpoint.
+//!
+//! Provides `/admin/search/{resourceType}/$explain` for diagnosing slow
+//! searches without direct database access. The search is built through the
+//! same SQL builder as regular searches and run under
+//! `EXPLAIN (ANALYZE, BUFFERS, FORMAT JSON)`; the plan is returned together
+//! with the generated SQL, its bind parameters, and a summary of sequential
+//! scans and index suggestions.
+
+use axum::{
+    Json,
+    extract::{Path, RawQuery, State},
+    response::IntoResponse,
+};
+use octofhir_api::ApiError;
+use octofhir_auth::middleware::AdminAuth;
+use octofhir_core::ResourceType;
+use octofhir_db_postgres::{AnalyzerConfig, QueryAnalyzer, SchemaManager};
+use serde_json::json;
+
+use crate::server::AppState;
+use crate::storage_adapter::map_storage_error;
+
+/// Explain a search for a resource type.
+///
+/// The query string is interpreted exactly like a type-level search. Pass
+/// `_analyze=false` to get the planner estimate without executing the query.
+///
+/// # Authorization
+///
+/// Requires admin authentication. The response contains unredacted SQL and
+/// bind values.
+pub async fn explain_search(
+    admin: AdminAuth,
+    State(state): State<AppState>,
+    Path(resource_type): Path<String>,
+    RawQuery(raw): RawQuery,
+) -> Result<impl IntoResponse, ApiError> {
+    if resource_type.parse::<ResourceType>().is_err() {
+        return Err(ApiError::bad_request(format!(
+            "Unknown resourceType '{resource_type}'"
+        )));
+    }
+
+    let raw_q = raw.unwrap_or_default();
+    let (analyze, raw_q) = split_analyze_param(&raw_q);
+    let cfg = state.search_config.config();
+    let mut search_params =
+        octofhir_search::parse_query_string(&raw_q, cfg.default_count as u32, cfg.max_count as u32);
+    cfg.default_sort.apply(&resource_type, &mut search_params);
+
+    tracing::info!(
+        admin_user = %admin.username,
+        resource_type = %resource_type,
+        analyze,
+        "Explaining search query"
+    );
+
+    let explain = octofhir_db_postgres::queries::explain_search(
+        &state.read_db_pool,
+        &SchemaManager::resource_schema(),
+        &resource_type,
+        &search_params,
+        Some(&cfg.registry),
+        state.terminology_provider.as_ref(),
+        octofhir_db_postgres::queries::RawSearchOptions {
+            unknown_param_handling: Some(octofhir_search::UnknownParamHandling::Strict),
+            collect_explain_plan: true,
+            collect_explain_analyze: analyze,
+            max_valueset_expansion: Some(state.config.search.max_valueset_expansion),
+            max_chain_depth: Some(state.config.search.max_chain_depth),
+            ..Default::default()
+        },
+    )
+    .await
+    .map_err(map_storage_error)?;
+
+    let analyzer = QueryAnalyzer::new(AnalyzerConfig::default().with_auto_log(false));
+    let summary = analyzer
+        .summarize_explain(&explain.sql, &explain.plan)
+        .map(|analysis| {
+            json!({
+                "executionTimeMs": analysis.execution_time_ms,
+                "planningTimeMs": analysis.planning_time_ms,
+                "isSlow": analysis.is_slow,
+                "indexesUsed": analysis.indexes_used,
+                "sequentialScans": analysis.sequential_scans,
+                "indexSuggestions": analysis.index_suggestions,
+                "rowsScanned": analysis.rows_scanned,
+                "rowsReturned": analysis.rows_returned,
+                "bufferStats": analysis.buffer_stats,
+            })
+        })
+        .unwrap_or_else(|e| {
+            tracing::warn!(error = %e, "Failed to summarize search EXPLAIN output");
+            serde_json::Value::Null
+        });
+
+    Ok(Json(json!({
+        "resourceType": resource_type,
+        "query": raw_q,
+        "analyze": explain.analyze,
+        "sql": explain.sql,
+        "params": explain.params,
+        "plan": explain.plan,
+        "summary": summary,
+    })))
+}
+
+/// Remove `_analyze` from the query string, returning its value (default true).
+fn split_analyze_param(raw_q: &str) -> (bool, String) {
+    let mut analyze = true;
+    let rest = raw_q
+        .split('&')
+        .filter(|part| match part.split_once('=') {
+            Some(("_analyze", value)) => {
+                analyze = value != "false";
+                false
+            }
+            _ => *part != "_analyze" && !part.is_empty(),
+        })
+        .collect::<Vec<_>>()
+        .join("&");
+    (analyze, rest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_analyze_param() {
+        assert_eq!(
+            split_analyze_param("name=smith&_count=5"),
+            (true, "name=smith&_count=5".to_string())
+        );
+        assert_eq!(
+            split_analyze_param("name=smith&_analyze=false"),
+            (false, "name=smith".to_string())
+        );
+        assert_eq!(split_analyze_param("_analyze=true"), (true, String::new()));
+        assert_eq!(split_analyze_param(""), (true, String::new()));
+    }
+}
@@ -16,15 +16,34 @@
 //! - Bulk exports (Group $everything)
 //! - Complex searches with massive result sets
 //! - Custom operations taking > 5 seconds
+//!
+//! ## Concurrency
+//! At most `max_concurrent_jobs` jobs run at once. [`JobLimits`] cap a kind
+//! of job further, overall and per OAuth client, so a few large exports
+//! cannot take every database connection. Jobs over a limit stay `queued`
+//! until a slot frees up. The client is the one of the submitting request,
+//! bound by [`with_submitting_client`].
+//!
+//! ## Resuming
+//! A running job holds a session advisory lock on a connection of its own.
+//! When its process dies the lock goes with it, and
+//! [`AsyncJobManager::resume_interrupted_jobs`] on the next instance to start
+//! runs the job again from the start or from whatever checkpoint the
+//! executor left in its result. Only kinds of job that are safe to run twice
+//! are resumed.
 
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
 use chrono::{DateTime, Utc};
+use octofhir_storage::tenant::{TenantId, current_tenant, with_tenant};
 use serde::{Deserialize, Serialize};
 use sqlx_core::query::query;
+use sqlx_core::query_scalar::query_scalar;
 use sqlx_core::row::Row;
-use sqlx_postgres::PgPool;
+use sqlx_postgres::{PgConnection, PgPool};
 use thiserror::Error;
 use uuid::Uuid;
 
@@ -88,6 +107,8 @@ pub struct AsyncJob {
     pub completed_at: Option<DateTime<Utc>>,
     pub client_id: Option<String>,
     pub expires_at: DateTime<Utc>,
+    /// Tenant the job runs against, if any
+    pub tenant_id: Option<String>,
 }
 
 /// Request to create a new async job
@@ -99,6 +120,9 @@ pub struct AsyncJobRequest {
     pub body: Option<serde_json::Value>,
     pub headers: Option<serde_json::Value>,
     pub client_id: Option<String>,
+    /// Credential the job needs, stored apart from its request so it is
+    /// never reported, and deleted once the job finishes
+    pub secret: Option<String>,
 }
 
 /// Configuration for async job manager
@@ -108,6 +132,8 @@ pub struct AsyncJobConfig {
     pub max_concurrent_jobs: usize,
     pub default_ttl_hours: i64,
     pub cleanup_interval_seconds: u64,
+    /// Limits for kinds of job, by `request_type`
+    pub job_limits: HashMap<String, JobLimits>,
 }
 
 impl Default for AsyncJobConfig {
@@ -117,10 +143,34 @@ impl Default for AsyncJobConfig {
             max_concurrent_jobs: 10,
             default_ttl_hours: 24,
             cleanup_interval_seconds: 3600, // 1 hour
+            job_limits: HashMap::new(),
         }
     }
 }
 
+/// Concurrency limits for one kind of job, on top of `max_concurrent_jobs`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct JobLimits {
+    /// Jobs of this kind running at once (0 = no limit of its own)
+    pub max_running: usize,
+    /// Jobs of this kind running at once for one client (0 = no limit)
+    pub max_running_per_client: usize,
+}
+
+tokio::task_local! {
+    static SUBMITTING_CLIENT: String;
+}
+
+/// Runs `fut` with `client_id` as the client submitting the jobs it starts.
+pub async fn with_submitting_client<F: Future>(client_id: String, fut: F) -> F::Output {
+    SUBMITTING_CLIENT.scope(client_id, fut).await
+}
+
+/// Client of the current request, if it is bound.
+fn submitting_client() -> Option<String> {
+    SUBMITTING_CLIENT.try_with(Clone::clone).ok()
+}
+
 /// Job executor function type
 /// Takes job ID and request details and returns result or error
 pub type JobExecutor = Arc<
@@ -145,6 +195,12 @@ pub struct AsyncJobManager {
     /// Caps concurrently executing jobs at `config.max_concurrent_jobs` so a
     /// submit burst cannot spawn unbounded background tasks.
     job_semaphore: Arc<tokio::sync::Semaphore>,
+    /// Jobs spawned in this process that have not finished yet.
+    running: octofhir_core::InFlight,
+    /// Semaphores enforcing `config.job_limits`, per kind (`None` client) and
+    /// per kind and client, created on first use. Per-client entries are
+    /// dropped once no job of that client is waiting or running.
+    limit_slots: Arc<Mutex<HashMap<(String, Option<String>), Arc<tokio::sync::Semaphore>>>>,
 }
 
 impl AsyncJobManager {
@@ -156,6 +212,8 @@ impl AsyncJobManager {
             config: Arc::new(config),
             executor: Arc::new(std::sync::RwLock::new(None)),
             job_semaphore: Arc::new(tokio::sync::Semaphore::new(permits)),
+            running: octofhir_core::InFlight::new(),
+            limit_slots: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
@@ -172,21 +230,37 @@ impl AsyncJobManager {
     }
 
     /// Submit a new async job
-    pub async fn submit_job(&self, request: AsyncJobRequest) -> Result<Uuid, AsyncJobError> {
+    ///
+    /// A request without a `client_id` is attributed to the client bound by
+    /// [`with_submitting_client`].
+    pub async fn submit_job(&self, mut request: AsyncJobRequest) -> Result<Uuid, AsyncJobError> {
+        if request.client_id.is_none() {
+            request.client_id = submitting_client();
+        }
         let ttl_hours = format!("{} hours", self.config.default_ttl_hours);
+        // The secret is stored with the job, so it is there before the job
+        // starts, on any instance that resumes it
         let row = query(
             r#"
-            INSERT INTO async_jobs (
-                request_type,
-                request_method,
-                request_url,
-                request_body,
-                request_headers,
-                client_id,
-                expires_at
+            WITH job AS (
+                INSERT INTO async_jobs (
+                    request_type,
+                    request_method,
+                    request_url,
+                    request_body,
+                    request_headers,
+                    client_id,
+                    tenant_id,
+                    expires_at
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, NOW() + $8::INTERVAL)
+                RETURNING id
+            ),
+            secret AS (
+                INSERT INTO async_job_secret (job_id, secret)
+                SELECT id, $9 FROM job WHERE $9::TEXT IS NOT NULL
             )
-            VALUES ($1, $2, $3, $4, $5, $6, NOW() + $7::INTERVAL)
-            RETURNING id
+            SELECT id FROM job
             "#,
         )
         .bind(&request.request_type)
@@ -195,7 +269,9 @@ impl AsyncJobManager {
         .bind(&request.body)
         .bind(&request.headers)
         .bind(&request.client_id)
+        .bind(current_tenant().map(|t| t.as_str().to_string()))
         .bind(&ttl_hours)
+        .bind(&request.secret)
         .fetch_one(self.db_pool.as_ref())
         .await?;
 
@@ -207,28 +283,174 @@ impl AsyncJobManager {
             "Async job created"
         );
 
-        // Spawn background execution if executor is configured
-        if let Some(executor) = self.executor.read().unwrap().as_ref() {
-            let manager = self.clone();
-            let exec = executor.clone();
-            let req_type = request.request_type.clone();
-            let req_method = request.method.clone();
-            let req_url = request.url.clone();
-            let req_body = request.body.clone();
-            let semaphore = self.job_semaphore.clone();
-
-            tokio::spawn(async move {
-                // Bound concurrency: wait for a permit before executing.
-                let _permit = semaphore.acquire().await;
-                manager
-                    .execute_job(job_id, exec, req_type, req_method, req_url, req_body)
-                    .await;
-            });
-        }
+        // The job runs against the submitting request's tenant, if any.
+        self.spawn_job(job_id, &request, current_tenant());
 
         Ok(job_id)
     }
 
+    /// Spawn background execution of a job, if an executor is configured.
+    fn spawn_job(&self, job_id: Uuid, request: &AsyncJobRequest, tenant: Option<TenantId>) {
+        let Some(executor) = self.executor.read().unwrap().clone() else {
+            return;
+        };
+        let manager = self.clone();
+        let req_type = request.request_type.clone();
+        let req_method = request.method.clone();
+        let req_url = request.url.clone();
+        let req_body = request.body.clone();
+        let slots = self.job_slots(request);
+        let running = self.running.enter();
+
+        let job = async move {
+            let _running = running;
+            crate::metrics::increment_async_jobs_queued(&req_type);
+            // Bound concurrency: wait for a permit of every limit before
+            // executing. The semaphores are closed on shutdown; the job
+            // then stays queued.
+            let mut permits = Vec::with_capacity(slots.len());
+            for slot in slots {
+                match slot.acquire_owned().await {
+                    Ok(permit) => permits.push(permit),
+                    Err(_) => {
+                        crate::metrics::decrement_async_jobs_queued(&req_type);
+                        tracing::info!(job_id = %job_id, "Server shutting down, job left queued");
+                        drop(permits);
+                        manager.prune_limit_slots();
+                        return;
+                    }
+                }
+            }
+            crate::metrics::decrement_async_jobs_queued(&req_type);
+            crate::metrics::increment_async_jobs_running(&req_type);
+            manager
+                .execute_job(
+                    job_id,
+                    executor,
+                    req_type.clone(),
+                    req_method,
+                    req_url,
+                    req_body,
+                )
+                .await;
+            crate::metrics::decrement_async_jobs_running(&req_type);
+            drop(permits);
+            manager.prune_limit_slots();
+        };
+        match tenant {
+            Some(tenant) => tokio::spawn(with_tenant(tenant, job)),
+            None => tokio::spawn(job),
+        };
+    }
+
+    /// Resume jobs of the given kinds that are `in_progress` but not running
+    /// anywhere, i.e. whose instance stopped before they finished.
+    ///
+    /// Returns the number of jobs resumed. Jobs left `queued` by a shutdown
+    /// are resumed too.
+    pub async fn resume_interrupted_jobs(
+        &self,
+        request_types: &[&str],
+    ) -> Result<usize, AsyncJobError> {
+        let request_types: Vec<String> = request_types.iter().map(|t| t.to_string()).collect();
+        let rows = query(
+            r#"
+            SELECT id, request_type, request_method, request_url, request_body,
+                   client_id, tenant_id
+            FROM async_jobs
+            WHERE status IN ('queued', 'in_progress')
+              AND request_type = ANY($1)
+              AND expires_at > NOW()
+            ORDER BY created_at
+            "#,
+        )
+        .bind(&request_types)
+        .fetch_all(self.db_pool.as_ref())
+        .await?;
+
+        let mut resumed = 0;
+        for row in rows {
+            let job_id: Uuid = row.try_get("id")?;
+            let tenant = match row.try_get::<Option<String>, _>("tenant_id")? {
+                Some(id) => match TenantId::new(id) {
+                    Ok(tenant) => Some(tenant),
+                    Err(e) => {
+                        tracing::warn!(job_id = %job_id, error = %e, "Skipping job with invalid tenant");
+                        continue;
+                    }
+                },
+                None => None,
+            };
+            let request = AsyncJobRequest {
+                request_type: row.try_get("request_type")?,
+                method: row.try_get("request_method")?,
+                url: row.try_get("request_url")?,
+                body: row.try_get("request_body")?,
+                headers: None,
+                client_id: row.try_get("client_id")?,
+                // Read by the job from the database when it runs
+                secret: None,
+            };
+            // A job another instance is running keeps its lock; execute_job
+            // skips it
+            self.spawn_job(job_id, &request, tenant);
+            resumed += 1;
+        }
+        Ok(resumed)
+    }
+
+    /// Number of jobs spawned by this process that are still waiting or running.
+    pub fn active_jobs(&self) -> usize {
+        self.running.count()
+    }
+
+    /// Stop starting queued jobs. Jobs already executing keep running.
+    pub fn stop_accepting(&self) {
+        self.job_semaphore.close();
+        if let Ok(slots) = self.limit_slots.lock() {
+            slots.values().for_each(|slot| slot.close());
+        }
+    }
+
+    /// Semaphores a job must hold a permit of to run, narrowest first: its
+    /// client's limit, its kind's limit, then the global limit.
+    fn job_slots(&self, request: &AsyncJobRequest) -> Vec<Arc<tokio::sync::Semaphore>> {
+        let mut slots = Vec::with_capacity(3);
+        if let Some(limits) = self.config.job_limits.get(&request.request_type)
+            && let Ok(mut limit_slots) = self.limit_slots.lock()
+        {
+            let mut slot = |client: Option<&String>, permits: usize| {
+                limit_slots
+                    .entry((request.request_type.clone(), client.cloned()))
+                    .or_insert_with(|| Arc::new(tokio::sync::Semaphore::new(permits)))
+                    .clone()
+            };
+            // Jobs without a client share no per-client slot
+            if limits.max_running_per_client > 0
+                && let Some(client) = request.client_id.as_ref()
+            {
+                slots.push(slot(Some(client), limits.max_running_per_client));
+            }
+            if limits.max_running > 0 {
+                slots.push(slot(None, limits.max_running));
+            }
+        }
+        slots.push(self.job_semaphore.clone());
+        slots
+    }
+
+    /// Drop per-client limit semaphores no job holds any more.
+    ///
+    /// Waiting jobs hold a clone of each of their slots and running jobs hold
+    /// owned permits, which keep the semaphore alive too, so an entry only
+    /// the map references has all its permits free. Checked under the lock
+    /// `job_slots` clones under, so no job can pick up an entry being removed.
+    fn prune_limit_slots(&self) {
+        if let Ok(mut slots) = self.limit_slots.lock() {
+            slots.retain(|(_, client), slot| client.is_none() || Arc::strong_count(slot) > 1);
+        }
+    }
+
     /// Execute a job in the background
     async fn execute_job(
         &self,
@@ -239,10 +461,30 @@ impl AsyncJobManager {
         url: String,
         body: Option<serde_json::Value>,
     ) {
-        // Mark job as in progress
-        if let Err(e) = self.update_status(job_id, AsyncJobStatus::InProgress).await {
-            tracing::error!(job_id = %job_id, error = %e, "Failed to mark job as in progress");
-            return;
+        // Hold the job's lock while it runs, so no other instance resumes it
+        let _lease = match self.lock_job(job_id).await {
+            Ok(Some(lease)) => lease,
+            Ok(None) => {
+                tracing::debug!(job_id = %job_id, "Job is running elsewhere, skipping execution");
+                return;
+            }
+            Err(e) => {
+                tracing::error!(job_id = %job_id, error = %e, "Failed to lock job");
+                return;
+            }
+        };
+
+        // Mark job as in progress, unless it was cancelled while queued
+        match self.start_job(job_id).await {
+            Ok(true) => {}
+            Ok(false) => {
+                tracing::info!(job_id = %job_id, "Job no longer pending, skipping execution");
+                return;
+            }
+            Err(e) => {
+                tracing::error!(job_id = %job_id, error = %e, "Failed to mark job as in progress");
+                return;
+            }
         }
 
         tracing::info!(job_id = %job_id, "Starting job execution");
@@ -265,6 +507,22 @@ impl AsyncJobManager {
         }
     }
 
+    /// Take the session advisory lock of a job on a connection of its own.
+    ///
+    /// The lock lasts as long as the returned connection; dropping it closes
+    /// the connection and releases the lock. Returns `None` when another
+    /// session holds the lock.
+    async fn lock_job(&self, job_id: Uuid) -> Result<Option<PgConnection>, AsyncJobError> {
+        let mut conn = self.db_pool.acquire().await?.detach();
+        let locked: bool = query_scalar(
+            "SELECT pg_try_advisory_lock(hashtext('octofhir_async_job:' || $1::text))",
+        )
+        .bind(job_id)
+        .fetch_one(&mut conn)
+        .await?;
+        Ok(locked.then_some(conn))
+    }
+
     /// Get job details by ID
     pub async fn get_job(&self, job_id: Uuid) -> Result<AsyncJob, AsyncJobError> {
         let row = query(
@@ -284,7 +542,8 @@ impl AsyncJobManager {
                 updated_at,
                 completed_at,
                 client_id,
-                expires_at
+                expires_at,
+                tenant_id
             FROM async_jobs
             WHERE id = $1
             "#,
@@ -320,9 +579,56 @@ impl AsyncJobManager {
             completed_at: row.try_get("completed_at")?,
             client_id: row.try_get("client_id")?,
             expires_at: row.try_get("expires_at")?,
+            tenant_id: row.try_get("tenant_id")?,
         })
     }
 
+    /// Move a queued job to `in_progress`. A job already `in_progress` is
+    /// being resumed and stays so.
+    ///
+    /// Returns `false` when the job is no longer pending, e.g. because it was
+    /// cancelled before a worker picked it up.
+    async fn start_job(&self, job_id: Uuid) -> Result<bool, AsyncJobError> {
+        let result = query(
+            r#"
+            UPDATE async_jobs
+            SET status = 'in_progress'
+            WHERE id = $1 AND status IN ('queued', 'in_progress')
+            "#,
+        )
+        .bind(job_id)
+        .execute(self.db_pool.as_ref())
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// The credential submitted with a job, while the job is pending or
+    /// running.
+    pub async fn job_secret(&self, job_id: Uuid) -> Result<Option<String>, AsyncJobError> {
+        Ok(
+            query_scalar("SELECT secret FROM async_job_secret WHERE job_id = $1")
+                .bind(job_id)
+                .fetch_optional(self.db_pool.as_ref())
+                .await?,
+        )
+    }
+
+    /// Whether cancellation of the job has been requested.
+    ///
+    /// Long-running executors poll this between units of work and stop at
+    /// the next consistent point.
+    pub async fn is_cancelled(&self, job_id: Uuid) -> Result<bool, AsyncJobError> {
+        let row = query("SELECT status FROM async_jobs WHERE id = $1")
+            .bind(job_id)
+            .fetch_optional(self.db_pool.as_ref())
+            .await?
+            .ok_or_else(|| AsyncJobError::NotFound(job_id.to_string()))?;
+
+        let status: String = row.try_get("status")?;
+        Ok(status == "cancelled")
+    }
+
     /// Update job status
     pub async fn update_status(
         &self,
@@ -367,7 +673,38 @@ impl AsyncJobManager {
         Ok(())
     }
 
+    /// Update job progress together with a partial result snapshot.
+    ///
+    /// Lets long-running jobs expose intermediate detail (e.g. per-input
+    /// counts for `$import`) through the status endpoint before they finish.
+    pub async fn update_progress_with_result(
+        &self,
+        job_id: Uuid,
+        progress: f32,
+        partial_result: &serde_json::Value,
+    ) -> Result<(), AsyncJobError> {
+        let clamped_progress = progress.clamp(0.0, 1.0);
+
+        query(
+            r#"
+            UPDATE async_jobs
+            SET progress = $1, result = $2
+            WHERE id = $3 AND status = 'in_progress'
+            "#,
+        )
+        .bind(clamped_progress)
+        .bind(partial_result)
+        .bind(job_id)
+        .execute(self.db_pool.as_ref())
+        .await?;
+
+        Ok(())
+    }
+
     /// Mark job as completed with result
+    ///
+    /// A cancelled job keeps its status; the result then records how far it
+    /// got before stopping. The job's secret is deleted.
     pub async fn complete_job(
         &self,
         job_id: Uuid,
@@ -375,11 +712,14 @@ impl AsyncJobManager {
     ) -> Result<(), AsyncJobError> {
         query(
             r#"
+            WITH secret AS (
+                DELETE FROM async_job_secret WHERE job_id = $2
+            )
             UPDATE async_jobs
             SET
-                status = 'completed',
+                status = CASE WHEN status = 'cancelled' THEN status ELSE 'completed' END,
                 result = $1,
-                progress = 1.0,
+                progress = CASE WHEN status = 'cancelled' THEN progress ELSE 1.0 END,
                 completed_at = NOW()
             WHERE id = $2
             "#,
@@ -395,12 +735,17 @@ impl AsyncJobManager {
     }
 
     /// Mark job as failed with error message
+    ///
+    /// A cancelled job keeps its status. The job's secret is deleted.
     pub async fn fail_job(&self, job_id: Uuid, error: String) -> Result<(), AsyncJobError> {
         query(
             r#"
+            WITH secret AS (
+                DELETE FROM async_job_secret WHERE job_id = $2
+            )
             UPDATE async_jobs
             SET
-                status = 'failed',
+                status = CASE WHEN status = 'cancelled' THEN status ELSE 'failed' END,
                 error_message = $1,
                 completed_at = NOW()
             WHERE id = $2
@@ -417,9 +762,15 @@ impl AsyncJobManager {
     }
 
     /// Cancel a job
+    ///
+    /// The job's secret is deleted; a job already running keeps the copy it
+    /// read until it stops.
     pub async fn cancel_job(&self, job_id: Uuid) -> Result<(), AsyncJobError> {
         query(
             r#"
+            WITH secret AS (
+                DELETE FROM async_job_secret WHERE job_id = $1
+            )
             UPDATE async_jobs
             SET status = 'cancelled'
             WHERE id = $1 AND status IN ('queued', 'in_progress')
@@ -500,7 +851,8 @@ impl AsyncJobManager {
                 updated_at,
                 completed_at,
                 client_id,
-                expires_at
+                expires_at,
+                tenant_id
             FROM async_jobs
             WHERE client_id = $1
             ORDER BY created_at DESC
@@ -541,6 +893,7 @@ impl AsyncJobManager {
                     completed_at: row.try_get("completed_at")?,
                     client_id: row.try_get("client_id")?,
                     expires_at: row.try_get("expires_at")?,
+                    tenant_id: row.try_get("tenant_id")?,
                 })
             })
             .collect::<Result<Vec<_>, sqlx_core::Error>>()?;
@@ -571,4 +924,111 @@ mod tests {
         assert_eq!(AsyncJobStatus::Failed.to_string(), "failed");
         assert_eq!(AsyncJobStatus::Cancelled.to_string(), "cancelled");
     }
+
+    #[tokio::test]
+    async fn test_job_slots_follow_limits() {
+        let pool = PgPool::connect_lazy("postgres://localhost/octofhir").unwrap();
+        let config = AsyncJobConfig {
+            max_concurrent_jobs: 4,
+            job_limits: HashMap::from([(
+                "bulk_export".to_string(),
+                JobLimits {
+                    max_running: 2,
+                    max_running_per_client: 1,
+                },
+            )]),
+            ..Default::default()
+        };
+        let manager = AsyncJobManager::new(Arc::new(pool), config);
+        let request = |request_type: &str, client_id: Option<&str>| AsyncJobRequest {
+            request_type: request_type.to_string(),
+            method: "GET".to_string(),
+            url: "/$export".to_string(),
+            body: None,
+            headers: None,
+            client_id: client_id.map(str::to_string),
+            secret: None,
+        };
+        let permits = |slots: &[Arc<tokio::sync::Semaphore>]| {
+            slots
+                .iter()
+                .map(|s| s.available_permits())
+                .collect::<Vec<_>>()
+        };
+
+        // Client, type and global slots
+        let first = manager.job_slots(&request("bulk_export", Some("app")));
+        assert_eq!(permits(&first), vec![1, 2, 4]);
+        let _held = first[0].clone().try_acquire_owned().unwrap();
+        // Same client shares its slot; another client gets its own
+        let again = manager.job_slots(&request("bulk_export", Some("app")));
+        assert_eq!(permits(&again), vec![0, 2, 4]);
+        let other = manager.job_slots(&request("bulk_export", Some("other")));
+        assert_eq!(permits(&other), vec![1, 2, 4]);
+        // No client, or a type without limits
+        assert_eq!(
+            permits(&manager.job_slots(&request("bulk_export", None))),
+            vec![2, 4]
+        );
+        assert_eq!(
+            permits(&manager.job_slots(&request("bulk_import", Some("app")))),
+            vec![4]
+        );
+
+        manager.stop_accepting();
+        assert!(again.iter().all(|slot| slot.is_closed()));
+    }
+
+    #[tokio::test]
+    async fn test_idle_client_slots_are_pruned() {
+        let pool = PgPool::connect_lazy("postgres://localhost/octofhir").unwrap();
+        let config = AsyncJobConfig {
+            job_limits: HashMap::from([(
+                "bulk_export".to_string(),
+                JobLimits {
+                    max_running: 2,
+                    max_running_per_client: 1,
+                },
+            )]),
+            ..Default::default()
+        };
+        let manager = AsyncJobManager::new(Arc::new(pool), config);
+        let request = |client_id: &str| AsyncJobRequest {
+            request_type: "bulk_export".to_string(),
+            method: "GET".to_string(),
+            url: "/$export".to_string(),
+            body: None,
+            headers: None,
+            client_id: Some(client_id.to_string()),
+            secret: None,
+        };
+        let clients = |manager: &AsyncJobManager| {
+            let mut clients: Vec<_> = manager
+                .limit_slots
+                .lock()
+                .unwrap()
+                .keys()
+                .filter_map(|(_, client)| client.clone())
+                .collect();
+            clients.sort();
+            clients
+        };
+
+        let waiting = manager.job_slots(&request("a"));
+        let running = manager.job_slots(&request("b"))[0]
+            .clone()
+            .try_acquire_owned()
+            .unwrap();
+        drop(manager.job_slots(&request("c")));
+
+        manager.prune_limit_slots();
+        assert_eq!(clients(&manager), vec!["a", "b"]);
+
+        drop(waiting);
+        drop(running);
+        manager.prune_limit_slots();
+        assert!(clients(&manager).is_empty());
+        // The per-kind slot stays
+        assert_eq!(manager.limit_slots.lock().unwrap().len(), 1);
+    }
 }
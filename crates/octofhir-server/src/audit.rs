@@ -16,8 +16,9 @@ use std::net::IpAddr;
 use time::OffsetDateTime;
 use uuid::Uuid;
 
-use crate::config::AuditConfig;
-use octofhir_storage::DynStorage;
+use crate::config::{AuditConfig, AuditDetail};
+use octofhir_core::events::{ResourceEvent, ResourceEventType};
+use octofhir_storage::{DynStorage, HistoryParams};
 
 /// Audit action types that map to AuditEvent.subtype
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -175,6 +176,9 @@ pub struct AuditEntity {
     pub resource_type: Option<String>,
     pub resource_id: Option<String>,
     pub query: Option<String>,
+    /// `entity.detail` entries as (type, value) pairs
+    #[serde(default)]
+    pub detail: Vec<(String, String)>,
 }
 
 /// Context for the audit event
@@ -282,10 +286,23 @@ impl AuditEventBuilder {
             resource_type,
             resource_id,
             query,
+            detail: Vec::new(),
         });
         self
     }
 
+    /// Add an `entity.detail` to the target entity (ignored without one)
+    pub fn entity_detail(
+        mut self,
+        detail_type: impl Into<String>,
+        value: impl Into<String>,
+    ) -> Self {
+        if let Some(entity) = &mut self.entity {
+            entity.detail.push((detail_type.into(), value.into()));
+        }
+        self
+    }
+
     /// Set the request ID
     pub fn request_id(mut self, id: impl Into<String>) -> Self {
         self.context.request_id = Some(id.into());
@@ -422,6 +439,14 @@ impl AuditEventBuilder {
                 e["description"] = json!(query);
             }
 
+            if !entity.detail.is_empty() {
+                e["detail"] = entity
+                    .detail
+                    .iter()
+                    .map(|(detail_type, value)| json!({"type": detail_type, "valueString": value}))
+                    .collect();
+            }
+
             vec![e]
         } else {
             vec![]
@@ -504,17 +529,79 @@ impl AuditEventBuilder {
     }
 }
 
+/// Removes the elements at `paths` from `resource`.
+///
+/// Paths are dotted element names from the resource root (`address.line`);
+/// repeating elements are followed into every item, a `[x]` suffix matches
+/// every choice type (`deceased[x]`), and primitive extensions (`_birthDate`)
+/// go with their element.
+pub fn redact_elements(resource: &mut Value, paths: &[String]) {
+    for path in paths {
+        let segments: Vec<&str> = path.split('.').collect();
+        redact_path(resource, &segments);
+    }
+}
+
+fn redact_path(value: &mut Value, segments: &[&str]) {
+    let Some((element, rest)) = segments.split_first() else {
+        return;
+    };
+    match value {
+        Value::Array(items) => {
+            for item in items {
+                redact_path(item, segments);
+            }
+        }
+        Value::Object(map) => {
+            let keys: Vec<String> = map
+                .keys()
+                .filter(|key| element_matches(key, element))
+                .cloned()
+                .collect();
+            for key in keys {
+                if rest.is_empty() {
+                    map.remove(&key);
+                } else if let Some(child) = map.get_mut(&key) {
+                    redact_path(child, rest);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn element_matches(key: &str, element: &str) -> bool {
+    let key = key.strip_prefix('_').unwrap_or(key);
+    match element.strip_suffix("[x]") {
+        Some(prefix) => key
+            .strip_prefix(prefix)
+            .is_some_and(|choice| choice.starts_with(|c: char| c.is_ascii_uppercase())),
+        None => key == element,
+    }
+}
+
 /// Audit service for creating and storing audit events
 #[derive(Clone)]
 pub struct AuditService {
     storage: DynStorage,
     config: AuditConfig,
+    /// Audit events currently being written
+    pending: octofhir_core::InFlight,
 }
 
 impl AuditService {
     /// Create a new audit service
     pub fn new(storage: DynStorage, _enabled: bool, config: AuditConfig) -> Self {
-        Self { storage, config }
+        Self {
+            storage,
+            config,
+            pending: octofhir_core::InFlight::new(),
+        }
+    }
+
+    /// Number of audit events still being written.
+    pub fn pending(&self) -> usize {
+        self.pending.count()
     }
 
     /// Check if audit logging is enabled
@@ -568,12 +655,75 @@ impl AuditService {
         }
     }
 
+    /// `entity.detail` recording a resource change at the `audit.detail`
+    /// level of its type, redacted per `audit.redact_elements`.
+    ///
+    /// Returns `None` at the `reference` level, for deletes, and when the
+    /// previous version an update diff needs cannot be read.
+    pub async fn change_detail(&self, event: &ResourceEvent) -> Option<(&'static str, String)> {
+        let resource = event.resource.as_deref()?;
+        let detail = self.config.detail_for(&event.resource_type);
+        if detail == AuditDetail::Reference {
+            return None;
+        }
+
+        let _pending = self.pending.enter();
+        let redact = self.config.redact_elements_for(&event.resource_type);
+        let mut current = resource.clone();
+        redact_elements(&mut current, redact);
+        if detail == AuditDetail::Full {
+            return Some(("resource", current.to_string()));
+        }
+
+        let mut previous = match event.event_type {
+            ResourceEventType::Created => json!({}),
+            _ => self.previous_version(event, resource).await?,
+        };
+        redact_elements(&mut previous, redact);
+        let patch = json_patch::diff(&previous, &current);
+        serde_json::to_string(&patch)
+            .ok()
+            .map(|patch| ("diff", patch))
+    }
+
+    /// The version before the one `event` wrote.
+    async fn previous_version(&self, event: &ResourceEvent, resource: &Value) -> Option<Value> {
+        let current = event
+            .version_id
+            .map(|v| v.to_string())
+            .or_else(|| resource["meta"]["versionId"].as_str().map(String::from))?;
+        // A few versions of slack in case the resource changed again since
+        let params = HistoryParams {
+            count: Some(5),
+            ..HistoryParams::default()
+        };
+        let history = match self
+            .storage
+            .history(&event.resource_type, Some(&event.resource_id), &params)
+            .await
+        {
+            Ok(history) => history,
+            Err(e) => {
+                tracing::warn!(
+                    error = %e,
+                    resource_type = %event.resource_type,
+                    "Failed to read previous version for audit diff"
+                );
+                return None;
+            }
+        };
+        let mut versions = history.entries.into_iter().map(|e| e.resource);
+        versions.find(|r| r.version_id == current)?;
+        versions.next().map(|r| r.resource)
+    }
+
     /// Log an audit event
     pub async fn log(&self, builder: AuditEventBuilder) -> Result<(), AuditError> {
         if !self.config.enabled {
             return Ok(());
         }
 
+        let _pending = self.pending.enter();
         let event = builder.build();
         let id = event["id"].as_str().unwrap_or_default().to_string();
 
@@ -768,6 +918,98 @@ pub fn actor_from_auth_context(auth_context: &octofhir_auth::middleware::AuthCon
     }
 }
 
+/// Build a FHIR Provenance resource attributing the writes of a transaction
+/// Bundle to the requesting agent.
+///
+/// `targets` holds one `(versioned reference, v3-DataOperation code)` pair per
+/// written resource, e.g. `("Patient/123/_history/1", "CREATE")`. `activity` is
+/// only set when every target shares the same operation. A user actor is
+/// recorded as author and the client (when known) as performer; without an
+/// actor the server itself is the agent.
+pub fn build_transaction_provenance(
+    targets: &[(String, &str)],
+    actor: Option<&ActorType>,
+    client_id: Option<&str>,
+) -> Value {
+    let participant = |code: &str, display: &str| {
+        json!({
+            "coding": [{
+                "system": "http://terminology.hl7.org/CodeSystem/provenance-participant-type",
+                "code": code,
+                "display": display
+            }]
+        })
+    };
+
+    let mut agents = Vec::new();
+    match actor {
+        Some(ActorType::User {
+            id,
+            name,
+            fhir_user,
+        }) => {
+            let mut who = match fhir_user {
+                Some(fhir_user) => json!({ "reference": fhir_user }),
+                None => json!({ "identifier": { "value": id } }),
+            };
+            if let Some(name) = name {
+                who["display"] = json!(name);
+            }
+            agents.push(json!({
+                "type": participant("author", "Author"),
+                "who": who
+            }));
+            if let Some(client_id) = client_id {
+                agents.push(json!({
+                    "type": participant("performer", "Performer"),
+                    "who": { "identifier": { "value": client_id } }
+                }));
+            }
+        }
+        Some(ActorType::Client { id, name }) => {
+            let mut who = json!({ "identifier": { "value": id } });
+            if let Some(name) = name {
+                who["display"] = json!(name);
+            }
+            agents.push(json!({
+                "type": participant("performer", "Performer"),
+                "who": who
+            }));
+        }
+        Some(ActorType::System) | None => {
+            agents.push(json!({
+                "type": participant("performer", "Performer"),
+                "who": { "display": "OctoFHIR Server" }
+            }));
+        }
+    }
+
+    let mut provenance = json!({
+        "resourceType": "Provenance",
+        "target": targets
+            .iter()
+            .map(|(reference, _)| json!({ "reference": reference }))
+            .collect::<Vec<_>>(),
+        "recorded": OffsetDateTime::now_utc()
+            .format(&time::format_description::well_known::Rfc3339)
+            .unwrap_or_default(),
+        "agent": agents
+    });
+
+    if let Some((_, operation)) = targets.first()
+        && targets.iter().all(|(_, op)| op == operation)
+    {
+        provenance["activity"] = json!({
+            "coding": [{
+                "system": "http://terminology.hl7.org/CodeSystem/v3-DataOperation",
+                "code": operation
+            }]
+        });
+    }
+
+    provenance
+}
+
 /// Determine audit action from HTTP method and path
 pub fn action_from_request(method: &axum::http::Method, path: &str) -> Option<AuditAction> {
     use axum::http::Method;
@@ -922,6 +1164,74 @@ mod tests {
         assert_eq!(event["subtype"][0]["code"], "resource.create");
     }
 
+    #[test]
+    fn test_transaction_provenance() {
+        let actor = ActorType::User {
+            id: "u1".to_string(),
+            name: Some("alice".to_string()),
+            fhir_user: Some("Practitioner/p1".to_string()),
+        };
+        let targets = vec![
+            ("Patient/1/_history/1".to_string(), "CREATE"),
+            ("Observation/2/_history/3".to_string(), "UPDATE"),
+        ];
+        let provenance = build_transaction_provenance(&targets, Some(&actor), Some("app"));
+
+        assert_eq!(provenance["resourceType"], "Provenance");
+        assert_eq!(
+            provenance["target"][1]["reference"],
+            "Observation/2/_history/3"
+        );
+        assert_eq!(
+            provenance["agent"][0]["who"]["reference"],
+            "Practitioner/p1"
+        );
+        assert_eq!(provenance["agent"][1]["who"]["identifier"]["value"], "app");
+        // Mixed operations leave the activity unset
+        assert!(provenance.get("activity").is_none());
+
+        let provenance = build_transaction_provenance(&targets[..1], None, None);
+        assert_eq!(provenance["activity"]["coding"][0]["code"], "CREATE");
+        assert_eq!(provenance["agent"][0]["who"]["display"], "OctoFHIR Server");
+    }
+
+    #[test]
+    fn test_redact_elements() {
+        let mut patient = json!({
+            "resourceType": "Patient",
+            "id": "p1",
+            "name": [{"family": "Doe", "given": ["Jane"]}],
+            "address": [{"line": ["1 Main St"], "city": "Springfield"}],
+            "birthDate": "1980-01-01",
+            "_birthDate": {"extension": [{"url": "x", "valueString": "y"}]},
+            "deceasedBoolean": false,
+            "gender": "female"
+        });
+        let paths = ["name", "address.line", "birthDate", "deceased[x]"].map(String::from);
+        redact_elements(&mut patient, &paths);
+
+        assert_eq!(
+            patient,
+            json!({
+                "resourceType": "Patient",
+                "id": "p1",
+                "address": [{"city": "Springfield"}],
+                "gender": "female"
+            })
+        );
+    }
+
+    #[test]
+    fn test_entity_detail() {
+        let event = AuditEventBuilder::new(AuditAction::ResourceUpdate)
+            .entity(Some("Patient".to_string()), Some("p1".to_string()), None)
+            .entity_detail("diff", "[]")
+            .build();
+
+        assert_eq!(event["entity"][0]["detail"][0]["type"], "diff");
+        assert_eq!(event["entity"][0]["detail"][0]["valueString"], "[]");
+    }
+
     #[test]
     fn test_action_codes() {
         assert_eq!(AuditAction::ResourceCreate.to_action_code(), "C");
@@ -0,0 +1,195 @@
+//! Externalized `Attachment.data`.
+//!
+//! Attachments use the same `_data` pointer as Binary content. They are found
+//! by shape rather than by type information: an object qualifies when every
+//! element in it belongs to the Attachment datatype. That keeps
+//! `Signature.data` and `SampledData.data`, whose siblings are not Attachment
+//! elements, out of it.
+
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use serde_json::{Map, Value};
+
+use super::{BlobPointer, blob_key, pointer_in, remove_pointer, set_pointer};
+
+/// Elements of the Attachment datatype (R4 and later).
+const ATTACHMENT_ELEMENTS: &[&str] = &[
+    "id",
+    "extension",
+    "contentType",
+    "language",
+    "data",
+    "url",
+    "size",
+    "hash",
+    "title",
+    "creation",
+    "height",
+    "width",
+    "frames",
+    "duration",
+    "pages",
+];
+
+fn is_attachment(obj: &Map<String, Value>) -> bool {
+    obj.keys().all(|key| {
+        let element = key.strip_prefix('_').unwrap_or(key);
+        ATTACHMENT_ELEMENTS.contains(&element)
+    })
+}
+
+/// Content moved out of an attachment, still to be written to the blob store.
+pub(super) struct Upload {
+    pub key: String,
+    pub data: Vec<u8>,
+    pub content_type: Option<String>,
+}
+
+fn for_each_attachment_mut(value: &mut Value, f: &mut impl FnMut(&mut Map<String, Value>)) {
+    match value {
+        Value::Object(obj) => {
+            if is_attachment(obj) {
+                f(obj);
+            }
+            for child in obj.values_mut() {
+                for_each_attachment_mut(child, f);
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                for_each_attachment_mut(item, f);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Replaces the `data` of attachments at least `threshold_bytes` long with
+/// blob pointers, and drops pointers sent by the client. Returns whether
+/// `resource` changed; the content to write is appended to `uploads`.
+pub(super) fn extract(
+    resource: &mut Value,
+    threshold_bytes: usize,
+    uploads: &mut Vec<Upload>,
+) -> bool {
+    let mut changed = false;
+    for_each_attachment_mut(resource, &mut |attachment| {
+        if pointer_in(attachment).is_some() {
+            remove_pointer(attachment);
+            changed = true;
+        }
+        let Some(encoded) = attachment.get("data").and_then(Value::as_str) else {
+            return;
+        };
+        // Undecodable data is left for validation to report.
+        let Ok(data) = BASE64.decode(encoded) else {
+            return;
+        };
+        if data.len() < threshold_bytes {
+            return;
+        }
+        let pointer = BlobPointer {
+            key: blob_key(&data),
+            size: data.len() as u64,
+        };
+        uploads.push(Upload {
+            key: pointer.key.clone(),
+            data,
+            content_type: attachment
+                .get("contentType")
+                .and_then(Value::as_str)
+                .map(str::to_string),
+        });
+        attachment.remove("data");
+        set_pointer(attachment, &pointer);
+        changed = true;
+    });
+    changed
+}
+
+fn for_each_attachment(value: &Value, f: &mut impl FnMut(&Map<String, Value>)) {
+    match value {
+        Value::Object(obj) => {
+            if is_attachment(obj) {
+                f(obj);
+            }
+            for child in obj.values() {
+                for_each_attachment(child, f);
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                for_each_attachment(item, f);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Whether [`extract`] could change `resource`: some attachment carries a
+/// pointer or inline data that may decode to `threshold_bytes` or more.
+pub(super) fn needs_extract(resource: &Value, threshold_bytes: usize) -> bool {
+    let mut found = false;
+    for_each_attachment(resource, &mut |attachment| {
+        found = found
+            || pointer_in(attachment).is_some()
+            || attachment
+                .get("data")
+                .and_then(Value::as_str)
+                .is_some_and(|encoded| encoded.len() / 4 * 3 >= threshold_bytes);
+    });
+    found
+}
+
+/// Blob pointers of the externalized attachments in `resource`.
+pub(super) fn pointers(resource: &Value) -> Vec<BlobPointer> {
+    let mut found = Vec::new();
+    for_each_attachment(resource, &mut |attachment| {
+        found.extend(pointer_in(attachment));
+    });
+    found
+}
+
+/// Removes the pointer from every externalized attachment in `resource` and
+/// hands the attachment to `fill` to put the content (or a link to it) back.
+pub(super) fn resolve(
+    resource: &mut Value,
+    fill: &mut impl FnMut(&BlobPointer, &mut Map<String, Value>),
+) {
+    for_each_attachment_mut(resource, &mut |attachment| {
+        if let Some(pointer) = pointer_in(attachment) {
+            remove_pointer(attachment);
+            fill(&pointer, attachment);
+        }
+    });
+}
+
+/// Like [`resolve`], but also passes the attachment at the same position in
+/// `original`, the resource as it was before [`extract`].
+pub(super) fn resolve_from(
+    stored: &mut Value,
+    original: &Value,
+    fill: &mut impl FnMut(&BlobPointer, &mut Map<String, Value>, &Map<String, Value>),
+) {
+    match (stored, original) {
+        (Value::Object(obj), Value::Object(original_obj)) => {
+            if is_attachment(obj)
+                && let Some(pointer) = pointer_in(obj)
+            {
+                remove_pointer(obj);
+                fill(&pointer, obj, original_obj);
+            }
+            for (key, child) in obj.iter_mut() {
+                if let Some(original_child) = original_obj.get(key) {
+                    resolve_from(child, original_child, fill);
+                }
+            }
+        }
+        (Value::Array(items), Value::Array(original_items)) => {
+            for (item, original_item) in items.iter_mut().zip(original_items) {
+                resolve_from(item, original_item, fill);
+            }
+        }
+        _ => {}
+    }
+}
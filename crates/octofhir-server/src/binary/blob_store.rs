@@ -0,0 +1,56 @@
+//! Blob store abstraction for Binary content.
+
+use std::time::SystemTime;
+
+use async_trait::async_trait;
+use octofhir_storage::StorageError;
+
+/// A blob in a [`BlobStore::list`] result.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlobInfo {
+    /// Blob store key
+    pub key: String,
+    /// When the blob was last written
+    pub modified: SystemTime,
+}
+
+/// Stores opaque blobs by key.
+///
+/// Keys are content-addressed (see [`super::blob_key`]), so the content under
+/// a key never changes. `put` of an existing key may skip the write, but must
+/// still refresh the blob's modification time: the sweep in [`super::gc`]
+/// only removes blobs nobody wrote recently.
+#[async_trait]
+pub trait BlobStore: Send + Sync {
+    /// Writes `data` under `key`.
+    async fn put(
+        &self,
+        key: &str,
+        data: Vec<u8>,
+        content_type: Option<&str>,
+    ) -> Result<(), StorageError>;
+
+    /// Reads the whole blob, or `None` if it does not exist.
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, StorageError>;
+
+    /// Reads bytes `start..=end` of the blob, or `None` if it does not exist.
+    /// `end` must be within the blob.
+    async fn get_range(
+        &self,
+        key: &str,
+        start: u64,
+        end: u64,
+    ) -> Result<Option<Vec<u8>>, StorageError>;
+
+    /// Lists the blobs whose keys start with `prefix`.
+    async fn list(&self, prefix: &str) -> Result<Vec<BlobInfo>, StorageError>;
+
+    /// When the blob was last written, or `None` if it does not exist.
+    async fn modified(&self, key: &str) -> Result<Option<SystemTime>, StorageError>;
+
+    /// Deletes the blob. Deleting a missing blob succeeds.
+    async fn delete(&self, key: &str) -> Result<(), StorageError>;
+
+    /// Backend name for logs.
+    fn backend_name(&self) -> &'static str;
+}
@@ -0,0 +1,260 @@
+//! Serving Binary content in its native form.
+//!
+//! A Binary read whose `Accept` header asks for something other than a FHIR
+//! format returns the content itself with the Binary's `contentType`
+//! (FHIR R4 §2.3 "Binary"). Single byte ranges (`Range: bytes=...`) are
+//! answered with `206 Partial Content`, read straight from the blob store when
+//! the content is external.
+
+use std::collections::HashMap;
+
+use axum::body::Body;
+use axum::http::{HeaderMap, StatusCode, header};
+use axum::response::Response;
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use octofhir_api::ApiError;
+use octofhir_storage::StoredResource;
+use serde_json::Value;
+
+use super::{BinaryBlobs, blob_pointer};
+use crate::storage_adapter::map_storage_error;
+
+/// Whether a Binary read should return the raw content instead of the
+/// resource: `_format` is absent and the preferred `Accept` media type is
+/// neither a FHIR/JSON/XML format nor a wildcard.
+pub fn wants_raw_content(headers: &HeaderMap, params: &HashMap<String, String>) -> bool {
+    if params.contains_key("_format") {
+        return false;
+    }
+    let Some(accept) = headers.get(header::ACCEPT).and_then(|v| v.to_str().ok()) else {
+        return false;
+    };
+    let preferred = accept
+        .split(',')
+        .next()
+        .and_then(|media| media.split(';').next())
+        .unwrap_or("")
+        .trim()
+        .to_ascii_lowercase();
+    !(preferred.is_empty()
+        || preferred == "*/*"
+        || preferred.contains("fhir")
+        || preferred.ends_with("/json")
+        || preferred.ends_with("/xml"))
+}
+
+/// Outcome of matching a `Range` header against the content length.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ByteRange {
+    /// No usable range: send everything
+    Full,
+    /// Bytes `start..=end`
+    Partial(u64, u64),
+    /// The range lies outside the content
+    Unsatisfiable,
+}
+
+/// Parses a single `bytes=` range. Multiple ranges and other units are
+/// ignored, which RFC 9110 allows by answering with the full content.
+fn parse_range(value: &str, size: u64) -> ByteRange {
+    let Some(spec) = value.trim().strip_prefix("bytes=") else {
+        return ByteRange::Full;
+    };
+    if spec.contains(',') {
+        return ByteRange::Full;
+    }
+    let Some((start, end)) = spec.trim().split_once('-') else {
+        return ByteRange::Full;
+    };
+    let (start, end) = (start.trim(), end.trim());
+    let range = if start.is_empty() {
+        // Suffix range: the last `end` bytes.
+        match end.parse::<u64>() {
+            Ok(0) => return ByteRange::Unsatisfiable,
+            Ok(n) => (size.saturating_sub(n), size.wrapping_sub(1)),
+            Err(_) => return ByteRange::Full,
+        }
+    } else {
+        let Ok(start) = start.parse::<u64>() else {
+            return ByteRange::Full;
+        };
+        let end = if end.is_empty() {
+            size.wrapping_sub(1)
+        } else {
+            match end.parse::<u64>() {
+                Ok(end) if end >= start => end.min(size.saturating_sub(1)),
+                Ok(_) => return ByteRange::Full,
+                Err(_) => return ByteRange::Full,
+            }
+        };
+        (start, end)
+    };
+    if size == 0 || range.0 >= size {
+        return ByteRange::Unsatisfiable;
+    }
+    ByteRange::Partial(range.0, range.1)
+}
+
+/// Builds the native-content response for a stored Binary. `stored` must be
+/// read with [`super::with_blob_pointers`] so external content is not loaded
+/// into the resource.
+pub async fn content_response(
+    stored: &StoredResource,
+    blobs: Option<&BinaryBlobs>,
+    headers: &HeaderMap,
+) -> Result<Response, ApiError> {
+    let resource = &stored.resource;
+    let etag = format!("W/\"{}\"", stored.version_id);
+    let content_type = resource
+        .get("contentType")
+        .and_then(Value::as_str)
+        .unwrap_or("application/octet-stream");
+
+    // External content is read from the blob store; inline content is decoded.
+    let (pointer, inline) = match (blob_pointer(resource), blobs) {
+        (Some(pointer), Some(blobs)) => (Some((pointer, blobs)), None),
+        (Some(_), None) => {
+            return Err(ApiError::internal(
+                "Binary content is in a blob store that is not configured",
+            ));
+        }
+        (None, _) => {
+            let data = match resource.get("data").and_then(Value::as_str) {
+                Some(encoded) => BASE64.decode(encoded).map_err(|e| {
+                    ApiError::internal(format!("Stored Binary data is invalid: {e}"))
+                })?,
+                None => Vec::new(),
+            };
+            (None, Some(data))
+        }
+    };
+    let size = match (&pointer, &inline) {
+        (Some((pointer, _)), _) => pointer.size,
+        (None, Some(data)) => data.len() as u64,
+        (None, None) => 0,
+    };
+
+    // If-Range: only honour the range while the client's copy is current.
+    let range_header = headers
+        .get(header::RANGE)
+        .and_then(|v| v.to_str().ok())
+        .filter(|_| {
+            headers
+                .get(header::IF_RANGE)
+                .and_then(|v| v.to_str().ok())
+                .is_none_or(|if_range| if_range == etag)
+        });
+    let range = range_header.map_or(ByteRange::Full, |value| parse_range(value, size));
+
+    let last_modified = httpdate::fmt_http_date(
+        std::time::UNIX_EPOCH
+            + std::time::Duration::from_secs(stored.last_updated.unix_timestamp() as u64),
+    );
+    let mut builder = Response::builder()
+        .header(header::ETAG, &etag)
+        .header(header::LAST_MODIFIED, last_modified)
+        .header(header::ACCEPT_RANGES, "bytes")
+        // The content is arbitrary client data served from the API origin.
+        .header(header::X_CONTENT_TYPE_OPTIONS, "nosniff")
+        .header(header::CONTENT_SECURITY_POLICY, "sandbox");
+    if let Some(security_context) = resource
+        .get("securityContext")
+        .and_then(|sc| sc.get("reference"))
+        .and_then(Value::as_str)
+    {
+        builder = builder.header("X-Security-Context", security_context);
+    }
+
+    let (status, body) = match range {
+        ByteRange::Unsatisfiable => {
+            return Ok(builder
+                .status(StatusCode::RANGE_NOT_SATISFIABLE)
+                .header(header::CONTENT_RANGE, format!("bytes */{size}"))
+                .body(Body::empty())
+                .unwrap());
+        }
+        ByteRange::Full => {
+            let body = match (pointer, inline) {
+                (Some((pointer, blobs)), _) => blobs
+                    .store()
+                    .get(&pointer.key)
+                    .await
+                    .map_err(map_storage_error)?
+                    .ok_or_else(|| missing_blob(&pointer.key))?,
+                (None, inline) => inline.unwrap_or_default(),
+            };
+            (StatusCode::OK, body)
+        }
+        ByteRange::Partial(start, end) => {
+            let body = match (pointer, inline) {
+                (Some((pointer, blobs)), _) => blobs
+                    .store()
+                    .get_range(&pointer.key, start, end)
+                    .await
+                    .map_err(map_storage_error)?
+                    .ok_or_else(|| missing_blob(&pointer.key))?,
+                (None, inline) => {
+                    inline.unwrap_or_default()[start as usize..=end as usize].to_vec()
+                }
+            };
+            builder = builder.header(header::CONTENT_RANGE, format!("bytes {start}-{end}/{size}"));
+            (StatusCode::PARTIAL_CONTENT, body)
+        }
+    };
+
+    Ok(builder
+        .status(status)
+        .header(header::CONTENT_TYPE, content_type)
+        .header(header::CONTENT_LENGTH, body.len())
+        .body(Body::from(body))
+        .unwrap())
+}
+
+fn missing_blob(key: &str) -> ApiError {
+    ApiError::internal(format!(
+        "Binary content is missing from the blob store ({key})"
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(parse_range("bytes=0-9", 100), ByteRange::Partial(0, 9));
+        assert_eq!(parse_range("bytes=90-", 100), ByteRange::Partial(90, 99));
+        assert_eq!(parse_range("bytes=-10", 100), ByteRange::Partial(90, 99));
+        assert_eq!(parse_range("bytes=-500", 100), ByteRange::Partial(0, 99));
+        assert_eq!(parse_range("bytes=50-500", 100), ByteRange::Partial(50, 99));
+        assert_eq!(parse_range("bytes=100-", 100), ByteRange::Unsatisfiable);
+        assert_eq!(parse_range("bytes=0-1,5-6", 100), ByteRange::Full);
+        assert_eq!(parse_range("items=0-1", 100), ByteRange::Full);
+        assert_eq!(parse_range("bytes=9-0", 100), ByteRange::Full);
+    }
+
+    #[test]
+    fn test_wants_raw_content() {
+        let accept = |value: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(header::ACCEPT, value.parse().unwrap());
+            headers
+        };
+        let no_params = HashMap::new();
+        assert!(wants_raw_content(&accept("image/png"), &no_params));
+        assert!(wants_raw_content(
+            &accept("application/pdf, */*;q=0.8"),
+            &no_params
+        ));
+        assert!(!wants_raw_content(
+            &accept("application/fhir+json"),
+            &no_params
+        ));
+        assert!(!wants_raw_content(&accept("*/*"), &no_params));
+        assert!(!wants_raw_content(&HeaderMap::new(), &no_params));
+
+        let format = HashMap::from([("_format".to_string(), "json".to_string())]);
+        assert!(!wants_raw_content(&accept("image/png"), &format));
+    }
+}
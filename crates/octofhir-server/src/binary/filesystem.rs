@@ -0,0 +1,278 @@
+//! Filesystem blob store.
+
+use std::io::{ErrorKind, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use async_trait::async_trait;
+use octofhir_storage::StorageError;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+use super::blob_store::{BlobInfo, BlobStore};
+
+/// Keeps each blob in a file under a root directory, at the blob's key.
+#[derive(Debug, Clone)]
+pub struct FilesystemBlobStore {
+    root: PathBuf,
+}
+
+impl FilesystemBlobStore {
+    /// Creates a store rooted at `root`. The directory is created on first
+    /// write.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    fn path_for(&self, key: &str) -> Result<PathBuf, StorageError> {
+        // Keys are generated by the server, but never let one leave the root.
+        if key
+            .split('/')
+            .any(|segment| segment.is_empty() || segment == "." || segment == "..")
+        {
+            return Err(StorageError::internal(format!("Invalid blob key '{key}'")));
+        }
+        Ok(self.root.join(key))
+    }
+}
+
+fn io_error(action: &str, path: &Path, e: std::io::Error) -> StorageError {
+    StorageError::internal(format!("Failed to {action} blob {}: {e}", path.display()))
+}
+
+#[async_trait]
+impl BlobStore for FilesystemBlobStore {
+    async fn put(
+        &self,
+        key: &str,
+        data: Vec<u8>,
+        _content_type: Option<&str>,
+    ) -> Result<(), StorageError> {
+        let path = self.path_for(key)?;
+        if tokio::fs::try_exists(&path).await.unwrap_or(false) {
+            return touch(&path).await;
+        }
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|e| io_error("create directory for", &path, e))?;
+        }
+        // Write to a temporary file and rename, so a reader never sees a
+        // partially written blob.
+        let tmp = path.with_extension(format!("tmp-{}", uuid::Uuid::new_v4()));
+        tokio::fs::write(&tmp, &data)
+            .await
+            .map_err(|e| io_error("write", &tmp, e))?;
+        if let Err(e) = tokio::fs::rename(&tmp, &path).await {
+            let _ = tokio::fs::remove_file(&tmp).await;
+            return Err(io_error("store", &path, e));
+        }
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, StorageError> {
+        let path = self.path_for(key)?;
+        match tokio::fs::read(&path).await {
+            Ok(data) => Ok(Some(data)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(io_error("read", &path, e)),
+        }
+    }
+
+    async fn get_range(
+        &self,
+        key: &str,
+        start: u64,
+        end: u64,
+    ) -> Result<Option<Vec<u8>>, StorageError> {
+        let path = self.path_for(key)?;
+        let mut file = match tokio::fs::File::open(&path).await {
+            Ok(file) => file,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(io_error("open", &path, e)),
+        };
+        file.seek(SeekFrom::Start(start))
+            .await
+            .map_err(|e| io_error("seek", &path, e))?;
+        let mut buf = vec![0u8; (end - start + 1) as usize];
+        file.read_exact(&mut buf)
+            .await
+            .map_err(|e| io_error("read", &path, e))?;
+        Ok(Some(buf))
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<BlobInfo>, StorageError> {
+        let mut blobs = Vec::new();
+        let mut dirs = vec![(self.root.clone(), String::new())];
+        while let Some((dir, dir_key)) = dirs.pop() {
+            let mut entries = match tokio::fs::read_dir(&dir).await {
+                Ok(entries) => entries,
+                Err(e) if e.kind() == ErrorKind::NotFound => continue,
+                Err(e) => return Err(io_error("list", &dir, e)),
+            };
+            while let Some(entry) = entries
+                .next_entry()
+                .await
+                .map_err(|e| io_error("list", &dir, e))?
+            {
+                let Ok(name) = entry.file_name().into_string() else {
+                    continue;
+                };
+                let key = format!("{dir_key}{name}");
+                let path = entry.path();
+                let metadata = entry
+                    .metadata()
+                    .await
+                    .map_err(|e| io_error("stat", &path, e))?;
+                if metadata.is_dir() {
+                    let dir_prefix = format!("{key}/");
+                    if dir_prefix.starts_with(prefix) || prefix.starts_with(&dir_prefix) {
+                        dirs.push((path, dir_prefix));
+                    }
+                } else if key.starts_with(prefix) && !name.contains('.') {
+                    // Files with an extension are writes still in progress.
+                    blobs.push(BlobInfo {
+                        key,
+                        modified: metadata
+                            .modified()
+                            .map_err(|e| io_error("stat", &path, e))?,
+                    });
+                }
+            }
+        }
+        Ok(blobs)
+    }
+
+    async fn modified(&self, key: &str) -> Result<Option<SystemTime>, StorageError> {
+        let path = self.path_for(key)?;
+        match tokio::fs::metadata(&path).await {
+            Ok(metadata) => metadata
+                .modified()
+                .map(Some)
+                .map_err(|e| io_error("stat", &path, e)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(io_error("stat", &path, e)),
+        }
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), StorageError> {
+        let path = self.path_for(key)?;
+        match tokio::fs::remove_file(&path).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(io_error("delete", &path, e)),
+        }
+    }
+
+    fn backend_name(&self) -> &'static str {
+        "filesystem"
+    }
+}
+
+/// Sets the modification time of an existing blob to now.
+async fn touch(path: &Path) -> Result<(), StorageError> {
+    let file = tokio::fs::OpenOptions::new()
+        .write(true)
+        .open(path)
+        .await
+        .map_err(|e| io_error("open", path, e))?
+        .into_std()
+        .await;
+    tokio::task::spawn_blocking(move || file.set_modified(SystemTime::now()))
+        .await
+        .map_err(|e| StorageError::internal(format!("Blob touch task failed: {e}")))?
+        .map_err(|e| io_error("touch", path, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_put_get_range_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FilesystemBlobStore::new(dir.path());
+
+        store
+            .put("default/ab/abcdef", b"hello world".to_vec(), None)
+            .await
+            .unwrap();
+        assert_eq!(
+            store.get("default/ab/abcdef").await.unwrap().as_deref(),
+            Some(&b"hello world"[..])
+        );
+        assert_eq!(
+            store
+                .get_range("default/ab/abcdef", 6, 10)
+                .await
+                .unwrap()
+                .as_deref(),
+            Some(&b"world"[..])
+        );
+        assert!(store.get("default/ab/missing").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_list_and_delete() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FilesystemBlobStore::new(dir.path());
+        for key in ["default/ab/abcdef", "tenant/t1/cd/cdef01"] {
+            store.put(key, b"x".to_vec(), None).await.unwrap();
+        }
+
+        let mut keys: Vec<_> = store
+            .list("")
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|blob| blob.key)
+            .collect();
+        keys.sort();
+        assert_eq!(keys, ["default/ab/abcdef", "tenant/t1/cd/cdef01"]);
+        let tenant = store.list("tenant/t1/").await.unwrap();
+        assert_eq!(tenant.len(), 1);
+        assert_eq!(tenant[0].key, "tenant/t1/cd/cdef01");
+
+        store.delete("default/ab/abcdef").await.unwrap();
+        store.delete("default/ab/abcdef").await.unwrap();
+        assert!(store.modified("default/ab/abcdef").await.unwrap().is_none());
+        assert!(
+            store
+                .modified("tenant/t1/cd/cdef01")
+                .await
+                .unwrap()
+                .is_some()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rewrite_refreshes_modified_time() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FilesystemBlobStore::new(dir.path());
+        store
+            .put("default/ab/abcdef", b"x".to_vec(), None)
+            .await
+            .unwrap();
+        let old = SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_000_000);
+        std::fs::File::options()
+            .write(true)
+            .open(dir.path().join("default/ab/abcdef"))
+            .unwrap()
+            .set_modified(old)
+            .unwrap();
+
+        store
+            .put("default/ab/abcdef", b"x".to_vec(), None)
+            .await
+            .unwrap();
+        let modified = store.modified("default/ab/abcdef").await.unwrap().unwrap();
+        assert!(modified > old);
+    }
+
+    #[tokio::test]
+    async fn test_rejects_keys_escaping_root() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FilesystemBlobStore::new(dir.path());
+        assert!(store.get("../etc/passwd").await.is_err());
+        assert!(store.get("/etc/passwd").await.is_err());
+    }
+}
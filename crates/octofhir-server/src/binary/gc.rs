@@ -0,0 +1,130 @@
+//! Sweeping of unreferenced blobs.
+//!
+//! Blobs are shared by every resource and version with the same content, so
+//! a delete cannot remove its blob on the spot. Instead a sweep lists the
+//! blob store, collects every blob key still referenced from a resource or
+//! history table (in all tenant schemas), and deletes the rest. A blob
+//! written within the grace period is kept, so content uploaded for a
+//! resource that is not committed yet survives; `put` of an existing key
+//! refreshes its modification time for the same reason.
+//!
+//! Sweeps run every `binary.gc.interval_secs`, and shortly after a committed
+//! delete or a history retention run asks for one with
+//! [`BinaryBlobs::request_sweep`].
+
+use std::collections::HashSet;
+use std::time::{Duration, SystemTime};
+
+use octofhir_storage::StorageError;
+use sqlx_core::query_scalar::query_scalar;
+use sqlx_core::sql_str::AssertSqlSafe;
+use sqlx_postgres::PgPool;
+use tokio::task::JoinHandle;
+
+use super::{BINARY_BLOB_EXTENSION, BinaryBlobs, BlobStore, is_blob_key};
+use crate::config::BinaryGcConfig;
+
+/// How long a requested sweep waits, so a burst of deletes is swept once.
+const REQUESTED_SWEEP_DELAY: Duration = Duration::from_secs(5 * 60);
+
+/// Blob keys as [`super::blob_key`] builds them, for matching in stored JSON.
+const BLOB_KEY_PATTERN: &str = r#"(?:default|tenant/[^/"]+)/[0-9a-f]{2}/[0-9a-f]{64}"#;
+
+/// Spawns the sweep task. The first scheduled sweep happens one interval
+/// after start.
+pub fn spawn_blob_gc(pool: PgPool, blobs: BinaryBlobs, config: BinaryGcConfig) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(config.interval_secs));
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        // Skip the immediate first tick — let the server warm up first.
+        ticker.tick().await;
+
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => {}
+                _ = blobs.sweep_requested() => {
+                    tokio::time::sleep(REQUESTED_SWEEP_DELAY).await;
+                }
+            }
+            let grace = Duration::from_secs(config.grace_secs);
+            match sweep(&pool, blobs.store().as_ref(), grace).await {
+                Ok(0) => {}
+                Ok(deleted) => tracing::info!(deleted = deleted, "Deleted unreferenced blobs"),
+                Err(e) => tracing::warn!(error = %e, "Blob sweep failed"),
+            }
+        }
+    })
+}
+
+/// Deletes blobs that no stored resource references and that were last
+/// written more than `grace` ago. Returns the number of blobs deleted.
+pub async fn sweep(
+    pool: &PgPool,
+    store: &dyn BlobStore,
+    grace: Duration,
+) -> Result<u64, StorageError> {
+    let cutoff = SystemTime::now()
+        .checked_sub(grace)
+        .unwrap_or(SystemTime::UNIX_EPOCH);
+    let candidates: Vec<_> = store
+        .list("")
+        .await?
+        .into_iter()
+        .filter(|blob| blob.modified < cutoff && is_blob_key(&blob.key))
+        .collect();
+    if candidates.is_empty() {
+        return Ok(0);
+    }
+
+    let referenced = referenced_keys(pool).await?;
+    let mut deleted = 0;
+    for blob in candidates {
+        if referenced.contains(&blob.key) {
+            continue;
+        }
+        // A write since the listing reuses the blob; check again right
+        // before deleting.
+        match store.modified(&blob.key).await? {
+            Some(modified) if modified < cutoff => {
+                store.delete(&blob.key).await?;
+                deleted += 1;
+            }
+            _ => {}
+        }
+    }
+    Ok(deleted)
+}
+
+/// Every blob key referenced from a resource or history table, in the shared
+/// schema and all tenant schemas.
+async fn referenced_keys(pool: &PgPool) -> Result<HashSet<String>, StorageError> {
+    let tables: Vec<String> = query_scalar(
+        r"SELECT format('%I.%I', n.nspname, c.relname)
+           FROM pg_class c
+           JOIN pg_namespace n ON n.oid = c.relnamespace
+           JOIN pg_attribute a ON a.attrelid = c.oid
+           WHERE a.attname = 'resource' AND NOT a.attisdropped
+             AND c.relkind IN ('r', 'p') AND NOT c.relispartition
+             AND (n.nspname = 'public' OR n.nspname LIKE 'tenant\_%')",
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| StorageError::internal(format!("Failed to list resource tables: {e}")))?;
+
+    let mut keys = HashSet::new();
+    for table in tables {
+        let table_keys: Vec<String> = query_scalar(AssertSqlSafe(format!(
+            "SELECT DISTINCT m[1] FROM {table}, regexp_matches(resource::text, $1, 'g') AS m \
+             WHERE strpos(resource::text, $2) > 0"
+        )))
+        .bind(BLOB_KEY_PATTERN)
+        .bind(BINARY_BLOB_EXTENSION)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| {
+            StorageError::internal(format!("Failed to collect blob keys from {table}: {e}"))
+        })?;
+        keys.extend(table_keys);
+    }
+    Ok(keys)
+}
@@ -0,0 +1,703 @@
+//! External blob storage for `Binary` content.
+//!
+//! With `binary.backend` set to `filesystem` or `s3`, the decoded `data` of a
+//! `Binary` resource at least `binary.threshold_bytes` long is written to a
+//! [`BlobStore`] instead of the resource table. The stored resource keeps no
+//! `data`; a pointer extension on `_data` records the blob key and size:
+//!
+//! ```json
+//! "_data": {"extension": [{
+//!   "url": "http://octofhir.io/StructureDefinition/binary-blob",
+//!   "extension": [
+//!     {"url": "key", "valueString": "default/3a/3a7bd3e2..."},
+//!     {"url": "size", "valueUnsignedInt": 1048576}
+//!   ]
+//! }]}
+//! ```
+//!
+//! [`BinaryBlobStorage`] does the swap below the rest of the storage stack,
+//! so every write through [`octofhir_storage::FhirStorage`] offloads and every
+//! read restores `data`. Blob keys are the SHA-256 of the content, scoped by
+//! tenant: identical content shares one blob. A blob is never rewritten; the
+//! sweep in [`gc`] deletes it once no resource version points to it.
+//!
+//! With `binary.attachments.enabled`, large `Attachment.data` in any other
+//! resource is moved out the same way. Reads of the current version and
+//! searches put it back or, with `on_read = "redirect"`, link to it at
+//! `{base}/fhir/_attachments/{sha256}`; history and vread always put it back.
+//!
+//! The REST search handlers read the resource tables directly, below the
+//! wrapper, and put the content of matches and included resources back with
+//! [`BinaryBlobs::inflate_raw`].
+
+mod attachments;
+pub mod blob_store;
+pub mod content;
+pub mod filesystem;
+pub mod gc;
+pub mod s3;
+pub mod storage;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use octofhir_storage::{RawStoredResource, StorageError};
+use serde_json::{Map, Value, json};
+use sha2::{Digest, Sha256};
+use tokio::sync::Notify;
+
+pub use blob_store::{BlobInfo, BlobStore};
+pub use filesystem::FilesystemBlobStore;
+pub use s3::S3BlobStore;
+pub use storage::BinaryBlobStorage;
+
+use crate::config::{AttachmentReadMode, BinaryBackend, BinaryConfig};
+
+/// Extension URL of the blob pointer on `Binary._data`.
+pub const BINARY_BLOB_EXTENSION: &str = "http://octofhir.io/StructureDefinition/binary-blob";
+
+tokio::task_local! {
+    static KEEP_POINTERS: ();
+}
+
+/// Runs `fut` with reads returning Binary resources as stored, with the blob
+/// pointer instead of `data`. Used to stream content straight from the blob
+/// store without loading it into the resource first.
+pub async fn with_blob_pointers<F: std::future::Future>(fut: F) -> F::Output {
+    KEEP_POINTERS.scope((), fut).await
+}
+
+fn keeping_pointers() -> bool {
+    KEEP_POINTERS.try_with(|_| ()).is_ok()
+}
+
+/// Location of a Binary's content in the blob store.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlobPointer {
+    /// Blob store key
+    pub key: String,
+    /// Content length in bytes
+    pub size: u64,
+}
+
+/// Reads the blob pointer of a stored Binary, if its content is external.
+pub fn blob_pointer(resource: &Value) -> Option<BlobPointer> {
+    pointer_in(resource.as_object()?)
+}
+
+/// Reads the blob pointer on the `_data` of a Binary or an Attachment.
+fn pointer_in(element: &Map<String, Value>) -> Option<BlobPointer> {
+    let extension = element
+        .get("_data")?
+        .get("extension")?
+        .as_array()?
+        .iter()
+        .find(|ext| ext.get("url").and_then(Value::as_str) == Some(BINARY_BLOB_EXTENSION))?;
+    let part = |name: &str| {
+        extension
+            .get("extension")?
+            .as_array()?
+            .iter()
+            .find(|ext| ext.get("url").and_then(Value::as_str) == Some(name))
+    };
+    Some(BlobPointer {
+        key: part("key")?.get("valueString")?.as_str()?.to_string(),
+        size: part("size")?.get("valueUnsignedInt")?.as_u64()?,
+    })
+}
+
+/// Removes the blob pointer from `_data`, dropping `_data` if nothing else
+/// remains in it.
+fn remove_pointer(resource: &mut Map<String, Value>) {
+    let Some(Value::Object(data_element)) = resource.get_mut("_data") else {
+        return;
+    };
+    if let Some(Value::Array(extensions)) = data_element.get_mut("extension") {
+        extensions
+            .retain(|ext| ext.get("url").and_then(Value::as_str) != Some(BINARY_BLOB_EXTENSION));
+        if extensions.is_empty() {
+            data_element.remove("extension");
+        }
+    }
+    if data_element.is_empty() {
+        resource.remove("_data");
+    }
+}
+
+fn set_pointer(resource: &mut Map<String, Value>, pointer: &BlobPointer) {
+    let data_element = resource
+        .entry("_data")
+        .or_insert_with(|| Value::Object(Map::new()));
+    if !data_element.is_object() {
+        *data_element = Value::Object(Map::new());
+    }
+    let extensions = data_element
+        .as_object_mut()
+        .expect("_data is an object")
+        .entry("extension")
+        .or_insert_with(|| Value::Array(Vec::new()));
+    if !extensions.is_array() {
+        *extensions = Value::Array(Vec::new());
+    }
+    extensions
+        .as_array_mut()
+        .expect("extension is an array")
+        .push(json!({
+            "url": BINARY_BLOB_EXTENSION,
+            "extension": [
+                {"url": "key", "valueString": pointer.key},
+                {"url": "size", "valueUnsignedInt": pointer.size}
+            ]
+        }));
+}
+
+/// Content-addressed blob key: the SHA-256 of the content, under the current
+/// tenant's scope.
+pub fn blob_key(data: &[u8]) -> String {
+    blob_key_for_hash(&hex::encode(Sha256::digest(data)))
+}
+
+/// Blob key of the content with the given hex SHA-256 in the current tenant.
+pub fn blob_key_for_hash(hash: &str) -> String {
+    let scope = match octofhir_storage::current_tenant() {
+        Some(tenant) => format!("tenant/{}", tenant.as_str()),
+        None => "default".to_string(),
+    };
+    format!("{scope}/{}/{hash}", &hash[..2.min(hash.len())])
+}
+
+/// Whether `hash` has the form of a hex SHA-256.
+pub fn is_content_hash(hash: &str) -> bool {
+    hash.len() == 64 && hash.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+/// Whether `key` has the form of a blob key from [`blob_key`].
+pub fn is_blob_key(key: &str) -> bool {
+    let (shard, hash) = match key.split('/').collect::<Vec<_>>().as_slice() {
+        ["default", shard, hash] => (*shard, *hash),
+        ["tenant", tenant, shard, hash] if !tenant.is_empty() => (*shard, *hash),
+        _ => return false,
+    };
+    is_content_hash(hash) && shard.len() == 2 && hash.starts_with(shard)
+}
+
+/// The content hash a blob key ends with.
+fn key_hash(key: &str) -> &str {
+    key.rsplit('/').next().unwrap_or(key)
+}
+
+fn is_binary(resource: &Value) -> bool {
+    resource.get("resourceType").and_then(Value::as_str) == Some("Binary")
+}
+
+/// Moves Binary content between resources and the blob store.
+#[derive(Clone)]
+pub struct BinaryBlobs {
+    store: Arc<dyn BlobStore>,
+    threshold_bytes: usize,
+    attachments: Option<AttachmentSettings>,
+    sweep: Arc<Notify>,
+}
+
+#[derive(Debug, Clone)]
+struct AttachmentSettings {
+    threshold_bytes: usize,
+    /// Link target prefix for `on_read = "redirect"`; `None` reads inline
+    url_base: Option<String>,
+}
+
+impl std::fmt::Debug for BinaryBlobs {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BinaryBlobs")
+            .field("backend", &self.store.backend_name())
+            .field("threshold_bytes", &self.threshold_bytes)
+            .field("attachments", &self.attachments)
+            .finish()
+    }
+}
+
+impl BinaryBlobs {
+    /// Creates a handle over `store`, offloading content of at least
+    /// `threshold_bytes`.
+    pub fn new(store: Arc<dyn BlobStore>, threshold_bytes: usize) -> Self {
+        Self {
+            store,
+            threshold_bytes,
+            attachments: None,
+            sweep: Arc::new(Notify::new()),
+        }
+    }
+
+    /// Also externalizes `Attachment.data` of at least `threshold_bytes` in
+    /// other resources. With `url_base`, current-version reads link to the
+    /// content at `{url_base}/{sha256}` instead of inlining it.
+    pub fn with_attachments(mut self, threshold_bytes: usize, url_base: Option<String>) -> Self {
+        self.attachments = Some(AttachmentSettings {
+            threshold_bytes,
+            url_base,
+        });
+        self
+    }
+
+    /// Builds the configured blob store, or `None` for the inline backend.
+    /// `base_url` is the server's public base URL, used for attachment links.
+    pub fn from_config(config: &BinaryConfig, base_url: &str) -> Result<Option<Self>, String> {
+        let store: Arc<dyn BlobStore> = match config.backend {
+            BinaryBackend::Inline => return Ok(None),
+            BinaryBackend::Filesystem => {
+                Arc::new(FilesystemBlobStore::new(&config.filesystem.path))
+            }
+            BinaryBackend::S3 => Arc::new(S3BlobStore::from_config(&config.s3)?),
+        };
+        let mut blobs = Self::new(store, config.threshold_bytes);
+        if config.attachments.enabled {
+            let url_base = (config.attachments.on_read == AttachmentReadMode::Redirect)
+                .then(|| format!("{}/fhir/_attachments", base_url.trim_end_matches('/')));
+            blobs = blobs.with_attachments(config.attachments.threshold_bytes, url_base);
+        }
+        Ok(Some(blobs))
+    }
+
+    /// The underlying blob store.
+    pub fn store(&self) -> &Arc<dyn BlobStore> {
+        &self.store
+    }
+
+    /// Asks the sweep task to look for unreferenced blobs soon, after
+    /// resource versions were removed.
+    pub fn request_sweep(&self) {
+        self.sweep.notify_one();
+    }
+
+    /// Waits for [`Self::request_sweep`].
+    async fn sweep_requested(&self) {
+        self.sweep.notified().await;
+    }
+
+    /// Prepares a resource for storage. Returns the resource to store in its
+    /// place, or `None` to store it unchanged.
+    ///
+    /// Large Binary content is written to the blob store and replaced by a
+    /// pointer. A pointer sent by the client is dropped: only the server may
+    /// point a Binary at a blob.
+    pub async fn offload(&self, resource: &Value) -> Result<Option<Value>, StorageError> {
+        if !is_binary(resource) {
+            return self.offload_attachments(resource).await;
+        }
+        let client_pointer = blob_pointer(resource).is_some();
+        let without_client_pointer = || {
+            client_pointer.then(|| {
+                let mut copy = resource.clone();
+                if let Some(obj) = copy.as_object_mut() {
+                    remove_pointer(obj);
+                }
+                copy
+            })
+        };
+
+        let Some(encoded) = resource.get("data").and_then(Value::as_str) else {
+            return Ok(without_client_pointer());
+        };
+        // Undecodable data is left for validation to report.
+        let Ok(data) = BASE64.decode(encoded) else {
+            return Ok(without_client_pointer());
+        };
+        if data.len() < self.threshold_bytes {
+            return Ok(without_client_pointer());
+        }
+
+        let pointer = BlobPointer {
+            key: blob_key(&data),
+            size: data.len() as u64,
+        };
+        let content_type = resource.get("contentType").and_then(Value::as_str);
+        self.store.put(&pointer.key, data, content_type).await?;
+
+        let mut stored = resource.clone();
+        if let Some(obj) = stored.as_object_mut() {
+            remove_pointer(obj);
+            obj.remove("data");
+            set_pointer(obj, &pointer);
+        }
+        Ok(Some(stored))
+    }
+
+    /// Externalizes large attachments of a non-Binary resource. Pointers sent
+    /// by the client are dropped here too.
+    async fn offload_attachments(&self, resource: &Value) -> Result<Option<Value>, StorageError> {
+        let threshold_bytes = self
+            .attachments
+            .as_ref()
+            .map_or(usize::MAX, |settings| settings.threshold_bytes);
+        if !attachments::needs_extract(resource, threshold_bytes) {
+            return Ok(None);
+        }
+        let mut stored = resource.clone();
+        let mut uploads = Vec::new();
+        if !attachments::extract(&mut stored, threshold_bytes, &mut uploads) {
+            return Ok(None);
+        }
+        for upload in uploads {
+            self.store
+                .put(&upload.key, upload.data, upload.content_type.as_deref())
+                .await?;
+        }
+        Ok(Some(stored))
+    }
+
+    /// Restores the content of a stored resource's current version: the
+    /// `data` of a Binary, and externalized attachments as configured by
+    /// `on_read`.
+    pub async fn inflate(&self, resource: &mut Value) -> Result<(), StorageError> {
+        let url_base = self
+            .attachments
+            .as_ref()
+            .and_then(|settings| settings.url_base.as_deref());
+        self.inflate_with(resource, url_base).await
+    }
+
+    /// Restores the content of a stored historical version. Attachments are
+    /// always put back inline, so the version reads as it was written.
+    pub async fn inflate_version(&self, resource: &mut Value) -> Result<(), StorageError> {
+        self.inflate_with(resource, None).await
+    }
+
+    async fn inflate_with(
+        &self,
+        resource: &mut Value,
+        url_base: Option<&str>,
+    ) -> Result<(), StorageError> {
+        if keeping_pointers() {
+            return Ok(());
+        }
+        if !is_binary(resource) {
+            return self.inflate_attachments(resource, url_base).await;
+        }
+        let Some(pointer) = blob_pointer(resource) else {
+            return Ok(());
+        };
+        let data = self.store.get(&pointer.key).await?.ok_or_else(|| {
+            StorageError::internal(format!(
+                "Content of Binary/{} is missing from the blob store ({})",
+                resource.get("id").and_then(Value::as_str).unwrap_or("?"),
+                pointer.key
+            ))
+        })?;
+        if let Some(obj) = resource.as_object_mut() {
+            remove_pointer(obj);
+            obj.insert("data".to_string(), Value::String(BASE64.encode(data)));
+        }
+        Ok(())
+    }
+
+    async fn inflate_attachments(
+        &self,
+        resource: &mut Value,
+        url_base: Option<&str>,
+    ) -> Result<(), StorageError> {
+        if let Some(url_base) = url_base {
+            attachments::resolve(resource, &mut |pointer, attachment| {
+                link_attachment(attachment, pointer, url_base);
+            });
+            return Ok(());
+        }
+
+        let pointers = attachments::pointers(resource);
+        if pointers.is_empty() {
+            return Ok(());
+        }
+        let mut contents = HashMap::with_capacity(pointers.len());
+        for pointer in pointers {
+            if contents.contains_key(&pointer.key) {
+                continue;
+            }
+            let data = self.store.get(&pointer.key).await?.ok_or_else(|| {
+                StorageError::internal(format!(
+                    "Attachment content of {}/{} is missing from the blob store ({})",
+                    resource
+                        .get("resourceType")
+                        .and_then(Value::as_str)
+                        .unwrap_or("?"),
+                    resource.get("id").and_then(Value::as_str).unwrap_or("?"),
+                    pointer.key
+                ))
+            })?;
+            contents.insert(pointer.key, BASE64.encode(data));
+        }
+        attachments::resolve(resource, &mut |pointer, attachment| {
+            if let Some(data) = contents.get(&pointer.key) {
+                attachment.insert("data".to_string(), Value::String(data.clone()));
+            }
+        });
+        Ok(())
+    }
+
+    /// [`Self::inflate`] for a raw JSON resource.
+    pub async fn inflate_raw(&self, raw: &mut RawStoredResource) -> Result<(), StorageError> {
+        if !raw.resource_json.contains(BINARY_BLOB_EXTENSION) {
+            return Ok(());
+        }
+        let mut resource = parse_raw(raw)?;
+        self.inflate(&mut resource).await?;
+        write_raw(raw, &resource)
+    }
+
+    /// [`Self::inflate_version`] for a raw JSON resource.
+    pub async fn inflate_version_raw(
+        &self,
+        raw: &mut RawStoredResource,
+    ) -> Result<(), StorageError> {
+        if !raw.resource_json.contains(BINARY_BLOB_EXTENSION) {
+            return Ok(());
+        }
+        let mut resource = parse_raw(raw)?;
+        self.inflate_version(&mut resource).await?;
+        write_raw(raw, &resource)
+    }
+
+    /// Puts the content of `original` back into the resource returned by a
+    /// write of its offloaded form, so callers see what they wrote (or, for
+    /// attachments read by link, what a read would return).
+    fn restore(&self, stored: &mut Value, original: &Value) {
+        if keeping_pointers() {
+            return;
+        }
+        if !is_binary(stored) {
+            let url_base = self
+                .attachments
+                .as_ref()
+                .and_then(|settings| settings.url_base.as_deref());
+            attachments::resolve_from(stored, original, &mut |pointer, attachment, written| {
+                match url_base {
+                    Some(url_base) => link_attachment(attachment, pointer, url_base),
+                    None => {
+                        if let Some(data) = written.get("data") {
+                            attachment.insert("data".to_string(), data.clone());
+                        }
+                    }
+                }
+            });
+            return;
+        }
+        if let Some(obj) = stored.as_object_mut() {
+            remove_pointer(obj);
+            if let Some(data) = original.get("data") {
+                obj.insert("data".to_string(), data.clone());
+            }
+        }
+    }
+
+    fn restore_raw(
+        &self,
+        raw: &mut RawStoredResource,
+        original: &Value,
+    ) -> Result<(), StorageError> {
+        if keeping_pointers() {
+            return Ok(());
+        }
+        let mut resource = parse_raw(raw)?;
+        self.restore(&mut resource, original);
+        write_raw(raw, &resource)
+    }
+}
+
+/// Points an externalized attachment at the server's copy of its content,
+/// unless the client gave it a `url` of its own.
+fn link_attachment(attachment: &mut Map<String, Value>, pointer: &BlobPointer, url_base: &str) {
+    attachment
+        .entry("url")
+        .or_insert_with(|| Value::String(format!("{url_base}/{}", key_hash(&pointer.key))));
+}
+
+fn parse_raw(raw: &RawStoredResource) -> Result<Value, StorageError> {
+    serde_json::from_str(&raw.resource_json).map_err(|e| {
+        StorageError::internal(format!(
+            "Stored {}/{} is not valid JSON: {e}",
+            raw.resource_type, raw.id
+        ))
+    })
+}
+
+fn write_raw(raw: &mut RawStoredResource, resource: &Value) -> Result<(), StorageError> {
+    raw.resource_json = serde_json::to_string(resource).map_err(|e| {
+        StorageError::internal(format!(
+            "Failed to serialize {}/{}: {e}",
+            raw.resource_type, raw.id
+        ))
+    })?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn blobs(dir: &std::path::Path, threshold_bytes: usize) -> BinaryBlobs {
+        BinaryBlobs::new(Arc::new(FilesystemBlobStore::new(dir)), threshold_bytes)
+    }
+
+    #[tokio::test]
+    async fn test_offload_and_inflate_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let blobs = blobs(dir.path(), 4);
+        let original = json!({
+            "resourceType": "Binary",
+            "id": "b1",
+            "contentType": "text/plain",
+            "data": BASE64.encode(b"hello world")
+        });
+
+        let mut stored = blobs.offload(&original).await.unwrap().expect("offloaded");
+        assert!(stored.get("data").is_none());
+        let pointer = blob_pointer(&stored).expect("pointer");
+        assert_eq!(pointer.size, 11);
+        assert!(pointer.key.starts_with("default/"));
+
+        blobs.inflate(&mut stored).await.unwrap();
+        assert_eq!(stored, original);
+    }
+
+    #[test]
+    fn test_is_blob_key() {
+        let hash = hex::encode(Sha256::digest(b"x"));
+        assert!(is_blob_key(&blob_key(b"x")));
+        assert!(is_blob_key(&format!("tenant/t1/{}/{hash}", &hash[..2])));
+        assert!(!is_blob_key(&format!("tenant/t1/zz/{hash}")));
+        assert!(!is_blob_key(&format!("other/{}/{hash}", &hash[..2])));
+        assert!(!is_blob_key("default/ab/abcdef"));
+    }
+
+    #[tokio::test]
+    async fn test_small_content_stays_inline() {
+        let dir = tempfile::tempdir().unwrap();
+        let blobs = blobs(dir.path(), 1024);
+        let resource = json!({
+            "resourceType": "Binary",
+            "contentType": "text/plain",
+            "data": BASE64.encode(b"tiny")
+        });
+        assert!(blobs.offload(&resource).await.unwrap().is_none());
+        assert!(
+            blobs
+                .offload(&json!({"resourceType": "Patient"}))
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_client_pointer_is_dropped() {
+        let dir = tempfile::tempdir().unwrap();
+        let blobs = blobs(dir.path(), 1024);
+        let mut resource = json!({"resourceType": "Binary", "contentType": "text/plain"});
+        set_pointer(
+            resource.as_object_mut().unwrap(),
+            &BlobPointer {
+                key: "tenant/other/ab/abcd".to_string(),
+                size: 10,
+            },
+        );
+        let stored = blobs.offload(&resource).await.unwrap().expect("rewritten");
+        assert!(blob_pointer(&stored).is_none());
+        assert!(stored.get("_data").is_none());
+    }
+
+    fn document_reference(data: &[u8]) -> Value {
+        json!({
+            "resourceType": "DocumentReference",
+            "id": "d1",
+            "content": [{"attachment": {
+                "contentType": "application/pdf",
+                "data": BASE64.encode(data)
+            }}],
+            "extension": [{
+                "url": "http://example.org/signature",
+                "valueSignature": {"type": [], "when": "2024-01-01T00:00:00Z", "data": BASE64.encode(data)}
+            }]
+        })
+    }
+
+    #[tokio::test]
+    async fn test_attachment_offload_and_inflate() {
+        let dir = tempfile::tempdir().unwrap();
+        let blobs = blobs(dir.path(), usize::MAX).with_attachments(4, None);
+        let original = document_reference(b"%PDF-1.7 document");
+
+        let mut stored = blobs.offload(&original).await.unwrap().expect("offloaded");
+        let attachment = &stored["content"][0]["attachment"];
+        assert!(attachment.get("data").is_none());
+        assert_eq!(blob_pointer(attachment).expect("pointer").size, 17);
+        // Signature.data is not an attachment and stays inline.
+        assert!(
+            stored["extension"][0]["valueSignature"]
+                .get("data")
+                .is_some()
+        );
+
+        let mut written = stored.clone();
+        blobs.restore(&mut written, &original);
+        assert_eq!(written, original);
+
+        blobs.inflate(&mut stored).await.unwrap();
+        assert_eq!(stored, original);
+    }
+
+    #[tokio::test]
+    async fn test_attachment_redirect_on_read_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let blobs = blobs(dir.path(), usize::MAX)
+            .with_attachments(4, Some("http://fhir.test/fhir/_attachments".to_string()));
+        let original = document_reference(b"%PDF-1.7 document");
+        let stored = blobs.offload(&original).await.unwrap().expect("offloaded");
+
+        let mut current = stored.clone();
+        blobs.inflate(&mut current).await.unwrap();
+        let attachment = &current["content"][0]["attachment"];
+        assert!(attachment.get("data").is_none());
+        assert!(attachment.get("_data").is_none());
+        let url = attachment["url"].as_str().unwrap();
+        let hash = url
+            .strip_prefix("http://fhir.test/fhir/_attachments/")
+            .unwrap();
+        assert!(is_content_hash(hash));
+
+        let mut version = stored.clone();
+        blobs.inflate_version(&mut version).await.unwrap();
+        assert_eq!(version, original);
+    }
+
+    #[tokio::test]
+    async fn test_raw_search_entry_attachment_follows_on_read() {
+        let dir = tempfile::tempdir().unwrap();
+        let blobs = blobs(dir.path(), usize::MAX)
+            .with_attachments(4, Some("http://fhir.test/fhir/_attachments".to_string()));
+        let stored = blobs
+            .offload(&document_reference(b"%PDF-1.7 document"))
+            .await
+            .unwrap()
+            .expect("offloaded");
+        let now = time::OffsetDateTime::now_utc();
+        let mut raw = RawStoredResource {
+            id: "d1".to_string(),
+            version_id: "1".to_string(),
+            resource_type: "DocumentReference".to_string(),
+            resource_json: stored.to_string(),
+            last_updated: now,
+            created_at: now,
+        };
+
+        blobs.inflate_raw(&mut raw).await.unwrap();
+        let entry: Value = serde_json::from_str(&raw.resource_json).unwrap();
+        let attachment = &entry["content"][0]["attachment"];
+        assert!(attachment.get("_data").is_none());
+        assert!(
+            attachment["url"]
+                .as_str()
+                .unwrap()
+                .starts_with("http://fhir.test/fhir/_attachments/")
+        );
+    }
+}
@@ -0,0 +1,392 @@
+//! BinaryBlobStorage - A storage wrapper that keeps large Binary content in a
+//! blob store.
+//!
+//! Writes go through [`BinaryBlobs::offload`] before reaching the inner
+//! storage; reads go through [`BinaryBlobs::inflate`] after, or
+//! [`BinaryBlobs::inflate_version`] for history and vread. Resources without
+//! Binary content or externalized attachments pass through unchanged.
+//! Deletes ask for a blob sweep once they are committed.
+
+use std::collections::HashSet;
+
+use async_trait::async_trait;
+use octofhir_storage::{
+    FhirStorage, HistoryParams, HistoryResult, RawHistoryResult, RawStoredResource, SearchParams,
+    SearchResult, StorageError, StoredResource, Transaction,
+};
+use serde_json::Value;
+
+use super::BinaryBlobs;
+
+/// A storage wrapper that moves large Binary content to a blob store.
+pub struct BinaryBlobStorage<S: FhirStorage> {
+    /// The inner storage implementation.
+    inner: S,
+    blobs: BinaryBlobs,
+}
+
+impl<S: FhirStorage> BinaryBlobStorage<S> {
+    /// Create a new wrapper writing Binary content through `blobs`.
+    pub fn new(inner: S, blobs: BinaryBlobs) -> Self {
+        Self { inner, blobs }
+    }
+
+    /// Get a reference to the inner storage.
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+}
+
+async fn inflate_stored(
+    blobs: &BinaryBlobs,
+    stored: &mut StoredResource,
+) -> Result<(), StorageError> {
+    blobs.inflate(&mut stored.resource).await
+}
+
+async fn inflate_search(
+    blobs: &BinaryBlobs,
+    result: &mut SearchResult,
+) -> Result<(), StorageError> {
+    for entry in &mut result.entries {
+        inflate_stored(blobs, entry).await?;
+    }
+    Ok(())
+}
+
+#[async_trait]
+impl<S: FhirStorage> FhirStorage for BinaryBlobStorage<S> {
+    async fn create(&self, resource: &Value) -> Result<StoredResource, StorageError> {
+        match self.blobs.offload(resource).await? {
+            Some(offloaded) => {
+                let mut stored = self.inner.create(&offloaded).await?;
+                self.blobs.restore(&mut stored.resource, resource);
+                Ok(stored)
+            }
+            None => self.inner.create(resource).await,
+        }
+    }
+
+    async fn create_raw(&self, resource: &Value) -> Result<RawStoredResource, StorageError> {
+        match self.blobs.offload(resource).await? {
+            Some(offloaded) => {
+                let mut stored = self.inner.create_raw(&offloaded).await?;
+                self.blobs.restore_raw(&mut stored, resource)?;
+                Ok(stored)
+            }
+            None => self.inner.create_raw(resource).await,
+        }
+    }
+
+    async fn read(
+        &self,
+        resource_type: &str,
+        id: &str,
+    ) -> Result<Option<StoredResource>, StorageError> {
+        let mut stored = self.inner.read(resource_type, id).await?;
+        if let Some(stored) = &mut stored {
+            inflate_stored(&self.blobs, stored).await?;
+        }
+        Ok(stored)
+    }
+
+    async fn exists(&self, resource_type: &str, id: &str) -> Result<bool, StorageError> {
+        self.inner.exists(resource_type, id).await
+    }
+
+    async fn exists_many(
+        &self,
+        resource_type: &str,
+        ids: &[String],
+    ) -> Result<HashSet<String>, StorageError> {
+        self.inner.exists_many(resource_type, ids).await
+    }
+
+    async fn exists_many_grouped(
+        &self,
+        groups: &[(String, Vec<String>)],
+    ) -> Result<HashSet<String>, StorageError> {
+        self.inner.exists_many_grouped(groups).await
+    }
+
+    async fn read_many(
+        &self,
+        resource_type: &str,
+        ids: &[String],
+    ) -> Result<Vec<StoredResource>, StorageError> {
+        let mut stored = self.inner.read_many(resource_type, ids).await?;
+        for resource in &mut stored {
+            inflate_stored(&self.blobs, resource).await?;
+        }
+        Ok(stored)
+    }
+
+    async fn read_raw(
+        &self,
+        resource_type: &str,
+        id: &str,
+    ) -> Result<Option<RawStoredResource>, StorageError> {
+        let mut stored = self.inner.read_raw(resource_type, id).await?;
+        if let Some(stored) = &mut stored {
+            self.blobs.inflate_raw(stored).await?;
+        }
+        Ok(stored)
+    }
+
+    async fn update(
+        &self,
+        resource: &Value,
+        if_match: Option<&str>,
+    ) -> Result<StoredResource, StorageError> {
+        match self.blobs.offload(resource).await? {
+            Some(offloaded) => {
+                let mut stored = self.inner.update(&offloaded, if_match).await?;
+                self.blobs.restore(&mut stored.resource, resource);
+                Ok(stored)
+            }
+            None => self.inner.update(resource, if_match).await,
+        }
+    }
+
+    async fn update_raw(
+        &self,
+        resource: &Value,
+        if_match: Option<&str>,
+    ) -> Result<RawStoredResource, StorageError> {
+        match self.blobs.offload(resource).await? {
+            Some(offloaded) => {
+                let mut stored = self.inner.update_raw(&offloaded, if_match).await?;
+                self.blobs.restore_raw(&mut stored, resource)?;
+                Ok(stored)
+            }
+            None => self.inner.update_raw(resource, if_match).await,
+        }
+    }
+
+    async fn delete(&self, resource_type: &str, id: &str) -> Result<(), StorageError> {
+        self.inner.delete(resource_type, id).await?;
+        self.blobs.request_sweep();
+        Ok(())
+    }
+
+    async fn vread(
+        &self,
+        resource_type: &str,
+        id: &str,
+        version: &str,
+    ) -> Result<Option<StoredResource>, StorageError> {
+        let mut stored = self.inner.vread(resource_type, id, version).await?;
+        if let Some(stored) = &mut stored {
+            self.blobs.inflate_version(&mut stored.resource).await?;
+        }
+        Ok(stored)
+    }
+
+    async fn vread_raw(
+        &self,
+        resource_type: &str,
+        id: &str,
+        version: &str,
+    ) -> Result<Option<RawStoredResource>, StorageError> {
+        let mut stored = self.inner.vread_raw(resource_type, id, version).await?;
+        if let Some(stored) = &mut stored {
+            self.blobs.inflate_version_raw(stored).await?;
+        }
+        Ok(stored)
+    }
+
+    async fn history(
+        &self,
+        resource_type: &str,
+        id: Option<&str>,
+        params: &HistoryParams,
+    ) -> Result<HistoryResult, StorageError> {
+        let mut result = self.inner.history(resource_type, id, params).await?;
+        for entry in &mut result.entries {
+            self.blobs
+                .inflate_version(&mut entry.resource.resource)
+                .await?;
+        }
+        Ok(result)
+    }
+
+    async fn system_history(&self, params: &HistoryParams) -> Result<HistoryResult, StorageError> {
+        let mut result = self.inner.system_history(params).await?;
+        for entry in &mut result.entries {
+            self.blobs
+                .inflate_version(&mut entry.resource.resource)
+                .await?;
+        }
+        Ok(result)
+    }
+
+    async fn history_raw(
+        &self,
+        resource_type: &str,
+        id: Option<&str>,
+        params: &HistoryParams,
+    ) -> Result<RawHistoryResult, StorageError> {
+        let mut result = self.inner.history_raw(resource_type, id, params).await?;
+        for entry in &mut result.entries {
+            self.blobs.inflate_version_raw(&mut entry.resource).await?;
+        }
+        Ok(result)
+    }
+
+    async fn system_history_raw(
+        &self,
+        params: &HistoryParams,
+    ) -> Result<RawHistoryResult, StorageError> {
+        let mut result = self.inner.system_history_raw(params).await?;
+        for entry in &mut result.entries {
+            self.blobs.inflate_version_raw(&mut entry.resource).await?;
+        }
+        Ok(result)
+    }
+
+    async fn search(
+        &self,
+        resource_type: &str,
+        params: &SearchParams,
+    ) -> Result<SearchResult, StorageError> {
+        let mut result = self.inner.search(resource_type, params).await?;
+        inflate_search(&self.blobs, &mut result).await?;
+        Ok(result)
+    }
+
+    async fn begin_transaction(&self) -> Result<Box<dyn Transaction>, StorageError> {
+        let inner = self.inner.begin_transaction().await?;
+        Ok(Box::new(BinaryBlobTransaction {
+            inner,
+            blobs: self.blobs.clone(),
+            deleted: false,
+        }))
+    }
+
+    fn supports_transactions(&self) -> bool {
+        self.inner.supports_transactions()
+    }
+
+    fn backend_name(&self) -> &'static str {
+        self.inner.backend_name()
+    }
+}
+
+impl<S: FhirStorage> std::fmt::Debug for BinaryBlobStorage<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BinaryBlobStorage")
+            .field("backend", &self.inner.backend_name())
+            .field("blobs", &self.blobs)
+            .finish()
+    }
+}
+
+/// Transaction counterpart of [`BinaryBlobStorage`].
+///
+/// Blobs are written as the transaction runs. A rollback leaves them behind
+/// unreferenced; since keys are content hashes, a retry reuses them, and
+/// otherwise a later sweep deletes them.
+struct BinaryBlobTransaction {
+    inner: Box<dyn Transaction>,
+    blobs: BinaryBlobs,
+    /// Whether the transaction deleted a resource
+    deleted: bool,
+}
+
+#[async_trait]
+impl Transaction for BinaryBlobTransaction {
+    async fn commit(self: Box<Self>) -> Result<(), StorageError> {
+        let Self {
+            inner,
+            blobs,
+            deleted,
+        } = *self;
+        inner.commit().await?;
+        if deleted {
+            blobs.request_sweep();
+        }
+        Ok(())
+    }
+
+    async fn rollback(self: Box<Self>) -> Result<(), StorageError> {
+        self.inner.rollback().await
+    }
+
+    async fn create(&mut self, resource: &Value) -> Result<StoredResource, StorageError> {
+        match self.blobs.offload(resource).await? {
+            Some(offloaded) => {
+                let mut stored = self.inner.create(&offloaded).await?;
+                self.blobs.restore(&mut stored.resource, resource);
+                Ok(stored)
+            }
+            None => self.inner.create(resource).await,
+        }
+    }
+
+    async fn update(
+        &mut self,
+        resource: &Value,
+        if_match: Option<&str>,
+    ) -> Result<StoredResource, StorageError> {
+        match self.blobs.offload(resource).await? {
+            Some(offloaded) => {
+                let mut stored = self.inner.update(&offloaded, if_match).await?;
+                self.blobs.restore(&mut stored.resource, resource);
+                Ok(stored)
+            }
+            None => self.inner.update(resource, if_match).await,
+        }
+    }
+
+    async fn delete(&mut self, resource_type: &str, id: &str) -> Result<(), StorageError> {
+        self.inner.delete(resource_type, id).await?;
+        self.deleted = true;
+        Ok(())
+    }
+
+    async fn read(
+        &self,
+        resource_type: &str,
+        id: &str,
+    ) -> Result<Option<StoredResource>, StorageError> {
+        let mut stored = self.inner.read(resource_type, id).await?;
+        if let Some(stored) = &mut stored {
+            inflate_stored(&self.blobs, stored).await?;
+        }
+        Ok(stored)
+    }
+
+    async fn search(
+        &self,
+        resource_type: &str,
+        params: &SearchParams,
+    ) -> Result<SearchResult, StorageError> {
+        let mut result = self.inner.search(resource_type, params).await?;
+        inflate_search(&self.blobs, &mut result).await?;
+        Ok(result)
+    }
+
+    async fn create_batch(
+        &mut self,
+        resource_type: &str,
+        resources: &[Value],
+    ) -> Result<Vec<StoredResource>, StorageError> {
+        let mut offloaded = Vec::with_capacity(resources.len());
+        for resource in resources {
+            offloaded.push(self.blobs.offload(resource).await?);
+        }
+        if offloaded.iter().all(Option::is_none) {
+            return self.inner.create_batch(resource_type, resources).await;
+        }
+        let offloaded: Vec<Value> = offloaded
+            .into_iter()
+            .zip(resources)
+            .map(|(offloaded, resource)| offloaded.unwrap_or_else(|| resource.clone()))
+            .collect();
+        let mut stored = self.inner.create_batch(resource_type, &offloaded).await?;
+        for (stored, original) in stored.iter_mut().zip(resources) {
+            self.blobs.restore(&mut stored.resource, original);
+        }
+        Ok(stored)
+    }
+}
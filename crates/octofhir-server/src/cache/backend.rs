@@ -96,7 +96,7 @@ impl CacheBackend {
                 if result.is_some() {
                     crate::metrics::record_cache_hit("L1");
                 } else {
-                    crate::metrics::record_cache_miss();
+                    crate::metrics::record_cache_miss("L1");
                 }
 
                 result
@@ -131,18 +131,18 @@ impl CacheBackend {
                         }
                         Ok(None) => {
                             tracing::debug!(key = %key, "cache miss");
-                            crate::metrics::record_cache_miss();
+                            crate::metrics::record_cache_miss("L2");
                             None
                         }
                         Err(e) => {
                             tracing::warn!(key = %key, error = %e, "Redis GET error");
-                            crate::metrics::record_cache_miss();
+                            crate::metrics::record_cache_miss("L2");
                             None
                         }
                     },
                     Err(e) => {
                         tracing::warn!(error = %e, "Failed to get Redis connection");
-                        crate::metrics::record_cache_miss();
+                        crate::metrics::record_cache_miss("L2");
                         None
                     }
                 }
@@ -229,6 +229,70 @@ impl CacheBackend {
         }
     }
 
+    /// Increment a counter, creating it with `ttl` on first use, and return
+    /// the new value.
+    ///
+    /// Counters bypass L1 in Redis mode so every instance sees the same
+    /// count. If Redis is unreachable the local counter is used instead.
+    pub async fn increment(&self, key: &str, ttl: Duration) -> u64 {
+        match self {
+            CacheBackend::Local(map) => increment_local(map, key, ttl),
+            CacheBackend::Redis { redis, local } => {
+                let result = match redis.get().await {
+                    Ok(mut conn) => {
+                        let result = redis::pipe()
+                            .atomic()
+                            .incr(key, 1u64)
+                            .expire(key, ttl.as_secs() as i64)
+                            .ignore()
+                            .query_async::<(u64,)>(&mut conn)
+                            .await;
+                        result.map(|(count,)| count).map_err(|e| e.to_string())
+                    }
+                    Err(e) => Err(e.to_string()),
+                };
+                result.unwrap_or_else(|e| {
+                    tracing::warn!(key = %key, error = %e, "Redis INCR error, counting locally");
+                    increment_local(local, key, ttl)
+                })
+            }
+        }
+    }
+
+    /// Current value of a counter written by [`Self::increment`], 0 if unset.
+    pub async fn counter(&self, key: &str) -> u64 {
+        match self {
+            CacheBackend::Local(map) => counter_local(map, key),
+            CacheBackend::Redis { redis, local } => {
+                let result = match redis.get().await {
+                    Ok(mut conn) => conn
+                        .get::<_, Option<u64>>(key)
+                        .await
+                        .map_err(|e| e.to_string()),
+                    Err(e) => Err(e.to_string()),
+                };
+                match result {
+                    Ok(count) => count.unwrap_or(0),
+                    Err(e) => {
+                        tracing::warn!(key = %key, error = %e, "Redis GET error, reading local counter");
+                        counter_local(local, key)
+                    }
+                }
+            }
+        }
+    }
+
+    /// Drop expired entries from L1 and return how many were removed.
+    pub fn prune_expired(&self) -> usize {
+        let map = match self {
+            CacheBackend::Local(map) => map,
+            CacheBackend::Redis { local, .. } => local,
+        };
+        let before = map.len();
+        map.retain(|_, entry| !entry.is_expired());
+        before.saturating_sub(map.len())
+    }
+
     /// Get cache statistics (L1 only).
     pub fn stats(&self) -> CacheStats {
         match self {
@@ -260,6 +324,33 @@ impl CacheBackend {
     }
 }
 
+/// Local counters are stored as decimal text, like Redis stores them.
+fn increment_local(map: &DashMap<String, CachedEntry>, key: &str, ttl: Duration) -> u64 {
+    let mut entry = map
+        .entry(key.to_string())
+        .or_insert_with(|| CachedEntry::new(b"0".to_vec(), ttl));
+    if entry.is_expired() {
+        *entry = CachedEntry::new(b"0".to_vec(), ttl);
+    }
+    let count = parse_counter(&entry.data) + 1;
+    entry.data = Arc::new(count.to_string().into_bytes());
+    count
+}
+
+fn counter_local(map: &DashMap<String, CachedEntry>, key: &str) -> u64 {
+    map.get(key)
+        .filter(|entry| !entry.is_expired())
+        .map(|entry| parse_counter(&entry.data))
+        .unwrap_or(0)
+}
+
+fn parse_counter(data: &[u8]) -> u64 {
+    std::str::from_utf8(data)
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(0)
+}
+
 /// Cache statistics.
 #[derive(Debug, Clone)]
 pub struct CacheStats {
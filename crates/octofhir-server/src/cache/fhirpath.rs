@@ -0,0 +1,128 @@
+//! Compiled FHIRPath expression cache.
+//!
+//! Subscription filters, gateway expressions and FHIRPath Patch evaluate the same expressions over and over. Parsing them is cheap
+//! but not free, and the engine's own AST cache is small and keeps no
+//! statistics. [`FhirPathCache`] is a process-wide cache of parsed
+//! expressions shared by all of them, reporting hits, misses and entries
+//! under the `fhirpath` tier of the cache metrics.
+//!
+//! [`FhirPathCache::warm_up`] compiles the criteria and filters of
+//! subscription topics as they are loaded, so the first events after a deploy
+//! do not pay for compiling them. Only expressions evaluated through this
+//! cache are warmed: search parameter expressions are turned into SQL and
+//! never evaluated here.
+
+use std::sync::{Arc, LazyLock};
+
+use moka::future::Cache;
+use octofhir_fhirpath::{
+    EvaluationContext, EvaluationResult, ExpressionNode, FhirPathEngine, parse_ast,
+};
+
+/// Maximum number of compiled expressions kept.
+const MAX_ENTRIES: u64 = 4096;
+
+static FHIRPATH_CACHE: LazyLock<FhirPathCache> = LazyLock::new(|| FhirPathCache::new(MAX_ENTRIES));
+
+/// The process-wide compiled expression cache.
+pub fn fhirpath_cache() -> &'static FhirPathCache {
+    &FHIRPATH_CACHE
+}
+
+/// Cache of parsed FHIRPath expressions, keyed by expression text.
+pub struct FhirPathCache {
+    cache: Cache<String, Arc<ExpressionNode>>,
+}
+
+impl FhirPathCache {
+    /// Create a cache bounded to `max_capacity` expressions.
+    pub fn new(max_capacity: u64) -> Self {
+        Self {
+            cache: Cache::builder().max_capacity(max_capacity).build(),
+        }
+    }
+
+    /// Get the compiled form of `expression`, parsing it on a miss.
+    ///
+    /// Expressions that fail to parse are not cached.
+    pub async fn compile(
+        &self,
+        expression: &str,
+    ) -> octofhir_fhirpath::Result<Arc<ExpressionNode>> {
+        if let Some(ast) = self.cache.get(expression).await {
+            crate::metrics::record_cache_hit("fhirpath");
+            return Ok(ast);
+        }
+        crate::metrics::record_cache_miss("fhirpath");
+        let ast = Arc::new(parse_ast(expression)?);
+        self.cache.insert(expression.to_string(), ast.clone()).await;
+        Ok(ast)
+    }
+
+    /// Evaluate `expression` with `engine`, compiling it through the cache.
+    pub async fn evaluate(
+        &self,
+        engine: &FhirPathEngine,
+        expression: &str,
+        context: &EvaluationContext,
+    ) -> octofhir_fhirpath::Result<EvaluationResult> {
+        let ast = self.compile(expression).await?;
+        engine.evaluate_ast(&ast, context).await
+    }
+
+    /// Compile `expressions` ahead of their first use. Returns how many were
+    /// compiled; expressions that are already cached or fail to parse are
+    /// skipped.
+    pub async fn warm_up<I, S>(&self, expressions: I) -> usize
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut compiled = 0;
+        for expression in expressions {
+            let expression = expression.as_ref();
+            if self.cache.contains_key(expression) {
+                continue;
+            }
+            match parse_ast(expression) {
+                Ok(ast) => {
+                    self.cache
+                        .insert(expression.to_string(), Arc::new(ast))
+                        .await;
+                    compiled += 1;
+                }
+                Err(e) => {
+                    tracing::debug!(expression, error = %e, "FHIRPath warmup skipped expression");
+                }
+            }
+        }
+        crate::metrics::set_cache_entries("fhirpath", self.entry_count() as usize);
+        compiled
+    }
+
+    /// Current number of cached expressions.
+    pub fn entry_count(&self) -> u64 {
+        self.cache.entry_count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_compile_caches_valid_expressions() {
+        let cache = FhirPathCache::new(16);
+        let first = cache.compile("Patient.name.given").await.unwrap();
+        let second = cache.compile("Patient.name.given").await.unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+        assert!(cache.compile("Patient.name.(").await.is_err());
+
+        let compiled = cache
+            .warm_up(["Patient.name.given", "Observation.code", "((("])
+            .await;
+        assert_eq!(compiled, 1);
+        assert!(cache.cache.contains_key("Observation.code"));
+        assert!(!cache.cache.contains_key("((("));
+    }
+}
@@ -0,0 +1,118 @@
+//! Token introspection cache on the shared [`CacheBackend`].
+//!
+//! Results are stored under the token hash, so with Redis enabled every
+//! instance serves the result another instance computed. Revocation writes a
+//! tombstone key for the JTI that lives for one TTL; every hit checks it, so
+//! a revocation on one instance stops the result from being served by all of
+//! them, including from their L1 copies.
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use octofhir_auth::token::{
+    DEFAULT_INTROSPECTION_CACHE_TTL, IntrospectionCache, IntrospectionResponse, cache_ttl,
+};
+use octofhir_auth::types::refresh_token::RefreshToken;
+
+use super::backend::CacheBackend;
+
+/// Introspection cache backed by the server's shared cache.
+pub struct SharedIntrospectionCache {
+    backend: CacheBackend,
+    ttl: Duration,
+}
+
+impl SharedIntrospectionCache {
+    /// Creates a cache with the default TTL.
+    pub fn new(backend: CacheBackend) -> Self {
+        Self {
+            backend,
+            ttl: DEFAULT_INTROSPECTION_CACHE_TTL,
+        }
+    }
+
+    fn entry_key(token: &str) -> String {
+        format!("introspection:{}", RefreshToken::hash_token(token))
+    }
+
+    fn revoked_key(jti: &str) -> String {
+        format!("introspection:revoked:{jti}")
+    }
+
+    async fn is_revoked(&self, response: &IntrospectionResponse) -> bool {
+        match &response.jti {
+            Some(jti) => self.backend.get(&Self::revoked_key(jti)).await.is_some(),
+            None => false,
+        }
+    }
+}
+
+#[async_trait]
+impl IntrospectionCache for SharedIntrospectionCache {
+    async fn get(&self, token: &str) -> Option<IntrospectionResponse> {
+        let data = self.backend.get(&Self::entry_key(token)).await?;
+        let response: IntrospectionResponse = serde_json::from_slice(&data).ok()?;
+        if self.is_revoked(&response).await {
+            return None;
+        }
+        Some(response)
+    }
+
+    async fn insert(&self, token: &str, response: &IntrospectionResponse) {
+        let Some(ttl) = cache_ttl(response, self.ttl) else {
+            return;
+        };
+        // Redis expiries have whole-second resolution
+        if ttl < Duration::from_secs(1) || self.is_revoked(response).await {
+            return;
+        }
+        let Ok(data) = serde_json::to_vec(response) else {
+            return;
+        };
+        self.backend.set(&Self::entry_key(token), data, ttl).await;
+    }
+
+    async fn invalidate_jti(&self, jti: &str) {
+        self.backend
+            .set(&Self::revoked_key(jti), Vec::new(), self.ttl)
+            .await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn active(jti: &str) -> IntrospectionResponse {
+        IntrospectionResponse::active()
+            .with_jti(jti.to_string())
+            .with_exp(time::OffsetDateTime::now_utc().unix_timestamp() + 3600)
+    }
+
+    #[tokio::test]
+    async fn test_shared_cache_round_trip() {
+        let cache = SharedIntrospectionCache::new(CacheBackend::new_local());
+        cache.insert("token-a", &active("jti-a")).await;
+
+        let cached = cache.get("token-a").await.expect("result should be cached");
+        assert!(cached.active);
+        assert_eq!(cached.jti.as_deref(), Some("jti-a"));
+        assert!(cache.get("token-b").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_shared_cache_revocation_is_seen_through_other_handles() {
+        let backend = CacheBackend::new_local();
+        let cache = SharedIntrospectionCache::new(backend.clone());
+        cache.insert("token-a", &active("jti-a")).await;
+
+        SharedIntrospectionCache::new(backend)
+            .invalidate_jti("jti-a")
+            .await;
+        assert!(cache.get("token-a").await.is_none());
+
+        // A lookup that raced the revocation cannot re-populate the entry
+        cache.insert("token-b", &active("jti-a")).await;
+        assert!(cache.get("token-b").await.is_none());
+    }
+}
@@ -2,10 +2,17 @@
 //!
 //! Caches raw FHIR resources in-process to avoid database round-trips for reads.
 //! Backed by a bounded `moka` cache (size-capped + TTL) keyed by
-//! `res:{resource_type}:{id}`. Entries are stored as `Arc<RawStoredResource>`,
-//! so a hit is a refcount bump with no deserialization.
+//! `res:{resource_type}:{id}`, prefixed with the tenant inside a tenant scope.
+//! Entries are stored as `Arc<RawStoredResource>`, so a hit is a refcount bump
+//! with no deserialization. The cached version ID doubles as the ETag, so a
+//! matching `If-None-Match` is answered with `304` straight from the cache.
 //!
-//! Invalidated on create, update, and delete via [`ResourceCache::invalidate`].
+//! Invalidated on create, update, and delete via [`ResourceCache::invalidate`]:
+//! directly by the writing handler, batch and transaction bundles included,
+//! and by [`ResourceCacheHook`](crate::hooks::ResourceCacheHook) for every
+//! resource event, including events received from other instances over Redis.
+//! Writes inside a tenant scope emit no events, so for tenant entries the
+//! handlers' own invalidation is the one that counts.
 
 use std::sync::Arc;
 use std::time::Duration;
@@ -33,13 +40,22 @@ impl ResourceCache {
 
     #[inline]
     fn cache_key(resource_type: &str, id: &str) -> String {
-        format!("res:{resource_type}:{id}")
+        match octofhir_storage::current_tenant() {
+            Some(tenant) => format!("res:{tenant}:{resource_type}:{id}"),
+            None => format!("res:{resource_type}:{id}"),
+        }
     }
 
     /// Get a cached resource by type and ID.
     pub async fn get(&self, resource_type: &str, id: &str) -> Option<Arc<RawStoredResource>> {
         let key = Self::cache_key(resource_type, id);
-        self.cache.get(&key).await
+        let cached = self.cache.get(&key).await;
+        if cached.is_some() {
+            crate::metrics::record_cache_hit("resource");
+        } else {
+            crate::metrics::record_cache_miss("resource");
+        }
+        cached
     }
 
     /// Cache a resource after a successful read.
@@ -3,7 +3,7 @@ use std::sync::{Arc, OnceLock, RwLock};
 use crate::config::{AppConfig, PackageSpec};
 use arc_swap::ArcSwap;
 use octofhir_core::fhir::FhirVersion;
-use octofhir_db_postgres::PostgresPackageStore;
+use octofhir_db_postgres::{CanonicalVersionPolicy, PostgresPackageStore};
 use octofhir_fhirschema::types::StructureDefinition;
 use std::str::FromStr;
 
@@ -280,7 +280,10 @@ async fn build_registry_with_manager(cfg: &AppConfig) -> Result<CanonicalRegistr
         .map_err(|e| format!("failed to create FCM PostgreSQL pool: {e}"))?;
 
     // Create PostgresPackageStore (implements both PackageStore and SearchStorage)
-    let postgres_store = Arc::new(PostgresPackageStore::new(pg_pool));
+    let postgres_store = Arc::new(
+        PostgresPackageStore::new(pg_pool)
+            .with_version_policy(cfg.packages.canonical_version_policy),
+    );
 
     // Check which packages are already installed in the database
     let installed_packages: std::collections::HashSet<String> =
@@ -525,6 +528,8 @@ async fn build_registry_with_manager(cfg: &AppConfig) -> Result<CanonicalRegistr
         "schema conversion completed"
     );
 
+    report_version_conflicts(&postgres_store, cfg.packages.canonical_version_policy).await;
+
     // Load embedded packages: octofhir-auth and octofhir-app
     // These packages are split for better organization: auth resources vs app resources
     let fhir_version = &cfg.fhir.version;
@@ -729,6 +734,39 @@ async fn build_registry_with_manager(cfg: &AppConfig) -> Result<CanonicalRegistr
 }
 
 /// Creates a PostgreSQL connection pool for FCM storage.
+/// Logs canonical URLs loaded in more than one version, which resolve
+/// according to `packages.canonical_version_policy` when requested without
+/// a version.
+async fn report_version_conflicts(store: &PostgresPackageStore, policy: CanonicalVersionPolicy) {
+    let conflicts = match store.list_version_conflicts().await {
+        Ok(conflicts) => conflicts,
+        Err(e) => {
+            tracing::warn!(error = %e, "failed to list canonical version conflicts");
+            return;
+        }
+    };
+    if conflicts.is_empty() {
+        return;
+    }
+
+    for (url, versions) in &conflicts {
+        tracing::debug!(url = %url, versions = ?versions, "canonical URL loaded in several versions");
+    }
+    const SHOWN: usize = 10;
+    let examples = conflicts
+        .iter()
+        .take(SHOWN)
+        .map(|(url, versions)| format!("{url} ({})", versions.join(", ")))
+        .collect::<Vec<_>>()
+        .join("; ");
+    tracing::warn!(
+        count = conflicts.len(),
+        policy = ?policy,
+        examples = %examples,
+        "canonical URLs are loaded in several versions; unversioned references resolve by the canonical version policy"
+    );
+}
+
 async fn create_fcm_postgres_pool(
     pg_cfg: &crate::config::PostgresStorageConfig,
 ) -> Result<sqlx_postgres::PgPool, String> {
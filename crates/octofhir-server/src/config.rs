@@ -1,7 +1,7 @@
 use octofhir_auth::config::AuthConfig;
 use octofhir_search::TerminologyConfig;
 use serde::{Deserialize, Serialize};
-use std::{net::SocketAddr, time::Duration};
+use std::{collections::HashMap, net::SocketAddr, time::Duration};
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct AppConfig {
@@ -49,6 +49,15 @@ pub struct AppConfig {
     /// Audit trail configuration
     #[serde(default)]
     pub audit: AuditConfig,
+    /// Resource event hook delivery configuration
+    #[serde(default)]
+    pub events: EventsConfig,
+    /// Binary resource content storage (inline or external blob store)
+    #[serde(default)]
+    pub binary: BinaryConfig,
+    /// Scheduled pruning of old resource versions
+    #[serde(default)]
+    pub history_retention: HistoryRetentionConfig,
     /// SQL on FHIR configuration (ViewDefinition editor, $run/$export operations)
     #[serde(default)]
     pub sql_on_fhir: SqlOnFhirConfig,
@@ -58,6 +67,18 @@ pub struct AppConfig {
     /// Bulk import configuration ($import operation)
     #[serde(default)]
     pub bulk_import: BulkImportConfig,
+    /// Multi-tenancy configuration (per-tenant data isolation)
+    #[serde(default)]
+    pub multitenancy: MultitenancyConfig,
+    /// Per-client API rate limiting
+    #[serde(default)]
+    pub rate_limit: RateLimitConfig,
+    /// FHIR operation allow/deny lists
+    #[serde(default)]
+    pub operations: OperationsConfig,
+    /// Outbound HTTP client used by the gateway proxy and federation fetches
+    #[serde(default)]
+    pub http_client: octofhir_core::http::HttpClientConfig,
 }
 
 // Default derived via field defaults
@@ -71,6 +92,53 @@ impl AppConfig {
         if self.server.read_timeout_ms == 0 || self.server.write_timeout_ms == 0 {
             return Err("server timeouts must be > 0".into());
         }
+        crate::forwarded::TrustedProxies::parse(&self.server.trusted_proxies)
+            .map_err(|e| format!("server.trusted_proxies: {e}"))?;
+        for (key, names) in [
+            ("server.cors.allow_headers", &self.server.cors.allow_headers),
+            (
+                "server.cors.expose_headers",
+                &self.server.cors.expose_headers,
+            ),
+        ] {
+            if let Some(bad) = names
+                .iter()
+                .find(|n| axum::http::HeaderName::from_bytes(n.trim().as_bytes()).is_err())
+            {
+                return Err(format!("{key}: '{bad}' is not a valid header name"));
+            }
+        }
+        if fhir_release(&self.fhir.version).is_none() {
+            return Err(format!(
+                "fhir.version '{}' is not supported (expected R4, R4B, R5 or R6)",
+                self.fhir.version
+            ));
+        }
+        if let Some(rt) = self
+            .fhir
+            .resource_types
+            .iter()
+            .find(|rt| !octofhir_core::fhir::is_valid_resource_type_name(rt))
+        {
+            return Err(format!(
+                "fhir.resource_types: '{rt}' is not a resource type name"
+            ));
+        }
+        // Audit validation
+        let redact_elements = self.audit.redact_elements.iter().chain(
+            self.audit
+                .resource_overrides
+                .values()
+                .filter_map(|o| o.redact_elements.as_ref())
+                .flatten(),
+        );
+        for element in redact_elements {
+            if element.split('.').any(str::is_empty) {
+                return Err(format!(
+                    "audit.redact_elements: '{element}' is not a dotted element path"
+                ));
+            }
+        }
         // Search validations
         if self.search.default_count == 0 {
             return Err("search.default_count must be > 0".into());
@@ -112,12 +180,115 @@ impl AppConfig {
             if pg.pool_size == 0 {
                 return Err("storage.postgres.pool_size must be > 0".into());
             }
+            if pg.min_connections.is_some_and(|min| min > pg.pool_size) {
+                return Err("storage.postgres.min_connections must be <= pool_size".into());
+            }
         }
         // Auth validation (always required)
         self.auth
             .validate()
             .map_err(|e| format!("auth config error: {e}"))?;
 
+        // Multi-tenancy validation
+        if self.multitenancy.enabled {
+            if self.multitenancy.claim.is_empty() {
+                return Err("multitenancy.claim must not be empty".into());
+            }
+            if self.multitenancy.tenants.is_empty() {
+                return Err("multitenancy.enabled=true requires at least one tenant".into());
+            }
+            for tenant in &self.multitenancy.tenants {
+                octofhir_storage::TenantId::new(tenant.as_str())
+                    .map_err(|e| format!("multitenancy.tenants: {e}"))?;
+            }
+            // Index tables live in `public` only; a tenant's searches would
+            // read the shared table.
+            if !self.search.materialized_params.is_empty() {
+                return Err(
+                    "search.materialized_params is not supported with multitenancy.enabled=true"
+                        .into(),
+                );
+            }
+        }
+
+        // Rate limit validation
+        if self.rate_limit.requests_per_window > 0 && self.rate_limit.window_secs == 0 {
+            return Err("rate_limit.window_secs must be > 0".into());
+        }
+
+        // Event outbox validation
+        if self.events.outbox_poll_interval_ms == 0 {
+            return Err("events.outbox_poll_interval_ms must be > 0".into());
+        }
+        if self.events.outbox_batch_size == 0 {
+            return Err("events.outbox_batch_size must be > 0".into());
+        }
+
+        // Binary blob store validation
+        match self.binary.backend {
+            BinaryBackend::Inline => {}
+            BinaryBackend::Filesystem => {
+                if self.binary.filesystem.path.trim().is_empty() {
+                    return Err("binary.filesystem.path cannot be empty".into());
+                }
+            }
+            BinaryBackend::S3 => {
+                if self.binary.s3.bucket.trim().is_empty() {
+                    return Err("binary.backend = \"s3\" requires binary.s3.bucket".into());
+                }
+                if self.binary.s3.region.trim().is_empty() {
+                    return Err("binary.s3.region cannot be empty".into());
+                }
+                url::Url::parse(&self.binary.s3.endpoint)
+                    .map_err(|e| format!("binary.s3.endpoint: {e}"))?;
+            }
+        }
+        // History retention validation
+        if self.history_retention.enabled {
+            if self.history_retention.interval_secs == 0 {
+                return Err("history_retention.interval_secs must be > 0".into());
+            }
+            if self.history_retention.batch_size == 0 {
+                return Err("history_retention.batch_size must be > 0".into());
+            }
+        }
+        let default_rule = HistoryRetentionRule {
+            keep_versions: self.history_retention.keep_versions,
+            max_age_days: self.history_retention.max_age_days,
+        };
+        let retention_rules = std::iter::once(("default", &default_rule)).chain(
+            self.history_retention
+                .resource_types
+                .iter()
+                .map(|(rt, rule)| (rt.as_str(), rule)),
+        );
+        for (scope, rule) in retention_rules {
+            if rule.keep_versions == Some(0) {
+                return Err(format!(
+                    "history_retention ({scope}): keep_versions must be > 0"
+                ));
+            }
+            if rule.max_age_days == Some(0) {
+                return Err(format!(
+                    "history_retention ({scope}): max_age_days must be > 0"
+                ));
+            }
+        }
+
+        if self.binary.attachments.enabled && self.binary.backend == BinaryBackend::Inline {
+            return Err(
+                "binary.attachments.enabled requires binary.backend = \"filesystem\" or \"s3\""
+                    .into(),
+            );
+        }
+
+        // Operation allow/deny list validation
+        for code in self.operations.allow.iter().chain(&self.operations.deny) {
+            if code.trim_start_matches('$').is_empty() {
+                return Err("operations.allow/deny entries must not be empty".into());
+            }
+        }
+
         // Validate backend client config if provided
         if let Some(ref backend_client) = self.bootstrap.backend_client {
             if backend_client.client_id.is_empty() {
@@ -177,14 +348,65 @@ pub struct ServerConfig {
     /// If not set, defaults to http://{host}:{port}
     #[serde(default)]
     pub base_url: Option<String>,
+    /// Reverse proxies (IPs or CIDR ranges) whose `Forwarded` /
+    /// `X-Forwarded-Proto` / `X-Forwarded-Host` headers are trusted to set the
+    /// scheme and host of links. Requests from other peers always use
+    /// `base_url`. Env: `OCTOFHIR__SERVER__TRUSTED_PROXIES`.
+    /// Default: empty (forwarding headers are ignored)
+    #[serde(default)]
+    pub trusted_proxies: Vec<String>,
     #[serde(default = "default_read_timeout_ms")]
     pub read_timeout_ms: u32,
     #[serde(default = "default_write_timeout_ms")]
     pub write_timeout_ms: u32,
+    /// How long a request may run before it is answered with 504. The
+    /// request's database statements are canceled at the same moment.
+    /// `0` disables the limit.
+    /// Default: 30000
+    #[serde(default = "default_request_timeout_ms")]
+    pub request_timeout_ms: u64,
+    /// Timeout for operations (`$export`, `$everything`, ...) and batch or
+    /// transaction bundles, which legitimately run longer than single
+    /// interactions. `0` disables the limit.
+    /// Default: 300000
+    #[serde(default = "default_operation_timeout_ms")]
+    pub operation_timeout_ms: u64,
+    /// Cancel a request's running search statements when its client
+    /// disconnects, instead of letting them run to completion. Costs every
+    /// search a transaction and a round-trip to read its backend id.
+    /// Default: false
+    #[serde(default = "default_cancel_queries_on_disconnect")]
+    pub cancel_queries_on_disconnect: bool,
+    /// Requests taking at least this long are logged at warn with their
+    /// route, status and a timing breakdown (auth, query, serialization).
+    /// `0` disables the log. Env: `OCTOFHIR__SERVER__SLOW_REQUEST_MS`.
+    /// Default: 1000
+    #[serde(default = "default_slow_request_ms")]
+    pub slow_request_ms: u64,
+    /// Also count slow requests in the `http_slow_requests_total` metric,
+    /// labelled by method and path, for alerting.
+    /// Default: false
+    #[serde(default)]
+    pub slow_request_metric: bool,
     #[serde(default = "default_body_limit")]
     pub body_limit_bytes: usize,
+    /// Maximum serialized size of a single resource written by create,
+    /// update, patch, or a Bundle entry; larger resources get 413. Checked
+    /// separately from `body_limit_bytes` because a patch or Bundle can
+    /// produce a resource larger than any one request. `0` disables the limit.
+    /// Default: 16 MiB
+    #[serde(default = "default_max_resource_size")]
+    pub max_resource_size_bytes: usize,
     #[serde(default)]
     pub compression: bool,
+    /// How long shutdown waits for in-flight requests, running async jobs and
+    /// queued audit/event work before exiting anyway.
+    /// Default: 30000
+    #[serde(default = "default_shutdown_timeout_ms")]
+    pub shutdown_timeout_ms: u64,
+    /// CORS policy for browser clients (FHIR API, SMART discovery and OAuth)
+    #[serde(default)]
+    pub cors: CorsConfig,
 }
 
 fn default_host() -> String {
@@ -199,9 +421,27 @@ fn default_read_timeout_ms() -> u32 {
 fn default_write_timeout_ms() -> u32 {
     15_000
 }
+fn default_request_timeout_ms() -> u64 {
+    30_000
+}
+fn default_operation_timeout_ms() -> u64 {
+    300_000
+}
+fn default_cancel_queries_on_disconnect() -> bool {
+    false
+}
+fn default_slow_request_ms() -> u64 {
+    1_000
+}
 fn default_body_limit() -> usize {
     1024 * 1024
 }
+fn default_max_resource_size() -> usize {
+    16 * 1024 * 1024
+}
+fn default_shutdown_timeout_ms() -> u64 {
+    30_000
+}
 
 impl Default for ServerConfig {
     fn default() -> Self {
@@ -209,10 +449,72 @@ impl Default for ServerConfig {
             host: default_host(),
             port: default_port(),
             base_url: None,
+            trusted_proxies: Vec::new(),
             read_timeout_ms: default_read_timeout_ms(),
             write_timeout_ms: default_write_timeout_ms(),
+            request_timeout_ms: default_request_timeout_ms(),
+            operation_timeout_ms: default_operation_timeout_ms(),
+            cancel_queries_on_disconnect: default_cancel_queries_on_disconnect(),
+            slow_request_ms: default_slow_request_ms(),
+            slow_request_metric: false,
             body_limit_bytes: default_body_limit(),
+            max_resource_size_bytes: default_max_resource_size(),
             compression: false,
+            shutdown_timeout_ms: default_shutdown_timeout_ms(),
+            cors: CorsConfig::default(),
+        }
+    }
+}
+
+/// CORS configuration.
+///
+/// Applies to the FHIR API as well as the SMART discovery
+/// (`/.well-known/smart-configuration`, `/.well-known/openid-configuration`,
+/// `/.well-known/jwks.json`) and OAuth endpoints, so SMART browser apps can
+/// discover the server and launch in-page. The request origin is reflected;
+/// client origins are checked against `Client.allowedOrigins` at token
+/// exchange.
+///
+/// The built-in header lists cover SMART and the FHIR REST API (`Authorization`,
+/// `If-Match`, `Prefer`, ... allowed; `ETag`, `Location`, `Content-Location`,
+/// `Last-Modified`, `WWW-Authenticate`, ... exposed). The lists here add to them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CorsConfig {
+    /// Extra request headers browsers may send (`Access-Control-Allow-Headers`).
+    /// Env: `OCTOFHIR__SERVER__CORS__ALLOW_HEADERS`.
+    /// Default: empty
+    #[serde(default)]
+    pub allow_headers: Vec<String>,
+    /// Extra response headers scripts may read (`Access-Control-Expose-Headers`),
+    /// e.g. custom `X-` headers added by apps or gateways.
+    /// Env: `OCTOFHIR__SERVER__CORS__EXPOSE_HEADERS`.
+    /// Default: empty
+    #[serde(default)]
+    pub expose_headers: Vec<String>,
+    /// Whether browsers may send credentials (cookies, `Authorization`).
+    /// Default: true
+    #[serde(default = "default_cors_allow_credentials")]
+    pub allow_credentials: bool,
+    /// How long browsers may cache a preflight response, in seconds.
+    /// Default: 3600
+    #[serde(default = "default_cors_max_age_secs")]
+    pub max_age_secs: u64,
+}
+
+fn default_cors_allow_credentials() -> bool {
+    true
+}
+fn default_cors_max_age_secs() -> u64 {
+    3600
+}
+
+impl Default for CorsConfig {
+    fn default() -> Self {
+        Self {
+            allow_headers: Vec::new(),
+            expose_headers: Vec::new(),
+            allow_credentials: true,
+            max_age_secs: default_cors_max_age_secs(),
         }
     }
 }
@@ -270,10 +572,27 @@ pub struct PostgresStorageConfig {
     #[serde(default = "default_postgres_pool_size")]
     pub pool_size: u32,
 
+    /// Connections kept open even when idle, so a quiet period doesn't
+    /// leave the pool cold. Defaults to pool_size / 4 (at least 2).
+    #[serde(default)]
+    pub min_connections: Option<u32>,
+
+    /// Connections opened at startup, before `/readyz` reports ready, so the
+    /// first requests after a (re)deploy don't pay connection setup. Capped
+    /// at `pool_size`; also applies to the read replica. 0 disables warmup.
+    #[serde(default)]
+    pub warmup_connections: u32,
+
     /// Connection timeout in milliseconds
     #[serde(default = "default_postgres_connect_timeout")]
     pub connect_timeout_ms: u64,
 
+    /// How long (milliseconds) a request waits for a free pooled connection.
+    /// When every connection stays busy for longer, the request fails with
+    /// 503 and a `Retry-After` header. Defaults to `connect_timeout_ms`.
+    #[serde(default)]
+    pub acquire_timeout_ms: Option<u64>,
+
     /// Idle timeout in milliseconds
     #[serde(default)]
     pub idle_timeout_ms: Option<u64>,
@@ -283,6 +602,21 @@ pub struct PostgresStorageConfig {
     /// to this replica pool while writes stay on the primary.
     #[serde(default)]
     pub read_replica: Option<ReadReplicaConfig>,
+
+    /// TOAST compression for the `resource` JSONB column: "default" (server
+    /// setting, usually pglz), "pglz" or "lz4". lz4 decompresses much faster
+    /// and helps read-heavy workloads with large resources; it needs
+    /// PostgreSQL 14+ built with LZ4. Existing tables are altered on startup;
+    /// already-stored rows are recompressed only when rewritten.
+    #[serde(default)]
+    pub resource_compression: octofhir_db_postgres::JsonbCompression,
+
+    /// Add the generated full-text columns backing `_text` and `_content` to
+    /// resource tables created before they existed. Rewrites each such table
+    /// under an exclusive lock, so enable it for one planned start; without
+    /// it startup only logs the tables that lack them.
+    #[serde(default)]
+    pub upgrade_full_text_columns: bool,
 }
 
 /// Read replica configuration. Only `url` is required;
@@ -300,6 +634,10 @@ pub struct ReadReplicaConfig {
     #[serde(default)]
     pub connect_timeout_ms: Option<u64>,
 
+    /// Pool acquire timeout in milliseconds (defaults to primary's).
+    #[serde(default)]
+    pub acquire_timeout_ms: Option<u64>,
+
     /// Idle timeout in milliseconds (defaults to primary's).
     #[serde(default)]
     pub idle_timeout_ms: Option<u64>,
@@ -357,9 +695,14 @@ impl Default for PostgresStorageConfig {
             password: None,
             database: default_postgres_database(),
             pool_size: default_postgres_pool_size(),
+            min_connections: None,
+            warmup_connections: 0,
             connect_timeout_ms: default_postgres_connect_timeout(),
+            acquire_timeout_ms: None,
             idle_timeout_ms: Some(300_000), // 5 minutes
             read_replica: None,
+            resource_compression: octofhir_db_postgres::JsonbCompression::Default,
+            upgrade_full_text_columns: false,
         }
     }
 }
@@ -385,11 +728,24 @@ pub struct SearchSettings {
     pub allow_debug_search_explain_analyze: bool,
     /// Search parameters to build functional in-place indexes for at bootstrap,
     /// each as `"ResourceType.code"` (e.g. `"Patient.birthdate"`). Indexes are
-    /// created once when tables + registry are ready; nothing is created at
-    /// runtime. Defaults to a popular set; override per deployment (env
+    /// created once when tables + registry are ready and only rebuilt on demand
+    /// by `$reindex`. Defaults to a popular set; override per deployment (env
     /// `OCTOFHIR__SEARCH__INDEXED_PARAMS`) to index only what you search.
     #[serde(default = "default_indexed_params")]
     pub indexed_params: Vec<String>,
+    /// String search parameters to keep in precomputed index tables, each as
+    /// `"ResourceType.code"` (e.g. `"Patient.name"`). A side table holding the
+    /// extracted values is kept in sync on every write and searches on the
+    /// parameter read it instead of extracting from the resource JSONB. Costs
+    /// write throughput; worth it only for hot parameters on read-heavy
+    /// deployments. Env: `OCTOFHIR__SEARCH__MATERIALIZED_PARAMS`.
+    #[serde(default)]
+    pub materialized_params: Vec<String>,
+    /// Pause between index statements of a `$reindex` job, in milliseconds, so
+    /// a rebuild of many indexes does not saturate the database. Env:
+    /// `OCTOFHIR__SEARCH__REINDEX_PAUSE_MS`. Default: 1000.
+    #[serde(default = "default_reindex_pause_ms")]
+    pub reindex_pause_ms: u64,
     /// Maximum number of concepts a token `:in`/`:not-in`/`:above`/`:below`
     /// ValueSet/hierarchy may expand to before the request is rejected. Each
     /// expanded code becomes an OR branch in the rewritten query, so an
@@ -397,6 +753,20 @@ pub struct SearchSettings {
     /// `OCTOFHIR__SEARCH__MAX_VALUESET_EXPANSION`.
     #[serde(default = "default_max_valueset_expansion")]
     pub max_valueset_expansion: usize,
+    /// Maximum number of distinct resources `_include`/`_revinclude` may add to
+    /// one search bundle. Past it the included set is truncated and the bundle
+    /// carries a warning OperationOutcome entry, so `_include=*` over a large
+    /// page cannot load the whole reference graph. Env:
+    /// `OCTOFHIR__SEARCH__MAX_INCLUDED`. Default: 1000.
+    #[serde(default = "default_max_included")]
+    pub max_included: usize,
+    /// Maximum number of reference links in one chained search parameter
+    /// (`subject.organization.name` has two). Each link adds a nested join, so
+    /// longer chains, and chains that loop back to a resource type already on
+    /// the path, are rejected with 400. Env: `OCTOFHIR__SEARCH__MAX_CHAIN_DEPTH`.
+    /// Default: 3.
+    #[serde(default = "default_max_chain_depth")]
+    pub max_chain_depth: usize,
     /// Targeted PARTIAL composite indexes, each pinned to one token value — e.g.
     /// `Observation.code-value-quantity` restricted to `code = 8867-4`. A composite
     /// search for that exact token is then served by a tiny btree pre-filtered to it
@@ -406,6 +776,73 @@ pub struct SearchSettings {
     /// (`ResourceType.param=system|code`, system optional: `...=|8867-4`).
     #[serde(default)]
     pub composite_index: Vec<CompositeIndexSpec>,
+    /// Sort applied to searches that carry no `_sort`, in `_sort` syntax
+    /// (e.g. `["-_lastUpdated", "_id"]`). Without it the result order is
+    /// undefined and may differ between identical queries; with it every
+    /// search pays for an `ORDER BY`. An empty list turns it off.
+    /// Env: `OCTOFHIR__SEARCH__DEFAULT_SORT`.
+    /// Default: `["_lastUpdated", "_id"]`
+    #[serde(default = "default_search_sort")]
+    pub default_sort: Vec<String>,
+    /// Per-resource-type replacements for `default_sort`, e.g.
+    /// `Observation = ["-date", "_id"]`. An empty list turns the default sort
+    /// off for that type.
+    /// Default: empty
+    #[serde(default)]
+    pub default_sort_overrides: HashMap<String, Vec<String>>,
+    /// Maximum number of database queries one FHIR search request may run:
+    /// each search, count and `_include`/`_revinclude` round-trip costs one,
+    /// as does each chain link and `_has` level joined into a search.
+    /// Past it the request fails with 400 as too complex. 0 disables the
+    /// limit. Env: `OCTOFHIR__SEARCH__QUERY_BUDGET`.
+    /// Default: 100
+    #[serde(default = "default_query_budget")]
+    pub query_budget: u32,
+    /// Per-OAuth-scope replacements for `query_budget`, e.g.
+    /// `"system/*.read" = 1000`. A token holding several listed scopes gets
+    /// the largest budget (0 meaning unlimited).
+    /// Default: empty
+    #[serde(default)]
+    pub query_budget_overrides: HashMap<String, u32>,
+    /// How searches treat parameters the server does not support when the
+    /// request carries no `Prefer: handling=...`: `lenient` ignores them and
+    /// lists them in a warning OperationOutcome in the bundle, `strict`
+    /// rejects the search with 400 naming them. Env:
+    /// `OCTOFHIR__SEARCH__UNKNOWN_PARAM_HANDLING`.
+    /// Default: lenient
+    #[serde(default)]
+    pub unknown_param_handling: octofhir_search::UnknownParamHandling,
+    /// How `next`/`previous`/`first`/`last` links in search, history and
+    /// `$everything` bundles address pages: `offset` spells out `_count` and
+    /// `_offset`, `cursor` carries them in an opaque `_cursor` token. Page
+    /// sizes for all three follow `default_count` and `max_count`. Env:
+    /// `OCTOFHIR__SEARCH__LINK_STYLE`.
+    /// Default: offset
+    #[serde(default)]
+    pub link_style: octofhir_core::PageLinkStyle,
+}
+
+impl SearchSettings {
+    /// Default sort settings in the form used by the search engine.
+    pub fn default_sort(&self) -> octofhir_search::DefaultSort {
+        octofhir_search::DefaultSort::new(&self.default_sort, &self.default_sort_overrides)
+    }
+
+    /// Query budget for a request holding `scopes`; `None` when unlimited.
+    pub fn query_budget<'a>(&self, scopes: impl IntoIterator<Item = &'a str>) -> Option<u32> {
+        let mut overrides = scopes
+            .into_iter()
+            .filter_map(|scope| self.query_budget_overrides.get(scope).copied())
+            .peekable();
+        let budget = if overrides.peek().is_some() {
+            overrides
+                .max_by_key(|&b| if b == 0 { u32::MAX } else { b })
+                .unwrap_or(self.query_budget)
+        } else {
+            self.query_budget
+        };
+        (budget > 0).then_some(budget)
+    }
 }
 
 /// One targeted partial composite index: index the quantity component of `param`
@@ -448,6 +885,24 @@ impl CompositeIndexSpec {
 fn default_max_valueset_expansion() -> usize {
     octofhir_search::terminology_preprocess::DEFAULT_MAX_EXPANSION_SIZE
 }
+fn default_query_budget() -> u32 {
+    100
+}
+fn default_max_included() -> usize {
+    octofhir_db_postgres::queries::DEFAULT_MAX_INCLUDED
+}
+fn default_search_sort() -> Vec<String> {
+    octofhir_search::DEFAULT_SORT
+        .split(',')
+        .map(str::to_string)
+        .collect()
+}
+fn default_max_chain_depth() -> usize {
+    octofhir_search::DEFAULT_MAX_CHAIN_DEPTH
+}
+fn default_reindex_pause_ms() -> u64 {
+    1000
+}
 fn default_indexed_params() -> Vec<String> {
     [
         "Patient.birthdate",
@@ -504,8 +959,18 @@ impl Default for SearchSettings {
             allow_debug_search_plan: false,
             allow_debug_search_explain_analyze: false,
             indexed_params: default_indexed_params(),
+            materialized_params: Vec::new(),
+            reindex_pause_ms: default_reindex_pause_ms(),
             max_valueset_expansion: default_max_valueset_expansion(),
+            max_included: default_max_included(),
+            max_chain_depth: default_max_chain_depth(),
             composite_index: Vec::new(),
+            default_sort: default_search_sort(),
+            default_sort_overrides: HashMap::new(),
+            query_budget: default_query_budget(),
+            query_budget_overrides: HashMap::new(),
+            unknown_param_handling: octofhir_search::UnknownParamHandling::default(),
+            link_style: octofhir_core::PageLinkStyle::default(),
         }
     }
 }
@@ -552,14 +1017,76 @@ pub struct OtelConfig {
 pub struct FhirSettings {
     #[serde(default = "default_fhir_version")]
     pub version: String,
+    /// Skip writing a new version when a PUT carries content identical to the
+    /// current version (ignoring key order, `meta.versionId` and
+    /// `meta.lastUpdated`). The current version is returned with 200 instead.
+    /// Set to false to create a history entry for every PUT.
+    /// Default: true
+    #[serde(default = "default_skip_noop_updates")]
+    pub skip_noop_updates: bool,
+    /// Resource types exposed on the FHIR API. When non-empty, other types
+    /// answer 404, are left out of the CapabilityStatement and REST console
+    /// suggestions, and get no database table. Internal administrative types
+    /// served at the root (`/User`, `/Client`, `/AccessPolicy`, ...) are not
+    /// affected.
+    /// Default: empty (all resource types)
+    #[serde(default)]
+    pub resource_types: Vec<String>,
+    /// Most entries an instance history (`GET /{type}/{id}/_history`) returns
+    /// across all its pages, newest first. Older versions stay stored (see
+    /// `history_retention` for pruning) but are not returned; a truncated
+    /// response carries a `Warning` header. 0 disables the cap.
+    /// Env: `OCTOFHIR__FHIR__MAX_HISTORY_ENTRIES`.
+    /// Default: 10000
+    #[serde(default = "default_max_history_entries")]
+    pub max_history_entries: u32,
 }
 fn default_fhir_version() -> String {
     "R4".into()
 }
+
+/// Full release number for a configured FHIR version (`R4`, `4.0`, `4.0.1`
+/// all map to `4.0.1`). Returns `None` for unsupported versions.
+pub fn fhir_release(version: &str) -> Option<&'static str> {
+    match version.trim().to_ascii_uppercase().as_str() {
+        "R4" | "4.0" | "4.0.1" => Some("4.0.1"),
+        "R4B" | "4.3" | "4.3.0" => Some("4.3.0"),
+        "R5" | "5.0" | "5.0.0" => Some("5.0.0"),
+        "R6" | "6.0" | "6.0.0" => Some("6.0.0"),
+        _ => None,
+    }
+}
+
+impl FhirSettings {
+    /// Full release number of the configured FHIR version, e.g. `4.0.1`.
+    pub fn release(&self) -> &'static str {
+        fhir_release(&self.version).unwrap_or("4.0.1")
+    }
+
+    /// Value of the `fhirVersion` MIME parameter served by this server,
+    /// e.g. `4.0`.
+    pub fn mime_version(&self) -> &'static str {
+        octofhir_api::fhir_mime_version(self.release())
+    }
+
+    /// Whether `resource_type` is exposed on the FHIR API.
+    pub fn is_resource_type_allowed(&self, resource_type: &str) -> bool {
+        self.resource_types.is_empty() || self.resource_types.iter().any(|rt| rt == resource_type)
+    }
+}
+fn default_skip_noop_updates() -> bool {
+    true
+}
+fn default_max_history_entries() -> u32 {
+    10_000
+}
 impl Default for FhirSettings {
     fn default() -> Self {
         Self {
             version: default_fhir_version(),
+            skip_noop_updates: default_skip_noop_updates(),
+            resource_types: Vec::new(),
+            max_history_entries: default_max_history_entries(),
         }
     }
 }
@@ -579,6 +1106,11 @@ pub struct PackagesConfig {
     /// Note: Package data is stored in PostgreSQL's 'fcm' schema.
     #[serde(default)]
     pub path: Option<String>,
+    /// Which version a canonical URL without `|version` resolves to when
+    /// several are loaded: "latest" (default), "latest-stable" (skip draft
+    /// and retired versions) or "strict" (require an explicit version).
+    #[serde(default)]
+    pub canonical_version_policy: octofhir_db_postgres::CanonicalVersionPolicy,
 }
 
 // Default derived
@@ -604,6 +1136,16 @@ pub enum PackageSpec {
 /// Validation configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ValidationSettings {
+    /// How creates and updates treat resources that fail validation against
+    /// their base StructureDefinition and the profiles in `meta.profile`.
+    /// Default: enforce
+    #[serde(default)]
+    pub mode: ValidationMode,
+
+    /// `mode` for particular resource types, e.g. `{ Observation = "warn" }`
+    #[serde(default)]
+    pub mode_overrides: HashMap<String, ValidationMode>,
+
     /// Allow clients to skip validation via X-Skip-Validation header
     /// Default: false (disabled for security)
     #[serde(default = "default_allow_skip_validation")]
@@ -635,6 +1177,54 @@ pub struct ValidationSettings {
     /// Maximum recursion depth for transitive `targetProfile` conformance.
     #[serde(default = "default_max_reference_depth")]
     pub max_reference_depth: usize,
+
+    /// Check on create/update that local references (found through the
+    /// resource's `reference` search parameters) point at existing resources.
+    /// Default: off.
+    #[serde(default)]
+    pub reference_integrity: ReferenceIntegrityMode,
+
+    /// Also apply `reference_integrity` to entries of transaction Bundles.
+    /// Resources created by the same Bundle count as existing. Default: false,
+    /// since Bundles commonly carry forward references.
+    #[serde(default)]
+    pub reference_integrity_in_bundles: bool,
+}
+
+impl ValidationSettings {
+    /// Validation mode for writes of `resource_type`.
+    pub fn mode_for(&self, resource_type: &str) -> ValidationMode {
+        self.mode_overrides
+            .get(resource_type)
+            .copied()
+            .unwrap_or(self.mode)
+    }
+}
+
+/// How writes treat resources that fail validation.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ValidationMode {
+    /// No validation
+    Off,
+    /// Log validation errors and accept the write
+    Warn,
+    /// Reject the write with 422 listing the validation errors
+    #[default]
+    Enforce,
+}
+
+/// How writes treat local references whose target does not exist.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReferenceIntegrityMode {
+    /// No check
+    #[default]
+    Off,
+    /// Log dangling references and accept the write
+    Warn,
+    /// Reject the write with 422 listing dangling references
+    Enforce,
 }
 
 fn default_allow_skip_validation() -> bool {
@@ -652,12 +1242,16 @@ fn default_max_reference_depth() -> usize {
 impl Default for ValidationSettings {
     fn default() -> Self {
         Self {
+            mode: ValidationMode::default(),
+            mode_overrides: HashMap::new(),
             allow_skip_validation: default_allow_skip_validation(),
             skip_reference_validation: false,
             check_target_profile: false,
             fetch_external_references: false,
             reference_fetch_timeout_ms: default_reference_fetch_timeout_ms(),
             max_reference_depth: default_max_reference_depth(),
+            reference_integrity: ReferenceIntegrityMode::default(),
+            reference_integrity_in_bundles: false,
         }
     }
 }
@@ -911,6 +1505,14 @@ pub struct GraphQLConfig {
     /// Default: [] (all resources)
     #[serde(default)]
     pub resource_types: Vec<String>,
+
+    /// Validate create/update mutation input against its StructureDefinition
+    /// and declared profiles before storing it, as the REST API does.
+    /// Invalid input fails the mutation with the failing element paths.
+    /// Primitive values are checked either way.
+    /// Default: false
+    #[serde(default)]
+    pub validate_mutations: bool,
 }
 
 fn default_graphql_enabled() -> bool {
@@ -942,6 +1544,7 @@ impl Default for GraphQLConfig {
             max_depth: default_graphql_max_depth(),
             max_complexity: default_graphql_max_complexity(),
             resource_types: Vec::new(),
+            validate_mutations: false,
         }
     }
 }
@@ -967,6 +1570,12 @@ pub struct BulkExportConfig {
     #[serde(default = "default_bulk_export_max_concurrent")]
     pub max_concurrent_jobs: usize,
 
+    /// Maximum concurrent export jobs of one OAuth client (0 = no limit)
+    /// Further jobs stay queued until one of the client's jobs finishes
+    /// Default: 2
+    #[serde(default = "default_bulk_export_max_concurrent_per_client")]
+    pub max_concurrent_jobs_per_client: usize,
+
     /// Export file retention period in hours
     /// Files older than this will be cleaned up
     /// Default: 24 hours
@@ -987,6 +1596,12 @@ pub struct BulkExportConfig {
     /// If empty, exports all available resource types
     #[serde(default)]
     pub default_resource_types: Vec<String>,
+
+    /// Gzip-compress export files (`.ndjson.gz`) unless the request's
+    /// `compression` parameter says otherwise
+    /// Default: false
+    #[serde(default)]
+    pub gzip: bool,
 }
 
 fn default_bulk_export_enabled() -> bool {
@@ -1001,6 +1616,10 @@ fn default_bulk_export_max_concurrent() -> usize {
     5
 }
 
+fn default_bulk_export_max_concurrent_per_client() -> usize {
+    2
+}
+
 fn default_bulk_export_retention_hours() -> u64 {
     24
 }
@@ -1019,14 +1638,134 @@ impl Default for BulkExportConfig {
             enabled: default_bulk_export_enabled(),
             export_path: default_bulk_export_path(),
             max_concurrent_jobs: default_bulk_export_max_concurrent(),
+            max_concurrent_jobs_per_client: default_bulk_export_max_concurrent_per_client(),
             retention_hours: default_bulk_export_retention_hours(),
             max_resources_per_file: default_bulk_export_max_resources_per_file(),
             batch_size: default_bulk_export_batch_size(),
             default_resource_types: Vec::new(),
+            gzip: false,
+        }
+    }
+}
+
+/// Multi-tenancy configuration
+///
+/// When enabled, each FHIR API request is scoped to the tenant named by a
+/// claim of its access token. A tenant's resources live in their own
+/// PostgreSQL schema (`tenant_<id>`); users, clients and access policies stay
+/// shared.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MultitenancyConfig {
+    /// Enable tenant isolation
+    /// Default: false
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Access token claim carrying the tenant ID
+    /// Default: "tenant"
+    #[serde(default = "default_tenant_claim")]
+    pub claim: String,
+
+    /// Tenants served by this server. Their schemas are provisioned on
+    /// startup; tokens naming any other tenant are rejected.
+    #[serde(default)]
+    pub tenants: Vec<String>,
+}
+
+fn default_tenant_claim() -> String {
+    "tenant".to_string()
+}
+
+impl Default for MultitenancyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            claim: default_tenant_claim(),
+            tenants: Vec::new(),
+        }
+    }
+}
+
+/// Per-client API rate limiting
+///
+/// Each client gets `requests_per_window` requests per fixed window. Clients
+/// are told apart by the access token's `client_id`, or by peer address for
+/// requests without a token. Counters live in the shared cache, so with Redis
+/// enabled the quota holds across all instances.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RateLimitConfig {
+    /// Requests allowed per client per window (0 disables)
+    /// Default: 0
+    #[serde(default)]
+    pub requests_per_window: u32,
+
+    /// Window length in seconds
+    /// Default: 60
+    #[serde(default = "default_rate_limit_window_secs")]
+    pub window_secs: u64,
+}
+
+fn default_rate_limit_window_secs() -> u64 {
+    60
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            requests_per_window: 0,
+            window_secs: default_rate_limit_window_secs(),
+        }
+    }
+}
+
+/// FHIR operation allow/deny lists
+///
+/// Operations are named by code, with or without the `$` prefix (`export`,
+/// `$expand`). A disabled operation answers 404 at every level and is left
+/// out of the CapabilityStatement.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OperationsConfig {
+    /// When non-empty, only these operations are served
+    /// Default: empty (all operations)
+    #[serde(default)]
+    pub allow: Vec<String>,
+
+    /// Operations that are never served, even if allowed
+    /// Default: empty
+    #[serde(default)]
+    pub deny: Vec<String>,
+
+    /// Check request parameters against the operation's OperationDefinition
+    /// (required parameters, cardinality, value types, `use`) and answer 400
+    /// before the operation runs
+    /// Default: true
+    #[serde(default = "default_validate_operation_parameters")]
+    pub validate_parameters: bool,
+}
+
+fn default_validate_operation_parameters() -> bool {
+    true
+}
+
+impl Default for OperationsConfig {
+    fn default() -> Self {
+        Self {
+            allow: Vec::new(),
+            deny: Vec::new(),
+            validate_parameters: default_validate_operation_parameters(),
         }
     }
 }
 
+impl OperationsConfig {
+    /// Whether the operation `code` (with or without `$`) may be served.
+    pub fn is_enabled(&self, code: &str) -> bool {
+        let code = code.trim_start_matches('$');
+        let listed = |list: &[String]| list.iter().any(|c| c.trim_start_matches('$') == code);
+        (self.allow.is_empty() || listed(&self.allow)) && !listed(&self.deny)
+    }
+}
+
 /// Bulk import configuration ($import operation)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BulkImportConfig {
@@ -1042,10 +1781,19 @@ pub struct BulkImportConfig {
     #[serde(default = "default_bulk_import_max_concurrent")]
     pub max_concurrent_jobs: usize,
 
+    /// Maximum concurrent import jobs of one OAuth client (0 = no limit)
+    #[serde(default = "default_bulk_import_max_concurrent_per_client")]
+    pub max_concurrent_jobs_per_client: usize,
+
     /// Maximum number of resources processed concurrently within a single import job
     #[serde(default = "default_bulk_import_parallelism")]
     pub max_parallel_resources: usize,
 
+    /// Maximum number of manifest inputs (NDJSON URLs) fetched and ingested
+    /// concurrently within a single import job. Default: 2
+    #[serde(default = "default_bulk_import_input_concurrency")]
+    pub max_concurrent_inputs: usize,
+
     /// Skip FHIR validation for trusted data (can be overridden per-request)
     #[serde(default)]
     pub default_skip_validation: bool,
@@ -1060,9 +1808,15 @@ fn default_bulk_import_batch_size() -> usize {
 fn default_bulk_import_max_concurrent() -> usize {
     3
 }
+fn default_bulk_import_max_concurrent_per_client() -> usize {
+    1
+}
 fn default_bulk_import_parallelism() -> usize {
     32
 }
+fn default_bulk_import_input_concurrency() -> usize {
+    2
+}
 
 impl Default for BulkImportConfig {
     fn default() -> Self {
@@ -1070,7 +1824,9 @@ impl Default for BulkImportConfig {
             enabled: default_bulk_import_enabled(),
             batch_size: default_bulk_import_batch_size(),
             max_concurrent_jobs: default_bulk_import_max_concurrent(),
+            max_concurrent_jobs_per_client: default_bulk_import_max_concurrent_per_client(),
             max_parallel_resources: default_bulk_import_parallelism(),
+            max_concurrent_inputs: default_bulk_import_input_concurrency(),
             default_skip_validation: false,
         }
     }
@@ -1182,6 +1938,92 @@ pub struct AuditConfig {
     /// Example: ["AuditEvent"] to avoid infinite loops
     #[serde(default = "default_audit_exclude_types")]
     pub exclude_resource_types: Vec<String>,
+
+    /// Write a Provenance resource for every committed transaction Bundle,
+    /// targeting each created or updated resource version and naming the
+    /// requesting user/client as agent. Stored in the same database
+    /// transaction as the writes it describes.
+    /// Default: false
+    #[serde(default)]
+    pub transaction_provenance: bool,
+
+    /// What the AuditEvent of a resource create or update records about the
+    /// change, in `entity.detail`: `reference` (only `Type/id`), `diff` (a
+    /// JSON Patch from the previous version) or `full` (the resource as
+    /// written).
+    /// Default: reference
+    #[serde(default)]
+    pub detail: AuditDetail,
+
+    /// Elements stripped from `diff` and `full` payloads before they are
+    /// stored, as dotted paths from the resource root (`name`,
+    /// `address.line`, `deceased[x]`). Repeating elements are followed into
+    /// every item. An empty list stores payloads unredacted.
+    /// Default: narrative and common demographic elements
+    #[serde(default = "default_audit_redact_elements")]
+    pub redact_elements: Vec<String>,
+
+    /// Per-resource-type replacements for `detail` and `redact_elements`,
+    /// e.g. `Patient = { detail = "reference" }`.
+    /// Default: empty
+    #[serde(default)]
+    pub resource_overrides: HashMap<String, AuditResourceOverride>,
+}
+
+/// How much of a changed resource an AuditEvent records.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditDetail {
+    /// Only the `Type/id` reference.
+    #[default]
+    Reference,
+    /// JSON Patch from the previous version (from `{}` on create).
+    Diff,
+    /// The resource as written.
+    Full,
+}
+
+/// Audit settings for one resource type; unset fields fall back to the
+/// `[audit]` values.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AuditResourceOverride {
+    #[serde(default)]
+    pub detail: Option<AuditDetail>,
+    #[serde(default)]
+    pub redact_elements: Option<Vec<String>>,
+}
+
+impl AuditConfig {
+    /// Detail level for changes to `resource_type`.
+    pub fn detail_for(&self, resource_type: &str) -> AuditDetail {
+        self.resource_overrides
+            .get(resource_type)
+            .and_then(|o| o.detail)
+            .unwrap_or(self.detail)
+    }
+
+    /// Elements redacted from the audit payload of `resource_type`.
+    pub fn redact_elements_for(&self, resource_type: &str) -> &[String] {
+        self.resource_overrides
+            .get(resource_type)
+            .and_then(|o| o.redact_elements.as_deref())
+            .unwrap_or(&self.redact_elements)
+    }
+}
+
+fn default_audit_redact_elements() -> Vec<String> {
+    [
+        "text",
+        "identifier",
+        "name",
+        "telecom",
+        "address",
+        "birthDate",
+        "photo",
+        "contact",
+    ]
+    .map(String::from)
+    .to_vec()
 }
 
 fn default_audit_enabled() -> bool {
@@ -1209,6 +2051,399 @@ impl Default for AuditConfig {
             log_read_operations: false,
             log_search_operations: false,
             exclude_resource_types: default_audit_exclude_types(),
+            transaction_provenance: false,
+            detail: AuditDetail::default(),
+            redact_elements: default_audit_redact_elements(),
+            resource_overrides: HashMap::new(),
+        }
+    }
+}
+
+/// Resource event hook delivery configuration.
+///
+/// Hooks are best-effort by default: they receive events from an in-process
+/// channel, and an event is lost if the server stops before they run. Hooks
+/// listed as `at_least_once` instead receive events from a database outbox
+/// written in the same transaction as the change, and an event is retried
+/// until the hook succeeds.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventsConfig {
+    /// Delivery guarantee by hook name (`best_effort` or `at_least_once`),
+    /// e.g. `async_audit = "at_least_once"`. Hooks not listed keep their
+    /// built-in guarantee. The outbox is enabled while any hook is
+    /// `at_least_once`.
+    /// Default: empty
+    #[serde(default)]
+    pub delivery: HashMap<String, octofhir_core::events::DeliveryGuarantee>,
+
+    /// How often the outbox relay polls for undelivered events.
+    /// Default: 1000
+    #[serde(default = "default_events_outbox_poll_interval_ms")]
+    pub outbox_poll_interval_ms: u64,
+
+    /// Maximum events delivered to one hook per poll.
+    /// Default: 100
+    #[serde(default = "default_events_outbox_batch_size")]
+    pub outbox_batch_size: usize,
+}
+
+fn default_events_outbox_poll_interval_ms() -> u64 {
+    1000
+}
+
+fn default_events_outbox_batch_size() -> usize {
+    100
+}
+
+impl Default for EventsConfig {
+    fn default() -> Self {
+        Self {
+            delivery: HashMap::new(),
+            outbox_poll_interval_ms: default_events_outbox_poll_interval_ms(),
+            outbox_batch_size: default_events_outbox_batch_size(),
+        }
+    }
+}
+
+/// Resource version retention.
+///
+/// When enabled, a background task periodically deletes old versions from the
+/// history tables according to a rule per resource type. The current version
+/// of a resource is never pruned, nor is the last version of a deleted one.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryRetentionConfig {
+    /// Run the pruning task
+    /// Default: false
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Seconds between pruning runs
+    /// Default: 3600
+    #[serde(default = "default_history_retention_interval_secs")]
+    pub interval_secs: u64,
+
+    /// Resources whose history one statement prunes; a table is walked in
+    /// id order over several statements to keep each one short
+    /// Default: 1000
+    #[serde(default = "default_history_retention_batch_size")]
+    pub batch_size: u32,
+
+    /// Default rule: keep this many most recent versions, counting the
+    /// current one
+    /// Default: unset (no limit)
+    #[serde(default)]
+    pub keep_versions: Option<u32>,
+
+    /// Default rule: keep versions written less than this many days ago
+    /// Default: unset (no limit)
+    #[serde(default)]
+    pub max_age_days: Option<u32>,
+
+    /// Rules by resource type, replacing the default rule as a whole,
+    /// e.g. `AuditEvent = { max_age_days = 90 }`. An entry with no limits
+    /// (`Consent = {}`) keeps every version.
+    /// Default: empty
+    #[serde(default)]
+    pub resource_types: HashMap<String, HistoryRetentionRule>,
+}
+
+fn default_history_retention_interval_secs() -> u64 {
+    3600
+}
+
+fn default_history_retention_batch_size() -> u32 {
+    1_000
+}
+
+impl Default for HistoryRetentionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_secs: default_history_retention_interval_secs(),
+            batch_size: default_history_retention_batch_size(),
+            keep_versions: None,
+            max_age_days: None,
+            resource_types: HashMap::new(),
+        }
+    }
+}
+
+impl HistoryRetentionConfig {
+    /// The rule for `resource_type`: its entry in `resource_types` (matched
+    /// case-insensitively, as table names are), or else the default rule.
+    pub fn rule_for(&self, resource_type: &str) -> HistoryRetentionRule {
+        self.resource_types
+            .iter()
+            .find(|(rt, _)| rt.eq_ignore_ascii_case(resource_type))
+            .map(|(_, rule)| rule.clone())
+            .unwrap_or(HistoryRetentionRule {
+                keep_versions: self.keep_versions,
+                max_age_days: self.max_age_days,
+            })
+    }
+}
+
+/// Which versions of a resource to keep. A version is pruned only when it
+/// falls outside every limit that is set; with no limits, all are kept.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryRetentionRule {
+    /// Keep this many most recent versions, counting the current one
+    #[serde(default)]
+    pub keep_versions: Option<u32>,
+
+    /// Keep versions written less than this many days ago
+    #[serde(default)]
+    pub max_age_days: Option<u32>,
+}
+
+impl HistoryRetentionRule {
+    /// Whether the rule keeps every version.
+    pub fn is_unlimited(&self) -> bool {
+        self.keep_versions.is_none() && self.max_age_days.is_none()
+    }
+}
+
+/// Binary resource content storage.
+///
+/// With an external backend, the decoded `data` of a `Binary` at least
+/// `threshold_bytes` long is written to the blob store and the stored resource
+/// keeps only a pointer to it. Reads restore `data`, and `GET /Binary/{id}`
+/// can serve the raw bytes (with range requests) straight from the store.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BinaryConfig {
+    /// Where Binary content is kept
+    /// Default: inline (in the resource JSON)
+    #[serde(default)]
+    pub backend: BinaryBackend,
+
+    /// Smallest decoded content size moved to the blob store; smaller content
+    /// stays inline.
+    /// Default: 65536
+    #[serde(default = "default_binary_threshold_bytes")]
+    pub threshold_bytes: usize,
+
+    /// Filesystem backend settings
+    #[serde(default)]
+    pub filesystem: BinaryFilesystemConfig,
+
+    /// S3-compatible backend settings
+    #[serde(default)]
+    pub s3: BinaryS3Config,
+
+    /// Externalization of large `Attachment.data` in other resources
+    #[serde(default)]
+    pub attachments: BinaryAttachmentsConfig,
+
+    /// Removal of blobs no resource version references any more
+    #[serde(default)]
+    pub gc: BinaryGcConfig,
+}
+
+fn default_binary_threshold_bytes() -> usize {
+    64 * 1024
+}
+
+impl Default for BinaryConfig {
+    fn default() -> Self {
+        Self {
+            backend: BinaryBackend::default(),
+            threshold_bytes: default_binary_threshold_bytes(),
+            filesystem: BinaryFilesystemConfig::default(),
+            s3: BinaryS3Config::default(),
+            attachments: BinaryAttachmentsConfig::default(),
+            gc: BinaryGcConfig::default(),
+        }
+    }
+}
+
+/// Blob sweep settings
+///
+/// A sweep deletes blobs that no stored resource or history version points
+/// to and that nobody has written for `grace_secs`. Sweeps run every
+/// `interval_secs`, and a few minutes after deletes and history pruning.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BinaryGcConfig {
+    /// Run the sweep task
+    /// Default: true
+    #[serde(default = "default_binary_gc_enabled")]
+    pub enabled: bool,
+
+    /// Seconds between scheduled sweeps
+    /// Default: 86400
+    #[serde(default = "default_binary_gc_interval_secs")]
+    pub interval_secs: u64,
+
+    /// Seconds a blob must go unwritten before a sweep may delete it; covers
+    /// writes whose resource is not committed yet
+    /// Default: 3600
+    #[serde(default = "default_binary_gc_grace_secs")]
+    pub grace_secs: u64,
+}
+
+fn default_binary_gc_enabled() -> bool {
+    true
+}
+
+fn default_binary_gc_interval_secs() -> u64 {
+    24 * 60 * 60
+}
+
+fn default_binary_gc_grace_secs() -> u64 {
+    3600
+}
+
+impl Default for BinaryGcConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_binary_gc_enabled(),
+            interval_secs: default_binary_gc_interval_secs(),
+            grace_secs: default_binary_gc_grace_secs(),
+        }
+    }
+}
+
+/// Blob store backend for Binary content
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BinaryBackend {
+    /// Content stays base64 in the resource JSON
+    #[default]
+    Inline,
+    /// Content is written to files under `binary.filesystem.path`
+    Filesystem,
+    /// Content is written to an S3-compatible bucket
+    S3,
+}
+
+/// Attachment externalization settings
+///
+/// When enabled, every `Attachment` outside a `Binary` whose decoded `data` is
+/// at least `threshold_bytes` long has its data moved to the blob store. The
+/// stored attachment keeps a pointer in place of `data`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BinaryAttachmentsConfig {
+    /// Externalize large attachment data on write (needs a non-inline backend)
+    /// Default: false
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Smallest decoded attachment size moved to the blob store
+    /// Default: 65536
+    #[serde(default = "default_binary_threshold_bytes")]
+    pub threshold_bytes: usize,
+
+    /// How reads of the current version and search results present
+    /// externalized attachments. History and vread always return the data
+    /// inline, as written.
+    /// Default: inline
+    #[serde(default)]
+    pub on_read: AttachmentReadMode,
+}
+
+impl Default for BinaryAttachmentsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            threshold_bytes: default_binary_threshold_bytes(),
+            on_read: AttachmentReadMode::default(),
+        }
+    }
+}
+
+/// Presentation of externalized attachments on read
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AttachmentReadMode {
+    /// Put `data` back into the attachment
+    #[default]
+    Inline,
+    /// Leave `data` out and set `url` to the server's copy of the content
+    Redirect,
+}
+
+/// Filesystem blob store settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BinaryFilesystemConfig {
+    /// Root directory for blobs
+    /// Default: "./binary" (relative to working directory)
+    #[serde(default = "default_binary_filesystem_path")]
+    pub path: String,
+}
+
+fn default_binary_filesystem_path() -> String {
+    "./binary".to_string()
+}
+
+impl Default for BinaryFilesystemConfig {
+    fn default() -> Self {
+        Self {
+            path: default_binary_filesystem_path(),
+        }
+    }
+}
+
+/// S3-compatible blob store settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BinaryS3Config {
+    /// Service endpoint, e.g. `https://s3.eu-west-1.amazonaws.com` or a
+    /// MinIO URL
+    /// Default: "https://s3.amazonaws.com"
+    #[serde(default = "default_binary_s3_endpoint")]
+    pub endpoint: String,
+
+    /// Bucket name (required for the s3 backend)
+    #[serde(default)]
+    pub bucket: String,
+
+    /// Signing region
+    /// Default: "us-east-1"
+    #[serde(default = "default_binary_s3_region")]
+    pub region: String,
+
+    /// Key prefix inside the bucket
+    /// Default: ""
+    #[serde(default)]
+    pub prefix: String,
+
+    /// Address the bucket as `{endpoint}/{bucket}` instead of
+    /// `{bucket}.{endpoint host}` (needed by most S3-compatible servers)
+    /// Default: false
+    #[serde(default)]
+    pub path_style: bool,
+
+    /// Access key ID; falls back to `AWS_ACCESS_KEY_ID`
+    #[serde(default)]
+    pub access_key_id: Option<String>,
+
+    /// Secret access key; falls back to `AWS_SECRET_ACCESS_KEY`
+    #[serde(default)]
+    pub secret_access_key: Option<String>,
+
+    /// Session token for temporary credentials; falls back to
+    /// `AWS_SESSION_TOKEN`
+    #[serde(default)]
+    pub session_token: Option<String>,
+}
+
+fn default_binary_s3_endpoint() -> String {
+    "https://s3.amazonaws.com".to_string()
+}
+
+fn default_binary_s3_region() -> String {
+    "us-east-1".to_string()
+}
+
+impl Default for BinaryS3Config {
+    fn default() -> Self {
+        Self {
+            endpoint: default_binary_s3_endpoint(),
+            bucket: String::new(),
+            region: default_binary_s3_region(),
+            prefix: String::new(),
+            path_style: false,
+            access_key_id: None,
+            secret_access_key: None,
+            session_token: None,
         }
     }
 }
@@ -1262,7 +2497,12 @@ pub mod loader {
                 .separator("__")
                 .list_separator(",")
                 .with_list_parse_key("packages.load")
-                .with_list_parse_key("search.indexed_params"),
+                .with_list_parse_key("search.indexed_params")
+                .with_list_parse_key("search.materialized_params")
+                .with_list_parse_key("search.default_sort")
+                .with_list_parse_key("server.trusted_proxies")
+                .with_list_parse_key("server.cors.allow_headers")
+                .with_list_parse_key("server.cors.expose_headers"),
         );
         let cfg = builder
             .build()
@@ -0,0 +1,404 @@
+//! External base URL resolution behind reverse proxies.
+//!
+//! Bundle `fullUrl`s, pagination links and `Location` headers are built from
+//! the configured base URL. When a request arrives from a peer listed in
+//! `server.trusted_proxies`, the scheme and host are taken from the
+//! `Forwarded` (RFC 7239) or `X-Forwarded-Proto`/`X-Forwarded-Host` headers
+//! instead, keeping the path of the configured base. Requests from any other
+//! peer, and servers without trusted proxies, always use the configured base.
+//!
+//! Operations (`$export`, `$import`, `$reindex`, ViewDefinition `$export`)
+//! build their async status and output file URLs from the configured base,
+//! since those URLs are stored with the job and outlive the request.
+//!
+//! The client address is resolved the same way: behind trusted proxies it is
+//! the nearest untrusted hop in `Forwarded` (`for=`) or `X-Forwarded-For`,
+//! otherwise the peer itself.
+
+use std::convert::Infallible;
+use std::net::{IpAddr, SocketAddr};
+
+use axum::extract::{ConnectInfo, FromRequestParts};
+use axum::http::HeaderMap;
+use axum::http::request::Parts;
+use ipnetwork::IpNetwork;
+
+use crate::server::AppState;
+
+/// Proxy addresses allowed to set forwarding headers.
+#[derive(Debug, Clone, Default)]
+pub struct TrustedProxies(Vec<IpNetwork>);
+
+impl TrustedProxies {
+    /// Parse IP addresses and CIDR ranges (`10.0.0.1`, `10.0.0.0/8`, `::1`).
+    pub fn parse(entries: &[String]) -> Result<Self, String> {
+        entries
+            .iter()
+            .map(|entry| {
+                entry
+                    .trim()
+                    .parse::<IpNetwork>()
+                    .map_err(|e| format!("invalid trusted proxy '{entry}': {e}"))
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(Self)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Whether `ip` is one of the trusted proxies. IPv4-mapped IPv6 peers are
+    /// matched against IPv4 entries.
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        let ip = match ip {
+            IpAddr::V6(v6) => v6.to_ipv4_mapped().map(IpAddr::V4).unwrap_or(ip),
+            v4 => v4,
+        };
+        self.0.iter().any(|net| net.contains(ip))
+    }
+}
+
+/// Base URL for links in the response to the current request.
+///
+/// Resolves to the forwarded external URL when the peer is a trusted proxy,
+/// otherwise to the configured base URL. Never rejects.
+#[derive(Debug, Clone)]
+pub struct BaseUrl(pub String);
+
+impl BaseUrl {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl FromRequestParts<AppState> for BaseUrl {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let peer = parts
+            .extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| addr.ip());
+        Ok(Self(resolve_base_url(
+            &state.base_url,
+            &state.trusted_proxies,
+            peer,
+            &parts.headers,
+        )))
+    }
+}
+
+/// Resolve the base URL for a request from `peer`.
+pub fn resolve_base_url(
+    configured: &str,
+    trusted: &TrustedProxies,
+    peer: Option<IpAddr>,
+    headers: &HeaderMap,
+) -> String {
+    match peer {
+        Some(peer) if trusted.contains(peer) => {
+            forwarded_base_url(configured, headers).unwrap_or_else(|| configured.to_string())
+        }
+        _ => configured.to_string(),
+    }
+}
+
+/// Resolve the address of the client behind `peer`.
+///
+/// When `peer` is a trusted proxy, the forwarded chain is walked from the
+/// nearest hop outwards, skipping further trusted proxies, and the first
+/// other address is the client. Hops a client could have forged (anything
+/// before that address) are never used. Falls back to `peer` when the chain
+/// holds no usable address.
+pub fn resolve_client_ip(
+    trusted: &TrustedProxies,
+    peer: Option<IpAddr>,
+    headers: &HeaderMap,
+) -> Option<IpAddr> {
+    let peer = peer?;
+    if !trusted.contains(peer) {
+        return Some(peer);
+    }
+    let chain = forwarded_for_chain(headers);
+    for hop in chain.iter().rev() {
+        match hop {
+            Some(ip) if trusted.contains(*ip) => continue,
+            Some(ip) => return Some(*ip),
+            // An obfuscated or malformed hop ends the trusted part of the
+            // chain; nothing before it can be attributed.
+            None => break,
+        }
+    }
+    Some(peer)
+}
+
+/// Addresses of the `for=` parameters of `Forwarded`, or else of
+/// `X-Forwarded-For`, client-most first. `None` marks a hop that is not an IP
+/// address (`unknown`, obfuscated identifiers).
+fn forwarded_for_chain(headers: &HeaderMap) -> Vec<Option<IpAddr>> {
+    let forwarded: Vec<_> = headers
+        .get_all("forwarded")
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|element| {
+            element.split(';').find_map(|pair| {
+                let (key, value) = pair.split_once('=')?;
+                key.trim()
+                    .eq_ignore_ascii_case("for")
+                    .then(|| parse_node(value))
+            })
+        })
+        .collect();
+    if !forwarded.is_empty() {
+        return forwarded;
+    }
+    headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(parse_node)
+        .collect()
+}
+
+/// Parse a forwarded node: `192.0.2.43`, `192.0.2.43:80`,
+/// `"[2001:db8::17]:4711"` or a bare IPv6 address.
+fn parse_node(node: &str) -> Option<IpAddr> {
+    let node = node.trim().trim_matches('"');
+    if let Some(rest) = node.strip_prefix('[') {
+        return rest.split_once(']')?.0.parse().ok();
+    }
+    node.parse::<IpAddr>()
+        .ok()
+        .or_else(|| node.parse::<SocketAddr>().ok().map(|addr| addr.ip()))
+}
+
+/// Build the external base URL from forwarding headers, or `None` when the
+/// request carries none (or only malformed ones).
+///
+/// `Forwarded` wins over the `X-Forwarded-*` headers. Only the first
+/// (client-most) element of each header is used. A forwarded scheme without a
+/// host falls back to the `Host` header.
+fn forwarded_base_url(configured: &str, headers: &HeaderMap) -> Option<String> {
+    let (proto, host) = match forwarded_header(headers) {
+        Some(pair) => pair,
+        None => {
+            let proto = first_value(headers, "x-forwarded-proto");
+            let mut host = first_value(headers, "x-forwarded-host");
+            if let (Some(h), Some(port)) = (&host, first_value(headers, "x-forwarded-port"))
+                && !h.contains(':')
+                && port.parse::<u16>().is_ok()
+            {
+                host = Some(format!("{h}:{port}"));
+            }
+            (proto, host)
+        }
+    };
+    if proto.is_none() && host.is_none() {
+        return None;
+    }
+
+    let configured_url = url::Url::parse(configured).ok()?;
+    let proto = proto
+        .map(|p| p.to_ascii_lowercase())
+        .unwrap_or_else(|| configured_url.scheme().to_string());
+    if proto != "http" && proto != "https" {
+        return None;
+    }
+    let host = host.or_else(|| first_value(headers, "host")).or_else(|| {
+        configured_url
+            .host_str()
+            .map(|h| match configured_url.port() {
+                Some(port) => format!("{h}:{port}"),
+                None => h.to_string(),
+            })
+    })?;
+    if !is_valid_host(&host) {
+        return None;
+    }
+
+    let path = configured_url.path().trim_end_matches('/');
+    Some(format!("{proto}://{host}{path}"))
+}
+
+/// `proto` and `host` from the first element of the `Forwarded` header.
+fn forwarded_header(headers: &HeaderMap) -> Option<(Option<String>, Option<String>)> {
+    let value = headers.get("forwarded")?.to_str().ok()?;
+    let first = value.split(',').next()?;
+    let mut proto = None;
+    let mut host = None;
+    for pair in first.split(';') {
+        let Some((key, value)) = pair.split_once('=') else {
+            continue;
+        };
+        let value = value.trim().trim_matches('"').to_string();
+        match key.trim().to_ascii_lowercase().as_str() {
+            "proto" => proto = Some(value),
+            "host" => host = Some(value),
+            _ => {}
+        }
+    }
+    (proto.is_some() || host.is_some()).then_some((proto, host))
+}
+
+fn first_value(headers: &HeaderMap, name: &str) -> Option<String> {
+    let value = headers.get(name)?.to_str().ok()?;
+    let first = value.split(',').next()?.trim();
+    (!first.is_empty()).then(|| first.to_string())
+}
+
+/// Reject hosts that would change the URL structure (userinfo, paths, spaces).
+fn is_valid_host(host: &str) -> bool {
+    !host.is_empty()
+        && host
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | ':' | '[' | ']' | '_'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+        let mut map = HeaderMap::new();
+        for (k, v) in pairs {
+            map.insert(*k, HeaderValue::from_static(v));
+        }
+        map
+    }
+
+    fn trusted() -> TrustedProxies {
+        TrustedProxies::parse(&["10.0.0.0/8".to_string(), "::1".to_string()]).unwrap()
+    }
+
+    #[test]
+    fn test_untrusted_peer_uses_configured_base() {
+        let h = headers(&[
+            ("x-forwarded-proto", "https"),
+            ("x-forwarded-host", "fhir.example.org"),
+        ]);
+        let peer = Some("192.168.1.5".parse().unwrap());
+        assert_eq!(
+            resolve_base_url("http://0.0.0.0:8080", &trusted(), peer, &h),
+            "http://0.0.0.0:8080"
+        );
+        assert_eq!(
+            resolve_base_url("http://0.0.0.0:8080", &trusted(), None, &h),
+            "http://0.0.0.0:8080"
+        );
+    }
+
+    #[test]
+    fn test_trusted_peer_uses_x_forwarded_headers() {
+        let h = headers(&[
+            ("x-forwarded-proto", "https"),
+            ("x-forwarded-host", "fhir.example.org, internal:8080"),
+        ]);
+        let peer = Some("10.1.2.3".parse().unwrap());
+        assert_eq!(
+            resolve_base_url("http://0.0.0.0:8080/fhir/", &trusted(), peer, &h),
+            "https://fhir.example.org/fhir"
+        );
+
+        let h = headers(&[
+            ("x-forwarded-host", "fhir.example.org"),
+            ("x-forwarded-port", "8443"),
+        ]);
+        assert_eq!(
+            resolve_base_url("http://0.0.0.0:8080", &trusted(), peer, &h),
+            "http://fhir.example.org:8443"
+        );
+    }
+
+    #[test]
+    fn test_forwarded_header_wins() {
+        let h = headers(&[
+            (
+                "forwarded",
+                "for=192.0.2.60;proto=https;host=\"api.example.org\", for=10.0.0.2",
+            ),
+            ("x-forwarded-host", "other.example.org"),
+        ]);
+        let peer = Some("::ffff:10.0.0.9".parse().unwrap());
+        assert_eq!(
+            resolve_base_url("http://0.0.0.0:8080", &trusted(), peer, &h),
+            "https://api.example.org"
+        );
+    }
+
+    #[test]
+    fn test_proto_only_and_malformed_headers() {
+        let peer = Some("::1".parse().unwrap());
+        let h = headers(&[("x-forwarded-proto", "https"), ("host", "fhir.local:9000")]);
+        assert_eq!(
+            resolve_base_url("http://0.0.0.0:8080", &trusted(), peer, &h),
+            "https://fhir.local:9000"
+        );
+
+        let h = headers(&[("x-forwarded-host", "evil.example.org/path@x")]);
+        assert_eq!(
+            resolve_base_url("http://0.0.0.0:8080", &trusted(), peer, &h),
+            "http://0.0.0.0:8080"
+        );
+        let h = headers(&[("x-forwarded-proto", "javascript")]);
+        assert_eq!(
+            resolve_base_url("http://0.0.0.0:8080", &trusted(), peer, &h),
+            "http://0.0.0.0:8080"
+        );
+    }
+
+    #[test]
+    fn test_client_ip_from_untrusted_peer_ignores_headers() {
+        let h = headers(&[("x-forwarded-for", "203.0.113.7")]);
+        let peer = Some("192.168.1.5".parse().unwrap());
+        assert_eq!(resolve_client_ip(&trusted(), peer, &h), peer);
+        assert_eq!(resolve_client_ip(&trusted(), None, &h), None);
+    }
+
+    #[test]
+    fn test_client_ip_skips_trusted_hops() {
+        let peer = Some("10.0.0.1".parse().unwrap());
+        // The client-most entry is forgeable; the nearest untrusted hop wins.
+        let h = headers(&[("x-forwarded-for", "1.2.3.4, 203.0.113.7, 10.0.0.2")]);
+        assert_eq!(
+            resolve_client_ip(&trusted(), peer, &h),
+            Some("203.0.113.7".parse().unwrap())
+        );
+
+        let h = headers(&[
+            (
+                "forwarded",
+                "for=\"[2001:db8::17]:4711\";proto=https, for=10.0.0.2:8080",
+            ),
+            ("x-forwarded-for", "203.0.113.7"),
+        ]);
+        assert_eq!(
+            resolve_client_ip(&trusted(), peer, &h),
+            Some("2001:db8::17".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn test_client_ip_falls_back_to_peer() {
+        let peer = Some("10.0.0.1".parse().unwrap());
+        assert_eq!(resolve_client_ip(&trusted(), peer, &HeaderMap::new()), peer);
+
+        let h = headers(&[("x-forwarded-for", "203.0.113.7, unknown")]);
+        assert_eq!(resolve_client_ip(&trusted(), peer, &h), peer);
+
+        let h = headers(&[("x-forwarded-for", "10.0.0.3")]);
+        assert_eq!(resolve_client_ip(&trusted(), peer, &h), peer);
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_entries() {
+        assert!(TrustedProxies::parse(&["not-an-ip".to_string()]).is_err());
+        assert!(TrustedProxies::parse(&[]).unwrap().is_empty());
+    }
+}
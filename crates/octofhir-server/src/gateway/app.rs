@@ -70,27 +70,24 @@ pub async fn handle_app(
     );
 
     // POST to App backend
-    let response = state
-        .gateway_router
-        .http_client()
+    let http_client = state.gateway_router.http_client();
+    let request = http_client
         .post(endpoint_url)
         .timeout(std::time::Duration::from_secs(timeout))
         .header("Content-Type", "application/json")
-        .json(&app_request)
-        .send()
-        .await
-        .map_err(|e| {
-            if e.is_timeout() {
-                GatewayError::ProxyError(format!(
-                    "App backend request timed out after {} seconds",
-                    timeout
-                ))
-            } else if e.is_connect() {
-                GatewayError::ProxyError(format!("Failed to connect to App backend: {}", e))
-            } else {
-                GatewayError::ProxyError(format!("App backend request failed: {}", e))
-            }
-        })?;
+        .json(&app_request);
+    let response = http_client.send(request).await.map_err(|e| {
+        if e.is_timeout() {
+            GatewayError::ProxyError(format!(
+                "App backend request timed out after {} seconds",
+                timeout
+            ))
+        } else if e.is_connect() {
+            GatewayError::ProxyError(format!("Failed to connect to App backend: {}", e))
+        } else {
+            GatewayError::ProxyError(format!("App backend request failed: {}", e))
+        }
+    })?;
 
     let status = response.status();
     info!(status = %status, "App backend responded");
@@ -77,9 +77,8 @@ pub async fn handle_fhirpath(
     let eval_context = EvaluationContext::new(collection, fhirpath_provider, None, None, None);
 
     // Evaluate FHIRPath expression
-    match state
-        .fhirpath_engine
-        .evaluate(fhirpath_expr, &eval_context)
+    match crate::cache::fhirpath_cache()
+        .evaluate(&state.fhirpath_engine, fhirpath_expr, &eval_context)
         .await
     {
         Ok(result) => {
@@ -83,34 +83,23 @@ pub async fn handle_proxy(
         .map_err(|e| GatewayError::ProxyError(format!("Failed to read request body: {}", e)))?;
 
     // Build the proxy request
-    let proxy_request = state
-        .gateway_router
-        .http_client()
+    let http_client = state.gateway_router.http_client();
+    let proxy_request = http_client
         .request(method.clone(), target_url)
         .headers(headers)
         .body(body_bytes.to_vec())
-        .timeout(std::time::Duration::from_secs(timeout))
-        .build()
-        .map_err(|e| GatewayError::ProxyError(format!("Failed to build proxy request: {}", e)))?;
-
-    // Execute the proxy request
-    let proxy_response = state
-        .gateway_router
-        .http_client()
-        .execute(proxy_request)
-        .await
-        .map_err(|e| {
-            if e.is_timeout() {
-                GatewayError::ProxyError(format!(
-                    "Proxy request timed out after {} seconds",
-                    timeout
-                ))
-            } else if e.is_connect() {
-                GatewayError::ProxyError(format!("Failed to connect to target: {}", e))
-            } else {
-                GatewayError::ProxyError(format!("Proxy request failed: {}", e))
-            }
-        })?;
+        .timeout(std::time::Duration::from_secs(timeout));
+
+    // Execute the proxy request, retrying transient upstream failures
+    let proxy_response = http_client.send(proxy_request).await.map_err(|e| {
+        if e.is_timeout() {
+            GatewayError::ProxyError(format!("Proxy request timed out after {} seconds", timeout))
+        } else if e.is_connect() {
+            GatewayError::ProxyError(format!("Failed to connect to target: {}", e))
+        } else {
+            GatewayError::ProxyError(format!("Proxy request failed: {}", e))
+        }
+    })?;
 
     let status = proxy_response.status();
     info!(status = %status, "Proxy request completed");
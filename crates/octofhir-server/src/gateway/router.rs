@@ -15,6 +15,7 @@ use tokio::sync::RwLock;
 use tracing::{debug, info, instrument};
 
 use crate::server::AppState;
+use octofhir_core::http::{HttpClient, HttpClientConfig};
 use octofhir_storage::{DynStorage, SearchParams};
 
 use super::error::GatewayError;
@@ -27,23 +28,25 @@ pub struct GatewayRouter {
     routes: Arc<RwLock<HashMap<String, CustomOperation>>>,
 
     /// HTTP client for proxy requests.
-    http_client: reqwest::Client,
+    http_client: HttpClient,
 }
 
 impl GatewayRouter {
-    /// Creates a new GatewayRouter.
+    /// Creates a new GatewayRouter with default outbound HTTP settings.
     pub fn new() -> Self {
+        Self::with_http_config(&HttpClientConfig::default())
+    }
+
+    /// Creates a new GatewayRouter whose proxy client uses `config`.
+    pub fn with_http_config(config: &HttpClientConfig) -> Self {
         Self {
             routes: Arc::new(RwLock::new(HashMap::new())),
-            http_client: reqwest::Client::builder()
-                .timeout(std::time::Duration::from_secs(30))
-                .build()
-                .expect("Failed to create HTTP client"),
+            http_client: HttpClient::new(config).expect("Failed to create HTTP client"),
         }
     }
 
     /// Returns the HTTP client for making proxy requests.
-    pub fn http_client(&self) -> &reqwest::Client {
+    pub fn http_client(&self) -> &HttpClient {
         &self.http_client
     }
 
@@ -1,9 +1,12 @@
 use crate::bootstrap::ADMIN_ACCESS_POLICY_ID;
+use crate::forwarded::BaseUrl;
 use crate::gateway::{App, PathValidationError, validate_app_operations};
 use crate::mapping::{IdPolicy, json_from_envelope, validate_payload_structure};
 use crate::operation_registry::{OperationStorage, PostgresOperationStorage};
-use crate::patch::{apply_fhirpath_patch, apply_json_patch};
+use crate::patch::{apply_fhirpath_patch, apply_json_patch, apply_json_patch_operations};
+use crate::request_timing::{self, Phase};
 use crate::server::SharedModelProvider;
+use crate::storage_adapter::{apply_in_transaction, map_storage_error};
 use axum::body::{Body, Bytes};
 use axum::http::Request;
 use axum::response::Response;
@@ -17,8 +20,9 @@ use include_dir::{Dir, include_dir};
 use mime_guess::MimeGuess;
 use octofhir_api::ApiError;
 use octofhir_core::ResourceType;
+use octofhir_core::paging::SYNC_PARAM;
 use octofhir_fhir_model::ModelProvider;
-use octofhir_storage::StorageError;
+use octofhir_storage::{StorageError, SyncPosition};
 use serde::{Deserialize, Serialize};
 use serde_json::{Value, json};
 use std::collections::{HashMap, HashSet};
@@ -80,13 +84,14 @@ pub async fn root() -> impl IntoResponse {
 #[tracing::instrument(name = "fhir.root", skip_all)]
 pub async fn fhir_root(
     state: State<crate::server::AppState>,
+    base_url: BaseUrl,
     headers: HeaderMap,
     params: Query<HashMap<String, String>>,
     raw: RawQuery,
 ) -> Result<impl IntoResponse, ApiError> {
     // If _type parameter is present, this is a system search
     if params.contains_key("_type") {
-        return system_search(state, headers, params, raw)
+        return system_search(state, base_url, headers, params, raw)
             .await
             .map(|r| r.into_response());
     }
@@ -117,12 +122,17 @@ pub async fn readyz(State(state): State<crate::server::AppState>) -> impl IntoRe
     // Check canonical manager is loaded
     let canonical_ok = crate::canonical::get_manager().is_some();
 
-    if db_ok && canonical_ok {
+    // Check the startup pool warmup is over
+    let pool_warm = state
+        .db_pools_warm
+        .load(std::sync::atomic::Ordering::Relaxed);
+
+    if db_ok && canonical_ok && pool_warm {
         (
             StatusCode::OK,
             Json(json!({
                 "status": "ready",
-                "checks": { "database": "ok", "canonical_manager": "ok" }
+                "checks": { "database": "ok", "canonical_manager": "ok", "database_pool": "ok" }
             })),
         )
     } else {
@@ -132,7 +142,8 @@ pub async fn readyz(State(state): State<crate::server::AppState>) -> impl IntoRe
                 "status": "not_ready",
                 "checks": {
                     "database": if db_ok { "ok" } else { "failed" },
-                    "canonical_manager": if canonical_ok { "ok" } else { "not_loaded" }
+                    "canonical_manager": if canonical_ok { "ok" } else { "not_loaded" },
+                    "database_pool": if pool_warm { "ok" } else { "warming" }
                 }
             })),
         )
@@ -154,6 +165,14 @@ pub async fn metrics(State(state): State<crate::server::AppState>) -> impl IntoR
         pool_size - pool_idle as u32,
     );
 
+    if let Some(cache) = &state.resource_cache {
+        crate::metrics::set_cache_entries("resource", cache.entry_count() as usize);
+    }
+    crate::metrics::set_cache_entries(
+        "fhirpath",
+        crate::cache::fhirpath_cache().entry_count() as usize,
+    );
+
     // Render Prometheus metrics
     match crate::metrics::render_metrics() {
         Some(output) => (
@@ -200,6 +219,57 @@ pub async fn metadata(
     (StatusCode::OK, Json(response))
 }
 
+/// GET /fhir/$versions - FHIR versions this server supports.
+///
+/// Each instance serves a single release, so the list has one entry, which is
+/// also the default. Values are the `fhirVersion` MIME parameter (`4.0`, not
+/// `4.0.1`). Returns a `Parameters` resource, or the plain JSON form of the
+/// spec when the client accepts only `application/json`.
+pub async fn versions(
+    State(state): State<crate::server::AppState>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    versions_response(state.config.fhir.mime_version(), &headers)
+}
+
+/// The body form depends on `Accept`, so shared caches must key on it too.
+fn versions_response(version: &str, headers: &HeaderMap) -> Response {
+    let body = versions_body(version, prefers_plain_json(headers));
+    (
+        StatusCode::OK,
+        [
+            (header::CACHE_CONTROL, "public, max-age=3600"),
+            (header::VARY, "Accept"),
+        ],
+        Json(body),
+    )
+        .into_response()
+}
+
+fn prefers_plain_json(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|accept| {
+            let accept = accept.to_ascii_lowercase();
+            accept.contains("application/json") && !accept.contains("application/fhir+json")
+        })
+}
+
+fn versions_body(version: &str, plain_json: bool) -> Value {
+    if plain_json {
+        json!({ "versions": [version], "default": version })
+    } else {
+        json!({
+            "resourceType": "Parameters",
+            "parameter": [
+                { "name": "version", "valueCode": version },
+                { "name": "default", "valueCode": version }
+            ]
+        })
+    }
+}
+
 /// Build the CapabilityStatement at server startup.
 ///
 /// This function is called once during initialization and the result is cached in AppState.
@@ -213,6 +283,7 @@ pub async fn build_capability_statement(
     db_pool: &sqlx_postgres::PgPool,
     resource_types: &[String],
     search_registry: &octofhir_search::SearchParameterRegistry,
+    operations_config: &crate::config::OperationsConfig,
 ) -> Value {
     use octofhir_api::{CapabilityStatementBuilder, SearchParam};
 
@@ -227,12 +298,7 @@ pub async fn build_capability_statement(
         .add_patch_format("application/fhir+json");
 
     // Apply FHIR version field
-    builder = builder.fhir_version(match fhir_version {
-        "R4" | "4.0.1" => "4.0.1",
-        "R5" | "5.0.0" => "5.0.0",
-        "R6" | "6.0.0" => "6.0.0",
-        _ => "4.3.0",
-    });
+    builder = builder.fhir_version(crate::config::fhir_release(fhir_version).unwrap_or("4.0.1"));
 
     // Fetch StructureDefinitions for profiles (search params come from registry)
     let manager = crate::canonical::get_manager();
@@ -361,7 +427,16 @@ pub async fn build_capability_statement(
         ];
 
         for op in operations {
-            if extended_ops.contains(&op.id.as_str()) {
+            if !extended_ops.contains(&op.id.as_str()) {
+                continue;
+            }
+            // Both `graphql.*` ids are served by `$graphql`
+            let code = if op.id.starts_with("graphql.") {
+                "graphql"
+            } else {
+                op.id.split('.').next_back().unwrap_or(&op.id)
+            };
+            if operations_config.is_enabled(code) {
                 // Format operation name with $ prefix
                 let op_name = op
                     .id
@@ -664,44 +739,199 @@ async fn existing_local_refs(
     }
 }
 
+/// The resource types accepted for writes. A type missing from the cached set
+/// whose StructureDefinition is loaded (e.g. from a package installed after
+/// startup or by another instance) is registered first; the storage creates
+/// its table on the first write.
+async fn known_resource_types(
+    state: &crate::server::AppState,
+    resource_type: &str,
+) -> Arc<HashSet<String>> {
+    let current = state.resource_type_set.load_full();
+    if current.is_empty() || current.contains(resource_type) {
+        return current;
+    }
+
+    let defines_resource = state
+        .model_provider
+        .get_schema(resource_type)
+        .await
+        .is_some_and(|schema| {
+            matches!(schema.kind.as_str(), "resource" | "logical")
+                && schema.derivation.as_deref() != Some("constraint")
+        });
+    if !defines_resource {
+        return current;
+    }
+
+    tracing::info!(
+        resource_type,
+        "Registering resource type from loaded StructureDefinition"
+    );
+    state.resource_type_set.rcu(|types| {
+        let mut types = HashSet::clone(types);
+        types.insert(resource_type.to_string());
+        types
+    });
+    state.resource_type_set.load_full()
+}
+
+/// Rejects a resource whose serialized JSON exceeds
+/// `server.max_resource_size_bytes` with 413. The request body limit does not
+/// cover this on its own: a patch or a Bundle entry can produce a resource far
+/// larger than the request.
+fn check_resource_size(resource: &Value, state: &crate::server::AppState) -> Result<(), ApiError> {
+    let limit = state.config.server.max_resource_size_bytes;
+    if limit == 0 {
+        return Ok(());
+    }
+    let size = octofhir_core::serialized_size(resource);
+    if size > limit {
+        return Err(ApiError::payload_too_large(format!(
+            "Resource is {size} bytes, exceeding the maximum of {limit} bytes"
+        )));
+    }
+    Ok(())
+}
+
+/// Applies `validation.reference_integrity` to a resource about to be written.
+///
+/// `known` lists `Type/id` references that count as existing without a lookup
+/// (resources created by the same transaction Bundle). In `warn` mode dangling
+/// references are logged; in `enforce` mode they reject the write with 422.
+async fn check_reference_integrity(
+    resource_type: &str,
+    payload: &Value,
+    state: &crate::server::AppState,
+    known: &HashSet<String>,
+) -> Result<(), ApiError> {
+    use crate::config::ReferenceIntegrityMode;
+
+    let mode = state.config.validation.reference_integrity;
+    if mode == ReferenceIntegrityMode::Off {
+        return Ok(());
+    }
+
+    let sites = {
+        let cfg = state.search_config.config();
+        crate::reference_integrity::collect_references(payload, resource_type, &cfg.registry)
+    };
+    if sites.is_empty() {
+        return Ok(());
+    }
+
+    let dangling = crate::reference_integrity::find_dangling(
+        &state.storage,
+        state.base_url.as_str(),
+        sites,
+        known,
+    )
+    .await
+    .map_err(|e| ApiError::internal(format!("Reference integrity check failed: {e}")))?;
+    if dangling.is_empty() {
+        return Ok(());
+    }
+
+    match mode {
+        ReferenceIntegrityMode::Enforce => Err(ApiError::UnprocessableEntity {
+            message: format!(
+                "Resource has {} reference(s) to non-existent resources",
+                dangling.len()
+            ),
+            operation_outcome: Some(crate::reference_integrity::dangling_outcome(&dangling)),
+        }),
+        _ => {
+            for site in &dangling {
+                tracing::warn!(
+                    resource_type = %resource_type,
+                    path = %site.path,
+                    reference = %site.reference,
+                    "Dangling reference accepted (reference_integrity = warn)"
+                );
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Applies `validation.mode` to the outcome of validating a resource about to
+/// be written. In `warn` mode validation errors are logged and the write goes
+/// ahead; in `enforce` mode they reject it with 422. Callers skip validation
+/// altogether in `off` mode.
+fn enforce_validation(
+    mode: crate::config::ValidationMode,
+    resource_type: &str,
+    outcome: &crate::validation::ValidationOutcome,
+    message: &str,
+) -> Result<(), ApiError> {
+    use crate::config::ValidationMode;
+
+    if outcome.valid {
+        return Ok(());
+    }
+    match mode {
+        ValidationMode::Enforce => Err(ApiError::UnprocessableEntity {
+            message: message.to_string(),
+            operation_outcome: Some(outcome.to_operation_outcome()),
+        }),
+        _ => {
+            for issue in &outcome.issues {
+                tracing::warn!(
+                    resource_type = %resource_type,
+                    code = %issue.code,
+                    location = issue.location.as_deref().unwrap_or_default(),
+                    diagnostics = %issue.diagnostics,
+                    "Invalid resource accepted (validation mode = warn)"
+                );
+            }
+            Ok(())
+        }
+    }
+}
+
 #[tracing::instrument(name = "fhir.create", skip_all, fields(resource_type = %resource_type))]
 pub async fn create_resource(
     State(state): State<crate::server::AppState>,
+    base_url: BaseUrl,
     Path(resource_type): Path<String>,
     headers: HeaderMap,
     Json(payload): Json<Value>,
 ) -> Result<Response, ApiError> {
     // Basic structural validation (resourceType match)
-    let resource_types = state.resource_type_set.load();
+    let resource_types = known_resource_types(&state, &resource_type).await;
     if let Err(e) =
         crate::validation::validate_resource(&resource_type, &payload, resource_types.as_ref())
     {
         return Err(ApiError::bad_request(format!("Validation failed: {e}")));
     }
+    check_resource_size(&payload, &state)?;
 
     // Full schema + FHIRPath constraint validation using ValidationService
     let skip_validation = should_skip_validation(&headers, &state.config.validation);
 
-    if !skip_validation {
-        let known_refs = existing_local_refs(&payload, &state).await;
-        let validation_outcome = state
-            .validation_service
-            .validate_with_known_refs(&payload, &known_refs)
-            .await;
-        if !validation_outcome.valid {
-            return Err(ApiError::UnprocessableEntity {
-                message: "Resource validation failed".to_string(),
-                operation_outcome: Some(validation_outcome.to_operation_outcome()),
-            });
-        }
-    } else {
+    let validation_mode = state.config.validation.mode_for(&resource_type);
+    if skip_validation {
         tracing::warn!(
             resource_type = %resource_type,
             operation = "create",
             "Validation skipped via X-Skip-Validation header"
         );
+    } else if validation_mode != crate::config::ValidationMode::Off {
+        let known_refs = existing_local_refs(&payload, &state).await;
+        let validation_outcome = state
+            .validation_service
+            .validate_with_known_refs(&payload, &known_refs)
+            .await;
+        enforce_validation(
+            validation_mode,
+            &resource_type,
+            &validation_outcome,
+            "Resource validation failed",
+        )?;
     }
 
+    check_reference_integrity(&resource_type, &payload, &state, &HashSet::new()).await?;
+
     let mut payload = payload;
     // FHIR R4 §3.1.0.1.3 (create): server SHALL ignore any id provided by the client
     // and assign its own. PUT (update or create-with-id) uses the URL id instead.
@@ -749,7 +979,7 @@ pub async fn create_resource(
                         &mut response_headers,
                         header::CONTENT_LOCATION,
                         fhir_versioned_resource_url(
-                            &state.base_url,
+                            base_url.as_str(),
                             &resource_type,
                             &existing.id,
                             version_id,
@@ -813,7 +1043,7 @@ pub async fn create_resource(
             insert_header_if_valid(
                 &mut response_headers,
                 header::LOCATION,
-                fhir_versioned_resource_url(&state.base_url, &resource_type, &id, &version_id),
+                fhir_versioned_resource_url(base_url.as_str(), &resource_type, &id, &version_id),
             );
 
             // ETag
@@ -1024,12 +1254,39 @@ pub struct HistoryQueryParams {
     /// Number of entries to skip for pagination
     #[serde(rename = "_offset", alias = "__offset")]
     pub offset: Option<u32>,
+    /// Opaque page token from a `cursor`-style page link; replaces `_count`
+    /// and `_offset`
+    #[serde(rename = "_cursor")]
+    pub cursor: Option<String>,
     /// Total-count mode: `accurate` computes `Bundle.total`. Omitted by default
     /// so history does not scan the full current + history set on every request.
     #[serde(rename = "_total")]
     pub total: Option<String>,
 }
 
+impl HistoryQueryParams {
+    /// Requested `(offset, count)`. The page size defaults to
+    /// `search.default_count` and is capped at `search.max_count`, as for
+    /// searches.
+    fn page(&self, cfg: &octofhir_search::SearchConfig) -> Result<(u32, u32), ApiError> {
+        let (offset, count) = match self.cursor.as_deref() {
+            Some(token) => {
+                let cursor = octofhir_core::PageCursor::decode(token).ok_or_else(|| {
+                    ApiError::bad_request(format!(
+                        "_cursor '{token}' is not a page cursor from this server"
+                    ))
+                })?;
+                (cursor.offset, Some(cursor.count))
+            }
+            None => (self.offset.unwrap_or(0), self.count),
+        };
+        let count = count
+            .unwrap_or(cfg.default_count as u32)
+            .min(cfg.max_count as u32);
+        Ok((offset, count))
+    }
+}
+
 /// Parse a FHIR instant/datetime string into OffsetDateTime
 fn parse_fhir_instant(value: &str) -> Result<time::OffsetDateTime, ApiError> {
     use time::format_description::well_known::Rfc3339;
@@ -1065,6 +1322,10 @@ pub async fn read_resource(
     headers: HeaderMap,
     Query(params): Query<HashMap<String, String>>,
 ) -> Result<Response, ApiError> {
+    if resource_type == "Binary" && crate::binary::content::wants_raw_content(&headers, &params) {
+        return read_binary_content(&state, &id, &headers).await;
+    }
+
     // Try resource cache first
     let stored: Option<std::sync::Arc<octofhir_storage::RawStoredResource>> =
         if let Some(cache) = &state.resource_cache {
@@ -1144,6 +1405,30 @@ pub async fn read_resource(
     }
 }
 
+/// GET /Binary/[id] with a non-FHIR `Accept` - the content in its native
+/// form, honouring single byte ranges.
+async fn read_binary_content(
+    state: &crate::server::AppState,
+    id: &str,
+    headers: &HeaderMap,
+) -> Result<Response, ApiError> {
+    // Keep the blob pointer so external content is streamed from the blob
+    // store once, not loaded into the resource and decoded again.
+    let stored = crate::binary::with_blob_pointers(state.storage.read("Binary", id))
+        .await
+        .map_err(map_storage_error)?
+        .ok_or_else(|| ApiError::not_found(format!("Binary with id '{id}' not found")))?;
+
+    if octofhir_api::check_if_none_match(headers, &stored.version_id) {
+        return Ok(Response::builder()
+            .status(StatusCode::NOT_MODIFIED)
+            .body(Body::empty())
+            .unwrap());
+    }
+
+    crate::binary::content::content_response(&stored, state.binary_blobs.as_ref(), headers).await
+}
+
 /// GET /[type]/[id]/_history/[vid] - Read a specific version of a resource
 #[tracing::instrument(name = "fhir.vread", skip_all, fields(resource_type = %resource_type, id = %id, version_id = %version_id))]
 pub async fn vread_resource(
@@ -1194,6 +1479,7 @@ pub async fn vread_resource(
 #[tracing::instrument(name = "fhir.history.instance", skip_all, fields(resource_type = %resource_type, id = %id))]
 pub async fn instance_history(
     State(state): State<crate::server::AppState>,
+    base_url: BaseUrl,
     Path((resource_type, id)): Path<(String, String)>,
     Query(params): Query<HistoryQueryParams>,
 ) -> Result<impl IntoResponse, ApiError> {
@@ -1240,9 +1526,14 @@ pub async fn instance_history(
     if let Some(ref at) = params.at {
         history_params.at = Some(parse_fhir_instant(at)?);
     }
-    let count = params.count.unwrap_or(100);
-    let offset = params.offset.unwrap_or(0);
-    history_params.count = Some(count);
+    let cfg = state.search_config.config();
+    let (offset, count) = params.page(&cfg)?;
+    let max_entries = state.config.fhir.max_history_entries;
+    let count = capped_history_count(max_entries, offset, count);
+    // A page ending at the cap fetches one more entry to tell whether older
+    // versions were cut off
+    let probe = max_entries > 0 && offset.saturating_add(count) >= max_entries;
+    history_params.count = Some(count + u32::from(probe));
     history_params.offset = Some(offset);
     history_params.total = parse_total_mode(params.total.as_deref());
 
@@ -1253,9 +1544,13 @@ pub async fn instance_history(
         .await
         .map_err(map_storage_error)?;
 
+    let mut raw_entries = result.entries;
+    let truncated = (probe && raw_entries.len() > count as usize)
+        || (max_entries > 0 && result.total.is_some_and(|total| total > max_entries));
+    raw_entries.truncate(count as usize);
+
     // Convert to bundle entries
-    let entries: Vec<HistoryBundleEntry> = result
-        .entries
+    let entries: Vec<HistoryBundleEntry> = raw_entries
         .into_iter()
         .map(|entry| {
             let method = match entry.method {
@@ -1278,15 +1573,20 @@ pub async fn instance_history(
         })
         .collect();
 
-    let bundle = bundle_from_history(
+    // Paging links stop at the cap; Bundle.total still reports every version
+    let link_total = match max_entries {
+        0 => result.total,
+        max => result.total.map(|total| total.min(max)),
+    };
+    let mut bundle = bundle_from_history(
         entries,
-        &state.base_url,
+        base_url.as_str(),
         &resource_type,
         Some(&id),
-        offset as usize,
-        count as usize,
-        result.total,
+        octofhir_api::Page::new(offset as usize, count as usize).with_link_style(cfg.link_style),
+        link_total,
     );
+    bundle.total = result.total.map(u64::from);
 
     let mut response_headers = HeaderMap::new();
     response_headers.insert(
@@ -1294,13 +1594,37 @@ pub async fn instance_history(
         header::HeaderValue::from_static("application/fhir+json; charset=utf-8"),
     );
 
+    if truncated {
+        tracing::warn!(
+            resource_type = %resource_type,
+            id = %id,
+            max_history_entries = max_entries,
+            "Instance history truncated at fhir.max_history_entries"
+        );
+        insert_header_if_valid(
+            &mut response_headers,
+            header::WARNING,
+            format!("199 - \"History truncated to the {max_entries} most recent versions\""),
+        );
+    }
+
     Ok((StatusCode::OK, response_headers, Json(bundle)))
 }
 
+/// Page size of an instance history request so that `offset + count` stays
+/// within `max_entries` (0 meaning no cap).
+fn capped_history_count(max_entries: u32, offset: u32, count: u32) -> u32 {
+    if max_entries == 0 {
+        return count;
+    }
+    count.min(max_entries.saturating_sub(offset))
+}
+
 /// Type history: GET /{type}/_history
 #[tracing::instrument(name = "fhir.history.type", skip_all, fields(resource_type = %resource_type))]
 pub async fn type_history(
     State(state): State<crate::server::AppState>,
+    base_url: BaseUrl,
     Path(resource_type): Path<String>,
     Query(params): Query<HistoryQueryParams>,
 ) -> Result<impl IntoResponse, ApiError> {
@@ -1326,8 +1650,8 @@ pub async fn type_history(
     if let Some(ref at) = params.at {
         history_params.at = Some(parse_fhir_instant(at)?);
     }
-    let count = params.count.unwrap_or(100);
-    let offset = params.offset.unwrap_or(0);
+    let cfg = state.search_config.config();
+    let (offset, count) = params.page(&cfg)?;
     history_params.count = Some(count);
     history_params.offset = Some(offset);
     history_params.total = parse_total_mode(params.total.as_deref());
@@ -1366,11 +1690,10 @@ pub async fn type_history(
 
     let bundle = bundle_from_history(
         entries,
-        &state.base_url,
+        base_url.as_str(),
         &resource_type,
         None,
-        offset as usize,
-        count as usize,
+        octofhir_api::Page::new(offset as usize, count as usize).with_link_style(cfg.link_style),
         result.total,
     );
 
@@ -1387,6 +1710,7 @@ pub async fn type_history(
 #[tracing::instrument(name = "fhir.history.system", skip_all)]
 pub async fn system_history(
     State(state): State<crate::server::AppState>,
+    base_url: BaseUrl,
     Query(params): Query<HistoryQueryParams>,
 ) -> Result<impl IntoResponse, ApiError> {
     use octofhir_api::{HistoryBundleEntry, HistoryBundleMethod, bundle_from_system_history};
@@ -1401,8 +1725,8 @@ pub async fn system_history(
     if let Some(ref at) = params.at {
         history_params.at = Some(parse_fhir_instant(at)?);
     }
-    let count = params.count.unwrap_or(100);
-    let offset = params.offset.unwrap_or(0);
+    let cfg = state.search_config.config();
+    let (offset, count) = params.page(&cfg)?;
     history_params.count = Some(count);
     history_params.offset = Some(offset);
     history_params.total = parse_total_mode(params.total.as_deref());
@@ -1441,9 +1765,8 @@ pub async fn system_history(
 
     let bundle = bundle_from_system_history(
         entries,
-        &state.base_url,
-        offset as usize,
-        count as usize,
+        base_url.as_str(),
+        octofhir_api::Page::new(offset as usize, count as usize).with_link_style(cfg.link_style),
         result.total,
     );
 
@@ -1459,42 +1782,47 @@ pub async fn system_history(
 #[tracing::instrument(name = "fhir.update", skip_all, fields(resource_type = %resource_type, id = %id))]
 pub async fn update_resource(
     State(state): State<crate::server::AppState>,
+    base_url: BaseUrl,
     Path((resource_type, id)): Path<(String, String)>,
     headers: HeaderMap,
     Json(payload): Json<Value>,
 ) -> Result<Response, ApiError> {
     // Basic structural validation (resourceType match)
-    let resource_types = state.resource_type_set.load();
+    let resource_types = known_resource_types(&state, &resource_type).await;
     if let Err(e) =
         crate::validation::validate_resource(&resource_type, &payload, resource_types.as_ref())
     {
         return Err(ApiError::bad_request(format!("Validation failed: {e}")));
     }
+    check_resource_size(&payload, &state)?;
 
     // Full schema + FHIRPath constraint validation using ValidationService
     let skip_validation = should_skip_validation(&headers, &state.config.validation);
 
-    if !skip_validation {
-        let known_refs = existing_local_refs(&payload, &state).await;
-        let validation_outcome = state
-            .validation_service
-            .validate_with_known_refs(&payload, &known_refs)
-            .await;
-        if !validation_outcome.valid {
-            return Err(ApiError::UnprocessableEntity {
-                message: "Resource validation failed".to_string(),
-                operation_outcome: Some(validation_outcome.to_operation_outcome()),
-            });
-        }
-    } else {
+    let validation_mode = state.config.validation.mode_for(&resource_type);
+    if skip_validation {
         tracing::warn!(
             resource_type = %resource_type,
             id = %id,
             operation = "update",
             "Validation skipped via X-Skip-Validation header"
         );
+    } else if validation_mode != crate::config::ValidationMode::Off {
+        let known_refs = existing_local_refs(&payload, &state).await;
+        let validation_outcome = state
+            .validation_service
+            .validate_with_known_refs(&payload, &known_refs)
+            .await;
+        enforce_validation(
+            validation_mode,
+            &resource_type,
+            &validation_outcome,
+            "Resource validation failed",
+        )?;
     }
 
+    check_reference_integrity(&resource_type, &payload, &state, &HashSet::new()).await?;
+
     let _rt = match resource_type.parse::<ResourceType>() {
         Ok(rt) => rt,
         Err(_) => {
@@ -1542,6 +1870,22 @@ pub async fn update_resource(
         obj.insert("id".to_string(), Value::String(id.clone()));
     }
 
+    // Identical content keeps the current version instead of writing a new one
+    if state.config.fhir.skip_noop_updates
+        && let Some(response) = noop_update_response(
+            &state,
+            base_url.as_str(),
+            &resource_type,
+            &id,
+            &payload,
+            if_match.as_deref(),
+            prefer_return,
+        )
+        .await?
+    {
+        return Ok(response);
+    }
+
     // Try update first using raw path (avoids serde round-trip).
     match state
         .storage
@@ -1558,7 +1902,7 @@ pub async fn update_resource(
                 &mut response_headers,
                 header::CONTENT_LOCATION,
                 fhir_versioned_resource_url(
-                    &state.base_url,
+                    base_url.as_str(),
                     &resource_type,
                     &id,
                     &stored.version_id,
@@ -1621,7 +1965,9 @@ pub async fn update_resource(
                 ));
             }
 
-            // Create new resource with provided ID (raw path)
+            // Create new resource with provided ID (raw path). The insert is
+            // the uniqueness check: if another request created the same id
+            // since the update missed, it fails with AlreadyExists (409).
             match state.storage.create_raw(&payload).await {
                 Ok(stored) => {
                     postprocess_resource(&resource_type, &id, &payload, &state).await?;
@@ -1633,7 +1979,7 @@ pub async fn update_resource(
                         &mut response_headers,
                         header::LOCATION,
                         fhir_versioned_resource_url(
-                            &state.base_url,
+                            base_url.as_str(),
                             &resource_type,
                             &id,
                             &stored.version_id,
@@ -1694,6 +2040,82 @@ pub async fn update_resource(
     }
 }
 
+/// Returns the current version unchanged when `payload` is equivalent to it.
+///
+/// Equivalence is decided on the canonical form, ignoring `meta.versionId` and
+/// `meta.lastUpdated`. Returns `None` (proceed with a normal update) when the
+/// resource does not exist, the content differs, or `If-Match` names another
+/// version, so the regular path still produces 404/412 as appropriate.
+async fn noop_update_response(
+    state: &crate::server::AppState,
+    base_url: &str,
+    resource_type: &str,
+    id: &str,
+    payload: &Value,
+    if_match: Option<&str>,
+    prefer_return: Option<PreferReturn>,
+) -> Result<Option<Response>, ApiError> {
+    let current = match state.storage.read(resource_type, id).await {
+        Ok(Some(current)) => current,
+        Ok(None) | Err(StorageError::Deleted { .. }) => return Ok(None),
+        Err(e) => return Err(map_storage_error(e)),
+    };
+
+    if if_match.is_some_and(|v| v != current.version_id)
+        || !octofhir_core::resources_equivalent(&current.resource, payload)
+    {
+        return Ok(None);
+    }
+
+    tracing::debug!(
+        resource_type = %resource_type,
+        id = %id,
+        version_id = %current.version_id,
+        "Update skipped: content identical to current version"
+    );
+
+    let mut response_headers = HeaderMap::new();
+    insert_header_if_valid(
+        &mut response_headers,
+        header::CONTENT_LOCATION,
+        fhir_versioned_resource_url(base_url, resource_type, id, &current.version_id),
+    );
+    insert_header_if_valid(
+        &mut response_headers,
+        header::ETAG,
+        format!("W/\"{}\"", current.version_id),
+    );
+    insert_header_if_valid(
+        &mut response_headers,
+        header::LAST_MODIFIED,
+        httpdate::fmt_http_date(
+            std::time::UNIX_EPOCH
+                + std::time::Duration::from_secs(current.last_updated.unix_timestamp() as u64),
+        ),
+    );
+    response_headers.insert(
+        header::CONTENT_TYPE,
+        header::HeaderValue::from_static("application/fhir+json; charset=utf-8"),
+    );
+
+    let response = match prefer_return {
+        Some(PreferReturn::Minimal) => (StatusCode::OK, response_headers, Json(json!({}))),
+        Some(PreferReturn::OperationOutcome) => {
+            let outcome = json!({
+                "resourceType": "OperationOutcome",
+                "issue": [{
+                    "severity": "information",
+                    "code": "informational",
+                    "diagnostics": format!("Resource unchanged: {}/{}", resource_type, id)
+                }]
+            });
+            (StatusCode::OK, response_headers, Json(outcome))
+        }
+        _ => (StatusCode::OK, response_headers, Json(current.resource)),
+    };
+    Ok(Some(response.into_response()))
+}
+
 /// Parse ETag header value (W/"version" or "version")
 fn parse_etag(header: &str) -> String {
     let trimmed = header.trim();
@@ -1710,6 +2132,7 @@ fn parse_etag(header: &str) -> String {
 #[tracing::instrument(name = "fhir.conditional_update", skip_all, fields(resource_type = %resource_type))]
 pub async fn conditional_update_resource(
     State(state): State<crate::server::AppState>,
+    base_url: BaseUrl,
     Path(resource_type): Path<String>,
     RawQuery(raw): RawQuery,
     headers: HeaderMap,
@@ -1734,20 +2157,24 @@ pub async fn conditional_update_resource(
     }
 
     // Basic structural validation (resourceType match)
-    let resource_types = state.resource_type_set.load();
+    let resource_types = known_resource_types(&state, &resource_type).await;
     if let Err(e) =
         crate::validation::validate_resource(&resource_type, &payload, resource_types.as_ref())
     {
         return Err(ApiError::bad_request(format!("Validation failed: {e}")));
     }
+    check_resource_size(&payload, &state)?;
 
     // Full schema + FHIRPath constraint validation using ValidationService
-    let validation_outcome = state.validation_service.validate(&payload).await;
-    if !validation_outcome.valid {
-        return Err(ApiError::UnprocessableEntity {
-            message: "Resource validation failed".to_string(),
-            operation_outcome: Some(validation_outcome.to_operation_outcome()),
-        });
+    let validation_mode = state.config.validation.mode_for(&resource_type);
+    if validation_mode != crate::config::ValidationMode::Off {
+        let validation_outcome = state.validation_service.validate(&payload).await;
+        enforce_validation(
+            validation_mode,
+            &resource_type,
+            &validation_outcome,
+            "Resource validation failed",
+        )?;
     }
 
     // Extract If-Match header for version checking
@@ -1785,7 +2212,7 @@ pub async fn conditional_update_resource(
                             &mut response_headers,
                             header::LOCATION,
                             fhir_versioned_resource_url(
-                                &state.base_url,
+                                base_url.as_str(),
                                 &resource_type,
                                 &stored.id,
                                 &stored.version_id,
@@ -1884,7 +2311,7 @@ pub async fn conditional_update_resource(
                             &mut response_headers,
                             header::CONTENT_LOCATION,
                             fhir_versioned_resource_url(
-                                &state.base_url,
+                                base_url.as_str(),
                                 &resource_type,
                                 &id,
                                 &stored.version_id,
@@ -2077,6 +2504,7 @@ pub async fn conditional_delete_resource(
 #[tracing::instrument(name = "fhir.patch", skip_all, fields(resource_type = %resource_type, id = %id))]
 pub async fn patch_resource(
     State(state): State<crate::server::AppState>,
+    base_url: BaseUrl,
     Path((resource_type, id)): Path<(String, String)>,
     headers: HeaderMap,
     body: Bytes,
@@ -2135,7 +2563,7 @@ pub async fn patch_resource(
     if let Some(ref expected_version) = if_match
         && expected_version != &existing.version_id
     {
-        return Err(ApiError::conflict(format!(
+        return Err(ApiError::precondition_failed(format!(
             "Version conflict: expected {}, but current is {}",
             expected_version, existing.version_id
         )));
@@ -2168,12 +2596,15 @@ pub async fn patch_resource(
     }
 
     // Full schema + FHIRPath constraint validation using ValidationService
-    let validation_outcome = state.validation_service.validate(&patched_json).await;
-    if !validation_outcome.valid {
-        return Err(ApiError::UnprocessableEntity {
-            message: "Patched resource validation failed".to_string(),
-            operation_outcome: Some(validation_outcome.to_operation_outcome()),
-        });
+    let validation_mode = state.config.validation.mode_for(&resource_type);
+    if validation_mode != crate::config::ValidationMode::Off {
+        let validation_outcome = state.validation_service.validate(&patched_json).await;
+        enforce_validation(
+            validation_mode,
+            &resource_type,
+            &validation_outcome,
+            "Patched resource validation failed",
+        )?;
     }
 
     // Verify resourceType hasn't changed (extra safety check)
@@ -2193,11 +2624,13 @@ pub async fn patch_resource(
     // Ensure id and resourceType are set in the payload
     patched_json["id"] = json!(id);
     patched_json["resourceType"] = json!(resource_type);
+    check_resource_size(&patched_json, &state)?;
 
-    // Update resource using modern storage API
+    // Store against the version the patch was applied to, so a concurrent
+    // update between read and write is never silently overwritten
     match state
         .storage
-        .update(&patched_json, if_match.as_deref())
+        .update(&patched_json, Some(&existing.version_id))
         .await
     {
         Ok(stored) => {
@@ -2209,7 +2642,7 @@ pub async fn patch_resource(
             insert_header_if_valid(
                 &mut response_headers,
                 header::CONTENT_LOCATION,
-                fhir_versioned_resource_url(&state.base_url, &resource_type, &id, version_id),
+                fhir_versioned_resource_url(base_url.as_str(), &resource_type, &id, version_id),
             );
 
             // ETag
@@ -2251,7 +2684,7 @@ pub async fn patch_resource(
                 _ => Ok((StatusCode::OK, response_headers, Json(stored.resource))),
             }
         }
-        Err(e) => Err(map_storage_error(e)),
+        Err(e) => Err(map_patch_storage_error(e, if_match.is_some())),
     }
 }
 
@@ -2259,6 +2692,7 @@ pub async fn patch_resource(
 #[tracing::instrument(name = "fhir.conditional_patch", skip_all, fields(resource_type = %resource_type))]
 pub async fn conditional_patch_resource(
     State(state): State<crate::server::AppState>,
+    base_url: BaseUrl,
     Path(resource_type): Path<String>,
     RawQuery(raw): RawQuery,
     headers: HeaderMap,
@@ -2328,7 +2762,7 @@ pub async fn conditional_patch_resource(
                 if let Some(ref expected_version) = if_match
                     && expected_version != &existing.version_id
                 {
-                    return Err(ApiError::conflict(format!(
+                    return Err(ApiError::precondition_failed(format!(
                         "Version conflict: expected {}, but current is {}",
                         expected_version, existing.version_id
                     )));
@@ -2363,12 +2797,15 @@ pub async fn conditional_patch_resource(
                 }
 
                 // Full schema + FHIRPath constraint validation using ValidationService
-                let validation_outcome = state.validation_service.validate(&patched_json).await;
-                if !validation_outcome.valid {
-                    return Err(ApiError::UnprocessableEntity {
-                        message: "Patched resource validation failed".to_string(),
-                        operation_outcome: Some(validation_outcome.to_operation_outcome()),
-                    });
+                let validation_mode = state.config.validation.mode_for(&resource_type);
+                if validation_mode != crate::config::ValidationMode::Off {
+                    let validation_outcome = state.validation_service.validate(&patched_json).await;
+                    enforce_validation(
+                        validation_mode,
+                        &resource_type,
+                        &validation_outcome,
+                        "Patched resource validation failed",
+                    )?;
                 }
 
                 // Verify resourceType hasn't changed
@@ -2388,11 +2825,12 @@ pub async fn conditional_patch_resource(
                 // Ensure id and resourceType are set in the payload
                 patched_json["id"] = json!(id.clone());
                 patched_json["resourceType"] = json!(resource_type.clone());
+                check_resource_size(&patched_json, &state)?;
 
-                // Update using modern storage API
+                // Store against the version the patch was applied to
                 match state
                     .storage
-                    .update(&patched_json, if_match.as_deref())
+                    .update(&patched_json, Some(&existing.version_id))
                     .await
                 {
                     Ok(stored) => {
@@ -2406,7 +2844,7 @@ pub async fn conditional_patch_resource(
                             &mut response_headers,
                             header::CONTENT_LOCATION,
                             fhir_versioned_resource_url(
-                                &state.base_url,
+                                base_url.as_str(),
                                 &resource_type,
                                 &id,
                                 &stored.version_id,
@@ -2455,7 +2893,7 @@ pub async fn conditional_patch_resource(
                             _ => Ok((StatusCode::OK, response_headers, Json(stored.resource))),
                         }
                     }
-                    Err(e) => Err(map_storage_error(e)),
+                    Err(e) => Err(map_patch_storage_error(e, if_match.is_some())),
                 }
             }
             _ => {
@@ -2469,6 +2907,20 @@ pub async fn conditional_patch_resource(
     }
 }
 
+/// Maps a storage error from a PATCH write.
+///
+/// The write is always guarded by the version the patch was applied to. With
+/// `If-Match` a version conflict is a failed precondition (412); without it
+/// another writer got in between read and write, which is a 409.
+fn map_patch_storage_error(e: StorageError, has_if_match: bool) -> ApiError {
+    match e {
+        StorageError::VersionConflict { .. } if !has_if_match => {
+            ApiError::conflict("Resource was modified concurrently, retry the patch")
+        }
+        e => map_storage_error(e),
+    }
+}
+
 /// Query parameters for search result modification
 #[derive(Debug, Clone, Default, serde::Deserialize)]
 pub struct SearchResultParams {
@@ -2489,6 +2941,7 @@ pub struct SearchResultParams {
 #[tracing::instrument(name = "fhir.search", skip_all, fields(resource_type = %resource_type))]
 pub async fn search_resource(
     State(state): State<crate::server::AppState>,
+    base_url: BaseUrl,
     headers: HeaderMap,
     Path(resource_type): Path<String>,
     Query(params): Query<HashMap<String, String>>,
@@ -2502,10 +2955,7 @@ pub async fn search_resource(
     }
 
     // Parse Prefer header for handling mode (strict/lenient)
-    let unknown_param_handling = headers
-        .get("Prefer")
-        .and_then(|h| h.to_str().ok())
-        .map(octofhir_search::UnknownParamHandling::from_prefer_header);
+    let unknown_param_handling = Some(unknown_param_handling(&headers, &state.config.search));
 
     let raw_q = raw.unwrap_or_default();
     let debug_request = search_debug_request(&state.config.search, &headers, &raw_q);
@@ -2513,29 +2963,37 @@ pub async fn search_resource(
     let cfg = state.search_config.config();
 
     // Parse query string to SearchParams
-    let search_params =
+    let mut search_params =
         octofhir_search::parse_query_string(&raw_q, cfg.default_count as u32, cfg.max_count as u32);
+    cfg.default_sort.apply(&resource_type, &mut search_params);
+    check_sync_search(&raw_q, &search_params)?;
 
     // Strip result params from query suffix for pagination links
     let suffix = build_query_suffix_for_links(&raw_q);
     let offset = search_params.offset.unwrap_or(0) as usize;
-    let count = search_params.count.unwrap_or(10) as usize;
+    let count = search_params.count.unwrap_or(cfg.default_count as u32) as usize;
 
     // Execute search with raw JSON optimization and handling mode
-    let result = octofhir_db_postgres::queries::execute_search_raw_with_terminology_options(
-        &state.read_db_pool,
-        &resource_type,
-        &search_params,
-        Some(&cfg.registry),
-        state.query_cache.as_deref(),
-        state.terminology_provider.as_ref(),
-        octofhir_db_postgres::queries::RawSearchOptions {
-            unknown_param_handling,
-            collect_debug_plan: debug_request.collect_plan(),
-            collect_explain_plan: debug_request.collect_explain_plan(),
-            collect_explain_analyze: debug_request.collect_explain_analyze(),
-            max_valueset_expansion: Some(state.config.search.max_valueset_expansion),
-        },
+    let mut result = request_timing::timed(
+        Phase::Query,
+        octofhir_db_postgres::queries::execute_search_raw_with_terminology_options(
+            search_pool(&state, &search_params),
+            &resource_type,
+            &search_params,
+            Some(&cfg.registry),
+            state.query_cache.as_deref(),
+            state.terminology_provider.as_ref(),
+            octofhir_db_postgres::queries::RawSearchOptions {
+                unknown_param_handling,
+                collect_debug_plan: debug_request.collect_plan(),
+                collect_explain_plan: debug_request.collect_explain_plan(),
+                collect_explain_analyze: debug_request.collect_explain_analyze(),
+                max_valueset_expansion: Some(state.config.search.max_valueset_expansion),
+                max_included: Some(state.config.search.max_included),
+                max_chain_depth: Some(state.config.search.max_chain_depth),
+                count_only: is_summary_count(&search_params),
+            },
+        ),
     )
     .await
     .map_err(|e| ApiError::bad_request(e.to_string()))?;
@@ -2552,9 +3010,16 @@ pub async fn search_resource(
         )
             .into_response());
     }
+    inflate_raw_resources(&state, &mut result.entries).await?;
+    inflate_raw_resources(&state, &mut result.included).await?;
 
-    let (total, total_is_exact) =
-        resolved_search_total(result.total, result.has_more, offset, result.entries.len());
+    let (total, total_is_exact) = match search_params.sync {
+        // Earlier pages of a sync are not counted, so only an explicit
+        // total means anything
+        Some(_) => (result.total.map(|t| t as usize), result.total.is_some()),
+        None => resolved_search_total(result.total, result.has_more, offset, result.entries.len()),
+    };
+    let sync_last = sync_page_end(&result);
 
     // Convert main entries to RawJson
     let (resources, ids): (Vec<_>, Vec<_>) = result
@@ -2581,36 +3046,64 @@ pub async fn search_resource(
         None
     };
 
-    let bundle = octofhir_api::bundle_from_search_raw_with_warnings_and_pagination(
+    let mut bundle = octofhir_api::bundle_from_search_raw_with_warnings_and_pagination(
         total,
         total_is_exact,
         result.has_more,
         resources,
         ids,
         included,
-        &state.base_url,
+        base_url.as_str(),
         &resource_type,
-        offset,
-        count,
+        octofhir_api::Page::new(offset, count).with_link_style(cfg.link_style),
         suffix.as_deref(),
         warnings,
+    )
+    .with_match_scores(result.scores)
+    .with_sync_deletions(
+        base_url.as_str(),
+        &resource_type,
+        result.deleted.into_iter().map(sync_deletion_entry),
     );
+    if let Some(position) = &search_params.sync {
+        bundle.link = octofhir_api::build_sync_links(
+            base_url.as_str(),
+            &resource_type,
+            count,
+            match position {
+                SyncPosition::Start => None,
+                SyncPosition::After(cursor) => Some(cursor),
+            },
+            sync_last.as_ref(),
+            suffix.as_deref(),
+        );
+    }
 
     // Apply _summary and _elements filters if present
     let has_result_params = params.contains_key("_summary") || params.contains_key("_elements");
     if has_result_params {
         let bundle_value = apply_result_params(bundle, &params)?;
-        return Ok((StatusCode::OK, Json(bundle_value)).into_response());
+        return Ok(search_bundle_response(bundle_value));
     }
 
     // Return Bundle directly - RawJson entries serialize efficiently via RawValue
-    Ok((StatusCode::OK, Json(bundle)).into_response())
+    Ok(search_bundle_response(bundle))
+}
+
+/// 200 response carrying a search Bundle. Serializing a page with many
+/// included resources can take a noticeable share of the request, so it is
+/// recorded as the serialization phase of the request timings.
+fn search_bundle_response<T: Serialize>(bundle: T) -> Response {
+    request_timing::timed_sync(Phase::Serialization, || {
+        (StatusCode::OK, Json(bundle)).into_response()
+    })
 }
 
 /// POST /[type]/_search - Search via POST with form-encoded parameters
 #[tracing::instrument(name = "fhir.search.post", skip_all, fields(resource_type = %resource_type))]
 pub async fn search_resource_post(
     State(state): State<crate::server::AppState>,
+    base_url: BaseUrl,
     headers: HeaderMap,
     Path(resource_type): Path<String>,
     RawQuery(raw): RawQuery,
@@ -2642,35 +3135,40 @@ pub async fn search_resource_post(
     let raw_q = strip_search_debug_params(&raw_q);
 
     let cfg = state.search_config.config();
-    let unknown_param_handling = headers
-        .get("Prefer")
-        .and_then(|h| h.to_str().ok())
-        .map(octofhir_search::UnknownParamHandling::from_prefer_header);
+    let unknown_param_handling = Some(unknown_param_handling(&headers, &state.config.search));
 
     // Parse query string to SearchParams
-    let search_params =
+    let mut search_params =
         octofhir_search::parse_query_string(&raw_q, cfg.default_count as u32, cfg.max_count as u32);
+    cfg.default_sort.apply(&resource_type, &mut search_params);
+    check_sync_search(&raw_q, &search_params)?;
 
     // Strip result params from query suffix for pagination links
     let suffix = build_query_suffix_for_links(&raw_q);
     let offset = search_params.offset.unwrap_or(0) as usize;
-    let count = search_params.count.unwrap_or(10) as usize;
+    let count = search_params.count.unwrap_or(cfg.default_count as u32) as usize;
 
     // Execute search with raw JSON optimization and terminology modifier support.
-    let result = octofhir_db_postgres::queries::execute_search_raw_with_terminology_options(
-        &state.read_db_pool,
-        &resource_type,
-        &search_params,
-        Some(&cfg.registry),
-        state.query_cache.as_deref(),
-        state.terminology_provider.as_ref(),
-        octofhir_db_postgres::queries::RawSearchOptions {
-            unknown_param_handling,
-            collect_debug_plan: debug_request.collect_plan(),
-            collect_explain_plan: debug_request.collect_explain_plan(),
-            collect_explain_analyze: debug_request.collect_explain_analyze(),
-            max_valueset_expansion: Some(state.config.search.max_valueset_expansion),
-        },
+    let mut result = request_timing::timed(
+        Phase::Query,
+        octofhir_db_postgres::queries::execute_search_raw_with_terminology_options(
+            search_pool(&state, &search_params),
+            &resource_type,
+            &search_params,
+            Some(&cfg.registry),
+            state.query_cache.as_deref(),
+            state.terminology_provider.as_ref(),
+            octofhir_db_postgres::queries::RawSearchOptions {
+                unknown_param_handling,
+                collect_debug_plan: debug_request.collect_plan(),
+                collect_explain_plan: debug_request.collect_explain_plan(),
+                collect_explain_analyze: debug_request.collect_explain_analyze(),
+                max_valueset_expansion: Some(state.config.search.max_valueset_expansion),
+                max_included: Some(state.config.search.max_included),
+                max_chain_depth: Some(state.config.search.max_chain_depth),
+                count_only: is_summary_count(&search_params),
+            },
+        ),
     )
     .await
     .map_err(|e| ApiError::bad_request(e.to_string()))?;
@@ -2687,9 +3185,16 @@ pub async fn search_resource_post(
         )
             .into_response());
     }
+    inflate_raw_resources(&state, &mut result.entries).await?;
+    inflate_raw_resources(&state, &mut result.included).await?;
 
-    let (total, total_is_exact) =
-        resolved_search_total(result.total, result.has_more, offset, result.entries.len());
+    let (total, total_is_exact) = match search_params.sync {
+        // Earlier pages of a sync are not counted, so only an explicit
+        // total means anything
+        Some(_) => (result.total.map(|t| t as usize), result.total.is_some()),
+        None => resolved_search_total(result.total, result.has_more, offset, result.entries.len()),
+    };
+    let sync_last = sync_page_end(&result);
 
     // Convert main entries to RawJson
     let (resources, ids): (Vec<_>, Vec<_>) = result
@@ -2716,20 +3221,38 @@ pub async fn search_resource_post(
         None
     };
 
-    let bundle = octofhir_api::bundle_from_search_raw_with_warnings_and_pagination(
+    let mut bundle = octofhir_api::bundle_from_search_raw_with_warnings_and_pagination(
         total,
         total_is_exact,
         result.has_more,
         resources,
         ids,
         included,
-        &state.base_url,
+        base_url.as_str(),
         &resource_type,
-        offset,
-        count,
+        octofhir_api::Page::new(offset, count).with_link_style(cfg.link_style),
         suffix.as_deref(),
         warnings,
+    )
+    .with_match_scores(result.scores)
+    .with_sync_deletions(
+        base_url.as_str(),
+        &resource_type,
+        result.deleted.into_iter().map(sync_deletion_entry),
     );
+    if let Some(position) = &search_params.sync {
+        bundle.link = octofhir_api::build_sync_links(
+            base_url.as_str(),
+            &resource_type,
+            count,
+            match position {
+                SyncPosition::Start => None,
+                SyncPosition::After(cursor) => Some(cursor),
+            },
+            sync_last.as_ref(),
+            suffix.as_deref(),
+        );
+    }
 
     let result_params: HashMap<String, String> = ["_summary", "_elements"]
         .into_iter()
@@ -2744,17 +3267,29 @@ pub async fn search_resource_post(
 
     if !result_params.is_empty() {
         let bundle_value = apply_result_params(bundle, &result_params)?;
-        return Ok((StatusCode::OK, Json(bundle_value)).into_response());
+        return Ok(search_bundle_response(bundle_value));
     }
 
     // Return Bundle directly - RawJson entries serialize efficiently via RawValue
-    Ok((StatusCode::OK, Json(bundle)).into_response())
+    Ok(search_bundle_response(bundle))
 }
 
+/// Deepest merged page a system search serves. Every type is searched for
+/// `_offset + _count` rows so the merge can order them; clients paging past
+/// this should narrow the search instead.
+const SYSTEM_SEARCH_MAX_WINDOW: usize = 10_000;
+
 /// GET / or GET /?_type=Patient,Observation - System-level search
+///
+/// Runs the search against each type in `_type` and merges the matches into
+/// one searchset. Matches are ordered by `_sort` (only `_lastUpdated` and
+/// `_id`, the elements every type shares; default `-_lastUpdated`), then by
+/// resource type and id, so pages are stable across requests. `total` is the
+/// sum of the per-type totals.
 #[tracing::instrument(name = "fhir.search.system", skip_all)]
 pub async fn system_search(
     State(state): State<crate::server::AppState>,
+    base_url: BaseUrl,
     headers: HeaderMap,
     Query(params): Query<HashMap<String, String>>,
     RawQuery(raw): RawQuery,
@@ -2764,70 +3299,100 @@ pub async fn system_search(
         ApiError::bad_request("System search requires _type parameter to specify resource types")
     })?;
 
-    let types: Vec<&str> = types_param.split(',').map(|s| s.trim()).collect();
-
-    if types.is_empty() {
-        return Err(ApiError::bad_request(
-            "_type parameter must specify at least one resource type",
-        ));
+    let types = parse_system_search_types(types_param)?;
+    for resource_type in &types {
+        ensure_resource_type_allowed(resource_type)?;
     }
 
     let raw_q = raw.unwrap_or_default();
     let debug_request = search_debug_request(&state.config.search, &headers, &raw_q);
     let raw_q = strip_search_debug_params(&raw_q);
     let cfg = state.search_config.config();
-    let unknown_param_handling = headers
-        .get("Prefer")
-        .and_then(|h| h.to_str().ok())
-        .map(octofhir_search::UnknownParamHandling::from_prefer_header);
-
-    // Collect raw entries: (resource_json, id, resource_type)
-    let mut all_entries: Vec<(String, String, String)> = Vec::new();
-    let mut total_count: usize = 0;
-    let mut debug_entries: Vec<(String, octofhir_storage::RawSearchDebug)> = Vec::new();
+    let unknown_param_handling = Some(unknown_param_handling(&headers, &state.config.search));
 
     // Parse search params once
-    let search_params =
+    let mut search_params =
         octofhir_search::parse_query_string(&raw_q, cfg.default_count as u32, cfg.max_count as u32);
+    search_params.parameters.remove("_type");
+    let sort = system_search_sort(search_params.sort.as_deref(), &cfg.default_sort)?;
+    let count = search_params.count.unwrap_or(cfg.default_count as u32) as usize;
+    let offset = search_params.offset.unwrap_or(0) as usize;
+    let window = offset + count;
+    if window > SYSTEM_SEARCH_MAX_WINDOW {
+        return Err(ApiError::bad_request(format!(
+            "System search can page at most {SYSTEM_SEARCH_MAX_WINDOW} results deep; narrow the search"
+        )));
+    }
 
-    // Search each resource type using raw path (skips JSONB → Value round-trip)
-    for type_name in &types {
-        if type_name.parse::<ResourceType>().is_err() {
-            tracing::warn!("Skipping unknown resource type: {}", type_name);
-            continue;
-        }
+    // Includes are resolved for the final page only
+    let include_params: Vec<(String, Vec<String>)> = search_params
+        .parameters
+        .iter()
+        .filter(|(key, _)| key.starts_with("_include") || key.starts_with("_revinclude"))
+        .map(|(key, values)| (key.clone(), values.clone()))
+        .collect();
 
-        match octofhir_db_postgres::queries::execute_search_raw_with_terminology_options(
-            &state.read_db_pool,
-            type_name,
-            &search_params,
-            Some(&cfg.registry),
-            state.query_cache.as_deref(),
-            state.terminology_provider.as_ref(),
-            octofhir_db_postgres::queries::RawSearchOptions {
-                unknown_param_handling,
-                collect_debug_plan: debug_request.collect_plan(),
-                collect_explain_plan: debug_request.collect_explain_plan(),
-                collect_explain_analyze: debug_request.collect_explain_analyze(),
-                max_valueset_expansion: Some(state.config.search.max_valueset_expansion),
-            },
+    let mut window_params = search_params.clone();
+    window_params
+        .parameters
+        .retain(|key, _| !key.starts_with("_include") && !key.starts_with("_revinclude"));
+    window_params.sort = Some(sort.clone());
+    window_params.count = Some(window as u32);
+    window_params.offset = None;
+    window_params.total = Some(
+        search_params
+            .total
+            .unwrap_or(octofhir_storage::TotalMode::Accurate),
+    );
+
+    let options = octofhir_db_postgres::queries::RawSearchOptions {
+        unknown_param_handling,
+        collect_debug_plan: debug_request.collect_plan(),
+        collect_explain_plan: debug_request.collect_explain_plan(),
+        collect_explain_analyze: debug_request.collect_explain_analyze(),
+        max_valueset_expansion: Some(state.config.search.max_valueset_expansion),
+        max_included: Some(state.config.search.max_included),
+        max_chain_depth: Some(state.config.search.max_chain_depth),
+        count_only: is_summary_count(&search_params),
+    };
+
+    let mut matches: Vec<octofhir_storage::RawStoredResource> = Vec::new();
+    let mut total: Option<usize> = Some(0);
+    let mut total_is_exact = true;
+    let mut any_has_more = false;
+    let mut warnings: Vec<String> = Vec::new();
+    let mut debug_entries: Vec<(String, octofhir_storage::RawSearchDebug)> = Vec::new();
+
+    for type_name in &types {
+        let result = request_timing::timed(
+            Phase::Query,
+            octofhir_db_postgres::queries::execute_search_raw_with_terminology_options(
+                &state.read_db_pool,
+                type_name,
+                &window_params,
+                Some(&cfg.registry),
+                state.query_cache.as_deref(),
+                state.terminology_provider.as_ref(),
+                options,
+            ),
         )
         .await
-        {
-            Ok(result) => {
-                total_count += result.total.unwrap_or(result.entries.len() as u32) as usize;
-                if let Some(debug) = result.debug {
-                    debug_entries.push(((*type_name).to_string(), debug));
-                }
-                for entry in result.entries {
-                    all_entries.push((entry.resource_json, entry.id, entry.resource_type));
-                }
-            }
-            Err(e) => {
-                tracing::warn!("Search failed for type {}: {}", type_name, e);
-                // Continue with other types
+        .map_err(map_storage_error)?;
+
+        let (type_total, exact) =
+            resolved_search_total(result.total, result.has_more, 0, result.entries.len());
+        total = total.zip(type_total).map(|(sum, n)| sum + n);
+        total_is_exact &= exact;
+        any_has_more |= result.has_more;
+        for warning in result.warnings {
+            if !warnings.contains(&warning) {
+                warnings.push(warning);
             }
         }
+        if let Some(debug) = result.debug {
+            debug_entries.push((type_name.clone(), debug));
+        }
+        matches.extend(result.entries);
     }
 
     if debug_request.collect_plan() {
@@ -2842,80 +3407,350 @@ pub async fn system_search(
             .into_response());
     }
 
-    // Apply _count limit to combined results
-    let count = params
-        .get("_count")
-        .and_then(|c| c.parse::<usize>().ok())
-        .unwrap_or(cfg.default_count)
-        .min(cfg.max_count);
+    matches.sort_by(|a, b| compare_system_search_matches(a, b, &sort));
+    let has_more = any_has_more || matches.len() > window;
+    let mut page: Vec<octofhir_storage::RawStoredResource> =
+        matches.into_iter().skip(offset).take(count).collect();
 
-    let offset = params
-        .get("_offset")
-        .and_then(|o| o.parse::<usize>().ok())
-        .unwrap_or(0);
-
-    // Paginate combined results and build bundle entries
-    let paginated: Vec<octofhir_api::BundleEntry> = all_entries
-        .into_iter()
-        .skip(offset)
-        .take(count)
-        .map(|(resource_json, id, rt)| {
-            let full_url = Some(format!(
-                "{}/{}/{}",
-                state.base_url.trim_end_matches('/'),
-                rt,
-                id
-            ));
-            octofhir_api::BundleEntry {
-                full_url,
-                resource: Some(octofhir_api::RawJson::from_string(resource_json)),
-                search: Some(octofhir_api::BundleEntrySearch {
-                    mode: "match".to_string(),
-                    score: None,
-                }),
-                request: None,
-                response: None,
-            }
-        })
-        .collect();
+    let mut included = if include_params.is_empty() {
+        Vec::new()
+    } else {
+        system_search_includes(&state, &cfg, &page, &include_params, options, &mut warnings).await?
+    };
+    inflate_raw_resources(&state, &mut page).await?;
+    inflate_raw_resources(&state, &mut included).await?;
 
-    // Build system search bundle
-    let primary_type = types.first().unwrap_or(&"Resource");
+    let to_entry = |r: octofhir_storage::RawStoredResource| octofhir_api::RawIncludedEntry {
+        resource: octofhir_api::RawJson::from_string(r.resource_json),
+        resource_type: r.resource_type,
+        id: r.id,
+    };
     let suffix = build_query_suffix_for_links(&raw_q);
-    let links = octofhir_api::build_search_links(
-        total_count,
-        &state.base_url,
-        primary_type,
-        offset,
-        count,
+    let bundle = octofhir_api::bundle_from_system_search_raw(
+        total.filter(|_| total_is_exact),
+        total_is_exact,
+        has_more,
+        page.into_iter().map(to_entry).collect(),
+        included.into_iter().map(to_entry).collect(),
+        base_url.as_str(),
+        octofhir_api::Page::new(offset, count).with_link_style(cfg.link_style),
         suffix.as_deref(),
+        (!warnings.is_empty()).then(|| octofhir_api::OperationOutcome::warnings(warnings)),
     );
-    let bundle = octofhir_api::Bundle::searchset(total_count as u64, paginated, links);
 
     if params.contains_key("_summary") || params.contains_key("_elements") {
         let bundle_value = apply_result_params(bundle, &params)?;
-        return Ok((StatusCode::OK, Json(bundle_value)).into_response());
+        return Ok(search_bundle_response(bundle_value));
     }
 
-    Ok((StatusCode::OK, Json(bundle)).into_response())
+    Ok(search_bundle_response(bundle))
+}
+
+/// Reject resource types left out of `fhir.resource_types` with 404.
+fn ensure_resource_type_allowed(resource_type: &str) -> Result<(), ApiError> {
+    if octofhir_storage::is_resource_type_allowed(resource_type) {
+        Ok(())
+    } else {
+        Err(ApiError::not_found(format!(
+            "Resource type '{resource_type}' is not supported by this server"
+        )))
+    }
+}
+
+/// Resource type of a bundle entry `request.url` (`Patient`, `Patient/1`,
+/// `Patient?identifier=x`); `None` for system-level urls (`metadata`, `$op`).
+fn entry_resource_type(url: &str) -> Option<&str> {
+    url.split(['/', '?'])
+        .next()
+        .filter(|segment| segment.starts_with(|c: char| c.is_ascii_uppercase()))
 }
 
-/// Build query suffix for pagination links, stripping result params
+/// Parse `_type` into distinct, known resource types (in request order).
+fn parse_system_search_types(types_param: &str) -> Result<Vec<String>, ApiError> {
+    let mut types: Vec<String> = Vec::new();
+    for type_name in types_param.split(',').map(str::trim) {
+        if type_name.is_empty() {
+            continue;
+        }
+        if type_name.parse::<ResourceType>().is_err() {
+            return Err(ApiError::bad_request(format!(
+                "Unknown resource type '{type_name}' in _type"
+            )));
+        }
+        if !types.iter().any(|t| t == type_name) {
+            types.push(type_name.to_string());
+        }
+    }
+    if types.is_empty() {
+        return Err(ApiError::bad_request(
+            "_type parameter must specify at least one resource type",
+        ));
+    }
+    Ok(types)
+}
+
+/// Merge order for a system search. Only elements every resource type has
+/// can be compared across types. Without `_sort`, the leading `_lastUpdated`
+/// and `_id` fields of the global default sort apply, or newest first when it
+/// has none.
+fn system_search_sort(
+    sort: Option<&[octofhir_storage::SortParam]>,
+    default_sort: &octofhir_search::DefaultSort,
+) -> Result<Vec<octofhir_storage::SortParam>, ApiError> {
+    let Some(sort) = sort.filter(|sort| !sort.is_empty()) else {
+        let sort = merge_sort_keys(default_sort.global());
+        if sort.is_empty() {
+            return Ok(vec![octofhir_storage::SortParam::new("_lastUpdated", true)]);
+        }
+        return Ok(sort);
+    };
+    if let Some(field) = sort
+        .iter()
+        .find(|s| !matches!(s.field.as_str(), "_lastUpdated" | "_id"))
+    {
+        return Err(ApiError::bad_request(format!(
+            "System search can only sort by _lastUpdated and _id, not '{}'",
+            field.field
+        )));
+    }
+    Ok(sort.to_vec())
+}
+
+/// The leading fields of `sort` that stored metadata can compare across
+/// searches: `_lastUpdated` and `_id`.
+fn merge_sort_keys(sort: &[octofhir_storage::SortParam]) -> Vec<octofhir_storage::SortParam> {
+    sort.iter()
+        .take_while(|s| matches!(s.field.as_str(), "_lastUpdated" | "_id"))
+        .cloned()
+        .collect()
+}
+
+/// Order matches merged from several searches by `sort`, then resource type
+/// and id.
+fn compare_system_search_matches(
+    a: &octofhir_storage::RawStoredResource,
+    b: &octofhir_storage::RawStoredResource,
+    sort: &[octofhir_storage::SortParam],
+) -> std::cmp::Ordering {
+    for s in sort {
+        let ord = match s.field.as_str() {
+            "_lastUpdated" => a.last_updated.cmp(&b.last_updated),
+            _ => a.id.cmp(&b.id),
+        };
+        let ord = if s.descending { ord.reverse() } else { ord };
+        if ord.is_ne() {
+            return ord;
+        }
+    }
+    a.resource_type
+        .cmp(&b.resource_type)
+        .then_with(|| a.id.cmp(&b.id))
+}
+
+/// Resolve `_include`/`_revinclude` for the matches on one system search page
+/// by re-running each type's search restricted to the page ids.
+///
+/// The types share one `max_included` budget: each search may only add what
+/// the ones before it left.
+async fn system_search_includes(
+    state: &crate::server::AppState,
+    cfg: &octofhir_search::SearchConfig,
+    page: &[octofhir_storage::RawStoredResource],
+    include_params: &[(String, Vec<String>)],
+    options: octofhir_db_postgres::queries::RawSearchOptions,
+    warnings: &mut Vec<String>,
+) -> Result<Vec<octofhir_storage::RawStoredResource>, ApiError> {
+    let max_included = options
+        .max_included
+        .unwrap_or(octofhir_db_postgres::queries::DEFAULT_MAX_INCLUDED);
+    let mut ids_by_type: Vec<(&str, Vec<&str>)> = Vec::new();
+    for m in page {
+        match ids_by_type
+            .iter_mut()
+            .find(|(rt, _)| *rt == m.resource_type)
+        {
+            Some((_, ids)) => ids.push(&m.id),
+            None => ids_by_type.push((&m.resource_type, vec![&m.id])),
+        }
+    }
+
+    let mut seen: HashSet<(String, String)> = page
+        .iter()
+        .map(|m| (m.resource_type.clone(), m.id.clone()))
+        .collect();
+    let mut included = Vec::new();
+    let limit_warning = octofhir_db_postgres::queries::included_limit_warning(max_included);
+    for (resource_type, ids) in ids_by_type {
+        let remaining = max_included - included.len();
+        let mut params = octofhir_storage::SearchParams::new()
+            .with_param("_id", ids.join(","))
+            .with_count(ids.len() as u32);
+        for (key, values) in include_params {
+            params.parameters.insert(key.clone(), values.clone());
+        }
+        let result = request_timing::timed(
+            Phase::Query,
+            octofhir_db_postgres::queries::execute_search_raw_with_terminology_options(
+                &state.read_db_pool,
+                resource_type,
+                &params,
+                Some(&cfg.registry),
+                state.query_cache.as_deref(),
+                state.terminology_provider.as_ref(),
+                octofhir_db_postgres::queries::RawSearchOptions {
+                    collect_debug_plan: false,
+                    collect_explain_plan: false,
+                    collect_explain_analyze: false,
+                    count_only: false,
+                    max_included: Some(remaining),
+                    ..options
+                },
+            ),
+        )
+        .await
+        .map_err(map_storage_error)?;
+        // The search reports its own cut against the remaining budget
+        let sub_limit_warning = octofhir_db_postgres::queries::included_limit_warning(remaining);
+        let mut truncated = false;
+        for warning in result.warnings {
+            if warning == sub_limit_warning {
+                truncated = true;
+            } else if !warnings.contains(&warning) {
+                warnings.push(warning);
+            }
+        }
+        for inc in result.included {
+            if !seen.insert((inc.resource_type.clone(), inc.id.clone())) {
+                continue;
+            }
+            if included.len() == max_included {
+                truncated = true;
+                break;
+            }
+            included.push(inc);
+        }
+        if truncated {
+            if !warnings.contains(&limit_warning) {
+                warnings.push(limit_warning);
+            }
+            return Ok(included);
+        }
+    }
+    Ok(included)
+}
+
+/// Build query suffix for pagination links, stripping result params.
+///
+/// The query is decoded and re-encoded parameter by parameter so repeats,
+/// modifiers and unencoded client input survive into every page URL.
 fn build_query_suffix_for_links(raw_q: &str) -> Option<String> {
-    if raw_q.is_empty() {
-        return None;
+    octofhir_api::link_query_suffix(
+        url::form_urlencoded::parse(raw_q.as_bytes())
+            .filter(|(name, _)| name != "_summary" && name != "_elements"),
+    )
+}
+
+/// Puts externalized Binary and attachment content back into resources read
+/// straight from the resource tables, below [`crate::binary::BinaryBlobStorage`].
+async fn inflate_raw_resources(
+    state: &crate::server::AppState,
+    resources: &mut [octofhir_storage::RawStoredResource],
+) -> Result<(), ApiError> {
+    let Some(blobs) = &state.binary_blobs else {
+        return Ok(());
+    };
+    for raw in resources {
+        blobs.inflate_raw(raw).await.map_err(map_storage_error)?;
     }
-    let filtered: Vec<_> = raw_q
-        .split('&')
-        .filter(|kv| {
-            !kv.starts_with("_count=")
-                && !kv.starts_with("_offset=")
-                && !kv.starts_with("_summary=")
-                && !kv.starts_with("_elements=")
-        })
+    Ok(())
+}
+
+/// Rejects a `_sync` search that cannot be served as one: a token this
+/// server did not issue, or paging and sort parameters that would break the
+/// `(_lastUpdated, _id)` order it pages by.
+fn check_sync_search(raw_q: &str, params: &octofhir_storage::SearchParams) -> Result<(), ApiError> {
+    let names: Vec<_> = url::form_urlencoded::parse(raw_q.as_bytes())
+        .map(|(name, _)| name)
         .collect();
-    let s = filtered.join("&");
-    if s.is_empty() { None } else { Some(s) }
+    if !names.iter().any(|name| name == SYNC_PARAM) {
+        return Ok(());
+    }
+    if params.sync.is_none() {
+        return Err(ApiError::bad_request(
+            "_sync must be 'true' or a sync cursor from this server",
+        ));
+    }
+    match names
+        .iter()
+        .find(|name| matches!(name.as_ref(), "_sort" | "_offset" | "_cursor"))
+    {
+        Some(name) => Err(ApiError::bad_request(format!(
+            "_sync orders results by _lastUpdated and _id and cannot be combined with {name}"
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Cursor after the last change on a sync page, whether a live entry or a
+/// delete.
+fn sync_page_end(result: &octofhir_storage::RawSearchResult) -> Option<octofhir_api::SyncCursor> {
+    let live = result
+        .entries
+        .last()
+        .map(|e| octofhir_api::SyncCursor::new(e.last_updated, e.id.clone()));
+    let deleted = result
+        .deleted
+        .last()
+        .map(|d| octofhir_api::SyncCursor::new(d.deleted_at, d.id.clone()));
+    live.into_iter()
+        .chain(deleted)
+        .max_by(|a, b| (a.last_updated_micros, &a.id).cmp(&(b.last_updated_micros, &b.id)))
+}
+
+/// `(id, version_id, last_modified)` of a delete on a sync page.
+fn sync_deletion_entry(deleted: octofhir_storage::SyncDeletion) -> (String, String, String) {
+    let last_modified = deleted
+        .deleted_at
+        .format(&time::format_description::well_known::Rfc3339)
+        .unwrap_or_default();
+    (deleted.id, deleted.version_id, last_modified)
+}
+
+/// Pool a search runs on. Sync pages read the primary: the horizon they
+/// stop at is computed from the transactions open there, which a replica
+/// cannot see.
+fn search_pool<'a>(
+    state: &'a crate::server::AppState,
+    params: &octofhir_storage::SearchParams,
+) -> &'a sqlx_postgres::PgPool {
+    if params.sync.is_some() {
+        &state.db_pool
+    } else {
+        &state.read_db_pool
+    }
+}
+
+/// True for `_summary=count`, which is answered from the count query alone.
+fn is_summary_count(params: &octofhir_storage::SearchParams) -> bool {
+    params
+        .parameters
+        .get("_summary")
+        .and_then(|values| values.last())
+        .is_some_and(|v| v == "count")
+}
+
+/// Unknown-parameter handling for a search: the request's
+/// `Prefer: handling=strict|lenient`, else `search.unknown_param_handling`.
+fn unknown_param_handling(
+    headers: &HeaderMap,
+    settings: &crate::config::SearchSettings,
+) -> octofhir_search::UnknownParamHandling {
+    headers
+        .get_all("Prefer")
+        .iter()
+        .filter_map(|h| h.to_str().ok())
+        .find_map(octofhir_search::UnknownParamHandling::from_prefer)
+        .unwrap_or(settings.unknown_param_handling)
 }
 
 fn resolved_search_total(
@@ -3753,26 +4588,6 @@ pub async fn api_operation_patch(
     }
 }
 
-// ---- Error mapping helpers ----
-fn map_storage_error(e: StorageError) -> ApiError {
-    match e {
-        StorageError::NotFound { resource_type, id } => {
-            ApiError::not_found(format!("{resource_type} with id '{id}' not found"))
-        }
-        StorageError::AlreadyExists { resource_type, id } => {
-            ApiError::conflict(format!("{resource_type} with id '{id}' already exists"))
-        }
-        StorageError::Deleted { resource_type, id } => {
-            ApiError::gone(format!("{resource_type} with id '{id}' has been deleted"))
-        }
-        StorageError::VersionConflict { expected, actual } => ApiError::precondition_failed(
-            format!("Version conflict: expected {expected}, got {actual}"),
-        ),
-        StorageError::InvalidResource { message } => ApiError::bad_request(message),
-        other => ApiError::internal(other.to_string()),
-    }
-}
-
 // ---- Embedded UI handlers ----
 static UI_DIR: Dir = include_dir!("$CARGO_MANIFEST_DIR/../../ui/dist");
 
@@ -3836,6 +4651,8 @@ pub async fn ui_static(Path(path): Path<String>) -> Response {
 #[tracing::instrument(name = "fhir.bundle", skip_all)]
 pub async fn transaction_handler(
     State(state): State<crate::server::AppState>,
+    base_url: BaseUrl,
+    auth_context: Option<axum::Extension<Arc<octofhir_auth::middleware::AuthContext>>>,
     headers: HeaderMap,
     Json(bundle): Json<Value>,
 ) -> Result<impl IntoResponse, ApiError> {
@@ -3852,11 +4669,12 @@ pub async fn transaction_handler(
     // FHIR R4 default for transaction-response entries is `return=representation`
     // (each entry's response includes the stored resource). Clients that
     // explicitly request `Prefer: return=minimal` get only status/location/etag.
-    let bundle_include_resource = headers
+    let prefer_return = headers
         .get("prefer")
         .and_then(|v| v.to_str().ok())
-        .map(|v| !v.to_ascii_lowercase().contains("return=minimal"))
-        .unwrap_or(true);
+        .map(parse_prefer_return)
+        .unwrap_or(PreferReturn::Representation);
+    let bundle_include_resource = prefer_return != PreferReturn::Minimal;
 
     // Validate bundle structure
     let resource_type = bundle["resourceType"].as_str();
@@ -3879,6 +4697,7 @@ pub async fn transaction_handler(
             body: Some(bundle),
             headers: None,
             client_id: None,
+            secret: None,
         };
 
         let job_id = state
@@ -3887,7 +4706,7 @@ pub async fn transaction_handler(
             .await
             .map_err(|e| ApiError::Internal(format!("Failed to submit async job: {}", e)))?;
 
-        return Ok(create_async_accepted_response(job_id, &state.base_url));
+        return Ok(create_async_accepted_response(job_id, base_url.as_str()));
     }
 
     // Validation of POST entries mirrors single-create: on by default, honoring
@@ -3897,14 +4716,27 @@ pub async fn transaction_handler(
     // Otherwise, process synchronously
     match bundle_type {
         "transaction" => {
-            let (status, json) =
-                process_transaction(&state, &bundle, bundle_include_resource, skip_validation)
-                    .await?;
+            let auth_context = auth_context.as_ref().map(|ext| ext.0.as_ref());
+            let (status, json) = process_transaction(
+                &state,
+                base_url.as_str(),
+                &bundle,
+                bundle_include_resource,
+                skip_validation,
+                auth_context,
+            )
+            .await?;
             Ok((status, HeaderMap::new(), json))
         }
         "batch" => {
-            let (status, json) =
-                process_batch(&state, &bundle, bundle_include_resource, skip_validation).await?;
+            let (status, json) = process_batch(
+                &state,
+                base_url.as_str(),
+                &bundle,
+                prefer_return,
+                skip_validation,
+            )
+            .await?;
             Ok((status, HeaderMap::new(), json))
         }
         _ => Err(ApiError::bad_request(format!(
@@ -3922,9 +4754,11 @@ pub async fn transaction_handler(
 /// 3. Execute: process entries in verb order (DELETE → POST → PUT → GET) in one DB transaction
 async fn process_transaction(
     state: &crate::server::AppState,
+    base_url: &str,
     bundle: &Value,
     include_resource: bool,
     skip_validation: bool,
+    auth_context: Option<&octofhir_auth::middleware::AuthContext>,
 ) -> Result<(StatusCode, Json<Value>), ApiError> {
     let entries = bundle["entry"]
         .as_array()
@@ -4064,22 +4898,42 @@ async fn process_transaction(
             resolve_references_recursive(res, &reference_map)?;
         }
 
+        let method = entry["request"]["method"]
+            .as_str()
+            .unwrap_or("")
+            .to_uppercase();
+        if matches!(method.as_str(), "POST" | "PUT")
+            && let Some(res) = resolved_entry.get("resource")
+        {
+            check_resource_size(res, state)?;
+        }
+
         resolved_entries.push((*original_idx, resolved_entry));
     }
 
-    // Validate POST entries before opening the transaction: validation issues
-    // its own DB reads, which must not run while the write connection is held.
+    // Validate POST and PUT entries before opening the transaction: validation
+    // issues its own DB reads, which must not run while the write connection
+    // is held.
     if !skip_validation {
         for (original_idx, entry) in &resolved_entries {
             let method = entry["request"]["method"]
                 .as_str()
                 .unwrap_or("")
                 .to_uppercase();
-            if method != "POST" || matched_conditional.contains_key(original_idx) {
+            let write = match method.as_str() {
+                "POST" => !matched_conditional.contains_key(original_idx),
+                "PUT" => true,
+                _ => false,
+            };
+            if !write {
                 continue;
             }
             let url = entry["request"]["url"].as_str().unwrap_or("");
-            let resource_type = url.split('?').next().unwrap_or(url).to_string();
+            let resource_type = url.split(['?', '/']).next().unwrap_or(url).to_string();
+            let validation_mode = state.config.validation.mode_for(&resource_type);
+            if validation_mode == crate::config::ValidationMode::Off {
+                continue;
+            }
             let Some(mut resource) = entry.get("resource").cloned() else {
                 continue;
             };
@@ -4088,132 +4942,202 @@ async fn process_transaction(
                 .validation_service
                 .validate_with_known_refs(&resource, &known_refs)
                 .await;
-            if !validation_outcome.valid {
-                return Err(ApiError::UnprocessableEntity {
-                    message: "Resource validation failed".to_string(),
-                    operation_outcome: Some(validation_outcome.to_operation_outcome()),
-                });
+            enforce_validation(
+                validation_mode,
+                &resource_type,
+                &validation_outcome,
+                "Resource validation failed",
+            )?;
+        }
+    }
+
+    // Reference integrity is opt-in for transactions: entries commonly point at
+    // resources created later in the same Bundle, which count as existing here.
+    if state.config.validation.reference_integrity_in_bundles {
+        for (original_idx, entry) in &resolved_entries {
+            let method = entry["request"]["method"]
+                .as_str()
+                .unwrap_or("")
+                .to_uppercase();
+            if !matches!(method.as_str(), "POST" | "PUT")
+                || matched_conditional.contains_key(original_idx)
+            {
+                continue;
             }
+            let Some(resource) = entry.get("resource") else {
+                continue;
+            };
+            let Some(resource_type) = resource["resourceType"].as_str() else {
+                continue;
+            };
+            check_reference_integrity(resource_type, resource, state, &known_refs).await?;
         }
     }
 
-    // Phase 3: Execute all entries in one database transaction.
+    // Phase 3: Execute all entries in one database transaction, rolled back
+    // as a whole if any entry fails.
     //
     // POST entries are gathered into per-resource-type batches and inserted
     // via `tx.create_batch` (one INSERT ... SELECT FROM UNNEST(...) per type).
     // All other methods are processed individually via the per-entry path.
+    //
+    // The entries run in a boxed future that must not borrow from this
+    // call, so it gets its own handles on the state and the caller.
     tracing::debug!("Beginning native PostgreSQL transaction for Bundle processing");
-    let mut tx = state
-        .storage
-        .begin_transaction()
-        .await
-        .map_err(|e| ApiError::internal(format!("Failed to begin transaction: {}", e)))?;
+    let tx_state = state.clone();
+    let tx_base_url = base_url.to_string();
+    let auth_context = auth_context.cloned();
+    let response_entries = apply_in_transaction(state.storage.as_ref(), |tx| {
+        Box::pin(async move {
+            let state = &tx_state;
+            let base_url = tx_base_url.as_str();
+            let auth_context = auth_context.as_ref();
+            let mut response_entries: Vec<Option<Value>> = vec![None; resolved_entries.len()];
+            let mut post_batches: std::collections::HashMap<String, Vec<(usize, Value)>> =
+                std::collections::HashMap::new();
+
+            for (original_idx, entry) in &resolved_entries {
+                let method = entry["request"]["method"]
+                    .as_str()
+                    .unwrap_or("")
+                    .to_uppercase();
+
+                if method == "POST" {
+                    // Conditional create that matched in pre-scan — return existing
+                    if let Some(matched) = matched_conditional.get(original_idx) {
+                        let url = entry["request"]["url"].as_str().unwrap_or("");
+                        let resource_type = url.split('?').next().unwrap_or(url);
+                        response_entries[*original_idx] = Some(build_transaction_response_entry(
+                            if include_resource {
+                                Some(&matched.resource)
+                            } else {
+                                None
+                            },
+                            "200 OK",
+                            Some(resource_type),
+                            Some(&matched.id),
+                            Some(&matched.version_id),
+                        ));
+                        continue;
+                    }
 
-    let mut response_entries: Vec<Option<Value>> = vec![None; resolved_entries.len()];
-    let mut post_batches: std::collections::HashMap<String, Vec<(usize, Value)>> =
-        std::collections::HashMap::new();
+                    let url = entry["request"]["url"].as_str().unwrap_or("");
+                    let resource_type = url.split('?').next().unwrap_or(url).to_string();
 
-    for (original_idx, entry) in &resolved_entries {
-        let method = entry["request"]["method"]
-            .as_str()
-            .unwrap_or("")
-            .to_uppercase();
+                    resource_type.parse::<ResourceType>().map_err(|_| {
+                        ApiError::bad_request(format!("Unknown resource type: {}", resource_type))
+                    })?;
 
-        if method == "POST" {
-            // Conditional create that matched in pre-scan — return existing
-            if let Some(matched) = matched_conditional.get(original_idx) {
-                let url = entry["request"]["url"].as_str().unwrap_or("");
-                let resource_type = url.split('?').next().unwrap_or(url);
-                response_entries[*original_idx] = Some(build_transaction_response_entry(
-                    if include_resource {
-                        Some(&matched.resource)
-                    } else {
-                        None
-                    },
-                    "200 OK",
-                    Some(resource_type),
-                    Some(&matched.id),
-                    Some(&matched.version_id),
-                ));
-                continue;
-            }
+                    let mut resource = entry
+                        .get("resource")
+                        .cloned()
+                        .ok_or_else(|| ApiError::bad_request("POST entry requires a resource"))?;
+                    resource["resourceType"] = json!(resource_type);
 
-            let url = entry["request"]["url"].as_str().unwrap_or("");
-            let resource_type = url.split('?').next().unwrap_or(url).to_string();
-
-            resource_type.parse::<ResourceType>().map_err(|_| {
-                ApiError::bad_request(format!("Unknown resource type: {}", resource_type))
-            })?;
+                    post_batches
+                        .entry(resource_type)
+                        .or_default()
+                        .push((*original_idx, resource));
+                }
+            }
 
-            let mut resource = entry
-                .get("resource")
-                .cloned()
-                .ok_or_else(|| ApiError::bad_request("POST entry requires a resource"))?;
-            resource["resourceType"] = json!(resource_type);
+            // Flush all POST batches.
+            for (resource_type, items) in post_batches {
+                let resources: Vec<Value> = items.iter().map(|(_, r)| r.clone()).collect();
+                let stored = tx
+                    .create_batch(&resource_type, &resources)
+                    .await
+                    .map_err(|e| {
+                        tracing::warn!("Transaction batch-create failed, rolling back: {}", e);
+                        map_storage_error(e)
+                    })?;
+                for ((idx, _), s) in items.iter().zip(stored) {
+                    response_entries[*idx] = Some(build_transaction_response_entry(
+                        if include_resource {
+                            Some(&s.resource)
+                        } else {
+                            None
+                        },
+                        "201 Created",
+                        Some(&s.resource_type),
+                        Some(&s.id),
+                        Some(&s.version_id),
+                    ));
+                }
+            }
 
-            post_batches
-                .entry(resource_type)
-                .or_default()
-                .push((*original_idx, resource));
-        }
-    }
+            // Process non-POST entries (DELETE/PUT/PATCH/GET) individually.
+            for (original_idx, entry) in &resolved_entries {
+                if response_entries[*original_idx].is_some() {
+                    continue;
+                }
+                match process_transaction_entry_with_tx(
+                    tx,
+                    state,
+                    base_url,
+                    entry,
+                    include_resource,
+                )
+                .await
+                {
+                    Ok(response_entry) => response_entries[*original_idx] = Some(response_entry),
+                    Err(e) => {
+                        tracing::warn!("Transaction failed, rolling back: {}", e);
+                        return Err(e);
+                    }
+                }
+            }
 
-    // Flush all POST batches.
-    for (resource_type, items) in post_batches {
-        let resources: Vec<Value> = items.iter().map(|(_, r)| r.clone()).collect();
-        let stored = tx
-            .create_batch(&resource_type, &resources)
-            .await
-            .map_err(|e| {
-                tracing::warn!("Transaction batch-create failed, will auto-rollback: {}", e);
-                map_storage_error(e)
-            })?;
-        for ((idx, _), s) in items.iter().zip(stored) {
-            response_entries[*idx] = Some(build_transaction_response_entry(
-                if include_resource {
-                    Some(&s.resource)
-                } else {
-                    None
-                },
-                "201 Created",
-                Some(&s.resource_type),
-                Some(&s.id),
-                Some(&s.version_id),
-            ));
-        }
-    }
+            let response_entries: Vec<Value> = response_entries
+                .into_iter()
+                .map(|opt| {
+                    opt.unwrap_or_else(
+                        || json!({"response": {"status": "500 Internal Server Error"}}),
+                    )
+                })
+                .collect();
 
-    // Process non-POST entries (DELETE/PUT/PATCH/GET) individually.
-    for (original_idx, entry) in &resolved_entries {
-        if response_entries[*original_idx].is_some() {
-            continue;
-        }
-        match process_transaction_entry_with_tx(&mut *tx, state, entry, include_resource).await {
-            Ok(response_entry) => response_entries[*original_idx] = Some(response_entry),
-            Err(e) => {
-                tracing::warn!("Transaction failed, will auto-rollback: {}", e);
-                return Err(e);
+            // Attribute every write to the requesting agent. The Provenance goes into
+            // the same transaction so it commits (or rolls back) with the writes.
+            if state.config.audit.transaction_provenance {
+                let targets = transaction_provenance_targets(
+                    &resolved_entries,
+                    &response_entries,
+                    &matched_conditional,
+                );
+                if !targets.is_empty() {
+                    let actor = auth_context.map(crate::audit::actor_from_auth_context);
+                    let client_id = auth_context.map(|ctx| ctx.client_id());
+                    let provenance = crate::audit::build_transaction_provenance(
+                        &targets,
+                        actor.as_ref(),
+                        client_id,
+                    );
+                    tx.create(&provenance).await.map_err(|e| {
+                        tracing::warn!("Transaction Provenance write failed, rolling back: {}", e);
+                        map_storage_error(e)
+                    })?;
+                }
             }
-        }
-    }
 
-    let response_entries: Vec<Value> = response_entries
-        .into_iter()
-        .map(|opt| {
-            opt.unwrap_or_else(|| json!({"response": {"status": "500 Internal Server Error"}}))
+            Ok(response_entries)
         })
-        .collect();
-
-    // Commit transaction
-    tx.commit()
-        .await
-        .map_err(|e| ApiError::internal(format!("Failed to commit transaction: {}", e)))?;
+    })
+    .await?;
 
     tracing::debug!(
         "Transaction committed successfully with {} entries",
         response_entries.len()
     );
 
+    // Writes in a tenant scope emit no events, so the cache hook never sees
+    // them. Evict here, where the cache key carries the request's tenant.
+    if let Some(cache) = &state.resource_cache {
+        for entry in &response_entries {
+            invalidate_bundle_entry(cache, entry).await;
+        }
+    }
+
     let response_bundle = json!({
         "resourceType": "Bundle",
         "type": "transaction-response",
@@ -4223,6 +5147,58 @@ async fn process_transaction(
     Ok((StatusCode::OK, Json(response_bundle)))
 }
 
+/// Evicts the resource a bundle response entry points at from the read cache.
+///
+/// Every entry that touched a resource carries its `location`
+/// (`{type}/{id}/_history/{version}`), deletes included.
+async fn invalidate_bundle_entry(cache: &crate::cache::ResourceCache, entry: &Value) {
+    let Some(location) = entry["response"]["location"].as_str() else {
+        return;
+    };
+    let mut parts = location.split('/');
+    if let (Some(resource_type), Some(id)) = (parts.next(), parts.next()) {
+        cache.invalidate(resource_type, id).await;
+    }
+}
+
+/// Collects the versioned `location` of every resource a transaction wrote,
+/// paired with its v3-DataOperation code. Reads, deletes and conditional
+/// creates that matched an existing resource are not writes and are skipped.
+fn transaction_provenance_targets(
+    resolved_entries: &[(usize, Value)],
+    response_entries: &[Value],
+    matched_conditional: &std::collections::HashMap<usize, MatchedExisting>,
+) -> Vec<(String, &'static str)> {
+    let mut targets = Vec::new();
+    for (original_idx, entry) in resolved_entries {
+        let operation = match entry["request"]["method"]
+            .as_str()
+            .unwrap_or("")
+            .to_uppercase()
+            .as_str()
+        {
+            "POST" if !matched_conditional.contains_key(original_idx) => "CREATE",
+            "PUT" | "PATCH" => "UPDATE",
+            _ => continue,
+        };
+        let response = &response_entries[*original_idx]["response"];
+        let Some(location) = response["location"].as_str() else {
+            continue;
+        };
+        // Conditional PUT can create; report what actually happened
+        let operation = if response["status"]
+            .as_str()
+            .is_some_and(|status| status.starts_with("201"))
+        {
+            "CREATE"
+        } else {
+            operation
+        };
+        targets.push((location.to_string(), operation));
+    }
+    targets
+}
+
 /// Snapshot of an existing resource captured during conditional-create
 /// pre-scan, so we don't re-issue the same search inside the transaction.
 struct MatchedExisting {
@@ -4255,6 +5231,7 @@ fn transaction_post_condition<'a>(request: &'a Value, url: &'a str) -> Option<&'
 async fn process_transaction_entry_with_tx(
     tx: &mut dyn octofhir_storage::Transaction,
     state: &crate::server::AppState,
+    base_url: &str,
     entry: &Value,
     include_resource: bool,
 ) -> Result<Value, ApiError> {
@@ -4265,6 +5242,9 @@ async fn process_transaction_entry_with_tx(
     let url = request["url"]
         .as_str()
         .ok_or_else(|| ApiError::bad_request("Missing request.url in bundle entry"))?;
+    if let Some(resource_type) = entry_resource_type(url) {
+        ensure_resource_type_allowed(resource_type)?;
+    }
 
     let resource = entry.get("resource").cloned();
 
@@ -4491,7 +5471,7 @@ async fn process_transaction_entry_with_tx(
                             None,
                             "204 No Content",
                             Some(resource_type),
-                            None,
+                            Some(id),
                             None,
                         ))
                     }
@@ -4530,7 +5510,7 @@ async fn process_transaction_entry_with_tx(
                     .map_err(|e| ApiError::bad_request(e.to_string()))?;
 
                 let offset = search_params.offset.unwrap_or(0) as usize;
-                let count = search_params.count.unwrap_or(10) as usize;
+                let count = search_params.count.unwrap_or(cfg.default_count as u32) as usize;
                 let total = result.total.unwrap_or(result.entries.len() as u32) as usize;
 
                 let search_bundle = octofhir_api::bundle_from_search(
@@ -4540,10 +5520,9 @@ async fn process_transaction_entry_with_tx(
                         .into_iter()
                         .map(|entry| entry.resource)
                         .collect(),
-                    &state.base_url,
+                    base_url,
                     resource_type,
-                    offset,
-                    count,
+                    octofhir_api::Page::new(offset, count).with_link_style(cfg.link_style),
                     None,
                 );
 
@@ -4598,24 +5577,52 @@ async fn process_transaction_entry_with_tx(
             let patch =
                 resource.ok_or_else(|| ApiError::bad_request("PATCH entry requires a resource"))?;
 
-            if url.contains('?') {
-                return Err(ApiError::bad_request(
-                    "Conditional PATCH is not supported in transaction bundles",
-                ));
-            }
+            let if_match = parse_bundle_if_match(request);
 
-            let parts: Vec<&str> = url.split('/').collect();
-            if parts.len() != 2 {
-                return Err(ApiError::bad_request(format!("Invalid PATCH URL: {}", url)));
-            }
+            let (resource_type, existing) =
+                if let Some((resource_type, condition)) = url.split_once('?') {
+                    let _rt = resource_type.parse::<ResourceType>().map_err(|_| {
+                        ApiError::bad_request(format!("Unknown resource type: {}", resource_type))
+                    })?;
+                    if condition.is_empty() {
+                        return Err(ApiError::bad_request(
+                            "Conditional patch requires search parameters",
+                        ));
+                    }
 
-            let resource_type = parts[0];
-            let id = parts[1];
-            let if_match = parse_bundle_if_match(request);
+                    let search_params = octofhir_search::parse_query_string(condition, 2, 10);
+                    let result = tx
+                        .search(resource_type, &search_params)
+                        .await
+                        .map_err(|e| ApiError::bad_request(e.to_string()))?;
 
-            let _rt = resource_type.parse::<ResourceType>().map_err(|_| {
-                ApiError::bad_request(format!("Unknown resource type: {}", resource_type))
-            })?;
+                    (
+                        resource_type,
+                        single_conditional_patch_match(result.entries)?,
+                    )
+                } else {
+                    let parts: Vec<&str> = url.split('/').collect();
+                    if parts.len() != 2 {
+                        return Err(ApiError::bad_request(format!("Invalid PATCH URL: {}", url)));
+                    }
+
+                    let (resource_type, id) = (parts[0], parts[1]);
+
+                    let _rt = resource_type.parse::<ResourceType>().map_err(|_| {
+                        ApiError::bad_request(format!("Unknown resource type: {}", resource_type))
+                    })?;
+
+                    let existing = tx
+                        .read(resource_type, id)
+                        .await
+                        .map_err(map_storage_error)?
+                        .ok_or_else(|| {
+                            ApiError::not_found(format!("{}/{} not found", resource_type, id))
+                        })?;
+
+                    (resource_type, existing)
+                };
+            let id = existing.id.as_str();
 
             if resource_type == "AccessPolicy" && id == ADMIN_ACCESS_POLICY_ID {
                 return Err(ApiError::forbidden(
@@ -4623,28 +5630,27 @@ async fn process_transaction_entry_with_tx(
                 ));
             }
 
-            let existing = tx
-                .read(resource_type, id)
-                .await
-                .map_err(map_storage_error)?
-                .ok_or_else(|| {
-                    ApiError::not_found(format!("{}/{} not found", resource_type, id))
-                })?;
+            if let Some(ref expected_version) = if_match
+                && expected_version != &existing.version_id
+            {
+                return Err(ApiError::precondition_failed(format!(
+                    "Version conflict: expected {}, but current is {}",
+                    expected_version, existing.version_id
+                )));
+            }
 
-            let mut patched_json = existing.resource.clone();
-            let patch_ops: Vec<json_patch::PatchOperation> = serde_json::from_value(patch)
+            let patch_ops: json_patch::Patch = serde_json::from_value(patch)
                 .map_err(|e| ApiError::bad_request(format!("Invalid JSON Patch: {}", e)))?;
-
-            json_patch::patch(&mut patched_json, &patch_ops)
-                .map_err(|e| ApiError::bad_request(format!("Patch failed: {}", e)))?;
+            let mut patched_json = apply_json_patch_operations(&existing.resource, &patch_ops)?;
 
             patched_json["id"] = json!(id);
             patched_json["resourceType"] = json!(resource_type);
+            check_resource_size(&patched_json, state)?;
 
             let stored = tx
-                .update(&patched_json, if_match.as_deref())
+                .update(&patched_json, Some(&existing.version_id))
                 .await
-                .map_err(map_storage_error)?;
+                .map_err(|e| map_patch_storage_error(e, if_match.is_some()))?;
 
             Ok(build_transaction_response_entry(
                 include_resource.then_some(&stored.resource),
@@ -4662,10 +5668,18 @@ async fn process_transaction_entry_with_tx(
 }
 
 /// Process a batch bundle non-atomically - each entry is independent
+///
+/// The response has one entry per request entry, in the same order. A failed
+/// entry carries the full OperationOutcome of its error, with each issue
+/// pointing at the entry, so clients can retry just the entries that failed.
+/// Successful entries follow `Prefer: return=`: the resource
+/// (`representation`, the default), an informational OperationOutcome
+/// (`OperationOutcome`), or neither (`minimal`).
 async fn process_batch(
     state: &crate::server::AppState,
+    base_url: &str,
     bundle: &Value,
-    include_resource: bool,
+    prefer_return: PreferReturn,
     skip_validation: bool,
 ) -> Result<(StatusCode, Json<Value>), ApiError> {
     let entries = bundle["entry"]
@@ -4681,60 +5695,119 @@ async fn process_batch(
         return Ok((StatusCode::OK, Json(response_bundle)));
     }
 
-    let mut response_entries: Vec<Value> = Vec::new();
+    let include_resource = prefer_return == PreferReturn::Representation;
+    let mut response_entries: Vec<Value> = Vec::with_capacity(entries.len());
 
     // Process each entry independently (no rollback on failure)
-    for entry in entries {
-        // Validate POST entries, mirroring single POST (create_resource). Batch is
-        // non-atomic: a failure becomes this entry's 422 response, siblings proceed.
-        let method = entry["request"]["method"]
-            .as_str()
-            .unwrap_or("")
-            .to_uppercase();
-        if method == "POST"
-            && !skip_validation
-            && let Some(resource) = entry.get("resource")
-        {
+    for (index, entry) in entries.iter().enumerate() {
+        let result =
+            process_batch_entry(state, base_url, entry, include_resource, skip_validation).await;
+        if let (Ok(response_entry), Some(cache)) = (&result, &state.resource_cache) {
+            invalidate_bundle_entry(cache, response_entry).await;
+        }
+        response_entries.push(match result {
+            Ok(response_entry) => batch_success_entry(response_entry, prefer_return),
+            // For batch, return error response for this entry and continue
+            Err(e) => batch_error_entry(index, &e),
+        });
+    }
+
+    let response_bundle = json!({
+        "resourceType": "Bundle",
+        "type": "batch-response",
+        "entry": response_entries
+    });
+
+    Ok((StatusCode::OK, Json(response_bundle)))
+}
+
+/// Run one batch entry, with the checks a single request of its kind gets.
+async fn process_batch_entry(
+    state: &crate::server::AppState,
+    base_url: &str,
+    entry: &Value,
+    include_resource: bool,
+    skip_validation: bool,
+) -> Result<Value, ApiError> {
+    // Validate POST and PUT entries, mirroring single create and update. Batch
+    // is non-atomic: a failure becomes this entry's 422 response, siblings
+    // proceed.
+    let method = entry["request"]["method"]
+        .as_str()
+        .unwrap_or("")
+        .to_uppercase();
+    if matches!(method.as_str(), "POST" | "PUT")
+        && !skip_validation
+        && let Some(resource) = entry.get("resource")
+    {
+        let resource_type = resource["resourceType"].as_str().unwrap_or_default();
+        let validation_mode = state.config.validation.mode_for(resource_type);
+        if validation_mode != crate::config::ValidationMode::Off {
             let validation_outcome = state.validation_service.validate(resource).await;
-            if !validation_outcome.valid {
-                response_entries.push(json!({
-                    "response": {
-                        "status": "422 Unprocessable Entity",
-                        "outcome": validation_outcome.to_operation_outcome()
-                    }
-                }));
-                continue;
-            }
+            enforce_validation(
+                validation_mode,
+                resource_type,
+                &validation_outcome,
+                "Resource validation failed",
+            )?;
         }
+    }
 
-        let mut reference_map: std::collections::HashMap<String, String> =
-            std::collections::HashMap::new();
-        let result =
-            process_transaction_entry(state, entry, &mut reference_map, include_resource).await;
+    // Batch entries are independent writes, so the size limit and reference
+    // integrity apply exactly as they do to single create/update requests.
+    if matches!(method.as_str(), "POST" | "PUT")
+        && let Some(resource) = entry.get("resource")
+    {
+        check_resource_size(resource, state)?;
+        let resource_type = resource["resourceType"].as_str().unwrap_or_default();
+        check_reference_integrity(resource_type, resource, state, &HashSet::new()).await?;
+    }
 
-        match result {
-            Ok((response_entry, _)) => {
-                response_entries.push(response_entry);
-            }
-            Err(e) => {
-                // For batch, return error response for this entry and continue
-                response_entries.push(json!({
-                    "response": {
-                        "status": format!("{} {}", e.status_code().as_u16(), e.status_code().canonical_reason().unwrap_or("Error")),
-                        "outcome": e.to_operation_outcome()
-                    }
-                }));
+    let mut reference_map: std::collections::HashMap<String, String> =
+        std::collections::HashMap::new();
+    process_transaction_entry(state, base_url, entry, &mut reference_map, include_resource)
+        .await
+        .map(|(response_entry, _)| response_entry)
+}
+
+/// Applies the `Prefer: return=` preference to a successful batch entry.
+fn batch_success_entry(mut response_entry: Value, prefer_return: PreferReturn) -> Value {
+    if prefer_return == PreferReturn::OperationOutcome {
+        let status = response_entry["response"]["status"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string();
+        response_entry["response"]["outcome"] = json!({
+            "resourceType": "OperationOutcome",
+            "issue": [{
+                "severity": "information",
+                "code": "informational",
+                "diagnostics": status
+            }]
+        });
+    }
+    response_entry
+}
+
+/// Response entry for a failed batch entry: its status and the error's full
+/// OperationOutcome, each issue pointing at `Bundle.entry[index]`.
+fn batch_error_entry(index: usize, error: &ApiError) -> Value {
+    let status = error.status_code();
+    let mut outcome = error.operation_outcome_json();
+    let location = format!("Bundle.entry[{index}]");
+    if let Some(issues) = outcome["issue"].as_array_mut() {
+        for issue in issues {
+            if issue.get("expression").is_none() {
+                issue["expression"] = json!([location]);
             }
         }
     }
-
-    let response_bundle = json!({
-        "resourceType": "Bundle",
-        "type": "batch-response",
-        "entry": response_entries
-    });
-
-    Ok((StatusCode::OK, Json(response_bundle)))
+    json!({
+        "response": {
+            "status": format!("{} {}", status.as_u16(), status.canonical_reason().unwrap_or("Error")),
+            "outcome": outcome
+        }
+    })
 }
 
 /// Sort transaction entries by HTTP method order per FHIR spec
@@ -4772,6 +5845,7 @@ fn transaction_method_order(method: &str) -> u8 {
 /// Returns (response_entry, Option<(resource_type, id)>) where the tuple indicates a created resource for rollback
 async fn process_transaction_entry(
     state: &crate::server::AppState,
+    base_url: &str,
     entry: &Value,
     reference_map: &mut std::collections::HashMap<String, String>,
     include_resource: bool,
@@ -4783,6 +5857,9 @@ async fn process_transaction_entry(
     let url = request["url"]
         .as_str()
         .ok_or_else(|| ApiError::bad_request("Missing request.url in bundle entry"))?;
+    if let Some(resource_type) = entry_resource_type(url) {
+        ensure_resource_type_allowed(resource_type)?;
+    }
 
     let full_url = entry["fullUrl"].as_str();
 
@@ -4807,7 +5884,7 @@ async fn process_transaction_entry(
         }
         "PUT" => process_put_entry(state, url, resource, request, include_resource).await,
         "DELETE" => process_delete_entry(state, url).await,
-        "GET" => process_get_entry(state, url).await,
+        "GET" => process_get_entry(state, base_url, url).await,
         "PATCH" => process_patch_entry(state, url, resource, request, include_resource).await,
         _ => Err(ApiError::bad_request(format!(
             "Unknown HTTP method in bundle entry: {}",
@@ -5124,7 +6201,7 @@ async fn process_delete_entry(
             None,
             "204 No Content",
             Some(resource_type),
-            None,
+            Some(id),
             None,
         );
 
@@ -5189,7 +6266,7 @@ async fn process_delete_entry(
                     None,
                     "204 No Content",
                     Some(resource_type),
-                    None,
+                    Some(id),
                     None,
                 );
                 Ok((response_entry, None))
@@ -5209,11 +6286,12 @@ async fn process_delete_entry(
 /// Process GET (read, vread, search) entry
 async fn process_get_entry(
     state: &crate::server::AppState,
+    base_url: &str,
     url: &str,
 ) -> Result<(Value, Option<(String, String)>), ApiError> {
     // Check for search: Type?params
     if url.contains('?') {
-        return process_get_search_entry(state, url).await;
+        return process_get_search_entry(state, base_url, url).await;
     }
 
     let parts: Vec<&str> = url.split('/').collect();
@@ -5293,6 +6371,7 @@ async fn process_get_entry(
 /// Process GET with search params in batch: Type?params
 async fn process_get_search_entry(
     state: &crate::server::AppState,
+    base_url: &str,
     url: &str,
 ) -> Result<(Value, Option<(String, String)>), ApiError> {
     let (resource_type, query) = if let Some(idx) = url.find('?') {
@@ -5311,22 +6390,24 @@ async fn process_get_search_entry(
 
     // Search using raw path (skips JSONB → Value round-trip)
     let cfg = state.search_config.config();
-    let search_params =
+    let mut search_params =
         octofhir_search::parse_query_string(query, cfg.default_count as u32, cfg.max_count as u32);
+    cfg.default_sort.apply(resource_type, &mut search_params);
 
-    let result = octofhir_db_postgres::queries::execute_search_raw_with_config(
+    let mut result = octofhir_db_postgres::queries::execute_search_raw_with_config(
         &state.read_db_pool,
         resource_type,
         &search_params,
         Some(&cfg.registry),
-        None,
+        Some(state.config.search.unknown_param_handling),
         state.query_cache.as_deref(),
     )
     .await
     .map_err(|e| ApiError::bad_request(e.to_string()))?;
+    inflate_raw_resources(state, &mut result.entries).await?;
 
     let offset = search_params.offset.unwrap_or(0) as usize;
-    let count = search_params.count.unwrap_or(10) as usize;
+    let count = search_params.count.unwrap_or(cfg.default_count as u32) as usize;
     let (total, total_is_exact) =
         resolved_search_total(result.total, result.has_more, offset, result.entries.len());
 
@@ -5343,12 +6424,12 @@ async fn process_get_search_entry(
         resources,
         ids,
         vec![],
-        &state.base_url,
+        base_url,
         resource_type,
-        offset,
-        count,
+        octofhir_api::Page::new(offset, count).with_link_style(cfg.link_style),
         None,
-    );
+    )
+    .with_match_scores(result.scores);
 
     // Return the bundle as the response resource
     let search_bundle_json =
@@ -5369,16 +6450,44 @@ async fn process_patch_entry(
     state: &crate::server::AppState,
     url: &str,
     resource: Option<Value>,
-    _request: &Value,
+    request: &Value,
     include_resource: bool,
 ) -> Result<(Value, Option<(String, String)>), ApiError> {
     let patch = resource.ok_or_else(|| ApiError::bad_request("PATCH entry requires a resource"))?;
+    let if_match = parse_bundle_if_match(request);
 
-    let parts: Vec<&str> = url.split('/').collect();
+    let (resource_type, existing) = if let Some((resource_type, condition)) = url.split_once('?') {
+        // Conditional patch: PATCH Type?condition
+        if resource_type.parse::<ResourceType>().is_err() {
+            return Err(ApiError::bad_request(format!(
+                "Unknown resource type: {}",
+                resource_type
+            )));
+        }
+        if condition.is_empty() {
+            return Err(ApiError::bad_request(
+                "Conditional patch requires search parameters",
+            ));
+        }
 
-    if parts.len() == 2 {
-        let resource_type = parts[0];
-        let id = parts[1];
+        let search_params = octofhir_search::parse_query_string(condition, 2, 10);
+        let result = state
+            .storage
+            .search(resource_type, &search_params)
+            .await
+            .map_err(|e| ApiError::bad_request(e.to_string()))?;
+
+        (
+            resource_type,
+            single_conditional_patch_match(result.entries)?,
+        )
+    } else {
+        let parts: Vec<&str> = url.split('/').collect();
+        if parts.len() != 2 {
+            return Err(ApiError::bad_request(format!("Invalid PATCH URL: {}", url)));
+        }
+
+        let (resource_type, id) = (parts[0], parts[1]);
 
         // Validate resource type
         if resource_type.parse::<ResourceType>().is_err() {
@@ -5388,13 +6497,6 @@ async fn process_patch_entry(
             )));
         }
 
-        // Protect the default admin access policy from modification
-        if resource_type == "AccessPolicy" && id == ADMIN_ACCESS_POLICY_ID {
-            return Err(ApiError::forbidden(
-                "The default admin access policy cannot be modified",
-            ));
-        }
-
         // Get existing resource using modern storage API
         let existing = state
             .storage
@@ -5403,36 +6505,68 @@ async fn process_patch_entry(
             .map_err(map_storage_error)?
             .ok_or_else(|| ApiError::not_found(format!("{}/{} not found", resource_type, id)))?;
 
-        // Apply JSON Patch
-        let mut patched_json = existing.resource.clone();
-        let patch_ops: Vec<json_patch::PatchOperation> = serde_json::from_value(patch)
-            .map_err(|e| ApiError::bad_request(format!("Invalid JSON Patch: {}", e)))?;
+        (resource_type, existing)
+    };
+    let id = existing.id.as_str();
 
-        json_patch::patch(&mut patched_json, &patch_ops)
-            .map_err(|e| ApiError::bad_request(format!("Patch failed: {}", e)))?;
+    // Protect the default admin access policy from modification
+    if resource_type == "AccessPolicy" && id == ADMIN_ACCESS_POLICY_ID {
+        return Err(ApiError::forbidden(
+            "The default admin access policy cannot be modified",
+        ));
+    }
 
-        // Ensure id and resourceType are set
-        patched_json["id"] = json!(id);
-        patched_json["resourceType"] = json!(resource_type);
+    // Check ifMatch if provided
+    if let Some(ref expected_version) = if_match
+        && expected_version != &existing.version_id
+    {
+        return Err(ApiError::precondition_failed(format!(
+            "Version conflict: expected {}, but current is {}",
+            expected_version, existing.version_id
+        )));
+    }
 
-        // Update the resource using modern storage API
-        let stored = state
-            .storage
-            .update(&patched_json, None)
-            .await
-            .map_err(map_storage_error)?;
+    // Apply JSON Patch
+    let patch_ops: json_patch::Patch = serde_json::from_value(patch)
+        .map_err(|e| ApiError::bad_request(format!("Invalid JSON Patch: {}", e)))?;
+    let mut patched_json = apply_json_patch_operations(&existing.resource, &patch_ops)?;
 
-        let response_entry = build_transaction_response_entry(
-            include_resource.then_some(&stored.resource),
-            "200 OK",
-            Some(resource_type),
-            Some(id),
-            Some(&stored.version_id),
-        );
+    // Ensure id and resourceType are set
+    patched_json["id"] = json!(id);
+    patched_json["resourceType"] = json!(resource_type);
+    check_resource_size(&patched_json, state)?;
 
-        Ok((response_entry, None))
-    } else {
-        Err(ApiError::bad_request(format!("Invalid PATCH URL: {}", url)))
+    // Store against the version the patch was applied to
+    let stored = state
+        .storage
+        .update(&patched_json, Some(&existing.version_id))
+        .await
+        .map_err(|e| map_patch_storage_error(e, if_match.is_some()))?;
+
+    let response_entry = build_transaction_response_entry(
+        include_resource.then_some(&stored.resource),
+        "200 OK",
+        Some(resource_type),
+        Some(id),
+        Some(&stored.version_id),
+    );
+
+    Ok((response_entry, None))
+}
+
+/// Picks the resource a conditional patch applies to: 404 when nothing
+/// matches the criteria, 412 when more than one does.
+fn single_conditional_patch_match(
+    mut entries: Vec<octofhir_storage::StoredResource>,
+) -> Result<octofhir_storage::StoredResource, ApiError> {
+    match entries.len() {
+        0 => Err(ApiError::not_found(
+            "No resources match the search criteria for conditional patch",
+        )),
+        1 => Ok(entries.remove(0)),
+        _ => Err(ApiError::precondition_failed(
+            "Multiple resources match the search criteria for conditional patch",
+        )),
     }
 }
 
@@ -5719,6 +6853,7 @@ pub async fn compartment_search(
     query: Query<HistoryQueryParams>,
     RawQuery(query_string): RawQuery,
     State(state): State<crate::server::AppState>,
+    base_url: BaseUrl,
 ) -> Result<impl IntoResponse, ApiError> {
     // Extract compartment type from the original URI path
     // e.g. /fhir/Patient/123/Observation → "Patient"
@@ -5736,6 +6871,7 @@ pub async fn compartment_search(
     if resource_type == "_history" {
         return instance_history(
             State(state),
+            base_url,
             Path((compartment_type, compartment_id)),
             query,
         )
@@ -5808,9 +6944,11 @@ pub async fn compartment_search(
 
     // Execute searches for all inclusion parameters and collect unique resources (raw path)
     let cfg = state.search_config.config();
-    let mut all_resources: std::collections::HashMap<String, String> =
-        std::collections::HashMap::new(); // Deduplicate by ID → raw JSON
+    let mut all_resources = Vec::new();
+    let mut seen = std::collections::HashSet::new(); // Deduplicate by ID
     let mut total_count = 0usize;
+    let mut sort = Vec::new();
+    let mut matched_params = 0usize;
 
     for param in inclusion_params {
         // Build query with this inclusion parameter
@@ -5826,7 +6964,9 @@ pub async fn compartment_search(
             "Searching with inclusion parameter"
         );
 
-        let search_params = octofhir_search::parse_query_string(&query, 1000, 1000);
+        let mut search_params = octofhir_search::parse_query_string(&query, 1000, 1000);
+        cfg.default_sort.apply(&resource_type, &mut search_params);
+        sort = search_params.sort.clone().unwrap_or_default();
         match octofhir_db_postgres::queries::execute_search_raw_with_config(
             &state.read_db_pool,
             &resource_type,
@@ -5839,10 +6979,15 @@ pub async fn compartment_search(
         {
             Ok(result) => {
                 total_count = total_count.max(result.total.unwrap_or(0) as usize);
+                if !result.entries.is_empty() {
+                    matched_params += 1;
+                }
 
-                // Add resources, deduplicating by ID
+                // Add resources in result order, deduplicating by ID
                 for entry in result.entries {
-                    all_resources.entry(entry.id).or_insert(entry.resource_json);
+                    if seen.insert(entry.id.clone()) {
+                        all_resources.push(entry);
+                    }
                 }
             }
             Err(e) => {
@@ -5855,7 +7000,14 @@ pub async fn compartment_search(
         }
     }
 
+    // Results merged from several params are re-ordered as far as the sort
+    // can be compared on stored metadata; ties fall back to id
+    let merge_sort = merge_sort_keys(&sort);
+    if matched_params > 1 && !merge_sort.is_empty() {
+        all_resources.sort_by(|a, b| compare_system_search_matches(a, b, &merge_sort));
+    }
     let actual_count = all_resources.len();
+    inflate_raw_resources(&state, &mut all_resources).await?;
 
     tracing::info!(
         compartment = %compartment_ref,
@@ -5868,7 +7020,12 @@ pub async fn compartment_search(
     // Build Bundle entries from deduplicated raw resources
     let (resources, ids): (Vec<_>, Vec<_>) = all_resources
         .into_iter()
-        .map(|(id, json)| (octofhir_api::RawJson::from_string(json), id))
+        .map(|entry| {
+            (
+                octofhir_api::RawJson::from_string(entry.resource_json),
+                entry.id,
+            )
+        })
         .unzip();
 
     let bundle = octofhir_api::bundle_from_search_raw(
@@ -5876,10 +7033,9 @@ pub async fn compartment_search(
         resources,
         ids,
         vec![],
-        &state.base_url,
+        base_url.as_str(),
         &resource_type,
-        0,
-        actual_count,
+        octofhir_api::Page::new(0, actual_count),
         None,
     );
 
@@ -5896,6 +7052,7 @@ pub async fn compartment_search(
 pub async fn compartment_search_all(
     Path((compartment_type, compartment_id)): Path<(String, String)>,
     State(state): State<crate::server::AppState>,
+    base_url: BaseUrl,
 ) -> Result<impl IntoResponse, ApiError> {
     tracing::info!(
         compartment_type = %compartment_type,
@@ -5976,7 +7133,9 @@ pub async fn compartment_search_all(
         }
 
         // Execute search using raw path (skips JSONB → Value round-trip)
-        let search_params = octofhir_search::parse_query_string(&query, 1000, 1000);
+        let mut search_params = octofhir_search::parse_query_string(&query, 1000, 1000);
+        cfg.default_sort
+            .apply(resource_type_str, &mut search_params);
         match octofhir_db_postgres::queries::execute_search_raw_with_config(
             &state.read_db_pool,
             resource_type_str,
@@ -5987,13 +7146,14 @@ pub async fn compartment_search_all(
         )
         .await
         {
-            Ok(result) => {
+            Ok(mut result) => {
                 total += result.entries.len();
+                inflate_raw_resources(&state, &mut result.entries).await?;
 
                 for entry in result.entries {
                     let full_url = Some(format!(
                         "{}/{}/{}",
-                        state.base_url.trim_end_matches('/'),
+                        base_url.as_str().trim_end_matches('/'),
                         entry.resource_type,
                         entry.id
                     ));
@@ -6054,8 +7214,8 @@ pub async fn async_job_status(
             _ => ApiError::Internal(format!("Failed to get job status: {}", e)),
         })?;
 
-    // For bulk export jobs that are completed, return the manifest directly
-    if job.request_type == "bulk_export"
+    // For bulk export/import jobs that are completed, return the manifest directly
+    if matches!(job.request_type.as_str(), "bulk_export" | "bulk_import")
         && job.status == crate::async_jobs::AsyncJobStatus::Completed
         && let Some(result) = job.result
     {
@@ -6063,7 +7223,7 @@ pub async fn async_job_status(
     }
 
     // Build response based on job status
-    let response = json!({
+    let mut response = json!({
         "jobId": job.id,
         "status": job.status,
         "progress": job.progress,
@@ -6075,6 +7235,53 @@ pub async fn async_job_status(
         "errorMessage": job.error_message,
     });
 
+    // Running imports publish per-input counts and errors as a partial result
+    if job.request_type == "bulk_import"
+        && let Some(inputs) = job.result.as_ref().and_then(|r| r.get("inputs"))
+    {
+        response["inputs"] = inputs.clone();
+    }
+
+    // Running group exports publish how many members are done as of their
+    // last checkpoint
+    if job.request_type == "bulk_export"
+        && job.status == crate::async_jobs::AsyncJobStatus::InProgress
+        && let Some(checkpoint) = job
+            .result
+            .as_ref()
+            .filter(|r| r.get("membersExported").is_some())
+    {
+        let mut progress = serde_json::Map::new();
+        for key in ["group", "membersExported", "resourcesExported"] {
+            if let Some(value) = checkpoint.get(key) {
+                progress.insert(key.to_string(), value.clone());
+            }
+        }
+        response["groupProgress"] = serde_json::Value::Object(progress);
+    }
+
+    // Reindex jobs publish processed/total counts and a time estimate
+    if job.request_type == "reindex"
+        && let Some(result) = job.result.as_ref()
+    {
+        for key in ["processed", "total", "estimatedRemainingSeconds"] {
+            if let Some(value) = result.get(key) {
+                response[key] = value.clone();
+            }
+        }
+    }
+
+    // Reference integrity scans publish running counts
+    if job.request_type == crate::admin::reference_integrity::JOB_TYPE
+        && let Some(result) = job.result.as_ref()
+    {
+        for key in ["scannedResources", "danglingTargets"] {
+            if let Some(value) = result.get(key) {
+                response[key] = value.clone();
+            }
+        }
+    }
+
     let status_code = match job.status {
         crate::async_jobs::AsyncJobStatus::Queued
         | crate::async_jobs::AsyncJobStatus::InProgress => StatusCode::ACCEPTED,
@@ -6083,15 +7290,19 @@ pub async fn async_job_status(
         crate::async_jobs::AsyncJobStatus::Cancelled => StatusCode::GONE,
     };
 
-    // Add X-Progress header for in-progress jobs (FHIR Bulk Data spec)
+    // Add X-Progress header for in-progress jobs (FHIR Bulk Data spec).
+    // Queued jobs are waiting for a free job slot.
     let mut headers = axum::http::HeaderMap::new();
     if job.status == crate::async_jobs::AsyncJobStatus::InProgress
         || job.status == crate::async_jobs::AsyncJobStatus::Queued
     {
-        let progress_pct = (job.progress * 100.0).round() as u32;
-        if let Ok(progress_value) =
-            axum::http::HeaderValue::from_str(&format!("Processing: {}% complete", progress_pct))
-        {
+        let progress = if job.status == crate::async_jobs::AsyncJobStatus::Queued {
+            "Queued: waiting for a free job slot".to_string()
+        } else {
+            let progress_pct = (job.progress * 100.0).round() as u32;
+            format!("Processing: {}% complete", progress_pct)
+        };
+        if let Ok(progress_value) = axum::http::HeaderValue::from_str(&progress) {
             headers.insert("X-Progress", progress_value);
         }
         // Also add Retry-After header to suggest when to poll again (in seconds)
@@ -6211,7 +7422,8 @@ pub fn create_async_accepted_response(
 /// GET /fhir/_bulk-files/{job_id}/{filename}
 ///
 /// Serve NDJSON files from bulk export jobs. Returns the file content
-/// with appropriate content type.
+/// with appropriate content type; `.ndjson.gz` files are sent as-is with
+/// `Content-Encoding: gzip`.
 pub async fn bulk_export_file(
     State(state): State<crate::server::AppState>,
     Path((job_id, filename)): Path<(String, String)>,
@@ -6251,6 +7463,12 @@ pub async fn bulk_export_file(
         header::CONTENT_TYPE,
         header::HeaderValue::from_static("application/fhir+ndjson"),
     );
+    if filename.ends_with(".gz") {
+        headers.insert(
+            header::CONTENT_ENCODING,
+            header::HeaderValue::from_static("gzip"),
+        );
+    }
     headers.insert(
         header::CONTENT_DISPOSITION,
         header::HeaderValue::from_str(&format!("attachment; filename=\"{}\"", filename))
@@ -6260,6 +7478,45 @@ pub async fn bulk_export_file(
     Ok((StatusCode::OK, headers, contents))
 }
 
+/// GET /fhir/_attachments/{hash}
+///
+/// Serves attachment content moved to the blob store, which reads link to
+/// when `binary.attachments.on_read = "redirect"`. The hash is looked up in
+/// the current tenant's scope only.
+pub async fn attachment_content(
+    State(state): State<crate::server::AppState>,
+    Path(hash): Path<String>,
+) -> Result<Response, ApiError> {
+    let Some(blobs) = &state.binary_blobs else {
+        return Err(ApiError::not_found("Attachment content not found"));
+    };
+    if !crate::binary::is_content_hash(&hash) {
+        return Err(ApiError::bad_request("Invalid attachment content hash"));
+    }
+    let key = crate::binary::blob_key_for_hash(&hash.to_ascii_lowercase());
+    let data = blobs
+        .store()
+        .get(&key)
+        .await
+        .map_err(map_storage_error)?
+        .ok_or_else(|| ApiError::not_found("Attachment content not found"))?;
+
+    Ok(Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "application/octet-stream")
+        .header(header::CONTENT_LENGTH, data.len())
+        .header(header::CONTENT_DISPOSITION, "attachment")
+        // Content-addressed: the bytes behind a hash never change.
+        .header(
+            header::CACHE_CONTROL,
+            "private, max-age=31536000, immutable",
+        )
+        .header(header::X_CONTENT_TYPE_OPTIONS, "nosniff")
+        .header(header::CONTENT_SECURITY_POLICY, "sandbox")
+        .body(Body::from(data))
+        .unwrap())
+}
+
 // ==================== Package Management API ====================
 
 /// Response type for package list
@@ -7297,6 +8554,7 @@ fn app_validation_errors_to_api_error(errors: Vec<PathValidationError>) -> ApiEr
 /// This allows App operations to be served at any root path (e.g., /psychportal/status).
 pub async fn internal_search_resource(
     state: State<crate::server::AppState>,
+    base_url: BaseUrl,
     headers: HeaderMap,
     Path(resource_type): Path<String>,
     query: Query<HashMap<String, String>>,
@@ -7304,7 +8562,7 @@ pub async fn internal_search_resource(
     request: Request<Body>,
 ) -> Result<Response, ApiError> {
     if is_internal_resource_type(&resource_type) {
-        return search_resource(state, headers, Path(resource_type), query, raw)
+        return search_resource(state, base_url, headers, Path(resource_type), query, raw)
             .await
             .map(IntoResponse::into_response);
     }
@@ -7320,6 +8578,7 @@ pub async fn internal_search_resource(
 /// If not, it checks if there's a matching gateway route and dispatches to it.
 pub async fn internal_create_resource(
     state: State<crate::server::AppState>,
+    base_url: BaseUrl,
     Path(resource_type): Path<String>,
     headers: HeaderMap,
     body: Bytes,
@@ -7349,6 +8608,7 @@ pub async fn internal_create_resource(
 
     let response = create_resource(
         state.clone(),
+        base_url,
         Path(resource_type.clone()),
         headers,
         Json(payload.clone()),
@@ -7437,6 +8697,7 @@ pub async fn internal_read_resource(
 /// If not, it checks if there's a matching gateway route and dispatches to it.
 pub async fn internal_update_resource(
     state: State<crate::server::AppState>,
+    base_url: BaseUrl,
     Path((resource_type, id)): Path<(String, String)>,
     headers: HeaderMap,
     body: Bytes,
@@ -7473,6 +8734,7 @@ pub async fn internal_update_resource(
 
     let response = update_resource(
         state.clone(),
+        base_url,
         Path((resource_type.clone(), id.clone())),
         headers,
         Json(payload.clone()),
@@ -7603,6 +8865,55 @@ mod tests {
         assert!(!is_conditional_reference("Patient?")); // empty query
     }
 
+    #[test]
+    fn entry_resource_type_of_urls() {
+        assert_eq!(entry_resource_type("Patient"), Some("Patient"));
+        assert_eq!(entry_resource_type("Patient/1/_history/2"), Some("Patient"));
+        assert_eq!(
+            entry_resource_type("Patient?identifier=x|1"),
+            Some("Patient")
+        );
+        assert_eq!(entry_resource_type("metadata"), None);
+        assert_eq!(entry_resource_type("$export"), None);
+    }
+
+    #[test]
+    fn versions_body_forms() {
+        assert_eq!(
+            versions_body("4.0", false),
+            json!({
+                "resourceType": "Parameters",
+                "parameter": [
+                    { "name": "version", "valueCode": "4.0" },
+                    { "name": "default", "valueCode": "4.0" }
+                ]
+            })
+        );
+        assert_eq!(
+            versions_body("5.0", true),
+            json!({ "versions": ["5.0"], "default": "5.0" })
+        );
+
+        let accept = |v: &'static str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(header::ACCEPT, header::HeaderValue::from_static(v));
+            headers
+        };
+        assert!(prefers_plain_json(&accept("application/json")));
+        assert!(!prefers_plain_json(&accept(
+            "application/fhir+json, application/json"
+        )));
+        assert!(!prefers_plain_json(&HeaderMap::new()));
+    }
+
+    #[test]
+    fn versions_response_varies_on_accept() {
+        let response = versions_response("4.0", &HeaderMap::new());
+        let headers = response.headers();
+        assert_eq!(headers[header::CACHE_CONTROL], "public, max-age=3600");
+        assert_eq!(headers[header::VARY], "Accept");
+    }
+
     #[test]
     fn single_identifier_condition_extracts_token() {
         assert_eq!(
@@ -7688,6 +8999,31 @@ mod tests {
         assert_eq!(legacy.offset, Some(30));
     }
 
+    #[test]
+    fn test_history_query_params_page() {
+        let cfg = octofhir_search::SearchConfig::new(Arc::new(
+            octofhir_search::SearchParameterRegistry::new(),
+        ));
+
+        let page = |value| {
+            serde_json::from_value::<HistoryQueryParams>(value)
+                .unwrap()
+                .page(&cfg)
+        };
+        assert_eq!(page(json!({})).unwrap(), (0, 10));
+        assert_eq!(
+            page(json!({"_count": 500, "_offset": 20})).unwrap(),
+            (20, 100)
+        );
+
+        let cursor = octofhir_core::PageCursor {
+            offset: 40,
+            count: 20,
+        };
+        assert_eq!(page(json!({"_cursor": cursor.encode()})).unwrap(), (40, 20));
+        assert!(page(json!({"_cursor": "bogus"})).is_err());
+    }
+
     #[tokio::test]
     async fn test_preprocess_app_secret_plaintext() {
         let resource_type = "App";
@@ -7814,6 +9150,57 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_check_sync_search() {
+        let check =
+            |q: &str| check_sync_search(q, &octofhir_search::parse_query_string(q, 10, 100));
+        assert!(check("name=smith&_sort=name").is_ok());
+        assert!(check("name=smith&_sync=true&_count=50").is_ok());
+        assert!(check("_sync=bogus").is_err());
+        assert!(check("_sync=true&_sort=-_lastUpdated").is_err());
+        assert!(check("_sync=true&_offset=10").is_err());
+    }
+
+    #[test]
+    fn test_batch_response_entries() {
+        let created = build_transaction_response_entry(
+            Some(&json!({"resourceType": "Patient", "id": "p1"})),
+            "201 Created",
+            Some("Patient"),
+            Some("p1"),
+            Some("1"),
+        );
+        let minimal = batch_success_entry(created.clone(), PreferReturn::Minimal);
+        assert!(minimal["response"].get("outcome").is_none());
+        let outcome = batch_success_entry(created, PreferReturn::OperationOutcome);
+        assert_eq!(
+            outcome["response"]["outcome"]["issue"][0]["severity"],
+            "information"
+        );
+
+        let failed = batch_error_entry(2, &ApiError::not_found("Patient/missing not found"));
+        assert_eq!(failed["response"]["status"], "404 Not Found");
+        let issue = &failed["response"]["outcome"]["issue"][0];
+        assert_eq!(issue["code"], "not-found");
+        assert_eq!(issue["expression"], json!(["Bundle.entry[2]"]));
+
+        // Detailed validation outcomes are kept, not collapsed to one issue
+        let invalid = ApiError::unprocessable_entity(
+            "Resource validation failed",
+            Some(json!({
+                "resourceType": "OperationOutcome",
+                "issue": [
+                    {"severity": "error", "code": "value", "expression": ["Patient.active"]},
+                    {"severity": "error", "code": "required"}
+                ]
+            })),
+        );
+        let failed = batch_error_entry(0, &invalid);
+        let issues = &failed["response"]["outcome"]["issue"];
+        assert_eq!(issues[0]["expression"], json!(["Patient.active"]));
+        assert_eq!(issues[1]["expression"], json!(["Bundle.entry[0]"]));
+    }
+
     #[test]
     fn test_search_plan_debug_requires_config_flag() {
         let mut settings = crate::config::SearchSettings::default();
@@ -7829,6 +9216,82 @@ mod tests {
         assert!(!search_debug_request(&settings, &headers, "_debug=other").collect_plan());
     }
 
+    #[test]
+    fn test_parse_system_search_types() {
+        assert_eq!(
+            parse_system_search_types("Patient, Observation,Patient,").unwrap(),
+            vec!["Patient".to_string(), "Observation".to_string()]
+        );
+        assert!(parse_system_search_types("Patient,not-a-type").is_err());
+        assert!(parse_system_search_types(" , ").is_err());
+    }
+
+    #[test]
+    fn test_system_search_sort() {
+        let configured = octofhir_search::DefaultSort::default();
+        let default = system_search_sort(None, &configured).unwrap();
+        assert_eq!(default.len(), 2);
+        assert_eq!(default[0].field, "_lastUpdated");
+        assert!(!default[0].descending);
+        assert_eq!(default[1].field, "_id");
+
+        let unordered = octofhir_search::DefaultSort::new(&[], &Default::default());
+        let default = system_search_sort(None, &unordered).unwrap();
+        assert_eq!(default.len(), 1);
+        assert_eq!(default[0].field, "_lastUpdated");
+        assert!(default[0].descending);
+
+        let sort = [octofhir_storage::SortParam::new("_id", false)];
+        assert_eq!(
+            system_search_sort(Some(&sort), &configured).unwrap()[0].field,
+            "_id"
+        );
+
+        let sort = [octofhir_storage::SortParam::new("birthdate", false)];
+        assert!(system_search_sort(Some(&sort), &configured).is_err());
+    }
+
+    #[test]
+    fn test_merge_sort_keys_stop_at_other_fields() {
+        let sort = octofhir_search::parse_sort_fields("-_lastUpdated,date,_id");
+        let keys = merge_sort_keys(&sort);
+        assert_eq!(keys.len(), 1);
+        assert_eq!(keys[0].field, "_lastUpdated");
+        assert!(merge_sort_keys(&octofhir_search::parse_sort_fields("date,_id")).is_empty());
+    }
+
+    #[test]
+    fn test_system_search_merge_order_is_deterministic() {
+        let at = |secs: i64| time::OffsetDateTime::from_unix_timestamp(secs).unwrap();
+        let raw = |rt: &str, id: &str, secs: i64| octofhir_storage::RawStoredResource {
+            id: id.to_string(),
+            version_id: "1".to_string(),
+            resource_type: rt.to_string(),
+            resource_json: "{}".to_string(),
+            last_updated: at(secs),
+            created_at: at(secs),
+        };
+        let newest_first =
+            octofhir_search::DefaultSort::new(&["-_lastUpdated".into()], &Default::default());
+        let sort = system_search_sort(None, &newest_first).unwrap();
+        let mut matches = [
+            raw("Patient", "b", 10),
+            raw("Observation", "a", 20),
+            raw("Patient", "a", 10),
+            raw("Observation", "z", 10),
+        ];
+        matches.sort_by(|a, b| compare_system_search_matches(a, b, &sort));
+        let order: Vec<_> = matches
+            .iter()
+            .map(|m| format!("{}/{}", m.resource_type, m.id))
+            .collect();
+        // Newest first; equal timestamps fall back to type, then id
+        assert_eq!(
+            order,
+            vec!["Observation/a", "Observation/z", "Patient/a", "Patient/b"]
+        );
+    }
+
     #[test]
     fn test_search_plan_debug_header_request() {
         let settings = crate::config::SearchSettings {
@@ -7927,6 +9390,23 @@ mod tests {
         assert!(!rendered.contains("$1"));
     }
 
+    #[test]
+    fn test_pagination_suffix_preserves_repeats_and_modifiers() {
+        let raw_q = "name:contains=van%20der&_include=Encounter:subject&_include=Encounter:participant\
+                     &_count=20&_summary=true&status=finished,in-progress&_offset=40";
+
+        assert_eq!(
+            build_query_suffix_for_links(raw_q),
+            Some(
+                "name:contains=van%20der&_include=Encounter:subject\
+                 &_include=Encounter:participant&status=finished,in-progress"
+                    .to_string()
+            )
+        );
+        assert_eq!(build_query_suffix_for_links("_count=5&_offset=0"), None);
+        assert_eq!(build_query_suffix_for_links(""), None);
+    }
+
     #[test]
     fn test_debug_param_removed_from_pagination_suffix() {
         let raw_q = strip_search_debug_params("birthdate=ge2000-01-01&_debug=search-plan&_count=5");
@@ -7964,4 +9444,51 @@ mod tests {
                 && tag["code"] == "SUBSETTED"
         }));
     }
+
+    #[test]
+    fn test_capped_history_count() {
+        assert_eq!(capped_history_count(0, 500, 100), 100);
+        assert_eq!(capped_history_count(1000, 0, 100), 100);
+        assert_eq!(capped_history_count(1000, 950, 100), 50);
+        assert_eq!(capped_history_count(1000, 1000, 100), 0);
+        assert_eq!(capped_history_count(1000, 5000, 100), 0);
+    }
+
+    #[tokio::test]
+    async fn test_invalidate_bundle_entry_per_tenant() {
+        let now = time::OffsetDateTime::now_utc();
+        let stored = octofhir_storage::RawStoredResource {
+            id: "p1".to_string(),
+            version_id: "1".to_string(),
+            resource_type: "Patient".to_string(),
+            resource_json: r#"{"resourceType":"Patient","id":"p1"}"#.to_string(),
+            last_updated: now,
+            created_at: now,
+        };
+        let cache = crate::cache::ResourceCache::new(100, std::time::Duration::from_secs(60));
+        let acme = octofhir_storage::TenantId::new("acme").unwrap();
+        cache.set(&stored).await;
+        octofhir_storage::with_tenant(acme.clone(), cache.set(&stored)).await;
+
+        let deleted = build_transaction_response_entry(
+            None,
+            "204 No Content",
+            Some("Patient"),
+            Some("p1"),
+            None,
+        );
+        octofhir_storage::with_tenant(acme.clone(), invalidate_bundle_entry(&cache, &deleted))
+            .await;
+
+        assert!(
+            octofhir_storage::with_tenant(acme, cache.get("Patient", "p1"))
+                .await
+                .is_none()
+        );
+        assert!(cache.get("Patient", "p1").await.is_some());
+
+        // Entries without a location touch nothing
+        invalidate_bundle_entry(&cache, &json!({"response": {"status": "200 OK"}})).await;
+        assert!(cache.get("Patient", "p1").await.is_some());
+    }
 }
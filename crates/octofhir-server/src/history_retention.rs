@@ -0,0 +1,138 @@
+//! Scheduled pruning of old resource versions.
+//!
+//! Runs every `history_retention.interval_secs`, applying the rule of each
+//! resource type (see [`HistoryRetentionConfig::rule_for`]) to its history
+//! table with [`octofhir_db_postgres::queries::prune_history`]. With
+//! multi-tenancy on, every tenant schema is pruned after the shared one.
+//! A run that prunes anything asks for a blob sweep, as the pruned versions
+//! may have been the last to reference some blobs.
+
+use std::time::Duration;
+
+use octofhir_db_postgres::queries::{history_table_resource_types, prune_history};
+use octofhir_storage::{StorageError, TenantId, with_tenant};
+use sqlx_postgres::PgPool;
+use tokio::task::JoinHandle;
+
+use crate::binary::BinaryBlobs;
+use crate::config::HistoryRetentionConfig;
+
+const SECS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Spawns the pruning task. The first run happens one interval after start.
+pub fn spawn_history_retention(
+    pool: PgPool,
+    config: HistoryRetentionConfig,
+    tenants: Vec<TenantId>,
+    blobs: Option<BinaryBlobs>,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(config.interval_secs));
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        // Skip the immediate first tick — let the server warm up first.
+        ticker.tick().await;
+
+        loop {
+            ticker.tick().await;
+            let mut pruned = match prune_schema(&pool, &config).await {
+                Ok(pruned) => pruned,
+                Err(e) => {
+                    tracing::warn!(error = %e, "History retention run failed");
+                    0
+                }
+            };
+            for tenant in &tenants {
+                match with_tenant(tenant.clone(), prune_schema(&pool, &config)).await {
+                    Ok(count) => pruned += count,
+                    Err(e) => {
+                        tracing::warn!(tenant = %tenant, error = %e, "History retention run failed");
+                    }
+                }
+            }
+            if pruned > 0 {
+                tracing::info!(pruned = pruned, "Pruned resource history");
+                if let Some(blobs) = &blobs {
+                    blobs.request_sweep();
+                }
+            }
+        }
+    })
+}
+
+/// Prunes every history table in the current schema. Returns the number of
+/// versions deleted.
+async fn prune_schema(pool: &PgPool, config: &HistoryRetentionConfig) -> Result<u64, StorageError> {
+    let mut total = 0;
+    for resource_type in history_table_resource_types(pool).await? {
+        let rule = config.rule_for(&resource_type);
+        if rule.is_unlimited() {
+            continue;
+        }
+        let max_age = rule
+            .max_age_days
+            .map(|days| Duration::from_secs(u64::from(days) * SECS_PER_DAY));
+        let pruned = prune_history(
+            pool,
+            &resource_type,
+            rule.keep_versions,
+            max_age,
+            config.batch_size,
+        )
+        .await?;
+        if pruned > 0 {
+            crate::metrics::record_history_pruned(&resource_type, pruned);
+            tracing::debug!(
+                resource_type = %resource_type,
+                pruned = pruned,
+                "Pruned resource history"
+            );
+        }
+        total += pruned;
+    }
+    Ok(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::config::HistoryRetentionRule;
+
+    use super::*;
+
+    #[test]
+    fn test_rule_for_prefers_type_entry() {
+        let config = HistoryRetentionConfig {
+            keep_versions: Some(10),
+            max_age_days: Some(365),
+            resource_types: HashMap::from([
+                ("Consent".to_string(), HistoryRetentionRule::default()),
+                (
+                    "AuditEvent".to_string(),
+                    HistoryRetentionRule {
+                        keep_versions: None,
+                        max_age_days: Some(90),
+                    },
+                ),
+            ]),
+            ..HistoryRetentionConfig::default()
+        };
+
+        // Table names are lower case.
+        assert!(config.rule_for("consent").is_unlimited());
+        assert_eq!(
+            config.rule_for("auditevent"),
+            HistoryRetentionRule {
+                keep_versions: None,
+                max_age_days: Some(90),
+            }
+        );
+        assert_eq!(
+            config.rule_for("patient"),
+            HistoryRetentionRule {
+                keep_versions: Some(10),
+                max_age_days: Some(365),
+            }
+        );
+    }
+}
@@ -15,7 +15,8 @@ use crate::audit::{AuditAction, AuditEventBuilder, AuditOutcome, AuditService};
 ///
 /// When a resource is created, updated, or deleted, this hook:
 /// 1. Checks if audit logging is enabled for this resource type
-/// 2. Builds a FHIR AuditEvent resource
+/// 2. Builds a FHIR AuditEvent resource, with the changed content as
+///    `entity.detail` when `audit.detail` asks for it
 /// 3. Stores it asynchronously (fire-and-forget)
 ///
 /// # Example
@@ -78,7 +79,13 @@ impl ResourceHook for AsyncAuditHook {
 
         // Log the event asynchronously (fire-and-forget)
         let audit_service = self.audit_service.clone();
+        let changed = event.clone();
         tokio::spawn(async move {
+            let mut audit_builder = audit_builder;
+            // Changed content at the configured detail level, PHI redacted
+            if let Some((detail_type, value)) = audit_service.change_detail(&changed).await {
+                audit_builder = audit_builder.entity_detail(detail_type, value);
+            }
             if let Err(e) = audit_service.log(audit_builder).await {
                 warn!(error = %e, "Failed to log audit event");
             }
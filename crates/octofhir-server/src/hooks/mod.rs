@@ -8,6 +8,7 @@
 //! - [`SearchParamHook`] - Updates search parameter registry on SearchParameter changes
 //! - [`GraphQLSubscriptionHook`] - Forwards events to GraphQL subscription broadcaster
 //! - [`AsyncAuditHook`] - Logs resource changes asynchronously as FHIR AuditEvents
+//! - [`ResourceCacheHook`] - Evicts changed resources from the read cache
 //!
 //! # Architecture
 //!
@@ -41,12 +42,14 @@ mod audit;
 mod gateway;
 mod graphql;
 mod policy;
+mod resource_cache;
 mod search;
 
 pub use audit::AsyncAuditHook;
 pub use gateway::GatewayReloadHook;
 pub use graphql::GraphQLSubscriptionHook;
 pub use policy::PolicyReloadHook;
+pub use resource_cache::ResourceCacheHook;
 pub use search::SearchParamHook;
 
 // Re-export core types for convenience
@@ -0,0 +1,114 @@
+//! Resource cache invalidation hook.
+//!
+//! This hook evicts resources from the read cache when they change. Because it
+//! runs on the event system, it also covers writes that bypass the CRUD
+//! handlers (transactions, batch, bulk import) and, with Redis event sync,
+//! writes made on other server instances. Writes inside a tenant scope emit no
+//! events; the handlers evict those entries themselves.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use octofhir_core::events::{HookError, ResourceEvent, ResourceHook};
+use tracing::debug;
+
+use crate::cache::ResourceCache;
+
+/// Hook that invalidates cached resources on any resource change.
+///
+/// # Example
+///
+/// ```ignore
+/// let hook = ResourceCacheHook::new(resource_cache.clone());
+/// registry.register(Arc::new(hook));
+/// ```
+pub struct ResourceCacheHook {
+    cache: Arc<ResourceCache>,
+}
+
+impl ResourceCacheHook {
+    /// Create a new resource cache hook.
+    ///
+    /// # Arguments
+    ///
+    /// * `cache` - The resource read cache to invalidate
+    pub fn new(cache: Arc<ResourceCache>) -> Self {
+        Self { cache }
+    }
+}
+
+#[async_trait]
+impl ResourceHook for ResourceCacheHook {
+    fn name(&self) -> &str {
+        "resource_cache"
+    }
+
+    fn resource_types(&self) -> &[&str] {
+        &[] // all resource types
+    }
+
+    async fn handle(&self, event: &ResourceEvent) -> Result<(), HookError> {
+        debug!(
+            resource_type = %event.resource_type,
+            resource_id = %event.resource_id,
+            event_type = %event.event_type,
+            "ResourceCacheHook: invalidating cached resource"
+        );
+
+        self.cache
+            .invalidate(&event.resource_type, &event.resource_id)
+            .await;
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for ResourceCacheHook {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ResourceCacheHook")
+            .field("entries", &self.cache.entry_count())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use octofhir_storage::RawStoredResource;
+    use serde_json::json;
+    use std::time::Duration;
+    use time::OffsetDateTime;
+
+    fn stored(resource_type: &str, id: &str) -> RawStoredResource {
+        let now = OffsetDateTime::now_utc();
+        RawStoredResource {
+            id: id.to_string(),
+            version_id: "1".to_string(),
+            resource_type: resource_type.to_string(),
+            resource_json: format!(r#"{{"resourceType":"{resource_type}","id":"{id}"}}"#),
+            last_updated: now,
+            created_at: now,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_invalidates_changed_resource() {
+        let cache = Arc::new(ResourceCache::new(100, Duration::from_secs(60)));
+        cache.set(&stored("Patient", "p1")).await;
+        cache.set(&stored("Patient", "p2")).await;
+
+        let hook = ResourceCacheHook::new(cache.clone());
+        assert!(hook.matches(&ResourceEvent::created("Observation", "o1", json!({}))));
+
+        hook.handle(&ResourceEvent::updated("Patient", "p1", json!({})))
+            .await
+            .unwrap();
+
+        assert!(cache.get("Patient", "p1").await.is_none());
+        assert!(cache.get("Patient", "p2").await.is_some());
+
+        hook.handle(&ResourceEvent::deleted("Patient", "p2"))
+            .await
+            .unwrap();
+        assert!(cache.get("Patient", "p2").await.is_none());
+    }
+}
@@ -11,6 +11,7 @@ pub mod admin;
 pub mod app_platform;
 pub mod async_jobs;
 pub mod audit;
+pub mod binary;
 pub mod bootstrap;
 // pub mod automations;
 pub mod cache;
@@ -19,8 +20,10 @@ pub mod compartments;
 pub mod config;
 pub mod config_manager;
 pub mod events;
+pub mod forwarded;
 pub mod gateway;
 pub mod handlers;
+pub mod history_retention;
 pub mod hooks;
 pub mod json_schema;
 pub mod log_stream;
@@ -36,12 +39,19 @@ pub mod observability;
 pub mod operation_registry;
 pub mod operations;
 pub mod patch;
+pub mod rate_limit;
 pub mod reconcile;
+pub mod reference_integrity;
 pub mod reference_resolver;
+pub mod request_features;
+pub mod request_timing;
 pub mod rest_console;
 pub mod routes;
 pub mod server;
+pub mod storage_adapter;
+pub mod storage_metrics;
 pub mod subscriptions;
+pub mod tenant;
 pub mod terminology_service;
 pub mod validation;
 
@@ -54,7 +64,10 @@ pub use config::{
 };
 pub use config_manager::{ServerConfigManager, ServerConfigManagerBuilder};
 pub use observability::{init_tracing, shutdown_tracing};
-pub use server::{AppState, OctofhirServer, ServerBuilder, build_app};
+pub use server::{
+    AppState, OctofhirServer, ServerBuilder, ShutdownCoordinator, build_app,
+    build_app_with_shutdown,
+};
 
 /// Create a cache backend based on configuration.
 ///
@@ -228,6 +228,26 @@ mod tests {
         );
     }
 
+    #[cfg(feature = "arbitrary-precision")]
+    #[test]
+    fn decimals_round_trip_exactly() {
+        let raw = r#"{"resourceType":"Observation","valueQuantity":{"value":1.00000000000000001},"component":[{"valueQuantity":{"value":12.50}}]}"#;
+        let body: Value = serde_json::from_str(raw).unwrap();
+        let env = envelope_from_json("Observation", &body, IdPolicy::Create).expect("map");
+
+        // Through the envelope and the storage serialization path
+        let stored = serde_json::to_string(&json_from_envelope(&env)).unwrap();
+        let read: Value = serde_json::from_str(&stored).unwrap();
+        assert_eq!(
+            read["valueQuantity"]["value"].to_string(),
+            "1.00000000000000001"
+        );
+        assert_eq!(
+            read["component"][0]["valueQuantity"]["value"].to_string(),
+            "12.50"
+        );
+    }
+
     #[test]
     fn wrong_resource_type_is_rejected() {
         let body = json!({"resourceType": "Observation"});
@@ -5,6 +5,9 @@
 //! - Database pool metrics (connections, utilization)
 //! - Cache metrics (hit/miss rates, entries)
 //! - FHIR-specific metrics (resources by type)
+//! - Storage metrics (per-operation latency and errors by resource type)
+//! - Async job metrics (jobs queued and running by type)
+//! - History retention metrics (versions pruned by resource type)
 
 use metrics::{counter, gauge, histogram};
 use metrics_exporter_prometheus::{PrometheusBuilder, PrometheusHandle};
@@ -20,12 +23,17 @@ pub mod names {
     pub const HTTP_REQUESTS_TOTAL: &str = "http_requests_total";
     pub const HTTP_REQUEST_DURATION_SECONDS: &str = "http_request_duration_seconds";
     pub const HTTP_ACTIVE_CONNECTIONS: &str = "http_active_connections";
+    pub const HTTP_REQUEST_TIMEOUTS_TOTAL: &str = "http_request_timeouts_total";
+    pub const HTTP_SLOW_REQUESTS_TOTAL: &str = "http_slow_requests_total";
 
     // Database pool metrics
     pub const DB_POOL_CONNECTIONS_TOTAL: &str = "db_pool_connections_total";
     pub const DB_POOL_CONNECTIONS_IDLE: &str = "db_pool_connections_idle";
     pub const DB_POOL_CONNECTIONS_ACTIVE: &str = "db_pool_connections_active";
     pub const DB_POOL_ACQUIRE_DURATION_SECONDS: &str = "db_pool_acquire_duration_seconds";
+    pub const DB_POOL_ACQUIRE_TIMEOUTS_TOTAL: &str = "db_pool_acquire_timeouts_total";
+    pub const DB_QUERIES_CANCELED_ON_DISCONNECT_TOTAL: &str =
+        "db_queries_canceled_on_disconnect_total";
 
     // Cache metrics
     pub const CACHE_HITS_TOTAL: &str = "cache_hits_total";
@@ -35,6 +43,15 @@ pub mod names {
     // FHIR metrics
     pub const FHIR_RESOURCES_TOTAL: &str = "fhir_resources_total";
     pub const FHIR_OPERATIONS_TOTAL: &str = "fhir_operations_total";
+
+    // Storage metrics
+    pub const STORAGE_OPERATION_DURATION_SECONDS: &str = "storage_operation_duration_seconds";
+    pub const STORAGE_OPERATION_ERRORS_TOTAL: &str = "storage_operation_errors_total";
+    pub const HISTORY_VERSIONS_PRUNED_TOTAL: &str = "history_versions_pruned_total";
+
+    // Async job metrics
+    pub const ASYNC_JOBS_QUEUED: &str = "async_jobs_queued";
+    pub const ASYNC_JOBS_RUNNING: &str = "async_jobs_running";
 }
 
 /// Initialize the Prometheus metrics exporter.
@@ -116,6 +133,26 @@ pub fn decrement_active_connections() {
     gauge!(names::HTTP_ACTIVE_CONNECTIONS).decrement(1.0);
 }
 
+/// Record a request that was abandoned after its timeout.
+pub fn record_request_timeout(class: &'static str) {
+    counter!(names::HTTP_REQUEST_TIMEOUTS_TOTAL, "class" => class).increment(1);
+}
+
+/// Record statements canceled because their client disconnected.
+pub fn record_queries_canceled_on_disconnect(count: usize) {
+    counter!(names::DB_QUERIES_CANCELED_ON_DISCONNECT_TOTAL).increment(count as u64);
+}
+
+/// Record a request that exceeded the slow-request threshold.
+pub fn record_slow_request(method: &str, path: &str) {
+    counter!(
+        names::HTTP_SLOW_REQUESTS_TOTAL,
+        "method" => method.to_string(),
+        "path" => normalize_path(path)
+    )
+    .increment(1);
+}
+
 // =============================================================================
 // Database Pool Metrics
 // =============================================================================
@@ -132,6 +169,11 @@ pub fn record_db_acquire_duration(duration: Duration) {
     histogram!(names::DB_POOL_ACQUIRE_DURATION_SECONDS).record(duration.as_secs_f64());
 }
 
+/// Record a request that found no free database connection in time.
+pub fn record_db_acquire_timeout() {
+    counter!(names::DB_POOL_ACQUIRE_TIMEOUTS_TOTAL).increment(1);
+}
+
 // =============================================================================
 // Cache Metrics
 // =============================================================================
@@ -142,8 +184,8 @@ pub fn record_cache_hit(tier: &str) {
 }
 
 /// Record a cache miss.
-pub fn record_cache_miss() {
-    counter!(names::CACHE_MISSES_TOTAL).increment(1);
+pub fn record_cache_miss(tier: &str) {
+    counter!(names::CACHE_MISSES_TOTAL, "tier" => tier.to_string()).increment(1);
 }
 
 /// Set the number of cache entries.
@@ -172,6 +214,70 @@ pub fn record_fhir_operation(operation: &str, resource_type: Option<&str>) {
     .increment(1);
 }
 
+// =============================================================================
+// Storage Metrics
+// =============================================================================
+
+/// Record the latency of a storage operation, and count it as an error when
+/// it failed. `error_category` is the storage error category (`not_found`,
+/// `conflict`, `busy`, ...) and `None` for a successful call.
+pub fn record_storage_operation(
+    operation: &'static str,
+    resource_type: &str,
+    duration: Duration,
+    error_category: Option<&str>,
+) {
+    histogram!(
+        names::STORAGE_OPERATION_DURATION_SECONDS,
+        "operation" => operation,
+        "resource_type" => resource_type.to_string()
+    )
+    .record(duration.as_secs_f64());
+
+    if let Some(category) = error_category {
+        counter!(
+            names::STORAGE_OPERATION_ERRORS_TOTAL,
+            "operation" => operation,
+            "resource_type" => resource_type.to_string(),
+            "category" => category.to_string()
+        )
+        .increment(1);
+    }
+}
+
+/// Record resource versions deleted by history retention.
+pub fn record_history_pruned(resource_type: &str, count: u64) {
+    counter!(
+        names::HISTORY_VERSIONS_PRUNED_TOTAL,
+        "resource_type" => resource_type.to_string()
+    )
+    .increment(count);
+}
+
+// =============================================================================
+// Async Job Metrics
+// =============================================================================
+
+/// Increment async jobs of a type waiting for a free slot.
+pub fn increment_async_jobs_queued(request_type: &str) {
+    gauge!(names::ASYNC_JOBS_QUEUED, "type" => request_type.to_string()).increment(1.0);
+}
+
+/// Decrement async jobs of a type waiting for a free slot.
+pub fn decrement_async_jobs_queued(request_type: &str) {
+    gauge!(names::ASYNC_JOBS_QUEUED, "type" => request_type.to_string()).decrement(1.0);
+}
+
+/// Increment async jobs of a type executing.
+pub fn increment_async_jobs_running(request_type: &str) {
+    gauge!(names::ASYNC_JOBS_RUNNING, "type" => request_type.to_string()).increment(1.0);
+}
+
+/// Decrement async jobs of a type executing.
+pub fn decrement_async_jobs_running(request_type: &str) {
+    gauge!(names::ASYNC_JOBS_RUNNING, "type" => request_type.to_string()).decrement(1.0);
+}
+
 // =============================================================================
 // Helpers
 // =============================================================================
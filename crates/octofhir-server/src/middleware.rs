@@ -8,7 +8,7 @@ use axum::{
     body::Body,
     http::{
         HeaderName, HeaderValue, Request, StatusCode,
-        header::{AUTHORIZATION, COOKIE, UPGRADE},
+        header::{AUTHORIZATION, COOKIE, RETRY_AFTER, UPGRADE},
     },
     middleware::Next,
     response::Response,
@@ -18,11 +18,13 @@ use uuid::Uuid;
 
 use base64::{Engine, engine::general_purpose::STANDARD};
 use octofhir_auth::config::CookieConfig;
+use octofhir_auth::federation::ExternalTokenValidator;
 use octofhir_auth::middleware::{AuthContext, AuthState, UserContext};
 use octofhir_auth::policy::{
     AccessDecision, DenyReason, PolicyContext, PolicyContextBuilder, PolicyEvaluator,
 };
 use octofhir_auth::token::jwt::AccessTokenClaims;
+use octofhir_auth::types::RefreshToken;
 
 use crate::bootstrap::DEFAULT_UI_CLIENT_ID;
 use crate::cache::{AuthContextCache, JwtVerificationCache};
@@ -341,6 +343,13 @@ async fn validate_token(
     jwt_cache: &Arc<JwtVerificationCache>,
     token: &str,
 ) -> Result<Arc<AuthContext>, octofhir_auth::AuthError> {
+    // Tokens from trusted external issuers are verified against their JWKS
+    if let Some(validator) = &state.external_token_validator
+        && validator.trusts(token)
+    {
+        return validate_external_token(validator, auth_cache, token).await;
+    }
+
     // Try JWT verification cache first (avoids expensive signature verification)
     let claims = if let Some(cached_claims) = jwt_cache.get(token) {
         tracing::trace!(jti = %cached_claims.jti, "JWT verification cache hit");
@@ -438,6 +447,35 @@ async fn validate_token(
     Ok(arc_context)
 }
 
+/// Validate an access token issued by a trusted external issuer.
+///
+/// External tokens have no local client, user or revocation record, so the
+/// validated context is cached by token hash until the token expires.
+async fn validate_external_token(
+    validator: &ExternalTokenValidator,
+    auth_cache: &Arc<dyn AuthContextCache>,
+    token: &str,
+) -> Result<Arc<AuthContext>, octofhir_auth::AuthError> {
+    let cache_key = format!("ext:{}", RefreshToken::hash_token(token));
+    let now = time::OffsetDateTime::now_utc().unix_timestamp();
+
+    if let Some(cached_ctx) = auth_cache.get(&cache_key).await {
+        if cached_ctx.token_claims.exp < now {
+            auth_cache.invalidate(&cache_key).await;
+            return Err(octofhir_auth::AuthError::TokenExpired);
+        }
+        return Ok(cached_ctx);
+    }
+
+    let auth_context = validator.authenticate(token).await?;
+    tracing::debug!(
+        issuer = %auth_context.token_claims.iss,
+        client_id = %auth_context.client.client_id,
+        "External token validated"
+    );
+    Ok(auth_cache.insert(cache_key, auth_context).await)
+}
+
 /// Extract token from cookie if cookie auth is enabled.
 fn extract_token_from_cookie(req: &Request<Body>, cookie_config: &CookieConfig) -> Option<String> {
     // Only try cookie auth if enabled
@@ -652,8 +690,14 @@ fn starts_with_ignore_ascii_case(haystack: &[u8], prefix: &[u8]) -> bool {
 /// Returns `Some(error_response)` if the request has an unsupported Accept or Content-Type
 /// for FHIR paths (`/fhir/*`). Returns `None` if the request is acceptable or not a FHIR path.
 ///
+/// A `fhirVersion` MIME parameter must match the served release (`fhir_mime_version`):
+/// an Accept pinning only other versions gets 406, a body declared in another version 415.
+///
+/// A `_format` query parameter takes precedence over Accept; a format the server
+/// cannot produce gets 406.
+///
 /// This is called inline from `auth_middleware` to avoid a separate middleware layer.
-fn check_content_negotiation(req: &Request<Body>) -> Option<Response> {
+fn check_content_negotiation(req: &Request<Body>, fhir_mime_version: &str) -> Option<Response> {
     let path = req.uri().path();
 
     // Only enforce for FHIR paths
@@ -661,7 +705,21 @@ fn check_content_negotiation(req: &Request<Body>) -> Option<Response> {
         return None;
     }
 
-    let accepts_hdr = req.headers().get("accept").and_then(|v| v.to_str().ok());
+    let format = match format_param(req.uri().query()) {
+        Some(value) => match octofhir_api::format_param_media_type(&value) {
+            Some(media_type) => Some(media_type),
+            None => {
+                return Some(error_response(
+                    StatusCode::NOT_ACCEPTABLE,
+                    &format!("Unsupported _format '{value}'; only JSON is supported"),
+                ));
+            }
+        },
+        None => None,
+    };
+    let accepts_hdr = format
+        .as_deref()
+        .or_else(|| req.headers().get("accept").and_then(|v| v.to_str().ok()));
     let accept_ok = accepts_hdr
         .map(|v| {
             let v_lower = v.as_bytes();
@@ -679,6 +737,20 @@ fn check_content_negotiation(req: &Request<Body>) -> Option<Response> {
         ));
     }
 
+    // `$versions` is how a client finds the right fhirVersion, so it must
+    // answer whatever version the client asked for.
+    if let Some(accept) = accepts_hdr
+        && path != "/fhir/$versions"
+        && !octofhir_api::accepts_fhir_version(accept, fhir_mime_version)
+    {
+        return Some(error_response(
+            StatusCode::NOT_ACCEPTABLE,
+            &format!(
+                "Unsupported fhirVersion in Accept; this server supports fhirVersion={fhir_mime_version}"
+            ),
+        ));
+    }
+
     let method = req.method();
     let needs_body_type = method == axum::http::Method::POST || method == axum::http::Method::PUT;
 
@@ -707,11 +779,49 @@ fn check_content_negotiation(req: &Request<Body>) -> Option<Response> {
                 "Content-Type must be application/fhir+json or application/json",
             ));
         }
+        if let Some(requested) = content_type.and_then(octofhir_api::fhir_version_param)
+            && octofhir_api::fhir_mime_version(requested) != fhir_mime_version
+        {
+            return Some(error_response(
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                &format!(
+                    "Content-Type fhirVersion={requested} is not supported; this server supports fhirVersion={fhir_mime_version}"
+                ),
+            ));
+        }
     }
 
     None
 }
 
+/// Value of the `_format` query parameter, if present.
+fn format_param(query: Option<&str>) -> Option<String> {
+    url::form_urlencoded::parse(query?.as_bytes())
+        .find(|(name, _)| name == "_format")
+        .map(|(_, value)| value.into_owned())
+}
+
+/// Check the resource type of FHIR paths against `fhir.resource_types`.
+///
+/// Returns a 404 response when the first segment below `/fhir/` names a
+/// resource type the server does not expose. Other segments (`metadata`,
+/// `_history`, `$operation`) never start with an uppercase letter. Storage
+/// enforces the same allowlist for everything a request reads or searches.
+fn check_resource_type_allowed(req: &Request<Body>) -> Option<Response> {
+    let resource_type = req.uri().path().strip_prefix("/fhir/")?.split('/').next()?;
+    if !resource_type.starts_with(|c: char| c.is_ascii_uppercase())
+        || octofhir_storage::is_resource_type_allowed(resource_type)
+    {
+        return None;
+    }
+    Some(
+        octofhir_api::ApiError::not_found(format!(
+            "Resource type '{resource_type}' is not supported by this server"
+        ))
+        .into_response(),
+    )
+}
+
 fn error_response(status: StatusCode, msg: &str) -> Response {
     let body: Value = json!({
         "resourceType": "OperationOutcome",
@@ -761,6 +871,8 @@ pub struct AuthorizationState {
     pub policy_evaluator: Arc<PolicyEvaluator>,
     /// Operation registry for public path lookups.
     pub operation_registry: Arc<OperationRegistryService>,
+    /// Include the deny reason in the 403 OperationOutcome diagnostics.
+    pub expose_deny_reason: bool,
 }
 
 impl AuthorizationState {
@@ -773,8 +885,16 @@ impl AuthorizationState {
         Self {
             policy_evaluator,
             operation_registry,
+            expose_deny_reason: false,
         }
     }
+
+    /// Include the deny reason in 403 responses (debugging aid).
+    #[must_use]
+    pub fn with_expose_deny_reason(mut self, expose: bool) -> Self {
+        self.expose_deny_reason = expose;
+        self
+    }
 }
 
 // =============================================================================
@@ -802,6 +922,10 @@ pub struct CombinedAuthState {
     pub anonymous_access: bool,
     /// Shared anonymous AuthContext used when `anonymous_access` is true.
     pub anonymous_context: Arc<AuthContext>,
+    /// Include the deny reason in the 403 OperationOutcome diagnostics.
+    pub expose_deny_reason: bool,
+    /// `fhirVersion` MIME parameter of the served FHIR release (e.g. `4.0`).
+    pub fhir_mime_version: &'static str,
 }
 
 /// Authorization middleware that enforces policy-based access control.
@@ -857,30 +981,19 @@ pub async fn authorization_middleware(
             req.extensions_mut().insert(policy_context);
             next.run(req).await
         }
-        AccessDecision::Deny(reason) => {
-            tracing::info!(
-                reason = %reason.message,
-                code = %reason.code,
-                user = ?auth_context.user.as_ref().map(|u| &u.username),
-                path = %req.uri().path(),
-                "Access denied"
-            );
-            forbidden_response(&reason)
-        }
-        AccessDecision::Abstain => {
-            // Default deny if all policies abstain
-            tracing::info!(
-                user = ?auth_context.user.as_ref().map(|u| &u.username),
-                path = %req.uri().path(),
-                "Access denied: no matching policy"
-            );
-            forbidden_response(&DenyReason {
-                code: "no-matching-policy".to_string(),
-                message: "No policy matched this request".to_string(),
-                details: None,
-                policy_id: None,
-            })
-        }
+        AccessDecision::Deny(reason) => deny_response(
+            &policy_context,
+            &auth_context,
+            &reason,
+            state.expose_deny_reason,
+        ),
+        // Default deny if all policies abstain
+        AccessDecision::Abstain => deny_response(
+            &policy_context,
+            &auth_context,
+            &DenyReason::no_matching_policy(),
+            state.expose_deny_reason,
+        ),
     }
 }
 
@@ -924,21 +1037,26 @@ pub async fn auth_middleware(
     mut req: Request<Body>,
     next: Next,
 ) -> Response {
+    let auth_started = std::time::Instant::now();
+
     // Content negotiation check (merged from separate middleware layer)
-    if let Some(response) = check_content_negotiation(&req) {
+    if let Some(response) = check_content_negotiation(&req, state.fhir_mime_version) {
+        return response;
+    }
+    if let Some(response) = check_resource_type_allowed(&req) {
         return response;
     }
 
     // Single public-path check (replaces two separate checks in authn + authz)
     if should_skip_auth(&req, &state.operation_registry) {
-        return next.run(req).await;
+        return run_authorized(auth_started, req, next).await;
     }
 
     // Inject the shared anonymous AuthContext; Arc::clone is one atomic.
     if state.anonymous_access {
         req.extensions_mut()
             .insert(Arc::clone(&state.anonymous_context));
-        return next.run(req).await;
+        return run_authorized(auth_started, req, next).await;
     }
 
     // --- Authentication phase ---
@@ -956,9 +1074,11 @@ pub async fn auth_middleware(
         if let Some(encoded) = auth_header.strip_prefix("Basic ") {
             let encoded = encoded.to_string();
             return handle_basic_auth_then_authorize(
+                auth_started,
                 auth_state,
                 &encoded,
                 &state.policy_evaluator,
+                state.expose_deny_reason,
                 req,
                 next,
             )
@@ -1071,39 +1191,41 @@ pub async fn auth_middleware(
     match decision {
         AccessDecision::Allow => {
             req.extensions_mut().insert(policy_context);
-            next.run(req).await
-        }
-        AccessDecision::Deny(reason) => {
-            tracing::info!(
-                reason = %reason.message,
-                code = %reason.code,
-                user = ?auth_context.user.as_ref().map(|u| &u.username),
-                path = %req.uri().path(),
-                "Access denied"
-            );
-            forbidden_response(&reason)
-        }
-        AccessDecision::Abstain => {
-            tracing::info!(
-                user = ?auth_context.user.as_ref().map(|u| &u.username),
-                path = %req.uri().path(),
-                "Access denied: no matching policy"
-            );
-            forbidden_response(&DenyReason {
-                code: "no-matching-policy".to_string(),
-                message: "No policy matched this request".to_string(),
-                details: None,
-                policy_id: None,
-            })
+            run_authorized(auth_started, req, next).await
         }
+        AccessDecision::Deny(reason) => deny_response(
+            &policy_context,
+            &auth_context,
+            &reason,
+            state.expose_deny_reason,
+        ),
+        AccessDecision::Abstain => deny_response(
+            &policy_context,
+            &auth_context,
+            &DenyReason::no_matching_policy(),
+            state.expose_deny_reason,
+        ),
     }
 }
 
+/// Records the time spent authenticating and authorizing since
+/// `auth_started`, then runs the rest of the stack.
+async fn run_authorized(
+    auth_started: std::time::Instant,
+    req: Request<Body>,
+    next: Next,
+) -> Response {
+    crate::request_timing::record(crate::request_timing::Phase::Auth, auth_started.elapsed());
+    next.run(req).await
+}
+
 /// Helper for Basic auth that continues into authorization after successful authn.
 async fn handle_basic_auth_then_authorize(
+    auth_started: std::time::Instant,
     auth_state: &AuthState,
     encoded: &str,
     policy_evaluator: &PolicyEvaluator,
+    expose_deny_reason: bool,
     mut req: Request<Body>,
     next: Next,
 ) -> Response {
@@ -1237,29 +1359,17 @@ async fn handle_basic_auth_then_authorize(
     match decision {
         AccessDecision::Allow => {
             req.extensions_mut().insert(policy_context);
-            next.run(req).await
+            run_authorized(auth_started, req, next).await
         }
         AccessDecision::Deny(reason) => {
-            tracing::info!(
-                reason = %reason.message,
-                code = %reason.code,
-                path = %req.uri().path(),
-                "Access denied"
-            );
-            forbidden_response(&reason)
-        }
-        AccessDecision::Abstain => {
-            tracing::info!(
-                path = %req.uri().path(),
-                "Access denied: no matching policy"
-            );
-            forbidden_response(&DenyReason {
-                code: "no-matching-policy".to_string(),
-                message: "No policy matched this request".to_string(),
-                details: None,
-                policy_id: None,
-            })
+            deny_response(&policy_context, &auth_context, &reason, expose_deny_reason)
         }
+        AccessDecision::Abstain => deny_response(
+            &policy_context,
+            &auth_context,
+            &DenyReason::no_matching_policy(),
+            expose_deny_reason,
+        ),
     }
 }
 
@@ -1333,14 +1443,50 @@ fn unauthorized_response(message: &str) -> Response {
         .into_response()
 }
 
+/// Log a denied request and build its 403 response.
+///
+/// Every denial is logged the same way, under the `access_denied` target at
+/// INFO, so security tooling can alert on it (`RUST_LOG=access_denied=info`).
+fn deny_response(
+    policy_context: &PolicyContext,
+    auth_context: &AuthContext,
+    reason: &DenyReason,
+    expose_reason: bool,
+) -> Response {
+    let request = &policy_context.request;
+    tracing::info!(
+        target: "access_denied",
+        resource_type = %request.resource_type,
+        operation = %request.operation,
+        operation_id = request.operation_id.as_deref().unwrap_or(""),
+        method = %request.method,
+        path = %request.path,
+        user = auth_context.user.as_ref().map(|u| u.username.as_str()).unwrap_or(""),
+        client_id = %policy_context.client.id,
+        policy_id = reason.policy_id.as_deref().unwrap_or(""),
+        code = %reason.code,
+        reason = %reason.message,
+        "Access denied"
+    );
+    forbidden_response(reason, expose_reason)
+}
+
 /// Create a forbidden (403) response with FHIR OperationOutcome.
-fn forbidden_response(reason: &DenyReason) -> Response {
+///
+/// The deny message and policy id only reach `diagnostics` when
+/// `expose_reason` is set; otherwise clients just see the reason code.
+fn forbidden_response(reason: &DenyReason, expose_reason: bool) -> Response {
+    let diagnostics = match (&reason.policy_id, expose_reason) {
+        (Some(policy_id), true) => format!("{} (policy: {policy_id})", reason.message),
+        (None, true) => reason.message.clone(),
+        (_, false) => "Access denied".to_string(),
+    };
     let body = json!({
         "resourceType": "OperationOutcome",
         "issue": [{
             "severity": "error",
             "code": "forbidden",
-            "diagnostics": reason.message,
+            "diagnostics": diagnostics,
             "details": {
                 "coding": [{
                     "system": "http://octofhir.io/CodeSystem/access-denied-reason",
@@ -1367,6 +1513,505 @@ fn internal_error_response(message: &str) -> Response {
     (StatusCode::INTERNAL_SERVER_ERROR, Json(body)).into_response()
 }
 
+// =============================================================================
+// Tenant Middleware
+// =============================================================================
+
+/// Tenant middleware that binds FHIR API requests to the caller's tenant.
+///
+/// Runs after authentication, so only validated tokens are consulted. The
+/// tenant is read from the `multitenancy.claim` claim of the bearer (or
+/// cookie) token and the rest of the request runs inside its storage scope.
+///
+/// Authenticated requests without a tenant are rejected on the FHIR API, and
+/// tenant-bound requests are rejected everywhere else, since admin and SQL
+/// endpoints are not tenant-aware. Public paths pass through unscoped.
+pub async fn tenant_middleware(
+    State(state): State<crate::server::AppState>,
+    req: Request<Body>,
+    next: Next,
+) -> Response {
+    let config = &state.config.multitenancy;
+    if !config.enabled || req.extensions().get::<Arc<AuthContext>>().is_none() {
+        return next.run(req).await;
+    }
+
+    let token = req
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "))
+        .map(str::to_string)
+        .or_else(|| extract_token_from_cookie(&req, &state.auth_state.cookie_config))
+        .or_else(|| {
+            is_websocket_upgrade(&req)
+                .then(|| extract_token_from_query(&req))
+                .flatten()
+        });
+    let claim = token.and_then(|t| crate::tenant::token_claim(&t, &config.claim));
+
+    let tenant = match crate::tenant::resolve_tenant(claim.as_deref(), config) {
+        Ok(tenant) => tenant,
+        Err(e) => {
+            tracing::debug!(error = %e, "Tenant rejected");
+            return error_response(StatusCode::FORBIDDEN, &e);
+        }
+    };
+
+    let fhir_path = crate::tenant::is_tenant_scoped_path(req.uri().path());
+    match tenant {
+        Some(tenant) if fhir_path => octofhir_storage::with_tenant(tenant, next.run(req)).await,
+        Some(_) => error_response(
+            StatusCode::FORBIDDEN,
+            "Tenant-scoped requests are limited to the FHIR API",
+        ),
+        None if fhir_path => {
+            error_response(StatusCode::FORBIDDEN, "Request is not bound to a tenant")
+        }
+        None => next.run(req).await,
+    }
+}
+
+// =============================================================================
+// Rate Limit Middleware
+// =============================================================================
+
+/// Marks a request already counted by [`anonymous_rate_limit_middleware`].
+#[derive(Debug, Clone, Copy)]
+struct RateLimitCounted;
+
+/// Rate limit middleware for requests without credentials, run ahead of
+/// authentication.
+///
+/// A request with no `Authorization` header, auth cookie or WebSocket
+/// `token` is counted per client address (`ip:<addr>`), resolved through
+/// trusted proxies (see [`crate::forwarded::resolve_client_ip`]), so
+/// anonymous traffic over quota is rejected before it costs any auth work.
+/// Requests with credentials are left to [`rate_limit_middleware`].
+pub async fn anonymous_rate_limit_middleware(
+    State(state): State<crate::server::AppState>,
+    mut req: Request<Body>,
+    next: Next,
+) -> Response {
+    let Some(limiter) = state.rate_limiter.as_ref() else {
+        return next.run(req).await;
+    };
+    if presents_credentials(&req, &state.auth_state.cookie_config) {
+        return next.run(req).await;
+    }
+
+    let client = client_address_key(&state, &req);
+    req.extensions_mut().insert(RateLimitCounted);
+    run_rate_limited(limiter, &client, req, next).await
+}
+
+/// Rate limit middleware that counts requests against the caller's quota.
+///
+/// The caller is the token's `client_id`. Requests without credentials were
+/// already counted by [`anonymous_rate_limit_middleware`]; public paths and
+/// the anonymous context are counted per client address, like them. Every
+/// response carries the `RateLimit-Limit`, `RateLimit-Remaining` and
+/// `RateLimit-Reset` headers. Requests over quota get 429 with `Retry-After`
+/// and never reach the handler.
+pub async fn rate_limit_middleware(
+    State(state): State<crate::server::AppState>,
+    req: Request<Body>,
+    next: Next,
+) -> Response {
+    let Some(limiter) = state.rate_limiter.as_ref() else {
+        return next.run(req).await;
+    };
+    if req.extensions().get::<RateLimitCounted>().is_some() {
+        return next.run(req).await;
+    }
+
+    let client = match req.extensions().get::<Arc<AuthContext>>() {
+        Some(auth) if !Arc::ptr_eq(auth, &state.anonymous_auth_context) => {
+            auth.client_id().to_string()
+        }
+        _ => client_address_key(&state, &req),
+    };
+    run_rate_limited(limiter, &client, req, next).await
+}
+
+/// Whether the request carries a token for the auth middleware to check.
+fn presents_credentials(req: &Request<Body>, cookie_config: &CookieConfig) -> bool {
+    req.headers().contains_key(AUTHORIZATION)
+        || extract_token_from_cookie(req, cookie_config).is_some()
+        || (is_websocket_upgrade(req) && extract_token_from_query(req).is_some())
+}
+
+/// Rate limit key of the client address behind trusted proxies.
+fn client_address_key(state: &crate::server::AppState, req: &Request<Body>) -> String {
+    let peer = req
+        .extensions()
+        .get::<axum::extract::ConnectInfo<std::net::SocketAddr>>()
+        .map(|info| info.0.ip());
+    crate::forwarded::resolve_client_ip(&state.trusted_proxies, peer, req.headers())
+        .map(|ip| format!("ip:{ip}"))
+        .unwrap_or_else(|| "ip:unknown".to_string())
+}
+
+/// Counts the request against `client`'s quota, then runs it or rejects it
+/// with 429. Either way the response gets the `RateLimit-*` headers.
+async fn run_rate_limited(
+    limiter: &crate::rate_limit::RateLimiter,
+    client: &str,
+    req: Request<Body>,
+    next: Next,
+) -> Response {
+    let quota = limiter.hit(client).await;
+    let mut response = if quota.exceeded() {
+        tracing::debug!(client = %client, limit = quota.limit, "Rate limit exceeded");
+        let body = json!({
+            "resourceType": "OperationOutcome",
+            "issue": [{
+                "severity": "error",
+                "code": "throttled",
+                "diagnostics": format!(
+                    "Rate limit of {} requests exceeded, retry in {} seconds",
+                    quota.limit, quota.reset_secs
+                ),
+            }]
+        });
+        let mut response = (StatusCode::TOO_MANY_REQUESTS, Json(body)).into_response();
+        response
+            .headers_mut()
+            .insert(RETRY_AFTER, quota.reset_secs.into());
+        response
+    } else {
+        next.run(req).await
+    };
+    quota.apply_headers(response.headers_mut());
+    response
+}
+
+// =============================================================================
+// Async Job Client Middleware
+// =============================================================================
+
+/// Binds the authenticated OAuth client for the rest of the request, so async
+/// jobs it submits (such as `$export` with `Prefer: respond-async`) are
+/// recorded against that client and count toward its concurrency limits.
+pub async fn async_job_client_middleware(req: Request<Body>, next: Next) -> Response {
+    match req.extensions().get::<Arc<AuthContext>>() {
+        Some(auth) => {
+            let client_id = auth.client_id().to_string();
+            crate::async_jobs::with_submitting_client(client_id, next.run(req)).await
+        }
+        None => next.run(req).await,
+    }
+}
+
+// =============================================================================
+// Query Budget Middleware
+// =============================================================================
+
+/// Query budget middleware that caps the database queries of a FHIR search
+/// request.
+///
+/// The budget is `search.query_budget`, or the largest
+/// `search.query_budget_overrides` entry among the token's scopes. Searches
+/// charge it per round-trip and fail with 400 once it is spent.
+///
+/// Only search interactions are limited: system, type and compartment
+/// searches by `GET` or `POST .../_search`. Reads, history and operations
+/// such as `$everything` and batch bundles issue many independent queries by
+/// design and are left alone.
+pub async fn query_budget_middleware(
+    State(state): State<crate::server::AppState>,
+    req: Request<Body>,
+    next: Next,
+) -> Response {
+    if !is_search_request(req.method(), req.uri().path()) {
+        return next.run(req).await;
+    }
+
+    let settings = &state.config.search;
+    let budget = match req.extensions().get::<Arc<AuthContext>>() {
+        Some(auth) => settings.query_budget(auth.scopes()),
+        None => settings.query_budget(std::iter::empty()),
+    };
+    match budget {
+        Some(limit) => octofhir_storage::with_query_budget(limit, next.run(req)).await,
+        None => next.run(req).await,
+    }
+}
+
+/// Whether a request is a FHIR search: `/fhir`, `/fhir/{type}` or
+/// `/fhir/{type}/{id}/{type}` by `GET`, or any of them followed by `/_search`
+/// by `POST`.
+fn is_search_request(method: &axum::http::Method, path: &str) -> bool {
+    let Some(rest) = path.strip_prefix("/fhir") else {
+        return false;
+    };
+    let mut segments: Vec<&str> = rest.split('/').filter(|s| !s.is_empty()).collect();
+    if *method == axum::http::Method::POST {
+        if segments.last() != Some(&"_search") {
+            return false;
+        }
+        segments.pop();
+    } else if *method != axum::http::Method::GET {
+        return false;
+    }
+    let is_type =
+        |segment: &str| segment == "*" || segment.starts_with(|c: char| c.is_ascii_uppercase());
+    match segments.as_slice() {
+        [] => true,
+        [resource_type] => is_type(resource_type),
+        [resource_type, id, compartment_type] => {
+            is_type(resource_type) && !id.starts_with(['_', '$']) && is_type(compartment_type)
+        }
+        _ => false,
+    }
+}
+
+// =============================================================================
+// Feature Flag Middleware
+// =============================================================================
+
+/// Feature flag middleware that evaluates the configured feature flags for the
+/// caller and exposes them to the rest of the request.
+///
+/// The [`FeatureContext`](octofhir_config::FeatureContext) carries the user
+/// (or the token subject for client credentials), the tenant, the request ID
+/// and a `client_id` attribute. The evaluated
+/// [`RequestFeatures`](crate::request_features::RequestFeatures) are inserted
+/// into the request extensions and bound to the request's task, where
+/// [`crate::request_features::feature_enabled`] reads them.
+pub async fn feature_flags_middleware(
+    State(state): State<crate::server::AppState>,
+    mut req: Request<Body>,
+    next: Next,
+) -> Response {
+    let Some(manager) = state.config_manager.as_ref() else {
+        return next.run(req).await;
+    };
+
+    let mut context = octofhir_config::FeatureContext::new();
+    if let Some(tenant) = octofhir_storage::current_tenant() {
+        context.tenant_id = Some(tenant.as_str().to_string());
+    }
+    if let Some(auth) = req.extensions().get::<Arc<AuthContext>>() {
+        let user_id = auth
+            .user
+            .as_ref()
+            .map_or_else(|| auth.subject().to_string(), |user| user.id.clone());
+        context = context
+            .user(user_id)
+            .attribute("client_id", auth.client_id());
+    }
+    if let Some(request_id) = req
+        .headers()
+        .get("x-request-id")
+        .and_then(|h| h.to_str().ok())
+    {
+        context = context.request(request_id);
+    }
+
+    let features = Arc::new(crate::request_features::RequestFeatures::evaluate(
+        &manager.feature_flags().await,
+        &context,
+    ));
+    req.extensions_mut().insert(features.clone());
+    crate::request_features::with_request_features(features, next.run(req)).await
+}
+
+// =============================================================================
+// Request Timeout Middleware
+// =============================================================================
+
+/// Request timeout middleware that answers 504 once a request has run for
+/// `server.request_timeout_ms`, or `server.operation_timeout_ms` for
+/// operations and bundles.
+///
+/// The handler runs inside an `octofhir_storage::with_deadline` scope, so its
+/// database statements carry a matching `statement_timeout` and are canceled
+/// by PostgreSQL rather than left running after the handler is dropped.
+///
+/// WebSocket upgrades and event streams are long-lived by design and are not
+/// limited.
+pub async fn request_timeout_middleware(
+    State(state): State<crate::server::AppState>,
+    req: Request<Body>,
+    next: Next,
+) -> Response {
+    if is_streaming_request(&req) {
+        return next.run(req).await;
+    }
+
+    let server = &state.config.server;
+    let (class, timeout_ms) = if is_long_running_request(&req) {
+        ("operation", server.operation_timeout_ms)
+    } else {
+        ("request", server.request_timeout_ms)
+    };
+    if timeout_ms == 0 {
+        return next.run(req).await;
+    }
+
+    let timeout = std::time::Duration::from_millis(timeout_ms);
+    let deadline = std::time::Instant::now() + timeout;
+    let method = req.method().clone();
+    let path = req.uri().path().to_string();
+    match tokio::time::timeout(
+        timeout,
+        octofhir_storage::with_deadline(deadline, next.run(req)),
+    )
+    .await
+    {
+        Ok(response) => response,
+        Err(_) => {
+            crate::metrics::record_request_timeout(class);
+            tracing::warn!(%method, %path, timeout_ms, "Request timed out");
+            octofhir_api::ApiError::gateway_timeout(format!(
+                "Request did not complete within {timeout_ms} ms"
+            ))
+            .into_response()
+        }
+    }
+}
+
+// =============================================================================
+// Disconnect Cancellation Middleware
+// =============================================================================
+
+/// Middleware that cancels a request's running database statements when its
+/// client disconnects (`server.cancel_queries_on_disconnect`).
+///
+/// Hyper drops the handler future when the connection goes away. The
+/// handler runs inside an `octofhir_storage::with_cancel_scope` scope; if the
+/// future is dropped before a response is produced, statements still running
+/// in the scope are canceled with `pg_cancel_backend` and counted in
+/// `db_queries_canceled_on_disconnect_total`. It sits outside the request
+/// timeout, which answers timed-out requests itself.
+pub async fn query_cancel_middleware(
+    State(state): State<crate::server::AppState>,
+    req: Request<Body>,
+    next: Next,
+) -> Response {
+    if !state.config.server.cancel_queries_on_disconnect || is_streaming_request(&req) {
+        return next.run(req).await;
+    }
+
+    let scope = octofhir_storage::CancelScope::new();
+    let mut on_disconnect = CancelOnDrop {
+        scope: Some(scope.clone()),
+        method: req.method().clone(),
+        path: req.uri().path().to_string(),
+    };
+    let response = octofhir_storage::with_cancel_scope(scope, next.run(req)).await;
+    on_disconnect.scope = None;
+    response
+}
+
+/// Cancels the statements of a scope when dropped while still armed.
+struct CancelOnDrop {
+    scope: Option<octofhir_storage::CancelScope>,
+    method: axum::http::Method,
+    path: String,
+}
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        let Some(scope) = self.scope.take() else {
+            return;
+        };
+        let canceled = scope.cancel();
+        if canceled > 0 {
+            crate::metrics::record_queries_canceled_on_disconnect(canceled);
+            tracing::debug!(
+                method = %self.method,
+                path = %self.path,
+                canceled,
+                "Client disconnected, canceled running queries"
+            );
+        }
+    }
+}
+
+/// Operations (any `$` segment) and bundles posted to the FHIR base get the
+/// longer operation timeout.
+fn is_long_running_request(req: &Request<Body>) -> bool {
+    let path = req.uri().path();
+    path.split('/').any(|segment| segment.starts_with('$'))
+        || (req.method() == axum::http::Method::POST && path.trim_end_matches('/') == "/fhir")
+}
+
+/// WebSocket upgrades and event streams, which stay open by design.
+fn is_streaming_request(req: &Request<Body>) -> bool {
+    is_websocket_upgrade(req)
+        || req
+            .headers()
+            .get(axum::http::header::ACCEPT)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.contains("text/event-stream"))
+}
+
+// =============================================================================
+// Slow Request Middleware
+// =============================================================================
+
+/// Slow-request middleware that logs, at warn, every request taking at least
+/// `server.slow_request_ms`.
+///
+/// The log line carries the method, path and status with a timing breakdown
+/// from [`crate::request_timing`]: authentication and authorization, storage
+/// and search queries (with their count), response serialization, and the
+/// remainder spent elsewhere in the handler. It complements the per-statement
+/// slow-query log by catching requests slowed by many fast queries, e.g. a
+/// search with deep `_include` iteration. With `server.slow_request_metric`
+/// set, slow requests are also counted in `http_slow_requests_total`.
+///
+/// WebSocket upgrades and event streams are not logged.
+pub async fn slow_request_middleware(
+    State(state): State<crate::server::AppState>,
+    req: Request<Body>,
+    next: Next,
+) -> Response {
+    let server = &state.config.server;
+    if server.slow_request_ms == 0 || is_streaming_request(&req) {
+        return next.run(req).await;
+    }
+
+    let method = req.method().clone();
+    let path = req.uri().path().to_string();
+    let timings = crate::request_timing::RequestTimings::new();
+    let started = std::time::Instant::now();
+    let response =
+        crate::request_timing::with_request_timings(timings.clone(), next.run(req)).await;
+    let elapsed = started.elapsed();
+
+    if elapsed.as_millis() < u128::from(server.slow_request_ms) {
+        return response;
+    }
+
+    use crate::request_timing::Phase;
+    let auth = timings.get(Phase::Auth);
+    let query = timings.get(Phase::Query);
+    let serialization = timings.get(Phase::Serialization);
+    let other = elapsed.saturating_sub(auth + query + serialization);
+    tracing::warn!(
+        target: "octofhir::slow_request",
+        %method,
+        %path,
+        status = response.status().as_u16(),
+        elapsed_ms = elapsed.as_millis() as u64,
+        auth_ms = auth.as_millis() as u64,
+        query_ms = query.as_millis() as u64,
+        queries = timings.queries(),
+        serialization_ms = serialization.as_millis() as u64,
+        other_ms = other.as_millis() as u64,
+        threshold_ms = server.slow_request_ms,
+        "Slow request"
+    );
+    if server.slow_request_metric {
+        crate::metrics::record_slow_request(method.as_str(), &path);
+    }
+
+    response
+}
+
 // =============================================================================
 // Audit Middleware
 // =============================================================================
@@ -1558,12 +2203,89 @@ pub async fn audit_middleware(
 // Dynamic CORS Middleware
 // =============================================================================
 
+/// Request headers always allowed in CORS preflights: SMART authorization
+/// plus the FHIR REST conditional and preference headers.
+const CORS_ALLOW_HEADERS: &[&str] = &[
+    "content-type",
+    "authorization",
+    "accept",
+    "x-request-id",
+    "x-skip-validation",
+    "if-match",
+    "if-none-match",
+    "if-none-exist",
+    "if-modified-since",
+    "prefer",
+];
+
+/// Response headers always exposed to scripts: versioning and location
+/// headers FHIR clients rely on, `WWW-Authenticate` for SMART errors, and the
+/// rate limit quota.
+const CORS_EXPOSE_HEADERS: &[&str] = &[
+    "content-type",
+    "x-request-id",
+    "location",
+    "content-location",
+    "etag",
+    "last-modified",
+    "www-authenticate",
+    "retry-after",
+    "ratelimit-limit",
+    "ratelimit-remaining",
+    "ratelimit-reset",
+];
+
+/// CORS policy resolved from [`crate::config::CorsConfig`].
+///
+/// Header values are built once at router construction so each request only
+/// clones them.
+#[derive(Debug, Clone)]
+pub struct CorsPolicy {
+    allow_headers: HeaderValue,
+    expose_headers: HeaderValue,
+    allow_credentials: bool,
+    max_age: HeaderValue,
+}
+
+impl CorsPolicy {
+    /// Builds the policy, appending configured headers to the built-in lists.
+    /// Invalid header names are skipped (they are rejected by config validation).
+    pub fn from_config(config: &crate::config::CorsConfig) -> Self {
+        Self {
+            allow_headers: header_list(CORS_ALLOW_HEADERS, &config.allow_headers),
+            expose_headers: header_list(CORS_EXPOSE_HEADERS, &config.expose_headers),
+            allow_credentials: config.allow_credentials,
+            max_age: HeaderValue::from(config.max_age_secs),
+        }
+    }
+}
+
+/// Joins built-in and configured header names into one comma-separated
+/// value, lowercased and without duplicates.
+fn header_list(builtin: &[&str], extra: &[String]) -> HeaderValue {
+    let mut names: Vec<String> = builtin.iter().map(|h| h.to_string()).collect();
+    for name in extra {
+        let Ok(name) = HeaderName::from_bytes(name.trim().as_bytes()) else {
+            continue;
+        };
+        let name = name.as_str().to_string();
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    HeaderValue::from_str(&names.join(", ")).unwrap_or_else(|_| HeaderValue::from_static(""))
+}
+
 /// Dynamic CORS middleware that validates origins against Client.allowedOrigins
 ///
 /// This middleware checks the Origin header and validates it against the
 /// allowedOrigins field in the Client resource. For OAuth endpoints, it extracts
 /// the client_id from the request and looks up the Client to check allowed origins.
+///
+/// The same layer wraps the FHIR API and the discovery/OAuth routes, so SMART
+/// browser apps get identical headers on both.
 pub async fn dynamic_cors_middleware(
+    State(policy): State<Arc<CorsPolicy>>,
     request: Request<Body>,
     next: Next,
 ) -> Result<Response, StatusCode> {
@@ -1577,7 +2299,7 @@ pub async fn dynamic_cors_middleware(
 
     // Handle preflight OPTIONS requests immediately
     if request.method() == Method::OPTIONS {
-        return Ok(build_preflight_response(origin.as_deref()));
+        return Ok(build_preflight_response(&policy, origin.as_deref()));
     }
 
     // Process the actual request
@@ -1593,17 +2315,21 @@ pub async fn dynamic_cors_middleware(
         if let Ok(header_value) = HeaderValue::from_str(&origin_value) {
             headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, header_value);
         }
+        // The reflected origin varies the response, so shared caches must key on it
+        headers.append(header::VARY, HeaderValue::from_static("origin"));
 
         // Allow credentials (cookies, authorization headers)
-        headers.insert(
-            header::ACCESS_CONTROL_ALLOW_CREDENTIALS,
-            HeaderValue::from_static("true"),
-        );
+        if policy.allow_credentials {
+            headers.insert(
+                header::ACCESS_CONTROL_ALLOW_CREDENTIALS,
+                HeaderValue::from_static("true"),
+            );
+        }
 
         // Expose headers that the client can access
         headers.insert(
             header::ACCESS_CONTROL_EXPOSE_HEADERS,
-            HeaderValue::from_static("content-type, x-request-id, location"),
+            policy.expose_headers.clone(),
         );
     }
 
@@ -1611,7 +2337,7 @@ pub async fn dynamic_cors_middleware(
 }
 
 /// Build a response for preflight OPTIONS requests
-fn build_preflight_response(origin: Option<&str>) -> Response {
+fn build_preflight_response(policy: &CorsPolicy, origin: Option<&str>) -> Response {
     use axum::http::{StatusCode, header};
 
     let mut response = Response::builder()
@@ -1630,12 +2356,15 @@ fn build_preflight_response(origin: Option<&str>) -> Response {
         // No origin header - don't set CORS headers
         return response;
     }
+    headers.insert(header::VARY, HeaderValue::from_static("origin"));
 
     // Allow credentials
-    headers.insert(
-        header::ACCESS_CONTROL_ALLOW_CREDENTIALS,
-        HeaderValue::from_static("true"),
-    );
+    if policy.allow_credentials {
+        headers.insert(
+            header::ACCESS_CONTROL_ALLOW_CREDENTIALS,
+            HeaderValue::from_static("true"),
+        );
+    }
 
     // Allow methods
     headers.insert(
@@ -1646,16 +2375,11 @@ fn build_preflight_response(origin: Option<&str>) -> Response {
     // Allow headers
     headers.insert(
         header::ACCESS_CONTROL_ALLOW_HEADERS,
-        HeaderValue::from_static(
-            "content-type, authorization, accept, x-request-id, x-skip-validation",
-        ),
+        policy.allow_headers.clone(),
     );
 
-    // Max age for preflight cache (1 hour)
-    headers.insert(
-        header::ACCESS_CONTROL_MAX_AGE,
-        HeaderValue::from_static("3600"),
-    );
+    // Max age for preflight cache
+    headers.insert(header::ACCESS_CONTROL_MAX_AGE, policy.max_age.clone());
 
     response
 }
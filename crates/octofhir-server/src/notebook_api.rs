@@ -4,10 +4,10 @@
 
 use crate::server::AppState;
 use axum::{
+    Json,
     extract::{Path, Query, State},
-    http::{header, StatusCode},
+    http::{StatusCode, header},
     response::{IntoResponse, Response},
-    Json,
 };
 use octofhir_api::ApiError;
 use octofhir_notebook::{self as nb, Format};
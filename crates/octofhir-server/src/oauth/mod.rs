@@ -20,15 +20,18 @@ use axum::{
     response::Response,
     routing::{get, post},
 };
+use octofhir_auth::http::introspect::IntrospectionState;
+use octofhir_auth::http::revoke::RevocationState;
 use octofhir_auth::oauth::service::{AuthorizationConfig, AuthorizationService};
 use octofhir_auth::oauth::token::TokenRequest;
+use octofhir_auth::token::IntrospectionCache;
 use octofhir_auth::token::jwt::JwtService;
 use octofhir_auth::token::service::TokenConfig;
 use octofhir_auth::{
     AuthState, AuthorizeState, JwksState, LaunchState, LogoutState, SmartConfigState, TokenState,
-    authorize_get, authorize_post, create_launch_handler, jwks_handler, logout_handler,
-    oidc_logout_handler, openid_configuration_handler, smart_configuration_handler, token_handler,
-    userinfo_handler,
+    authorize_get, authorize_post, create_launch_handler, introspect_handler, jwks_handler,
+    logout_handler, oidc_logout_handler, openid_configuration_handler, revoke_handler,
+    smart_configuration_handler, token_handler, userinfo_handler,
 };
 use octofhir_auth_postgres::{
     ArcAuthorizeSessionStorage, ArcClientStorage, ArcConsentStorage, ArcLaunchContextStorage,
@@ -39,12 +42,15 @@ use time::Duration;
 use url::Url;
 
 use crate::audit::AuditService;
+use crate::cache::SharedIntrospectionCache;
 use crate::server::AppState;
 
 /// OAuth state containing all auth-related storage.
 #[derive(Clone)]
 pub struct OAuthState {
     pub token_state: TokenState,
+    pub introspection_state: IntrospectionState,
+    pub revocation_state: RevocationState,
     pub logout_state: LogoutState,
     pub jwks_state: JwksState,
     pub smart_config_state: SmartConfigState,
@@ -82,7 +88,20 @@ impl OAuthState {
         // Create token config
         let token_config = TokenConfig::new(config.auth.issuer.clone(), config.base_url())
             .with_access_token_lifetime(Duration::seconds(access_token_secs))
-            .with_refresh_token_lifetime(Duration::seconds(refresh_token_secs));
+            .with_refresh_token_lifetime(Duration::seconds(refresh_token_secs))
+            .with_refresh_token_max_lifetime(
+                config
+                    .auth
+                    .oauth
+                    .refresh_token_max_lifetime
+                    .map(|max| Duration::seconds(max.as_secs() as i64)),
+            );
+
+        // Introspection results are shared across instances; logout and
+        // revocation invalidate them by JTI
+        let introspection_cache: Arc<dyn IntrospectionCache> = Arc::new(
+            SharedIntrospectionCache::new(app_state.cache_backend.clone()),
+        );
 
         // Create user storage for password grant support
         let user_storage = Arc::new(ArcUserStorage::new(db_pool.clone()));
@@ -97,8 +116,13 @@ impl OAuthState {
             token_config,
         )
         .with_user_storage(user_storage.clone())
+        .with_introspection_cache(introspection_cache.clone())
         .with_cookie_config(config.auth.cookie.clone())
         .with_fhir_storage(app_state.storage.clone());
+        let introspection_state =
+            IntrospectionState::new(token_state.token_service().clone(), client_storage.clone());
+        let revocation_state =
+            RevocationState::new(token_state.token_service().clone(), client_storage.clone());
 
         // Create PostgresSsoSessionStorage for SSO logout support
         // Uses FHIR storage for AuthSession resources
@@ -107,7 +131,8 @@ impl OAuthState {
 
         // Create LogoutState for browser-based logout
         // Includes client_storage for OIDC RP-Initiated Logout validation
-        // Includes JWT cache invalidation callback to immediately reject revoked tokens
+        // Includes JWT and introspection cache invalidation callback to immediately
+        // reject revoked tokens
         let jwt_cache = app_state.jwt_cache.clone();
         let logout_state = LogoutState::new(
             jwt_service.clone(),
@@ -119,6 +144,9 @@ impl OAuthState {
         )
         .with_token_revoked_callback(Arc::new(move |jti: &str| {
             jwt_cache.invalidate_by_jti(jti);
+            let introspection_cache = introspection_cache.clone();
+            let jti = jti.to_string();
+            tokio::spawn(async move { introspection_cache.invalidate_jti(&jti).await });
         }));
 
         // Create JwksState
@@ -131,14 +159,17 @@ impl OAuthState {
         // Create LaunchState for EHR launch context
         let launch_storage: Arc<ArcLaunchContextStorage> =
             Arc::new(ArcLaunchContextStorage::new(db_pool));
-        let launch_state = LaunchState::new(launch_storage.clone());
+        let launch_state =
+            LaunchState::new(launch_storage.clone()).with_fhir_storage(app_state.storage.clone());
 
         // Create AuthorizationService for authorize endpoint
         let authorization_service = Arc::new(
             AuthorizationService::new(
                 client_storage.clone(),
                 session_storage.clone(),
-                AuthorizationConfig::default(),
+                AuthorizationConfig::default()
+                    .with_audience(config.base_url())
+                    .with_audience(format!("{}/fhir", config.base_url().trim_end_matches('/'))),
             )
             .with_launch_storage(launch_storage.clone()),
         );
@@ -161,6 +192,8 @@ impl OAuthState {
 
         Some(Self {
             token_state,
+            introspection_state,
+            revocation_state,
             logout_state,
             jwks_state,
             smart_config_state,
@@ -185,9 +218,25 @@ pub fn oauth_routes(state: OAuthState) -> Router {
         // Token endpoint with audit logging - accepts x-www-form-urlencoded, returns JSON
         .route("/auth/token", post(auditing_token_handler))
         .with_state(auditing_state)
+        .merge(introspect_route(state.introspection_state))
+        .merge(revoke_route(state.revocation_state))
         .merge(logout_route(state.logout_state))
 }
 
+/// Creates the token revocation route (RFC 7009).
+pub fn revoke_route(state: RevocationState) -> Router {
+    Router::new()
+        .route("/auth/revoke", post(revoke_handler))
+        .with_state(state)
+}
+
+/// Creates the token introspection route (RFC 7662).
+pub fn introspect_route(state: IntrospectionState) -> Router {
+    Router::new()
+        .route("/auth/introspect", post(introspect_handler))
+        .with_state(state)
+}
+
 /// Token handler with audit logging.
 ///
 /// Wraps the standard token handler and logs authentication events as FHIR AuditEvents.
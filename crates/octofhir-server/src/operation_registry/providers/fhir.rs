@@ -209,6 +209,24 @@ impl OperationProvider for FhirOperationProvider {
                 modules::SERVER,
             )
             .with_description("Evaluate FHIRPath expressions on a resource instance"),
+            OperationDefinition::new(
+                "fhir.search-params",
+                "$search-params",
+                categories::FHIR,
+                vec!["GET".to_string()],
+                fhir_path("/$search-params"),
+                modules::SERVER,
+            )
+            .with_description("List supported search parameters for all resource types"),
+            OperationDefinition::new(
+                "fhir.search-params-type",
+                "$search-params",
+                categories::FHIR,
+                vec!["GET".to_string()],
+                fhir_path("/{type}/$search-params"),
+                modules::SERVER,
+            )
+            .with_description("List supported search parameters for a resource type"),
             // $export operations (fixing existing gap)
             OperationDefinition::new(
                 "fhir.export-system",
@@ -41,6 +41,16 @@ impl OperationProvider for SystemOperationProvider {
             )
             .with_description("Get the server's capability statement")
             .with_public(true),
+            OperationDefinition::new(
+                "system.versions",
+                "Supported FHIR Versions",
+                categories::SYSTEM,
+                vec!["GET".to_string()],
+                "/fhir/$versions",
+                modules::SERVER,
+            )
+            .with_description("List the FHIR versions the server supports")
+            .with_public(true),
             OperationDefinition::new(
                 "system.health",
                 "Health Check",
@@ -200,6 +200,32 @@ impl OperationProvider for UiOperationProvider {
             )
             .with_description("Get audit event analytics and aggregations")
             .with_public(false),
+            // Admin Search Diagnostics API
+            OperationDefinition::new(
+                "admin.search.explain",
+                "Explain Search",
+                categories::UI,
+                vec!["GET".to_string()],
+                "/admin/search/{resource_type}/$explain",
+                modules::SERVER,
+            )
+            .with_description(
+                "Show the query plan, generated SQL and index suggestions for a search",
+            )
+            .with_public(false),
+            // Admin Maintenance API
+            OperationDefinition::new(
+                "admin.maintenance.reference-integrity",
+                "Reference Integrity Check",
+                categories::UI,
+                vec!["GET".to_string(), "POST".to_string()],
+                "/admin/maintenance/$reference-integrity",
+                modules::SERVER,
+            )
+            .with_description(
+                "Scan for references that point to resources that do not exist, as an async job",
+            )
+            .with_public(false),
         ]
     }
 
@@ -8,6 +8,9 @@
 //! - Group: `/Group/{id}/$export` - Export group member data
 //! - ViewDefinition: `/ViewDefinition/$export` - Export ViewDefinition results (SQL on FHIR)
 
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use serde_json::{Value, json};
@@ -20,6 +23,7 @@ use crate::server::AppState;
 use octofhir_sof::ViewDefinition;
 
 use super::NDJSON_CONTENT_TYPE;
+use super::group::{GroupMembers, MemberCursor};
 use super::status::{BulkExportLevel, BulkExportParams};
 use super::writer::NdjsonWriter;
 
@@ -91,12 +95,25 @@ impl ExportOperation {
             .and_then(|v| v.as_str())
             .map(|s| s.to_string());
 
+        let gzip = match params.get("compression").and_then(|v| v.as_str()) {
+            None => None,
+            Some("gzip") => Some(true),
+            Some("none") => Some(false),
+            Some(other) => {
+                return Err(OperationError::InvalidParameters(format!(
+                    "Unsupported compression: {}. Use gzip or none.",
+                    other
+                )));
+            }
+        };
+
         Ok(BulkExportParams {
             output_format,
             since,
             resource_types,
             type_filter,
             group_id,
+            gzip,
         })
     }
 
@@ -116,6 +133,7 @@ impl ExportOperation {
 
         // Store job parameters as JSON for the async job
         // The async job manager will assign its own job ID
+        let gzip = params.gzip.unwrap_or(self.config.gzip);
         let job_params = json!({
             "level": level.to_string(),
             "params": params,
@@ -124,6 +142,7 @@ impl ExportOperation {
                 "max_resources_per_file": self.config.max_resources_per_file,
                 "batch_size": self.config.batch_size,
                 "retention_hours": self.config.retention_hours,
+                "gzip": gzip,
             },
             "request_url": request_url,
         });
@@ -135,7 +154,8 @@ impl ExportOperation {
             url: request_url.to_string(),
             body: Some(job_params),
             headers: None,
-            client_id: None, // TODO: Extract from auth context
+            client_id: None, // The submitting request's client
+            secret: None,
         };
 
         let job_id = state
@@ -155,7 +175,7 @@ impl ExportOperation {
         Ok(json!({
             "status": "accepted",
             "job_id": job_id.to_string(),
-            "status_url": format!("{}/_async-status/{}", state.base_url, job_id),
+            "status_url": format!("{}/fhir/_async-status/{}", state.base_url, job_id),
         }))
     }
 
@@ -225,6 +245,7 @@ impl ExportOperation {
             body: Some(job_params),
             headers: None,
             client_id: None,
+            secret: None,
         };
 
         let job_id = state
@@ -242,7 +263,7 @@ impl ExportOperation {
         Ok(json!({
             "status": "accepted",
             "job_id": job_id.to_string(),
-            "status_url": format!("{}/_async-status/{}", state.base_url, job_id),
+            "status_url": format!("{}/fhir/_async-status/{}", state.base_url, job_id),
         }))
     }
 }
@@ -354,7 +375,7 @@ pub async fn execute_bulk_export(
     tracing::info!(job_id = %job_id, "Starting bulk export execution");
 
     // Parse job parameters
-    let _level: BulkExportLevel = params
+    let level: BulkExportLevel = params
         .get("level")
         .and_then(|v| v.as_str())
         .and_then(|s| match s {
@@ -383,6 +404,10 @@ pub async fn execute_bulk_export(
         .get("batch_size")
         .and_then(|v| v.as_u64())
         .unwrap_or(1000) as usize;
+    let gzip = config
+        .get("gzip")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
 
     let request_url = params
         .get("request_url")
@@ -392,12 +417,78 @@ pub async fn execute_bulk_export(
     // Create NDJSON writer
     let mut writer = NdjsonWriter::new(export_path, job_id, max_resources_per_file)
         .await
-        .map_err(|e| format!("Failed to create NDJSON writer: {}", e))?;
+        .map_err(|e| format!("Failed to create NDJSON writer: {}", e))?
+        .with_gzip(gzip);
+
+    let total_exported = if level == BulkExportLevel::Group {
+        export_group(&state, job_id, &mut writer, &export_params, batch_size).await?
+    } else {
+        export_all(&state, job_id, &mut writer, &export_params, batch_size).await?
+    };
+
+    // Finish writing and get file information
+    let files = writer
+        .finish()
+        .await
+        .map_err(|e| format!("Failed to finish writing: {}", e))?;
+
+    // Build output manifest
+    let mut output = Vec::new();
+    for (resource_type, file_list) in files {
+        for (path, count) in file_list {
+            let filename = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or("unknown");
+
+            output.push(json!({
+                "type": resource_type,
+                "url": format!("{}/fhir/_bulk-files/{}/{}", state.base_url, job_id, filename),
+                "count": count,
+            }));
+        }
+    }
+
+    tracing::info!(
+        job_id = %job_id,
+        total_exported = total_exported,
+        output_files = output.len(),
+        "Bulk export completed"
+    );
 
+    let mut manifest = json!({
+        "transactionTime": Utc::now().to_rfc3339(),
+        "request": request_url,
+        "requiresAccessToken": true,
+        "output": output,
+        "error": [],
+    });
+    // `extension` is the manifest field the Bulk Data IG reserves for servers
+    if gzip {
+        manifest["extension"] = json!({ "compression": "gzip" });
+    }
+    Ok(manifest)
+}
+
+/// Number of group members whose compartments are exported per search round
+const GROUP_PATIENT_CHUNK: usize = 100;
+
+/// Pages of group members between checkpoints. Each checkpoint closes the
+/// open files, so the next resources go to new ones.
+const GROUP_CHECKPOINT_PAGES: usize = 10;
+
+/// Export every resource of the requested types (system and patient level)
+async fn export_all(
+    state: &AppState,
+    job_id: Uuid,
+    writer: &mut NdjsonWriter,
+    export_params: &BulkExportParams,
+    batch_size: usize,
+) -> Result<usize, String> {
     // Determine resource types to export
     let resource_types = if export_params.get_resource_types().is_empty() {
         // Get all resource types from storage if not specified
-        get_all_resource_types(&state).await?
+        get_all_resource_types(state).await?
     } else {
         export_params.get_resource_types()
     };
@@ -413,11 +504,13 @@ pub async fn execute_bulk_export(
     // Export each resource type
     for resource_type in &resource_types {
         match export_resource_type(
-            &state,
-            &mut writer,
+            state,
+            writer,
             resource_type,
-            &export_params,
+            export_params,
             batch_size,
+            None,
+            None,
         )
         .await
         {
@@ -458,43 +551,215 @@ pub async fn execute_bulk_export(
         }
     }
 
-    // Finish writing and get file information
-    let files = writer
-        .finish()
-        .await
-        .map_err(|e| format!("Failed to finish writing: {}", e))?;
-
-    // Build output manifest
-    let mut output = Vec::new();
-    for (resource_type, file_list) in files {
-        for (path, count) in file_list {
-            let filename = path
-                .file_name()
-                .and_then(|n| n.to_str())
-                .unwrap_or("unknown");
+    Ok(total_exported)
+}
 
-            output.push(json!({
-                "type": resource_type,
-                "url": format!("{}/fhir/_bulk-files/{}/{}", state.base_url, job_id, filename),
-                "count": count,
-            }));
+/// Export the Patient compartments of a group's members
+///
+/// Members are paged in id order (see [`GroupMembers`]) and exported in
+/// chunks of [`GROUP_PATIENT_CHUNK`] patients, so memory does not grow with
+/// the group. A resource is written once per chunk even if it is in several
+/// of the chunk's compartments; one in the compartments of members in
+/// different chunks is written once for each of them. Every
+/// [`GROUP_CHECKPOINT_PAGES`] pages the job result records a
+/// [`GroupCheckpoint`]; a run of the same job after a restart continues from
+/// it.
+async fn export_group(
+    state: &AppState,
+    job_id: Uuid,
+    writer: &mut NdjsonWriter,
+    export_params: &BulkExportParams,
+    batch_size: usize,
+) -> Result<usize, String> {
+    let group_id = export_params
+        .group_id
+        .as_deref()
+        .ok_or("Missing groupId for group-level export")?;
+    let members = GroupMembers::resolve(state, group_id).await?;
+    let group = format!("Group/{}", group_id);
+
+    let compartment = state
+        .compartment_registry
+        .get("Patient")
+        .map_err(|e| format!("Patient compartment unavailable: {}", e))?;
+
+    let requested = export_params.get_resource_types();
+    let mut resource_types: Vec<String> = std::iter::once("Patient")
+        .chain(compartment.resource_types())
+        .filter(|t| requested.is_empty() || requested.iter().any(|r| r == t))
+        .map(str::to_string)
+        .collect();
+    resource_types.sort();
+    resource_types.dedup();
+
+    // A previous run of this job that stopped part way left a checkpoint;
+    // it only applies to the same version of the group
+    let resumed = match state.async_job_manager.get_job(job_id).await {
+        Ok(job) => job
+            .result
+            .and_then(|result| serde_json::from_value::<GroupCheckpoint>(result).ok())
+            .filter(|c| c.group == group && c.group_version == members.version()),
+        Err(e) => {
+            tracing::warn!(job_id = %job_id, error = %e, "Failed to read export checkpoint");
+            None
         }
-    }
+    };
+    let mut checkpoint = match resumed {
+        Some(checkpoint) => {
+            writer.restore(checkpoint.files(&writer.job_dir()));
+            tracing::info!(
+                job_id = %job_id,
+                group = %group,
+                members_exported = checkpoint.members_exported,
+                "Resuming group export from checkpoint"
+            );
+            checkpoint
+        }
+        None => GroupCheckpoint {
+            group: group.clone(),
+            group_version: members.version().to_string(),
+            ..Default::default()
+        },
+    };
+    let candidates = members.candidates(state).await?;
 
     tracing::info!(
         job_id = %job_id,
-        total_exported = total_exported,
-        output_files = output.len(),
-        "Bulk export completed"
+        group = %group,
+        candidates = candidates,
+        resource_types = resource_types.len(),
+        "Exporting group member compartments"
     );
 
-    Ok(json!({
-        "transactionTime": Utc::now().to_rfc3339(),
-        "request": request_url,
-        "requiresAccessToken": true,
-        "output": output,
-        "error": [],
-    }))
+    let mut cursor = checkpoint.cursor.clone();
+    let mut members_exported = checkpoint.members_exported;
+    let mut resources_exported = checkpoint.resources_exported;
+    let mut pages = 0;
+
+    while let Some(page) = members.next_page(state, &mut cursor).await? {
+        for chunk in page.chunks(GROUP_PATIENT_CHUNK) {
+            let references = chunk
+                .iter()
+                .map(|id| format!("Patient/{}", id))
+                .collect::<Vec<_>>()
+                .join(",");
+
+            for resource_type in &resource_types {
+                let filters: Vec<(&str, String)> = if resource_type == "Patient" {
+                    vec![("_id", chunk.join(","))]
+                } else {
+                    compartment
+                        .get_inclusion_params(resource_type)
+                        .unwrap_or_default()
+                        .iter()
+                        .map(|param| (param.as_str(), references.clone()))
+                        .collect()
+                };
+                // A resource can sit in several compartments of the chunk;
+                // export it once
+                let mut seen = HashSet::new();
+
+                for (param, value) in &filters {
+                    match export_resource_type(
+                        state,
+                        writer,
+                        resource_type,
+                        export_params,
+                        batch_size,
+                        Some((*param, value.as_str())),
+                        Some(&mut seen),
+                    )
+                    .await
+                    {
+                        Ok(count) => resources_exported += count,
+                        Err(e) => {
+                            tracing::warn!(
+                                job_id = %job_id,
+                                resource_type = %resource_type,
+                                param = %param,
+                                error = %e,
+                                "Failed to export group compartment resources"
+                            );
+                        }
+                    }
+                }
+            }
+            members_exported += chunk.len();
+        }
+
+        pages += 1;
+        let progress = GroupMembers::progress(&cursor, candidates);
+        let result = if pages % GROUP_CHECKPOINT_PAGES == 0 {
+            let files = writer
+                .checkpoint()
+                .await
+                .map_err(|e| format!("Failed to checkpoint export files: {}", e))?;
+            checkpoint.cursor = cursor.clone();
+            checkpoint.members_exported = members_exported;
+            checkpoint.resources_exported = resources_exported;
+            checkpoint.set_files(files);
+            state
+                .async_job_manager
+                .update_progress_with_result(job_id, progress, &json!(checkpoint))
+                .await
+        } else {
+            state
+                .async_job_manager
+                .update_progress(job_id, progress)
+                .await
+        };
+        if let Err(e) = result {
+            tracing::warn!(error = %e, "Failed to update job progress");
+        }
+    }
+
+    Ok(resources_exported)
+}
+
+/// Where a group export got to, kept in the job result while it runs.
+///
+/// The counts and files are those of the last checkpoint; the files are
+/// complete, and resuming continues after `cursor`.
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GroupCheckpoint {
+    group: String,
+    group_version: String,
+    cursor: MemberCursor,
+    members_exported: usize,
+    resources_exported: usize,
+    /// Files by resource type, as (file name, resource count)
+    files: std::collections::BTreeMap<String, Vec<(String, usize)>>,
+}
+
+impl GroupCheckpoint {
+    fn set_files(&mut self, files: std::collections::HashMap<String, Vec<(PathBuf, usize)>>) {
+        self.files = files
+            .into_iter()
+            .map(|(resource_type, files)| {
+                let files = files
+                    .into_iter()
+                    .filter_map(|(path, count)| {
+                        Some((path.file_name()?.to_str()?.to_string(), count))
+                    })
+                    .collect();
+                (resource_type, files)
+            })
+            .collect();
+    }
+
+    fn files(&self, job_dir: &Path) -> std::collections::HashMap<String, Vec<(PathBuf, usize)>> {
+        self.files
+            .iter()
+            .map(|(resource_type, files)| {
+                let files = files
+                    .iter()
+                    .map(|(name, count)| (job_dir.join(name), *count))
+                    .collect();
+                (resource_type.clone(), files)
+            })
+            .collect()
+    }
 }
 
 /// Get all available resource types from storage
@@ -526,26 +791,37 @@ async fn get_all_resource_types(_state: &AppState) -> Result<Vec<String>, String
 }
 
 /// Export a single resource type to NDJSON files
+///
+/// Pages are read in id order, each after the last id of the one before.
+/// `filter` adds one more search parameter (a compartment link for group
+/// exports). Resources whose IDs are already in `seen` are skipped, and
+/// written IDs are added to it.
 async fn export_resource_type(
     state: &AppState,
     writer: &mut NdjsonWriter,
     resource_type: &str,
     params: &BulkExportParams,
     batch_size: usize,
+    filter: Option<(&str, &str)>,
+    mut seen: Option<&mut HashSet<String>>,
 ) -> Result<usize, String> {
     use octofhir_storage::SearchParams;
 
     let mut total = 0;
-    let mut offset = 0;
+    let mut after = String::new();
 
     loop {
         // Build search parameters
         let mut search_params = SearchParams {
             count: Some(batch_size as u32),
-            offset: Some(offset as u32),
+            after_id: Some(after.clone()),
             ..Default::default()
         };
 
+        if let Some((param, value)) = filter {
+            search_params = search_params.with_param(param, value);
+        }
+
         // Add _since filter if specified
         if let Some(since) = &params.since {
             search_params
@@ -579,25 +855,27 @@ async fn export_resource_type(
             .map_err(|e| format!("Search failed for {}: {}", resource_type, e))?;
 
         let entries = result.entries;
-        let count = entries.len();
-
-        if count == 0 {
+        let Some(last) = entries.last() else {
             break;
-        }
+        };
+        after = last.id.clone();
 
         // Write resources to NDJSON
         for entry in &entries {
+            if let Some(seen) = seen.as_deref_mut()
+                && !seen.insert(entry.id.clone())
+            {
+                continue;
+            }
             writer
                 .write_resource(resource_type, &entry.resource)
                 .await
                 .map_err(|e| format!("Failed to write resource: {}", e))?;
+            total += 1;
         }
 
-        total += count;
-        offset += count;
-
         // Check if we've retrieved all resources
-        if count < batch_size {
+        if !result.has_more {
             break;
         }
     }
@@ -609,6 +887,24 @@ async fn export_resource_type(
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_group_checkpoint_files_are_relative_to_the_job() {
+        let mut checkpoint = GroupCheckpoint::default();
+        checkpoint.set_files(std::collections::HashMap::from([(
+            "Patient".to_string(),
+            vec![(PathBuf::from("/exports/old/Patient.ndjson"), 2)],
+        )]));
+
+        let value = json!(checkpoint);
+        assert_eq!(value["files"]["Patient"], json!([["Patient.ndjson", 2]]));
+        let restored: GroupCheckpoint = serde_json::from_value(value).unwrap();
+        let files = restored.files(Path::new("/exports/new"));
+        assert_eq!(
+            files["Patient"],
+            vec![(PathBuf::from("/exports/new/Patient.ndjson"), 2)]
+        );
+    }
+
     #[test]
     fn test_parse_params_empty() {
         let config = BulkExportConfig::default();
@@ -640,6 +936,17 @@ mod tests {
         assert!(result.since.is_some());
     }
 
+    #[test]
+    fn test_parse_params_compression() {
+        let op = ExportOperation::new(BulkExportConfig::default());
+        let gzip = op.parse_params(&json!({"compression": "gzip"})).unwrap();
+        assert_eq!(gzip.gzip, Some(true));
+        let none = op.parse_params(&json!({"compression": "none"})).unwrap();
+        assert_eq!(none.gzip, Some(false));
+        assert!(op.parse_params(&json!({})).unwrap().gzip.is_none());
+        assert!(op.parse_params(&json!({"compression": "zstd"})).is_err());
+    }
+
     #[test]
     fn test_parse_params_invalid_format() {
         let config = BulkExportConfig::default();
@@ -0,0 +1,444 @@
+//! Group member resolution for group-level bulk export
+//!
+//! Resolves `/Group/{id}/$export` to the set of member patients:
+//!
+//! - Enumerated groups: active `Group.member.entity` references to `Patient`
+//! - Dynamic groups: `member-filter` modifier extensions from the Bulk Data
+//!   IG, each holding a FHIR search (`Patient?gender=female`,
+//!   `Condition?code=...`). A patient is a member when it matches every
+//!   filter; filters on other resource types match the patients those
+//!   resources refer to through `subject` or `patient`.
+//!
+//! Both kinds may be combined, in which case the union is exported.
+//!
+//! Members are produced a page at a time, enumerated members first and then
+//! the patients of the member filters in id order, so a group of any size is
+//! exported without holding its member list. A [`MemberCursor`] records how
+//! far the pages got, which lets an interrupted export continue.
+
+use std::collections::BTreeSet;
+
+use octofhir_storage::{SearchParams, TotalMode};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::server::AppState;
+
+/// Bulk Data IG extension holding a member-filter search expression
+pub const MEMBER_FILTER_EXTENSION_URL: &str =
+    "http://hl7.org/fhir/uv/bulkdata/StructureDefinition/member-filter";
+
+/// Members per page, and page size of member-filter searches
+const MEMBER_PAGE_SIZE: usize = 1000;
+
+/// Search parameters linking a member-filter resource to its patients
+const PATIENT_LINK_PARAMS: [&str; 2] = ["subject", "patient"];
+
+/// The members of a group, paged by [`GroupMembers::next_page`].
+#[derive(Debug)]
+pub struct GroupMembers {
+    /// Version of the Group the members were resolved from
+    version: String,
+    /// Enumerated member IDs, sorted and deduplicated
+    enumerated: Vec<String>,
+    /// Member filters, if the group has any
+    filters: Option<MemberFilters>,
+}
+
+/// Member-filter searches, all of which a member matches.
+#[derive(Debug)]
+struct MemberFilters {
+    /// The `Patient` filters, combined into one query
+    patient_query: String,
+    /// Filters on other resource types
+    others: Vec<(String, String)>,
+}
+
+/// Position of an export in the pages of [`GroupMembers`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MemberCursor {
+    /// Enumerated members paged out
+    pub enumerated: usize,
+    /// Last candidate patient of the member filters paged out
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub after: Option<String>,
+    /// Candidate patients of the member filters paged out
+    pub scanned: usize,
+    /// Whether the member filters have no candidates left
+    pub done: bool,
+}
+
+impl GroupMembers {
+    /// Read a group and prepare paging through its members.
+    pub async fn resolve(state: &AppState, group_id: &str) -> Result<Self, String> {
+        let stored = state
+            .storage
+            .read("Group", group_id)
+            .await
+            .map_err(|e| format!("Failed to read Group/{}: {}", group_id, e))?
+            .ok_or_else(|| format!("Group/{} not found", group_id))?;
+        let group = &stored.resource;
+
+        let enumerated: Vec<String> = enumerated_members(group)
+            .into_iter()
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+
+        let filters = member_filters(group)?;
+        if filters.is_empty() && enumerated.is_empty() && group.get("characteristic").is_some() {
+            return Err(format!(
+                "Group/{} is defined by characteristics only; add a member-filter extension \
+                 with a search expression to export it",
+                group_id
+            ));
+        }
+
+        let filters = (!filters.is_empty()).then(|| {
+            let (patient, others): (Vec<_>, Vec<_>) = filters
+                .into_iter()
+                .partition(|(resource_type, _)| resource_type == "Patient");
+            MemberFilters {
+                patient_query: patient
+                    .into_iter()
+                    .map(|(_, query)| query)
+                    .filter(|query| !query.is_empty())
+                    .collect::<Vec<_>>()
+                    .join("&"),
+                others,
+            }
+        });
+
+        Ok(Self {
+            version: stored.version_id,
+            enumerated,
+            filters,
+        })
+    }
+
+    /// Version of the Group the members were resolved from.
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    /// Number of enumerated members plus candidate patients of the member
+    /// filters, which bounds the work [`Self::progress`] is measured against.
+    pub async fn candidates(&self, state: &AppState) -> Result<usize, String> {
+        let Some(filters) = &self.filters else {
+            return Ok(self.enumerated.len());
+        };
+        let mut params = search_params(&filters.patient_query, 1);
+        params.total = Some(TotalMode::Accurate);
+        let result = state
+            .storage
+            .search("Patient", &params)
+            .await
+            .map_err(|e| format!("member-filter search on Patient failed: {}", e))?;
+        Ok(self.enumerated.len() + result.total.unwrap_or(0) as usize)
+    }
+
+    /// Share of `candidates` a cursor has paged out.
+    pub fn progress(cursor: &MemberCursor, candidates: usize) -> f32 {
+        let done = cursor.enumerated + cursor.scanned;
+        if done >= candidates {
+            1.0
+        } else {
+            done as f32 / candidates as f32
+        }
+    }
+
+    /// Next page of member IDs after `cursor`, which is advanced past it.
+    ///
+    /// A page of the member filters can be empty when none of its candidates
+    /// match; `None` means every member has been paged out.
+    pub async fn next_page(
+        &self,
+        state: &AppState,
+        cursor: &mut MemberCursor,
+    ) -> Result<Option<Vec<String>>, String> {
+        if cursor.enumerated < self.enumerated.len() {
+            let page: Vec<String> = self.enumerated[cursor.enumerated..]
+                .iter()
+                .take(MEMBER_PAGE_SIZE)
+                .cloned()
+                .collect();
+            cursor.enumerated += page.len();
+            return Ok(Some(page));
+        }
+
+        let Some(filters) = &self.filters else {
+            return Ok(None);
+        };
+        if cursor.done {
+            return Ok(None);
+        }
+
+        let mut params = search_params(&filters.patient_query, MEMBER_PAGE_SIZE);
+        params.after_id = Some(cursor.after.clone().unwrap_or_default());
+        let result = state
+            .storage
+            .search("Patient", &params)
+            .await
+            .map_err(|e| format!("member-filter search on Patient failed: {}", e))?;
+        let Some(last) = result.entries.last() else {
+            cursor.done = true;
+            return Ok(None);
+        };
+        cursor.after = Some(last.id.clone());
+        cursor.scanned += result.entries.len();
+        cursor.done = !result.has_more;
+
+        // Enumerated members were paged out already
+        let mut matched: BTreeSet<String> = result
+            .entries
+            .iter()
+            .map(|entry| entry.id.clone())
+            .filter(|id| self.enumerated.binary_search(id).is_err())
+            .collect();
+        for (resource_type, query) in &filters.others {
+            if matched.is_empty() {
+                break;
+            }
+            let found = referencing_patients(state, resource_type, query, &matched).await?;
+            matched.retain(|id| found.contains(id));
+        }
+
+        Ok(Some(matched.into_iter().collect()))
+    }
+}
+
+/// Patient IDs listed in `Group.member`, skipping inactive members.
+pub fn enumerated_members(group: &Value) -> Vec<String> {
+    group
+        .get("member")
+        .and_then(|m| m.as_array())
+        .map(|members| {
+            members
+                .iter()
+                .filter(|m| m.get("inactive").and_then(|v| v.as_bool()) != Some(true))
+                .filter_map(|m| m.get("entity")?.get("reference")?.as_str())
+                .filter_map(patient_id_from_reference)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Parse `member-filter` extensions into (resource type, query) pairs.
+pub fn member_filters(group: &Value) -> Result<Vec<(String, String)>, String> {
+    let Some(extensions) = group.get("modifierExtension").and_then(|e| e.as_array()) else {
+        return Ok(Vec::new());
+    };
+
+    extensions
+        .iter()
+        .filter(|ext| ext.get("url").and_then(|u| u.as_str()) == Some(MEMBER_FILTER_EXTENSION_URL))
+        .map(|ext| {
+            let expression = ext
+                .get("valueExpression")
+                .and_then(|v| v.get("expression"))
+                .and_then(|e| e.as_str())
+                .ok_or("member-filter extension requires valueExpression.expression")?;
+            let (resource_type, query) = expression.split_once('?').unwrap_or((expression, ""));
+            if resource_type.is_empty() {
+                return Err(format!("Invalid member-filter expression: {}", expression));
+            }
+            Ok((resource_type.to_string(), query.to_string()))
+        })
+        .collect()
+}
+
+/// Search parameters of a member-filter query.
+fn search_params(query: &str, count: usize) -> SearchParams {
+    let mut params = SearchParams {
+        count: Some(count as u32),
+        ..Default::default()
+    };
+    for (key, value) in url::form_urlencoded::parse(query.as_bytes()) {
+        params = params.with_param(key, value);
+    }
+    params
+}
+
+/// Those of `patients` referred to by a resource matching a member-filter
+/// search on another resource type.
+async fn referencing_patients(
+    state: &AppState,
+    resource_type: &str,
+    query: &str,
+    patients: &BTreeSet<String>,
+) -> Result<BTreeSet<String>, String> {
+    let references = patients
+        .iter()
+        .map(|id| format!("Patient/{}", id))
+        .collect::<Vec<_>>()
+        .join(",");
+    let compartment = state
+        .compartment_registry
+        .get("Patient")
+        .map_err(|e| format!("Patient compartment unavailable: {}", e))?;
+    let links: Vec<&str> = compartment
+        .get_inclusion_params(resource_type)
+        .unwrap_or_default()
+        .iter()
+        .map(String::as_str)
+        .filter(|param| PATIENT_LINK_PARAMS.contains(param))
+        .collect();
+    if links.is_empty() {
+        return Err(format!(
+            "member-filter on {} cannot be matched to patients: it has no subject or \
+             patient search parameter",
+            resource_type
+        ));
+    }
+    let mut found = BTreeSet::new();
+
+    for param in links {
+        let mut base = search_params(query, MEMBER_PAGE_SIZE);
+        // Values of one parameter are alternatives, so a filter on the link
+        // itself cannot be narrowed further
+        if !base.parameters.contains_key(param) {
+            base = base.with_param(param, references.as_str());
+        }
+        let mut after = String::new();
+        loop {
+            let mut params = base.clone();
+            params.after_id = Some(after.clone());
+            let result = state
+                .storage
+                .search(resource_type, &params)
+                .await
+                .map_err(|e| format!("member-filter search on {} failed: {}", resource_type, e))?;
+            for entry in &result.entries {
+                found.extend(
+                    referenced_patients(&entry.resource)
+                        .into_iter()
+                        .filter(|id| patients.contains(id)),
+                );
+            }
+            match result.entries.last() {
+                Some(last) if result.has_more => after = last.id.clone(),
+                _ => break,
+            }
+        }
+    }
+
+    Ok(found)
+}
+
+/// Patients a resource refers to through its `subject` or `patient` element.
+fn referenced_patients(resource: &Value) -> Vec<String> {
+    ["subject", "patient"]
+        .iter()
+        .filter_map(|field| resource.get(field)?.get("reference")?.as_str())
+        .filter_map(patient_id_from_reference)
+        .collect()
+}
+
+fn patient_id_from_reference(reference: &str) -> Option<String> {
+    let id = reference.strip_prefix("Patient/")?;
+    // Ignore versioned references' history suffix
+    let id = id.split('/').next()?;
+    (!id.is_empty()).then(|| id.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_enumerated_members() {
+        let group = json!({
+            "resourceType": "Group",
+            "member": [
+                {"entity": {"reference": "Patient/p1"}},
+                {"entity": {"reference": "Patient/p2/_history/3"}},
+                {"entity": {"reference": "Patient/p3"}, "inactive": true},
+                {"entity": {"reference": "Practitioner/d1"}}
+            ]
+        });
+        assert_eq!(enumerated_members(&group), vec!["p1", "p2"]);
+        assert!(enumerated_members(&json!({"resourceType": "Group"})).is_empty());
+    }
+
+    #[test]
+    fn test_member_filters() {
+        let group = json!({
+            "resourceType": "Group",
+            "modifierExtension": [
+                {
+                    "url": MEMBER_FILTER_EXTENSION_URL,
+                    "valueExpression": {
+                        "language": "application/x-fhir-query",
+                        "expression": "Patient?gender=female"
+                    }
+                },
+                {
+                    "url": MEMBER_FILTER_EXTENSION_URL,
+                    "valueExpression": {
+                        "language": "application/x-fhir-query",
+                        "expression": "Condition?code=http://snomed.info/sct|44054006"
+                    }
+                }
+            ]
+        });
+        assert_eq!(
+            member_filters(&group).unwrap(),
+            vec![
+                ("Patient".to_string(), "gender=female".to_string()),
+                (
+                    "Condition".to_string(),
+                    "code=http://snomed.info/sct|44054006".to_string()
+                ),
+            ]
+        );
+
+        let invalid = json!({
+            "modifierExtension": [{"url": MEMBER_FILTER_EXTENSION_URL, "valueString": "x"}]
+        });
+        assert!(member_filters(&invalid).is_err());
+    }
+
+    #[test]
+    fn test_member_cursor_progress() {
+        let mut cursor = MemberCursor::default();
+        assert_eq!(GroupMembers::progress(&cursor, 0), 1.0);
+        assert_eq!(GroupMembers::progress(&cursor, 4), 0.0);
+
+        cursor.enumerated = 1;
+        cursor.scanned = 1;
+        assert_eq!(GroupMembers::progress(&cursor, 4), 0.5);
+        // Patients created since the estimate
+        cursor.scanned = 10;
+        assert_eq!(GroupMembers::progress(&cursor, 4), 1.0);
+    }
+
+    #[test]
+    fn test_member_cursor_round_trip() {
+        let cursor = MemberCursor {
+            enumerated: 3,
+            after: Some("p-100".to_string()),
+            scanned: 100,
+            done: false,
+        };
+        let value = serde_json::to_value(&cursor).unwrap();
+        assert_eq!(value["after"], "p-100");
+        assert_eq!(
+            serde_json::from_value::<MemberCursor>(value).unwrap(),
+            cursor
+        );
+    }
+
+    #[test]
+    fn test_referenced_patients() {
+        let observation = json!({
+            "resourceType": "Observation",
+            "subject": {"reference": "Patient/p1"}
+        });
+        assert_eq!(referenced_patients(&observation), vec!["p1"]);
+
+        let encounter_subject = json!({"subject": {"reference": "Group/g1"}});
+        assert!(referenced_patients(&encounter_subject).is_empty());
+    }
+}
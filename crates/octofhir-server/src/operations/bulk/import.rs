@@ -1,14 +1,29 @@
 //! Bulk import operation handler ($import)
 //!
-//! Imports NDJSON resources via async job.
+//! Imports NDJSON resources via async job, following the "ping-pong" kickoff
+//! of the Bulk Data Import IG.
 //!
 //! - System: `POST /$import` with Parameters resource containing NDJSON URLs
 //!
+//! The kickoff returns `202 Accepted` with a `Content-Location` pointing at
+//! `/_async-status/{job-id}`. While the job runs, the status response carries
+//! per-input progress (`inputs`); once complete it returns the import manifest.
+//!
 //! The Parameters resource follows the Bulk Data Import pattern:
 //! ```json
 //! {
 //!   "resourceType": "Parameters",
 //!   "parameter": [
+//!     { "name": "inputFormat", "valueCode": "application/fhir+ndjson" },
+//!     { "name": "inputSource", "valueUri": "https://example.com" },
+//!     {
+//!       "name": "storageDetail",
+//!       "part": [
+//!         { "name": "type", "valueCode": "https" },
+//!         { "name": "authorization", "valueString": "Bearer <token>" }
+//!       ]
+//!     },
+//!     { "name": "ifNotExists", "valueBoolean": true },
 //!     {
 //!       "name": "input",
 //!       "part": [
@@ -19,16 +34,26 @@
 //!   ]
 //! }
 //! ```
+//!
+//! `storageDetail.authorization` is sent verbatim as the `Authorization`
+//! header when fetching each input. It is stored as the job's secret, apart
+//! from the job parameters, is never reported by `_async-status`, and is
+//! deleted once the job completes, fails or is cancelled. With
+//! `ifNotExists`, resources whose id
+//! already exists (and is not deleted) are left untouched and counted as
+//! skipped instead of being overwritten.
 
 use async_trait::async_trait;
 use chrono::Utc;
 use futures_util::{StreamExt, stream};
 use octofhir_db_postgres::SchemaManager;
 use octofhir_search::SearchParameterRegistry;
+use serde::Serialize;
 use serde_json::{Value, json};
 use sqlx_core::query::query;
 use sqlx_core::query_as::query_as;
 use sqlx_core::sql_str::AssertSqlSafe;
+use tokio::sync::Mutex;
 use uuid::Uuid;
 
 use crate::async_jobs::AsyncJobRequest;
@@ -36,6 +61,10 @@ use crate::config::BulkImportConfig;
 use crate::operations::handler::{OperationError, OperationHandler};
 use crate::server::AppState;
 
+/// Input formats accepted in `inputFormat`.
+const SUPPORTED_INPUT_FORMATS: &[&str] =
+    &[super::NDJSON_CONTENT_TYPE, "application/ndjson", "ndjson"];
+
 /// Input source descriptor for import
 #[derive(Debug, Clone)]
 struct ImportInput {
@@ -43,6 +72,13 @@ struct ImportInput {
     url: String,
 }
 
+/// Where and how inputs are fetched (`storageDetail`).
+#[derive(Debug, Clone, Default, PartialEq)]
+struct StorageDetail {
+    /// Value sent as the `Authorization` header on every input fetch
+    authorization: Option<String>,
+}
+
 /// The $import operation handler
 pub struct ImportOperation {
     config: BulkImportConfig,
@@ -53,25 +89,40 @@ impl ImportOperation {
         Self { config }
     }
 
+    /// Unwrap bodies that `OperationParams` auto-wrapped as
+    /// `{"resourceType":"Parameters","parameter":[{"name":"resource","resource":{...}}]}`.
+    fn effective_params(params: &Value) -> &Value {
+        if let Some(parameter) = params.get("parameter").and_then(|v| v.as_array())
+            && parameter.len() == 1
+            && parameter[0].get("name").and_then(|v| v.as_str()) == Some("resource")
+        {
+            return parameter[0].get("resource").unwrap_or(params);
+        }
+        params
+    }
+
+    /// Look up a named parameter in either the FHIR Parameters form
+    /// (`parameter[name=..].value[x]` / `part`) or the simplified JSON form
+    /// (top-level key).
+    fn named_param<'a>(params: &'a Value, name: &str) -> Option<&'a Value> {
+        if let Some(parameter) = params.get("parameter").and_then(|v| v.as_array()) {
+            return parameter
+                .iter()
+                .find(|p| p.get("name").and_then(|v| v.as_str()) == Some(name))
+                .and_then(|p| {
+                    p.as_object().and_then(|obj| {
+                        obj.iter()
+                            .find(|(k, _)| k.starts_with("value") || *k == "part")
+                            .map(|(_, v)| v)
+                    })
+                });
+        }
+        params.get(name)
+    }
+
     /// Parse FHIR Parameters resource into import inputs
     fn parse_inputs(params: &Value) -> Result<Vec<ImportInput>, OperationError> {
-        // OperationParams wraps non-Parameters bodies as:
-        // {"resourceType":"Parameters","parameter":[{"name":"resource","resource":{...}}]}
-        // Unwrap if the body was auto-wrapped
-        let effective_params =
-            if let Some(parameter) = params.get("parameter").and_then(|v| v.as_array()) {
-                if parameter.len() == 1
-                    && parameter[0].get("name").and_then(|v| v.as_str()) == Some("resource")
-                {
-                    // Unwrap the auto-wrapped resource
-                    parameter[0].get("resource").unwrap_or(params)
-                } else {
-                    params
-                }
-            } else {
-                params
-            };
-        let params = effective_params;
+        let params = Self::effective_params(params);
 
         // Support both direct JSON format and FHIR Parameters resource
         let inputs = if let Some(parameter) = params.get("parameter").and_then(|v| v.as_array()) {
@@ -97,6 +148,7 @@ impl ImportOperation {
                         "type" => {
                             resource_type = part
                                 .get("valueString")
+                                .or_else(|| part.get("valueCode"))
                                 .and_then(|v| v.as_str())
                                 .map(|s| s.to_string());
                         }
@@ -161,8 +213,83 @@ impl ImportOperation {
             ));
         }
 
+        // Inputs are fetched with the storageDetail credential, so plain
+        // http would send it in cleartext.
+        for input in &inputs {
+            if !input.url.starts_with("https://") {
+                return Err(OperationError::InvalidParameters(format!(
+                    "Input url '{}' must be an https URL",
+                    input.url
+                )));
+            }
+        }
+
         Ok(inputs)
     }
+
+    /// Reject any `inputFormat` other than NDJSON.
+    fn check_input_format(params: &Value) -> Result<(), OperationError> {
+        let params = Self::effective_params(params);
+        match Self::named_param(params, "inputFormat").and_then(|v| v.as_str()) {
+            Some(format) if !SUPPORTED_INPUT_FORMATS.contains(&format) => {
+                Err(OperationError::NotSupported(format!(
+                    "inputFormat '{format}' is not supported; use {}",
+                    super::NDJSON_CONTENT_TYPE
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Parse `storageDetail`. Only `https` storage is supported.
+    fn parse_storage_detail(params: &Value) -> Result<StorageDetail, OperationError> {
+        let params = Self::effective_params(params);
+        let Some(detail) = Self::named_param(params, "storageDetail") else {
+            return Ok(StorageDetail::default());
+        };
+
+        let (storage_type, authorization) = match detail {
+            // Parameters form: list of parts
+            Value::Array(parts) => {
+                let part_value = |name: &str| {
+                    parts
+                        .iter()
+                        .find(|p| p.get("name").and_then(|v| v.as_str()) == Some(name))
+                        .and_then(|p| {
+                            p.get("valueCode")
+                                .or_else(|| p.get("valueString"))
+                                .and_then(|v| v.as_str())
+                        })
+                        .map(str::to_string)
+                };
+                (part_value("type"), part_value("authorization"))
+            }
+            // Simplified form: { "type": "https", "authorization": "..." }
+            Value::Object(obj) => (
+                obj.get("type").and_then(|v| v.as_str()).map(str::to_string),
+                obj.get("authorization")
+                    .and_then(|v| v.as_str())
+                    .map(str::to_string),
+            ),
+            // `valueCode` shorthand: just the storage type
+            Value::String(s) => (Some(s.clone()), None),
+            _ => {
+                return Err(OperationError::InvalidParameters(
+                    "storageDetail must be a part list or object".to_string(),
+                ));
+            }
+        };
+
+        if let Some(storage_type) = storage_type
+            && storage_type != "https"
+        {
+            return Err(OperationError::NotSupported(format!(
+                "storageDetail type '{storage_type}' is not supported; only 'https' is available"
+            )));
+        }
+
+        Ok(StorageDetail { authorization })
+    }
 }
 
 #[async_trait]
@@ -183,7 +310,17 @@ impl OperationHandler for ImportOperation {
             ));
         }
 
+        Self::check_input_format(params)?;
         let inputs = Self::parse_inputs(params)?;
+        let storage = Self::parse_storage_detail(params)?;
+
+        let effective = Self::effective_params(params);
+        let if_not_exists = Self::named_param(effective, "ifNotExists")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        let input_source = Self::named_param(effective, "inputSource")
+            .and_then(|v| v.as_str())
+            .map(str::to_string);
 
         let skip_validation = params
             .get("skipValidation")
@@ -214,8 +351,12 @@ impl OperationHandler for ImportOperation {
 
         let job_params = json!({
             "input": input_list,
+            "input_source": input_source,
+            "has_authorization": storage.authorization.is_some(),
+            "if_not_exists": if_not_exists,
             "batch_size": batch_size,
             "parallelism": parallelism,
+            "input_concurrency": self.config.max_concurrent_inputs.max(1),
             "skip_validation": skip_validation,
         });
 
@@ -226,6 +367,7 @@ impl OperationHandler for ImportOperation {
             body: Some(job_params),
             headers: None,
             client_id: None,
+            secret: storage.authorization,
         };
 
         let job_id = state
@@ -237,6 +379,7 @@ impl OperationHandler for ImportOperation {
         tracing::info!(
             job_id = %job_id,
             inputs = inputs.len(),
+            if_not_exists,
             "Bulk import job submitted"
         );
 
@@ -250,14 +393,18 @@ impl OperationHandler for ImportOperation {
 
 /// Upsert a resource via INSERT ... ON CONFLICT DO UPDATE.
 /// Handles new resources, existing resources, and previously-deleted resources.
+///
+/// With `if_not_exists`, a live resource with the same id is left untouched
+/// and `Ok(false)` is returned.
 async fn upsert_resource_with_indexes(
     pool: &sqlx_postgres::PgPool,
     _registry: &SearchParameterRegistry,
     resource_type: &str,
     id: &str,
     resource: &Value,
-) -> Result<String, octofhir_storage::StorageError> {
-    let table = SchemaManager::table_name(resource_type);
+    if_not_exists: bool,
+) -> Result<bool, octofhir_storage::StorageError> {
+    let table = SchemaManager::qualified_table_name(resource_type);
     let now = Utc::now();
     let mut tx = pool.begin().await.map_err(|e| {
         octofhir_storage::StorageError::transaction_error(format!(
@@ -271,7 +418,7 @@ async fn upsert_resource_with_indexes(
         r#"WITH new_tx AS (
                SELECT nextval('_transaction_txid_seq') AS txid
            )
-           INSERT INTO "{table}" (id, txid, created_at, updated_at, resource, status)
+           INSERT INTO {table} (id, txid, created_at, updated_at, resource, status)
            SELECT
                $1,
                new_tx.txid,
@@ -296,14 +443,16 @@ async fn upsert_resource_with_indexes(
                resource = EXCLUDED.resource,
                status = 'updated',
                updated_at = EXCLUDED.updated_at
-           RETURNING id"#
+           {conflict_filter}
+           RETURNING id"#,
+        conflict_filter = conflict_filter(&table, if_not_exists)
     );
 
-    let row: (String,) = query_as::<_, (String,)>(AssertSqlSafe(sql.to_string()))
+    let row: Option<(String,)> = query_as::<_, (String,)>(AssertSqlSafe(sql.to_string()))
         .bind(id)
         .bind(now)
         .bind(resource)
-        .fetch_one(&mut *tx)
+        .fetch_optional(&mut *tx)
         .await
         .map_err(|e| {
             octofhir_storage::StorageError::internal(format!("Failed to upsert resource: {e}"))
@@ -315,7 +464,17 @@ async fn upsert_resource_with_indexes(
         ))
     })?;
 
-    Ok(row.0)
+    Ok(row.is_some())
+}
+
+/// `ON CONFLICT ... DO UPDATE` filter for `ifNotExists` imports: only
+/// tombstoned rows may be overwritten.
+fn conflict_filter(table: &str, if_not_exists: bool) -> String {
+    if if_not_exists {
+        format!("WHERE {table}.status = 'deleted'")
+    } else {
+        String::new()
+    }
 }
 
 /// A parsed + validated import line, ready for the batch upsert.
@@ -366,6 +525,15 @@ async fn prepare_import_line(
         }));
     }
 
+    if let Err(error) = check_resource_size(&resource, state.config.server.max_resource_size_bytes)
+    {
+        return Err(json!({
+            "source": source_url,
+            "line": line_number,
+            "error": error,
+        }));
+    }
+
     if !skip_validation {
         let validation_outcome = state.validation_service.validate(&resource).await;
         if !validation_outcome.valid {
@@ -397,15 +565,31 @@ async fn prepare_import_line(
     })
 }
 
+/// Applies `server.max_resource_size_bytes` to an imported resource, as the
+/// REST write handlers do. `0` disables the limit.
+fn check_resource_size(resource: &Value, limit: usize) -> Result<(), String> {
+    let size = octofhir_core::serialized_size(resource);
+    if limit != 0 && size > limit {
+        return Err(format!(
+            "Resource is {size} bytes, exceeding the maximum of {limit} bytes"
+        ));
+    }
+    Ok(())
+}
+
 /// Upsert a whole batch in ONE transaction: one `_transaction` row, one
 /// UNNEST INSERT ... ON CONFLICT, one commit — instead of a transaction and
 /// commit per resource.
+///
+/// Returns the number of rows written; with `if_not_exists` this excludes
+/// rows skipped because the id already existed.
 async fn batch_upsert_resources(
     pool: &sqlx_postgres::PgPool,
     resource_type: &str,
     prepared: &[PreparedImportResource],
+    if_not_exists: bool,
 ) -> Result<u64, octofhir_storage::StorageError> {
-    let table = SchemaManager::table_name(resource_type);
+    let table = SchemaManager::qualified_table_name(resource_type);
     let now = Utc::now();
 
     let ids: Vec<&str> = prepared.iter().map(|p| p.id.as_str()).collect();
@@ -421,7 +605,7 @@ async fn batch_upsert_resources(
         r#"WITH new_tx AS (
                SELECT nextval('_transaction_txid_seq') AS txid
            )
-           INSERT INTO "{table}" (id, txid, created_at, updated_at, resource, status)
+           INSERT INTO {table} (id, txid, created_at, updated_at, resource, status)
            SELECT
                t.id,
                new_tx.txid,
@@ -442,7 +626,9 @@ async fn batch_upsert_resources(
            SET txid = EXCLUDED.txid,
                resource = EXCLUDED.resource,
                status = 'updated',
-               updated_at = EXCLUDED.updated_at"#
+               updated_at = EXCLUDED.updated_at
+           {conflict_filter}"#,
+        conflict_filter = conflict_filter(&table, if_not_exists)
     );
 
     let result = query(AssertSqlSafe(sql.to_string()))
@@ -466,18 +652,25 @@ async fn batch_upsert_resources(
     Ok(result.rows_affected())
 }
 
+/// Outcome of one processed batch.
+#[derive(Debug, Default)]
+struct BatchOutcome {
+    created: usize,
+    skipped: usize,
+    errors: Vec<Value>,
+}
+
 async fn process_import_batch(
     state: AppState,
     job_id: Uuid,
     resource_type: &str,
     source_url: &str,
     lines: Vec<(usize, String)>,
-    parallelism: usize,
-    skip_validation: bool,
-) -> (usize, Vec<Value>) {
-    let concurrency = lines.len().min(parallelism).max(1);
-    let mut created = 0usize;
-    let mut errors = Vec::new();
+    settings: &ImportSettings,
+) -> BatchOutcome {
+    let concurrency = lines.len().min(settings.parallelism).max(1);
+    let mut outcome = BatchOutcome::default();
+    let skip_validation = settings.skip_validation;
 
     // Phase 1 — parse + validate in parallel (no DB writes).
     let mut prepared: Vec<PreparedImportResource> = Vec::with_capacity(lines.len());
@@ -500,21 +693,32 @@ async fn process_import_batch(
     }))
     .buffer_unordered(concurrency);
 
-    while let Some(outcome) = outcomes.next().await {
-        match outcome {
+    while let Some(result) = outcomes.next().await {
+        match result {
             Ok(p) => prepared.push(p),
-            Err(error) => errors.push(error),
+            Err(error) => outcome.errors.push(error),
         }
     }
 
     if prepared.is_empty() {
-        return (created, errors);
+        return outcome;
     }
 
     // Phase 2 — one batched upsert. On batch failure, fall back to per-resource
     // upserts so the offending line(s) are isolated and reported individually.
-    match batch_upsert_resources(state.db_pool.as_ref(), resource_type, &prepared).await {
-        Ok(_) => created += prepared.len(),
+    match batch_upsert_resources(
+        state.db_pool.as_ref(),
+        resource_type,
+        &prepared,
+        settings.if_not_exists,
+    )
+    .await
+    {
+        Ok(written) => {
+            let written = (written as usize).min(prepared.len());
+            outcome.created += written;
+            outcome.skipped += prepared.len() - written;
+        }
         Err(batch_err) => {
             tracing::warn!(
                 job_id = %job_id,
@@ -528,10 +732,12 @@ async fn process_import_batch(
                     resource_type,
                     &p.id,
                     &p.resource,
+                    settings.if_not_exists,
                 )
                 .await
                 {
-                    Ok(_) => created += 1,
+                    Ok(true) => outcome.created += 1,
+                    Ok(false) => outcome.skipped += 1,
                     Err(e) => {
                         tracing::warn!(
                             job_id = %job_id,
@@ -539,7 +745,7 @@ async fn process_import_batch(
                             error = %e,
                             "Failed to create resource"
                         );
-                        errors.push(json!({
+                        outcome.errors.push(json!({
                             "source": source_url,
                             "line": p.line_number,
                             "error": format!("Create failed: {e}"),
@@ -550,246 +756,661 @@ async fn process_import_batch(
         }
     }
 
-    (created, errors)
+    outcome
 }
 
-/// Execute a bulk import job (called by async job executor)
-pub async fn execute_bulk_import(
-    state: AppState,
-    job_id: Uuid,
-    params: Value,
-) -> Result<Value, String> {
-    tracing::info!(job_id = %job_id, "Starting bulk import execution");
+/// Per-job knobs shared by every input.
+#[derive(Debug, Clone)]
+struct ImportSettings {
+    batch_size: usize,
+    parallelism: usize,
+    skip_validation: bool,
+    if_not_exists: bool,
+    authorization: Option<String>,
+}
 
-    let inputs = params
-        .get("input")
-        .and_then(|v| v.as_array())
-        .ok_or("Missing input array in job params")?;
+/// Lifecycle of a single manifest input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum InputStatus {
+    Queued,
+    InProgress,
+    Completed,
+    Failed,
+}
 
-    let batch_size = params
-        .get("batch_size")
-        .and_then(|v| v.as_u64())
-        .unwrap_or(1000)
-        .max(1) as usize;
-    let parallelism = params
-        .get("parallelism")
-        .and_then(|v| v.as_u64())
-        .unwrap_or(32)
-        .max(1) as usize;
+/// Per-input counters reported through `_async-status`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct InputReport {
+    #[serde(rename = "type")]
+    resource_type: String,
+    url: String,
+    status: InputStatus,
+    processed: usize,
+    created: usize,
+    skipped: usize,
+    errors: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error_message: Option<String>,
+    /// Fraction of this input consumed (0.0 to 1.0)
+    #[serde(skip)]
+    fraction: f32,
+}
 
-    let skip_validation = params
-        .get("skip_validation")
-        .and_then(|v| v.as_bool())
-        .unwrap_or(false);
+/// Shared progress for all inputs of a job.
+struct ImportProgress {
+    inputs: Vec<InputReport>,
+    error_details: Vec<Value>,
+}
 
-    let mut total_created: usize = 0;
-    let mut total_errors: usize = 0;
-    let mut error_details: Vec<Value> = Vec::new();
+impl ImportProgress {
+    fn new(inputs: &[ImportInput]) -> Self {
+        Self {
+            inputs: inputs
+                .iter()
+                .map(|i| InputReport {
+                    resource_type: i.resource_type.clone(),
+                    url: i.url.clone(),
+                    status: InputStatus::Queued,
+                    processed: 0,
+                    created: 0,
+                    skipped: 0,
+                    errors: 0,
+                    error_message: None,
+                    fraction: 0.0,
+                })
+                .collect(),
+            error_details: Vec::new(),
+        }
+    }
 
-    let http_client = reqwest::Client::new();
+    /// Overall job progress: mean of per-input fractions.
+    fn overall(&self) -> f32 {
+        if self.inputs.is_empty() {
+            return 1.0;
+        }
+        self.inputs.iter().map(|i| i.fraction).sum::<f32>() / self.inputs.len() as f32
+    }
 
-    for (idx, input) in inputs.iter().enumerate() {
-        let resource_type = input
-            .get("type")
-            .and_then(|v| v.as_str())
-            .ok_or("Missing type in input")?;
-        let url = input
-            .get("url")
-            .and_then(|v| v.as_str())
-            .ok_or("Missing url in input")?;
+    /// Snapshot stored in the job result while the job is running.
+    fn snapshot(&self) -> Value {
+        json!({ "inputs": self.inputs })
+    }
+}
 
-        tracing::info!(
+/// Apply a change to one input's report and publish the new snapshot.
+///
+/// The lock is held across the database write so snapshots are persisted in
+/// the order they were produced.
+async fn report_input(
+    state: &AppState,
+    job_id: Uuid,
+    progress: &Mutex<ImportProgress>,
+    idx: usize,
+    update: impl FnOnce(&mut InputReport, &mut Vec<Value>),
+) {
+    let mut guard = progress.lock().await;
+    let ImportProgress {
+        inputs,
+        error_details,
+    } = &mut *guard;
+    update(&mut inputs[idx], error_details);
+
+    if let Err(e) = state
+        .async_job_manager
+        .update_progress_with_result(job_id, guard.overall(), &guard.snapshot())
+        .await
+    {
+        tracing::warn!(
             job_id = %job_id,
-            resource_type = %resource_type,
-            url = %url,
-            input_index = idx,
-            "Processing import source"
+            error = %e,
+            "Failed to update progress"
         );
+    }
+}
 
-        // Fetch NDJSON from URL
-        let response = http_client
-            .get(url)
-            .send()
-            .await
-            .map_err(|e| format!("Failed to fetch {url}: {e}"))?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let msg = format!("Failed to fetch {url}: HTTP {status}");
-            tracing::error!(job_id = %job_id, %msg);
-            error_details.push(json!({
-                "source": url,
-                "error": msg,
-            }));
-            total_errors += 1;
-            continue;
-        }
+/// Fetch one NDJSON input and ingest it batch by batch.
+///
+/// Returns an error only when the input as a whole could not be read;
+/// line-level failures are recorded in the progress report.
+async fn import_source(
+    state: &AppState,
+    job_id: Uuid,
+    http_client: &reqwest::Client,
+    idx: usize,
+    input: &ImportInput,
+    settings: &ImportSettings,
+    progress: &Mutex<ImportProgress>,
+) -> Result<(), String> {
+    let resource_type = input.resource_type.as_str();
+    let url = input.url.as_str();
 
-        let content_length = response.content_length();
-        let mut response = response;
-        let mut buffered = Vec::new();
-        let mut line_number = 0usize;
-        let mut processed_lines = 0usize;
-        let mut source_created = 0usize;
-        let mut bytes_read = 0u64;
-        let mut pending_batch: Vec<(usize, String)> = Vec::with_capacity(batch_size);
-        let progress_start = (idx as f32 / inputs.len() as f32) * 100.0;
-        let progress_end = ((idx + 1) as f32 / inputs.len() as f32) * 100.0;
-
-        while let Some(chunk) = response
-            .chunk()
-            .await
-            .map_err(|e| format!("Failed to read chunk from {url}: {e}"))?
-        {
-            bytes_read = bytes_read.saturating_add(chunk.len() as u64);
+    tracing::info!(
+        job_id = %job_id,
+        resource_type = %resource_type,
+        url = %url,
+        input_index = idx,
+        "Processing import source"
+    );
+
+    report_input(state, job_id, progress, idx, |report, _| {
+        report.status = InputStatus::InProgress;
+    })
+    .await;
 
-            buffered.extend_from_slice(&chunk);
+    // Fetch NDJSON from URL
+    let mut request = http_client.get(url);
+    if let Some(authorization) = &settings.authorization {
+        request = request.header(reqwest::header::AUTHORIZATION, authorization);
+    }
+    let mut response = request
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch {url}: {e}"))?;
 
-            while let Some(newline_pos) = buffered.iter().position(|byte| *byte == b'\n') {
-                let mut line_bytes: Vec<u8> = buffered.drain(..=newline_pos).collect();
-                if line_bytes.last() == Some(&b'\n') {
-                    line_bytes.pop();
-                }
-                if line_bytes.last() == Some(&b'\r') {
-                    line_bytes.pop();
-                }
+    if !response.status().is_success() {
+        return Err(format!("Failed to fetch {url}: HTTP {}", response.status()));
+    }
 
-                let line = match String::from_utf8(line_bytes) {
-                    Ok(line) => line,
-                    Err(e) => {
-                        line_number += 1;
-                        processed_lines += 1;
-                        tracing::warn!(
-                            job_id = %job_id,
-                            line = line_number,
-                            error = %e,
-                            "Invalid UTF-8 line, skipping"
-                        );
-                        error_details.push(json!({
-                            "source": url,
-                            "line": line_number,
-                            "error": format!("Invalid UTF-8: {e}"),
-                        }));
-                        total_errors += 1;
-                        continue;
-                    }
-                };
+    let content_length = response.content_length();
+    let mut buffered = Vec::new();
+    let mut line_number = 0usize;
+    let mut bytes_read = 0u64;
+    let mut pending_batch: Vec<(usize, String)> = Vec::with_capacity(settings.batch_size);
 
-                if line.trim().is_empty() {
-                    continue;
-                }
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| format!("Failed to read chunk from {url}: {e}"))?
+    {
+        bytes_read = bytes_read.saturating_add(chunk.len() as u64);
+
+        buffered.extend_from_slice(&chunk);
+
+        while let Some(newline_pos) = buffered.iter().position(|byte| *byte == b'\n') {
+            let mut line_bytes: Vec<u8> = buffered.drain(..=newline_pos).collect();
+            if line_bytes.last() == Some(&b'\n') {
+                line_bytes.pop();
+            }
+            if line_bytes.last() == Some(&b'\r') {
+                line_bytes.pop();
+            }
 
-                line_number += 1;
-                processed_lines += 1;
-                pending_batch.push((line_number, line));
-
-                if pending_batch.len() >= batch_size {
-                    let (batch_created, mut batch_errors) = process_import_batch(
-                        state.clone(),
-                        job_id,
-                        resource_type,
-                        url,
-                        std::mem::take(&mut pending_batch),
-                        parallelism,
-                        skip_validation,
-                    )
+            let line = match String::from_utf8(line_bytes) {
+                Ok(line) => line,
+                Err(e) => {
+                    line_number += 1;
+                    tracing::warn!(
+                        job_id = %job_id,
+                        line = line_number,
+                        error = %e,
+                        "Invalid UTF-8 line, skipping"
+                    );
+                    let error = json!({
+                        "source": url,
+                        "line": line_number,
+                        "error": format!("Invalid UTF-8: {e}"),
+                    });
+                    report_input(state, job_id, progress, idx, |report, errors| {
+                        report.processed += 1;
+                        report.errors += 1;
+                        errors.push(error);
+                    })
                     .await;
-                    source_created += batch_created;
-                    total_errors += batch_errors.len();
-                    error_details.append(&mut batch_errors);
-
-                    let progress_pct = if let Some(total_bytes) = content_length {
-                        if total_bytes > 0 {
-                            progress_start
-                                + ((bytes_read as f32 / total_bytes as f32)
-                                    * (progress_end - progress_start))
-                        } else {
-                            progress_end
-                        }
-                    } else {
-                        progress_start
-                    };
-
-                    if let Err(e) = state
-                        .async_job_manager
-                        .update_progress(job_id, progress_pct)
-                        .await
-                    {
-                        tracing::warn!(
-                            job_id = %job_id,
-                            error = %e,
-                            "Failed to update progress"
-                        );
-                    }
+                    continue;
                 }
+            };
+
+            if line.trim().is_empty() {
+                continue;
             }
-        }
 
-        if !buffered.iter().all(|byte| byte.is_ascii_whitespace()) {
             line_number += 1;
-            processed_lines += 1;
+            pending_batch.push((line_number, line));
+
+            if pending_batch.len() >= settings.batch_size {
+                let batch = std::mem::take(&mut pending_batch);
+                let batch_len = batch.len();
+                let outcome = process_import_batch(
+                    state.clone(),
+                    job_id,
+                    resource_type,
+                    url,
+                    batch,
+                    settings,
+                )
+                .await;
 
-            if buffered.last() == Some(&b'\r') {
-                buffered.pop();
+                let fraction = match content_length {
+                    Some(total_bytes) if total_bytes > 0 => {
+                        (bytes_read as f32 / total_bytes as f32).min(1.0)
+                    }
+                    Some(_) => 1.0,
+                    None => 0.0,
+                };
+                report_input(state, job_id, progress, idx, |report, errors| {
+                    apply_batch(report, errors, batch_len, outcome);
+                    report.fraction = fraction;
+                })
+                .await;
             }
+        }
+    }
 
-            let trailing_line = String::from_utf8(buffered)
-                .map_err(|e| format!("Invalid UTF-8 in trailing NDJSON line from {url}: {e}"))?;
+    if !buffered.iter().all(|byte| byte.is_ascii_whitespace()) {
+        line_number += 1;
 
-            pending_batch.push((line_number, trailing_line));
+        if buffered.last() == Some(&b'\r') {
+            buffered.pop();
         }
 
-        if !pending_batch.is_empty() {
-            let (batch_created, mut batch_errors) = process_import_batch(
-                state.clone(),
-                job_id,
-                resource_type,
-                url,
-                std::mem::take(&mut pending_batch),
-                parallelism,
-                skip_validation,
-            )
-            .await;
-            source_created += batch_created;
-            total_errors += batch_errors.len();
-            error_details.append(&mut batch_errors);
-        }
+        let trailing_line = String::from_utf8(buffered)
+            .map_err(|e| format!("Invalid UTF-8 in trailing NDJSON line from {url}: {e}"))?;
 
-        total_created += source_created;
+        pending_batch.push((line_number, trailing_line));
+    }
 
-        if let Err(e) = state
-            .async_job_manager
-            .update_progress(job_id, progress_end)
-            .await
-        {
-            tracing::warn!(
-                job_id = %job_id,
-                error = %e,
-                "Failed to update progress"
-            );
-        }
+    let batch_len = pending_batch.len();
+    let outcome = if pending_batch.is_empty() {
+        BatchOutcome::default()
+    } else {
+        process_import_batch(
+            state.clone(),
+            job_id,
+            resource_type,
+            url,
+            pending_batch,
+            settings,
+        )
+        .await
+    };
+
+    report_input(state, job_id, progress, idx, |report, errors| {
+        apply_batch(report, errors, batch_len, outcome);
+        report.fraction = 1.0;
+        report.status = InputStatus::Completed;
 
         tracing::info!(
             job_id = %job_id,
-            resource_type = %resource_type,
-            processed_lines = processed_lines,
-            created = source_created,
+            resource_type = %report.resource_type,
+            processed_lines = report.processed,
+            created = report.created,
+            skipped = report.skipped,
             "Completed streaming NDJSON import source"
         );
-    }
+    })
+    .await;
 
-    let result = json!({
-        "total_created": total_created,
-        "total_errors": total_errors,
-        "errors": error_details,
-    });
+    Ok(())
+}
+
+fn apply_batch(
+    report: &mut InputReport,
+    errors: &mut Vec<Value>,
+    batch_len: usize,
+    mut outcome: BatchOutcome,
+) {
+    report.processed += batch_len;
+    report.created += outcome.created;
+    report.skipped += outcome.skipped;
+    report.errors += outcome.errors.len();
+    errors.append(&mut outcome.errors);
+}
+
+/// Execute a bulk import job (called by async job executor)
+pub async fn execute_bulk_import(
+    state: AppState,
+    job_id: Uuid,
+    params: Value,
+) -> Result<Value, String> {
+    tracing::info!(job_id = %job_id, "Starting bulk import execution");
+
+    let inputs = params
+        .get("input")
+        .and_then(|v| v.as_array())
+        .ok_or("Missing input array in job params")?
+        .iter()
+        .map(|input| {
+            Ok(ImportInput {
+                resource_type: input
+                    .get("type")
+                    .and_then(|v| v.as_str())
+                    .ok_or("Missing type in input")?
+                    .to_string(),
+                url: input
+                    .get("url")
+                    .and_then(|v| v.as_str())
+                    .ok_or("Missing url in input")?
+                    .to_string(),
+            })
+        })
+        .collect::<Result<Vec<_>, &str>>()?;
+
+    let secret = state
+        .async_job_manager
+        .job_secret(job_id)
+        .await
+        .map_err(|e| format!("Failed to read storageDetail authorization: {e}"))?;
+    let authorization = job_authorization(&params, secret)?;
+
+    let settings = ImportSettings {
+        batch_size: params
+            .get("batch_size")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(1000)
+            .max(1) as usize,
+        parallelism: params
+            .get("parallelism")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(32)
+            .max(1) as usize,
+        skip_validation: params
+            .get("skip_validation")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false),
+        if_not_exists: params
+            .get("if_not_exists")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false),
+        authorization,
+    };
+    let input_concurrency = params
+        .get("input_concurrency")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(1)
+        .max(1) as usize;
+
+    // https only, so neither a queued plain-http input nor a redirect can
+    // carry the storageDetail authorization in cleartext
+    let http_client = reqwest::Client::builder()
+        .https_only(true)
+        .build()
+        .map_err(|e| format!("Failed to build import HTTP client: {e}"))?;
+    let progress = Mutex::new(ImportProgress::new(&inputs));
+
+    // Collected up front so the stream holds concrete futures rather than a
+    // closure over borrowed inputs, which the job runner's `Send` bound rejects
+    let imports: Vec<_> = inputs
+        .iter()
+        .enumerate()
+        .map(|(idx, input)| {
+            let state = &state;
+            let http_client = &http_client;
+            let settings = &settings;
+            let progress = &progress;
+            async move {
+                if let Err(msg) =
+                    import_source(state, job_id, http_client, idx, input, settings, progress).await
+                {
+                    tracing::error!(job_id = %job_id, input_index = idx, %msg);
+                    let error = json!({ "source": input.url, "error": msg.clone() });
+                    report_input(state, job_id, progress, idx, |report, errors| {
+                        report.status = InputStatus::Failed;
+                        report.fraction = 1.0;
+                        report.errors += 1;
+                        report.error_message = Some(msg);
+                        errors.push(error);
+                    })
+                    .await;
+                }
+            }
+        })
+        .collect();
+
+    stream::iter(imports)
+        .buffer_unordered(input_concurrency)
+        .collect::<Vec<()>>()
+        .await;
+
+    let progress = progress.lock().await;
+    let result = import_manifest(
+        &progress,
+        params
+            .get("input_source")
+            .and_then(|v| v.as_str())
+            .map(str::to_string),
+        format!("{}/fhir/$import", state.base_url),
+    );
 
     tracing::info!(
         job_id = %job_id,
-        total_created = total_created,
-        total_errors = total_errors,
+        total_created = %result["total_created"],
+        total_errors = %result["total_errors"],
         "Bulk import completed"
     );
 
     Ok(result)
 }
+
+/// The `storageDetail.authorization` of a job, from the job's stored secret.
+///
+/// Fails when the job was submitted with one that is no longer stored, e.g.
+/// because the job was cancelled or expired: fetching without it would only
+/// fail later with less obvious errors.
+fn job_authorization(params: &Value, secret: Option<String>) -> Result<Option<String>, String> {
+    let submitted = params
+        .get("has_authorization")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    match secret {
+        None if submitted => Err(
+            "storageDetail authorization is no longer available; resubmit the import".to_string(),
+        ),
+        secret => Ok(secret),
+    }
+}
+
+/// Build the completion manifest returned by `_async-status`.
+fn import_manifest(
+    progress: &ImportProgress,
+    input_source: Option<String>,
+    request: String,
+) -> Value {
+    let total_created: usize = progress.inputs.iter().map(|i| i.created).sum();
+    let total_skipped: usize = progress.inputs.iter().map(|i| i.skipped).sum();
+    let total_errors: usize = progress.inputs.iter().map(|i| i.errors).sum();
+
+    let output: Vec<Value> = progress
+        .inputs
+        .iter()
+        .filter(|i| i.status == InputStatus::Completed)
+        .map(|i| {
+            json!({
+                "type": i.resource_type,
+                "inputUrl": i.url,
+                "count": i.created,
+                "skipped": i.skipped,
+            })
+        })
+        .collect();
+    let error: Vec<Value> = progress
+        .inputs
+        .iter()
+        .filter(|i| i.errors > 0)
+        .map(|i| {
+            json!({
+                "type": "OperationOutcome",
+                "inputUrl": i.url,
+                "count": i.errors,
+            })
+        })
+        .collect();
+
+    json!({
+        "transactionTime": Utc::now().to_rfc3339(),
+        "request": request,
+        "inputSource": input_source,
+        "output": output,
+        "error": error,
+        "inputs": progress.inputs,
+        "total_created": total_created,
+        "total_skipped": total_skipped,
+        "total_errors": total_errors,
+        "errors": progress.error_details,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kickoff(extra: Vec<Value>) -> Value {
+        let mut parameter = vec![json!({
+            "name": "input",
+            "part": [
+                { "name": "type", "valueString": "Patient" },
+                { "name": "url", "valueUrl": "https://example.com/patients.ndjson" }
+            ]
+        })];
+        parameter.extend(extra);
+        json!({ "resourceType": "Parameters", "parameter": parameter })
+    }
+
+    #[test]
+    fn test_parse_inputs_parameters_form() {
+        let inputs = ImportOperation::parse_inputs(&kickoff(vec![])).unwrap();
+        assert_eq!(inputs.len(), 1);
+        assert_eq!(inputs[0].resource_type, "Patient");
+        assert_eq!(inputs[0].url, "https://example.com/patients.ndjson");
+    }
+
+    #[test]
+    fn test_parse_inputs_rejects_non_http_url() {
+        let params = json!({ "input": [{ "type": "Patient", "url": "file:///etc/passwd" }] });
+        assert!(ImportOperation::parse_inputs(&params).is_err());
+    }
+
+    #[test]
+    fn test_parse_inputs_rejects_plain_http_url() {
+        let params =
+            json!({ "input": [{ "type": "Patient", "url": "http://example.com/p.ndjson" }] });
+        assert!(matches!(
+            ImportOperation::parse_inputs(&params),
+            Err(OperationError::InvalidParameters(_))
+        ));
+    }
+
+    #[test]
+    fn test_check_resource_size() {
+        let resource = json!({ "resourceType": "Patient", "id": "p1" });
+        let size = octofhir_core::serialized_size(&resource);
+
+        assert!(check_resource_size(&resource, 0).is_ok());
+        assert!(check_resource_size(&resource, size).is_ok());
+        let error = check_resource_size(&resource, size - 1).unwrap_err();
+        assert!(error.contains("exceeding the maximum"), "{error}");
+    }
+
+    #[test]
+    fn test_job_authorization() {
+        assert_eq!(job_authorization(&json!({}), None), Ok(None));
+
+        let params = json!({ "has_authorization": true });
+        assert_eq!(
+            job_authorization(&params, Some("Bearer abc".to_string())),
+            Ok(Some("Bearer abc".to_string()))
+        );
+        // Submitted with one that is no longer stored
+        assert!(job_authorization(&params, None).is_err());
+    }
+
+    #[test]
+    fn test_parse_storage_detail() {
+        let params = kickoff(vec![json!({
+            "name": "storageDetail",
+            "part": [
+                { "name": "type", "valueCode": "https" },
+                { "name": "authorization", "valueString": "Bearer abc" }
+            ]
+        })]);
+        assert_eq!(
+            ImportOperation::parse_storage_detail(&params).unwrap(),
+            StorageDetail {
+                authorization: Some("Bearer abc".to_string())
+            }
+        );
+
+        let simplified = json!({ "storageDetail": { "type": "https" } });
+        assert_eq!(
+            ImportOperation::parse_storage_detail(&simplified).unwrap(),
+            StorageDetail::default()
+        );
+
+        let s3 = kickoff(vec![json!({
+            "name": "storageDetail",
+            "part": [{ "name": "type", "valueCode": "aws-s3" }]
+        })]);
+        assert!(matches!(
+            ImportOperation::parse_storage_detail(&s3),
+            Err(OperationError::NotSupported(_))
+        ));
+    }
+
+    #[test]
+    fn test_named_param_if_not_exists() {
+        let params = kickoff(vec![json!({ "name": "ifNotExists", "valueBoolean": true })]);
+        assert_eq!(
+            ImportOperation::named_param(&params, "ifNotExists").and_then(|v| v.as_bool()),
+            Some(true)
+        );
+        let simplified = json!({ "ifNotExists": true });
+        assert_eq!(
+            ImportOperation::named_param(&simplified, "ifNotExists").and_then(|v| v.as_bool()),
+            Some(true)
+        );
+    }
+
+    #[test]
+    fn test_check_input_format() {
+        let ndjson = kickoff(vec![
+            json!({ "name": "inputFormat", "valueCode": "application/fhir+ndjson" }),
+        ]);
+        assert!(ImportOperation::check_input_format(&ndjson).is_ok());
+
+        let csv = kickoff(vec![
+            json!({ "name": "inputFormat", "valueCode": "text/csv" }),
+        ]);
+        assert!(ImportOperation::check_input_format(&csv).is_err());
+    }
+
+    #[test]
+    fn test_conflict_filter() {
+        let table = r#""public"."patient""#;
+        assert_eq!(conflict_filter(table, false), "");
+        assert_eq!(
+            conflict_filter(table, true),
+            r#"WHERE "public"."patient".status = 'deleted'"#
+        );
+    }
+
+    #[test]
+    fn test_import_manifest_counts() {
+        let mut progress = ImportProgress::new(&[
+            ImportInput {
+                resource_type: "Patient".to_string(),
+                url: "https://example.com/a.ndjson".to_string(),
+            },
+            ImportInput {
+                resource_type: "Observation".to_string(),
+                url: "https://example.com/b.ndjson".to_string(),
+            },
+        ]);
+        progress.inputs[0].status = InputStatus::Completed;
+        progress.inputs[0].created = 3;
+        progress.inputs[0].skipped = 1;
+        progress.inputs[1].status = InputStatus::Failed;
+        progress.inputs[1].errors = 1;
+
+        let manifest = import_manifest(&progress, None, "http://localhost/fhir/$import".into());
+        assert_eq!(manifest["total_created"], 3);
+        assert_eq!(manifest["total_skipped"], 1);
+        assert_eq!(manifest["total_errors"], 1);
+        assert_eq!(manifest["output"].as_array().unwrap().len(), 1);
+        assert_eq!(
+            manifest["error"][0]["inputUrl"],
+            "https://example.com/b.ndjson"
+        );
+        assert_eq!(manifest["inputs"][1]["status"], "failed");
+    }
+}
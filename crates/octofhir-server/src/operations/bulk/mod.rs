@@ -7,7 +7,8 @@
 //!
 //! - `GET /$export` - System-level export (all resources)
 //! - `GET /Patient/$export` - Patient-level export (patient compartment data)
-//! - `GET /Group/{id}/$export` - Group-level export (group member data)
+//! - `GET /Group/{id}/$export` - Group-level export (Patient compartments of
+//!   the group's members, enumerated or selected by `member-filter` searches)
 //!
 //! ## Parameters
 //!
@@ -15,6 +16,8 @@
 //! - `_since` - Only resources updated since this timestamp
 //! - `_type` - Comma-separated list of resource types to include
 //! - `_typeFilter` - FHIR search queries per resource type
+//! - `compression` - `gzip` or `none`; overrides `bulk_export.gzip` (server
+//!   extension, files are named `.ndjson.gz`)
 //!
 //! ## Response Flow
 //!
@@ -22,7 +25,8 @@
 //! 2. Server returns `202 Accepted` with `Content-Location` header pointing to status URL
 //! 3. Client polls status endpoint (`GET /fhir/_async-status/{job-id}`)
 //! 4. When complete, status returns manifest with file URLs
-//! 5. Client downloads NDJSON files from manifest URLs
+//! 5. Client downloads NDJSON files from manifest URLs (gzip files are served
+//!    with `Content-Encoding: gzip`)
 //!
 //! ## References
 //!
@@ -30,6 +34,7 @@
 //! - [FHIR Asynchronous Request Pattern](http://hl7.org/fhir/async.html)
 
 mod export;
+mod group;
 mod import;
 mod status;
 mod writer;
@@ -78,6 +78,10 @@ pub struct BulkExportParams {
     /// For group-level exports, the group ID
     #[serde(skip_serializing_if = "Option::is_none")]
     pub group_id: Option<String>,
+
+    /// Gzip-compress the output files; `None` uses the server default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gzip: Option<bool>,
 }
 
 impl Default for BulkExportParams {
@@ -88,6 +92,7 @@ impl Default for BulkExportParams {
             resource_types: None,
             type_filter: None,
             group_id: None,
+            gzip: None,
         }
     }
 }
@@ -1,10 +1,18 @@
 //! NDJSON file writer for bulk export
 //!
 //! Provides streaming NDJSON file writing with automatic file splitting
-//! when resource limits are reached.
+//! when resource limits are reached, optionally gzip-compressed
+//! (`.ndjson.gz`).
+//!
+//! [`NdjsonWriter::checkpoint`] closes the open files and reports every
+//! complete file, so a resumed job can [`NdjsonWriter::restore`] them and
+//! carry on in new files.
 
+use std::io::Write;
 use std::path::{Path, PathBuf};
 
+use flate2::Compression;
+use flate2::write::GzEncoder;
 use serde_json::Value;
 use thiserror::Error;
 use tokio::fs::{self, File};
@@ -35,6 +43,9 @@ pub struct NdjsonWriter {
     /// Maximum resources per file before splitting
     max_resources_per_file: usize,
 
+    /// Whether files are gzip-compressed
+    gzip: bool,
+
     /// Current file writers by resource type
     writers: std::collections::HashMap<String, TypeWriter>,
 }
@@ -44,67 +55,116 @@ struct TypeWriter {
     /// Resource type name
     resource_type: String,
 
-    /// Current file index (for splitting)
-    file_index: usize,
-
     /// Resources written to current file
     current_count: usize,
 
     /// Total resources written across all files
     total_count: usize,
 
-    /// Current buffered file writer
-    writer: Option<BufWriter<File>>,
+    /// Current file writer
+    writer: Option<FileSink>,
 
-    /// List of generated file paths
-    files: Vec<PathBuf>,
+    /// Generated file paths with the resources written to each
+    files: Vec<(PathBuf, usize)>,
 
     /// Maximum resources per file
     max_per_file: usize,
 
     /// Job directory path
     job_dir: PathBuf,
+
+    /// Whether files are gzip-compressed
+    gzip: bool,
+}
+
+/// Compressed bytes buffered before they are written to the file
+const GZIP_FLUSH_THRESHOLD: usize = 64 * 1024;
+
+/// Destination of one NDJSON file
+enum FileSink {
+    Plain(BufWriter<File>),
+    /// Lines are compressed into an in-memory buffer that is drained to the
+    /// file once it exceeds [`GZIP_FLUSH_THRESHOLD`].
+    Gzip {
+        file: BufWriter<File>,
+        encoder: GzEncoder<Vec<u8>>,
+    },
+}
+
+impl FileSink {
+    fn new(file: File, gzip: bool) -> Self {
+        let file = BufWriter::new(file);
+        if gzip {
+            Self::Gzip {
+                file,
+                encoder: GzEncoder::new(Vec::new(), Compression::default()),
+            }
+        } else {
+            Self::Plain(file)
+        }
+    }
+
+    async fn write_line(&mut self, line: &[u8]) -> std::io::Result<()> {
+        match self {
+            Self::Plain(file) => file.write_all(line).await,
+            Self::Gzip { file, encoder } => {
+                encoder.write_all(line)?;
+                if encoder.get_ref().len() >= GZIP_FLUSH_THRESHOLD {
+                    let compressed = std::mem::take(encoder.get_mut());
+                    file.write_all(&compressed).await?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    async fn finish(self) -> std::io::Result<()> {
+        match self {
+            Self::Plain(mut file) => file.flush().await,
+            Self::Gzip { mut file, encoder } => {
+                file.write_all(&encoder.finish()?).await?;
+                file.flush().await
+            }
+        }
+    }
 }
 
 impl TypeWriter {
-    fn new(resource_type: &str, job_dir: PathBuf, max_per_file: usize) -> Self {
+    fn new(resource_type: &str, job_dir: PathBuf, max_per_file: usize, gzip: bool) -> Self {
         Self {
             resource_type: resource_type.to_string(),
-            file_index: 0,
             current_count: 0,
             total_count: 0,
             writer: None,
             files: Vec::new(),
             max_per_file,
             job_dir,
+            gzip,
         }
     }
 
     /// Get or create a writer for the current file
-    async fn get_writer(&mut self) -> Result<&mut BufWriter<File>, NdjsonWriterError> {
+    async fn get_writer(&mut self) -> Result<&mut FileSink, NdjsonWriterError> {
         if self.writer.is_none() || self.current_count >= self.max_per_file {
             // Close current writer if exists
-            if let Some(mut w) = self.writer.take() {
-                w.flush().await?;
-            }
-
-            // Increment file index if we're splitting
-            if self.current_count >= self.max_per_file {
-                self.file_index += 1;
-                self.current_count = 0;
+            if let Some(w) = self.writer.take() {
+                w.finish().await?;
             }
 
-            // Create new file
-            let filename = if self.file_index == 0 {
-                format!("{}.ndjson", self.resource_type)
+            // Create new file, numbered after the ones before it
+            let file_index = self.files.len();
+            let extension = if self.gzip { "ndjson.gz" } else { "ndjson" };
+            let filename = if file_index == 0 {
+                format!("{}.{}", self.resource_type, extension)
             } else {
-                format!("{}.{}.ndjson", self.resource_type, self.file_index)
+                format!("{}.{}.{}", self.resource_type, file_index, extension)
             };
 
             let file_path = self.job_dir.join(&filename);
             let file = File::create(&file_path).await?;
-            self.files.push(file_path);
-            self.writer = Some(BufWriter::new(file));
+            self.files.push((file_path, 0));
+            self.current_count = 0;
+            self.writer = Some(FileSink::new(file, self.gzip));
         }
 
         Ok(self.writer.as_mut().unwrap())
@@ -117,30 +177,28 @@ impl TypeWriter {
         // Serialize to JSON and write as a single line
         let mut line = serde_json::to_vec(resource)?;
         line.push(b'\n');
-        writer.write_all(&line).await?;
+        writer.write_line(&line).await?;
 
         self.current_count += 1;
         self.total_count += 1;
+        if let Some((_, count)) = self.files.last_mut() {
+            *count += 1;
+        }
 
         Ok(())
     }
 
     /// Flush and close the writer
     async fn finish(&mut self) -> Result<(), NdjsonWriterError> {
-        if let Some(mut w) = self.writer.take() {
-            w.flush().await?;
+        if let Some(w) = self.writer.take() {
+            w.finish().await?;
         }
         Ok(())
     }
 
     /// Get list of generated files with their resource counts
     fn get_files(&self) -> Vec<(PathBuf, usize)> {
-        // For simplicity, we report total count for each file
-        // In production, we'd track per-file counts
-        self.files
-            .iter()
-            .map(|p| (p.clone(), self.total_count))
-            .collect()
+        self.files.clone()
     }
 }
 
@@ -161,10 +219,18 @@ impl NdjsonWriter {
             base_path,
             job_id,
             max_resources_per_file,
+            gzip: false,
             writers: std::collections::HashMap::new(),
         })
     }
 
+    /// Gzip-compress the files written from now on (`.ndjson.gz`)
+    #[must_use]
+    pub fn with_gzip(mut self, gzip: bool) -> Self {
+        self.gzip = gzip;
+        self
+    }
+
     /// Get the job directory path
     pub fn job_dir(&self) -> PathBuf {
         self.base_path.join(self.job_id.to_string())
@@ -179,11 +245,12 @@ impl NdjsonWriter {
         // Extract values before borrowing self.writers to avoid borrow conflict
         let job_dir = self.job_dir();
         let max_per_file = self.max_resources_per_file;
+        let gzip = self.gzip;
 
         let writer = self
             .writers
             .entry(resource_type.to_string())
-            .or_insert_with(|| TypeWriter::new(resource_type, job_dir, max_per_file));
+            .or_insert_with(|| TypeWriter::new(resource_type, job_dir, max_per_file, gzip));
 
         writer.write_resource(resource).await
     }
@@ -221,6 +288,40 @@ impl NdjsonWriter {
         Ok(result)
     }
 
+    /// Close the open files and return every file written so far, by
+    /// resource type. Resources written afterwards go to new files.
+    pub async fn checkpoint(
+        &mut self,
+    ) -> Result<std::collections::HashMap<String, Vec<(PathBuf, usize)>>, NdjsonWriterError> {
+        let mut result = std::collections::HashMap::new();
+        for (resource_type, writer) in &mut self.writers {
+            writer.finish().await?;
+            let files = writer.get_files();
+            if !files.is_empty() {
+                result.insert(resource_type.clone(), files);
+            }
+        }
+        Ok(result)
+    }
+
+    /// Take over files a previous run reported from [`Self::checkpoint`].
+    ///
+    /// New resources of a restored type go to files numbered after them;
+    /// files the previous run wrote after its checkpoint are overwritten.
+    pub fn restore(&mut self, files: std::collections::HashMap<String, Vec<(PathBuf, usize)>>) {
+        for (resource_type, files) in files {
+            let mut writer = TypeWriter::new(
+                &resource_type,
+                self.job_dir(),
+                self.max_resources_per_file,
+                self.gzip,
+            );
+            writer.total_count = files.iter().map(|(_, count)| count).sum();
+            writer.files = files;
+            self.writers.insert(resource_type, writer);
+        }
+    }
+
     /// Get current statistics
     pub fn stats(&self) -> NdjsonWriterStats {
         let mut total_resources = 0;
@@ -338,6 +439,71 @@ mod tests {
         let files = writer.finish().await.unwrap();
         // With max 2 per file and 5 resources, we should have 3 files
         assert_eq!(files["Patient"].len(), 3);
+        let counts: Vec<usize> = files["Patient"].iter().map(|(_, count)| *count).collect();
+        assert_eq!(counts, [2, 2, 1]);
+    }
+
+    #[tokio::test]
+    async fn test_checkpoint_and_restore() {
+        let dir = tempdir().unwrap();
+        let job_id = Uuid::new_v4();
+        let patient =
+            |id: usize| serde_json::json!({"resourceType": "Patient", "id": id.to_string()});
+
+        let mut writer = NdjsonWriter::new(dir.path(), job_id, 1000).await.unwrap();
+        writer.write_resource("Patient", &patient(1)).await.unwrap();
+        let checkpoint = writer.checkpoint().await.unwrap();
+        // Written after the checkpoint, then lost with the process
+        writer.write_resource("Patient", &patient(2)).await.unwrap();
+        drop(writer);
+
+        let mut resumed = NdjsonWriter::new(dir.path(), job_id, 1000).await.unwrap();
+        resumed.restore(checkpoint);
+        resumed
+            .write_resource("Patient", &patient(3))
+            .await
+            .unwrap();
+        let files = resumed.finish().await.unwrap();
+
+        let names: Vec<String> = files["Patient"]
+            .iter()
+            .map(|(path, _)| path.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(names, ["Patient.ndjson", "Patient.1.ndjson"]);
+        let first = std::fs::read_to_string(&files["Patient"][0].0).unwrap();
+        let second = std::fs::read_to_string(&files["Patient"][1].0).unwrap();
+        assert_eq!(first.lines().count(), 1);
+        assert!(second.contains("\"3\"") && !second.contains("\"2\""));
+    }
+
+    #[tokio::test]
+    async fn test_gzip_output() {
+        use std::io::Read;
+
+        let dir = tempdir().unwrap();
+        let job_id = Uuid::new_v4();
+        let mut writer = NdjsonWriter::new(dir.path(), job_id, 1000)
+            .await
+            .unwrap()
+            .with_gzip(true);
+
+        for i in 0..3 {
+            let resource = serde_json::json!({"resourceType": "Patient", "id": i.to_string()});
+            writer.write_resource("Patient", &resource).await.unwrap();
+        }
+
+        let files = writer.finish().await.unwrap();
+        let (path, _) = &files["Patient"][0];
+        assert!(path.to_string_lossy().ends_with("Patient.ndjson.gz"));
+
+        let compressed = std::fs::read(path).unwrap();
+        let mut ndjson = String::new();
+        flate2::read::GzDecoder::new(compressed.as_slice())
+            .read_to_string(&mut ndjson)
+            .unwrap();
+        assert_eq!(ndjson.lines().count(), 3);
+        let first: Value = serde_json::from_str(ndjson.lines().next().unwrap()).unwrap();
+        assert_eq!(first["id"], "0");
     }
 
     #[tokio::test]
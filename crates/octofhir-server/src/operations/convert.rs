@@ -0,0 +1,368 @@
+//! $convert Operation Handler
+//!
+//! Implements the FHIR `$convert` operation, which returns a resource in
+//! another format and, optionally, another FHIR version.
+//!
+//! # Parameters
+//!
+//! - **input** (or a resource posted as the body): the resource to convert
+//! - **format**: the format to return, a MIME type such as
+//!   `application/fhir+json`. A `fhirVersion` MIME parameter selects the
+//!   target version. Only JSON is supported.
+//! - **source**: the FHIR version of the input, defaults to the server's
+//! - **target**: the FHIR version to convert to, defaults to the source
+//!
+//! Versions are given as `R4`, `4.0`, `4.0.1`, `R4B`, `4.3` or `4.3.0`.
+//!
+//! # Version Conversion
+//!
+//! R4 and R4B share all but a few resource types and their shared resources
+//! are structurally compatible, so conversion between them is a built-in
+//! mapping that keeps shared resources as they are. Resources whose type does
+//! not exist in the target version, or was redesigned in R4B, cannot be
+//! converted; they are reported in an OperationOutcome with the location of
+//! each one, including resources contained in the input, in Bundle entries
+//! and in Parameters.
+//!
+//! # Example
+//!
+//! ```text
+//! POST /fhir/$convert
+//! {
+//!   "resourceType": "Parameters",
+//!   "parameter": [
+//!     { "name": "input", "resource": { "resourceType": "Patient", ... } },
+//!     { "name": "source", "valueCode": "4.0.1" },
+//!     { "name": "target", "valueCode": "4.3.0" }
+//!   ]
+//! }
+//! ```
+
+use async_trait::async_trait;
+use serde_json::{Value, json};
+
+use super::{OperationError, OperationHandler};
+use crate::config::fhir_release;
+use crate::server::AppState;
+
+/// R4 resource types that R4B removed.
+const R4_ONLY_TYPES: &[&str] = &[
+    "EffectEvidenceSynthesis",
+    "MedicinalProduct",
+    "MedicinalProductAuthorization",
+    "MedicinalProductContraindication",
+    "MedicinalProductIndication",
+    "MedicinalProductIngredient",
+    "MedicinalProductInteraction",
+    "MedicinalProductManufactured",
+    "MedicinalProductPackaged",
+    "MedicinalProductPharmaceutical",
+    "MedicinalProductUndesirableEffect",
+    "RiskEvidenceSynthesis",
+    "SubstanceNucleicAcid",
+    "SubstancePolymer",
+    "SubstanceProtein",
+    "SubstanceReferenceInformation",
+    "SubstanceSourceMaterial",
+    "SubstanceSpecification",
+];
+
+/// Resource types that R4B added.
+const R4B_ONLY_TYPES: &[&str] = &[
+    "AdministrableProductDefinition",
+    "Citation",
+    "ClinicalUseDefinition",
+    "EvidenceReport",
+    "Ingredient",
+    "ManufacturedItemDefinition",
+    "MedicinalProductDefinition",
+    "NutritionProduct",
+    "PackagedProductDefinition",
+    "RegulatedAuthorization",
+    "SubscriptionStatus",
+    "SubscriptionTopic",
+    "SubstanceDefinition",
+];
+
+/// Resource types in both versions whose structure R4B redesigned.
+const REDESIGNED_TYPES: &[&str] = &["Evidence", "EvidenceVariable"];
+
+/// Handler for the `$convert` operation.
+pub struct ConvertOperation;
+
+impl ConvertOperation {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for ConvertOperation {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl OperationHandler for ConvertOperation {
+    fn code(&self) -> &str {
+        "convert"
+    }
+
+    async fn handle_system(
+        &self,
+        state: &AppState,
+        params: &Value,
+    ) -> Result<Value, OperationError> {
+        let input = parameter(params, "input")
+            .or_else(|| parameter(params, "resource"))
+            .and_then(|p| p.get("resource"))
+            .filter(|r| r.get("resourceType").and_then(|t| t.as_str()).is_some())
+            .ok_or_else(|| {
+                OperationError::InvalidParameters("Missing required parameter: input".to_string())
+            })?;
+
+        let mut target_version = None;
+        if let Some(format) = string_parameter(params, "format") {
+            check_format(&format)?;
+            target_version = octofhir_api::fhir_version_param(&format)
+                .map(|v| parse_version("format", v))
+                .transpose()?;
+        }
+        let source = match string_parameter(params, "source") {
+            Some(v) => parse_version("source", &v)?,
+            None => state.config.fhir.release(),
+        };
+        let target = match string_parameter(params, "target") {
+            Some(v) => parse_version("target", &v)?,
+            None => target_version.unwrap_or(source),
+        };
+
+        let output = convert(input, source, target)?;
+        Ok(json!({
+            "resourceType": "Parameters",
+            "parameter": [{ "name": "output", "resource": output }]
+        }))
+    }
+}
+
+fn parameter<'a>(params: &'a Value, name: &str) -> Option<&'a Value> {
+    params
+        .get("parameter")?
+        .as_array()?
+        .iter()
+        .find(|p| p.get("name").and_then(|n| n.as_str()) == Some(name))
+}
+
+/// A primitive parameter value, whatever its `value[x]` type.
+fn string_parameter(params: &Value, name: &str) -> Option<String> {
+    let param = parameter(params, name)?.as_object()?;
+    param
+        .iter()
+        .find(|(key, _)| key.starts_with("value"))
+        .and_then(|(_, value)| value.as_str())
+        .map(str::to_string)
+}
+
+/// Rejects formats other than JSON.
+fn check_format(format: &str) -> Result<(), OperationError> {
+    let mime = format.split(';').next().unwrap_or_default().trim();
+    match mime.to_ascii_lowercase().as_str() {
+        "json" | "application/json" | "application/fhir+json" => Ok(()),
+        "xml" | "application/xml" | "application/fhir+xml" | "text/xml" => Err(
+            OperationError::NotSupported("Conversion to XML is not supported".to_string()),
+        ),
+        _ => Err(OperationError::InvalidParameters(format!(
+            "Unsupported format '{format}'"
+        ))),
+    }
+}
+
+/// Full release number of a version parameter.
+fn parse_version(name: &str, value: &str) -> Result<&'static str, OperationError> {
+    fhir_release(value).ok_or_else(|| {
+        OperationError::InvalidParameters(format!("Unknown FHIR version '{value}' in {name}"))
+    })
+}
+
+/// Converts `resource` from the `source` to the `target` release.
+fn convert(resource: &Value, source: &str, target: &str) -> Result<Value, OperationError> {
+    if source == target {
+        return Ok(resource.clone());
+    }
+    let unavailable: &[&str] = match (source, target) {
+        ("4.0.1", "4.3.0") => R4_ONLY_TYPES,
+        ("4.3.0", "4.0.1") => R4B_ONLY_TYPES,
+        _ => {
+            return Err(OperationError::NotSupported(format!(
+                "Conversion from FHIR {source} to {target} is not supported"
+            )));
+        }
+    };
+
+    let mut issues = Vec::new();
+    check_resource(resource, None, unavailable, target, &mut issues);
+    if issues.is_empty() {
+        Ok(resource.clone())
+    } else {
+        Err(OperationError::ValidationFailed(json!({
+            "resourceType": "OperationOutcome",
+            "issue": issues
+        })))
+    }
+}
+
+/// Reports `resource` and the resources nested in it that have no
+/// counterpart in `target`.
+fn check_resource(
+    resource: &Value,
+    location: Option<String>,
+    unavailable: &[&str],
+    target: &str,
+    issues: &mut Vec<Value>,
+) {
+    let Some(resource_type) = resource.get("resourceType").and_then(|t| t.as_str()) else {
+        return;
+    };
+    let location = location.unwrap_or_else(|| resource_type.to_string());
+
+    let problem = if unavailable.contains(&resource_type) {
+        Some("does not exist")
+    } else if REDESIGNED_TYPES.contains(&resource_type) {
+        Some("has an incompatible structure")
+    } else {
+        None
+    };
+    if let Some(problem) = problem {
+        issues.push(json!({
+            "severity": "error",
+            "code": "not-supported",
+            "diagnostics": format!(
+                "Resource type {resource_type} {problem} in FHIR {target}"
+            ),
+            "expression": [location]
+        }));
+    }
+
+    // Contained resources, Bundle.entry.resource and Parameters.parameter.resource
+    for field in ["contained", "entry", "parameter"] {
+        let Some(items) = resource.get(field).and_then(|v| v.as_array()) else {
+            continue;
+        };
+        for (index, item) in items.iter().enumerate() {
+            let (nested, path) = if field == "contained" {
+                (Some(item), format!("{location}.contained[{index}]"))
+            } else {
+                (
+                    item.get("resource"),
+                    format!("{location}.{field}[{index}].resource"),
+                )
+            };
+            if let Some(nested) = nested {
+                check_resource(nested, Some(path), unavailable, target, issues);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn outcome_issues(result: Result<Value, OperationError>) -> Vec<Value> {
+        match result {
+            Err(OperationError::ValidationFailed(outcome)) => {
+                outcome["issue"].as_array().cloned().unwrap_or_default()
+            }
+            other => panic!("expected an OperationOutcome, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_convert_shared_resource() {
+        let patient = json!({"resourceType": "Patient", "id": "1", "active": true});
+        assert_eq!(convert(&patient, "4.0.1", "4.3.0").unwrap(), patient);
+        assert_eq!(convert(&patient, "4.3.0", "4.0.1").unwrap(), patient);
+        assert_eq!(convert(&patient, "5.0.0", "5.0.0").unwrap(), patient);
+        assert!(matches!(
+            convert(&patient, "4.0.1", "5.0.0"),
+            Err(OperationError::NotSupported(_))
+        ));
+    }
+
+    #[test]
+    fn test_convert_reports_untranslatable_resources() {
+        let issues = outcome_issues(convert(
+            &json!({"resourceType": "MedicinalProduct"}),
+            "4.0.1",
+            "4.3.0",
+        ));
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0]["code"], "not-supported");
+        assert_eq!(issues[0]["expression"], json!(["MedicinalProduct"]));
+
+        // R4B-only types going back to R4, and redesigned types either way
+        assert_eq!(
+            outcome_issues(convert(
+                &json!({"resourceType": "SubscriptionTopic"}),
+                "4.3.0",
+                "4.0.1"
+            ))
+            .len(),
+            1
+        );
+        assert!(
+            outcome_issues(convert(
+                &json!({"resourceType": "Evidence"}),
+                "4.0.1",
+                "4.3.0"
+            ))[0]["diagnostics"]
+                .as_str()
+                .unwrap()
+                .contains("incompatible structure")
+        );
+    }
+
+    #[test]
+    fn test_convert_checks_nested_resources() {
+        let bundle = json!({
+            "resourceType": "Bundle",
+            "type": "collection",
+            "entry": [
+                {"resource": {"resourceType": "Patient"}},
+                {"resource": {
+                    "resourceType": "Observation",
+                    "contained": [{"resourceType": "Citation"}]
+                }}
+            ]
+        });
+        assert!(convert(&bundle, "4.0.1", "4.3.0").is_ok());
+
+        let issues = outcome_issues(convert(&bundle, "4.3.0", "4.0.1"));
+        assert_eq!(
+            issues[0]["expression"],
+            json!(["Bundle.entry[1].resource.contained[0]"])
+        );
+    }
+
+    #[test]
+    fn test_parameters() {
+        let params = json!({
+            "resourceType": "Parameters",
+            "parameter": [
+                {"name": "source", "valueCode": "R4"},
+                {"name": "format", "valueString": "application/fhir+json; fhirVersion=4.3"}
+            ]
+        });
+        assert_eq!(string_parameter(&params, "source").as_deref(), Some("R4"));
+        assert_eq!(parse_version("source", "R4").unwrap(), "4.0.1");
+        assert_eq!(parse_version("target", "4.3").unwrap(), "4.3.0");
+        assert!(parse_version("target", "3.0.2").is_err());
+
+        assert!(check_format("application/fhir+json; fhirVersion=4.3").is_ok());
+        assert!(matches!(
+            check_format("application/fhir+xml"),
+            Err(OperationError::NotSupported(_))
+        ));
+        assert!(check_format("text/turtle").is_err());
+    }
+}
@@ -166,12 +166,13 @@ impl CqlOperation {
         }
 
         if context_type.is_none()
-            && let Some(cv) = &context_value {
-                context_type = cv
-                    .get("resourceType")
-                    .and_then(|v| v.as_str())
-                    .map(|s| s.to_string());
-            }
+            && let Some(cv) = &context_value
+        {
+            context_type = cv
+                .get("resourceType")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string());
+        }
 
         (context_type, context_value)
     }
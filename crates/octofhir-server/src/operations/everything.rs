@@ -9,7 +9,9 @@
 //! - **Group**: Returns resources for all members of the group (bulk export use case)
 //!
 //! # Query Parameters
-//! - `_count`: Limit number of results (pagination)
+//! - `_count`: Page size (defaults to `search.default_count`, capped at `search.max_count`)
+//! - `_offset` / `_cursor`: Page to return, as written into the Bundle's page links
+//!   (stable across requests, as each type is searched in `search.default_sort` order)
 //! - `_since`: Only resources updated since this date
 //! - `_type`: Filter to specific resource types (comma-separated)
 //! - `_elements`: Select specific elements to include
@@ -23,7 +25,8 @@ use time::format_description::well_known::Rfc3339;
 
 use super::handler::{OperationError, OperationHandler};
 use crate::server::AppState;
-use octofhir_api::bundle_from_search;
+use octofhir_api::{Page, bundle_from_search};
+use octofhir_core::paging::{CURSOR_PARAM, PageCursor};
 use octofhir_storage::{SearchParams, StoredResource};
 
 /// Handler for the $everything operation.
@@ -47,6 +50,22 @@ impl EverythingOperation {
         patient_id: &str,
         params: &EverythingParams,
     ) -> Result<Value, OperationError> {
+        let resources = self.patient_record(state, patient_id, params).await?;
+        self.page_bundle(
+            state,
+            resources,
+            &format!("Patient/{}/$everything", patient_id),
+            params,
+        )
+    }
+
+    /// The patient and every resource in its compartment, deduplicated.
+    async fn patient_record(
+        &self,
+        state: &AppState,
+        patient_id: &str,
+        params: &EverythingParams,
+    ) -> Result<Vec<StoredResource>, OperationError> {
         // 1. Verify patient exists and fetch it
         let patient = state
             .storage
@@ -97,6 +116,12 @@ impl EverythingOperation {
                 search_params = search_params.with_param("date", format!("le{}", end_str));
             }
 
+            state
+                .search_config
+                .config()
+                .default_sort
+                .apply(&resource_type, &mut search_params);
+
             match state.storage.search(&resource_type, &search_params).await {
                 Ok(result) => {
                     resources.extend(result.entries);
@@ -113,33 +138,7 @@ impl EverythingOperation {
         }
 
         // 3. Deduplicate resources by id
-        let deduplicated = self.deduplicate_resources(resources);
-
-        // 4. Apply pagination if _count is specified
-        let (paginated, total) = if let Some(count) = params.count {
-            let offset = params.offset.unwrap_or(0);
-            let total = deduplicated.len();
-            let end = std::cmp::min(offset + count, total);
-            (deduplicated[offset..end].to_vec(), total)
-        } else {
-            let total = deduplicated.len();
-            (deduplicated, total)
-        };
-
-        // 5. Build searchset Bundle
-        let resources_json: Vec<Value> = paginated.iter().map(|r| r.resource.clone()).collect();
-        let bundle = bundle_from_search(
-            total,
-            resources_json,
-            &state.base_url,
-            &format!("Patient/{}/$everything", patient_id),
-            params.offset.unwrap_or(0),
-            params.count.unwrap_or(50),
-            None, // No query suffix for operation
-        );
-
-        serde_json::to_value(bundle)
-            .map_err(|e| OperationError::Internal(format!("Failed to serialize bundle: {}", e)))
+        Ok(self.deduplicate_resources(resources))
     }
 
     /// Encounter $everything: Retrieve all resources related to an encounter
@@ -183,6 +182,12 @@ impl EverythingOperation {
                     search_params.with_param("_lastUpdated", format!("ge{}", since_str));
             }
 
+            state
+                .search_config
+                .config()
+                .default_sort
+                .apply(&resource_type, &mut search_params);
+
             match state.storage.search(&resource_type, &search_params).await {
                 Ok(result) => {
                     resources.extend(result.entries);
@@ -199,30 +204,12 @@ impl EverythingOperation {
         }
 
         let deduplicated = self.deduplicate_resources(resources);
-
-        let (paginated, total) = if let Some(count) = params.count {
-            let offset = params.offset.unwrap_or(0);
-            let total = deduplicated.len();
-            let end = std::cmp::min(offset + count, total);
-            (deduplicated[offset..end].to_vec(), total)
-        } else {
-            let total = deduplicated.len();
-            (deduplicated, total)
-        };
-
-        let resources_json: Vec<Value> = paginated.iter().map(|r| r.resource.clone()).collect();
-        let bundle = bundle_from_search(
-            total,
-            resources_json,
-            &state.base_url,
+        self.page_bundle(
+            state,
+            deduplicated,
             &format!("Encounter/{}/$everything", encounter_id),
-            params.offset.unwrap_or(0),
-            params.count.unwrap_or(50),
-            None,
-        );
-
-        serde_json::to_value(bundle)
-            .map_err(|e| OperationError::Internal(format!("Failed to serialize bundle: {}", e)))
+            params,
+        )
     }
 
     /// Group $everything: Retrieve resources for all group members
@@ -245,59 +232,21 @@ impl EverythingOperation {
 
         if members.is_empty() {
             // Return just the group resource if no members
-            let resources_json: Vec<Value> = vec![group.resource.clone()];
-            let bundle = bundle_from_search(
-                1,
-                resources_json,
-                &state.base_url,
+            return self.page_bundle(
+                state,
+                vec![group],
                 &format!("Group/{}/$everything", group_id),
-                0,
-                50,
-                None,
+                params,
             );
-
-            return serde_json::to_value(bundle).map_err(|e| {
-                OperationError::Internal(format!("Failed to serialize bundle: {}", e))
-            });
         }
 
         // 3. For each member, run Patient $everything
         let mut all_resources = vec![group];
 
         for member_id in members {
-            match self.patient_everything(state, &member_id, params).await {
-                Ok(bundle_value) => {
-                    // Extract resources from the bundle
-                    if let Some(entries) = bundle_value.get("entry").and_then(|e| e.as_array()) {
-                        for entry in entries {
-                            if let Some(resource) = entry.get("resource") {
-                                // Create a StoredResource from the bundle entry
-                                let stored = StoredResource {
-                                    id: resource
-                                        .get("id")
-                                        .and_then(|v| v.as_str())
-                                        .unwrap_or("")
-                                        .to_string(),
-                                    version_id: resource
-                                        .get("meta")
-                                        .and_then(|m| m.get("versionId"))
-                                        .and_then(|v| v.as_str())
-                                        .unwrap_or("1")
-                                        .to_string(),
-                                    resource_type: resource
-                                        .get("resourceType")
-                                        .and_then(|v| v.as_str())
-                                        .unwrap_or("")
-                                        .to_string(),
-                                    resource: resource.clone(),
-                                    last_updated: time::OffsetDateTime::now_utc(),
-                                    created_at: time::OffsetDateTime::now_utc(),
-                                };
-                                all_resources.push(stored);
-                            }
-                        }
-                    }
-                }
+            // Page the group as a whole, not each member's record
+            match self.patient_record(state, &member_id, params).await {
+                Ok(record) => all_resources.extend(record),
                 Err(e) => {
                     tracing::warn!(
                         "Failed to fetch $everything for patient {}: {}",
@@ -310,27 +259,42 @@ impl EverythingOperation {
 
         // 4. Deduplicate resources
         let deduplicated = self.deduplicate_resources(all_resources);
+        self.page_bundle(
+            state,
+            deduplicated,
+            &format!("Group/{}/$everything", group_id),
+            params,
+        )
+    }
 
-        // 5. Apply pagination
-        let (paginated, total) = if let Some(count) = params.count {
-            let offset = params.offset.unwrap_or(0);
-            let total = deduplicated.len();
-            let end = std::cmp::min(offset + count, total);
-            (deduplicated[offset..end].to_vec(), total)
-        } else {
-            let total = deduplicated.len();
-            (deduplicated, total)
-        };
-
-        let resources_json: Vec<Value> = paginated.iter().map(|r| r.resource.clone()).collect();
+    /// Builds the searchset Bundle for one page of `resources`. The page size
+    /// defaults to `search.default_count` and is capped at `search.max_count`,
+    /// as for searches.
+    fn page_bundle(
+        &self,
+        state: &AppState,
+        resources: Vec<StoredResource>,
+        path: &str,
+        params: &EverythingParams,
+    ) -> Result<Value, OperationError> {
+        let cfg = state.search_config.config();
+        let count = params.count.unwrap_or(cfg.default_count).min(cfg.max_count);
+        let offset = params.offset.unwrap_or(0);
+        let total = resources.len();
+
+        let resources_json: Vec<Value> = resources
+            .into_iter()
+            .skip(offset)
+            .take(count)
+            .map(|r| r.resource)
+            .collect();
         let bundle = bundle_from_search(
             total,
             resources_json,
             &state.base_url,
-            &format!("Group/{}/$everything", group_id),
-            params.offset.unwrap_or(0),
-            params.count.unwrap_or(50),
-            None,
+            path,
+            Page::new(offset, count).with_link_style(cfg.link_style),
+            None, // No query suffix for operation
         );
 
         serde_json::to_value(bundle)
@@ -656,14 +620,25 @@ impl EverythingParams {
                 .or_else(|| count.as_str().and_then(|s| s.parse().ok()));
         }
 
-        // Parse __offset parameter (internal pagination)
-        if let Some(offset) = params.get("__offset") {
+        // Parse _offset parameter (`__offset` is the legacy internal spelling)
+        if let Some(offset) = params.get("_offset").or_else(|| params.get("__offset")) {
             result.offset = offset
                 .as_u64()
                 .map(|n| n as usize)
                 .or_else(|| offset.as_str().and_then(|s| s.parse().ok()));
         }
 
+        // Parse _cursor parameter, which replaces _count and _offset
+        if let Some(token) = params.get(CURSOR_PARAM).and_then(|v| v.as_str()) {
+            let cursor = PageCursor::decode(token).ok_or_else(|| {
+                OperationError::InvalidParameters(format!(
+                    "_cursor '{token}' is not a page cursor from this server"
+                ))
+            })?;
+            result.offset = Some(cursor.offset as usize);
+            result.count = Some(cursor.count as usize);
+        }
+
         // Parse _since parameter
         if let Some(since) = params.get("_since").and_then(|v| v.as_str()) {
             result.since = OffsetDateTime::parse(since, &Rfc3339).ok().or_else(|| {
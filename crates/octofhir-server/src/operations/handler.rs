@@ -104,10 +104,9 @@ impl From<OperationError> for octofhir_api::ApiError {
             OperationError::NotFound(msg) => octofhir_api::ApiError::not_found(msg),
             OperationError::NotSupported(msg) => octofhir_api::ApiError::bad_request(msg),
             OperationError::Internal(msg) => octofhir_api::ApiError::internal(msg),
-            OperationError::ValidationFailed(_outcome) => {
-                // ValidationFailed contains an OperationOutcome - convert to BadRequest
-                // The outcome will be returned as the error message
-                octofhir_api::ApiError::bad_request("Validation failed")
+            OperationError::ValidationFailed(outcome) => {
+                // ValidationFailed contains an OperationOutcome - return it as is
+                octofhir_api::ApiError::unprocessable_entity("Validation failed", Some(outcome))
             }
         }
     }
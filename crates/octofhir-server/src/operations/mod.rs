@@ -42,6 +42,7 @@
 
 pub mod auth_session;
 pub mod bulk;
+pub mod convert;
 pub mod cql;
 pub mod db_console_api;
 pub mod definition;
@@ -55,10 +56,14 @@ pub mod meta;
 pub mod notifications;
 pub mod params;
 pub mod registry;
+pub mod reindex;
 pub mod router;
+pub mod search_params;
 pub mod sof;
 pub mod sql;
+pub mod structure_map;
 pub mod terminology;
+pub mod transform;
 pub mod validate;
 
 // Re-export main types for convenience
@@ -66,6 +71,7 @@ pub use bulk::{
     BulkExportJob, BulkExportLevel, BulkExportManifest, BulkExportStatus, ExportOperation,
     ImportOperation, cleanup_expired_exports, execute_bulk_export, execute_bulk_import,
 };
+pub use convert::ConvertOperation;
 pub use cql::CqlOperation;
 pub use definition::{OperationDefinition, OperationKind, OperationParameter, ParameterUse};
 pub use evaluate_measure::EvaluateMeasureOperation;
@@ -77,11 +83,13 @@ pub use loader::{LoadError, load_operations};
 pub use meta::{MetaAddOperation, MetaDeleteOperation, MetaOperation};
 pub use params::OperationParams;
 pub use registry::OperationRegistry;
+pub use reindex::{ReindexOperation, ReindexStatusOperation, execute_reindex};
 pub use router::{
     compartment_post_handler, instance_operation_handler, instance_operation_or_history_handler,
     is_operation, merged_root_get_handler, merged_root_post_handler, merged_type_get_handler,
     merged_type_post_handler, system_operation_handler, type_operation_handler,
 };
+pub use search_params::SearchParamsOperation;
 pub use sof::{
     ViewDefinitionRunOperation, ViewDefinitionSqlOperation, execute_viewdefinition_export,
 };
@@ -89,6 +97,7 @@ pub use terminology::{
     ClosureOperation, ExpandOperation, LookupOperation, SubsumesOperation, TranslateOperation,
     ValidateCodeOperation,
 };
+pub use transform::TransformOperation;
 pub use validate::{Issue, Severity, ValidateOperation};
 
 use std::collections::HashMap;
@@ -108,6 +117,10 @@ use octofhir_fhirpath::FhirPathEngine;
 /// - `$export` - Bulk data export (system, patient, group, ViewDefinition level)
 /// - `$run` - Execute ViewDefinition synchronously (SQL on FHIR)
 /// - `$sql` - Generate SQL from ViewDefinition (SQL on FHIR)
+/// - `$search-params` - Search parameter introspection
+/// - `$reindex` - Asynchronous search index rebuild
+/// - `$reindex-status` - Progress of a reindex job
+/// - `$cancel` - Cancel a notification or an async job
 ///
 /// # Arguments
 ///
@@ -256,6 +269,12 @@ pub fn register_core_operations_all(
     // $fhirpath operation
     handlers.insert("fhirpath".to_string(), Arc::new(FhirPathOperation::new()));
 
+    // $convert operation
+    handlers.insert("convert".to_string(), Arc::new(ConvertOperation::new()));
+
+    // StructureMap $transform operation
+    handlers.insert("transform".to_string(), Arc::new(TransformOperation::new()));
+
     // CQL operations ($cql, $evaluate-measure) - only if enabled
     if cql_enabled {
         tracing::info!("Registering CQL operations (cql_enabled=true)");
@@ -278,5 +297,15 @@ pub fn register_core_operations_all(
     // $graph operation
     handlers.insert("graph".to_string(), Arc::new(GraphOperation::new()));
 
+    // $search-params operation
+    handlers.insert("search-params".to_string(), Arc::new(SearchParamsOperation));
+
+    // $reindex operation
+    handlers.insert("reindex".to_string(), Arc::new(ReindexOperation));
+    handlers.insert(
+        "reindex-status".to_string(),
+        Arc::new(ReindexStatusOperation),
+    );
+
     handlers
 }
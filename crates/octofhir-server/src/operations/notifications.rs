@@ -4,7 +4,8 @@
 //! - `$resend` - Resend a failed notification
 //! - `$resend-all` - Resend all failed notifications
 //! - `$stats` - Get notification statistics
-//! - `$cancel` - Cancel a pending notification
+//! - `$cancel` - Cancel a pending notification, or at system level
+//!   (`$cancel?job=<id>`) a queued or running async job such as `$reindex`
 
 use async_trait::async_trait;
 use serde_json::{Value, json};
@@ -152,7 +153,7 @@ impl OperationHandler for StatsOperation {
     }
 }
 
-/// $cancel operation - Cancel a pending notification
+/// $cancel operation - Cancel a pending notification or an async job
 pub struct CancelOperation;
 
 #[async_trait]
@@ -161,6 +162,53 @@ impl OperationHandler for CancelOperation {
         "cancel"
     }
 
+    /// Requests cancellation of the async job named by the `job` parameter.
+    ///
+    /// Queued jobs never start; running jobs stop at their next checkpoint.
+    async fn handle_system(
+        &self,
+        state: &AppState,
+        params: &Value,
+    ) -> Result<Value, OperationError> {
+        let job_id = super::reindex::job_id_param(params)?;
+        let job = state
+            .async_job_manager
+            .get_job(job_id)
+            .await
+            .map_err(|e| match e {
+                crate::async_jobs::AsyncJobError::NotFound(_) => {
+                    OperationError::NotFound(format!("Async job {job_id} not found"))
+                }
+                _ => OperationError::Internal(e.to_string()),
+            })?;
+
+        if !matches!(
+            job.status,
+            crate::async_jobs::AsyncJobStatus::Queued
+                | crate::async_jobs::AsyncJobStatus::InProgress
+        ) {
+            return Err(OperationError::InvalidParameters(format!(
+                "Async job {job_id} is already {}",
+                job.status
+            )));
+        }
+
+        state
+            .async_job_manager
+            .cancel_job(job_id)
+            .await
+            .map_err(|e| OperationError::Internal(e.to_string()))?;
+
+        Ok(json!({
+            "resourceType": "OperationOutcome",
+            "issue": [{
+                "severity": "information",
+                "code": "informational",
+                "diagnostics": format!("Cancellation of async job {} requested", job_id)
+            }]
+        }))
+    }
+
     async fn handle_instance(
         &self,
         state: &AppState,
@@ -14,6 +14,8 @@ use axum::{
 };
 use serde_json::{Value, json};
 
+use super::definition::{OperationDefinition, OperationParameter, ParameterUse};
+
 /// Operation parameters extracted from an HTTP request.
 ///
 /// Parameters can come from either:
@@ -146,6 +148,172 @@ impl OperationParams {
                 .unwrap_or(true),
         }
     }
+
+    /// Checks the supplied parameters against the `in` parameters declared by
+    /// `definition` and returns one message per violation.
+    ///
+    /// Checks that required parameters are present, that no parameter occurs
+    /// more often than its `max`, that values can be read as the declared type
+    /// (query string values are coerced from text) and that no output-only
+    /// parameter is supplied. Nested `part`s are checked the same way.
+    /// Parameters the definition does not declare are left to the handler, as
+    /// is a POST body that is a single resource rather than `Parameters`.
+    pub fn validate(&self, definition: &OperationDefinition) -> Vec<String> {
+        let from_url = matches!(self, Self::Get(_));
+        let parameters = match self {
+            Self::Post(Value::Null) => Value::Null,
+            Self::Post(value)
+                if value.get("resourceType").and_then(|v| v.as_str()) != Some("Parameters") =>
+            {
+                return Vec::new();
+            }
+            _ => self.to_value(),
+        };
+        let entries = parameters
+            .get("parameter")
+            .and_then(|arr| arr.as_array())
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+
+        let mut violations = Vec::new();
+        check_parameters(
+            entries,
+            &definition.parameters,
+            "",
+            from_url,
+            &mut violations,
+        );
+        violations
+    }
+}
+
+fn entry_name(entry: &Value) -> &str {
+    entry.get("name").and_then(|n| n.as_str()).unwrap_or("")
+}
+
+fn check_parameters(
+    entries: &[Value],
+    declared: &[OperationParameter],
+    prefix: &str,
+    from_url: bool,
+    violations: &mut Vec<String>,
+) {
+    for param in declared.iter().filter(|p| p.use_ == ParameterUse::In) {
+        let name = format!("{prefix}{}", param.name);
+        let supplied: Vec<&Value> = entries
+            .iter()
+            .filter(|entry| entry_name(entry) == param.name)
+            .collect();
+
+        if supplied.is_empty() && param.min > 0 {
+            violations.push(format!("Missing required parameter '{name}'"));
+        } else if (supplied.len() as u32) < param.min {
+            violations.push(format!(
+                "Parameter '{name}' must occur at least {} times, got {}",
+                param.min,
+                supplied.len()
+            ));
+        }
+        if let Ok(max) = param.max.parse::<usize>()
+            && supplied.len() > max
+        {
+            violations.push(format!(
+                "Parameter '{name}' may occur at most {max} times, got {}",
+                supplied.len()
+            ));
+        }
+
+        for entry in supplied {
+            if let Some(parts) = entry.get("part").and_then(|p| p.as_array()) {
+                check_parameters(
+                    parts,
+                    &param.parts,
+                    &format!("{name}."),
+                    from_url,
+                    violations,
+                );
+            } else if let Some(fhir_type) = &param.param_type
+                && let Err(message) = check_type(entry, fhir_type, from_url)
+            {
+                violations.push(format!("Parameter '{name}': {message}"));
+            }
+        }
+    }
+
+    for entry in entries {
+        let name = entry_name(entry);
+        let declared_as = |use_| declared.iter().any(|p| p.name == name && p.use_ == use_);
+        if declared_as(ParameterUse::Out) && !declared_as(ParameterUse::In) {
+            violations.push(format!(
+                "Parameter '{prefix}{name}' is an output of this operation"
+            ));
+        }
+    }
+}
+
+/// Whether a Parameters entry holds a value of `fhir_type`.
+fn check_type(entry: &Value, fhir_type: &str, from_url: bool) -> Result<(), String> {
+    if matches!(fhir_type, "Any" | "Element" | "Type" | "DataType") {
+        return Ok(());
+    }
+    let value = entry.as_object().and_then(|obj| {
+        obj.iter()
+            .find(|(k, _)| k.starts_with("value"))
+            .map(|(_, v)| v)
+    });
+
+    // FHIR primitive types are the lower-case ones
+    if fhir_type.starts_with(|c: char| c.is_ascii_lowercase()) {
+        return match value {
+            Some(v) if primitive_coerces(v, fhir_type) => Ok(()),
+            Some(v) => Err(format!("{v} is not a valid {fhir_type}")),
+            None => Err(format!("expected a {fhir_type} value")),
+        };
+    }
+    // Complex values cannot be written in a query string; the handler decides
+    if from_url {
+        return Ok(());
+    }
+    if let Some(resource) = entry.get("resource") {
+        let actual = resource
+            .get("resourceType")
+            .and_then(|v| v.as_str())
+            .unwrap_or("");
+        return if matches!(fhir_type, "Resource" | "DomainResource") || actual == fhir_type {
+            Ok(())
+        } else {
+            Err(format!("expected a {fhir_type}, got a {actual} resource"))
+        };
+    }
+    if entry.get(format!("value{fhir_type}")).is_some() {
+        Ok(())
+    } else {
+        Err(format!("expected a {fhir_type} value"))
+    }
+}
+
+fn primitive_coerces(value: &Value, fhir_type: &str) -> bool {
+    match fhir_type {
+        "boolean" => value.is_boolean() || matches!(value.as_str(), Some("true" | "false")),
+        "integer" | "integer64" | "positiveInt" | "unsignedInt" => {
+            let number = value
+                .as_i64()
+                .or_else(|| value.as_str().and_then(|s| s.trim().parse().ok()));
+            match number {
+                Some(n) if fhir_type == "positiveInt" => n > 0,
+                Some(n) if fhir_type == "unsignedInt" => n >= 0,
+                Some(_) => true,
+                None => false,
+            }
+        }
+        "decimal" => {
+            value.is_number()
+                || value
+                    .as_str()
+                    .is_some_and(|s| s.trim().parse::<f64>().is_ok())
+        }
+        _ => value.is_string(),
+    }
 }
 
 /// Rejection type for operation parameter extraction failures.
@@ -321,6 +489,117 @@ mod tests {
         assert_eq!(codes[1], "b");
     }
 
+    fn parameter(name: &str, min: u32, max: &str, param_type: Option<&str>) -> OperationParameter {
+        OperationParameter {
+            name: name.to_string(),
+            use_: ParameterUse::In,
+            min,
+            max: max.to_string(),
+            param_type: param_type.map(String::from),
+            search_type: None,
+            target_profile: Vec::new(),
+            parts: Vec::new(),
+        }
+    }
+
+    fn definition(parameters: Vec<OperationParameter>) -> OperationDefinition {
+        OperationDefinition {
+            code: "test".to_string(),
+            url: String::new(),
+            kind: crate::operations::definition::OperationKind::Operation,
+            system: true,
+            type_level: false,
+            instance: false,
+            resource: Vec::new(),
+            parameters,
+            affects_state: false,
+        }
+    }
+
+    #[test]
+    fn test_validate_cardinality_and_use() {
+        let mut result = parameter("result", 0, "1", Some("boolean"));
+        result.use_ = ParameterUse::Out;
+        let def = definition(vec![
+            parameter("code", 1, "1", Some("code")),
+            parameter("system", 0, "1", Some("uri")),
+            result,
+        ]);
+
+        let params = OperationParams::Post(json!({
+            "resourceType": "Parameters",
+            "parameter": [
+                {"name": "system", "valueUri": "http://a"},
+                {"name": "system", "valueUri": "http://b"},
+                {"name": "result", "valueBoolean": true}
+            ]
+        }));
+        let violations = params.validate(&def);
+
+        assert_eq!(violations.len(), 3, "{violations:?}");
+        assert!(violations[0].contains("Missing required parameter 'code'"));
+        assert!(violations[1].contains("'system' may occur at most 1 times"));
+        assert!(violations[2].contains("'result' is an output"));
+    }
+
+    #[test]
+    fn test_validate_types() {
+        let def = definition(vec![
+            parameter("count", 0, "1", Some("positiveInt")),
+            parameter("flag", 0, "1", Some("boolean")),
+            parameter("coding", 0, "1", Some("Coding")),
+            parameter("patient", 0, "1", Some("Patient")),
+        ]);
+
+        let query = HashMap::from([
+            ("count".to_string(), "10".to_string()),
+            ("flag".to_string(), "yes".to_string()),
+            ("coding".to_string(), "http://loinc.org|1234-5".to_string()),
+        ]);
+        let violations = OperationParams::Get(query).validate(&def);
+        assert_eq!(violations.len(), 1, "{violations:?}");
+        assert!(violations[0].contains("'flag'"));
+
+        let params = OperationParams::Post(json!({
+            "resourceType": "Parameters",
+            "parameter": [
+                {"name": "count", "valueInteger": 0},
+                {"name": "coding", "valueCoding": {"code": "x"}},
+                {"name": "patient", "resource": {"resourceType": "Observation"}}
+            ]
+        }));
+        let violations = params.validate(&def);
+        assert_eq!(violations.len(), 2, "{violations:?}");
+        assert!(violations[0].contains("'count'"));
+        assert!(violations[1].contains("got a Observation resource"));
+    }
+
+    #[test]
+    fn test_validate_parts_and_resource_body() {
+        let mut group = parameter("group", 0, "*", None);
+        group.parts = vec![parameter("code", 1, "1", Some("code"))];
+        let def = definition(vec![group, parameter("mode", 1, "1", Some("code"))]);
+
+        let params = OperationParams::Post(json!({
+            "resourceType": "Parameters",
+            "parameter": [
+                {"name": "mode", "valueCode": "create"},
+                {"name": "group", "part": [{"name": "code", "valueCode": "a"}]},
+                {"name": "group", "part": []}
+            ]
+        }));
+        assert_eq!(
+            params.validate(&def),
+            vec!["Missing required parameter 'group.code'".to_string()]
+        );
+
+        // A bare resource body is the operation's single input
+        let body = OperationParams::Post(json!({"resourceType": "Patient"}));
+        assert!(body.validate(&def).is_empty());
+        // An empty body supplies nothing
+        assert_eq!(OperationParams::Post(Value::Null).validate(&def).len(), 1);
+    }
+
     #[test]
     fn test_is_empty() {
         let empty_get = OperationParams::Get(HashMap::new());
@@ -0,0 +1,447 @@
+//! $reindex operation handler.
+//!
+//! Rebuilds the functional search indexes configured in
+//! `search.indexed_params` and refills the precomputed index tables of
+//! `search.materialized_params` against the current SearchParameter
+//! definitions, e.g. after a SearchParameter expression changed. Runs as an async job, so
+//! the response is 202 Accepted and progress is polled via `_async-status`.
+//!
+//! - `POST /$reindex` - every configured resource type (optionally `type=A,B`)
+//! - `POST /{type}/$reindex` - a single resource type
+//!
+//! Indexes are built concurrently and swapped in one at a time, and index
+//! tables are refilled in batches, with `search.reindex_pause_ms` between
+//! statements so a large rebuild does not saturate the database.
+//!
+//! `GET /$reindex-status?job=<id>` reports processed and remaining counts;
+//! `POST /$cancel?job=<id>` stops the job before its next index.
+
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use serde::Serialize;
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+use super::{OperationError, OperationHandler, OperationParameter, ParameterUse};
+use crate::async_jobs::AsyncJobRequest;
+use crate::server::AppState;
+
+/// $reindex operation - Asynchronous search index rebuild
+pub struct ReindexOperation;
+
+impl ReindexOperation {
+    /// Submits a reindex job for `resource_types` and returns the status URL.
+    async fn submit_reindex(
+        &self,
+        state: &AppState,
+        resource_types: Vec<String>,
+    ) -> Result<Value, OperationError> {
+        let request_url = format!("{}/fhir/$reindex", state.base_url);
+        let async_request = AsyncJobRequest {
+            request_type: "reindex".to_string(),
+            method: "POST".to_string(),
+            url: request_url,
+            body: Some(json!({ "types": resource_types })),
+            headers: None,
+            client_id: None,
+            secret: None,
+        };
+
+        let job_id = state
+            .async_job_manager
+            .submit_job(async_request)
+            .await
+            .map_err(|e| {
+                OperationError::Internal(format!("Failed to submit reindex job: {}", e))
+            })?;
+
+        tracing::info!(job_id = %job_id, types = ?resource_types, "Reindex job submitted");
+
+        Ok(json!({
+            "status": "accepted",
+            "job_id": job_id.to_string(),
+            "status_url": format!("{}/fhir/_async-status/{}", state.base_url, job_id),
+        }))
+    }
+}
+
+#[async_trait]
+impl OperationHandler for ReindexOperation {
+    fn code(&self) -> &str {
+        "reindex"
+    }
+
+    async fn handle_system(
+        &self,
+        state: &AppState,
+        params: &Value,
+    ) -> Result<Value, OperationError> {
+        let configured = configured_params(state);
+        let resource_types = match requested_types(params) {
+            Some(requested) => {
+                if let Some(missing) = requested
+                    .iter()
+                    .find(|t| params_for_type(&configured, t).is_empty())
+                {
+                    return Err(OperationError::InvalidParameters(format!(
+                        "No indexed search parameters configured for '{missing}'"
+                    )));
+                }
+                requested
+            }
+            None => indexed_types(&configured),
+        };
+
+        self.submit_reindex(state, resource_types).await
+    }
+
+    async fn handle_type(
+        &self,
+        state: &AppState,
+        resource_type: &str,
+        _params: &Value,
+    ) -> Result<Value, OperationError> {
+        if params_for_type(&configured_params(state), resource_type).is_empty() {
+            return Err(OperationError::NotFound(format!(
+                "No indexed search parameters configured for {resource_type}"
+            )));
+        }
+
+        self.submit_reindex(state, vec![resource_type.to_string()])
+            .await
+    }
+}
+
+/// $reindex-status operation - Progress of a reindex job
+///
+/// `GET /$reindex-status?job=<id>` reports the job status with the number of
+/// index units processed, the total, and an estimate of the time remaining.
+pub struct ReindexStatusOperation;
+
+#[async_trait]
+impl OperationHandler for ReindexStatusOperation {
+    fn code(&self) -> &str {
+        "reindex-status"
+    }
+
+    async fn handle_system(
+        &self,
+        state: &AppState,
+        params: &Value,
+    ) -> Result<Value, OperationError> {
+        let job_id = job_id_param(params)?;
+        let job = state
+            .async_job_manager
+            .get_job(job_id)
+            .await
+            .ok()
+            .filter(|job| job.request_type == "reindex")
+            .ok_or_else(|| OperationError::NotFound(format!("Reindex job {job_id} not found")))?;
+
+        let result = job.result.unwrap_or_else(|| json!({}));
+        let mut status = json!({
+            "job": job_id.to_string(),
+            "status": job.status.to_string(),
+            "progress": job.progress,
+            "processed": result.get("processed").cloned().unwrap_or(json!(0)),
+            "total": result.get("total").cloned().unwrap_or(Value::Null),
+            "output": result.get("output").cloned().unwrap_or(json!([])),
+        });
+        if let Some(remaining) = result.get("estimatedRemainingSeconds") {
+            status["estimatedRemainingSeconds"] = remaining.clone();
+        }
+        if let Some(error) = job.error_message {
+            status["error"] = json!(error);
+        }
+        Ok(status)
+    }
+}
+
+/// One unit of reindex work. The job checks for cancellation between units,
+/// each of which leaves the database consistent on its own.
+enum ReindexStep {
+    /// Concurrent build and swap of one functional index.
+    Index(String),
+    /// Batched refill of one precomputed index table.
+    Table(String),
+}
+
+/// Per-type counts reported in the job output.
+#[derive(Serialize)]
+struct ReindexedType<'a> {
+    #[serde(rename = "type")]
+    resource_type: &'a str,
+    indexes: usize,
+    tables: usize,
+}
+
+/// Executes a reindex job submitted by [`ReindexOperation`].
+///
+/// Works through the configured indexes one at a time, publishing processed
+/// and estimated-remaining counts after each. A job cancelled via `$cancel`
+/// stops before the next index and keeps the output of the finished ones.
+pub async fn execute_reindex(
+    state: AppState,
+    job_id: Uuid,
+    params: Value,
+) -> Result<Value, String> {
+    let resource_types: Vec<String> = params
+        .get("types")
+        .and_then(|v| v.as_array())
+        .ok_or("Missing types array in job params")?
+        .iter()
+        .filter_map(|v| v.as_str().map(ToString::to_string))
+        .collect();
+
+    tracing::info!(job_id = %job_id, types = ?resource_types, "Starting reindex execution");
+
+    let search = &state.config.search;
+    let search_config = state.search_config.config();
+    let pause = Duration::from_millis(search.reindex_pause_ms);
+
+    let mut steps = Vec::new();
+    for (i, resource_type) in resource_types.iter().enumerate() {
+        let indexed = params_for_type(&search.indexed_params, resource_type);
+        let materialized = params_for_type(&search.materialized_params, resource_type);
+        steps.extend(indexed.into_iter().map(|p| (i, ReindexStep::Index(p))));
+        steps.extend(materialized.into_iter().map(|p| (i, ReindexStep::Table(p))));
+    }
+    let total = steps.len();
+
+    let mut output: Vec<ReindexedType> = resource_types
+        .iter()
+        .map(|t| ReindexedType {
+            resource_type: t,
+            indexes: 0,
+            tables: 0,
+        })
+        .collect();
+    let started = Instant::now();
+    let mut processed = 0;
+    let mut cancelled = false;
+
+    for (type_index, step) in steps {
+        match state.async_job_manager.is_cancelled(job_id).await {
+            Ok(true) => {
+                cancelled = true;
+                break;
+            }
+            Ok(false) => {}
+            Err(e) => {
+                tracing::warn!(job_id = %job_id, error = %e, "Failed to check reindex cancellation");
+            }
+        }
+
+        let counts = &mut output[type_index];
+        match step {
+            ReindexStep::Index(param) => {
+                counts.indexes += octofhir_db_postgres::rebuild_search_indexes(
+                    &state.db_pool,
+                    &search_config.registry,
+                    &[param],
+                    state.model_provider.as_ref(),
+                    pause,
+                )
+                .await;
+            }
+            ReindexStep::Table(param) => {
+                counts.tables += octofhir_db_postgres::backfill_search_index_tables(
+                    &state.db_pool,
+                    &search_config.registry,
+                    &[param],
+                    pause,
+                )
+                .await;
+            }
+        }
+        processed += 1;
+
+        let snapshot = json!({
+            "processed": processed,
+            "total": total,
+            "estimatedRemainingSeconds":
+                estimated_remaining(started.elapsed(), processed, total).as_secs(),
+            "output": output,
+        });
+        if let Err(e) = state
+            .async_job_manager
+            .update_progress_with_result(job_id, processed as f32 / total as f32, &snapshot)
+            .await
+        {
+            tracing::warn!(job_id = %job_id, error = %e, "Failed to update reindex progress");
+        }
+    }
+
+    if cancelled {
+        tracing::info!(job_id = %job_id, processed, total, "Reindex cancelled");
+    } else {
+        tracing::info!(job_id = %job_id, processed, "Rebuilt search indexes");
+    }
+
+    Ok(json!({
+        "processed": processed,
+        "total": total,
+        "cancelled": cancelled,
+        "output": output,
+    }))
+}
+
+/// Time left for the remaining units at the average pace so far.
+fn estimated_remaining(elapsed: Duration, processed: usize, total: usize) -> Duration {
+    if processed == 0 {
+        return Duration::ZERO;
+    }
+    let remaining = total.saturating_sub(processed) as u32;
+    elapsed / processed as u32 * remaining
+}
+
+/// The `job` input shared by `$reindex-status` and system-level `$cancel`.
+pub(crate) fn job_parameter() -> OperationParameter {
+    OperationParameter {
+        name: "job".to_string(),
+        use_: ParameterUse::In,
+        min: 1,
+        max: "1".to_string(),
+        param_type: Some("string".to_string()),
+        search_type: None,
+        target_profile: vec![],
+        parts: vec![],
+    }
+}
+
+/// Reads the required `job` parameter as an async job id.
+pub(crate) fn job_id_param(params: &Value) -> Result<Uuid, OperationError> {
+    let value = params["parameter"]
+        .as_array()
+        .and_then(|parameters| {
+            parameters
+                .iter()
+                .find(|p| p["name"].as_str() == Some("job"))
+        })
+        .and_then(|p| {
+            p["valueString"]
+                .as_str()
+                .or(p["valueId"].as_str())
+                .or(p["valueUuid"].as_str())
+        })
+        .ok_or_else(|| OperationError::InvalidParameters("Missing 'job' parameter".to_string()))?;
+
+    Uuid::parse_str(value.trim_start_matches("urn:uuid:"))
+        .map_err(|_| OperationError::InvalidParameters(format!("Invalid job id '{value}'")))
+}
+
+/// Reads the optional `type` parameter (repeated or comma-separated).
+fn requested_types(params: &Value) -> Option<Vec<String>> {
+    let types: Vec<String> = params["parameter"]
+        .as_array()?
+        .iter()
+        .filter(|p| p["name"].as_str() == Some("type"))
+        .filter_map(|p| p["valueString"].as_str().or(p["valueCode"].as_str()))
+        .flat_map(|v| v.split(','))
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(ToString::to_string)
+        .collect();
+    (!types.is_empty()).then_some(types)
+}
+
+/// Every `"Type.code"` entry `$reindex` maintains: functional indexes and
+/// precomputed index tables.
+fn configured_params(state: &AppState) -> Vec<String> {
+    let search = &state.config.search;
+    search
+        .indexed_params
+        .iter()
+        .chain(&search.materialized_params)
+        .cloned()
+        .collect()
+}
+
+/// The configured `"Type.code"` entries for one resource type.
+fn params_for_type(indexed: &[String], resource_type: &str) -> Vec<String> {
+    indexed
+        .iter()
+        .filter(|p| p.split_once('.').is_some_and(|(t, _)| t == resource_type))
+        .cloned()
+        .collect()
+}
+
+/// Distinct resource types named in `indexed`, sorted.
+fn indexed_types(indexed: &[String]) -> Vec<String> {
+    let mut types: Vec<String> = indexed
+        .iter()
+        .filter_map(|p| p.split_once('.').map(|(t, _)| t.to_string()))
+        .collect();
+    types.sort();
+    types.dedup();
+    types
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn indexed() -> Vec<String> {
+        [
+            "Patient.birthdate",
+            "Observation.date",
+            "Patient.name",
+            "Observation.code",
+        ]
+        .into_iter()
+        .map(ToString::to_string)
+        .collect()
+    }
+
+    #[test]
+    fn test_params_for_type() {
+        assert_eq!(
+            params_for_type(&indexed(), "Patient"),
+            vec!["Patient.birthdate".to_string(), "Patient.name".to_string()]
+        );
+        assert!(params_for_type(&indexed(), "Encounter").is_empty());
+    }
+
+    #[test]
+    fn test_indexed_types() {
+        assert_eq!(
+            indexed_types(&indexed()),
+            vec!["Observation".to_string(), "Patient".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_estimated_remaining() {
+        let elapsed = Duration::from_secs(30);
+        assert_eq!(estimated_remaining(elapsed, 3, 9), Duration::from_secs(60));
+        assert_eq!(estimated_remaining(elapsed, 9, 9), Duration::ZERO);
+        assert_eq!(estimated_remaining(elapsed, 0, 9), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_job_id_param() {
+        let job = Uuid::new_v4();
+        let params = json!({
+            "resourceType": "Parameters",
+            "parameter": [{"name": "job", "valueString": job.to_string()}]
+        });
+        assert_eq!(job_id_param(&params).unwrap(), job);
+        assert!(job_id_param(&json!({})).is_err());
+    }
+
+    #[test]
+    fn test_requested_types() {
+        let params = json!({
+            "resourceType": "Parameters",
+            "parameter": [
+                {"name": "type", "valueCode": "Patient,Observation"}
+            ]
+        });
+        assert_eq!(
+            requested_types(&params),
+            Some(vec!["Patient".to_string(), "Observation".to_string()])
+        );
+        assert_eq!(requested_types(&json!({})), None);
+    }
+}
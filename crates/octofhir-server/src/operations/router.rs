@@ -16,16 +16,73 @@
 use axum::{
     Json,
     extract::{Path, Query, RawQuery, State},
-    http::{HeaderMap, StatusCode},
+    http::{HeaderMap, HeaderValue, StatusCode, header},
     response::{IntoResponse, Response},
 };
+use serde_json::Value;
 use std::collections::HashMap;
 
+use super::definition::OperationDefinition;
 use super::params::OperationParams;
 use crate::handlers;
 use crate::server::AppState;
 use octofhir_api::ApiError;
 
+/// Converts an operation handler result into an HTTP response.
+///
+/// Handlers that start an async job (`$export`, `$import`) return
+/// `{"status": "accepted", "status_url": ...}`; those become `202 Accepted`
+/// with `Content-Location` pointing at the status endpoint, per the FHIR
+/// asynchronous request pattern. Everything else is `200 OK`.
+fn operation_response(result: Value) -> Response {
+    if result.get("status").and_then(|v| v.as_str()) == Some("accepted")
+        && let Some(status_url) = result.get("status_url").and_then(|v| v.as_str())
+    {
+        let mut headers = HeaderMap::new();
+        if let Ok(value) = HeaderValue::from_str(status_url) {
+            headers.insert(header::CONTENT_LOCATION, value);
+        }
+        return (StatusCode::ACCEPTED, headers, Json(result)).into_response();
+    }
+    (StatusCode::OK, Json(result)).into_response()
+}
+
+/// Rejects operations turned off by the `[operations]` allow/deny lists.
+///
+/// Runs before the registry lookup, so a disabled operation is
+/// indistinguishable from an unknown one.
+fn ensure_enabled(state: &AppState, code: &str) -> Result<(), ApiError> {
+    if state.config.operations.is_enabled(code) {
+        Ok(())
+    } else {
+        Err(ApiError::not_found(format!(
+            "Operation ${code} is not available on this server"
+        )))
+    }
+}
+
+/// Rejects parameters that do not match the operation's definition (see
+/// [`OperationParams::validate`]) unless `operations.validate_parameters` is off.
+fn ensure_valid_params(
+    state: &AppState,
+    definition: &OperationDefinition,
+    params: &OperationParams,
+) -> Result<(), ApiError> {
+    if !state.config.operations.validate_parameters {
+        return Ok(());
+    }
+    let violations = params.validate(definition);
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(ApiError::bad_request(format!(
+            "Invalid parameters for ${}: {}",
+            definition.code,
+            violations.join("; ")
+        )))
+    }
+}
+
 /// Checks if a path segment represents an operation (starts with `$`).
 #[inline]
 pub fn is_operation(segment: &str) -> bool {
@@ -42,16 +99,18 @@ pub async fn system_operation_handler(
 ) -> Result<impl IntoResponse, ApiError> {
     // Strip the $ prefix if present
     let code = operation.trim_start_matches('$');
+    ensure_enabled(&state, code)?;
 
     // Check if the operation is defined at system level
     let op_def = state.fhir_operations.get_system_operation(code);
 
-    if op_def.is_none() {
+    let Some(op_def) = op_def else {
         return Err(ApiError::not_found(format!(
             "Operation ${} not found at system level",
             code
         )));
-    }
+    };
+    ensure_valid_params(&state, &op_def, &params)?;
 
     // Look for a handler implementation
     let handler = state.operation_handlers.get(code);
@@ -64,7 +123,7 @@ pub async fn system_operation_handler(
                 .await
                 .map_err(ApiError::from)?;
 
-            Ok((StatusCode::OK, Json(result)))
+            Ok(operation_response(result))
         }
         None => Err(ApiError::not_implemented(format!(
             "Operation ${} is not implemented",
@@ -82,18 +141,20 @@ pub async fn type_operation_handler(
     params: OperationParams,
 ) -> Result<impl IntoResponse, ApiError> {
     let code = operation.trim_start_matches('$');
+    ensure_enabled(&state, code)?;
 
     // Check if the operation is defined at type level for this resource type
     let op_def = state
         .fhir_operations
         .get_type_operation(&resource_type, code);
 
-    if op_def.is_none() {
+    let Some(op_def) = op_def else {
         return Err(ApiError::not_found(format!(
             "Operation ${} not found for type {}",
             code, resource_type
         )));
-    }
+    };
+    ensure_valid_params(&state, &op_def, &params)?;
 
     // Look for a handler implementation
     let handler = state.operation_handlers.get(code);
@@ -106,7 +167,7 @@ pub async fn type_operation_handler(
                 .await
                 .map_err(ApiError::from)?;
 
-            Ok((StatusCode::OK, Json(result)))
+            Ok(operation_response(result))
         }
         None => Err(ApiError::not_implemented(format!(
             "Operation ${} is not implemented",
@@ -129,38 +190,45 @@ pub async fn type_operation_handler(
 /// both `/{a}/{b}/_history` and `/{a}/{b}/{c}` as separate routes.
 pub async fn instance_operation_or_history_handler(
     state: State<AppState>,
+    base_url: crate::forwarded::BaseUrl,
     Path((resource_type, id, operation)): Path<(String, String, String)>,
     Query(query_params): Query<HashMap<String, String>>,
     query: Query<crate::handlers::HistoryQueryParams>,
 ) -> Response {
     if operation == "_history" {
         let path = Path((resource_type, id));
-        match crate::handlers::instance_history(state, path, query).await {
+        match crate::handlers::instance_history(state, base_url, path, query).await {
             Ok(resp) => resp.into_response(),
             Err(e) => e.into_response(),
         }
     } else if is_operation(&operation) {
         let app_state = state.0;
         let code = operation.trim_start_matches('$');
+        if let Err(e) = ensure_enabled(&app_state, code) {
+            return e.into_response();
+        }
         let op_def = app_state
             .fhir_operations
             .get_instance_operation(&resource_type, code);
-        if op_def.is_none() {
+        let Some(op_def) = op_def else {
             return ApiError::not_found(format!(
                 "Operation ${code} not found for {resource_type}/{id}"
             ))
             .into_response();
+        };
+        let params = OperationParams::Get(query_params);
+        if let Err(e) = ensure_valid_params(&app_state, &op_def, &params) {
+            return e.into_response();
         }
         let handler = app_state.operation_handlers.get(code);
         match handler {
             Some(h) => {
-                let params = OperationParams::Get(query_params);
                 let params_value = params.to_value();
                 match h
                     .handle_instance(&app_state, &resource_type, &id, &params_value)
                     .await
                 {
-                    Ok(result) => (StatusCode::OK, Json(result)).into_response(),
+                    Ok(result) => operation_response(result),
                     Err(e) => ApiError::from(e).into_response(),
                 }
             }
@@ -189,18 +257,20 @@ pub async fn instance_operation_handler(
     }
 
     let code = operation.trim_start_matches('$');
+    ensure_enabled(&state, code)?;
 
     // Check if the operation is defined at instance level for this resource type
     let op_def = state
         .fhir_operations
         .get_instance_operation(&resource_type, code);
 
-    if op_def.is_none() {
+    let Some(op_def) = op_def else {
         return Err(ApiError::not_found(format!(
             "Operation ${} not found for {}/{}",
             code, resource_type, id
         )));
-    }
+    };
+    ensure_valid_params(&state, &op_def, &params)?;
 
     // Look for a handler implementation
     let handler = state.operation_handlers.get(code);
@@ -213,7 +283,7 @@ pub async fn instance_operation_handler(
                 .await
                 .map_err(ApiError::from)?;
 
-            Ok((StatusCode::OK, Json(result)))
+            Ok(operation_response(result))
         }
         None => Err(ApiError::not_implemented(format!(
             "Operation ${} is not implemented",
@@ -233,6 +303,7 @@ pub async fn instance_operation_handler(
 /// This allows a single route to handle both `/$meta` and `/Patient`.
 pub async fn merged_root_get_handler(
     state: State<AppState>,
+    base_url: crate::forwarded::BaseUrl,
     headers: HeaderMap,
     Path(param): Path<String>,
     Query(query_params): Query<HashMap<String, String>>,
@@ -247,6 +318,7 @@ pub async fn merged_root_get_handler(
         // Dispatch to resource search handler
         let result = handlers::search_resource(
             state,
+            base_url,
             headers,
             Path(param),
             Query(query_params),
@@ -262,6 +334,7 @@ pub async fn merged_root_get_handler(
 /// - Resource create handler otherwise
 pub async fn merged_root_post_handler(
     state: State<AppState>,
+    base_url: crate::forwarded::BaseUrl,
     Path(param): Path<String>,
     headers: HeaderMap,
     body: axum::body::Bytes,
@@ -282,7 +355,8 @@ pub async fn merged_root_post_handler(
         let json_value: serde_json::Value = serde_json::from_slice(&body)
             .map_err(|e| ApiError::bad_request(format!("Invalid JSON: {}", e)))?;
         let result =
-            handlers::create_resource(state, Path(param), headers, Json(json_value)).await?;
+            handlers::create_resource(state, base_url, Path(param), headers, Json(json_value))
+                .await?;
         Ok(result.into_response())
     }
 }
@@ -390,14 +464,16 @@ async fn system_operation_handler_internal(
     params: OperationParams,
 ) -> Result<impl IntoResponse, ApiError> {
     let code = operation.trim_start_matches('$');
+    ensure_enabled(&state, code)?;
 
     let op_def = state.fhir_operations.get_system_operation(code);
-    if op_def.is_none() {
+    let Some(op_def) = op_def else {
         return Err(ApiError::not_found(format!(
             "Operation ${} not found at system level",
             code
         )));
-    }
+    };
+    ensure_valid_params(&state, &op_def, &params)?;
 
     let handler = state.operation_handlers.get(code);
     match handler {
@@ -407,7 +483,7 @@ async fn system_operation_handler_internal(
                 .handle_system(&state, &params_value)
                 .await
                 .map_err(ApiError::from)?;
-            Ok((StatusCode::OK, Json(result)))
+            Ok(operation_response(result))
         }
         None => Err(ApiError::not_implemented(format!(
             "Operation ${} is not implemented",
@@ -425,15 +501,17 @@ async fn instance_operation_handler_internal(
     params: OperationParams,
 ) -> Result<Response, ApiError> {
     let code = operation.trim_start_matches('$');
+    ensure_enabled(state, code)?;
 
     let op_def = state
         .fhir_operations
         .get_instance_operation(resource_type, code);
-    if op_def.is_none() {
+    let Some(op_def) = op_def else {
         return Err(ApiError::not_found(format!(
             "Operation ${code} not found for {resource_type}/{id}"
         )));
-    }
+    };
+    ensure_valid_params(state, &op_def, &params)?;
 
     let handler = state.operation_handlers.get(code);
     match handler {
@@ -443,7 +521,7 @@ async fn instance_operation_handler_internal(
                 .handle_instance(state, resource_type, id, &params_value)
                 .await
                 .map_err(ApiError::from)?;
-            Ok((StatusCode::OK, Json(result)).into_response())
+            Ok(operation_response(result))
         }
         None => Err(ApiError::not_implemented(format!(
             "Operation ${code} is not implemented"
@@ -459,16 +537,18 @@ async fn type_operation_handler_internal(
     params: OperationParams,
 ) -> Result<impl IntoResponse, ApiError> {
     let code = operation.trim_start_matches('$');
+    ensure_enabled(&state, code)?;
 
     let op_def = state
         .fhir_operations
         .get_type_operation(&resource_type, code);
-    if op_def.is_none() {
+    let Some(op_def) = op_def else {
         return Err(ApiError::not_found(format!(
             "Operation ${} not found for type {}",
             code, resource_type
         )));
-    }
+    };
+    ensure_valid_params(&state, &op_def, &params)?;
 
     let handler = state.operation_handlers.get(code);
     match handler {
@@ -478,7 +558,7 @@ async fn type_operation_handler_internal(
                 .handle_type(&state, &resource_type, &params_value)
                 .await
                 .map_err(ApiError::from)?;
-            Ok((StatusCode::OK, Json(result)))
+            Ok(operation_response(result))
         }
         None => Err(ApiError::not_implemented(format!(
             "Operation ${} is not implemented",
@@ -0,0 +1,201 @@
+//! $search-params operation handler.
+//!
+//! Exposes the search parameters supported for each resource type, with
+//! their type, modifiers, comparators and reference targets. This is the same
+//! data the REST console introspection endpoint assembles, as a FHIR
+//! `Parameters` resource so client tooling can discover it programmatically.
+//!
+//! - `GET /$search-params` - all resource types (optionally `?type=A,B`)
+//! - `GET /{type}/$search-params` - a single resource type
+
+use async_trait::async_trait;
+use serde_json::{Value, json};
+
+use super::{OperationError, OperationHandler};
+use crate::rest_console::{SearchParamSuggestion, search_param_suggestions};
+use crate::server::AppState;
+
+/// $search-params operation - Search parameter introspection
+pub struct SearchParamsOperation;
+
+#[async_trait]
+impl OperationHandler for SearchParamsOperation {
+    fn code(&self) -> &str {
+        "search-params"
+    }
+
+    async fn handle_system(
+        &self,
+        state: &AppState,
+        params: &Value,
+    ) -> Result<Value, OperationError> {
+        let config = state.search_config.config();
+        let registry = &config.registry;
+        let known = registry.list_resource_types();
+
+        let mut resource_types = match requested_types(params) {
+            Some(requested) => {
+                if let Some(unknown) = requested.iter().find(|t| !known.contains(t)) {
+                    return Err(OperationError::InvalidParameters(format!(
+                        "Unknown resource type '{unknown}'"
+                    )));
+                }
+                requested
+            }
+            None => known,
+        };
+        resource_types.sort();
+        resource_types.dedup();
+
+        let parameter: Vec<Value> = resource_types
+            .iter()
+            .map(|rt| {
+                resource_parameter(
+                    rt,
+                    &search_param_suggestions(registry, rt, &state.fhir_version),
+                )
+            })
+            .collect();
+
+        Ok(json!({
+            "resourceType": "Parameters",
+            "parameter": parameter
+        }))
+    }
+
+    async fn handle_type(
+        &self,
+        state: &AppState,
+        resource_type: &str,
+        _params: &Value,
+    ) -> Result<Value, OperationError> {
+        let config = state.search_config.config();
+        let registry = &config.registry;
+        if !registry
+            .list_resource_types()
+            .iter()
+            .any(|t| t == resource_type)
+        {
+            return Err(OperationError::NotFound(format!(
+                "No search parameters registered for {resource_type}"
+            )));
+        }
+
+        let suggestions = search_param_suggestions(registry, resource_type, &state.fhir_version);
+        Ok(json!({
+            "resourceType": "Parameters",
+            "parameter": [resource_parameter(resource_type, &suggestions)]
+        }))
+    }
+}
+
+/// Reads the optional `type` parameter (repeated or comma-separated).
+fn requested_types(params: &Value) -> Option<Vec<String>> {
+    let types: Vec<String> = params["parameter"]
+        .as_array()?
+        .iter()
+        .filter(|p| p["name"].as_str() == Some("type"))
+        .filter_map(|p| p["valueString"].as_str().or(p["valueCode"].as_str()))
+        .flat_map(|v| v.split(','))
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(ToString::to_string)
+        .collect();
+    (!types.is_empty()).then_some(types)
+}
+
+/// Builds the `resource` part for one resource type.
+fn resource_parameter(resource_type: &str, suggestions: &[SearchParamSuggestion]) -> Value {
+    let mut part = vec![json!({"name": "type", "valueCode": resource_type})];
+    part.extend(suggestions.iter().map(search_param_part));
+    json!({
+        "name": "resource",
+        "part": part
+    })
+}
+
+/// Builds the `searchParam` part for one search parameter.
+fn search_param_part(param: &SearchParamSuggestion) -> Value {
+    let mut part = vec![
+        json!({"name": "code", "valueString": param.code}),
+        json!({"name": "type", "valueCode": param.search_type}),
+    ];
+    if let Some(description) = &param.description {
+        part.push(json!({"name": "description", "valueString": description}));
+    }
+    part.extend(
+        param
+            .modifiers
+            .iter()
+            .map(|m| json!({"name": "modifier", "valueCode": m.code})),
+    );
+    part.extend(
+        param
+            .comparators
+            .iter()
+            .map(|c| json!({"name": "comparator", "valueCode": c})),
+    );
+    part.extend(
+        param
+            .targets
+            .iter()
+            .map(|t| json!({"name": "target", "valueCode": t})),
+    );
+    json!({
+        "name": "searchParam",
+        "part": part
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rest_console::ModifierSuggestion;
+
+    #[test]
+    fn test_requested_types() {
+        let params = json!({
+            "resourceType": "Parameters",
+            "parameter": [
+                {"name": "type", "valueString": "Patient, Observation"},
+                {"name": "type", "valueString": "Encounter"}
+            ]
+        });
+        assert_eq!(
+            requested_types(&params),
+            Some(vec![
+                "Patient".to_string(),
+                "Observation".to_string(),
+                "Encounter".to_string()
+            ])
+        );
+        assert_eq!(
+            requested_types(&json!({"resourceType": "Parameters", "parameter": []})),
+            None
+        );
+    }
+
+    #[test]
+    fn test_search_param_part() {
+        let param = SearchParamSuggestion {
+            code: "subject".to_string(),
+            search_type: "reference".to_string(),
+            description: None,
+            modifiers: vec![ModifierSuggestion {
+                code: "missing".to_string(),
+                description: None,
+            }],
+            comparators: vec![],
+            targets: vec!["Patient".to_string(), "Group".to_string()],
+            is_common: false,
+        };
+
+        let part = search_param_part(&param);
+        assert_eq!(part["name"], "searchParam");
+        let parts = part["part"].as_array().unwrap();
+        assert_eq!(parts[0]["valueString"], "subject");
+        assert_eq!(parts[1]["valueCode"], "reference");
+        assert_eq!(parts[2]["name"], "modifier");
+        assert_eq!(parts.iter().filter(|p| p["name"] == "target").count(), 2);
+    }
+}
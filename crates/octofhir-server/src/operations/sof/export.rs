@@ -127,6 +127,7 @@ impl ViewDefinitionExportOperation {
             body: Some(job_params),
             headers: None,
             client_id: None,
+            secret: None,
         };
 
         let job_id = state
@@ -145,7 +146,7 @@ impl ViewDefinitionExportOperation {
         Ok(json!({
             "status": "accepted",
             "job_id": job_id.to_string(),
-            "status_url": format!("{}/_async-status/{}", state.base_url, job_id),
+            "status_url": format!("{}/fhir/_async-status/{}", state.base_url, job_id),
         }))
     }
 }
@@ -0,0 +1,871 @@
+//! StructureMap execution.
+//!
+//! Runs a StructureMap resource (the compiled form of a FHIR Mapping
+//! Language map) against a source resource and builds the target resource.
+//!
+//! # Supported Constructs
+//!
+//! - Groups with source and target inputs, `extends`, and calls to other
+//!   groups of the map through rule dependents
+//! - Rules with one or more sources (`element`, `type`, `variable`,
+//!   `listMode`, `min`/`max`, `defaultValue[x]`), targets and nested rules
+//! - The transforms `create`, `copy`, `truncate`, `append`, `cast` (to
+//!   string), `uuid`, `reference`, `c`, `cc` and `id`
+//!
+//! FHIRPath conditions, checks and the `evaluate`, `translate` and date
+//! transforms are not supported, nor are groups imported from other maps.
+//! Rules using them fail with an error; errors are collected per rule and
+//! reported together.
+//!
+//! # Cardinality
+//!
+//! Whether a target element is written as a JSON array is taken from the
+//! target's type definition through [`TargetModel`]. Elements the model does
+//! not know are written as arrays when the source element they come from
+//! repeats.
+
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use futures::future::BoxFuture;
+use octofhir_fhir_model::provider::{ModelProvider, TypeInfo};
+use serde_json::{Map, Value, json};
+
+use crate::model_provider::OctoFhirModelProvider;
+
+/// Limit on nested group calls, which guards against maps that recurse.
+const MAX_DEPTH: usize = 64;
+
+/// Type information about the targets of a map.
+#[async_trait]
+pub trait TargetModel: Send + Sync {
+    /// Type of `element` of `type_name` and whether the element repeats,
+    /// `None` if unknown. `type_name` may be a backbone element path such
+    /// as `Patient.contact`.
+    async fn element(&self, type_name: &str, element: &str) -> Option<(String, bool)>;
+}
+
+#[async_trait]
+impl TargetModel for OctoFhirModelProvider {
+    async fn element(&self, type_name: &str, element: &str) -> Option<(String, bool)> {
+        let parent = TypeInfo {
+            type_name: "Any".to_string(),
+            singleton: Some(true),
+            is_empty: Some(false),
+            namespace: Some("FHIR".to_string()),
+            name: Some(type_name.to_string()),
+        };
+        let info = self.get_element_type(&parent, element).await.ok()??;
+        Some((info.name?, info.singleton == Some(false)))
+    }
+}
+
+/// Position of a value in one of the target trees.
+#[derive(Debug, Clone)]
+enum Step {
+    Key(String),
+    Index(usize),
+}
+
+/// A target value: the tree it is in, its position and its type if known.
+#[derive(Debug, Clone)]
+struct TargetRef {
+    root: usize,
+    path: Vec<Step>,
+    type_name: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+enum Variable {
+    Source(Value),
+    Target(TargetRef),
+}
+
+type Variables = HashMap<String, Variable>;
+
+/// Runs `map` against `content` and returns the resource built in the first
+/// target input of the map's first group, or the errors of the rules that
+/// failed.
+pub async fn execute(
+    map: &Value,
+    content: &Value,
+    model: &dyn TargetModel,
+) -> Result<Value, Vec<String>> {
+    let group = array(map, "group")
+        .first()
+        .ok_or_else(|| vec!["StructureMap has no groups".to_string()])?;
+    let mut engine = Engine {
+        map,
+        model,
+        roots: Vec::new(),
+        errors: Vec::new(),
+        depth: 0,
+    };
+
+    let group_name = str_field(group, "name").unwrap_or_default();
+    let mut variables = Variables::new();
+    let mut has_source = false;
+    let mut output = None;
+    for input in array(group, "input") {
+        let name = str_field(input, "name").unwrap_or_default().to_string();
+        match str_field(input, "mode") {
+            Some("source") if !has_source => {
+                has_source = true;
+                variables.insert(name, Variable::Source(content.clone()));
+            }
+            Some("source") => {
+                return Err(vec![format!(
+                    "Group '{group_name}' has more than one source input"
+                )]);
+            }
+            Some("target") => {
+                let resource_type =
+                    engine
+                        .target_type(str_field(input, "type"))
+                        .ok_or_else(|| {
+                            vec![format!(
+                                "Type of target input '{name}' of group '{group_name}' is unknown"
+                            )]
+                        })?;
+                let target = engine.new_root(
+                    json!({ "resourceType": resource_type }),
+                    Some(resource_type),
+                );
+                output.get_or_insert(target.root);
+                variables.insert(name, Variable::Target(target));
+            }
+            _ => {
+                return Err(vec![format!(
+                    "Input '{name}' of group '{group_name}' has no mode"
+                )]);
+            }
+        }
+    }
+    let output = output.ok_or_else(|| vec![format!("Group '{group_name}' has no target input")])?;
+
+    engine.run_group(group, variables).await;
+    if engine.errors.is_empty() {
+        Ok(engine.roots.swap_remove(output))
+    } else {
+        Err(engine.errors)
+    }
+}
+
+/// OperationOutcome reporting mapping errors.
+pub fn outcome(errors: &[String]) -> Value {
+    let issues: Vec<Value> = errors
+        .iter()
+        .map(|error| {
+            json!({
+                "severity": "error",
+                "code": "processing",
+                "diagnostics": error
+            })
+        })
+        .collect();
+    json!({
+        "resourceType": "OperationOutcome",
+        "issue": issues
+    })
+}
+
+struct Engine<'a> {
+    map: &'a Value,
+    model: &'a dyn TargetModel,
+    /// Target trees; the first target input's resource and values created
+    /// by rules without a target context
+    roots: Vec<Value>,
+    errors: Vec<String>,
+    depth: usize,
+}
+
+impl<'a> Engine<'a> {
+    /// Resource type of a target input: the type of the structure the map
+    /// declares under the input's type alias, or the first target structure.
+    fn target_type(&self, alias: Option<&str>) -> Option<String> {
+        let last_segment = |url: &str| url.rsplit('/').next().map(str::to_string);
+        let structures = array(self.map, "structure");
+        let structure = match alias {
+            Some(alias) => structures.iter().find(|s| {
+                str_field(s, "alias") == Some(alias)
+                    || str_field(s, "url").and_then(last_segment).as_deref() == Some(alias)
+            }),
+            None => structures
+                .iter()
+                .find(|s| str_field(s, "mode") == Some("target")),
+        };
+        structure
+            .and_then(|s| str_field(s, "url"))
+            .and_then(last_segment)
+            .or_else(|| alias.map(str::to_string))
+    }
+
+    fn find_group(&self, name: &str) -> Option<&'a Value> {
+        array(self.map, "group")
+            .iter()
+            .find(|g| str_field(g, "name") == Some(name))
+    }
+
+    fn run_group<'b>(&'b mut self, group: &'a Value, variables: Variables) -> BoxFuture<'b, ()> {
+        Box::pin(async move {
+            let name = str_field(group, "name").unwrap_or_default();
+            if let Some(base) = str_field(group, "extends") {
+                let args = array(group, "input")
+                    .iter()
+                    .filter_map(|input| variables.get(str_field(input, "name")?).cloned())
+                    .collect();
+                if let Err(e) = self.call_group(base, args).await {
+                    self.errors.push(format!("Group '{name}': {e}"));
+                }
+            }
+            for rule in array(group, "rule") {
+                let path = format!("{name}.{}", str_field(rule, "name").unwrap_or_default());
+                self.run_rule(rule, &variables, path).await;
+            }
+        })
+    }
+
+    /// Runs a group with `args` bound to its inputs in order.
+    async fn call_group(&mut self, name: &str, args: Vec<Variable>) -> Result<(), String> {
+        let group = self
+            .find_group(name)
+            .ok_or_else(|| format!("Unknown group '{name}'"))?;
+        let inputs = array(group, "input");
+        if inputs.len() != args.len() {
+            return Err(format!(
+                "Group '{name}' takes {} inputs, got {}",
+                inputs.len(),
+                args.len()
+            ));
+        }
+        if self.depth >= MAX_DEPTH {
+            return Err(format!("Group calls nested more than {MAX_DEPTH} deep"));
+        }
+        let variables = inputs
+            .iter()
+            .map(|input| str_field(input, "name").unwrap_or_default().to_string())
+            .zip(args)
+            .collect();
+
+        self.depth += 1;
+        self.run_group(group, variables).await;
+        self.depth -= 1;
+        Ok(())
+    }
+
+    /// Runs a rule, recording its error under `path`.
+    fn run_rule<'b>(
+        &'b mut self,
+        rule: &'a Value,
+        variables: &'b Variables,
+        path: String,
+    ) -> BoxFuture<'b, ()> {
+        Box::pin(async move {
+            if let Err(e) = self.try_run_rule(rule, variables, &path).await {
+                self.errors.push(format!("Rule '{path}': {e}"));
+            }
+        })
+    }
+
+    async fn try_run_rule(
+        &mut self,
+        rule: &'a Value,
+        variables: &Variables,
+        path: &str,
+    ) -> Result<(), String> {
+        // One binding of the rule's variables per combination of source values
+        let mut bindings = vec![(variables.clone(), false, None::<Value>)];
+        for (index, source) in array(rule, "source").iter().enumerate() {
+            if source.get("condition").is_some() || source.get("check").is_some() {
+                return Err("FHIRPath conditions and checks are not supported".to_string());
+            }
+            let mut next = Vec::new();
+            for (variables, repeats, first) in bindings {
+                let (values, list) = self.source_values(source, &variables)?;
+                for value in values {
+                    let mut variables = variables.clone();
+                    if let Some(name) = str_field(source, "variable") {
+                        variables.insert(name.to_string(), Variable::Source(value.clone()));
+                    }
+                    let first = if index == 0 {
+                        Some(value)
+                    } else {
+                        first.clone()
+                    };
+                    next.push((variables, repeats || list, first));
+                }
+            }
+            bindings = next;
+        }
+
+        // A target without a transform copies the source value, unless
+        // nested rules or groups fill it in
+        let copies_source = array(rule, "rule").is_empty() && array(rule, "dependent").is_empty();
+        for (mut variables, repeats, first) in bindings {
+            let default = first.filter(|_| copies_source);
+            for target in array(rule, "target") {
+                self.apply_target(target, &mut variables, repeats, default.as_ref())
+                    .await?;
+            }
+            for nested in array(rule, "rule") {
+                let nested_path =
+                    format!("{path}.{}", str_field(nested, "name").unwrap_or_default());
+                self.run_rule(nested, &variables, nested_path).await;
+            }
+            for dependent in array(rule, "dependent") {
+                let name = str_field(dependent, "name")
+                    .ok_or_else(|| "dependent has no name".to_string())?;
+                // R4 lists variable names, R5 lists parameters
+                let arg_names: Vec<&str> = match dependent.get("variable") {
+                    Some(names) => names
+                        .as_array()
+                        .map(|names| names.iter().filter_map(Value::as_str).collect())
+                        .unwrap_or_default(),
+                    None => array(dependent, "parameter")
+                        .iter()
+                        .filter_map(|p| {
+                            str_field(p, "valueId").or_else(|| str_field(p, "valueString"))
+                        })
+                        .collect(),
+                };
+                let args = arg_names
+                    .iter()
+                    .map(|arg| {
+                        variables
+                            .get(*arg)
+                            .cloned()
+                            .ok_or_else(|| format!("Unknown variable '{arg}'"))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                self.call_group(name, args).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Values a rule source selects, and whether they come from a list.
+    fn source_values(
+        &self,
+        source: &Value,
+        variables: &Variables,
+    ) -> Result<(Vec<Value>, bool), String> {
+        let context = str_field(source, "context").ok_or("source has no context")?;
+        let value = match variables.get(context) {
+            Some(Variable::Source(value)) => value.clone(),
+            Some(Variable::Target(target)) => self.get(target).cloned().unwrap_or(Value::Null),
+            None => return Err(format!("Unknown variable '{context}'")),
+        };
+        let type_filter = str_field(source, "type");
+
+        let (mut values, mut list) = match str_field(source, "element") {
+            None => (vec![value], false),
+            Some(element) => match element_value(&value, element, type_filter) {
+                Some(Value::Array(items)) => (items.clone(), true),
+                Some(value) => (vec![value.clone()], false),
+                None => (Vec::new(), false),
+            },
+        };
+        if let Some(type_filter) = type_filter {
+            values.retain(|v| {
+                v.get("resourceType")
+                    .and_then(Value::as_str)
+                    .is_none_or(|t| t == type_filter)
+            });
+        }
+        if values.is_empty()
+            && let Some(default) = source
+                .as_object()
+                .and_then(|s| s.iter().find(|(k, _)| k.starts_with("defaultValue")))
+        {
+            values.push(default.1.clone());
+        }
+
+        match str_field(source, "listMode") {
+            Some("first") => values.truncate(1),
+            Some("not_first") => {
+                values.drain(..values.len().min(1));
+            }
+            Some("last") => {
+                values.drain(..values.len().saturating_sub(1));
+            }
+            Some("not_last") => {
+                values.pop();
+            }
+            Some("only_one") if values.len() > 1 => {
+                return Err(format!("source has {} values, expected one", values.len()));
+            }
+            _ => {}
+        }
+        if matches!(
+            str_field(source, "listMode"),
+            Some("first" | "last" | "only_one")
+        ) {
+            list = false;
+        }
+
+        let min = source.get("min").and_then(Value::as_u64).unwrap_or(0) as usize;
+        if values.len() < min {
+            return Err(format!(
+                "source has {} values, expected at least {min}",
+                values.len()
+            ));
+        }
+        if let Some(max) = str_field(source, "max").and_then(|m| m.parse::<usize>().ok())
+            && values.len() > max
+        {
+            return Err(format!(
+                "source has {} values, expected at most {max}",
+                values.len()
+            ));
+        }
+        Ok((values, list))
+    }
+
+    async fn apply_target(
+        &mut self,
+        target: &Value,
+        variables: &mut Variables,
+        repeats: bool,
+        default: Option<&Value>,
+    ) -> Result<(), String> {
+        let params = array(target, "parameter")
+            .iter()
+            .map(|param| self.parameter(param, variables))
+            .collect::<Result<Vec<_>, _>>()?;
+        let transform = str_field(target, "transform");
+        let value = match transform {
+            None => default.cloned().unwrap_or_else(|| json!({})),
+            Some(name) => transform_value(name, &params, target.get("context").is_none())?,
+        };
+
+        let reference = match str_field(target, "context") {
+            Some(context) => {
+                let Some(Variable::Target(parent)) = variables.get(context).cloned() else {
+                    return Err(format!("'{context}' is not a target variable"));
+                };
+                let element = str_field(target, "element")
+                    .ok_or_else(|| format!("target '{context}' has no element"))?;
+                self.set(&parent, element, value, repeats).await?
+            }
+            None => {
+                let type_name = value
+                    .get("resourceType")
+                    .and_then(Value::as_str)
+                    .map(str::to_string);
+                self.new_root(value, type_name)
+            }
+        };
+        if let Some(name) = str_field(target, "variable") {
+            variables.insert(name.to_string(), Variable::Target(reference));
+        }
+        Ok(())
+    }
+
+    /// Value of a transform parameter: a variable for `valueId`, otherwise
+    /// the literal.
+    fn parameter(&self, param: &Value, variables: &Variables) -> Result<Value, String> {
+        if let Some(name) = str_field(param, "valueId") {
+            return match variables.get(name) {
+                Some(Variable::Source(value)) => Ok(value.clone()),
+                Some(Variable::Target(target)) => {
+                    Ok(self.get(target).cloned().unwrap_or(Value::Null))
+                }
+                None => Err(format!("Unknown variable '{name}'")),
+            };
+        }
+        param
+            .as_object()
+            .and_then(|p| p.iter().find(|(k, _)| k.starts_with("value")))
+            .map(|(_, v)| v.clone())
+            .ok_or_else(|| "parameter has no value".to_string())
+    }
+
+    fn new_root(&mut self, value: Value, type_name: Option<String>) -> TargetRef {
+        self.roots.push(value);
+        TargetRef {
+            root: self.roots.len() - 1,
+            path: Vec::new(),
+            type_name,
+        }
+    }
+
+    fn get(&self, target: &TargetRef) -> Option<&Value> {
+        target
+            .path
+            .iter()
+            .try_fold(self.roots.get(target.root)?, |value, step| match step {
+                Step::Key(key) => value.get(key),
+                Step::Index(index) => value.get(index),
+            })
+    }
+
+    fn get_mut(&mut self, target: &TargetRef) -> Option<&mut Value> {
+        target
+            .path
+            .iter()
+            .try_fold(self.roots.get_mut(target.root)?, |value, step| match step {
+                Step::Key(key) => value.get_mut(key),
+                Step::Index(index) => value.get_mut(index),
+            })
+    }
+
+    /// Sets `element` of the target `parent` to `value`, appending to
+    /// repeating elements.
+    async fn set(
+        &mut self,
+        parent: &TargetRef,
+        element: &str,
+        value: Value,
+        source_repeats: bool,
+    ) -> Result<TargetRef, String> {
+        let element_type = match &parent.type_name {
+            Some(type_name) => self.model.element(type_name, element).await,
+            None => None,
+        };
+        let repeats = element_type
+            .as_ref()
+            .map_or(source_repeats, |(_, repeats)| *repeats);
+        let type_name = element_type.map(|(type_name, _)| type_name).or_else(|| {
+            value
+                .get("resourceType")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+        });
+
+        let object = self
+            .get_mut(parent)
+            .and_then(Value::as_object_mut)
+            .ok_or_else(|| format!("cannot set '{element}' on a primitive value"))?;
+        let mut path = parent.path.clone();
+        path.push(Step::Key(element.to_string()));
+        if let Some(Value::Array(items)) = object.get_mut(element) {
+            items.push(value);
+            path.push(Step::Index(items.len() - 1));
+        } else if repeats {
+            object.insert(element.to_string(), Value::Array(vec![value]));
+            path.push(Step::Index(0));
+        } else {
+            object.insert(element.to_string(), value);
+        }
+        Ok(TargetRef {
+            root: parent.root,
+            path,
+            type_name,
+        })
+    }
+}
+
+/// Value of `element` in `value`, trying the choice element of `type_name`
+/// (e.g. `valueString` for `value` of type `string`) or any choice element.
+fn element_value<'v>(
+    value: &'v Value,
+    element: &str,
+    type_name: Option<&str>,
+) -> Option<&'v Value> {
+    if let Some(found) = value.get(element) {
+        return Some(found);
+    }
+    let object = value.as_object()?;
+    if let Some(type_name) = type_name {
+        return object.get(&format!("{element}{}", capitalize(type_name)));
+    }
+    object.iter().find_map(|(key, found)| {
+        key.strip_prefix(element)
+            .filter(|suffix| suffix.starts_with(|c: char| c.is_ascii_uppercase()))
+            .map(|_| found)
+    })
+}
+
+/// Applies a named transform to its parameter values.
+fn transform_value(name: &str, params: &[Value], standalone: bool) -> Result<Value, String> {
+    let text = |index: usize| -> Result<String, String> {
+        match params.get(index) {
+            Some(Value::String(s)) => Ok(s.clone()),
+            Some(Value::Number(n)) => Ok(n.to_string()),
+            Some(Value::Bool(b)) => Ok(b.to_string()),
+            _ => Err(format!("{name} needs a primitive parameter {}", index + 1)),
+        }
+    };
+    match name {
+        "create" => Ok(match params.first().and_then(Value::as_str) {
+            Some(type_name) if standalone => json!({ "resourceType": type_name }),
+            _ => json!({}),
+        }),
+        "copy" => params
+            .first()
+            .cloned()
+            .ok_or_else(|| "copy needs a parameter".to_string()),
+        "truncate" => {
+            let length = params
+                .get(1)
+                .and_then(Value::as_u64)
+                .ok_or("truncate needs a length")?;
+            Ok(Value::String(
+                text(0)?.chars().take(length as usize).collect(),
+            ))
+        }
+        "append" => (0..params.len())
+            .map(text)
+            .collect::<Result<String, _>>()
+            .map(Value::String),
+        "cast" => match params.get(1).and_then(Value::as_str) {
+            None | Some("string") => text(0).map(Value::String),
+            Some(other) => Err(format!("cast to '{other}' is not supported")),
+        },
+        "uuid" => Ok(Value::String(uuid::Uuid::new_v4().to_string())),
+        "reference" => {
+            let resource = params.first().ok_or("reference needs a parameter")?;
+            match (
+                resource.get("resourceType").and_then(Value::as_str),
+                resource.get("id").and_then(Value::as_str),
+            ) {
+                (Some(resource_type), Some(id)) => {
+                    Ok(json!({ "reference": format!("{resource_type}/{id}") }))
+                }
+                _ => Err("reference needs a resource with an id".to_string()),
+            }
+        }
+        "c" => {
+            let mut coding = Map::new();
+            coding.insert("system".into(), Value::String(text(0)?));
+            coding.insert("code".into(), Value::String(text(1)?));
+            if params.len() > 2 {
+                coding.insert("display".into(), Value::String(text(2)?));
+            }
+            Ok(Value::Object(coding))
+        }
+        "cc" if params.len() == 1 => Ok(json!({ "text": text(0)? })),
+        "cc" => Ok(json!({ "coding": [transform_value("c", params, standalone)?] })),
+        "id" => {
+            let mut identifier = json!({ "system": text(0)?, "value": text(1)? });
+            if params.len() > 2 {
+                identifier["type"] = json!({ "text": text(2)? });
+            }
+            Ok(identifier)
+        }
+        other => Err(format!("transform '{other}' is not supported")),
+    }
+}
+
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    chars
+        .next()
+        .map(|c| c.to_ascii_uppercase().to_string() + chars.as_str())
+        .unwrap_or_default()
+}
+
+fn array<'v>(value: &'v Value, field: &str) -> &'v [Value] {
+    value
+        .get(field)
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+}
+
+fn str_field<'v>(value: &'v Value, field: &str) -> Option<&'v str> {
+    value.get(field).and_then(Value::as_str)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Patient's elements used by the test maps.
+    struct Model;
+
+    #[async_trait]
+    impl TargetModel for Model {
+        async fn element(&self, type_name: &str, element: &str) -> Option<(String, bool)> {
+            let (element_type, repeats) = match (type_name, element) {
+                ("Patient", "identifier") => ("Identifier", true),
+                ("Patient", "name") => ("HumanName", true),
+                ("Patient", "gender") => ("code", false),
+                ("Patient", "active") => ("boolean", false),
+                ("HumanName", "family") => ("string", false),
+                ("HumanName", "given") => ("string", true),
+                _ => return None,
+            };
+            Some((element_type.to_string(), repeats))
+        }
+    }
+
+    fn rule(name: &str, source: Value, target: Value) -> Value {
+        json!({ "name": name, "source": [source], "target": [target] })
+    }
+
+    fn map(rules: Vec<Value>, extra_groups: Vec<Value>) -> Value {
+        let mut groups = vec![json!({
+            "name": "person",
+            "input": [
+                { "name": "src", "type": "TPerson", "mode": "source" },
+                { "name": "tgt", "type": "TPatient", "mode": "target" }
+            ],
+            "rule": rules
+        })];
+        groups.extend(extra_groups);
+        json!({
+            "resourceType": "StructureMap",
+            "url": "http://example.org/StructureMap/person",
+            "structure": [
+                { "url": "http://example.org/StructureDefinition/Person", "mode": "source", "alias": "TPerson" },
+                { "url": "http://hl7.org/fhir/StructureDefinition/Patient", "mode": "target", "alias": "TPatient" }
+            ],
+            "group": groups
+        })
+    }
+
+    fn person() -> Value {
+        json!({
+            "resourceType": "Person",
+            "mrn": "12345",
+            "active": "yes",
+            "surname": "Chalmers",
+            "names": [
+                { "last": "Chalmers", "first": ["Peter", "James"] },
+                { "last": "Windsor", "first": ["Jim"] }
+            ]
+        })
+    }
+
+    #[tokio::test]
+    async fn test_simple_transforms() {
+        let map = map(
+            vec![
+                rule(
+                    "mrn",
+                    json!({ "context": "src", "element": "mrn", "variable": "m" }),
+                    json!({
+                        "context": "tgt", "element": "identifier", "transform": "id",
+                        "parameter": [{ "valueString": "http://hospital.org/mrn" }, { "valueId": "m" }]
+                    }),
+                ),
+                rule(
+                    "active",
+                    json!({ "context": "src", "element": "active" }),
+                    json!({
+                        "context": "tgt", "element": "active", "transform": "copy",
+                        "parameter": [{ "valueBoolean": true }]
+                    }),
+                ),
+                rule(
+                    "gender",
+                    json!({ "context": "src", "element": "sex", "defaultValueCode": "unknown", "variable": "s" }),
+                    json!({ "context": "tgt", "element": "gender" }),
+                ),
+                rule(
+                    "initial",
+                    json!({ "context": "src", "element": "surname", "variable": "s" }),
+                    json!({
+                        "context": "tgt", "element": "text", "transform": "truncate",
+                        "parameter": [{ "valueId": "s" }, { "valueInteger": 1 }]
+                    }),
+                ),
+            ],
+            vec![],
+        );
+
+        let patient = execute(&map, &person(), &Model).await.unwrap();
+        assert_eq!(
+            patient,
+            json!({
+                "resourceType": "Patient",
+                "identifier": [{ "system": "http://hospital.org/mrn", "value": "12345" }],
+                "active": true,
+                "gender": "unknown",
+                "text": "C"
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_nested_rules_and_groups() {
+        let names = json!({
+            "name": "names",
+            "source": [{ "context": "src", "element": "names", "variable": "sn" }],
+            "target": [{ "context": "tgt", "element": "name", "variable": "tn" }],
+            "rule": [{
+                "name": "family",
+                "source": [{ "context": "sn", "element": "last", "variable": "l" }],
+                "target": [{ "context": "tn", "element": "family", "transform": "copy", "parameter": [{ "valueId": "l" }] }]
+            }],
+            "dependent": [{ "name": "given", "variable": ["sn", "tn"] }]
+        });
+        let given = json!({
+            "name": "given",
+            "input": [
+                { "name": "sn", "mode": "source" },
+                { "name": "tn", "mode": "target" }
+            ],
+            "rule": [rule(
+                "first",
+                json!({ "context": "sn", "element": "first", "variable": "f" }),
+                json!({ "context": "tn", "element": "given", "transform": "copy", "parameter": [{ "valueId": "f" }] }),
+            )]
+        });
+        let map = map(vec![names], vec![given]);
+
+        let patient = execute(&map, &person(), &Model).await.unwrap();
+        assert_eq!(
+            patient["name"],
+            json!([
+                { "family": "Chalmers", "given": ["Peter", "James"] },
+                { "family": "Windsor", "given": ["Jim"] }
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_mapping_errors() {
+        let map = map(
+            vec![
+                rule(
+                    "fhirpath",
+                    json!({ "context": "src", "element": "mrn", "condition": "true" }),
+                    json!({ "context": "tgt", "element": "id" }),
+                ),
+                rule(
+                    "translate",
+                    json!({ "context": "src", "element": "mrn", "variable": "m" }),
+                    json!({ "context": "tgt", "element": "id", "transform": "translate" }),
+                ),
+                json!({
+                    "name": "call",
+                    "source": [{ "context": "src" }],
+                    "dependent": [{ "name": "missing", "variable": ["src"] }]
+                }),
+                rule(
+                    "only",
+                    json!({ "context": "src", "element": "names", "listMode": "only_one" }),
+                    json!({ "context": "tgt", "element": "name" }),
+                ),
+            ],
+            vec![],
+        );
+
+        let errors = execute(&map, &person(), &Model).await.unwrap_err();
+        assert_eq!(errors.len(), 4);
+        assert!(errors[0].starts_with("Rule 'person.fhirpath': FHIRPath"));
+        assert!(errors[1].contains("transform 'translate' is not supported"));
+        assert!(errors[2].contains("Unknown group 'missing'"));
+        assert!(errors[3].contains("expected one"));
+
+        let outcome = outcome(&errors);
+        assert_eq!(outcome["issue"].as_array().unwrap().len(), 4);
+        assert_eq!(outcome["issue"][0]["code"], "processing");
+    }
+
+    #[tokio::test]
+    async fn test_recursive_groups_are_bounded() {
+        let map = map(
+            vec![json!({
+                "name": "loop",
+                "source": [{ "context": "src" }],
+                "dependent": [{ "name": "person", "variable": ["src", "tgt"] }]
+            })],
+            vec![],
+        );
+        let errors = execute(&map, &person(), &Model).await.unwrap_err();
+        assert!(errors.iter().any(|e| e.contains("nested more than")));
+    }
+}
@@ -6,8 +6,12 @@
 //! This reduces repeated database/canonical manager queries for frequently
 //! accessed terminology resources during $lookup, $subsumes, and $translate
 //! operations.
+//!
+//! Keys carry the current tenant, so a tenant never sees a CodeSystem or
+//! ConceptMap loaded from another tenant's schema.
 
 use moka::future::Cache;
+use octofhir_storage::TenantId;
 use serde_json::Value;
 use std::sync::{Arc, LazyLock};
 use std::time::Duration;
@@ -21,6 +25,8 @@ const DEFAULT_TTL_SECS: u64 = 3600;
 /// Cache key for CodeSystem lookups
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
 pub struct CodeSystemKey {
+    /// Tenant the resource was loaded for (`None` outside a tenant scope)
+    pub tenant: Option<TenantId>,
     /// CodeSystem URL
     pub url: String,
     /// Optional version
@@ -28,8 +34,10 @@ pub struct CodeSystemKey {
 }
 
 impl CodeSystemKey {
+    /// Key for `url`/`version` in the current tenant.
     pub fn new(url: impl Into<String>, version: Option<String>) -> Self {
         Self {
+            tenant: octofhir_storage::current_tenant(),
             url: url.into(),
             version,
         }
@@ -39,6 +47,8 @@ impl CodeSystemKey {
 /// Cache key for ConceptMap lookups
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
 pub struct ConceptMapKey {
+    /// Tenant the resource was loaded for (`None` outside a tenant scope)
+    pub tenant: Option<TenantId>,
     /// ConceptMap URL
     pub url: String,
     /// Optional version
@@ -46,8 +56,10 @@ pub struct ConceptMapKey {
 }
 
 impl ConceptMapKey {
+    /// Key for `url`/`version` in the current tenant.
     pub fn new(url: impl Into<String>, version: Option<String>) -> Self {
         Self {
+            tenant: octofhir_storage::current_tenant(),
             url: url.into(),
             version,
         }
@@ -189,6 +201,27 @@ mod tests {
         assert_eq!(cached.unwrap().as_ref(), &value);
     }
 
+    #[tokio::test]
+    async fn test_cache_is_per_tenant() {
+        let cache = TerminologyResourceCache::new();
+        let url = "http://example.org/cs-tenant";
+        let acme = TenantId::new("acme").unwrap();
+
+        let acme_key =
+            octofhir_storage::with_tenant(acme, async { CodeSystemKey::new(url, None) }).await;
+        cache
+            .insert_code_system(acme_key.clone(), Some(json!({"tenant": "acme"})))
+            .await;
+
+        assert!(cache.get_code_system(&acme_key).await.is_some());
+        assert!(
+            cache
+                .get_code_system(&CodeSystemKey::new(url, None))
+                .await
+                .is_none()
+        );
+    }
+
     #[tokio::test]
     async fn test_cache_clear() {
         let cache = TerminologyResourceCache::new();
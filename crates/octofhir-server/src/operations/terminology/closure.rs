@@ -23,8 +23,17 @@ use crate::server::AppState;
 
 /// Global storage for closure tables.
 /// Each closure table is identified by a unique name and stores the transitive closure.
+/// Entries are keyed by [`closure_key`], so each tenant has its own namespace.
 static CLOSURE_TABLES: LazyLock<DashMap<String, ClosureTable>> = LazyLock::new(DashMap::new);
 
+/// Key of the closure table `name` in the current tenant.
+fn closure_key(name: &str) -> String {
+    match octofhir_storage::current_tenant() {
+        Some(tenant) => format!("{tenant}:{name}"),
+        None => name.to_string(),
+    }
+}
+
 /// A closure table storing transitive relationships between concepts.
 #[derive(Debug, Clone, Default)]
 pub struct ClosureTable {
@@ -176,7 +185,7 @@ impl ClosureOperation {
     /// Initialize a new closure table.
     fn initialize_closure(name: &str) -> Value {
         let table = ClosureTable::new();
-        CLOSURE_TABLES.insert(name.to_string(), table);
+        CLOSURE_TABLES.insert(closure_key(name), table);
 
         json!({
             "resourceType": "ConceptMap",
@@ -196,7 +205,7 @@ impl ClosureOperation {
         concepts: &[Value],
     ) -> Result<Value, OperationError> {
         let mut table = CLOSURE_TABLES
-            .get(name)
+            .get(&closure_key(name))
             .map(|t| t.clone())
             .unwrap_or_default();
 
@@ -262,7 +271,7 @@ impl ClosureOperation {
         }
 
         // Store updated table
-        CLOSURE_TABLES.insert(name.to_string(), table.clone());
+        CLOSURE_TABLES.insert(closure_key(name), table.clone());
 
         // Return ConceptMap with new mappings
         Ok(json!({
@@ -358,6 +367,14 @@ impl OperationHandler for ClosureOperation {
 mod tests {
     use super::*;
 
+    #[tokio::test]
+    async fn test_closure_key_is_per_tenant() {
+        assert_eq!(closure_key("c1"), "c1");
+        let tenant = octofhir_storage::TenantId::new("acme").unwrap();
+        let key = octofhir_storage::with_tenant(tenant, async { closure_key("c1") }).await;
+        assert_eq!(key, "acme:c1");
+    }
+
     #[test]
     fn test_closure_table_basic() {
         let mut table = ClosureTable::new();
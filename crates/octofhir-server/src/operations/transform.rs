@@ -0,0 +1,145 @@
+//! $transform Operation Handler
+//!
+//! Implements the FHIR `StructureMap/$transform` operation, which runs a
+//! StructureMap against a resource and returns the resource it produces.
+//! See [`super::structure_map`] for the mapping constructs supported.
+//!
+//! The map is the StructureMap the operation is invoked on
+//! (`POST [base]/StructureMap/[id]/$transform`), or at type level the one
+//! with the canonical URL in `source`, or a StructureMap given inline in
+//! `sourceMap`. The input is the `content` parameter, or the resource posted
+//! as the body. Mapping errors are returned as an OperationOutcome.
+//!
+//! # Example
+//!
+//! ```text
+//! POST /fhir/StructureMap/$transform
+//! {
+//!   "resourceType": "Parameters",
+//!   "parameter": [
+//!     { "name": "source", "valueUri": "http://example.org/StructureMap/person" },
+//!     { "name": "content", "resource": { "resourceType": "Person", ... } }
+//!   ]
+//! }
+//! ```
+
+use async_trait::async_trait;
+use serde_json::Value;
+
+use super::structure_map::{execute, outcome};
+use super::{OperationError, OperationHandler};
+use crate::server::AppState;
+use octofhir_storage::SearchParams;
+
+/// Handler for the `$transform` operation.
+pub struct TransformOperation;
+
+impl TransformOperation {
+    pub fn new() -> Self {
+        Self
+    }
+
+    async fn transform(
+        &self,
+        state: &AppState,
+        map: &Value,
+        params: &Value,
+    ) -> Result<Value, OperationError> {
+        let content = parameter(params, "content")
+            .or_else(|| parameter(params, "resource"))
+            .and_then(|p| p.get("resource"))
+            .ok_or_else(|| {
+                OperationError::InvalidParameters("Missing required parameter: content".to_string())
+            })?;
+
+        execute(map, content, state.model_provider.as_ref())
+            .await
+            .map_err(|errors| OperationError::ValidationFailed(outcome(&errors)))
+    }
+}
+
+impl Default for TransformOperation {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl OperationHandler for TransformOperation {
+    fn code(&self) -> &str {
+        "transform"
+    }
+
+    async fn handle_type(
+        &self,
+        state: &AppState,
+        resource_type: &str,
+        params: &Value,
+    ) -> Result<Value, OperationError> {
+        if resource_type != "StructureMap" {
+            return Err(OperationError::NotSupported(format!(
+                "$transform is not supported for {resource_type}"
+            )));
+        }
+
+        if let Some(map) = parameter(params, "sourceMap").and_then(|p| p.get("resource")) {
+            return self.transform(state, map, params).await;
+        }
+
+        let url = parameter(params, "source")
+            .and_then(|p| {
+                p.get("valueUri")
+                    .or_else(|| p.get("valueCanonical"))
+                    .or_else(|| p.get("valueString"))
+            })
+            .and_then(Value::as_str)
+            .ok_or_else(|| {
+                OperationError::InvalidParameters(
+                    "Missing required parameter: source or sourceMap".to_string(),
+                )
+            })?;
+        let search_params = SearchParams::new().with_count(1).with_param("url", url);
+        let result = state
+            .storage
+            .search("StructureMap", &search_params)
+            .await
+            .map_err(|e| OperationError::Internal(e.to_string()))?;
+        let entry = result
+            .entries
+            .first()
+            .ok_or_else(|| OperationError::NotFound(format!("StructureMap '{url}' not found")))?;
+
+        self.transform(state, &entry.resource, params).await
+    }
+
+    async fn handle_instance(
+        &self,
+        state: &AppState,
+        resource_type: &str,
+        id: &str,
+        params: &Value,
+    ) -> Result<Value, OperationError> {
+        if resource_type != "StructureMap" {
+            return Err(OperationError::NotSupported(format!(
+                "$transform is not supported for {resource_type}"
+            )));
+        }
+
+        let stored = state
+            .storage
+            .read("StructureMap", id)
+            .await
+            .map_err(|e| OperationError::Internal(e.to_string()))?
+            .ok_or_else(|| OperationError::NotFound(format!("StructureMap/{id} not found")))?;
+
+        self.transform(state, &stored.resource, params).await
+    }
+}
+
+fn parameter<'a>(params: &'a Value, name: &str) -> Option<&'a Value> {
+    params
+        .get("parameter")?
+        .as_array()?
+        .iter()
+        .find(|p| p.get("name").and_then(|n| n.as_str()) == Some(name))
+}
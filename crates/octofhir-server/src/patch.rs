@@ -2,7 +2,7 @@
 
 use std::sync::Arc;
 
-use json_patch::{Patch, PatchOperation, patch};
+use json_patch::{Patch, PatchErrorKind, PatchOperation, patch};
 use octofhir_api::ApiError;
 use octofhir_fhir_model::provider::ModelProvider;
 use octofhir_fhirpath::{Collection, EvaluationContext, FhirPathEngine, FhirPathValue};
@@ -28,6 +28,19 @@ pub fn apply_json_patch(resource: &Value, patch_bytes: &[u8]) -> Result<Value, A
     let operations: Patch = serde_json::from_slice(patch_bytes)
         .map_err(|e| ApiError::bad_request(format!("Invalid JSON Patch document: {e}")))?;
 
+    apply_json_patch_operations(resource, &operations)
+}
+
+/// Applies parsed JSON Patch operations to a FHIR resource.
+///
+/// The patch is applied atomically to a copy of `resource`, so `test`
+/// operations are evaluated against the current resource before any of its
+/// mutations take effect. A failed `test` is reported as 422 Unprocessable
+/// Entity; other failures are 400 Bad Request.
+pub fn apply_json_patch_operations(
+    resource: &Value,
+    operations: &Patch,
+) -> Result<Value, ApiError> {
     // Validate patch operations before applying
     validate_json_patch_operations(&operations.0)?;
 
@@ -35,8 +48,16 @@ pub fn apply_json_patch(resource: &Value, patch_bytes: &[u8]) -> Result<Value, A
     let mut patched = resource.clone();
 
     // Apply patch
-    patch(&mut patched, &operations)
-        .map_err(|e| ApiError::bad_request(format!("Patch operation failed: {e}")))?;
+    patch(&mut patched, operations).map_err(|e| match e.kind {
+        PatchErrorKind::TestFailed => ApiError::unprocessable_entity(
+            format!(
+                "JSON Patch test operation {} failed at '{}'",
+                e.operation, e.path
+            ),
+            None,
+        ),
+        _ => ApiError::bad_request(format!("Patch operation failed: {e}")),
+    })?;
 
     Ok(patched)
 }
@@ -473,8 +494,8 @@ async fn evaluate_fhirpath(
         .map_err(|e| ApiError::bad_request(format!("Failed to create FHIRPath input: {}", e)))?;
     let context = EvaluationContext::new(input, fhirpath_provider, None, None, None);
 
-    engine
-        .evaluate(path, &context)
+    crate::cache::fhirpath_cache()
+        .evaluate(engine, path, &context)
         .await
         .map(|r| r.value.into_vec())
         .map_err(|e| ApiError::bad_request(format!("FHIRPath evaluation failed: {}", e)))
@@ -629,4 +650,27 @@ mod tests {
 
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_failed_test_operation_is_unprocessable() {
+        let resource = json!({
+            "resourceType": "Patient",
+            "id": "123",
+            "active": true
+        });
+
+        let patch = r#"[
+            {"op": "test", "path": "/active", "value": false},
+            {"op": "replace", "path": "/active", "value": false}
+        ]"#;
+        let err = apply_json_patch(&resource, patch.as_bytes()).unwrap_err();
+        assert!(matches!(err, ApiError::UnprocessableEntity { .. }));
+
+        let patch = r#"[
+            {"op": "test", "path": "/active", "value": true},
+            {"op": "replace", "path": "/active", "value": false}
+        ]"#;
+        let result = apply_json_patch(&resource, patch.as_bytes()).unwrap();
+        assert_eq!(result["active"], false);
+    }
 }
@@ -0,0 +1,208 @@
+//! Per-client API rate limiting.
+//!
+//! Each client gets a fixed number of requests per fixed window. Counters are
+//! kept in the [`CacheBackend`], so with Redis enabled every instance counts
+//! against the same quota. The current quota is reported on responses with
+//! the `RateLimit-Limit`, `RateLimit-Remaining` and `RateLimit-Reset` headers
+//! from the IETF RateLimit header fields draft, letting well-behaved clients
+//! slow down before they are rejected.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use axum::http::{HeaderMap, HeaderName, HeaderValue};
+use serde::Serialize;
+use tokio::task::JoinHandle;
+
+use crate::cache::CacheBackend;
+use crate::config::RateLimitConfig;
+
+pub const RATELIMIT_LIMIT: HeaderName = HeaderName::from_static("ratelimit-limit");
+pub const RATELIMIT_REMAINING: HeaderName = HeaderName::from_static("ratelimit-remaining");
+pub const RATELIMIT_RESET: HeaderName = HeaderName::from_static("ratelimit-reset");
+
+/// A client's quota in the current window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Quota {
+    /// Requests allowed per window
+    pub limit: u32,
+    /// Requests counted so far, including rejected ones
+    pub used: u64,
+    /// Requests left before the client is rejected
+    pub remaining: u32,
+    /// Seconds until the window ends and the quota is restored
+    pub reset_secs: u64,
+}
+
+impl Quota {
+    fn new(limit: u32, used: u64, reset_secs: u64) -> Self {
+        let remaining = u64::from(limit).saturating_sub(used) as u32;
+        Self {
+            limit,
+            used,
+            remaining,
+            reset_secs,
+        }
+    }
+
+    /// Whether the request that produced this quota is over the limit.
+    pub fn exceeded(&self) -> bool {
+        self.used > u64::from(self.limit)
+    }
+
+    /// Sets the `RateLimit-*` headers describing this quota.
+    pub fn apply_headers(&self, headers: &mut HeaderMap) {
+        headers.insert(RATELIMIT_LIMIT, HeaderValue::from(self.limit));
+        headers.insert(RATELIMIT_REMAINING, HeaderValue::from(self.remaining));
+        headers.insert(RATELIMIT_RESET, HeaderValue::from(self.reset_secs));
+    }
+}
+
+/// Fixed-window request counter per client.
+pub struct RateLimiter {
+    cache: CacheBackend,
+    limit: u32,
+    window_secs: u64,
+}
+
+impl RateLimiter {
+    /// Creates a limiter for `config`, or `None` when rate limiting is
+    /// disabled.
+    pub fn new(cache: CacheBackend, config: &RateLimitConfig) -> Option<Self> {
+        (config.requests_per_window > 0 && config.window_secs > 0).then(|| Self {
+            cache,
+            limit: config.requests_per_window,
+            window_secs: config.window_secs,
+        })
+    }
+
+    /// Spawns a task that drops expired counters once per window. Counters
+    /// kept in memory (without Redis, or while it is unreachable) get a new
+    /// key every window, so the old ones would otherwise pile up.
+    pub fn spawn_pruning(&self) -> JoinHandle<()> {
+        let cache = self.cache.clone();
+        let period = Duration::from_secs(self.window_secs);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(period);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                let pruned = cache.prune_expired();
+                if pruned > 0 {
+                    tracing::debug!(pruned = pruned, "Pruned expired rate limit counters");
+                }
+            }
+        })
+    }
+
+    /// Counts a request from `client` and returns the resulting quota.
+    pub async fn hit(&self, client: &str) -> Quota {
+        self.hit_at(client, now_secs()).await
+    }
+
+    /// Returns `client`'s quota without counting a request.
+    pub async fn quota(&self, client: &str) -> Quota {
+        self.quota_at(client, now_secs()).await
+    }
+
+    /// Restores `client`'s full quota for the current window.
+    pub async fn reset(&self, client: &str) {
+        let (window, _) = self.window(now_secs());
+        self.cache.invalidate(&counter_key(client, window)).await;
+    }
+
+    async fn hit_at(&self, client: &str, now: u64) -> Quota {
+        let (window, reset_secs) = self.window(now);
+        let used = self
+            .cache
+            .increment(
+                &counter_key(client, window),
+                Duration::from_secs(reset_secs),
+            )
+            .await;
+        Quota::new(self.limit, used, reset_secs)
+    }
+
+    async fn quota_at(&self, client: &str, now: u64) -> Quota {
+        let (window, reset_secs) = self.window(now);
+        let used = self.cache.counter(&counter_key(client, window)).await;
+        Quota::new(self.limit, used, reset_secs)
+    }
+
+    /// Index of the window containing `now` and the seconds left in it.
+    fn window(&self, now: u64) -> (u64, u64) {
+        let window = now / self.window_secs;
+        let reset_secs = (window + 1) * self.window_secs - now;
+        (window, reset_secs)
+    }
+}
+
+fn counter_key(client: &str, window: u64) -> String {
+    format!("rate_limit:{client}:{window}")
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter(limit: u32) -> RateLimiter {
+        let config = RateLimitConfig {
+            requests_per_window: limit,
+            window_secs: 60,
+        };
+        RateLimiter::new(CacheBackend::new_local(), &config).expect("enabled")
+    }
+
+    #[test]
+    fn test_disabled_by_default() {
+        assert!(RateLimiter::new(CacheBackend::new_local(), &RateLimitConfig::default()).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_quota_per_client_and_window() {
+        let limiter = limiter(2);
+
+        let first = limiter.hit_at("a", 610).await;
+        assert_eq!(first, Quota::new(2, 1, 50));
+        assert_eq!(first.remaining, 1);
+        assert!(!limiter.hit_at("a", 611).await.exceeded());
+
+        let third = limiter.hit_at("a", 612).await;
+        assert!(third.exceeded());
+        assert_eq!(third.remaining, 0);
+        assert_eq!(limiter.quota_at("a", 612).await.used, 3);
+
+        assert!(!limiter.hit_at("b", 612).await.exceeded());
+        assert_eq!(limiter.hit_at("a", 660).await.used, 1);
+    }
+
+    #[tokio::test]
+    async fn test_expired_counters_are_pruned() {
+        let cache = CacheBackend::new_local();
+        cache.increment("rate_limit:a:1", Duration::ZERO).await;
+        cache
+            .increment("rate_limit:a:2", Duration::from_secs(60))
+            .await;
+        tokio::time::sleep(Duration::from_millis(5)).await;
+
+        assert_eq!(cache.prune_expired(), 1);
+        assert_eq!(cache.stats().l1_entries, 1);
+        assert_eq!(cache.counter("rate_limit:a:2").await, 1);
+    }
+
+    #[test]
+    fn test_headers() {
+        let mut headers = HeaderMap::new();
+        Quota::new(100, 40, 17).apply_headers(&mut headers);
+        assert_eq!(headers[RATELIMIT_LIMIT], "100");
+        assert_eq!(headers[RATELIMIT_REMAINING], "60");
+        assert_eq!(headers[RATELIMIT_RESET], "17");
+    }
+}
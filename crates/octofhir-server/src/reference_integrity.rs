@@ -0,0 +1,226 @@
+//! Reference integrity checks on write.
+//!
+//! With `validation.reference_integrity` set to `warn` or `enforce`, create
+//! and update look up every local reference the resource exposes through its
+//! `reference` search parameters and report those whose target does not
+//! exist. `enforce` rejects the write with a 422 OperationOutcome listing the
+//! dangling references; `warn` only logs them.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use octofhir_search::{SearchParameterRegistry, SearchParameterType};
+use octofhir_storage::{DynStorage, StorageError};
+use serde_json::{Value, json};
+
+/// A reference found in a resource, with where it was found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReferenceSite {
+    /// FHIRPath of the Reference element (e.g. `Observation.performer[1]`).
+    pub path: String,
+    /// The `reference` string as written.
+    pub reference: String,
+}
+
+/// Collects the references reachable through `resource_type`'s `reference`
+/// search parameters, in path order. A path shared by several parameters
+/// (e.g. `subject` and `patient`) is reported once.
+pub fn collect_references(
+    resource: &Value,
+    resource_type: &str,
+    registry: &SearchParameterRegistry,
+) -> Vec<ReferenceSite> {
+    let mut sites: BTreeMap<String, String> = BTreeMap::new();
+    for param in registry.get_all_for_type(resource_type) {
+        if param.param_type != SearchParameterType::Reference {
+            continue;
+        }
+        let Some(expression) = param.expression.as_deref() else {
+            continue;
+        };
+        for segments in
+            octofhir_search::sql_builder::fhirpath_to_jsonb_paths(expression, resource_type)
+        {
+            walk(resource, &segments, resource_type.to_string(), &mut sites);
+        }
+    }
+    sites
+        .into_iter()
+        .map(|(path, reference)| ReferenceSite { path, reference })
+        .collect()
+}
+
+fn walk(value: &Value, segments: &[String], path: String, out: &mut BTreeMap<String, String>) {
+    match value {
+        Value::Array(items) => {
+            for (i, item) in items.iter().enumerate() {
+                walk(item, segments, format!("{path}[{i}]"), out);
+            }
+        }
+        Value::Object(map) => match segments.split_first() {
+            Some((head, rest)) => {
+                if let Some(child) = map.get(head) {
+                    walk(child, rest, format!("{path}.{head}"), out);
+                }
+            }
+            None => {
+                if let Some(reference) = map.get("reference").and_then(Value::as_str) {
+                    out.insert(path, reference.to_string());
+                }
+            }
+        },
+        _ => {}
+    }
+}
+
+/// Returns the sites whose local reference points at a resource that does not
+/// exist. Contained, `urn:`, and external references are not checked;
+/// references listed in `known` (`Type/id`) count as existing.
+pub async fn find_dangling(
+    storage: &DynStorage,
+    base_url: &str,
+    sites: Vec<ReferenceSite>,
+    known: &HashSet<String>,
+) -> Result<Vec<ReferenceSite>, StorageError> {
+    let mut local: Vec<(ReferenceSite, String)> = Vec::new();
+    let mut by_type: HashMap<String, Vec<String>> = HashMap::new();
+    for site in sites {
+        let Ok(parsed) =
+            octofhir_core::fhir_reference::parse_reference(&site.reference, Some(base_url))
+        else {
+            continue;
+        };
+        let key = format!("{}/{}", parsed.resource_type, parsed.id);
+        if known.contains(&key) {
+            continue;
+        }
+        by_type
+            .entry(parsed.resource_type)
+            .or_default()
+            .push(parsed.id);
+        local.push((site, key));
+    }
+    if local.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let groups: Vec<(String, Vec<String>)> = by_type
+        .into_iter()
+        .map(|(rtype, mut ids)| {
+            ids.sort();
+            ids.dedup();
+            (rtype, ids)
+        })
+        .collect();
+    let found = storage.exists_many_grouped(&groups).await?;
+
+    Ok(local
+        .into_iter()
+        .filter(|(_, key)| !found.contains(key))
+        .map(|(site, _)| site)
+        .collect())
+}
+
+/// Builds the OperationOutcome returned when `enforce` rejects a write: one
+/// `not-found` issue per dangling reference.
+pub fn dangling_outcome(dangling: &[ReferenceSite]) -> Value {
+    json!({
+        "resourceType": "OperationOutcome",
+        "issue": dangling.iter().map(|site| json!({
+            "severity": "error",
+            "code": "not-found",
+            "diagnostics": format!(
+                "Referenced resource '{}' does not exist",
+                site.reference
+            ),
+            "expression": [site.path],
+            "details": {
+                "coding": [{
+                    "system": "http://octofhir.io/CodeSystem/operation-outcome-type",
+                    "code": "non-existent-resource"
+                }]
+            }
+        })).collect::<Vec<_>>()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_walk_indexes_array_elements() {
+        let resource = json!({
+            "resourceType": "Observation",
+            "performer": [
+                {"reference": "Practitioner/a"},
+                {"display": "no reference"},
+                {"reference": "Organization/b"}
+            ]
+        });
+        let mut out = BTreeMap::new();
+        walk(
+            &resource,
+            &["performer".to_string()],
+            "Observation".to_string(),
+            &mut out,
+        );
+        assert_eq!(out.len(), 2);
+        assert_eq!(
+            out.get("Observation.performer[0]").map(String::as_str),
+            Some("Practitioner/a")
+        );
+        assert_eq!(
+            out.get("Observation.performer[2]").map(String::as_str),
+            Some("Organization/b")
+        );
+    }
+
+    #[test]
+    fn test_walk_follows_nested_segments() {
+        let resource = json!({
+            "resourceType": "Encounter",
+            "participant": [
+                {"individual": {"reference": "Practitioner/p1"}},
+                {"individual": {"reference": "Practitioner/p2"}}
+            ]
+        });
+        let mut out = BTreeMap::new();
+        walk(
+            &resource,
+            &["participant".to_string(), "individual".to_string()],
+            "Encounter".to_string(),
+            &mut out,
+        );
+        assert_eq!(
+            out.keys().cloned().collect::<Vec<_>>(),
+            vec![
+                "Encounter.participant[0].individual".to_string(),
+                "Encounter.participant[1].individual".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dangling_outcome_lists_each_reference() {
+        let outcome = dangling_outcome(&[
+            ReferenceSite {
+                path: "Observation.subject".to_string(),
+                reference: "Patient/missing".to_string(),
+            },
+            ReferenceSite {
+                path: "Observation.performer[0]".to_string(),
+                reference: "Practitioner/gone".to_string(),
+            },
+        ]);
+        let issues = outcome["issue"].as_array().expect("issues");
+        assert_eq!(issues.len(), 2);
+        assert_eq!(issues[0]["code"], "not-found");
+        assert_eq!(issues[0]["expression"], json!(["Observation.subject"]));
+        assert!(
+            issues[1]["diagnostics"]
+                .as_str()
+                .unwrap()
+                .contains("Practitioner/gone")
+        );
+    }
+}
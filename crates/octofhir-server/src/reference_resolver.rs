@@ -11,7 +11,7 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use async_trait::async_trait;
-use octofhir_core::fhir_reference::{parse_reference, FhirReference, UnresolvableReference};
+use octofhir_core::fhir_reference::{FhirReference, UnresolvableReference, parse_reference};
 use octofhir_fhirschema::reference::{
     ReferenceError, ReferenceResolutionResult, ReferenceResolver, ReferenceResult,
 };
@@ -0,0 +1,105 @@
+//! Feature flags evaluated for the current request.
+//!
+//! [`feature_flags_middleware`](crate::middleware::feature_flags_middleware)
+//! evaluates every configured flag once per request against a
+//! [`FeatureContext`] built from the caller (user, client, tenant, request ID),
+//! so percentage rollouts stay stable for a caller and a flag cannot flip
+//! halfway through a request. The result is attached to the request
+//! extensions and bound to the request's task:
+//!
+//! - handlers extract `Extension<Arc<RequestFeatures>>`
+//! - operations and anything below them call [`feature_enabled`]
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+
+use octofhir_config::{FeatureContext, FeatureFlags};
+
+tokio::task_local! {
+    static REQUEST_FEATURES: Arc<RequestFeatures>;
+}
+
+/// Flags evaluated for one request.
+#[derive(Debug, Clone, Default)]
+pub struct RequestFeatures {
+    enabled: HashMap<String, bool>,
+}
+
+impl RequestFeatures {
+    /// Evaluates every flag in `flags` for `context`.
+    #[must_use]
+    pub fn evaluate(flags: &FeatureFlags, context: &FeatureContext) -> Self {
+        Self {
+            enabled: flags
+                .list()
+                .map(|flag| (flag.name.clone(), flag.evaluate(context)))
+                .collect(),
+        }
+    }
+
+    /// Whether `name` is enabled for this request. Unknown flags are off.
+    #[must_use]
+    pub fn is_enabled(&self, name: &str) -> bool {
+        self.enabled.get(name).copied().unwrap_or(false)
+    }
+}
+
+/// Runs `fut` with `features` as the current request's flags.
+pub async fn with_request_features<F: Future>(features: Arc<RequestFeatures>, fut: F) -> F::Output {
+    REQUEST_FEATURES.scope(features, fut).await
+}
+
+/// Returns the flags of the current request, if any.
+#[must_use]
+pub fn current_request_features() -> Option<Arc<RequestFeatures>> {
+    REQUEST_FEATURES.try_with(Clone::clone).ok()
+}
+
+/// Whether `name` is enabled for the current request. Off outside a request
+/// (background jobs, startup) and for unknown flags.
+#[must_use]
+pub fn feature_enabled(name: &str) -> bool {
+    REQUEST_FEATURES
+        .try_with(|features| features.is_enabled(name))
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use octofhir_config::FeatureFlag;
+
+    fn flags() -> FeatureFlags {
+        let mut flags = FeatureFlags::new();
+        flags.set(FeatureFlag::boolean("search.new_path", true));
+        flags.set(FeatureFlag::boolean("search.old_path", false));
+        flags.set(FeatureFlag::tenant_based(
+            "tenant.beta",
+            vec!["acme".to_string()],
+        ));
+        flags
+    }
+
+    #[test]
+    fn test_evaluate_uses_context() {
+        let features = RequestFeatures::evaluate(&flags(), &FeatureContext::with_tenant("acme"));
+        assert!(features.is_enabled("search.new_path"));
+        assert!(!features.is_enabled("search.old_path"));
+        assert!(features.is_enabled("tenant.beta"));
+        assert!(!features.is_enabled("unknown"));
+
+        let other = RequestFeatures::evaluate(&flags(), &FeatureContext::with_tenant("other"));
+        assert!(!other.is_enabled("tenant.beta"));
+    }
+
+    #[tokio::test]
+    async fn test_feature_enabled_reads_request_scope() {
+        assert!(!feature_enabled("search.new_path"));
+
+        let features = Arc::new(RequestFeatures::evaluate(&flags(), &FeatureContext::new()));
+        let enabled =
+            with_request_features(features, async { feature_enabled("search.new_path") }).await;
+        assert!(enabled);
+    }
+}
@@ -0,0 +1,129 @@
+//! Per-request timing breakdown.
+//!
+//! The slow-request log reports where a request spent its time: in
+//! authentication and authorization, in storage and search queries, and in
+//! serializing the response. Code on the request path records each phase with
+//! [`record`] or [`timed`]; the middleware reads the totals once the response
+//! is ready.
+//!
+//! Like the query budget, the timings live in a task-local, so work spawned
+//! onto another task is not recorded. Outside a [`with_request_timings`]
+//! scope, recording is a no-op.
+
+use std::future::Future;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+tokio::task_local! {
+    static REQUEST_TIMINGS: RequestTimings;
+}
+
+/// A phase of request handling.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    /// Authentication and access policy evaluation.
+    Auth,
+    /// Storage calls and search SQL.
+    Query,
+    /// Building and serializing the response body.
+    Serialization,
+}
+
+/// Time spent per phase by everything serving one request.
+#[derive(Debug, Clone, Default)]
+pub struct RequestTimings {
+    auth_us: Arc<AtomicU64>,
+    query_us: Arc<AtomicU64>,
+    serialization_us: Arc<AtomicU64>,
+    queries: Arc<AtomicU32>,
+}
+
+impl RequestTimings {
+    /// Creates empty timings.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `elapsed` to `phase`. Query phases are also counted.
+    pub fn record(&self, phase: Phase, elapsed: Duration) {
+        let micros = u64::try_from(elapsed.as_micros()).unwrap_or(u64::MAX);
+        let total = match phase {
+            Phase::Auth => &self.auth_us,
+            Phase::Query => {
+                self.queries.fetch_add(1, Ordering::Relaxed);
+                &self.query_us
+            }
+            Phase::Serialization => &self.serialization_us,
+        };
+        total.fetch_add(micros, Ordering::Relaxed);
+    }
+
+    /// Total time recorded for `phase`.
+    #[must_use]
+    pub fn get(&self, phase: Phase) -> Duration {
+        let total = match phase {
+            Phase::Auth => &self.auth_us,
+            Phase::Query => &self.query_us,
+            Phase::Serialization => &self.serialization_us,
+        };
+        Duration::from_micros(total.load(Ordering::Relaxed))
+    }
+
+    /// Number of storage calls and search queries recorded.
+    #[must_use]
+    pub fn queries(&self) -> u32 {
+        self.queries.load(Ordering::Relaxed)
+    }
+}
+
+/// Runs `fut` recording its phases into `timings`.
+pub async fn with_request_timings<F: Future>(timings: RequestTimings, fut: F) -> F::Output {
+    REQUEST_TIMINGS.scope(timings, fut).await
+}
+
+/// Adds `elapsed` to `phase` of the current request. A no-op outside a
+/// [`with_request_timings`] scope.
+pub fn record(phase: Phase, elapsed: Duration) {
+    let _ = REQUEST_TIMINGS.try_with(|timings| timings.record(phase, elapsed));
+}
+
+/// Awaits `fut` and records its duration under `phase`.
+pub async fn timed<F: Future>(phase: Phase, fut: F) -> F::Output {
+    let start = Instant::now();
+    let output = fut.await;
+    record(phase, start.elapsed());
+    output
+}
+
+/// Runs `f` and records its duration under `phase`.
+pub fn timed_sync<T>(phase: Phase, f: impl FnOnce() -> T) -> T {
+    let start = Instant::now();
+    let output = f();
+    record(phase, start.elapsed());
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_records_within_scope_only() {
+        record(Phase::Query, Duration::from_millis(5));
+
+        let timings = RequestTimings::new();
+        with_request_timings(timings.clone(), async {
+            record(Phase::Auth, Duration::from_millis(2));
+            record(Phase::Query, Duration::from_millis(3));
+            record(Phase::Query, Duration::from_millis(4));
+            timed_sync(Phase::Serialization, || ());
+        })
+        .await;
+
+        assert_eq!(timings.get(Phase::Auth), Duration::from_millis(2));
+        assert_eq!(timings.get(Phase::Query), Duration::from_millis(7));
+        assert_eq!(timings.queries(), 2);
+    }
+}
@@ -9,6 +9,7 @@
 //! touched). `analyze = true` runs `EXPLAIN (ANALYZE)` and therefore executes the query.
 
 use axum::{Json, extract::State, http::StatusCode, response::IntoResponse};
+use octofhir_db_postgres::SchemaManager;
 use octofhir_search::ir::SearchDebugPlan;
 use octofhir_search::{
     ParamsSearchConfig, SqlValue, UnknownParamHandling,
@@ -19,9 +20,6 @@ use serde_json::json;
 
 use crate::server::AppState;
 
-/// Postgres schema FHIR resource tables live in.
-const SEARCH_SCHEMA: &str = "public";
-
 #[derive(Debug, Deserialize)]
 pub struct ExplainRequest {
     /// FHIR resource type, e.g. "Patient".
@@ -127,18 +125,20 @@ pub async fn explain_search(
     }
 
     let cfg = state.search_config.config();
-    let params = parse_query_string(&req.query, cfg.default_count as u32, cfg.max_count as u32);
+    let mut params = parse_query_string(&req.query, cfg.default_count as u32, cfg.max_count as u32);
+    cfg.default_sort.apply(&req.resource_type, &mut params);
 
     let build_cfg = ParamsSearchConfig {
         unknown_param_handling: UnknownParamHandling::Lenient,
         collect_debug_plan: true,
+        max_chain_depth: Some(state.config.search.max_chain_depth),
     };
 
     let converted = build_native_ir_query_from_params_with_config(
         &req.resource_type,
         &params,
         cfg.registry.as_ref(),
-        SEARCH_SCHEMA,
+        &SchemaManager::resource_schema(),
         &build_cfg,
     )
     .map_err(|e| {
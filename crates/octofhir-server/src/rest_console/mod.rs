@@ -32,6 +32,8 @@ pub struct RestConsoleState {
     fhir_version: String,
     db_pool: Arc<PgPool>,
     storage: DynStorage,
+    /// Resource type allowlist; empty offers every type
+    allowed_resource_types: Vec<String>,
 }
 
 impl RestConsoleState {
@@ -48,8 +50,32 @@ impl RestConsoleState {
             fhir_version: fhir_version.into(),
             db_pool,
             storage,
+            allowed_resource_types: Vec::new(),
         }
     }
+
+    /// Only offer these resource types (see `FhirSettings::resource_types`).
+    #[must_use]
+    pub fn with_allowed_resource_types(mut self, resource_types: Vec<String>) -> Self {
+        self.allowed_resource_types = resource_types;
+        self
+    }
+
+    /// Whether suggestions for `resource_type` are offered.
+    fn offers(&self, resource_type: &str) -> bool {
+        self.allowed_resource_types.is_empty()
+            || self
+                .allowed_resource_types
+                .iter()
+                .any(|rt| rt == resource_type)
+    }
+
+    /// Resource types with search parameters that the server serves.
+    fn list_resource_types(&self) -> Vec<String> {
+        let mut types = self.registry.list_resource_types();
+        types.retain(|rt| self.offers(rt));
+        types
+    }
 }
 
 impl FromRef<AppState> for RestConsoleState {
@@ -61,6 +87,7 @@ impl FromRef<AppState> for RestConsoleState {
             state.db_pool.clone(),
             state.storage.clone(),
         )
+        .with_allowed_resource_types(state.config.fhir.resource_types.clone())
     }
 }
 
@@ -85,6 +112,7 @@ pub async fn introspect(State(state): State<RestConsoleState>) -> impl IntoRespo
 pub async fn build_unified_payload(state: &RestConsoleState) -> RestConsoleResponse {
     let registry = &state.registry;
     let operations = load_all_operations_for_console(state).await;
+    let resource_types = state.list_resource_types();
 
     // === Build autocomplete suggestions ===
     let mut suggestions = Suggestions {
@@ -95,8 +123,8 @@ pub async fn build_unified_payload(state: &RestConsoleState) -> RestConsoleRespo
         api_endpoints: Vec::new(),
     };
 
-    for resource_type in registry.list_resource_types() {
-        let param_count = registry.get_all_for_type(&resource_type).len();
+    for resource_type in &resource_types {
+        let param_count = registry.get_all_for_type(resource_type).len();
         suggestions.resources.push(AutocompleteSuggestion {
             id: format!("resource:{}", resource_type),
             kind: SuggestionKind::Resource,
@@ -136,8 +164,11 @@ pub async fn build_unified_payload(state: &RestConsoleState) -> RestConsoleRespo
         }
         if op.type_level {
             for resource_type in &op.resource_types {
+                if resource_type != "Resource" && !state.offers(resource_type) {
+                    continue;
+                }
                 if resource_type == "Resource" {
-                    for rt in registry.list_resource_types() {
+                    for rt in &resource_types {
                         suggestions.type_operations.push(AutocompleteSuggestion {
                             id: format!("type-op:{}:{}", rt, code),
                             kind: SuggestionKind::TypeOp,
@@ -175,8 +206,11 @@ pub async fn build_unified_payload(state: &RestConsoleState) -> RestConsoleRespo
         }
         if op.instance {
             for resource_type in &op.resource_types {
+                if resource_type != "Resource" && !state.offers(resource_type) {
+                    continue;
+                }
                 if resource_type == "Resource" {
-                    for rt in registry.list_resource_types() {
+                    for rt in &resource_types {
                         suggestions
                             .instance_operations
                             .push(AutocompleteSuggestion {
@@ -294,32 +328,17 @@ pub async fn build_unified_payload(state: &RestConsoleState) -> RestConsoleRespo
 
     // === Build search params by resource (flat map for autocomplete) ===
     let mut search_params: HashMap<String, Vec<SearchParamSuggestion>> = HashMap::new();
-    for resource_type in registry.list_resource_types() {
-        let params = registry.get_all_for_type(&resource_type);
-        let mut param_suggestions: Vec<SearchParamSuggestion> = params
-            .iter()
-            .map(|param| SearchParamSuggestion {
-                code: param.code.clone(),
-                search_type: format_param_type(&param.param_type),
-                description: if param.description.is_empty() {
-                    None
-                } else {
-                    Some(param.description.clone())
-                },
-                modifiers: get_modifier_suggestions(param, &state.fhir_version),
-                comparators: param.comparator.clone(),
-                targets: param.target.clone(),
-                is_common: param.is_common(),
-            })
-            .collect();
-        param_suggestions.sort_by(|a, b| a.code.cmp(&b.code));
-        search_params.insert(resource_type.to_string(), param_suggestions);
+    for resource_type in &resource_types {
+        search_params.insert(
+            resource_type.to_string(),
+            search_param_suggestions(registry, resource_type, &state.fhir_version),
+        );
     }
 
     // === Build enriched resource capabilities ===
     let mut resources = Vec::new();
-    for resource_type in registry.list_resource_types() {
-        let params = registry.get_all_for_type(&resource_type);
+    for resource_type in &resource_types {
+        let params = registry.get_all_for_type(resource_type);
 
         let enriched_params: Vec<EnrichedSearchParam> = params
             .iter()
@@ -350,11 +369,11 @@ pub async fn build_unified_payload(state: &RestConsoleState) -> RestConsoleRespo
             .collect();
 
         let includes = compute_includes(&params);
-        let rev_includes = compute_rev_includes(registry, &resource_type);
+        let rev_includes = compute_rev_includes(registry, resource_type);
         let sort_params = compute_sort_params(&params);
-        let type_operations = filter_operations_enriched(&operations, &resource_type, true, false);
+        let type_operations = filter_operations_enriched(&operations, resource_type, true, false);
         let instance_operations =
-            filter_operations_enriched(&operations, &resource_type, false, true);
+            filter_operations_enriched(&operations, resource_type, false, true);
 
         resources.push(ResourceCapability {
             resource_type: resource_type.to_string(),
@@ -598,6 +617,35 @@ fn is_r5_or_later(fhir_version: &str) -> bool {
     matches!(v.as_str(), "R5" | "R6" | "5.0.0" | "6.0.0")
 }
 
+/// Supported search parameters for a resource type, sorted by code.
+///
+/// Shared by the REST console payload and the `$search-params` operation.
+pub fn search_param_suggestions(
+    registry: &SearchParameterRegistry,
+    resource_type: &str,
+    fhir_version: &str,
+) -> Vec<SearchParamSuggestion> {
+    let mut suggestions: Vec<SearchParamSuggestion> = registry
+        .get_all_for_type(resource_type)
+        .iter()
+        .map(|param| SearchParamSuggestion {
+            code: param.code.clone(),
+            search_type: format_param_type(&param.param_type),
+            description: if param.description.is_empty() {
+                None
+            } else {
+                Some(param.description.clone())
+            },
+            modifiers: get_modifier_suggestions(param, fhir_version),
+            comparators: param.comparator.clone(),
+            targets: param.target.clone(),
+            is_common: param.is_common(),
+        })
+        .collect();
+    suggestions.sort_by(|a, b| a.code.cmp(&b.code));
+    suggestions
+}
+
 /// Return modifier suggestions for a search parameter, respecting FHIR version.
 /// If the parameter has explicit modifiers, use those; otherwise infer from the parameter type.
 fn get_modifier_suggestions(
@@ -752,23 +800,23 @@ struct SuggestionMetadata {
 }
 
 #[derive(Clone, Serialize, Hash)]
-struct SearchParamSuggestion {
-    code: String,
+pub struct SearchParamSuggestion {
+    pub code: String,
     #[serde(rename = "type")]
-    search_type: String,
+    pub search_type: String,
     #[serde(skip_serializing_if = "Option::is_none")]
-    description: Option<String>,
-    modifiers: Vec<ModifierSuggestion>,
-    comparators: Vec<String>,
-    targets: Vec<String>,
-    is_common: bool,
+    pub description: Option<String>,
+    pub modifiers: Vec<ModifierSuggestion>,
+    pub comparators: Vec<String>,
+    pub targets: Vec<String>,
+    pub is_common: bool,
 }
 
 #[derive(Clone, Serialize, Hash)]
-struct ModifierSuggestion {
-    code: String,
+pub struct ModifierSuggestion {
+    pub code: String,
     #[serde(skip_serializing_if = "Option::is_none")]
-    description: Option<String>,
+    pub description: Option<String>,
 }
 
 // Internal operation metadata (for building suggestions)
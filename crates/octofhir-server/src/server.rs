@@ -1,6 +1,7 @@
 use std::collections::{HashMap, HashSet};
 use std::net::SocketAddr;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 use anyhow::Context;
 use arc_swap::ArcSwap;
@@ -12,6 +13,9 @@ use axum::{
     routing::{get, post},
 };
 use octofhir_auth::extractors::BasicAuthState;
+use octofhir_auth::federation::{
+    ExternalTokenValidator, ProviderJwksCache, ProviderJwksCacheConfig,
+};
 use octofhir_auth::middleware::AuthState;
 use octofhir_auth::policy::{
     PolicyCache, PolicyChangeNotifier, PolicyEvaluator, PolicyEvaluatorConfig, PolicyReloadService,
@@ -39,11 +43,12 @@ use tower_http::compression::CompressionLayer;
 use crate::events::{RedisEventSyncBuilder, RedisPublishHook};
 use crate::hooks::{
     AsyncAuditHook, GatewayReloadHook, GraphQLSubscriptionHook, HookRegistry, PolicyReloadHook,
-    SearchParamHook,
+    ResourceCacheHook, SearchParamHook,
 };
 use crate::operation_registry::OperationRegistryService;
 use crate::operations::{DynOperationHandler, OperationRegistry, register_core_operations_all};
 use crate::reference_resolver::StorageReferenceResolver;
+use crate::storage_metrics::MeteredStorage;
 use crate::subscriptions::{SubscriptionHook, SubscriptionState};
 use crate::validation::ValidationService;
 
@@ -55,7 +60,7 @@ use octofhir_db_postgres::PostgresConfig;
 use octofhir_db_postgres::PostgresStorage;
 use octofhir_fhirschema::TerminologyProviderAdapter;
 use octofhir_search::{HybridTerminologyProvider, ReloadableSearchConfig};
-use octofhir_storage::{DynStorage, EventedStorage};
+use octofhir_storage::{DynStorage, EventedStorage, FhirStorage};
 
 /// Shared model provider type for FHIRPath evaluation
 pub type SharedModelProvider = Arc<dyn ModelProvider + Send + Sync>;
@@ -82,6 +87,8 @@ pub struct AppStateInner {
     pub fhir_version: String,
     /// Base URL for the server, used in links and responses
     pub base_url: String,
+    /// Proxies allowed to override the base URL (see [`crate::forwarded`])
+    pub trusted_proxies: crate::forwarded::TrustedProxies,
     /// FHIRPath engine for FHIRPath Patch support
     pub fhirpath_engine: Arc<FhirPathEngine>,
     /// Model provider for validation, FHIRPath, LSP, and all server features
@@ -98,6 +105,9 @@ pub struct AppStateInner {
     pub db_pool: Arc<sqlx_postgres::PgPool>,
     /// Read replica pool (falls back to db_pool if no replica configured)
     pub read_db_pool: Arc<sqlx_postgres::PgPool>,
+    /// Set once the startup pool warmup is over (see `warmup_connections`);
+    /// `/readyz` reports not ready until then
+    pub db_pools_warm: Arc<AtomicBool>,
     /// Custom handler registry for gateway operations
     pub handler_registry: Arc<crate::gateway::HandlerRegistry>,
     /// Compartment registry for compartment-based search
@@ -128,6 +138,8 @@ pub struct AppStateInner {
     pub json_schema_cache: Arc<dashmap::DashMap<String, serde_json::Value>>,
     /// Cache for FHIR resource reads (reduces DB queries for read-heavy workloads)
     pub resource_cache: Option<Arc<crate::cache::ResourceCache>>,
+    /// External blob store for Binary content (None = content stays inline)
+    pub binary_blobs: Option<crate::binary::BinaryBlobs>,
     /// Query cache for search SQL template reuse
     pub query_cache: Option<Arc<octofhir_search::QueryCache>>,
     /// Cached resource types for fast validation
@@ -155,6 +167,10 @@ pub struct AppStateInner {
     /// Shared anonymous AuthContext used in anonymous-access mode. Built once
     /// at startup so the auth middleware just `Arc::clone`s it.
     pub anonymous_auth_context: Arc<octofhir_auth::middleware::AuthContext>,
+    /// Per-client request quota (None = rate limiting disabled)
+    pub rate_limiter: Option<Arc<crate::rate_limit::RateLimiter>>,
+    /// Cache shared across instances (Redis when enabled, local otherwise)
+    pub cache_backend: crate::cache::CacheBackend,
     // pub automation_state: Option<crate::automations::AutomationState>,
 }
 
@@ -188,7 +204,9 @@ impl FromRef<AppState> for crate::middleware::CombinedAuthState {
             jwt_cache: state.jwt_cache.clone(),
             policy_evaluator: state.policy_evaluator.clone(),
             anonymous_access: state.config.auth.policy.anonymous_access,
+            fhir_mime_version: state.config.fhir.mime_version(),
             anonymous_context: state.anonymous_auth_context.clone(),
+            expose_deny_reason: state.config.auth.policy.expose_deny_reason,
         }
     }
 }
@@ -199,6 +217,7 @@ impl FromRef<AppState> for AuthorizationState {
             state.policy_evaluator.clone(),
             state.operation_registry.clone(),
         )
+        .with_expose_deny_reason(state.config.auth.policy.expose_deny_reason)
     }
 }
 
@@ -248,6 +267,116 @@ impl FromRef<AppState> for crate::automations::AutomationState {
 pub struct OctofhirServer {
     addr: SocketAddr,
     app: Router,
+    shutdown: Arc<ShutdownCoordinator>,
+}
+
+/// Coordinates graceful shutdown.
+///
+/// Counts in-flight requests and, once a shutdown signal arrives, drains the
+/// server in order: stop accepting connections and let in-flight requests
+/// finish, let running async jobs finish, then flush queued audit events and
+/// event hooks. Every phase shares one deadline (`server.shutdown_timeout_ms`);
+/// whatever is still running when it passes is logged and abandoned.
+pub struct ShutdownCoordinator {
+    timeout: std::time::Duration,
+    requests: octofhir_core::InFlight,
+    async_jobs: Arc<crate::async_jobs::AsyncJobManager>,
+    audit_service: Arc<AuditService>,
+    hook_registry: Arc<HookRegistry>,
+}
+
+impl ShutdownCoordinator {
+    const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(50);
+
+    fn new(
+        timeout: std::time::Duration,
+        async_jobs: Arc<crate::async_jobs::AsyncJobManager>,
+        audit_service: Arc<AuditService>,
+        hook_registry: Arc<HookRegistry>,
+    ) -> Self {
+        Self {
+            timeout,
+            requests: octofhir_core::InFlight::new(),
+            async_jobs,
+            audit_service,
+            hook_registry,
+        }
+    }
+
+    /// Number of requests currently being handled.
+    pub fn in_flight_requests(&self) -> usize {
+        self.requests.count()
+    }
+
+    /// Queued audit writes plus undelivered hook events.
+    fn pending_events(&self) -> usize {
+        self.audit_service.pending() + self.hook_registry.pending()
+    }
+
+    /// Drain after the server has been told to stop accepting connections.
+    ///
+    /// `server` is the task running `axum::serve` with graceful shutdown
+    /// already triggered; it is aborted if requests outlive the deadline.
+    async fn drain(&self, mut server: tokio::task::JoinHandle<std::io::Result<()>>) {
+        let deadline = tokio::time::Instant::now() + self.timeout;
+        self.async_jobs.stop_accepting();
+        tracing::info!(
+            in_flight_requests = self.in_flight_requests(),
+            running_jobs = self.async_jobs.active_jobs(),
+            timeout_ms = self.timeout.as_millis() as u64,
+            "Shutdown started, draining in-flight work"
+        );
+
+        match tokio::time::timeout_at(deadline, &mut server).await {
+            Ok(Ok(Ok(()))) => {}
+            Ok(Ok(Err(e))) => tracing::warn!(error = %e, "Server exited with error while draining"),
+            Ok(Err(e)) => tracing::warn!(error = %e, "Server task failed while draining"),
+            Err(_) => {}
+        }
+
+        self.wait_until(deadline, || self.async_jobs.active_jobs() == 0)
+            .await;
+        self.wait_until(deadline, || self.pending_events() == 0)
+            .await;
+
+        let requests = self.in_flight_requests();
+        let jobs = self.async_jobs.active_jobs();
+        let events = self.pending_events();
+        if requests + jobs + events > 0 {
+            server.abort();
+            tracing::warn!(
+                in_flight_requests = requests,
+                running_jobs = jobs,
+                pending_events = events,
+                "Shutdown timeout reached, exiting with work still running"
+            );
+        } else {
+            tracing::info!("Shutdown drained cleanly");
+        }
+
+        if let Err(e) = self.hook_registry.on_shutdown().await {
+            tracing::warn!(error = %e, "Error during hook shutdown");
+        }
+    }
+
+    async fn wait_until(&self, deadline: tokio::time::Instant, done: impl Fn() -> bool) {
+        while !done() && tokio::time::Instant::now() < deadline {
+            tokio::time::sleep_until(
+                deadline.min(tokio::time::Instant::now() + Self::POLL_INTERVAL),
+            )
+            .await;
+        }
+    }
+}
+
+/// Middleware counting requests in flight for [`ShutdownCoordinator`].
+async fn track_in_flight(
+    State(shutdown): State<Arc<ShutdownCoordinator>>,
+    request: axum::extract::Request,
+    next: middleware::Next,
+) -> axum::response::Response {
+    let _in_flight = shutdown.requests.enter();
+    next.run(request).await
 }
 
 /// Handler for WebSocket subscription events endpoint.
@@ -333,7 +462,15 @@ async fn bootstrap_conformance_if_postgres(
     // This includes all resource-kind and logical-kind StructureDefinitions.
     // Must complete BEFORE the auth bootstraps because they write into the
     // User / Client / AccessPolicy tables managed by this routine.
-    let fcm_storage = octofhir_db_postgres::PostgresPackageStore::new((*pool).clone());
+    let compression = cfg
+        .storage
+        .postgres
+        .as_ref()
+        .map(|pg| pg.resource_compression)
+        .unwrap_or_default();
+    let fcm_storage = octofhir_db_postgres::PostgresPackageStore::new((*pool).clone())
+        .with_compression(compression)
+        .with_resource_types(cfg.fhir.resource_types.clone());
     match fcm_storage.ensure_resource_tables().await {
         Ok(count) => {
             tracing::info!(
@@ -349,6 +486,31 @@ async fn bootstrap_conformance_if_postgres(
         }
     }
 
+    // Storage serves only the allowed types, plus the internal ones the
+    // server itself reads and writes.
+    if !cfg.fhir.resource_types.is_empty() {
+        let internal = fcm_storage
+            .internal_resource_types()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to load internal resource types: {e}"))?;
+        octofhir_storage::set_resource_type_allowlist(
+            cfg.fhir.resource_types.iter().cloned().chain(internal),
+        );
+    }
+
+    // Tenant schemas copy the resource tables created above, so they are
+    // provisioned right after them. A missing tenant schema would silently
+    // fall back to shared data, hence this is fatal.
+    if cfg.multitenancy.enabled {
+        crate::tenant::provision_tenants(&pool, &cfg.multitenancy, compression)
+            .await
+            .map_err(anyhow::Error::msg)?;
+        tracing::info!(
+            tenants = cfg.multitenancy.tenants.len(),
+            "Tenant schemas provisioned"
+        );
+    }
+
     // Fan-out remaining bootstrap steps. Each one operates on a different
     // FHIR resource table (Client / User / AccessPolicy / Client policy) and
     // is fully idempotent (existence check + insert), so they don't
@@ -522,9 +684,9 @@ async fn create_redis_pool(
     Ok(pool)
 }
 
-/// Creates PostgreSQL storage.
+/// Creates PostgreSQL storage and starts warming its pools in the background.
 ///
-/// Returns (PostgresStorage, primary pool, read pool).
+/// Returns (PostgresStorage, primary pool, read pool, warmup done flag).
 /// The caller is responsible for wrapping with EventedStorage and Arc if needed.
 async fn create_storage(
     cfg: &AppConfig,
@@ -533,6 +695,7 @@ async fn create_storage(
         PostgresStorage,
         Arc<sqlx_postgres::PgPool>,
         Arc<sqlx_postgres::PgPool>,
+        Arc<AtomicBool>,
     ),
     anyhow::Error,
 > {
@@ -544,40 +707,68 @@ async fn create_storage(
 
     let postgres_config = PostgresConfig::new(pg_cfg.connection_url())
         .with_pool_size(pg_cfg.pool_size)
+        .with_min_connections(pg_cfg.min_connections)
+        .with_warmup_connections(pg_cfg.warmup_connections)
         .with_connect_timeout_ms(pg_cfg.connect_timeout_ms)
+        .with_acquire_timeout_ms(pg_cfg.acquire_timeout_ms)
         .with_idle_timeout_ms(pg_cfg.idle_timeout_ms)
-        .with_run_migrations(true);
+        .with_run_migrations(true)
+        .with_resource_compression(pg_cfg.resource_compression)
+        .with_upgrade_full_text_columns(pg_cfg.upgrade_full_text_columns)
+        .with_tenant_isolation(cfg.multitenancy.enabled);
 
-    let mut pg_storage = PostgresStorage::new(postgres_config)
+    let mut pg_storage = PostgresStorage::new(postgres_config.clone())
         .await
         .map_err(|e| anyhow::anyhow!("Failed to create PostgreSQL storage: {e}"))?;
 
     let primary_pool = Arc::new(pg_storage.pool().clone());
+    let mut warmups = vec![((*primary_pool).clone(), postgres_config)];
 
     // Create read replica pool if configured
     let read_pool = if let Some(ref replica_cfg) = pg_cfg.read_replica {
         tracing::info!(url = %octofhir_db_postgres::pool::mask_password(&replica_cfg.url), "Creating read replica pool");
         let replica_config = PostgresConfig::new(&replica_cfg.url)
             .with_pool_size(replica_cfg.pool_size.unwrap_or(pg_cfg.pool_size))
+            .with_min_connections(pg_cfg.min_connections)
+            .with_warmup_connections(pg_cfg.warmup_connections)
             .with_connect_timeout_ms(
                 replica_cfg
                     .connect_timeout_ms
                     .unwrap_or(pg_cfg.connect_timeout_ms),
             )
+            .with_acquire_timeout_ms(replica_cfg.acquire_timeout_ms.or(pg_cfg.acquire_timeout_ms))
             .with_idle_timeout_ms(replica_cfg.idle_timeout_ms.or(pg_cfg.idle_timeout_ms))
-            .with_run_migrations(false);
+            .with_run_migrations(false)
+            .with_tenant_isolation(cfg.multitenancy.enabled);
 
         let replica_pool = octofhir_db_postgres::pool::create_pool(&replica_config)
             .await
             .map_err(|e| anyhow::anyhow!("Failed to create read replica pool: {e}"))?;
 
         pg_storage.set_read_pool(replica_pool.clone());
+        warmups.push((replica_pool.clone(), replica_config));
         Arc::new(replica_pool)
     } else {
         primary_pool.clone()
     };
 
-    Ok((pg_storage, primary_pool, read_pool))
+    // Warm while the rest of startup (packages, registries) runs. A failed
+    // warmup only costs latency, so it still ends the readiness wait.
+    let warmup = pg_cfg.warmup_connections > 0;
+    let pools_warm = Arc::new(AtomicBool::new(!warmup));
+    if warmup {
+        let pools_warm = pools_warm.clone();
+        tokio::spawn(async move {
+            for (pool, config) in warmups {
+                if let Err(e) = octofhir_db_postgres::pool::warm_pool(&pool, &config).await {
+                    tracing::warn!(error = %e, "PostgreSQL connection pool warmup failed");
+                }
+            }
+            pools_warm.store(true, Ordering::Relaxed);
+        });
+    }
+
+    Ok((pg_storage, primary_pool, read_pool, pools_warm))
 }
 
 /// Builds the application router with the given configuration.
@@ -585,8 +776,36 @@ pub async fn build_app(
     cfg: &AppConfig,
     config_manager: Arc<octofhir_config::ConfigurationManager>,
 ) -> Result<Router, anyhow::Error> {
+    build_app_with_shutdown(cfg, config_manager)
+        .await
+        .map(|(app, _)| app)
+}
+
+/// Wraps `inner` with the metrics and event decorators. Subscription
+/// signing secrets are moved to `db_pool` below all of them.
+fn wrap_storage<S: FhirStorage + 'static>(
+    inner: S,
+    db_pool: &sqlx_postgres::PgPool,
+    event_broadcaster: &Arc<EventBroadcaster>,
+) -> DynStorage {
+    let inner = crate::subscriptions::SubscriptionSecretStorage::new(
+        inner,
+        crate::subscriptions::SubscriptionSecrets::new(db_pool.clone()),
+    );
+    Arc::new(EventedStorage::new(
+        MeteredStorage::new(inner),
+        event_broadcaster.clone(),
+    ))
+}
+
+/// Like [`build_app`], also returning the coordinator that drains the app on
+/// shutdown.
+pub async fn build_app_with_shutdown(
+    cfg: &AppConfig,
+    config_manager: Arc<octofhir_config::ConfigurationManager>,
+) -> Result<(Router, Arc<ShutdownCoordinator>), anyhow::Error> {
     let body_limit = cfg.server.body_limit_bytes;
-    let (pg_storage, db_pool, read_db_pool) = create_storage(cfg).await?;
+    let (pg_storage, db_pool, read_db_pool, db_pools_warm) = create_storage(cfg).await?;
 
     if crate::canonical::get_manager().is_none() && cfg.storage.postgres.is_some() {
         let registry = crate::canonical::init_from_config_async(cfg)
@@ -601,9 +820,25 @@ pub async fn build_app(
     // Save the search registry slot for late initialization (after parallel init)
     let search_registry_slot = pg_storage.search_registry_slot().clone();
 
-    // Wrap storage with EventedStorage to emit events on CRUD operations
-    let evented_storage = EventedStorage::new(pg_storage, event_broadcaster.clone());
-    let storage: DynStorage = Arc::new(evented_storage);
+    // Wrap storage with MeteredStorage to record per-operation metrics, then
+    // with EventedStorage to emit events on CRUD operations. With an external
+    // Binary backend, BinaryBlobStorage sits innermost so metrics include blob
+    // I/O and events carry the full content.
+    let binary_blobs = crate::binary::BinaryBlobs::from_config(&cfg.binary, &cfg.base_url())
+        .map_err(|e| anyhow::anyhow!(e))?;
+    let storage: DynStorage = match &binary_blobs {
+        Some(blobs) => {
+            tracing::info!(
+                backend = blobs.store().backend_name(),
+                threshold_bytes = cfg.binary.threshold_bytes,
+                attachments = cfg.binary.attachments.enabled,
+                "Binary content stored in external blob store"
+            );
+            let blob_storage = crate::binary::BinaryBlobStorage::new(pg_storage, blobs.clone());
+            wrap_storage(blob_storage, &db_pool, &event_broadcaster)
+        }
+        None => wrap_storage(pg_storage, &db_pool, &event_broadcaster),
+    };
     tracing::info!("Event broadcaster initialized, storage wrapped with EventedStorage");
 
     // Create PostgreSQL package store for FHIR package management
@@ -612,12 +847,11 @@ pub async fn build_app(
     ));
 
     // Parse FHIR version early - used by both model provider and GraphQL
-    let fhir_version = match cfg.fhir.version.as_str() {
-        "R4" | "4.0" | "4.0.1" => FhirVersion::R4,
-        "R4B" | "4.3" | "4.3.0" => FhirVersion::R4B,
-        "R5" | "5.0" | "5.0.0" => FhirVersion::R5,
-        "R6" | "6.0" => FhirVersion::R6,
-        _ => FhirVersion::R4, // Default to R4
+    let fhir_version = match cfg.fhir.release() {
+        "4.3.0" => FhirVersion::R4B,
+        "5.0.0" => FhirVersion::R5,
+        "6.0.0" => FhirVersion::R6,
+        _ => FhirVersion::R4,
     };
 
     // ── Phase 1: Create lightweight components (instant, no I/O) ──
@@ -678,7 +912,25 @@ pub async fn build_app(
     let policy_cache = Arc::new(PolicyCache::new(policy_storage, Duration::minutes(5)));
 
     // Async job manager (instant)
-    let async_job_config = crate::async_jobs::AsyncJobConfig::default();
+    let async_job_config = crate::async_jobs::AsyncJobConfig {
+        job_limits: HashMap::from([
+            (
+                "bulk_export".to_string(),
+                crate::async_jobs::JobLimits {
+                    max_running: cfg.bulk_export.max_concurrent_jobs,
+                    max_running_per_client: cfg.bulk_export.max_concurrent_jobs_per_client,
+                },
+            ),
+            (
+                "bulk_import".to_string(),
+                crate::async_jobs::JobLimits {
+                    max_running: cfg.bulk_import.max_concurrent_jobs,
+                    max_running_per_client: cfg.bulk_import.max_concurrent_jobs_per_client,
+                },
+            ),
+        ]),
+        ..Default::default()
+    };
     let async_job_manager = Arc::new(crate::async_jobs::AsyncJobManager::new(
         db_pool.clone(),
         async_job_config,
@@ -686,7 +938,9 @@ pub async fn build_app(
     tracing::info!("Async job manager initialized");
 
     // Gateway router (creation is instant, route loading is async — done in Phase 2)
-    let gateway_router = Arc::new(crate::gateway::GatewayRouter::new());
+    let gateway_router = Arc::new(crate::gateway::GatewayRouter::with_http_config(
+        &cfg.http_client,
+    ));
 
     // ── Phase 2: Parallel heavy I/O operations ──
     // These are all independent: they need only db_pool, cfg, or canonical_manager
@@ -696,6 +950,8 @@ pub async fn build_app(
         default_count: cfg.search.default_count,
         max_count: cfg.search.max_count,
         cache_capacity: cfg.search.cache_capacity,
+        default_sort: cfg.search.default_sort(),
+        link_style: cfg.search.link_style,
     };
 
     let canonical_manager = crate::canonical::get_manager()
@@ -793,6 +1049,37 @@ pub async fn build_app(
     )
     .await;
 
+    // Precomputed index tables for hot string params (opt-in via
+    // `search.materialized_params`), after the functional indexes so the
+    // registry entries already carry their typed extraction functions.
+    if !cfg.search.materialized_params.is_empty() {
+        octofhir_db_postgres::create_search_index_tables(
+            &db_pool,
+            &cfg_snapshot.registry,
+            &cfg.search.materialized_params,
+            model_provider.as_ref(),
+        )
+        .await;
+        // Searches inside a tenant scope read the tenant schema's copies.
+        if cfg.multitenancy.enabled {
+            for tenant in &cfg.multitenancy.tenants {
+                let Ok(tenant) = octofhir_storage::TenantId::new(tenant.as_str()) else {
+                    continue;
+                };
+                octofhir_storage::with_tenant(
+                    tenant,
+                    octofhir_db_postgres::create_search_index_tables(
+                        &db_pool,
+                        &cfg_snapshot.registry,
+                        &cfg.search.materialized_params,
+                        model_provider.as_ref(),
+                    ),
+                )
+                .await;
+            }
+        }
+    }
+
     // Targeted partial composite indexes (opt-in via `search.composite_index`).
     if !cfg.search.composite_index.is_empty() {
         let composite_specs: Vec<octofhir_db_postgres::CompositePartialIndex> = cfg
@@ -1031,6 +1318,81 @@ pub async fn build_app(
                 affects_state: false,
             });
             tracing::info!("Registered $fhirpath operation");
+            // Register $convert operation (built-in R4/R4B conversion)
+            registry.register(crate::operations::OperationDefinition {
+                code: "convert".to_string(),
+                url: "http://hl7.org/fhir/OperationDefinition/Resource-convert".to_string(),
+                kind: crate::operations::OperationKind::Operation,
+                system: true,
+                type_level: false,
+                instance: false,
+                resource: vec![],
+                parameters: vec![],
+                affects_state: false,
+            });
+            // Register StructureMap $transform operation
+            registry.register(crate::operations::OperationDefinition {
+                code: "transform".to_string(),
+                url: "http://hl7.org/fhir/OperationDefinition/StructureMap-transform".to_string(),
+                kind: crate::operations::OperationKind::Operation,
+                system: false,
+                type_level: true,
+                instance: true,
+                resource: vec!["StructureMap".to_string()],
+                parameters: vec![],
+                affects_state: false,
+            });
+            tracing::info!("Registered $convert and $transform operations");
+            // Register $search-params operation
+            registry.register(crate::operations::OperationDefinition {
+                code: "search-params".to_string(),
+                url: "http://octofhir.org/OperationDefinition/search-params".to_string(),
+                kind: crate::operations::OperationKind::Operation,
+                system: true,
+                type_level: true,
+                instance: false,
+                resource: vec![], // All resource types
+                parameters: vec![],
+                affects_state: false,
+            });
+            tracing::info!("Registered $search-params operation");
+            // Register $reindex operation
+            registry.register(crate::operations::OperationDefinition {
+                code: "reindex".to_string(),
+                url: "http://octofhir.org/OperationDefinition/reindex".to_string(),
+                kind: crate::operations::OperationKind::Operation,
+                system: true,
+                type_level: true,
+                instance: false,
+                resource: vec![], // All resource types
+                parameters: vec![],
+                affects_state: true,
+            });
+            // Register $reindex-status operation
+            registry.register(crate::operations::OperationDefinition {
+                code: "reindex-status".to_string(),
+                url: "http://octofhir.org/OperationDefinition/reindex-status".to_string(),
+                kind: crate::operations::OperationKind::Operation,
+                system: true,
+                type_level: false,
+                instance: false,
+                resource: vec![],
+                parameters: vec![crate::operations::reindex::job_parameter()],
+                affects_state: false,
+            });
+            // Register system-level $cancel for async jobs
+            registry.register(crate::operations::OperationDefinition {
+                code: "cancel".to_string(),
+                url: "http://octofhir.org/OperationDefinition/cancel".to_string(),
+                kind: crate::operations::OperationKind::Operation,
+                system: true,
+                type_level: false,
+                instance: false,
+                resource: vec![],
+                parameters: vec![crate::operations::reindex::job_parameter()],
+                affects_state: true,
+            });
+            tracing::info!("Registered $reindex, $reindex-status and $cancel operations");
 
             // Register CQL operations if enabled
             if cfg.cql.enabled {
@@ -1143,6 +1505,78 @@ pub async fn build_app(
                 parameters: vec![],
                 affects_state: false,
             });
+            // Register $convert operation (built-in R4/R4B conversion)
+            registry.register(crate::operations::OperationDefinition {
+                code: "convert".to_string(),
+                url: "http://hl7.org/fhir/OperationDefinition/Resource-convert".to_string(),
+                kind: crate::operations::OperationKind::Operation,
+                system: true,
+                type_level: false,
+                instance: false,
+                resource: vec![],
+                parameters: vec![],
+                affects_state: false,
+            });
+            // Register StructureMap $transform operation
+            registry.register(crate::operations::OperationDefinition {
+                code: "transform".to_string(),
+                url: "http://hl7.org/fhir/OperationDefinition/StructureMap-transform".to_string(),
+                kind: crate::operations::OperationKind::Operation,
+                system: false,
+                type_level: true,
+                instance: true,
+                resource: vec!["StructureMap".to_string()],
+                parameters: vec![],
+                affects_state: false,
+            });
+            // Register $search-params operation
+            registry.register(crate::operations::OperationDefinition {
+                code: "search-params".to_string(),
+                url: "http://octofhir.org/OperationDefinition/search-params".to_string(),
+                kind: crate::operations::OperationKind::Operation,
+                system: true,
+                type_level: true,
+                instance: false,
+                resource: vec![], // All resource types
+                parameters: vec![],
+                affects_state: false,
+            });
+            // Register $reindex operation
+            registry.register(crate::operations::OperationDefinition {
+                code: "reindex".to_string(),
+                url: "http://octofhir.org/OperationDefinition/reindex".to_string(),
+                kind: crate::operations::OperationKind::Operation,
+                system: true,
+                type_level: true,
+                instance: false,
+                resource: vec![], // All resource types
+                parameters: vec![],
+                affects_state: true,
+            });
+            // Register $reindex-status operation
+            registry.register(crate::operations::OperationDefinition {
+                code: "reindex-status".to_string(),
+                url: "http://octofhir.org/OperationDefinition/reindex-status".to_string(),
+                kind: crate::operations::OperationKind::Operation,
+                system: true,
+                type_level: false,
+                instance: false,
+                resource: vec![],
+                parameters: vec![crate::operations::reindex::job_parameter()],
+                affects_state: false,
+            });
+            // Register system-level $cancel for async jobs
+            registry.register(crate::operations::OperationDefinition {
+                code: "cancel".to_string(),
+                url: "http://octofhir.org/OperationDefinition/cancel".to_string(),
+                kind: crate::operations::OperationKind::Operation,
+                system: true,
+                type_level: false,
+                instance: false,
+                resource: vec![],
+                parameters: vec![crate::operations::reindex::job_parameter()],
+                affects_state: true,
+            });
 
             // Register CQL operations if enabled
             if cfg.cql.enabled {
@@ -1315,6 +1749,44 @@ pub async fn build_app(
         );
     }
 
+    // Start scheduled pruning of old resource versions
+    if cfg.history_retention.enabled {
+        let tenants = if cfg.multitenancy.enabled {
+            cfg.multitenancy
+                .tenants
+                .iter()
+                .filter_map(|t| octofhir_storage::TenantId::new(t.as_str()).ok())
+                .collect()
+        } else {
+            Vec::new()
+        };
+        let _history_retention_handle = crate::history_retention::spawn_history_retention(
+            db_pool.as_ref().clone(),
+            cfg.history_retention.clone(),
+            tenants,
+            binary_blobs.clone(),
+        );
+        tracing::info!(
+            interval_secs = cfg.history_retention.interval_secs,
+            overrides = cfg.history_retention.resource_types.len(),
+            "History retention task started"
+        );
+    }
+
+    // Start the sweep of blobs no resource version references any more
+    if let Some(blobs) = binary_blobs.as_ref().filter(|_| cfg.binary.gc.enabled) {
+        let _blob_gc_handle = crate::binary::gc::spawn_blob_gc(
+            db_pool.as_ref().clone(),
+            blobs.clone(),
+            cfg.binary.gc.clone(),
+        );
+        tracing::info!(
+            interval_secs = cfg.binary.gc.interval_secs,
+            grace_secs = cfg.binary.gc.grace_secs,
+            "Blob sweep task started"
+        );
+    }
+
     // Create policy change notifier and reload service
     let policy_notifier = Arc::new(PolicyChangeNotifier::new(64));
     let reload_config = ReloadConfig::default();
@@ -1353,9 +1825,16 @@ pub async fn build_app(
 
     // Create GraphQL state if enabled (schema build was started early)
     let (graphql_state, graphql_subscription_broadcaster) = if let Some(lazy_schema) = lazy_schema {
-        // Create PostgresStorage from the pool for GraphQL
-        let graphql_storage: octofhir_storage::DynStorage =
-            Arc::new(PostgresStorage::from_pool(db_pool.as_ref().clone()));
+        // Create PostgresStorage from the pool for GraphQL, moving Binary
+        // content through the blob store like the REST storage stack does
+        let graphql_pg = PostgresStorage::from_pool(db_pool.as_ref().clone());
+        let graphql_storage: octofhir_storage::DynStorage = match &binary_blobs {
+            Some(blobs) => Arc::new(crate::binary::BinaryBlobStorage::new(
+                graphql_pg,
+                blobs.clone(),
+            )),
+            None => Arc::new(graphql_pg),
+        };
 
         // Create subscription broadcaster for real-time updates
         let subscription_broadcaster =
@@ -1367,6 +1846,10 @@ pub async fn build_app(
             search_config: (*search_config.config()).clone(),
             policy_evaluator: policy_evaluator.clone(),
             subscription_broadcaster: Some(subscription_broadcaster.clone()),
+            validator: cfg.graphql.validate_mutations.then(|| {
+                Arc::new(validation_service.clone()) as octofhir_graphql::DynResourceValidator
+            }),
+            max_resource_size: cfg.server.max_resource_size_bytes,
         };
 
         let state = GraphQLState {
@@ -1511,12 +1994,18 @@ pub async fn build_app(
 
     // Build CapabilityStatement at startup (cached for /metadata requests)
     // Reuses the search registry that was already loaded by ReloadableSearchConfig
+    let capability_types: Vec<String> = resource_types
+        .iter()
+        .filter(|rt| cfg.fhir.is_resource_type_allowed(rt))
+        .cloned()
+        .collect();
     let mut capability_statement = handlers::build_capability_statement(
         &cfg.fhir.version,
         &cfg.base_url(),
         &db_pool,
-        &resource_types,
+        &capability_types,
         &search_config.config().registry,
+        &cfg.operations,
     )
     .await;
 
@@ -1533,7 +2022,7 @@ pub async fn build_app(
 
     // Register hooks for unified event system
     // Hooks run asynchronously with isolation (timeout + panic recovery)
-    let hook_registry = HookRegistry::new();
+    let hook_registry = HookRegistry::new().with_delivery_overrides(cfg.events.delivery.clone());
 
     // PolicyReloadHook: triggers policy cache reload on AccessPolicy changes
     let policy_hook = PolicyReloadHook::new(policy_notifier.clone());
@@ -1552,6 +2041,19 @@ pub async fn build_app(
     let search_hook = SearchParamHook::new(search_config.clone());
     hook_registry.register_resource(Arc::new(search_hook)).await;
 
+    // ResourceCacheHook: evicts changed resources from the read cache, for
+    // local writes of any kind and for events synced from other instances
+    let resource_cache = (cfg.cache.resource_ttl_secs > 0).then(|| {
+        Arc::new(crate::cache::ResourceCache::new(
+            cfg.cache.local_cache_max_entries as u64,
+            std::time::Duration::from_secs(cfg.cache.resource_ttl_secs),
+        ))
+    });
+    if let Some(ref cache) = resource_cache {
+        let cache_hook = ResourceCacheHook::new(cache.clone());
+        hook_registry.register_resource(Arc::new(cache_hook)).await;
+    }
+
     // GraphQLSubscriptionHook: forwards events to GraphQL subscription clients
     if let Some(ref graphql_broadcaster) = graphql_subscription_broadcaster {
         let graphql_hook = GraphQLSubscriptionHook::new(graphql_broadcaster.clone());
@@ -1696,7 +2198,27 @@ pub async fn build_app(
     // Start the event dispatcher - subscribes to broadcaster and dispatches to hooks
     let hook_registry = Arc::new(hook_registry);
     let dispatcher = octofhir_core::events::HookDispatcher::new(hook_registry.clone());
-    tokio::spawn(dispatcher.run(event_broadcaster.subscribe()));
+    let receiver = dispatcher.subscribe(&event_broadcaster);
+    tokio::spawn(dispatcher.run(receiver));
+
+    // Event outbox: record changes transactionally only while some hook needs
+    // at-least-once delivery, and relay them to those hooks
+    let at_least_once_hooks = hook_registry.at_least_once_hooks().await.len();
+    octofhir_db_postgres::event_outbox::set_outbox_enabled(&db_pool, at_least_once_hooks > 0)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to configure event outbox: {e}"))?;
+    if at_least_once_hooks > 0 {
+        octofhir_db_postgres::OutboxRelay::new(db_pool.as_ref().clone(), hook_registry.clone())
+            .with_poll_interval(std::time::Duration::from_millis(
+                cfg.events.outbox_poll_interval_ms,
+            ))
+            .with_batch_size(cfg.events.outbox_batch_size)
+            .spawn();
+        tracing::info!(
+            hooks = at_least_once_hooks,
+            "Event outbox relay started for at-least-once hooks"
+        );
+    }
     tracing::info!(
         "Unified event system: {} hooks registered, dispatcher started",
         hook_registry.hook_count().await
@@ -1710,6 +2232,19 @@ pub async fn build_app(
         octofhir_db_postgres::PostgresNotificationStorage::new(db_pool.as_ref().clone()),
     );
 
+    // Rate limit counters and introspection results live in the shared
+    // cache (Redis when enabled)
+    let cache_backend = crate::create_cache_backend(&cfg.redis).await;
+    let rate_limiter = if cfg.rate_limit.requests_per_window > 0 {
+        let limiter = crate::rate_limit::RateLimiter::new(cache_backend.clone(), &cfg.rate_limit);
+        if let Some(limiter) = &limiter {
+            let _pruning_handle = limiter.spawn_pruning();
+        }
+        limiter.map(Arc::new)
+    } else {
+        None
+    };
+
     // Create AppState wrapped in Arc for cheap cloning across all middleware/handlers
     // This is a single Arc::clone per request instead of cloning 25+ individual fields
     let state = AppState(Arc::new(AppStateInner {
@@ -1717,6 +2252,11 @@ pub async fn build_app(
         search_config,
         fhir_version: cfg.fhir.version.clone(),
         base_url: cfg.base_url(),
+        trusted_proxies: crate::forwarded::TrustedProxies::parse(&cfg.server.trusted_proxies)
+            .unwrap_or_else(|e| {
+                tracing::warn!(error = %e, "Ignoring invalid trusted proxy configuration");
+                Default::default()
+            }),
         fhirpath_engine,
         model_provider,
         fhir_operations,
@@ -1725,6 +2265,7 @@ pub async fn build_app(
         gateway_router: (*gateway_router).clone(),
         db_pool,
         read_db_pool,
+        db_pools_warm,
         handler_registry,
         compartment_registry,
         async_job_manager,
@@ -1739,14 +2280,8 @@ pub async fn build_app(
         auth_cache,
         jwt_cache,
         json_schema_cache: Arc::new(dashmap::DashMap::new()),
-        resource_cache: if cfg.cache.resource_ttl_secs > 0 {
-            Some(Arc::new(crate::cache::ResourceCache::new(
-                cfg.cache.local_cache_max_entries as u64,
-                std::time::Duration::from_secs(cfg.cache.resource_ttl_secs),
-            )))
-        } else {
-            None
-        },
+        resource_cache,
+        binary_blobs,
         query_cache: Some(Arc::new(octofhir_search::QueryCache::new(
             cfg.search.cache_capacity,
         ))),
@@ -1760,6 +2295,8 @@ pub async fn build_app(
         subscription_state,
         terminology_provider,
         anonymous_auth_context: Arc::new(octofhir_auth::middleware::AuthContext::system_anonymous()),
+        rate_limiter,
+        cache_backend,
         // automation_state,
     }));
 
@@ -1792,6 +2329,17 @@ pub async fn build_app(
                         let body = body.ok_or_else(|| "Missing job parameters".to_string())?;
                         crate::operations::execute_bulk_import(state, job_id, body).await
                     }
+                    "reindex" => {
+                        let body = body.ok_or_else(|| "Missing job parameters".to_string())?;
+                        crate::operations::execute_reindex(state, job_id, body).await
+                    }
+                    crate::admin::reference_integrity::JOB_TYPE => {
+                        let body = body.ok_or_else(|| "Missing job parameters".to_string())?;
+                        crate::admin::reference_integrity::execute_reference_integrity(
+                            state, job_id, body,
+                        )
+                        .await
+                    }
                     _ => Err(format!("Unknown job type: {}", request_type)),
                 }
             })
@@ -1804,7 +2352,27 @@ pub async fn build_app(
     state.async_job_manager.set_executor(executor);
     tracing::info!("Async job executor configured for bulk export and ViewDefinition export");
 
-    Ok(build_router(state, body_limit, cfg.server.compression))
+    // Bulk exports only write files, so running an interrupted one again is
+    // safe; group exports continue from their checkpoint
+    let job_manager = state.async_job_manager.clone();
+    tokio::spawn(async move {
+        match job_manager.resume_interrupted_jobs(&["bulk_export"]).await {
+            Ok(0) => {}
+            Ok(resumed) => tracing::info!(resumed, "Resumed interrupted bulk export jobs"),
+            Err(e) => tracing::warn!(error = %e, "Failed to resume interrupted jobs"),
+        }
+    });
+
+    let shutdown = Arc::new(ShutdownCoordinator::new(
+        std::time::Duration::from_millis(cfg.server.shutdown_timeout_ms),
+        state.async_job_manager.clone(),
+        state.audit_service.clone(),
+        hook_registry,
+    ));
+    let app = build_router(state, body_limit, cfg.server.compression).layer(
+        middleware::from_fn_with_state(shutdown.clone(), track_in_flight),
+    );
+    Ok((app, shutdown))
 }
 
 /// Creates routes for internal administrative resources.
@@ -1833,8 +2401,13 @@ fn internal_resource_routes() -> Router<AppState> {
 }
 
 fn build_router(state: AppState, body_limit: usize, compression: bool) -> Router {
+    // One CORS policy for the FHIR API and the OAuth/SMART discovery routes
+    let cors_policy = Arc::new(app_middleware::CorsPolicy::from_config(
+        &state.config.server.cors,
+    ));
+
     // Build OAuth routes if auth is enabled (these are merged AFTER middleware, as they're public)
-    let oauth_routes = build_oauth_routes(&state);
+    let oauth_routes = build_oauth_routes(&state, cors_policy.clone());
 
     // NOTE: GraphQL routes are now added directly to the main router BEFORE middleware
     // is applied, so they go through authentication. Previously they were merged after
@@ -1988,10 +2561,18 @@ fn build_router(state: AppState, body_limit: usize, compression: bool) -> Router
                     .merge(crate::admin::admin_routes())
                     .merge(crate::admin::config_routes())
                     .merge(crate::admin::audit_routes())
+                    .merge(crate::admin::search_explain_routes())
+                    .merge(crate::admin::maintenance_routes())
+                    .merge(crate::admin::policy_evaluate_routes())
+                    .merge(crate::admin::rate_limit_routes())
             } else {
                 Router::new()
                     .merge(crate::admin::admin_routes())
                     .merge(crate::admin::audit_routes())
+                    .merge(crate::admin::search_explain_routes())
+                    .merge(crate::admin::maintenance_routes())
+                    .merge(crate::admin::policy_evaluate_routes())
+                    .merge(crate::admin::rate_limit_routes())
             },
         )
         // API routes (nested under /api)
@@ -2031,6 +2612,8 @@ fn build_router(state: AppState, body_limit: usize, compression: bool) -> Router
             "/_bulk-files/{job_id}/{filename}",
             get(handlers::bulk_export_file),
         )
+        // Externalized attachment content (binary.attachments.on_read = "redirect")
+        .route("/_attachments/{hash}", get(handlers::attachment_content))
         // System search: GET /?_type=... or POST /_search
         .route("/_search", axum::routing::post(handlers::system_search))
         // System history: GET /_history
@@ -2106,6 +2689,7 @@ fn build_router(state: AppState, body_limit: usize, compression: bool) -> Router
         )
         // Metadata endpoint scoped to /fhir base
         .route("/metadata", get(handlers::metadata))
+        .route("/$versions", get(handlers::versions))
         // Combined root/system-search at base /fhir
         // GET /fhir - returns service info
         // GET /fhir?_type=Patient,Observation - system-level search
@@ -2120,14 +2704,15 @@ fn build_router(state: AppState, body_limit: usize, compression: bool) -> Router
 
     // Add GraphQL route conditionally (before middleware so it goes through auth)
     // GraphQL handlers use State<GraphQLState> which is extracted from AppState via FromRef
-    if state.graphql_state.is_some() {
+    if state.graphql_state.is_some() && state.config.operations.is_enabled("graphql") {
         router = router.route(
             "/$graphql",
             get(octofhir_graphql::graphql_handler_get).post(octofhir_graphql::graphql_handler),
         );
         fhir_router = fhir_router.route(
             "/{resource_type}/{id}/$graphql",
-            post(octofhir_graphql::instance_graphql_handler),
+            get(octofhir_graphql::instance_graphql_handler_get)
+                .post(octofhir_graphql::instance_graphql_handler),
         );
         tracing::info!(
             "GraphQL endpoints enabled: /$graphql and /fhir/{{resourceType}}/{{id}}/$graphql"
@@ -2144,8 +2729,9 @@ fn build_router(state: AppState, body_limit: usize, compression: bool) -> Router
 
     // Apply middleware stack (outer to inner):
     // DefaultBodyLimit → trace_metrics(+request_id) → cors → compression →
-    //   auth_combined(+content_negotiation) → audit → handler
-    // 6 layers total (down from 10), reducing Tower BoxCloneSyncService clone overhead.
+    //   slow_request → query_cancel → request_timeout → anonymous_rate_limit →
+    //   auth_combined(+content_negotiation) → rate_limit → tenant → feature_flags → query_budget →
+    //   async_job_client → audit → handler
     // Note: Layers wrap from outside-in, so first .layer() is closest to handler.
 
     router = router
@@ -2154,10 +2740,54 @@ fn build_router(state: AppState, body_limit: usize, compression: bool) -> Router
             state.clone(),
             app_middleware::audit_middleware,
         ))
+        // Client that async jobs submitted by the request count against
+        .layer(middleware::from_fn(
+            app_middleware::async_job_client_middleware,
+        ))
+        // Per-request database query budget
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            app_middleware::query_budget_middleware,
+        ))
+        // Feature flags evaluated for the caller (inside the tenant scope)
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            app_middleware::feature_flags_middleware,
+        ))
+        // Tenant scope, resolved from the validated access token
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            app_middleware::tenant_middleware,
+        ))
+        // Per-client quota with RateLimit-* response headers
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            app_middleware::rate_limit_middleware,
+        ))
         // Combined auth (authn + authz) — single layer instead of two
         .layer(middleware::from_fn_with_state(
             state.clone(),
             app_middleware::auth_middleware,
+        ))
+        // Quota of requests without credentials, counted before auth runs
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            app_middleware::anonymous_rate_limit_middleware,
+        ))
+        // Request deadline: 504 and canceled statements once it passes
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            app_middleware::request_timeout_middleware,
+        ))
+        // Cancel running statements when the client disconnects
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            app_middleware::query_cancel_middleware,
+        ))
+        // Slow-request log with its auth/query/serialization breakdown
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            app_middleware::slow_request_middleware,
         ));
 
     let router = if compression {
@@ -2170,7 +2800,10 @@ fn build_router(state: AppState, body_limit: usize, compression: bool) -> Router
         router
     };
     let router: Router = router
-        .layer(middleware::from_fn(app_middleware::dynamic_cors_middleware))
+        .layer(middleware::from_fn_with_state(
+            cors_policy,
+            app_middleware::dynamic_cors_middleware,
+        ))
         // Combined tracing + metrics + request ID (replaces separate TraceLayer + metrics_middleware)
         .layer(middleware::from_fn(
             app_middleware::trace_metrics_middleware,
@@ -2190,7 +2823,10 @@ fn build_router(state: AppState, body_limit: usize, compression: bool) -> Router
 }
 
 /// Build OAuth routes.
-fn build_oauth_routes(state: &AppState) -> Option<Router> {
+fn build_oauth_routes(
+    state: &AppState,
+    cors_policy: Arc<app_middleware::CorsPolicy>,
+) -> Option<Router> {
     let auth_state = &state.auth_state;
 
     // Create OAuth state using the JWT service from auth state
@@ -2223,8 +2859,12 @@ fn build_oauth_routes(state: &AppState) -> Option<Router> {
             .merge(authorize_router)
             .merge(launch_router)
             .nest("/fhir", fhir_discovery)
-            // Apply CORS middleware to OAuth routes to allow cross-origin requests
-            .layer(middleware::from_fn(app_middleware::dynamic_cors_middleware)),
+            // Apply CORS middleware to OAuth and SMART discovery routes so
+            // browser apps can fetch them, with the same policy as the FHIR API
+            .layer(middleware::from_fn_with_state(
+                cors_policy,
+                app_middleware::dynamic_cors_middleware,
+            )),
     )
 }
 
@@ -2257,29 +2897,77 @@ impl ServerBuilder {
 
     /// Builds the server asynchronously.
     pub async fn build(self) -> Result<OctofhirServer, anyhow::Error> {
-        let app = build_app(&self.config, self.config_manager).await?;
+        let (app, shutdown) = build_app_with_shutdown(&self.config, self.config_manager).await?;
 
         Ok(OctofhirServer {
             addr: self.addr,
             app,
+            shutdown,
         })
     }
 }
 
 impl OctofhirServer {
+    /// Serve until SIGTERM/Ctrl+C, then drain (see [`ShutdownCoordinator`]).
+    ///
+    /// Returns once draining is done; flushing traces with
+    /// [`crate::shutdown_tracing`] is left to the caller so the drain summary
+    /// is exported too.
     pub async fn run(self) -> anyhow::Result<()> {
         let listener = tokio::net::TcpListener::bind(self.addr).await?;
         tracing::info!("listening on {}", self.addr);
-        axum::serve(listener, self.app)
-            .with_graceful_shutdown(shutdown_signal())
-            .await?;
+
+        let (stop_tx, stop_rx) = tokio::sync::oneshot::channel::<()>();
+        let app = self.app;
+        let mut server = tokio::spawn(async move {
+            axum::serve(
+                listener,
+                app.into_make_service_with_connect_info::<std::net::SocketAddr>(),
+            )
+            .with_graceful_shutdown(async move {
+                let _ = stop_rx.await;
+            })
+            .await
+        });
+
+        tokio::select! {
+            result = &mut server => {
+                result??;
+                return Ok(());
+            }
+            _ = shutdown_signal() => {}
+        }
+
+        let _ = stop_tx.send(());
+        self.shutdown.drain(server).await;
         Ok(())
     }
 }
 
 async fn shutdown_signal() {
-    // Wait for Ctrl+C
-    let _ = tokio::signal::ctrl_c().await;
+    let ctrl_c = async {
+        let _ = tokio::signal::ctrl_c().await;
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(e) => {
+                tracing::warn!(error = %e, "Failed to install SIGTERM handler");
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
     tracing::info!("shutdown signal received");
 }
 
@@ -2359,13 +3047,39 @@ async fn initialize_auth_state(
     let revoked_token_storage = Arc::new(ArcRevokedTokenStorage::new(db_pool.clone()));
     let user_storage = Arc::new(ArcUserStorage::new(db_pool));
 
-    Ok(AuthState::new(
+    let mut auth_state = AuthState::new(
         jwt_service,
         client_storage,
         revoked_token_storage,
         user_storage,
     )
-    .with_cookie_config(cfg.auth.cookie.clone()))
+    .with_cookie_config(cfg.auth.cookie.clone());
+
+    // Accept access tokens from trusted external issuers (e.g. a corporate IdP)
+    let federation = &cfg.auth.federation;
+    if !federation.trusted_issuers.is_empty() {
+        let jwks_cache = Arc::new(ProviderJwksCache::new(
+            ProviderJwksCacheConfig::default()
+                .with_default_ttl(federation.jwks_cache_ttl)
+                .with_ttl_jitter(federation.jwks_cache_jitter)
+                .with_max_stale(federation.jwks_max_stale)
+                .with_refresh_cooldown(federation.jwks_refresh_cooldown)
+                .with_retry(cfg.http_client.retry.clone()),
+        ));
+        let validator = ExternalTokenValidator::from_config(federation, jwks_cache)
+            .map_err(|e| anyhow::anyhow!("Invalid trusted issuer configuration: {}", e))?;
+        tracing::info!(
+            issuers = ?federation
+                .trusted_issuers
+                .iter()
+                .map(|i| i.issuer.as_str())
+                .collect::<Vec<_>>(),
+            "Accepting access tokens from trusted external issuers"
+        );
+        auth_state = auth_state.with_external_token_validator(Arc::new(validator));
+    }
+
+    Ok(auth_state)
 }
 
 /// Build the `SearchParameter` registry without going through
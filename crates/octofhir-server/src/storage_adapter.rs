@@ -0,0 +1,489 @@
+//! Mapping of storage errors to API errors.
+//!
+//! Every `StorageError` returned to a client goes through
+//! [`map_storage_error`], so the same failure gets the same HTTP status and
+//! OperationOutcome issue code on every interaction. Database errors carrying
+//! a SQLSTATE are mapped by their [`ErrorCategory`]:
+//!
+//! | Category         | SQLSTATE examples             | Status | Issue code  |
+//! |------------------|-------------------------------|--------|-------------|
+//! | `Conflict`       | 23505 unique, 23P01 exclusion | 409    | `duplicate` |
+//! | `Validation`     | 23503 FK, 23502, 23514, 22xxx | 400    | `invalid`   |
+//! | `Transaction`    | 40001 serialization, 40P01    | 409    | `conflict`  |
+//! | `Busy`           | 57014 timeout, 55P03 lock     | 503    | `throttled` |
+//! | `Infrastructure` | 08xxx, 53xxx, 57P01           | 503    | `transient` |
+//! | other            | 42P01, XX000                  | 500    | `exception` |
+//!
+//! Both 503 cases tell "busy" from "broken": a saturated server (busy
+//! statement, exhausted connection pool) answers `throttled` with a
+//! `Retry-After` header, an unreachable database answers `transient` without.
+//!
+//! [`apply_in_transaction`] is the entry point for applying several writes
+//! atomically, used by the transaction bundle handler: it runs them in one
+//! storage transaction, commits when all succeed, and otherwise rolls
+//! everything back before reporting the first failure.
+
+use futures_util::future::BoxFuture;
+use octofhir_api::ApiError;
+use octofhir_storage::{ErrorCategory, FhirStorage, StorageError, Transaction};
+
+/// Runs `apply` within one storage transaction.
+///
+/// Commits when `apply` succeeds and returns its result. When it fails the
+/// transaction is rolled back, so none of its writes remain, and the failure
+/// is returned as is. Storage errors raised while applying operations should
+/// go through [`map_storage_error`], like those of beginning and committing
+/// the transaction here, so they render as the same OperationOutcome.
+///
+/// `apply` returns a boxed future borrowing only the transaction, so it has
+/// to own whatever else it uses:
+///
+/// ```ignore
+/// let created = apply_in_transaction(storage, |tx| {
+///     Box::pin(async move { tx.create(&resource).await.map_err(map_storage_error) })
+/// })
+/// .await?;
+/// ```
+pub async fn apply_in_transaction<T, F>(storage: &dyn FhirStorage, apply: F) -> Result<T, ApiError>
+where
+    F: for<'tx> FnOnce(&'tx mut dyn Transaction) -> BoxFuture<'tx, Result<T, ApiError>>,
+{
+    let mut tx = storage
+        .begin_transaction()
+        .await
+        .map_err(map_storage_error)?;
+
+    match apply(tx.as_mut()).await {
+        Ok(result) => {
+            tx.commit().await.map_err(map_storage_error)?;
+            Ok(result)
+        }
+        Err(e) => {
+            tracing::warn!(error = %e, "Transaction operation failed, rolling back");
+            if let Err(rollback_error) = tx.rollback().await {
+                tracing::warn!(error = %rollback_error, "Transaction rollback failed");
+            }
+            Err(e)
+        }
+    }
+}
+
+/// Maps a storage error to the API error reported to the client.
+pub fn map_storage_error(e: StorageError) -> ApiError {
+    match e {
+        StorageError::NotFound { resource_type, id } => {
+            ApiError::not_found(format!("{resource_type} with id '{id}' not found"))
+        }
+        StorageError::AlreadyExists { resource_type, id } => {
+            ApiError::duplicate(format!("{resource_type} with id '{id}' already exists"))
+        }
+        StorageError::Deleted { resource_type, id } => {
+            ApiError::gone(format!("{resource_type} with id '{id}' has been deleted"))
+        }
+        StorageError::VersionConflict { expected, actual } => ApiError::precondition_failed(
+            format!("Version conflict: expected {expected}, got {actual}"),
+        ),
+        StorageError::InvalidResource { message } => ApiError::bad_request(message),
+        StorageError::Busy { message } => {
+            crate::metrics::record_db_acquire_timeout();
+            ApiError::busy(format!("{message}, retry later"))
+        }
+        StorageError::Timeout { message } => ApiError::gateway_timeout(message),
+        StorageError::ConnectionError { message } => {
+            tracing::warn!(error = %message, "Storage backend unavailable");
+            ApiError::service_unavailable("The database is unavailable, retry later")
+        }
+        e @ StorageError::Database { .. } => map_database_error(e),
+        other => ApiError::internal(other.to_string()),
+    }
+}
+
+fn map_database_error(e: StorageError) -> ApiError {
+    let category = e.category();
+    let StorageError::Database { code, message } = e else {
+        return ApiError::internal(e.to_string());
+    };
+
+    match category {
+        ErrorCategory::Conflict => ApiError::duplicate(message),
+        ErrorCategory::Validation => ApiError::bad_request(message),
+        ErrorCategory::Transaction => ApiError::conflict(format!(
+            "{message} (concurrent modification, retry the request)"
+        )),
+        ErrorCategory::Busy => {
+            tracing::warn!(sqlstate = %code, error = %message, "Database busy");
+            ApiError::busy(format!("{message} (retry later)"))
+        }
+        ErrorCategory::Infrastructure => {
+            tracing::warn!(sqlstate = %code, error = %message, "Database unavailable");
+            ApiError::service_unavailable(format!("{message} (retry later)"))
+        }
+        _ => ApiError::internal(format!("{message} (SQLSTATE {code})")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use axum::http::StatusCode;
+    use octofhir_storage::{
+        HistoryParams, HistoryResult, RawHistoryResult, SearchParams, SearchResult, StoredResource,
+    };
+    use serde_json::{Value, json};
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    fn outcome_code(e: StorageError) -> (StatusCode, &'static str) {
+        let api = map_storage_error(e);
+        (api.status_code(), api.to_operation_outcome().issue[0].code)
+    }
+
+    #[test]
+    fn test_variant_mapping() {
+        assert_eq!(
+            outcome_code(StorageError::not_found("Patient", "1")),
+            (StatusCode::NOT_FOUND, "not-found")
+        );
+        assert_eq!(
+            outcome_code(StorageError::already_exists("Patient", "1")),
+            (StatusCode::CONFLICT, "duplicate")
+        );
+        assert_eq!(
+            outcome_code(StorageError::deleted("Patient", "1")),
+            (StatusCode::GONE, "deleted")
+        );
+        assert_eq!(
+            outcome_code(StorageError::version_conflict("1", "2")),
+            (StatusCode::PRECONDITION_FAILED, "conflict")
+        );
+        assert_eq!(
+            outcome_code(StorageError::invalid_resource("bad")),
+            (StatusCode::BAD_REQUEST, "invalid")
+        );
+        assert_eq!(
+            outcome_code(StorageError::connection_error("refused")),
+            (StatusCode::SERVICE_UNAVAILABLE, "transient")
+        );
+        assert_eq!(
+            outcome_code(StorageError::busy("pool exhausted")),
+            (StatusCode::SERVICE_UNAVAILABLE, "throttled")
+        );
+        assert_eq!(
+            outcome_code(StorageError::timeout("deadline exceeded")),
+            (StatusCode::GATEWAY_TIMEOUT, "timeout")
+        );
+        assert_eq!(
+            outcome_code(StorageError::internal("boom")),
+            (StatusCode::INTERNAL_SERVER_ERROR, "exception")
+        );
+    }
+
+    #[test]
+    fn test_sqlstate_mapping() {
+        let cases = [
+            ("23505", StatusCode::CONFLICT, "duplicate"),
+            ("23P01", StatusCode::CONFLICT, "duplicate"),
+            ("23503", StatusCode::BAD_REQUEST, "invalid"),
+            ("23502", StatusCode::BAD_REQUEST, "invalid"),
+            ("23514", StatusCode::BAD_REQUEST, "invalid"),
+            ("22P02", StatusCode::BAD_REQUEST, "invalid"),
+            ("22001", StatusCode::BAD_REQUEST, "invalid"),
+            ("40001", StatusCode::CONFLICT, "conflict"),
+            ("40P01", StatusCode::CONFLICT, "conflict"),
+            ("57014", StatusCode::SERVICE_UNAVAILABLE, "throttled"),
+            ("55P03", StatusCode::SERVICE_UNAVAILABLE, "throttled"),
+            ("53300", StatusCode::SERVICE_UNAVAILABLE, "transient"),
+            ("08006", StatusCode::SERVICE_UNAVAILABLE, "transient"),
+            ("57P01", StatusCode::SERVICE_UNAVAILABLE, "transient"),
+            ("42P01", StatusCode::INTERNAL_SERVER_ERROR, "exception"),
+            ("XX000", StatusCode::INTERNAL_SERVER_ERROR, "exception"),
+        ];
+        for (sqlstate, status, code) in cases {
+            assert_eq!(
+                outcome_code(StorageError::database(sqlstate, "error")),
+                (status, code),
+                "SQLSTATE {sqlstate}"
+            );
+        }
+    }
+
+    type Key = (String, String);
+    type Resources = Arc<Mutex<HashMap<Key, Value>>>;
+
+    /// Storage keeping resources in a map. Its transactions write through and
+    /// keep an undo log, so a rollback has partial changes to revert.
+    #[derive(Default)]
+    struct MemoryStorage {
+        resources: Resources,
+    }
+
+    impl MemoryStorage {
+        fn contains(&self, resource_type: &str, id: &str) -> bool {
+            let key = (resource_type.to_string(), id.to_string());
+            self.resources.lock().unwrap().contains_key(&key)
+        }
+    }
+
+    struct MemoryTransaction {
+        resources: Resources,
+        undo: Vec<(Key, Option<Value>)>,
+    }
+
+    fn key_of(resource: &Value) -> Key {
+        (
+            resource["resourceType"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+            resource["id"].as_str().unwrap_or_default().to_string(),
+        )
+    }
+
+    fn stored(resource: &Value) -> StoredResource {
+        let (resource_type, id) = key_of(resource);
+        StoredResource {
+            id,
+            version_id: "1".to_string(),
+            resource_type,
+            resource: resource.clone(),
+            last_updated: time::OffsetDateTime::now_utc(),
+            created_at: time::OffsetDateTime::now_utc(),
+        }
+    }
+
+    impl MemoryTransaction {
+        fn write(&mut self, key: Key, value: Option<Value>) {
+            let mut resources = self.resources.lock().unwrap();
+            let previous = match value {
+                Some(value) => resources.insert(key.clone(), value),
+                None => resources.remove(&key),
+            };
+            self.undo.push((key, previous));
+        }
+    }
+
+    /// Fails calls these tests do not expect, without panicking the runtime.
+    fn unsupported(operation: &str) -> StorageError {
+        StorageError::internal(format!("MemoryStorage does not support {operation}"))
+    }
+
+    #[async_trait]
+    impl Transaction for MemoryTransaction {
+        async fn commit(self: Box<Self>) -> Result<(), StorageError> {
+            Ok(())
+        }
+
+        async fn rollback(self: Box<Self>) -> Result<(), StorageError> {
+            let mut resources = self.resources.lock().unwrap();
+            for (key, previous) in self.undo.into_iter().rev() {
+                match previous {
+                    Some(value) => resources.insert(key, value),
+                    None => resources.remove(&key),
+                };
+            }
+            Ok(())
+        }
+
+        async fn create(&mut self, resource: &Value) -> Result<StoredResource, StorageError> {
+            let key = key_of(resource);
+            if self.resources.lock().unwrap().contains_key(&key) {
+                return Err(StorageError::already_exists(&key.0, &key.1));
+            }
+            self.write(key, Some(resource.clone()));
+            Ok(stored(resource))
+        }
+
+        async fn update(
+            &mut self,
+            resource: &Value,
+            _if_match: Option<&str>,
+        ) -> Result<StoredResource, StorageError> {
+            let key = key_of(resource);
+            if !self.resources.lock().unwrap().contains_key(&key) {
+                return Err(StorageError::not_found(&key.0, &key.1));
+            }
+            self.write(key, Some(resource.clone()));
+            Ok(stored(resource))
+        }
+
+        async fn delete(&mut self, resource_type: &str, id: &str) -> Result<(), StorageError> {
+            self.write((resource_type.to_string(), id.to_string()), None);
+            Ok(())
+        }
+
+        async fn read(
+            &self,
+            resource_type: &str,
+            id: &str,
+        ) -> Result<Option<StoredResource>, StorageError> {
+            let key = (resource_type.to_string(), id.to_string());
+            Ok(self.resources.lock().unwrap().get(&key).map(stored))
+        }
+
+        async fn search(
+            &self,
+            _resource_type: &str,
+            _params: &SearchParams,
+        ) -> Result<SearchResult, StorageError> {
+            Err(unsupported("search"))
+        }
+    }
+
+    #[async_trait]
+    impl FhirStorage for MemoryStorage {
+        async fn create(&self, _resource: &Value) -> Result<StoredResource, StorageError> {
+            Err(unsupported("create"))
+        }
+
+        async fn read(
+            &self,
+            resource_type: &str,
+            id: &str,
+        ) -> Result<Option<StoredResource>, StorageError> {
+            let key = (resource_type.to_string(), id.to_string());
+            Ok(self.resources.lock().unwrap().get(&key).map(stored))
+        }
+
+        async fn update(
+            &self,
+            _resource: &Value,
+            _if_match: Option<&str>,
+        ) -> Result<StoredResource, StorageError> {
+            Err(unsupported("update"))
+        }
+
+        async fn delete(&self, _resource_type: &str, _id: &str) -> Result<(), StorageError> {
+            Err(unsupported("delete"))
+        }
+
+        async fn vread(
+            &self,
+            _resource_type: &str,
+            _id: &str,
+            _version: &str,
+        ) -> Result<Option<StoredResource>, StorageError> {
+            Err(unsupported("vread"))
+        }
+
+        async fn search(
+            &self,
+            _resource_type: &str,
+            _params: &SearchParams,
+        ) -> Result<SearchResult, StorageError> {
+            Err(unsupported("search"))
+        }
+
+        async fn history(
+            &self,
+            _resource_type: &str,
+            _id: Option<&str>,
+            _params: &HistoryParams,
+        ) -> Result<HistoryResult, StorageError> {
+            Err(unsupported("history"))
+        }
+
+        async fn system_history(
+            &self,
+            _params: &HistoryParams,
+        ) -> Result<HistoryResult, StorageError> {
+            Err(unsupported("system_history"))
+        }
+
+        async fn history_raw(
+            &self,
+            _resource_type: &str,
+            _id: Option<&str>,
+            _params: &HistoryParams,
+        ) -> Result<RawHistoryResult, StorageError> {
+            Err(unsupported("history_raw"))
+        }
+
+        async fn system_history_raw(
+            &self,
+            _params: &HistoryParams,
+        ) -> Result<RawHistoryResult, StorageError> {
+            Err(unsupported("system_history_raw"))
+        }
+
+        async fn begin_transaction(&self) -> Result<Box<dyn Transaction>, StorageError> {
+            Ok(Box::new(MemoryTransaction {
+                resources: self.resources.clone(),
+                undo: Vec::new(),
+            }))
+        }
+
+        fn supports_transactions(&self) -> bool {
+            true
+        }
+
+        fn backend_name(&self) -> &'static str {
+            "memory"
+        }
+    }
+
+    fn patient(id: &str, family: &str) -> Value {
+        json!({"resourceType": "Patient", "id": id, "name": [{"family": family}]})
+    }
+
+    #[tokio::test]
+    async fn test_apply_in_transaction_commits_all_operations() {
+        let storage = MemoryStorage::default();
+
+        let created = apply_in_transaction(&storage, |tx| {
+            Box::pin(async move {
+                let a = tx
+                    .create(&patient("a", "A"))
+                    .await
+                    .map_err(map_storage_error)?;
+                tx.create(&patient("b", "B"))
+                    .await
+                    .map_err(map_storage_error)?;
+                tx.delete("Patient", "b").await.map_err(map_storage_error)?;
+                Ok(a)
+            })
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(created.id, "a");
+        assert!(storage.contains("Patient", "a"));
+        assert!(!storage.contains("Patient", "b"));
+    }
+
+    #[tokio::test]
+    async fn test_apply_in_transaction_rolls_back_on_failure() {
+        let storage = MemoryStorage::default();
+        apply_in_transaction(&storage, |tx| {
+            Box::pin(async move {
+                tx.create(&patient("kept", "Before"))
+                    .await
+                    .map_err(map_storage_error)
+            })
+        })
+        .await
+        .unwrap();
+
+        let err = apply_in_transaction(&storage, |tx| {
+            Box::pin(async move {
+                tx.update(&patient("kept", "After"), None)
+                    .await
+                    .map_err(map_storage_error)?;
+                tx.create(&patient("new", "New"))
+                    .await
+                    .map_err(map_storage_error)?;
+                tx.update(&patient("missing", "Missing"), None)
+                    .await
+                    .map_err(map_storage_error)
+            })
+        })
+        .await
+        .unwrap_err();
+
+        assert_eq!(err.status_code(), StatusCode::NOT_FOUND);
+        assert_eq!(err.to_operation_outcome().issue[0].code, "not-found");
+        assert!(!storage.contains("Patient", "new"));
+        let kept = storage.read("Patient", "kept").await.unwrap().unwrap();
+        assert_eq!(kept.resource["name"][0]["family"], "Before");
+    }
+}
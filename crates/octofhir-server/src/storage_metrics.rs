@@ -0,0 +1,331 @@
+//! MeteredStorage - A storage wrapper that records per-operation metrics.
+//!
+//! Every [`FhirStorage`] call is timed and recorded in
+//! `storage_operation_duration_seconds`, labelled by operation and resource
+//! type. Failed calls also increment `storage_operation_errors_total` with the
+//! [`octofhir_storage::ErrorCategory`] of the error. The wrapper is backend
+//! agnostic, so the same metrics are reported for PostgreSQL and in-memory
+//! storage.
+//!
+//! Operation labels collapse the raw/typed variants of a call: `create` and
+//! `create_raw` are both reported as `create`, and so on. System-level
+//! history is reported with the resource type `system`.
+//!
+//! Searches served by the REST search handlers run SQL directly against the
+//! pool and do not pass through this wrapper; they are covered by
+//! `http_request_duration_seconds`.
+//!
+//! Each call's duration also counts toward the query phase of the current
+//! request's [`crate::request_timing`] breakdown.
+
+use std::collections::HashSet;
+use std::future::Future;
+use std::time::Instant;
+
+use async_trait::async_trait;
+use octofhir_storage::{
+    FhirStorage, HistoryParams, HistoryResult, RawHistoryResult, RawStoredResource, SearchParams,
+    SearchResult, StorageError, StoredResource, Transaction,
+};
+use serde_json::Value;
+
+use crate::metrics::record_storage_operation;
+
+/// Resource type label used for operations that span all resource types.
+const SYSTEM_RESOURCE_TYPE: &str = "system";
+
+/// A storage wrapper that records latency and error metrics for each
+/// operation before returning the inner storage's result unchanged.
+pub struct MeteredStorage<S: FhirStorage> {
+    /// The inner storage implementation.
+    inner: S,
+}
+
+impl<S: FhirStorage> MeteredStorage<S> {
+    /// Create a new metered storage wrapper.
+    pub fn new(inner: S) -> Self {
+        Self { inner }
+    }
+
+    /// Get a reference to the inner storage.
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+}
+
+/// Runs a storage operation and records its duration and outcome.
+async fn metered<T>(
+    operation: &'static str,
+    resource_type: &str,
+    fut: impl Future<Output = Result<T, StorageError>>,
+) -> Result<T, StorageError> {
+    let start = Instant::now();
+    let result = fut.await;
+    let elapsed = start.elapsed();
+    let category = result.as_ref().err().map(|e| e.category().to_string());
+    record_storage_operation(operation, resource_type, elapsed, category.as_deref());
+    crate::request_timing::record(crate::request_timing::Phase::Query, elapsed);
+    result
+}
+
+/// Resource type label for a resource body, taken from its `resourceType`.
+fn resource_type_of(resource: &Value) -> &str {
+    resource
+        .get("resourceType")
+        .and_then(|v| v.as_str())
+        .unwrap_or("Unknown")
+}
+
+#[async_trait]
+impl<S: FhirStorage> FhirStorage for MeteredStorage<S> {
+    async fn create(&self, resource: &Value) -> Result<StoredResource, StorageError> {
+        metered(
+            "create",
+            resource_type_of(resource),
+            self.inner.create(resource),
+        )
+        .await
+    }
+
+    async fn create_raw(&self, resource: &Value) -> Result<RawStoredResource, StorageError> {
+        metered(
+            "create",
+            resource_type_of(resource),
+            self.inner.create_raw(resource),
+        )
+        .await
+    }
+
+    async fn read(
+        &self,
+        resource_type: &str,
+        id: &str,
+    ) -> Result<Option<StoredResource>, StorageError> {
+        metered("read", resource_type, self.inner.read(resource_type, id)).await
+    }
+
+    async fn exists(&self, resource_type: &str, id: &str) -> Result<bool, StorageError> {
+        metered(
+            "exists",
+            resource_type,
+            self.inner.exists(resource_type, id),
+        )
+        .await
+    }
+
+    async fn exists_many(
+        &self,
+        resource_type: &str,
+        ids: &[String],
+    ) -> Result<HashSet<String>, StorageError> {
+        metered(
+            "exists",
+            resource_type,
+            self.inner.exists_many(resource_type, ids),
+        )
+        .await
+    }
+
+    async fn exists_many_grouped(
+        &self,
+        groups: &[(String, Vec<String>)],
+    ) -> Result<HashSet<String>, StorageError> {
+        metered(
+            "exists",
+            SYSTEM_RESOURCE_TYPE,
+            self.inner.exists_many_grouped(groups),
+        )
+        .await
+    }
+
+    async fn read_many(
+        &self,
+        resource_type: &str,
+        ids: &[String],
+    ) -> Result<Vec<StoredResource>, StorageError> {
+        metered(
+            "read",
+            resource_type,
+            self.inner.read_many(resource_type, ids),
+        )
+        .await
+    }
+
+    async fn read_raw(
+        &self,
+        resource_type: &str,
+        id: &str,
+    ) -> Result<Option<RawStoredResource>, StorageError> {
+        metered(
+            "read",
+            resource_type,
+            self.inner.read_raw(resource_type, id),
+        )
+        .await
+    }
+
+    async fn update(
+        &self,
+        resource: &Value,
+        if_match: Option<&str>,
+    ) -> Result<StoredResource, StorageError> {
+        metered(
+            "update",
+            resource_type_of(resource),
+            self.inner.update(resource, if_match),
+        )
+        .await
+    }
+
+    async fn update_raw(
+        &self,
+        resource: &Value,
+        if_match: Option<&str>,
+    ) -> Result<RawStoredResource, StorageError> {
+        metered(
+            "update",
+            resource_type_of(resource),
+            self.inner.update_raw(resource, if_match),
+        )
+        .await
+    }
+
+    async fn delete(&self, resource_type: &str, id: &str) -> Result<(), StorageError> {
+        metered(
+            "delete",
+            resource_type,
+            self.inner.delete(resource_type, id),
+        )
+        .await
+    }
+
+    async fn vread(
+        &self,
+        resource_type: &str,
+        id: &str,
+        version: &str,
+    ) -> Result<Option<StoredResource>, StorageError> {
+        metered(
+            "vread",
+            resource_type,
+            self.inner.vread(resource_type, id, version),
+        )
+        .await
+    }
+
+    async fn vread_raw(
+        &self,
+        resource_type: &str,
+        id: &str,
+        version: &str,
+    ) -> Result<Option<RawStoredResource>, StorageError> {
+        metered(
+            "vread",
+            resource_type,
+            self.inner.vread_raw(resource_type, id, version),
+        )
+        .await
+    }
+
+    async fn history(
+        &self,
+        resource_type: &str,
+        id: Option<&str>,
+        params: &HistoryParams,
+    ) -> Result<HistoryResult, StorageError> {
+        metered(
+            "history",
+            resource_type,
+            self.inner.history(resource_type, id, params),
+        )
+        .await
+    }
+
+    async fn system_history(&self, params: &HistoryParams) -> Result<HistoryResult, StorageError> {
+        metered(
+            "history",
+            SYSTEM_RESOURCE_TYPE,
+            self.inner.system_history(params),
+        )
+        .await
+    }
+
+    async fn history_raw(
+        &self,
+        resource_type: &str,
+        id: Option<&str>,
+        params: &HistoryParams,
+    ) -> Result<RawHistoryResult, StorageError> {
+        metered(
+            "history",
+            resource_type,
+            self.inner.history_raw(resource_type, id, params),
+        )
+        .await
+    }
+
+    async fn system_history_raw(
+        &self,
+        params: &HistoryParams,
+    ) -> Result<RawHistoryResult, StorageError> {
+        metered(
+            "history",
+            SYSTEM_RESOURCE_TYPE,
+            self.inner.system_history_raw(params),
+        )
+        .await
+    }
+
+    async fn search(
+        &self,
+        resource_type: &str,
+        params: &SearchParams,
+    ) -> Result<SearchResult, StorageError> {
+        metered(
+            "search",
+            resource_type,
+            self.inner.search(resource_type, params),
+        )
+        .await
+    }
+
+    async fn begin_transaction(&self) -> Result<Box<dyn Transaction>, StorageError> {
+        metered(
+            "begin_transaction",
+            SYSTEM_RESOURCE_TYPE,
+            self.inner.begin_transaction(),
+        )
+        .await
+    }
+
+    fn supports_transactions(&self) -> bool {
+        self.inner.supports_transactions()
+    }
+
+    fn backend_name(&self) -> &'static str {
+        self.inner.backend_name()
+    }
+}
+
+impl<S: FhirStorage> std::fmt::Debug for MeteredStorage<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MeteredStorage")
+            .field("backend", &self.inner.backend_name())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_resource_type_of() {
+        assert_eq!(
+            resource_type_of(&json!({"resourceType": "Patient"})),
+            "Patient"
+        );
+        assert_eq!(resource_type_of(&json!({"id": "1"})), "Unknown");
+    }
+}
@@ -5,6 +5,7 @@
 use std::time::Instant;
 
 use async_trait::async_trait;
+use octofhir_core::WebhookSignature;
 use reqwest::{Client, header};
 
 use super::DeliveryChannel;
@@ -57,6 +58,7 @@ impl DeliveryChannel for RestHookChannel {
             ref endpoint,
             ref headers,
             ref content_type,
+            ref signing_secret,
             ..
         } = subscription.channel
         else {
@@ -73,8 +75,11 @@ impl DeliveryChannel for RestHookChannel {
             .post(endpoint)
             .header(header::CONTENT_TYPE, content_type.as_str());
 
-        // Add custom headers
+        // Add custom headers; the content type is the negotiated one
         for (key, value) in headers {
+            if key.eq_ignore_ascii_case(header::CONTENT_TYPE.as_str()) {
+                continue;
+            }
             request = request.header(key.as_str(), value.as_str());
         }
 
@@ -83,6 +88,13 @@ impl DeliveryChannel for RestHookChannel {
             SubscriptionError::DeliveryError(format!("Failed to serialize bundle: {e}"))
         })?;
 
+        // Sign over timestamp, nonce and body when the channel has a secret
+        if let Some(secret) = signing_secret {
+            for (name, value) in WebhookSignature::sign(secret, body.as_bytes()).headers() {
+                request = request.header(name, value);
+            }
+        }
+
         let response = request.body(body).send().await;
 
         let elapsed = start.elapsed().as_millis() as u32;
@@ -111,9 +111,8 @@ impl EventMatcher {
         let context = EvaluationContext::new(collection, provider, None, None, None);
 
         // Evaluate expression
-        let result = self
-            .engine
-            .evaluate(expression, &context)
+        let result = crate::cache::fhirpath_cache()
+            .evaluate(&self.engine, expression, &context)
             .await
             .map_err(|e| {
                 SubscriptionError::FhirPathError(format!("FHIRPath evaluation failed: {e}"))
@@ -253,9 +252,12 @@ impl EventMatcher {
 
         let context = EvaluationContext::new(collection, provider, None, None, None);
 
-        let result = self.engine.evaluate(&expr, &context).await.map_err(|e| {
-            SubscriptionError::FhirPathError(format!("FHIRPath evaluation failed: {e}"))
-        })?;
+        let result = crate::cache::fhirpath_cache()
+            .evaluate(&self.engine, &expr, &context)
+            .await
+            .map_err(|e| {
+                SubscriptionError::FhirPathError(format!("FHIRPath evaluation failed: {e}"))
+            })?;
 
         // Compare result with filter value - extract value from EvaluationResult
         let values = result.value.into_vec();
@@ -165,6 +165,7 @@ impl SubscriptionHook {
                             0, // Event number will be assigned by storage
                         )
                         .with_focus(serde_json::Value::clone(resource))
+                        .with_payload_content(subscription.channel.payload_content())
                         .build();
 
                         // Queue the event
@@ -49,6 +49,8 @@ pub mod event_matcher;
 pub mod handlers;
 pub mod hook;
 pub mod operations;
+pub mod secret_storage;
+pub mod secrets;
 pub mod storage;
 pub mod subscription_manager;
 pub mod topic_registry;
@@ -58,6 +60,8 @@ pub use delivery::{WebSocketRegistry, handle_subscription_websocket};
 pub use error::{SubscriptionError, SubscriptionResult};
 pub use event_matcher::EventMatcher;
 pub use hook::SubscriptionHook;
+pub use secret_storage::SubscriptionSecretStorage;
+pub use secrets::SubscriptionSecrets;
 pub use storage::SubscriptionEventStorage;
 pub use subscription_manager::SubscriptionManager;
 pub use topic_registry::TopicRegistry;
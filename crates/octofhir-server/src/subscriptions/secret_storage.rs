@@ -0,0 +1,375 @@
+//! SubscriptionSecretStorage - A storage wrapper that keeps rest-hook signing
+//! secrets out of stored Subscriptions.
+//!
+//! Writes go through [`take_signing_secret`] before reaching the inner
+//! storage; once the write succeeds the secret is saved in
+//! [`SubscriptionSecrets`] under the stored id. Inside a transaction the
+//! secrets are saved after the commit. Reads pass through unchanged, so the
+//! secret is never returned by read, vread, search or history.
+
+use std::collections::HashSet;
+
+use async_trait::async_trait;
+use octofhir_storage::{
+    FhirStorage, HistoryParams, HistoryResult, RawHistoryResult, RawStoredResource, SearchParams,
+    SearchResult, StorageError, StoredResource, Transaction,
+};
+use serde_json::Value;
+
+use super::secrets::{SigningSecretChange, SubscriptionSecrets, take_signing_secret};
+
+/// Copy of `resource` without its signing secret, if it carries one.
+fn strip_secret(resource: &Value) -> Option<(Value, SigningSecretChange)> {
+    if resource.get("resourceType").and_then(Value::as_str) != Some("Subscription") {
+        return None;
+    }
+    let mut stripped = resource.clone();
+    let change = take_signing_secret(&mut stripped)?;
+    Some((stripped, change))
+}
+
+/// A storage wrapper that moves Subscription signing secrets to
+/// [`SubscriptionSecrets`].
+pub struct SubscriptionSecretStorage<S: FhirStorage> {
+    /// The inner storage implementation.
+    inner: S,
+    secrets: SubscriptionSecrets,
+}
+
+impl<S: FhirStorage> SubscriptionSecretStorage<S> {
+    /// Create a new wrapper saving signing secrets to `secrets`.
+    pub fn new(inner: S, secrets: SubscriptionSecrets) -> Self {
+        Self { inner, secrets }
+    }
+
+    /// Get a reference to the inner storage.
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+}
+
+#[async_trait]
+impl<S: FhirStorage> FhirStorage for SubscriptionSecretStorage<S> {
+    async fn create(&self, resource: &Value) -> Result<StoredResource, StorageError> {
+        match strip_secret(resource) {
+            Some((stripped, change)) => {
+                let stored = self.inner.create(&stripped).await?;
+                self.secrets.apply(&stored.id, &change).await?;
+                Ok(stored)
+            }
+            None => self.inner.create(resource).await,
+        }
+    }
+
+    async fn create_raw(&self, resource: &Value) -> Result<RawStoredResource, StorageError> {
+        match strip_secret(resource) {
+            Some((stripped, change)) => {
+                let stored = self.inner.create_raw(&stripped).await?;
+                self.secrets.apply(&stored.id, &change).await?;
+                Ok(stored)
+            }
+            None => self.inner.create_raw(resource).await,
+        }
+    }
+
+    async fn read(
+        &self,
+        resource_type: &str,
+        id: &str,
+    ) -> Result<Option<StoredResource>, StorageError> {
+        self.inner.read(resource_type, id).await
+    }
+
+    async fn exists(&self, resource_type: &str, id: &str) -> Result<bool, StorageError> {
+        self.inner.exists(resource_type, id).await
+    }
+
+    async fn exists_many(
+        &self,
+        resource_type: &str,
+        ids: &[String],
+    ) -> Result<HashSet<String>, StorageError> {
+        self.inner.exists_many(resource_type, ids).await
+    }
+
+    async fn exists_many_grouped(
+        &self,
+        groups: &[(String, Vec<String>)],
+    ) -> Result<HashSet<String>, StorageError> {
+        self.inner.exists_many_grouped(groups).await
+    }
+
+    async fn read_many(
+        &self,
+        resource_type: &str,
+        ids: &[String],
+    ) -> Result<Vec<StoredResource>, StorageError> {
+        self.inner.read_many(resource_type, ids).await
+    }
+
+    async fn read_raw(
+        &self,
+        resource_type: &str,
+        id: &str,
+    ) -> Result<Option<RawStoredResource>, StorageError> {
+        self.inner.read_raw(resource_type, id).await
+    }
+
+    async fn update(
+        &self,
+        resource: &Value,
+        if_match: Option<&str>,
+    ) -> Result<StoredResource, StorageError> {
+        match strip_secret(resource) {
+            Some((stripped, change)) => {
+                let stored = self.inner.update(&stripped, if_match).await?;
+                self.secrets.apply(&stored.id, &change).await?;
+                Ok(stored)
+            }
+            None => self.inner.update(resource, if_match).await,
+        }
+    }
+
+    async fn update_raw(
+        &self,
+        resource: &Value,
+        if_match: Option<&str>,
+    ) -> Result<RawStoredResource, StorageError> {
+        match strip_secret(resource) {
+            Some((stripped, change)) => {
+                let stored = self.inner.update_raw(&stripped, if_match).await?;
+                self.secrets.apply(&stored.id, &change).await?;
+                Ok(stored)
+            }
+            None => self.inner.update_raw(resource, if_match).await,
+        }
+    }
+
+    async fn delete(&self, resource_type: &str, id: &str) -> Result<(), StorageError> {
+        self.inner.delete(resource_type, id).await?;
+        if resource_type == "Subscription" {
+            self.secrets.delete(id).await?;
+        }
+        Ok(())
+    }
+
+    async fn vread(
+        &self,
+        resource_type: &str,
+        id: &str,
+        version: &str,
+    ) -> Result<Option<StoredResource>, StorageError> {
+        self.inner.vread(resource_type, id, version).await
+    }
+
+    async fn vread_raw(
+        &self,
+        resource_type: &str,
+        id: &str,
+        version: &str,
+    ) -> Result<Option<RawStoredResource>, StorageError> {
+        self.inner.vread_raw(resource_type, id, version).await
+    }
+
+    async fn history(
+        &self,
+        resource_type: &str,
+        id: Option<&str>,
+        params: &HistoryParams,
+    ) -> Result<HistoryResult, StorageError> {
+        self.inner.history(resource_type, id, params).await
+    }
+
+    async fn system_history(&self, params: &HistoryParams) -> Result<HistoryResult, StorageError> {
+        self.inner.system_history(params).await
+    }
+
+    async fn history_raw(
+        &self,
+        resource_type: &str,
+        id: Option<&str>,
+        params: &HistoryParams,
+    ) -> Result<RawHistoryResult, StorageError> {
+        self.inner.history_raw(resource_type, id, params).await
+    }
+
+    async fn system_history_raw(
+        &self,
+        params: &HistoryParams,
+    ) -> Result<RawHistoryResult, StorageError> {
+        self.inner.system_history_raw(params).await
+    }
+
+    async fn search(
+        &self,
+        resource_type: &str,
+        params: &SearchParams,
+    ) -> Result<SearchResult, StorageError> {
+        self.inner.search(resource_type, params).await
+    }
+
+    async fn begin_transaction(&self) -> Result<Box<dyn Transaction>, StorageError> {
+        let inner = self.inner.begin_transaction().await?;
+        Ok(Box::new(SubscriptionSecretTransaction {
+            inner,
+            secrets: self.secrets.clone(),
+            pending: Vec::new(),
+        }))
+    }
+
+    fn supports_transactions(&self) -> bool {
+        self.inner.supports_transactions()
+    }
+
+    fn backend_name(&self) -> &'static str {
+        self.inner.backend_name()
+    }
+}
+
+impl<S: FhirStorage> std::fmt::Debug for SubscriptionSecretStorage<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SubscriptionSecretStorage")
+            .field("backend", &self.inner.backend_name())
+            .finish()
+    }
+}
+
+/// Transaction counterpart of [`SubscriptionSecretStorage`].
+///
+/// Secret changes are held until the commit succeeds, so a rollback leaves
+/// the stored secrets untouched.
+struct SubscriptionSecretTransaction {
+    inner: Box<dyn Transaction>,
+    secrets: SubscriptionSecrets,
+    pending: Vec<(String, SigningSecretChange)>,
+}
+
+#[async_trait]
+impl Transaction for SubscriptionSecretTransaction {
+    async fn commit(self: Box<Self>) -> Result<(), StorageError> {
+        let Self {
+            inner,
+            secrets,
+            pending,
+        } = *self;
+        inner.commit().await?;
+        for (id, change) in &pending {
+            secrets.apply(id, change).await?;
+        }
+        Ok(())
+    }
+
+    async fn rollback(self: Box<Self>) -> Result<(), StorageError> {
+        self.inner.rollback().await
+    }
+
+    async fn create(&mut self, resource: &Value) -> Result<StoredResource, StorageError> {
+        match strip_secret(resource) {
+            Some((stripped, change)) => {
+                let stored = self.inner.create(&stripped).await?;
+                self.pending.push((stored.id.clone(), change));
+                Ok(stored)
+            }
+            None => self.inner.create(resource).await,
+        }
+    }
+
+    async fn update(
+        &mut self,
+        resource: &Value,
+        if_match: Option<&str>,
+    ) -> Result<StoredResource, StorageError> {
+        match strip_secret(resource) {
+            Some((stripped, change)) => {
+                let stored = self.inner.update(&stripped, if_match).await?;
+                self.pending.push((stored.id.clone(), change));
+                Ok(stored)
+            }
+            None => self.inner.update(resource, if_match).await,
+        }
+    }
+
+    async fn delete(&mut self, resource_type: &str, id: &str) -> Result<(), StorageError> {
+        self.inner.delete(resource_type, id).await?;
+        if resource_type == "Subscription" {
+            self.pending
+                .push((id.to_string(), SigningSecretChange::Clear));
+        }
+        Ok(())
+    }
+
+    async fn read(
+        &self,
+        resource_type: &str,
+        id: &str,
+    ) -> Result<Option<StoredResource>, StorageError> {
+        self.inner.read(resource_type, id).await
+    }
+
+    async fn search(
+        &self,
+        resource_type: &str,
+        params: &SearchParams,
+    ) -> Result<SearchResult, StorageError> {
+        self.inner.search(resource_type, params).await
+    }
+
+    async fn create_batch(
+        &mut self,
+        resource_type: &str,
+        resources: &[Value],
+    ) -> Result<Vec<StoredResource>, StorageError> {
+        if resource_type != "Subscription" {
+            return self.inner.create_batch(resource_type, resources).await;
+        }
+        let mut stripped = Vec::with_capacity(resources.len());
+        let mut changes = Vec::with_capacity(resources.len());
+        for resource in resources {
+            match strip_secret(resource) {
+                Some((resource, change)) => {
+                    stripped.push(resource);
+                    changes.push(Some(change));
+                }
+                None => {
+                    stripped.push(resource.clone());
+                    changes.push(None);
+                }
+            }
+        }
+        let stored = self.inner.create_batch(resource_type, &stripped).await?;
+        for (stored, change) in stored.iter().zip(changes) {
+            if let Some(change) = change {
+                self.pending.push((stored.id.clone(), change));
+            }
+        }
+        Ok(stored)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::subscriptions::SIGNING_SECRET_EXTENSION;
+
+    #[test]
+    fn strip_secret_only_copies_subscriptions_with_a_secret() {
+        let patient = json!({"resourceType": "Patient", "id": "p1"});
+        assert!(strip_secret(&patient).is_none());
+
+        let plain = json!({"resourceType": "Subscription", "channel": {"type": "rest-hook"}});
+        assert!(strip_secret(&plain).is_none());
+
+        let signed = json!({
+            "resourceType": "Subscription",
+            "channel": {
+                "type": "rest-hook",
+                "extension": [{"url": SIGNING_SECRET_EXTENSION, "valueString": "s3cret"}]
+            }
+        });
+        let (stripped, change) = strip_secret(&signed).expect("secret should be found");
+        assert_eq!(change, SigningSecretChange::Set("s3cret".to_string()));
+        assert!(!stripped.to_string().contains("s3cret"));
+    }
+}
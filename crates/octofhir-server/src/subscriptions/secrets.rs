@@ -0,0 +1,194 @@
+//! Storage for rest-hook signing secrets.
+//!
+//! A secret supplied with the [`SIGNING_SECRET_EXTENSION`] channel extension
+//! is taken off the Subscription before it is written (see
+//! [`SubscriptionSecretStorage`](super::secret_storage::SubscriptionSecretStorage))
+//! and kept in the `subscription_secret` table instead, keyed by tenant and
+//! subscription id. The stored resource, its history and every read of it
+//! never carry the secret.
+
+use octofhir_storage::{StorageError, current_tenant};
+use serde_json::Value;
+use sqlx_core::query::query;
+use sqlx_core::query_scalar::query_scalar;
+use sqlx_postgres::PgPool;
+
+use super::types::SIGNING_SECRET_EXTENSION;
+
+/// A secret taken off a Subscription on its way to storage.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SigningSecretChange {
+    /// Sign deliveries with this secret.
+    Set(String),
+    /// The extension was sent empty: stop signing.
+    Clear,
+}
+
+/// Remove the signing secret extension from a Subscription's channel.
+///
+/// Returns `None` when the resource carries no such extension, in which case
+/// an existing secret is left as it is. This lets clients update a
+/// Subscription they read back without resending the secret.
+pub fn take_signing_secret(resource: &mut Value) -> Option<SigningSecretChange> {
+    if resource.get("resourceType").and_then(Value::as_str) != Some("Subscription") {
+        return None;
+    }
+    let channel = resource.get_mut("channel")?.as_object_mut()?;
+    let extensions = channel.get_mut("extension")?.as_array_mut()?;
+    let index = extensions
+        .iter()
+        .position(|e| e.get("url").and_then(Value::as_str) == Some(SIGNING_SECRET_EXTENSION))?;
+    let extension = extensions.remove(index);
+    if extensions.is_empty() {
+        channel.remove("extension");
+    }
+
+    let secret = extension
+        .get("valueString")
+        .and_then(Value::as_str)
+        .filter(|s| !s.is_empty());
+    Some(match secret {
+        Some(secret) => SigningSecretChange::Set(secret.to_string()),
+        None => SigningSecretChange::Clear,
+    })
+}
+
+/// Signing secrets in the `subscription_secret` table.
+#[derive(Debug, Clone)]
+pub struct SubscriptionSecrets {
+    pool: PgPool,
+}
+
+impl SubscriptionSecrets {
+    /// Create a secret store on `pool`.
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Tenant key for the current scope; empty outside any tenant.
+    fn tenant() -> String {
+        current_tenant().map(|t| t.to_string()).unwrap_or_default()
+    }
+
+    /// Get the signing secret of a subscription, if it has one.
+    pub async fn get(&self, subscription_id: &str) -> Result<Option<String>, StorageError> {
+        query_scalar(
+            "SELECT secret FROM subscription_secret WHERE tenant = $1 AND subscription_id = $2",
+        )
+        .bind(Self::tenant())
+        .bind(subscription_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| StorageError::internal(format!("Failed to read signing secret: {e}")))
+    }
+
+    /// Apply a change taken off a write of `subscription_id`.
+    pub async fn apply(
+        &self,
+        subscription_id: &str,
+        change: &SigningSecretChange,
+    ) -> Result<(), StorageError> {
+        match change {
+            SigningSecretChange::Set(secret) => {
+                query(
+                    r#"
+                    INSERT INTO subscription_secret (tenant, subscription_id, secret, updated_at)
+                    VALUES ($1, $2, $3, NOW())
+                    ON CONFLICT (tenant, subscription_id) DO UPDATE
+                    SET secret = EXCLUDED.secret, updated_at = NOW()
+                    "#,
+                )
+                .bind(Self::tenant())
+                .bind(subscription_id)
+                .bind(secret)
+                .execute(&self.pool)
+                .await
+                .map_err(|e| {
+                    StorageError::internal(format!("Failed to store signing secret: {e}"))
+                })?;
+                Ok(())
+            }
+            SigningSecretChange::Clear => self.delete(subscription_id).await,
+        }
+    }
+
+    /// Forget the signing secret of a subscription.
+    pub async fn delete(&self, subscription_id: &str) -> Result<(), StorageError> {
+        query("DELETE FROM subscription_secret WHERE tenant = $1 AND subscription_id = $2")
+            .bind(Self::tenant())
+            .bind(subscription_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| StorageError::internal(format!("Failed to delete signing secret: {e}")))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn subscription(secret: Value) -> Value {
+        json!({
+            "resourceType": "Subscription",
+            "channel": {
+                "type": "rest-hook",
+                "endpoint": "https://example.com/hook",
+                "extension": [
+                    {"url": "http://example.com/other", "valueString": "kept"},
+                    {"url": SIGNING_SECRET_EXTENSION, "valueString": secret}
+                ]
+            }
+        })
+    }
+
+    #[test]
+    fn takes_the_secret_off_the_channel() {
+        let mut resource = subscription(json!("s3cret"));
+        assert_eq!(
+            take_signing_secret(&mut resource),
+            Some(SigningSecretChange::Set("s3cret".to_string()))
+        );
+        assert_eq!(
+            resource["channel"]["extension"],
+            json!([{"url": "http://example.com/other", "valueString": "kept"}])
+        );
+        assert!(!resource.to_string().contains("s3cret"));
+    }
+
+    #[test]
+    fn empty_secret_clears() {
+        let mut resource = subscription(json!(""));
+        assert_eq!(
+            take_signing_secret(&mut resource),
+            Some(SigningSecretChange::Clear)
+        );
+    }
+
+    #[test]
+    fn drops_the_extension_array_when_emptied() {
+        let mut resource = json!({
+            "resourceType": "Subscription",
+            "channel": {
+                "type": "rest-hook",
+                "extension": [{"url": SIGNING_SECRET_EXTENSION, "valueString": "s3cret"}]
+            }
+        });
+        assert!(take_signing_secret(&mut resource).is_some());
+        assert!(resource["channel"].get("extension").is_none());
+    }
+
+    #[test]
+    fn leaves_other_resources_alone() {
+        let mut resource = subscription(json!("s3cret"));
+        resource["resourceType"] = json!("Basic");
+        let before = resource.clone();
+        assert_eq!(take_signing_secret(&mut resource), None);
+        assert_eq!(resource, before);
+
+        let mut without = json!({"resourceType": "Subscription", "channel": {"type": "rest-hook"}});
+        assert_eq!(take_signing_secret(&mut without), None);
+    }
+}
@@ -11,8 +11,11 @@ use sqlx_postgres::PgPool;
 use time::OffsetDateTime;
 
 use super::error::{SubscriptionError, SubscriptionResult};
+use super::secrets::SubscriptionSecrets;
 use super::types::{
-    ActiveSubscription, AppliedFilter, PayloadContent, SubscriptionChannel, SubscriptionStatus,
+    ActiveSubscription, AppliedFilter, BACKPORT_PAYLOAD_CONTENT_EXTENSION,
+    DEFAULT_NOTIFICATION_CONTENT_TYPE, PayloadContent, SubscriptionChannel, SubscriptionStatus,
+    negotiate_content_type,
 };
 
 /// Manager for subscription lifecycle and querying.
@@ -22,12 +25,20 @@ pub struct SubscriptionManager {
 
     /// Database pool for subscription status updates
     db_pool: PgPool,
+
+    /// Rest-hook signing secrets, kept outside the resources
+    secrets: SubscriptionSecrets,
 }
 
 impl SubscriptionManager {
     /// Create a new subscription manager.
     pub fn new(storage: Arc<dyn FhirStorage>, db_pool: PgPool) -> Self {
-        Self { storage, db_pool }
+        let secrets = SubscriptionSecrets::new(db_pool.clone());
+        Self {
+            storage,
+            db_pool,
+            secrets,
+        }
     }
 
     /// Get all active subscriptions for a given topic URL.
@@ -76,7 +87,7 @@ impl SubscriptionManager {
         Ok(subscriptions)
     }
 
-    /// Get a subscription by ID.
+    /// Get a subscription by ID, with its signing secret for delivery.
     pub async fn get_subscription(
         &self,
         id: &str,
@@ -87,10 +98,18 @@ impl SubscriptionManager {
             .await
             .map_err(|e| SubscriptionError::Storage(e.to_string()))?;
 
-        match stored {
-            Some(s) => Ok(Some(self.parse_subscription(&s.resource)?)),
-            None => Ok(None),
+        let Some(stored) = stored else {
+            return Ok(None);
+        };
+        let mut subscription = self.parse_subscription(&stored.resource)?;
+        if let SubscriptionChannel::RestHook { signing_secret, .. } = &mut subscription.channel {
+            *signing_secret = self
+                .secrets
+                .get(&subscription.id)
+                .await
+                .map_err(|e| SubscriptionError::Storage(e.to_string()))?;
         }
+        Ok(Some(subscription))
     }
 
     /// Activate a subscription (transition from requested to active).
@@ -294,35 +313,17 @@ impl SubscriptionManager {
                     })
                     .unwrap_or_default();
 
-                // Parse payload content
-                let payload_content = channel
-                    .get("payload")
-                    .and_then(|v| v.as_str())
-                    .map(PayloadContent::from)
-                    .unwrap_or_default();
-
-                // Parse content type
-                let content_type = channel
-                    .get("payload")
-                    .and_then(|p| {
-                        // R5: payload.contentType
-                        p.get("contentType").and_then(|v| v.as_str())
-                    })
-                    .or_else(|| {
-                        // R4: header with Content-Type
-                        headers
-                            .iter()
-                            .find(|(k, _)| k.eq_ignore_ascii_case("content-type"))
-                            .map(|(_, v)| v.as_str())
-                    })
-                    .unwrap_or("application/fhir+json")
-                    .to_string();
+                let payload_content = parse_payload_content(resource, channel);
+                let content_type = parse_content_type(resource, channel, &headers);
 
+                // The signing secret is not part of the resource, see
+                // `get_subscription`
                 Ok(SubscriptionChannel::RestHook {
                     endpoint,
                     headers,
                     payload_content,
                     content_type,
+                    signing_secret: None,
                 })
             }
             "websocket" => {
@@ -392,3 +393,133 @@ impl SubscriptionManager {
         Vec::new()
     }
 }
+
+/// Payload content requested by a Subscription.
+///
+/// R5 has `content` on the resource; the R4 Backport IG puts the code in an
+/// extension on `channel.payload` (`_payload` in JSON). Older subscriptions
+/// put the code in `channel.payload` itself.
+fn parse_payload_content(
+    resource: &serde_json::Value,
+    channel: &serde_json::Value,
+) -> PayloadContent {
+    let backport = || {
+        channel
+            .get("_payload")
+            .and_then(|p| p.get("extension"))
+            .and_then(|v| v.as_array())?
+            .iter()
+            .find(|e| {
+                e.get("url").and_then(|u| u.as_str()) == Some(BACKPORT_PAYLOAD_CONTENT_EXTENSION)
+            })
+            .and_then(|e| e.get("valueCode"))
+            .and_then(|v| v.as_str())
+    };
+
+    resource
+        .get("content")
+        .and_then(|v| v.as_str())
+        .or_else(backport)
+        .or_else(|| channel.get("payload").and_then(|v| v.as_str()))
+        .and_then(PayloadContent::from_code)
+        .unwrap_or_default()
+}
+
+/// Content type notifications are delivered in.
+///
+/// R5 has `contentType` on the resource; in R4 `channel.payload` is the MIME
+/// type. A `Content-Type` channel header is used as a last resort.
+fn parse_content_type(
+    resource: &serde_json::Value,
+    channel: &serde_json::Value,
+    headers: &[(String, String)],
+) -> String {
+    let requested = resource
+        .get("contentType")
+        .and_then(|v| v.as_str())
+        .or_else(|| {
+            channel
+                .get("payload")
+                .and_then(|v| v.as_str())
+                .filter(|p| p.contains('/'))
+        })
+        .or_else(|| {
+            headers
+                .iter()
+                .find(|(k, _)| k.eq_ignore_ascii_case("content-type"))
+                .map(|(_, v)| v.as_str())
+        });
+
+    match requested {
+        Some(requested) => {
+            let content_type = negotiate_content_type(requested);
+            if !content_type.eq_ignore_ascii_case(requested.trim()) {
+                tracing::warn!(
+                    requested = requested,
+                    content_type = content_type,
+                    "Unsupported subscription content type, delivering JSON"
+                );
+            }
+            content_type
+        }
+        None => DEFAULT_NOTIFICATION_CONTENT_TYPE.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_payload_content() {
+        // R5
+        let resource = json!({"content": "id-only", "channel": {}});
+        assert_eq!(
+            parse_payload_content(&resource, &resource["channel"]),
+            PayloadContent::IdOnly
+        );
+
+        // R4 Backport extension on channel.payload
+        let resource = json!({"channel": {
+            "payload": "application/fhir+json",
+            "_payload": {"extension": [{
+                "url": BACKPORT_PAYLOAD_CONTENT_EXTENSION,
+                "valueCode": "empty"
+            }]}
+        }});
+        assert_eq!(
+            parse_payload_content(&resource, &resource["channel"]),
+            PayloadContent::Empty
+        );
+
+        // A MIME type alone means the default
+        let resource = json!({"channel": {"payload": "application/fhir+json"}});
+        assert_eq!(
+            parse_payload_content(&resource, &resource["channel"]),
+            PayloadContent::FullResource
+        );
+    }
+
+    #[test]
+    fn test_parse_content_type() {
+        let resource = json!({"channel": {"payload": "application/fhir+json;fhirVersion=4.0"}});
+        assert_eq!(
+            parse_content_type(&resource, &resource["channel"], &[]),
+            "application/fhir+json;fhirVersion=4.0"
+        );
+
+        let resource = json!({"contentType": "application/fhir+xml", "channel": {}});
+        assert_eq!(
+            parse_content_type(&resource, &resource["channel"], &[]),
+            "application/fhir+json"
+        );
+
+        let headers = vec![("Content-Type".to_string(), "application/json".to_string())];
+        let resource = json!({"channel": {"payload": "full-resource"}});
+        assert_eq!(
+            parse_content_type(&resource, &resource["channel"], &headers),
+            "application/json"
+        );
+    }
+}
@@ -91,6 +91,11 @@ impl TopicRegistry {
         *self.topics.write() = topics;
         *self.type_index.write() = type_index;
 
+        // Compile the topics' expressions before the first event needs them
+        crate::cache::fhirpath_cache()
+            .warm_up(self.fhirpath_expressions())
+            .await;
+
         Ok(())
     }
 
@@ -127,6 +132,26 @@ impl TopicRegistry {
             .collect()
     }
 
+    /// FHIRPath expressions of the cached topics: trigger criteria and filter
+    /// definitions, for compiling ahead of the first event.
+    pub fn fhirpath_expressions(&self) -> Vec<String> {
+        let topics = self.topics.read();
+        topics
+            .values()
+            .flat_map(|topic| {
+                let criteria = topic
+                    .resource_triggers
+                    .iter()
+                    .filter_map(|t| t.fhirpath_criteria.clone());
+                let filters = topic
+                    .can_filter_by
+                    .iter()
+                    .filter_map(|f| f.filter_definition.clone());
+                criteria.chain(filters).collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
     /// Get a topic by URL.
     pub fn get_topic(&self, url: &str) -> Option<ParsedSubscriptionTopic> {
         self.topics.read().get(url).cloned()
@@ -270,6 +270,14 @@ impl SubscriptionStatus {
     }
 }
 
+/// Extension on `Subscription.channel` carrying the rest-hook signing secret.
+///
+/// Accepted on writes only: the secret is moved to
+/// [`SubscriptionSecrets`](super::secrets::SubscriptionSecrets) before the
+/// resource is stored.
+pub const SIGNING_SECRET_EXTENSION: &str =
+    "http://octofhir.io/StructureDefinition/subscription-signing-secret";
+
 /// Channel configuration for notification delivery.
 #[derive(Debug, Clone)]
 pub enum SubscriptionChannel {
@@ -283,6 +291,8 @@ pub enum SubscriptionChannel {
         payload_content: PayloadContent,
         /// Content type for the payload
         content_type: String,
+        /// Shared secret for signing deliveries, see [`octofhir_core::signing`]
+        signing_secret: Option<String>,
     },
 
     /// WebSocket: Real-time connection
@@ -314,6 +324,18 @@ impl SubscriptionChannel {
             Self::Message { .. } => "message",
         }
     }
+
+    /// Returns how much of the focus resource notifications carry.
+    ///
+    /// Only REST-hook channels configure this; others get full resources.
+    pub fn payload_content(&self) -> PayloadContent {
+        match self {
+            Self::RestHook {
+                payload_content, ..
+            } => *payload_content,
+            _ => PayloadContent::FullResource,
+        }
+    }
 }
 
 /// Payload content level for notifications.
@@ -329,14 +351,50 @@ pub enum PayloadContent {
     FullResource,
 }
 
+impl PayloadContent {
+    /// Parses a payload content code, `None` if it is not one.
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code.to_lowercase().as_str() {
+            "empty" => Some(Self::Empty),
+            "id-only" => Some(Self::IdOnly),
+            "full-resource" => Some(Self::FullResource),
+            _ => None,
+        }
+    }
+}
+
 impl From<&str> for PayloadContent {
     fn from(s: &str) -> Self {
-        match s.to_lowercase().as_str() {
-            "empty" => Self::Empty,
-            "id-only" => Self::IdOnly,
-            "full-resource" => Self::FullResource,
-            _ => Self::FullResource,
+        Self::from_code(s).unwrap_or_default()
+    }
+}
+
+/// Extension on `Subscription.channel.payload` carrying the payload content
+/// code in the R4 Subscriptions Backport IG.
+pub const BACKPORT_PAYLOAD_CONTENT_EXTENSION: &str =
+    "http://hl7.org/fhir/uv/subscriptions-backport/StructureDefinition/backport-payload-content";
+
+/// Default content type of notification bundles.
+pub const DEFAULT_NOTIFICATION_CONTENT_TYPE: &str = "application/fhir+json";
+
+/// Content type to deliver notifications in, given the one a subscriber
+/// asked for.
+///
+/// Notifications are only rendered as JSON, so JSON types are kept as
+/// requested (including parameters such as `fhirVersion`) and anything else
+/// falls back to [`DEFAULT_NOTIFICATION_CONTENT_TYPE`].
+pub fn negotiate_content_type(requested: &str) -> String {
+    let essence = requested
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+    match essence.as_str() {
+        "application/fhir+json" | "application/json" | "application/json+fhir" => {
+            requested.trim().to_string()
         }
+        _ => DEFAULT_NOTIFICATION_CONTENT_TYPE.to_string(),
     }
 }
 
@@ -531,6 +589,7 @@ pub struct NotificationBundleBuilder {
     event_type: SubscriptionEventType,
     event_number: i64,
     focus: Option<serde_json::Value>,
+    payload_content: PayloadContent,
 }
 
 impl NotificationBundleBuilder {
@@ -547,6 +606,7 @@ impl NotificationBundleBuilder {
             event_type,
             event_number,
             focus: None,
+            payload_content: PayloadContent::default(),
         }
     }
 
@@ -556,6 +616,15 @@ impl NotificationBundleBuilder {
         self
     }
 
+    /// Set how much of the focus resource the bundle carries.
+    ///
+    /// `empty` leaves the focus out, `id-only` references it without its
+    /// content and `full-resource` (the default) includes it.
+    pub fn with_payload_content(mut self, payload_content: PayloadContent) -> Self {
+        self.payload_content = payload_content;
+        self
+    }
+
     /// Build the notification bundle as FHIR Bundle resource.
     pub fn build(self) -> serde_json::Value {
         let timestamp = OffsetDateTime::now_utc()
@@ -564,16 +633,39 @@ impl NotificationBundleBuilder {
 
         let mut entries = vec![];
 
+        // Focus reference, unless the subscriber asked for an empty payload
+        let focus = self
+            .focus
+            .filter(|_| self.payload_content != PayloadContent::Empty)
+            .map(|focus| {
+                let resource_type = focus
+                    .get("resourceType")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("Resource")
+                    .to_string();
+                let resource_id = focus
+                    .get("id")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("unknown")
+                    .to_string();
+                (format!("{resource_type}/{resource_id}"), focus)
+            });
+
+        let mut notification_event = serde_json::json!({
+            "eventNumber": self.event_number.to_string(),
+            "timestamp": timestamp
+        });
+        if let Some((reference, _)) = &focus {
+            notification_event["focus"] = serde_json::json!({ "reference": reference });
+        }
+
         // Add SubscriptionStatus as first entry
         let status = serde_json::json!({
             "resourceType": "SubscriptionStatus",
             "status": "active",
             "type": self.event_type.as_str(),
             "eventsSinceSubscriptionStart": self.event_number.to_string(),
-            "notificationEvent": [{
-                "eventNumber": self.event_number.to_string(),
-                "timestamp": timestamp
-            }],
+            "notificationEvent": [notification_event],
             "subscription": {
                 "reference": format!("Subscription/{}", self.subscription_id)
             },
@@ -592,28 +684,22 @@ impl NotificationBundleBuilder {
             }
         }));
 
-        // Add focus resource if present
-        if let Some(focus) = self.focus {
-            let resource_type = focus
-                .get("resourceType")
-                .and_then(|v| v.as_str())
-                .unwrap_or("Resource");
-            let resource_id = focus
-                .get("id")
-                .and_then(|v| v.as_str())
-                .unwrap_or("unknown");
-
-            entries.push(serde_json::json!({
-                "fullUrl": format!("{}/{}", resource_type, resource_id),
-                "resource": focus,
+        // Add focus entry, with the resource only for full-resource payloads
+        if let Some((reference, resource)) = focus {
+            let mut entry = serde_json::json!({
+                "fullUrl": reference,
                 "request": {
                     "method": "GET",
-                    "url": format!("{}/{}", resource_type, resource_id)
+                    "url": reference
                 },
                 "response": {
                     "status": "200"
                 }
-            }));
+            });
+            if self.payload_content == PayloadContent::FullResource {
+                entry["resource"] = resource;
+            }
+            entries.push(entry);
         }
 
         serde_json::json!({
@@ -624,3 +710,63 @@ impl NotificationBundleBuilder {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn bundle(payload_content: PayloadContent) -> serde_json::Value {
+        NotificationBundleBuilder::new(
+            "sub-1".to_string(),
+            "http://example.org/topic".to_string(),
+            SubscriptionEventType::EventNotification,
+            3,
+        )
+        .with_focus(json!({"resourceType": "Patient", "id": "p1", "active": true}))
+        .with_payload_content(payload_content)
+        .build()
+    }
+
+    #[test]
+    fn test_notification_bundle_payload_content() {
+        let full = bundle(PayloadContent::FullResource);
+        assert_eq!(full["entry"][1]["resource"]["id"], "p1");
+        assert_eq!(
+            full["entry"][0]["resource"]["notificationEvent"][0]["focus"]["reference"],
+            "Patient/p1"
+        );
+
+        let id_only = bundle(PayloadContent::IdOnly);
+        assert_eq!(id_only["entry"][1]["fullUrl"], "Patient/p1");
+        assert!(id_only["entry"][1].get("resource").is_none());
+        assert_eq!(
+            id_only["entry"][0]["resource"]["notificationEvent"][0]["focus"]["reference"],
+            "Patient/p1"
+        );
+
+        let empty = bundle(PayloadContent::Empty);
+        assert_eq!(empty["entry"].as_array().unwrap().len(), 1);
+        assert!(
+            empty["entry"][0]["resource"]["notificationEvent"][0]
+                .get("focus")
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_negotiate_content_type() {
+        assert_eq!(
+            negotiate_content_type("application/fhir+json; fhirVersion=4.0"),
+            "application/fhir+json; fhirVersion=4.0"
+        );
+        assert_eq!(
+            negotiate_content_type("application/json"),
+            "application/json"
+        );
+        assert_eq!(
+            negotiate_content_type("application/fhir+xml"),
+            DEFAULT_NOTIFICATION_CONTENT_TYPE
+        );
+    }
+}
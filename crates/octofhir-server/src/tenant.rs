@@ -0,0 +1,121 @@
+//! Tenant resolution for multi-tenant deployments.
+//!
+//! With `multitenancy.enabled`, every authenticated FHIR API request is bound
+//! to the tenant named by a claim of its access token (`multitenancy.claim`).
+//! The request then runs inside an [`octofhir_storage::with_tenant`] scope, and
+//! the PostgreSQL pool points each connection at the tenant's schema.
+//!
+//! Only tokens that passed validation are consulted, so the claim is as
+//! trustworthy as the issuer that signed it. Tokens naming a tenant that is
+//! not listed in `multitenancy.tenants` are rejected.
+
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use octofhir_db_postgres::{JsonbCompression, SchemaManager};
+use octofhir_storage::TenantId;
+use serde_json::Value;
+use sqlx_postgres::PgPool;
+
+use crate::config::MultitenancyConfig;
+
+/// Reads a string claim from a JWT payload.
+///
+/// The signature is not checked here; callers must only pass tokens that
+/// have already been validated.
+pub fn token_claim(token: &str, claim: &str) -> Option<String> {
+    let payload = token.split('.').nth(1)?;
+    let bytes = URL_SAFE_NO_PAD.decode(payload.trim_end_matches('=')).ok()?;
+    let claims: Value = serde_json::from_slice(&bytes).ok()?;
+    claims.get(claim)?.as_str().map(str::to_string)
+}
+
+/// Maps a claim value to a configured tenant.
+///
+/// Returns `Ok(None)` when the token carries no tenant, and an error when it
+/// names a malformed or unknown tenant.
+pub fn resolve_tenant(
+    value: Option<&str>,
+    config: &MultitenancyConfig,
+) -> Result<Option<TenantId>, String> {
+    let Some(value) = value else {
+        return Ok(None);
+    };
+    let tenant = TenantId::new(value).map_err(|e| e.to_string())?;
+    if !config.tenants.iter().any(|t| t == tenant.as_str()) {
+        return Err(format!("Unknown tenant '{tenant}'"));
+    }
+    Ok(Some(tenant))
+}
+
+/// Whether `path` belongs to the FHIR API, the only surface served per tenant.
+pub fn is_tenant_scoped_path(path: &str) -> bool {
+    path == "/fhir" || path.starts_with("/fhir/")
+}
+
+/// Creates the schema of every configured tenant, copying the current set of
+/// resource tables. Existing tables are left untouched.
+pub async fn provision_tenants(
+    pool: &PgPool,
+    config: &MultitenancyConfig,
+    compression: JsonbCompression,
+) -> Result<(), String> {
+    let schema_manager = SchemaManager::new(pool.clone()).with_compression(compression);
+    for tenant in &config.tenants {
+        let tenant = TenantId::new(tenant.as_str()).map_err(|e| e.to_string())?;
+        schema_manager
+            .provision_tenant_schema(&tenant)
+            .await
+            .map_err(|e| format!("Failed to provision tenant '{tenant}': {e}"))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> MultitenancyConfig {
+        MultitenancyConfig {
+            enabled: true,
+            tenants: vec!["acme".to_string()],
+            ..Default::default()
+        }
+    }
+
+    fn token(payload: &str) -> String {
+        format!(
+            "eyJhbGciOiJSUzI1NiJ9.{}.sig",
+            URL_SAFE_NO_PAD.encode(payload)
+        )
+    }
+
+    #[test]
+    fn test_token_claim() {
+        let token = token(r#"{"sub":"u1","tenant":"acme","n":1}"#);
+        assert_eq!(token_claim(&token, "tenant").as_deref(), Some("acme"));
+        assert_eq!(token_claim(&token, "org"), None);
+        assert_eq!(token_claim(&token, "n"), None);
+        assert_eq!(token_claim("not-a-jwt", "tenant"), None);
+    }
+
+    #[test]
+    fn test_resolve_tenant() {
+        let cfg = config();
+        assert_eq!(resolve_tenant(None, &cfg), Ok(None));
+        assert_eq!(
+            resolve_tenant(Some("acme"), &cfg),
+            Ok(Some(TenantId::new("acme").unwrap()))
+        );
+        assert!(resolve_tenant(Some("globex"), &cfg).is_err());
+        assert!(resolve_tenant(Some("ACME"), &cfg).is_err());
+    }
+
+    #[test]
+    fn test_is_tenant_scoped_path() {
+        assert!(is_tenant_scoped_path("/fhir"));
+        assert!(is_tenant_scoped_path("/fhir/Patient/1"));
+        assert!(!is_tenant_scoped_path("/fhirx"));
+        assert!(!is_tenant_scoped_path("/api/sql"));
+        assert!(!is_tenant_scoped_path("/admin/users"));
+    }
+}
@@ -378,6 +378,25 @@ impl ValidationService {
     }
 }
 
+/// Lets GraphQL create/update mutations validate input the way the REST API
+/// does. Only error and fatal issues are reported; warnings do not block the
+/// write.
+#[async_trait::async_trait]
+impl octofhir_graphql::ResourceValidator for ValidationService {
+    async fn validate(&self, resource: &JsonValue) -> Vec<octofhir_graphql::ValidationIssue> {
+        ValidationService::validate(self, resource)
+            .await
+            .issues
+            .into_iter()
+            .filter(|issue| matches!(issue.severity, IssueSeverity::Fatal | IssueSeverity::Error))
+            .map(|issue| octofhir_graphql::ValidationIssue {
+                path: issue.location,
+                message: issue.diagnostics,
+            })
+            .collect()
+    }
+}
+
 /// Placeholder for resource validation using cached resource types.
 /// This will evolve to real profile/StructureDefinition validation.
 pub fn validate_resource(
@@ -229,3 +229,268 @@ async fn error_cases_invalid_resource_and_id_mismatch_and_delete_404() {
     let _ = shutdown_tx.send(());
     let _ = handle.await;
 }
+
+#[tokio::test]
+#[ignore = "requires FHIR packages in .fhir/ directory"]
+async fn identical_update_does_not_create_new_version() {
+    let (_container, postgres_url) = start_postgres().await;
+    let config = create_config(&postgres_url);
+    let (base, shutdown_tx, handle) = start_server(&config).await;
+    let client = reqwest::Client::new();
+    let fhir_base = format!("{base}/fhir");
+
+    let payload = json!({
+        "resourceType": "Patient",
+        "active": true,
+        "name": [{"family": "Noop", "given": ["Same"]}],
+    });
+    let resp = client
+        .post(format!("{fhir_base}/Patient"))
+        .header("accept", "application/fhir+json")
+        .header("content-type", "application/fhir+json")
+        .json(&payload)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), reqwest::StatusCode::CREATED);
+    let created: Value = resp.json().await.unwrap();
+    let id = created["id"].as_str().unwrap().to_string();
+    let version = created["meta"]["versionId"].as_str().unwrap().to_string();
+
+    // Same content with reordered keys and the stored meta echoed back
+    let same = json!({
+        "name": [{"given": ["Same"], "family": "Noop"}],
+        "meta": created["meta"].clone(),
+        "id": id,
+        "active": true,
+        "resourceType": "Patient",
+    });
+    let resp = client
+        .put(format!("{fhir_base}/Patient/{id}"))
+        .header("accept", "application/fhir+json")
+        .header("content-type", "application/fhir+json")
+        .json(&same)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), reqwest::StatusCode::OK);
+    let etag = resp.headers()["etag"].to_str().unwrap().to_string();
+    assert_eq!(etag, format!("W/\"{version}\""));
+    let after: Value = resp.json().await.unwrap();
+    assert_eq!(after["meta"]["versionId"], version.as_str());
+
+    // History still has a single version
+    let resp = client
+        .get(format!("{fhir_base}/Patient/{id}/_history"))
+        .header("accept", "application/fhir+json")
+        .send()
+        .await
+        .unwrap();
+    assert!(resp.status().is_success());
+    let history: Value = resp.json().await.unwrap();
+    assert_eq!(history["entry"].as_array().map(Vec::len), Some(1));
+
+    let _ = shutdown_tx.send(());
+    let _ = handle.await;
+}
+
+#[tokio::test]
+#[ignore = "requires FHIR packages in .fhir/ directory"]
+async fn put_as_create_returns_201_then_updates_return_200() {
+    let (_container, postgres_url) = start_postgres().await;
+    let config = create_config(&postgres_url);
+    let (base, shutdown_tx, handle) = start_server(&config).await;
+    let client = reqwest::Client::new();
+    let fhir_base = format!("{base}/fhir");
+
+    // PUT to an id that does not exist yet creates it
+    let id = "put-as-create-1";
+    let payload = json!({
+        "resourceType": "Patient",
+        "id": id,
+        "name": [{"family": "Upsert"}],
+    });
+    let resp = client
+        .put(format!("{fhir_base}/Patient/{id}"))
+        .header("accept", "application/fhir+json")
+        .header("content-type", "application/fhir+json")
+        .json(&payload)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), reqwest::StatusCode::CREATED);
+    let location = resp.headers()["location"].to_str().unwrap().to_string();
+    let etag = resp.headers()["etag"].to_str().unwrap().to_string();
+    let created: Value = resp.json().await.unwrap();
+    let version = created["meta"]["versionId"].as_str().unwrap();
+    assert!(location.ends_with(&format!("/Patient/{id}/_history/{version}")));
+    assert_eq!(etag, format!("W/\"{version}\""));
+
+    // The same PUT with changed content is now an update
+    let changed = json!({
+        "resourceType": "Patient",
+        "id": id,
+        "name": [{"family": "Upsert", "given": ["Again"]}],
+    });
+    let resp = client
+        .put(format!("{fhir_base}/Patient/{id}"))
+        .header("accept", "application/fhir+json")
+        .header("content-type", "application/fhir+json")
+        .json(&changed)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), reqwest::StatusCode::OK);
+    assert!(resp.headers().get("location").is_none());
+    assert!(resp.headers().contains_key("content-location"));
+
+    // Transaction entries make the same distinction
+    let bundle = json!({
+        "resourceType": "Bundle",
+        "type": "transaction",
+        "entry": [
+            {
+                "resource": {"resourceType": "Patient", "name": [{"family": "TxNew"}]},
+                "request": {"method": "PUT", "url": "Patient/put-as-create-2"}
+            },
+            {
+                "resource": {"resourceType": "Patient", "name": [{"family": "TxExisting"}]},
+                "request": {"method": "PUT", "url": format!("Patient/{id}")}
+            }
+        ]
+    });
+    let resp = client
+        .post(fhir_base.clone())
+        .header("accept", "application/fhir+json")
+        .header("content-type", "application/fhir+json")
+        .json(&bundle)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), reqwest::StatusCode::OK);
+    let result: Value = resp.json().await.unwrap();
+    let entries = result["entry"].as_array().unwrap();
+    assert_eq!(entries[0]["response"]["status"], "201 Created");
+    assert!(entries[0]["response"]["location"].is_string());
+    assert!(entries[0]["response"]["etag"].is_string());
+    assert_eq!(entries[1]["response"]["status"], "200 OK");
+
+    let _ = shutdown_tx.send(());
+    let _ = handle.await;
+}
+
+#[tokio::test]
+#[ignore = "requires FHIR packages in .fhir/ directory"]
+async fn default_sort_orders_searches_without_sort() {
+    let (_container, postgres_url) = start_postgres().await;
+    let mut config = create_config(&postgres_url);
+    config.search.default_sort = vec!["-_lastUpdated".to_string(), "_id".to_string()];
+    let (base, shutdown_tx, handle) = start_server(&config).await;
+    let client = reqwest::Client::new();
+    let fhir_base = format!("{base}/fhir");
+
+    let mut created_ids = Vec::new();
+    for given in ["First", "Second", "Third"] {
+        let payload = json!({
+            "resourceType": "Patient",
+            "name": [{"family": "Ordered", "given": [given]}],
+        });
+        let resp = client
+            .post(format!("{fhir_base}/Patient"))
+            .header("accept", "application/fhir+json")
+            .header("content-type", "application/fhir+json")
+            .json(&payload)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), reqwest::StatusCode::CREATED);
+        let created: Value = resp.json().await.unwrap();
+        created_ids.push(created["id"].as_str().unwrap().to_string());
+    }
+
+    let url = format!("{fhir_base}/Patient?family=Ordered");
+    let first = search_result_ids(&client, &url).await;
+    let second = search_result_ids(&client, &url).await;
+    assert_eq!(
+        first, second,
+        "identical searches must return the same order"
+    );
+
+    // Most recently updated first
+    created_ids.reverse();
+    assert_eq!(first, created_ids);
+
+    let _ = shutdown_tx.send(());
+    let _ = handle.await;
+}
+
+#[tokio::test]
+#[ignore = "requires FHIR packages in .fhir/ directory"]
+async fn built_in_default_sort_orders_compartment_and_everything() {
+    let (_container, postgres_url) = start_postgres().await;
+    let config = create_config(&postgres_url);
+    let (base, shutdown_tx, handle) = start_server(&config).await;
+    let client = reqwest::Client::new();
+    let fhir_base = format!("{base}/fhir");
+
+    let create = |payload: Value| {
+        let client = client.clone();
+        let url = format!("{fhir_base}/{}", payload["resourceType"].as_str().unwrap());
+        async move {
+            let resp = client
+                .post(url)
+                .header("accept", "application/fhir+json")
+                .header("content-type", "application/fhir+json")
+                .json(&payload)
+                .send()
+                .await
+                .unwrap();
+            assert_eq!(resp.status(), reqwest::StatusCode::CREATED);
+            let created: Value = resp.json().await.unwrap();
+            created["id"].as_str().unwrap().to_string()
+        }
+    };
+
+    let patient_id = create(json!({"resourceType": "Patient"})).await;
+    let mut observation_ids = Vec::new();
+    for text in ["first", "second", "third"] {
+        observation_ids.push(
+            create(json!({
+                "resourceType": "Observation",
+                "status": "final",
+                "code": {"text": text},
+                "subject": {"reference": format!("Patient/{patient_id}")},
+            }))
+            .await,
+        );
+    }
+
+    // Oldest first, as `_lastUpdated,_id` orders them
+    let url = format!("{fhir_base}/Patient/{patient_id}/Observation");
+    assert_eq!(search_result_ids(&client, &url).await, observation_ids);
+
+    let url = format!("{fhir_base}/Patient/{patient_id}/$everything");
+    let mut expected = vec![patient_id.clone()];
+    expected.extend(observation_ids);
+    assert_eq!(search_result_ids(&client, &url).await, expected);
+
+    let _ = shutdown_tx.send(());
+    let _ = handle.await;
+}
+
+async fn search_result_ids(client: &reqwest::Client, url: &str) -> Vec<String> {
+    let resp = client
+        .get(url)
+        .header("accept", "application/fhir+json")
+        .send()
+        .await
+        .unwrap();
+    assert!(resp.status().is_success());
+    let bundle: Value = resp.json().await.unwrap();
+    bundle["entry"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["resource"]["id"].as_str().unwrap().to_string())
+        .collect()
+}
@@ -6,6 +6,7 @@
 //! - Date prefixes: eq, ne, lt, le, gt, ge, sa, eb, ap
 //! - _include and _revinclude with :iterate
 //! - _elements and _summary filtering
+//! - _text relevance scoring and _sort=_score
 //! - Composite search parameters
 //! - Chained search parameters
 //!
@@ -838,6 +839,71 @@ async fn test_sort_descending() {
     let _ = shutdown_tx.send(());
 }
 
+#[tokio::test]
+async fn test_text_search_sorted_by_score() {
+    let (_container, postgres_url) = start_postgres().await;
+    let config = create_config(&postgres_url);
+    let (base, shutdown_tx, _handle) = start_server(&config).await;
+    let client = reqwest::Client::new();
+
+    let narratives = [
+        ("once", "Reports a headache after lunch."),
+        (
+            "often",
+            "Headache on waking, headache in the evening, recurring headache.",
+        ),
+        ("never", "Routine check-up, no complaints."),
+    ];
+    for (family, text) in narratives {
+        let patient = json!({
+            "resourceType": "Patient",
+            "name": [{"family": family}],
+            "text": {
+                "status": "generated",
+                "div": format!("<div xmlns=\"http://www.w3.org/1999/xhtml\">{text}</div>")
+            }
+        });
+        let resp = client
+            .post(format!("{base}/Patient"))
+            .header("content-type", "application/fhir+json")
+            .json(&patient)
+            .send()
+            .await
+            .expect("create patient");
+        assert!(resp.status().is_success(), "patient create failed");
+    }
+
+    let resp = client
+        .get(format!("{base}/Patient?_text=headache&_sort=_score"))
+        .header("accept", "application/fhir+json")
+        .send()
+        .await
+        .expect("search request");
+
+    let status = resp.status();
+    let body = resp.text().await.expect("read response body");
+    assert!(status.is_success(), "search failed with {status}: {body}");
+    let bundle: Value = serde_json::from_str(&body).expect("parse bundle");
+
+    let entries = get_bundle_entries(&bundle);
+    let families: Vec<&str> = entries
+        .iter()
+        .map(|e| e["resource"]["name"][0]["family"].as_str().unwrap())
+        .collect();
+    assert_eq!(families, ["often", "once"], "higher-ranked match first");
+
+    let scores: Vec<f64> = entries
+        .iter()
+        .map(|e| e["search"]["score"].as_f64().expect("entry.search.score"))
+        .collect();
+    assert!(
+        scores[0] > scores[1],
+        "scores must descend with _sort=_score: {scores:?}"
+    );
+
+    let _ = shutdown_tx.send(());
+}
+
 // =============================================================================
 // Combined Search Tests
 // =============================================================================
@@ -438,6 +438,84 @@ async fn test_transaction_rollback_preserves_existing_data() {
     let _ = shutdown_tx.send(());
 }
 
+#[tokio::test]
+async fn test_transaction_rollback_reverts_earlier_writes() {
+    let (_container, postgres_url) = start_postgres().await;
+    let config = create_config(&postgres_url);
+    let (base, shutdown_tx, _handle) = start_server(&config).await;
+    let client = reqwest::Client::new();
+
+    let resp = client
+        .put(format!("{base}/Patient/rollback-target"))
+        .header("content-type", "application/fhir+json")
+        .json(&json!({
+            "resourceType": "Patient",
+            "id": "rollback-target",
+            "name": [{"family": "Original"}]
+        }))
+        .send()
+        .await
+        .expect("create patient");
+    assert!(resp.status().is_success());
+
+    // The POST is written first; the stale If-Match then fails the PUT inside
+    // the same database transaction.
+    let bundle = json!({
+        "resourceType": "Bundle",
+        "type": "transaction",
+        "entry": [
+            {
+                "resource": {"resourceType": "Patient", "name": [{"family": "RolledBack"}]},
+                "request": {"method": "POST", "url": "Patient"}
+            },
+            {
+                "resource": {
+                    "resourceType": "Patient",
+                    "id": "rollback-target",
+                    "name": [{"family": "Modified"}]
+                },
+                "request": {
+                    "method": "PUT",
+                    "url": "Patient/rollback-target",
+                    "ifMatch": "W/\"999\""
+                }
+            }
+        ]
+    });
+    let resp = client
+        .post(&base)
+        .header("content-type", "application/fhir+json")
+        .json(&bundle)
+        .send()
+        .await
+        .expect("transaction request");
+    assert_eq!(resp.status(), reqwest::StatusCode::PRECONDITION_FAILED);
+    let outcome: Value = resp.json().await.expect("parse outcome");
+    assert_eq!(outcome["resourceType"], "OperationOutcome");
+
+    let search: Value = client
+        .get(format!("{base}/Patient?family=RolledBack"))
+        .header("accept", "application/fhir+json")
+        .send()
+        .await
+        .expect("search request")
+        .json()
+        .await
+        .expect("parse search");
+    assert_eq!(
+        search["entry"].as_array().map_or(0, Vec::len),
+        0,
+        "the POST written before the failure must be rolled back"
+    );
+
+    let target = read_resource(&client, &base, "Patient", "rollback-target")
+        .await
+        .expect("patient should still exist");
+    assert_eq!(target["name"][0]["family"], "Original");
+
+    let _ = shutdown_tx.send(());
+}
+
 // =============================================================================
 // Batch vs Transaction Tests (Isolation Difference)
 // =============================================================================
@@ -1285,6 +1363,128 @@ async fn test_transaction_patch_entry() {
     let _ = shutdown_tx.send(());
 }
 
+#[tokio::test]
+async fn test_conditional_patch_entries() {
+    let (_container, postgres_url) = start_postgres().await;
+    let config = create_config(&postgres_url);
+    let (base, shutdown_tx, _handle) = start_server(&config).await;
+    let client = reqwest::Client::new();
+
+    for family in ["CondPatchOne", "CondPatchTwin", "CondPatchTwin"] {
+        let resp = client
+            .post(format!("{base}/Patient"))
+            .header("content-type", "application/fhir+json")
+            .json(&json!({
+                "resourceType": "Patient",
+                "identifier": [{"system": "http://example.org/mrn", "value": family}],
+                "name": [{"family": family, "given": ["Before"]}]
+            }))
+            .send()
+            .await
+            .expect("create patient");
+        assert!(resp.status().is_success());
+    }
+
+    let bundle = json!({
+        "resourceType": "Bundle",
+        "type": "transaction",
+        "entry": [
+            {
+                "resource": [
+                    {"op": "replace", "path": "/name/0/given/0", "value": "After"}
+                ],
+                "request": {
+                    "method": "PATCH",
+                    "url": "Patient?identifier=http://example.org/mrn|CondPatchOne"
+                }
+            }
+        ]
+    });
+
+    let resp = client
+        .post(&base)
+        .header("content-type", "application/fhir+json")
+        .json(&bundle)
+        .send()
+        .await
+        .expect("transaction request");
+
+    assert!(
+        resp.status().is_success(),
+        "Conditional PATCH in transaction should succeed"
+    );
+
+    let search_bundle: Value = client
+        .get(format!(
+            "{base}/Patient?identifier=http://example.org/mrn|CondPatchOne"
+        ))
+        .header("accept", "application/fhir+json")
+        .send()
+        .await
+        .expect("search request")
+        .json()
+        .await
+        .expect("parse");
+    assert_eq!(
+        search_bundle["entry"][0]["resource"]["name"][0]["given"][0],
+        "After"
+    );
+
+    // Ambiguous and unmatched criteria fail per entry in a batch
+    let bundle = json!({
+        "resourceType": "Bundle",
+        "type": "batch",
+        "entry": [
+            {
+                "resource": [
+                    {"op": "replace", "path": "/name/0/given/0", "value": "After"}
+                ],
+                "request": {
+                    "method": "PATCH",
+                    "url": "Patient?identifier=http://example.org/mrn|CondPatchTwin"
+                }
+            },
+            {
+                "resource": [
+                    {"op": "replace", "path": "/name/0/given/0", "value": "After"}
+                ],
+                "request": {
+                    "method": "PATCH",
+                    "url": "Patient?identifier=http://example.org/mrn|CondPatchNone"
+                }
+            },
+            {
+                "resource": [
+                    {"op": "replace", "path": "/name/0/given/0", "value": "After"}
+                ],
+                "request": {
+                    "method": "PATCH",
+                    "url": "Patient?"
+                }
+            }
+        ]
+    });
+
+    let response_bundle: Value = client
+        .post(&base)
+        .header("content-type", "application/fhir+json")
+        .json(&bundle)
+        .send()
+        .await
+        .expect("batch request")
+        .json()
+        .await
+        .expect("parse response");
+
+    let entries = response_bundle["entry"].as_array().expect("entries");
+    let status = |i: usize| entries[i]["response"]["status"].as_str().unwrap_or("");
+    assert!(status(0).starts_with("412"), "got {}", status(0));
+    assert!(status(1).starts_with("404"), "got {}", status(1));
+    assert!(status(2).starts_with("400"), "got {}", status(2));
+
+    let _ = shutdown_tx.send(());
+}
+
 #[tokio::test]
 async fn test_transaction_get_search_sees_uncommitted_create() {
     let (_container, postgres_url) = start_postgres().await;
@@ -1560,3 +1760,85 @@ async fn test_batch_invalid_resource_per_entry_422_siblings_persist() {
 
     let _ = shutdown_tx.send(());
 }
+
+/// Every batch entry gets a response in request order: failures carry their
+/// OperationOutcome pointing at the entry, and successes follow `Prefer`.
+#[tokio::test]
+async fn test_batch_mixed_entries_report_per_entry_outcomes() {
+    let (_container, postgres_url) = start_postgres().await;
+    let config = create_config(&postgres_url);
+    let (base, shutdown_tx, _handle) = start_server(&config).await;
+    let client = reqwest::Client::new();
+
+    let bundle = json!({
+        "resourceType": "Bundle",
+        "type": "batch",
+        "entry": [
+            {
+                "resource": {"resourceType": "Patient", "name": [{"family": "BatchMixed"}]},
+                "request": {"method": "POST", "url": "Patient"}
+            },
+            {
+                "request": {"method": "GET", "url": "Patient/does-not-exist"}
+            },
+            {
+                "resource": {"resourceType": "Patient", "active": "not-a-boolean"},
+                "request": {"method": "POST", "url": "Patient"}
+            },
+            {
+                "request": {"method": "FETCH", "url": "Patient"}
+            }
+        ]
+    });
+
+    let resp = client
+        .post(&base)
+        .header("content-type", "application/fhir+json")
+        .header("prefer", "return=OperationOutcome")
+        .json(&bundle)
+        .send()
+        .await
+        .expect("batch request");
+    assert_eq!(resp.status(), reqwest::StatusCode::OK);
+
+    let response_bundle: Value = resp.json().await.expect("parse response");
+    assert_eq!(response_bundle["type"], "batch-response");
+    let entries = response_bundle["entry"].as_array().expect("entries");
+    assert_eq!(entries.len(), 4, "one response per request entry");
+
+    let statuses: Vec<&str> = entries
+        .iter()
+        .map(|e| e["response"]["status"].as_str().unwrap_or(""))
+        .collect();
+    assert!(statuses[0].starts_with("201"), "got {}", statuses[0]);
+    assert!(statuses[1].starts_with("404"), "got {}", statuses[1]);
+    assert!(statuses[2].starts_with("422"), "got {}", statuses[2]);
+    assert!(statuses[3].starts_with("400"), "got {}", statuses[3]);
+
+    // return=OperationOutcome: the created entry has an outcome, no resource
+    assert!(entries[0].get("resource").is_none());
+    assert_eq!(
+        entries[0]["response"]["outcome"]["issue"][0]["severity"],
+        "information"
+    );
+    assert!(entries[0]["response"]["location"].is_string());
+
+    for (index, entry) in entries.iter().enumerate().skip(1) {
+        let outcome = &entry["response"]["outcome"];
+        assert_eq!(outcome["resourceType"], "OperationOutcome");
+        let issues = outcome["issue"].as_array().expect("issues");
+        assert!(!issues.is_empty());
+        assert!(
+            issues
+                .iter()
+                .all(|issue| issue["diagnostics"].is_string() || issue["expression"].is_array()),
+            "entry {index} issues should explain the failure"
+        );
+    }
+    assert_eq!(
+        entries[1]["response"]["outcome"]["issue"][0]["expression"],
+        json!(["Bundle.entry[1]"])
+    );
+
+    let _ = shutdown_tx.send(());
+}
@@ -38,6 +38,7 @@ fn test_skip_validation_header_parsing() {
     let config = ValidationSettings {
         allow_skip_validation: true,
         skip_reference_validation: false,
+        ..Default::default()
     };
 
     // Test with "true"
@@ -96,6 +97,7 @@ fn test_two_layer_security() {
     let config_disabled = ValidationSettings {
         allow_skip_validation: false,
         skip_reference_validation: false,
+        ..Default::default()
     };
 
     let mut headers = HeaderMap::new();
@@ -108,6 +110,7 @@ fn test_two_layer_security() {
     let config_enabled = ValidationSettings {
         allow_skip_validation: true,
         skip_reference_validation: false,
+        ..Default::default()
     };
     headers.clear();
 
@@ -118,3 +121,18 @@ fn test_two_layer_security() {
     headers.insert("X-Skip-Validation", "true".parse().unwrap());
     assert!(should_skip_validation(&headers, &config_enabled));
 }
+
+#[test]
+fn test_validation_mode_overrides() {
+    use octofhir_server::config::ValidationMode;
+
+    let json = r#"{"mode":"warn","mode_overrides":{"Patient":"enforce","Basic":"off"}}"#;
+    let config: ValidationSettings = serde_json::from_str(json).unwrap();
+    assert_eq!(config.mode_for("Patient"), ValidationMode::Enforce);
+    assert_eq!(config.mode_for("Basic"), ValidationMode::Off);
+    assert_eq!(config.mode_for("Observation"), ValidationMode::Warn);
+
+    // Writes are validated and rejected unless configured otherwise
+    let config = ValidationSettings::default();
+    assert_eq!(config.mode_for("Observation"), ValidationMode::Enforce);
+}
@@ -0,0 +1,47 @@
+//! Resource types exposed by the server.
+//!
+//! `fhir.resource_types` narrows the FHIR API to a set of resource types. The
+//! set is process-wide: it is installed once at startup with
+//! [`set_resource_type_allowlist`], and backends check
+//! [`is_resource_type_allowed`] on every read and search, including the
+//! resources `_include`/`_revinclude` pull in, so no path (compartment
+//! search, `$everything`, GraphQL, ...) returns other types. An empty list
+//! allows every type.
+
+use std::collections::HashSet;
+use std::sync::{Arc, RwLock};
+
+static ALLOWLIST: RwLock<Option<Arc<HashSet<String>>>> = RwLock::new(None);
+
+/// Installs the allowed resource types; an empty list allows all of them.
+pub fn set_resource_type_allowlist(resource_types: impl IntoIterator<Item = String>) {
+    let types: HashSet<String> = resource_types.into_iter().collect();
+    let allowlist = (!types.is_empty()).then(|| Arc::new(types));
+    *ALLOWLIST.write().unwrap_or_else(|e| e.into_inner()) = allowlist;
+}
+
+/// Whether `resource_type` may be read or searched.
+#[must_use]
+pub fn is_resource_type_allowed(resource_type: &str) -> bool {
+    match ALLOWLIST.read().unwrap_or_else(|e| e.into_inner()).as_ref() {
+        Some(types) => types.contains(resource_type),
+        None => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_allowlist() {
+        assert!(is_resource_type_allowed("Observation"));
+
+        set_resource_type_allowlist(["Patient".to_string()]);
+        assert!(is_resource_type_allowed("Patient"));
+        assert!(!is_resource_type_allowed("Observation"));
+
+        set_resource_type_allowlist(Vec::new());
+        assert!(is_resource_type_allowed("Observation"));
+    }
+}
@@ -0,0 +1,168 @@
+//! Per-request query budget.
+//!
+//! A single search can fan out into many database round-trips: the main
+//! query, a count, and one or more queries per `_include` / `_revinclude`
+//! (repeated for every `:iterate` level). Chained parameters and `_has` stay
+//! in one statement, but each link adds a join or subquery to it. A
+//! [`with_query_budget`] scope caps how many of those one request may issue.
+//! Backends call [`charge_query`] before each round-trip and
+//! [`charge_queries`] for the joins a search plan adds; once the budget is
+//! spent the call fails and the request is rejected as too complex instead
+//! of running on.
+//!
+//! Like the tenant, the budget lives in a task-local, so work spawned onto
+//! another task is not charged unless it is bound with
+//! [`in_current_budget`]. Code outside a scope is never limited.
+
+use std::future::Future;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use crate::error::StorageError;
+
+tokio::task_local! {
+    static QUERY_BUDGET: QueryBudget;
+}
+
+/// Query budget shared by everything serving one request.
+#[derive(Debug, Clone)]
+pub struct QueryBudget {
+    limit: u32,
+    used: Arc<AtomicU32>,
+}
+
+impl QueryBudget {
+    /// Creates a budget allowing `limit` queries.
+    #[must_use]
+    pub fn new(limit: u32) -> Self {
+        Self {
+            limit,
+            used: Arc::new(AtomicU32::new(0)),
+        }
+    }
+
+    /// Maximum number of queries.
+    #[must_use]
+    pub fn limit(&self) -> u32 {
+        self.limit
+    }
+
+    /// Number of queries charged so far, including rejected ones.
+    #[must_use]
+    pub fn used(&self) -> u32 {
+        self.used.load(Ordering::Relaxed)
+    }
+
+    /// Charges one query.
+    ///
+    /// # Errors
+    ///
+    /// Returns `StorageError::InvalidResource` once the limit is exceeded.
+    pub fn charge(&self) -> Result<(), StorageError> {
+        self.charge_many(1)
+    }
+
+    /// Charges `count` queries or joins at once.
+    ///
+    /// # Errors
+    ///
+    /// Returns `StorageError::InvalidResource` once the limit is exceeded.
+    pub fn charge_many(&self, count: u32) -> Result<(), StorageError> {
+        let used = self
+            .used
+            .fetch_add(count, Ordering::Relaxed)
+            .saturating_add(count);
+        if used > self.limit {
+            return Err(StorageError::invalid_resource(format!(
+                "Query is too complex: it needs more than {} database queries and joins. \
+                 Reduce _include/_revinclude iteration, chaining or _has nesting.",
+                self.limit
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Runs `fut` with a budget of `limit` queries.
+pub async fn with_query_budget<F: Future>(limit: u32, fut: F) -> F::Output {
+    QUERY_BUDGET.scope(QueryBudget::new(limit), fut).await
+}
+
+/// Returns the budget of the current task, if any.
+#[must_use]
+pub fn current_query_budget() -> Option<QueryBudget> {
+    QUERY_BUDGET.try_with(Clone::clone).ok()
+}
+
+/// Charges one query against the current budget. A no-op outside a
+/// [`with_query_budget`] scope.
+///
+/// # Errors
+///
+/// Returns `StorageError::InvalidResource` once the budget is exceeded.
+pub fn charge_query() -> Result<(), StorageError> {
+    QUERY_BUDGET.try_with(QueryBudget::charge).unwrap_or(Ok(()))
+}
+
+/// Charges `count` queries or joins against the current budget. A no-op
+/// outside a [`with_query_budget`] scope.
+///
+/// # Errors
+///
+/// Returns `StorageError::InvalidResource` once the budget is exceeded.
+pub fn charge_queries(count: u32) -> Result<(), StorageError> {
+    QUERY_BUDGET
+        .try_with(|budget| budget.charge_many(count))
+        .unwrap_or(Ok(()))
+}
+
+/// Binds `fut` to the budget of the calling task, so queries it runs on
+/// another task draw from the same budget.
+pub fn in_current_budget<F: Future>(fut: F) -> impl Future<Output = F::Output> {
+    let budget = current_query_budget();
+    async move {
+        match budget {
+            Some(budget) => QUERY_BUDGET.scope(budget, fut).await,
+            None => fut.await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_budget_exhaustion() {
+        assert!(charge_query().is_ok());
+        with_query_budget(2, async {
+            assert!(charge_query().is_ok());
+            assert!(charge_query().is_ok());
+            assert!(charge_query().is_err());
+            assert_eq!(current_query_budget().unwrap().used(), 3);
+        })
+        .await;
+        assert!(current_query_budget().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_budget_charges_joins() {
+        assert!(charge_queries(5).is_ok());
+        with_query_budget(4, async {
+            assert!(charge_queries(3).is_ok());
+            assert!(charge_query().is_ok());
+            assert!(charge_queries(1).is_err());
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_budget_shared_with_spawned_work() {
+        with_query_budget(1, async {
+            let carried = tokio::spawn(in_current_budget(async { charge_query() }));
+            assert!(carried.await.unwrap().is_ok());
+            assert!(charge_query().is_err());
+        })
+        .await;
+    }
+}
@@ -0,0 +1,165 @@
+//! Cancellation of statements abandoned by a disconnected client.
+//!
+//! When a client disconnects, the server drops the request's future, but a
+//! database statement already sent keeps running until it completes. Inside
+//! a [`with_cancel_scope`], backends [`register_statement`] with a canceller
+//! before running a statement and [`StatementGuard::finish`] it once the
+//! result is back. A guard dropped without `finish` leaves its statement
+//! registered as still running; [`CancelScope::cancel`] then cancels every
+//! such statement. The server calls it when a request is dropped before it
+//! produced a response.
+//!
+//! Like the deadline, the scope lives in a task-local, so statements run on
+//! another task are only covered if they are bound with
+//! [`in_current_cancel_scope`].
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+tokio::task_local! {
+    static CANCEL_SCOPE: CancelScope;
+}
+
+/// Cancels one running statement.
+pub type Canceller = Box<dyn FnOnce() + Send>;
+
+/// Statements running for one request.
+#[derive(Clone, Default)]
+pub struct CancelScope {
+    running: Arc<Mutex<HashMap<u64, Canceller>>>,
+    next_id: Arc<AtomicU64>,
+}
+
+impl CancelScope {
+    /// Creates a scope with no statements.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancels the statements that were started but never finished and
+    /// returns how many there were.
+    pub fn cancel(&self) -> usize {
+        let cancellers: Vec<Canceller> = self
+            .running
+            .lock()
+            .map(|mut running| running.drain().map(|(_, c)| c).collect())
+            .unwrap_or_default();
+        let count = cancellers.len();
+        for cancel in cancellers {
+            cancel();
+        }
+        count
+    }
+
+    /// Number of statements registered and not finished.
+    #[must_use]
+    pub fn running(&self) -> usize {
+        self.running.lock().map(|r| r.len()).unwrap_or_default()
+    }
+}
+
+impl std::fmt::Debug for CancelScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CancelScope")
+            .field("running", &self.running())
+            .finish()
+    }
+}
+
+/// Registration of a running statement.
+#[must_use = "a statement that is never finished is canceled with its scope"]
+pub struct StatementGuard {
+    scope: CancelScope,
+    id: u64,
+}
+
+impl StatementGuard {
+    /// Marks the statement as completed, so it is not canceled.
+    pub fn finish(self) {
+        if let Ok(mut running) = self.scope.running.lock() {
+            running.remove(&self.id);
+        }
+    }
+}
+
+/// Runs `fut` in `scope`.
+pub async fn with_cancel_scope<F: Future>(scope: CancelScope, fut: F) -> F::Output {
+    CANCEL_SCOPE.scope(scope, fut).await
+}
+
+/// Returns `true` inside a [`with_cancel_scope`] scope.
+#[must_use]
+pub fn cancel_scope_active() -> bool {
+    CANCEL_SCOPE.try_with(|_| ()).is_ok()
+}
+
+/// Registers a statement about to run with the scope of the current task.
+/// Returns `None` outside a scope.
+pub fn register_statement(canceller: Canceller) -> Option<StatementGuard> {
+    CANCEL_SCOPE
+        .try_with(|scope| {
+            let id = scope.next_id.fetch_add(1, Ordering::Relaxed);
+            scope.running.lock().ok()?.insert(id, canceller);
+            Some(StatementGuard {
+                scope: scope.clone(),
+                id,
+            })
+        })
+        .ok()
+        .flatten()
+}
+
+/// Binds `fut` to the cancel scope of the calling task, so statements it
+/// runs on another task are canceled with the request.
+pub fn in_current_cancel_scope<F: Future>(fut: F) -> impl Future<Output = F::Output> {
+    let scope = CANCEL_SCOPE.try_with(Clone::clone).ok();
+    async move {
+        match scope {
+            Some(scope) => CANCEL_SCOPE.scope(scope, fut).await,
+            None => fut.await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+
+    #[tokio::test]
+    async fn test_cancel_unfinished_statements() {
+        let canceled = Arc::new(AtomicUsize::new(0));
+        let canceller = || {
+            let canceled = canceled.clone();
+            Box::new(move || {
+                canceled.fetch_add(1, Ordering::Relaxed);
+            }) as Canceller
+        };
+
+        assert!(!cancel_scope_active());
+        assert!(register_statement(canceller()).is_none());
+
+        let scope = CancelScope::new();
+        with_cancel_scope(scope.clone(), async {
+            assert!(cancel_scope_active());
+            register_statement(canceller()).unwrap().finish();
+            // Dropped without finishing, like a statement whose future was
+            // dropped mid-query
+            drop(register_statement(canceller()));
+            let spawned = canceller();
+            let carried = tokio::spawn(in_current_cancel_scope(async move {
+                register_statement(spawned).is_some()
+            }));
+            assert!(carried.await.unwrap());
+        })
+        .await;
+
+        assert_eq!(scope.running(), 2);
+        assert_eq!(scope.cancel(), 2);
+        assert_eq!(canceled.load(Ordering::Relaxed), 2);
+        assert_eq!(scope.cancel(), 0);
+    }
+}
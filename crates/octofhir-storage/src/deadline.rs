@@ -0,0 +1,82 @@
+//! Per-request deadline.
+//!
+//! When a request times out, the server drops its future, but a database
+//! statement already sent keeps running until it completes. A
+//! [`with_deadline`] scope records when the request gives up; backends read
+//! [`remaining_time`] and bound their statements by it (PostgreSQL:
+//! `statement_timeout`), so the database stops at the same moment the client
+//! is answered.
+//!
+//! Like the query budget, the deadline lives in a task-local, so work spawned
+//! onto another task is not bounded unless it is bound with
+//! [`in_current_deadline`]. Code outside a scope is never bounded.
+
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+tokio::task_local! {
+    static DEADLINE: Instant;
+}
+
+/// Runs `fut` with a deadline at `deadline`.
+pub async fn with_deadline<F: Future>(deadline: Instant, fut: F) -> F::Output {
+    DEADLINE.scope(deadline, fut).await
+}
+
+/// Returns the deadline of the current task, if any.
+#[must_use]
+pub fn current_deadline() -> Option<Instant> {
+    DEADLINE.try_with(|d| *d).ok()
+}
+
+/// Time left until the deadline of the current task: `None` outside a
+/// [`with_deadline`] scope, zero once the deadline has passed.
+#[must_use]
+pub fn remaining_time() -> Option<Duration> {
+    current_deadline().map(|d| d.saturating_duration_since(Instant::now()))
+}
+
+/// Returns `true` if the current task has a deadline and it has passed.
+#[must_use]
+pub fn deadline_passed() -> bool {
+    remaining_time().is_some_and(|d| d.is_zero())
+}
+
+/// Binds `fut` to the deadline of the calling task, so statements it runs on
+/// another task are bounded by the same deadline.
+pub fn in_current_deadline<F: Future>(fut: F) -> impl Future<Output = F::Output> {
+    let deadline = current_deadline();
+    async move {
+        match deadline {
+            Some(deadline) => DEADLINE.scope(deadline, fut).await,
+            None => fut.await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_deadline_scope() {
+        assert!(remaining_time().is_none());
+        assert!(!deadline_passed());
+
+        with_deadline(Instant::now() + Duration::from_secs(60), async {
+            let left = remaining_time().unwrap();
+            assert!(left > Duration::from_secs(59));
+            assert!(!deadline_passed());
+
+            let carried = tokio::spawn(in_current_deadline(async { remaining_time() }));
+            assert!(carried.await.unwrap().is_some());
+        })
+        .await;
+
+        with_deadline(Instant::now(), async {
+            assert_eq!(remaining_time(), Some(Duration::ZERO));
+            assert!(deadline_passed());
+        })
+        .await;
+    }
+}
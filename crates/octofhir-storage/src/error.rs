@@ -55,6 +55,22 @@ pub enum StorageError {
         message: String,
     },
 
+    /// The backend has no capacity for the request right now (for example
+    /// the connection pool is exhausted); the request may be retried.
+    #[error("Storage busy: {message}")]
+    Busy {
+        /// Description of the saturated resource.
+        message: String,
+    },
+
+    /// The request deadline passed before the backend finished; the backend
+    /// has stopped working on it.
+    #[error("Storage timeout: {message}")]
+    Timeout {
+        /// Description of the interrupted work.
+        message: String,
+    },
+
     /// An internal storage error occurred.
     #[error("Internal error: {message}")]
     Internal {
@@ -70,6 +86,15 @@ pub enum StorageError {
         /// The ID of the resource that was deleted.
         id: String,
     },
+
+    /// The database rejected a statement.
+    #[error("Database error ({code}): {message}")]
+    Database {
+        /// The SQLSTATE code reported by the database.
+        code: String,
+        /// Description of the database error.
+        message: String,
+    },
 }
 
 impl StorageError {
@@ -124,6 +149,22 @@ impl StorageError {
         }
     }
 
+    /// Creates a new `Busy` error.
+    #[must_use]
+    pub fn busy(message: impl Into<String>) -> Self {
+        Self::Busy {
+            message: message.into(),
+        }
+    }
+
+    /// Creates a new `Timeout` error.
+    #[must_use]
+    pub fn timeout(message: impl Into<String>) -> Self {
+        Self::Timeout {
+            message: message.into(),
+        }
+    }
+
     /// Creates a new `Internal` error.
     #[must_use]
     pub fn internal(message: impl Into<String>) -> Self {
@@ -141,6 +182,24 @@ impl StorageError {
         }
     }
 
+    /// Creates a new `Database` error from a SQLSTATE code.
+    #[must_use]
+    pub fn database(code: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::Database {
+            code: code.into(),
+            message: message.into(),
+        }
+    }
+
+    /// Returns the SQLSTATE code of a database error.
+    #[must_use]
+    pub fn sqlstate(&self) -> Option<&str> {
+        match self {
+            Self::Database { code, .. } => Some(code),
+            _ => None,
+        }
+    }
+
     /// Returns `true` if this is a not found error.
     #[must_use]
     pub fn is_not_found(&self) -> bool {
@@ -175,12 +234,34 @@ impl StorageError {
             Self::InvalidResource { .. } => ErrorCategory::Validation,
             Self::TransactionError { .. } => ErrorCategory::Transaction,
             Self::ConnectionError { .. } => ErrorCategory::Infrastructure,
+            Self::Busy { .. } | Self::Timeout { .. } => ErrorCategory::Busy,
             Self::Internal { .. } => ErrorCategory::Internal,
             Self::Deleted { .. } => ErrorCategory::Deleted,
+            Self::Database { code, .. } => sqlstate_category(code),
         }
     }
 }
 
+/// Categorizes a SQLSTATE code by its class (first two characters).
+fn sqlstate_category(code: &str) -> ErrorCategory {
+    match code {
+        // unique_violation, exclusion_violation
+        "23505" | "23P01" => ErrorCategory::Conflict,
+        // serialization_failure, deadlock_detected
+        "40001" | "40P01" => ErrorCategory::Transaction,
+        // lock_not_available, query_canceled (statement timeout)
+        "55P03" | "57014" => ErrorCategory::Busy,
+        _ => match code.get(..2) {
+            // integrity constraint violation, data exception
+            Some("23" | "22") => ErrorCategory::Validation,
+            // connection exception, insufficient resources, operator intervention
+            Some("08" | "53" | "57") => ErrorCategory::Infrastructure,
+            Some("40") => ErrorCategory::Transaction,
+            _ => ErrorCategory::Internal,
+        },
+    }
+}
+
 /// Categories of storage errors for logging and monitoring.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum ErrorCategory {
@@ -198,6 +279,8 @@ pub enum ErrorCategory {
     Internal,
     /// Resource has been deleted (soft delete - 410 Gone).
     Deleted,
+    /// The backend is overloaded or timed out; the request may be retried.
+    Busy,
 }
 
 impl fmt::Display for ErrorCategory {
@@ -210,6 +293,7 @@ impl fmt::Display for ErrorCategory {
             Self::Infrastructure => write!(f, "infrastructure"),
             Self::Internal => write!(f, "internal"),
             Self::Deleted => write!(f, "deleted"),
+            Self::Busy => write!(f, "busy"),
         }
     }
 }
@@ -260,5 +344,33 @@ mod tests {
             StorageError::invalid_resource("bad data").category(),
             ErrorCategory::Validation
         );
+        assert_eq!(
+            StorageError::busy("pool exhausted").category(),
+            ErrorCategory::Busy
+        );
+        assert_eq!(
+            StorageError::timeout("search").category(),
+            ErrorCategory::Busy
+        );
+    }
+
+    #[test]
+    fn test_sqlstate_category() {
+        let category = |code| StorageError::database(code, "error").category();
+        assert_eq!(category("23505"), ErrorCategory::Conflict);
+        assert_eq!(category("23503"), ErrorCategory::Validation);
+        assert_eq!(category("23502"), ErrorCategory::Validation);
+        assert_eq!(category("22P02"), ErrorCategory::Validation);
+        assert_eq!(category("40001"), ErrorCategory::Transaction);
+        assert_eq!(category("40P01"), ErrorCategory::Transaction);
+        assert_eq!(category("57014"), ErrorCategory::Busy);
+        assert_eq!(category("55P03"), ErrorCategory::Busy);
+        assert_eq!(category("08006"), ErrorCategory::Infrastructure);
+        assert_eq!(category("53300"), ErrorCategory::Infrastructure);
+        assert_eq!(category("42P01"), ErrorCategory::Internal);
+        assert_eq!(
+            StorageError::database("23505", "duplicate").sqlstate(),
+            Some("23505")
+        );
     }
 }
@@ -24,6 +24,7 @@ use serde_json::Value;
 use tracing::trace;
 
 use crate::error::StorageError;
+use crate::tenant::current_tenant;
 use crate::traits::{FhirStorage, Transaction};
 use crate::types::{
     HistoryParams, HistoryResult, RawHistoryResult, RawStoredResource, SearchParams, SearchResult,
@@ -37,6 +38,10 @@ use crate::types::{
 ///
 /// Events are emitted **after** the operation succeeds, ensuring that events
 /// only correspond to actual changes in the database.
+///
+/// Writes made inside a tenant scope (see [`crate::tenant`]) emit no events:
+/// subscribers are not tenant-aware and would otherwise see other tenants'
+/// resources.
 pub struct EventedStorage<S: FhirStorage> {
     /// The inner storage implementation.
     inner: S,
@@ -61,7 +66,7 @@ impl<S: FhirStorage> EventedStorage<S> {
     }
 
     fn emit_created(&self, resource_type: &str, resource_id: &str, resource: &Value) {
-        if self.broadcaster.subscriber_count() == 0 {
+        if self.broadcaster.subscriber_count() == 0 || current_tenant().is_some() {
             return;
         }
         let count = self
@@ -76,7 +81,7 @@ impl<S: FhirStorage> EventedStorage<S> {
     }
 
     fn emit_updated(&self, resource_type: &str, resource_id: &str, resource: &Value) {
-        if self.broadcaster.subscriber_count() == 0 {
+        if self.broadcaster.subscriber_count() == 0 || current_tenant().is_some() {
             return;
         }
         let count = self
@@ -91,7 +96,7 @@ impl<S: FhirStorage> EventedStorage<S> {
     }
 
     fn emit_deleted(&self, resource_type: &str, resource_id: &str) {
-        if self.broadcaster.subscriber_count() == 0 {
+        if self.broadcaster.subscriber_count() == 0 || current_tenant().is_some() {
             return;
         }
         let count = self.broadcaster.send_deleted(resource_type, resource_id);
@@ -326,6 +331,9 @@ impl EventedTransaction {
 
     /// Queue an event to be emitted on commit.
     fn queue_event(&mut self, event: ResourceEvent) {
+        if current_tenant().is_some() {
+            return;
+        }
         self.pending_events.push(event);
     }
 }
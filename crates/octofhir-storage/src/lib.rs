@@ -13,6 +13,9 @@
 //! - Search
 //! - Transactions
 //!
+//! Multi-tenant deployments scope storage calls with the task-local tenant
+//! context in [`tenant`].
+//!
 //! ## Example
 //!
 //! ```ignore
@@ -52,14 +55,27 @@
 //! }
 //! ```
 
+pub mod allowlist;
+pub mod budget;
+pub mod cancel;
+pub mod deadline;
 mod error;
 pub mod evented;
+pub mod tenant;
 mod traits;
 mod types;
 
 // Re-export everything from submodules
+pub use allowlist::{is_resource_type_allowed, set_resource_type_allowlist};
+pub use budget::{QueryBudget, charge_queries, charge_query, with_query_budget};
+pub use cancel::{
+    CancelScope, Canceller, StatementGuard, cancel_scope_active, register_statement,
+    with_cancel_scope,
+};
+pub use deadline::{deadline_passed, remaining_time, with_deadline};
 pub use error::{ErrorCategory, StorageError};
 pub use evented::{EventedStorage, EventedTransaction};
+pub use tenant::{TenantId, current_tenant, with_tenant};
 pub use traits::{
     ConformanceChangeEvent, ConformanceChangeOp, ConformanceStorage, FhirStorage,
     StorageCapabilities, Transaction,
@@ -67,7 +83,7 @@ pub use traits::{
 pub use types::{
     HistoryEntry, HistoryMethod, HistoryParams, HistoryResult, RawHistoryEntry, RawHistoryResult,
     RawSearchDebug, RawSearchResult, RawStoredResource, SearchParams, SearchResult, SortParam,
-    StoredResource, TotalMode,
+    StoredResource, SyncCursor, SyncDeletion, SyncPosition, TotalMode,
 };
 
 /// Type alias for a storage result.
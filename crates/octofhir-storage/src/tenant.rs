@@ -0,0 +1,134 @@
+//! Tenant context for multi-tenant deployments.
+//!
+//! The tenant of the current request is carried in a task-local, so every
+//! storage call made while serving the request (through [`FhirStorage`] or
+//! directly against a backend's pool) sees the same tenant without threading
+//! it through each signature. Backends map the tenant to their isolation unit;
+//! the PostgreSQL backend uses one schema per tenant.
+//!
+//! Code outside a [`with_tenant`] scope runs against the default (shared)
+//! data. Work spawned onto another task does not inherit the scope; wrap it
+//! with [`in_current_tenant`] to carry the tenant along.
+//!
+//! [`FhirStorage`]: crate::FhirStorage
+
+use std::fmt;
+use std::future::Future;
+
+use crate::error::StorageError;
+
+/// Maximum tenant ID length. Keeps the derived schema name well inside
+/// PostgreSQL's 63-byte identifier limit.
+pub const MAX_TENANT_ID_LEN: usize = 48;
+
+tokio::task_local! {
+    static CURRENT_TENANT: TenantId;
+}
+
+/// A validated tenant identifier.
+///
+/// Tenant IDs are 1-48 characters of lowercase ASCII letters, digits and `_`,
+/// so they can be embedded in identifiers without quoting concerns.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TenantId(String);
+
+impl TenantId {
+    /// Validates and wraps a tenant ID.
+    ///
+    /// # Errors
+    ///
+    /// Returns `StorageError::InvalidResource` if the ID is empty, too long,
+    /// or contains characters other than `[a-z0-9_]`.
+    pub fn new(id: impl Into<String>) -> Result<Self, StorageError> {
+        let id = id.into();
+        if id.is_empty() || id.len() > MAX_TENANT_ID_LEN {
+            return Err(StorageError::invalid_resource(format!(
+                "Tenant ID must be 1-{MAX_TENANT_ID_LEN} characters"
+            )));
+        }
+        if !id
+            .bytes()
+            .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'_')
+        {
+            return Err(StorageError::invalid_resource(format!(
+                "Invalid tenant ID '{id}': only lowercase letters, digits and '_' are allowed"
+            )));
+        }
+        Ok(Self(id))
+    }
+
+    /// Returns the tenant ID.
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Name of the database schema holding this tenant's data.
+    #[must_use]
+    pub fn schema_name(&self) -> String {
+        format!("tenant_{}", self.0)
+    }
+}
+
+impl fmt::Display for TenantId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Runs `fut` with `tenant` as the current tenant.
+pub async fn with_tenant<F: Future>(tenant: TenantId, fut: F) -> F::Output {
+    CURRENT_TENANT.scope(tenant, fut).await
+}
+
+/// Returns the tenant of the current task, if any.
+#[must_use]
+pub fn current_tenant() -> Option<TenantId> {
+    CURRENT_TENANT.try_with(Clone::clone).ok()
+}
+
+/// Binds `fut` to the tenant of the calling task, for handing work to
+/// `tokio::spawn` without dropping the tenant scope.
+///
+/// The tenant is captured when this is called, not when the future is polled.
+pub fn in_current_tenant<F: Future>(fut: F) -> impl Future<Output = F::Output> {
+    let tenant = current_tenant();
+    async move {
+        match tenant {
+            Some(tenant) => with_tenant(tenant, fut).await,
+            None => fut.await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tenant_id_validation() {
+        assert_eq!(TenantId::new("acme_01").unwrap().as_str(), "acme_01");
+        assert_eq!(TenantId::new("acme").unwrap().schema_name(), "tenant_acme");
+        assert!(TenantId::new("").is_err());
+        assert!(TenantId::new("Acme").is_err());
+        assert!(TenantId::new("acme-corp").is_err());
+        assert!(TenantId::new("a\"; DROP SCHEMA public; --").is_err());
+        assert!(TenantId::new("a".repeat(MAX_TENANT_ID_LEN + 1)).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_tenant_scope() {
+        assert!(current_tenant().is_none());
+        let acme = TenantId::new("acme").unwrap();
+        with_tenant(acme.clone(), async {
+            assert_eq!(current_tenant(), Some(acme.clone()));
+            // Spawned work only sees the tenant when explicitly carried along
+            let carried = tokio::spawn(in_current_tenant(async { current_tenant() }));
+            let dropped = tokio::spawn(async { current_tenant() });
+            assert_eq!(carried.await.unwrap(), Some(acme.clone()));
+            assert_eq!(dropped.await.unwrap(), None);
+        })
+        .await;
+        assert!(current_tenant().is_none());
+    }
+}
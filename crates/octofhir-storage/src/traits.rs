@@ -201,6 +201,10 @@ pub trait FhirStorage: Send + Sync {
     /// string directly from the database, skipping the JSONB → Value
     /// deserialization step.
     ///
+    /// Fails exactly like `update()`; in particular `StorageError::NotFound`
+    /// tells callers the resource does not exist, so a FHIR PUT can fall back
+    /// to creating it (and answer 201 rather than 200).
+    ///
     /// Default implementation delegates to `update()` and serializes the Value.
     async fn update_raw(
         &self,
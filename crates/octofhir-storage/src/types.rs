@@ -7,6 +7,8 @@ use serde_json::Value;
 use std::collections::HashMap;
 use time::OffsetDateTime;
 
+pub use octofhir_core::SyncCursor;
+
 /// A FHIR resource as stored in the storage backend.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StoredResource {
@@ -144,18 +146,35 @@ impl SearchResult {
 pub struct RawSearchResult {
     /// The matching resources with raw JSON.
     pub entries: Vec<RawStoredResource>,
+    /// Relevance score of each entry, in the same order, when the search
+    /// ranks its matches (`_text` / `_content`); empty otherwise.
+    pub scores: Vec<f64>,
     /// Included resources from _include/_revinclude (different resource types).
     pub included: Vec<RawStoredResource>,
     /// Total count of matching resources, if requested and available.
     pub total: Option<u32>,
     /// Whether there are more results available beyond this page.
     pub has_more: bool,
+    /// Resources deleted within the page of an incremental sync (`_sync`),
+    /// in page order. Always empty for other searches.
+    pub deleted: Vec<SyncDeletion>,
     /// Warning messages to include in the response (e.g., unknown parameters ignored).
     pub warnings: Vec<String>,
     /// Optional safe search debug payload, populated only by explicit debug-gated callers.
     pub debug: Option<RawSearchDebug>,
 }
 
+/// A resource deleted within the page of an incremental sync (`_sync`).
+#[derive(Debug, Clone)]
+pub struct SyncDeletion {
+    /// The resource ID.
+    pub id: String,
+    /// The version ID of the delete.
+    pub version_id: String,
+    /// When the resource was deleted.
+    pub deleted_at: OffsetDateTime,
+}
+
 /// Safe search debug payload for internal/debug responses.
 #[derive(Debug, Clone, Default)]
 pub struct RawSearchDebug {
@@ -373,6 +392,14 @@ pub struct SearchParams {
     /// How to calculate the total count.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub total: Option<TotalMode>,
+    /// Incremental sync: results ordered by `(_lastUpdated, _id)` instead of
+    /// `sort`, starting after this position.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sync: Option<SyncPosition>,
+    /// Keyset paging by id: results ordered by `_id` instead of `sort`,
+    /// starting after this id. Replaces `offset`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub after_id: Option<String>,
 }
 
 impl SearchParams {
@@ -422,6 +449,20 @@ impl SearchParams {
         self
     }
 
+    /// Sets the incremental sync position.
+    #[must_use]
+    pub fn with_sync(mut self, position: SyncPosition) -> Self {
+        self.sync = Some(position);
+        self
+    }
+
+    /// Pages by id, starting after `id`.
+    #[must_use]
+    pub fn with_after_id(mut self, id: impl Into<String>) -> Self {
+        self.after_id = Some(id.into());
+        self
+    }
+
     /// Returns true if this search has no parameters.
     #[must_use]
     pub fn is_empty(&self) -> bool {
@@ -429,6 +470,16 @@ impl SearchParams {
     }
 }
 
+/// Where an incremental sync page starts.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SyncPosition {
+    /// The first page.
+    Start,
+    /// After the last resource of the previous page.
+    After(SyncCursor),
+}
+
 /// A sort parameter for search results.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SortParam {
@@ -64,6 +64,7 @@ export default defineConfig({
           items: [
             { label: 'Transactions', link: 'transactions/' },
             { label: 'Bulk Data Export', link: 'bulk-export/' },
+            { label: 'Bulk Data Import', link: 'bulk-import/' },
             { label: 'Terminology', link: 'terminology/' },
             { label: 'Notifications', link: 'notifications/' },
           ]